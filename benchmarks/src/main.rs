@@ -224,7 +224,7 @@ impl BenchmarkRunner {
         }
 
         let (target_conn_str, handle, shutdown_tx) = match self.deployment_params.database_type {
-            DatabaseType::MySQL => {
+            DatabaseType::MySQL | DatabaseType::MariaDB => {
                 if matches!(&upstream_addr, Some(addr) if !addr.starts_with("mysql")) {
                     bail!("--database-type specifies MySQL but the upstream address does not");
                 }
@@ -272,6 +272,9 @@ impl WorkloadSpec {
                     unparsed_create_cache_statement: None,
                     always: false,
                     concurrently: false,
+                    expiration: None,
+                    memory_limit: None,
+                    priority: None,
                 };
 
                 let _ = conn
@@ -214,6 +214,9 @@ impl ArbitraryQueryParameters {
             inner: Ok(nom_sql::CacheInner::Statement(Box::new(stmt))),
             always: false,
             concurrently: false,
+            expiration: None,
+            memory_limit: None,
+            priority: None,
             unparsed_create_cache_statement: None,
         };
 
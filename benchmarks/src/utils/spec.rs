@@ -89,7 +89,7 @@ impl From<Dialect> for SchemaKind {
 impl From<DatabaseType> for SchemaKind {
     fn from(dialect: DatabaseType) -> Self {
         match dialect {
-            DatabaseType::MySQL => Self::MySQL {
+            DatabaseType::MySQL | DatabaseType::MariaDB => Self::MySQL {
                 user_vars: HashMap::new(),
             },
             DatabaseType::PostgreSQL => Self::PostgreSQL,
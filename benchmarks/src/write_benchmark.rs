@@ -156,6 +156,9 @@ async fn create_indices(
                 inner: Ok(CacheInner::Statement(Box::new(query))),
                 always: false,
                 concurrently: false,
+                expiration: None,
+                memory_limit: None,
+                priority: None,
                 unparsed_create_cache_statement: None,
             };
             conn.query_drop(create_cache.display(conn.dialect()).to_string())
@@ -78,7 +78,7 @@ pub enum DatabaseURLParseError {
 
 /// Error type for the [`FromStr`] implementation for [`DatabaseType`]
 #[derive(Debug, Error)]
-#[error("Invalid upstream type `{value}`, expected one of `mysql` or `postgres`")]
+#[error("Invalid upstream type `{value}`, expected one of `mysql`, `mariadb` or `postgres`")]
 pub struct DatabaseTypeParseError {
     /// The value that was originally being parsed
     pub value: String,
@@ -10,6 +10,7 @@ use clap::{Parser, ValueEnum};
 use connection::DatabaseConnectionPoolBuilder;
 use derive_more::From;
 use error::DatabaseTypeParseError;
+use mysql_async::prelude::Queryable;
 use mysql_async::OptsBuilder;
 use native_tls::TlsConnectorBuilder;
 use nom_sql::Dialect;
@@ -63,6 +64,16 @@ pub struct UpstreamConfig {
     #[serde(default)]
     pub disable_setup_ddl_replication: bool,
 
+    /// URL for the primary server of the upstream database, if `--upstream-db-url` points at a
+    /// PostgreSQL standby (postgres only, ignored for mysql).
+    ///
+    /// Creating a replication slot on a standby waits for the primary to log a snapshot of the
+    /// transactions running on it, which an idle primary may not do on its own. If this is set,
+    /// ReadySet asks the primary to log one (using `pg_log_standby_snapshot()`) while it waits.
+    #[arg(long, env = "STANDBY_PRIMARY_DB_URL")]
+    #[serde(default)]
+    pub standby_primary_db_url: Option<RedactedString>,
+
     /// Server ID to use when registering as a replication follower with the upstream db
     ///
     /// This can be used to differentiate different ReadySet deployments connected to the same
@@ -187,6 +198,7 @@ impl Default for UpstreamConfig {
             upstream_db_url: Default::default(),
             disable_upstream_ssl_verification: false,
             disable_setup_ddl_replication: false,
+            standby_primary_db_url: None,
             replication_server_id: Default::default(),
             replicator_restart_timeout: Duration::from_secs(1),
             replication_tables: Default::default(),
@@ -205,12 +217,17 @@ pub enum DatabaseType {
     #[value(name = "mysql")]
     MySQL,
 
+    /// MariaDB, which speaks the MySQL protocol (and uses `mysql://` URLs) but differs from MySQL
+    /// in how it replicates
+    #[value(name = "mariadb")]
+    MariaDB,
+
     #[value(name = "postgresql")]
     #[default]
     PostgreSQL,
 }
 
-/// Parses the strings `"mysql"` and `"postgresql"`, case-insensitively
+/// Parses the strings `"mysql"`, `"mariadb"` and `"postgresql"`, case-insensitively
 ///
 /// # Examples
 ///
@@ -227,6 +244,9 @@ pub enum DatabaseType {
 /// let my: DatabaseType = "mysql".parse().unwrap();
 /// assert_eq!(my, DatabaseType::MySQL);
 ///
+/// let maria: DatabaseType = "mariadb".parse().unwrap();
+/// assert_eq!(maria, DatabaseType::MariaDB);
+///
 /// assert!("pgsql".parse::<DatabaseType>().is_err());
 /// ```
 impl FromStr for DatabaseType {
@@ -234,6 +254,7 @@ impl FromStr for DatabaseType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "mysql" => Ok(Self::MySQL),
+            "mariadb" => Ok(Self::MariaDB),
             "postgresql" | "postgres" => Ok(Self::PostgreSQL),
             _ => Err(DatabaseTypeParseError {
                 value: s.to_owned(),
@@ -242,7 +263,7 @@ impl FromStr for DatabaseType {
     }
 }
 
-/// Displays as either `MySQL` or PostgreSQL
+/// Displays as either `MySQL`, `MariaDB` or PostgreSQL
 ///
 /// # Examples
 ///
@@ -255,11 +276,48 @@ impl Display for DatabaseType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DatabaseType::MySQL => f.write_str("mysql"),
+            DatabaseType::MariaDB => f.write_str("mariadb"),
             DatabaseType::PostgreSQL => f.write_str("postgresql"),
         }
     }
 }
 
+impl DatabaseType {
+    /// Determine whether a database that speaks the MySQL protocol (and so is addressed by a
+    /// `mysql://` URL) is MySQL or MariaDB, from its version string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use database_utils::DatabaseType;
+    ///
+    /// assert_eq!(
+    ///     DatabaseType::from_mysql_version("8.0.32"),
+    ///     DatabaseType::MySQL
+    /// );
+    /// assert_eq!(
+    ///     DatabaseType::from_mysql_version("10.11.2-MariaDB-1:10.11.2+maria~ubu2204-log"),
+    ///     DatabaseType::MariaDB
+    /// );
+    /// ```
+    pub fn from_mysql_version(version: &str) -> Self {
+        if version.contains("MariaDB") {
+            Self::MariaDB
+        } else {
+            Self::MySQL
+        }
+    }
+
+    /// Query the version of the database connected to by `conn` to determine whether it's MySQL
+    /// or MariaDB (see [`Self::from_mysql_version`])
+    pub async fn detect_mysql(conn: &mut mysql::Conn) -> mysql::Result<Self> {
+        let version: Option<String> = conn.query_first("SELECT VERSION()").await?;
+        Ok(version
+            .map(|version| Self::from_mysql_version(&version))
+            .unwrap_or(Self::MySQL))
+    }
+}
+
 /// URL for an upstream database.
 ///
 /// [`DatabaseURL`]s can be constructed directly via the [`From`] implementations, or parsed from a
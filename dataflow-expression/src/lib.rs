@@ -5,6 +5,7 @@ mod eval;
 pub mod like;
 mod lower;
 mod post_lookup;
+pub mod sketch;
 pub mod utils;
 
 use std::fmt::{self, Display, Formatter};
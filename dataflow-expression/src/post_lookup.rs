@@ -1,18 +1,27 @@
 use std::cmp::{self, Ordering};
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::sync::Arc;
 
-use nom_sql::OrderType;
+use nom_sql::{NullOrder, OrderType};
 use partial_map::InsertionOrder;
 use readyset_data::DfValue;
-use readyset_errors::{internal, ReadySetResult};
+use readyset_errors::{internal, internal_err, ReadySetResult};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::sketch::{HyperLogLog, QuantileSketch};
+use crate::Expr;
+
 /// Representation of an aggregate function
+///
+/// Some aggregates can't be re-aggregated from their own results, and are instead recomputed from
+/// the results of other aggregates, which are projected as hidden columns in the reader. The
+/// indices of those columns are stored in the aggregate function itself.
 // TODO(aspen): It would be really nice to deduplicate this somehow with the grouped operator itself
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
-pub enum PostLookupAggregateFunction {
+pub enum PostLookupAggregateFunction<Column = usize> {
     /// Add together all the input numbers
     ///
     /// Note that this encapsulates both `SUM` *and* `COUNT` in base SQL, as re-aggregating counts
@@ -26,6 +35,111 @@ pub enum PostLookupAggregateFunction {
     Max,
     /// Take the minimum input value
     Min,
+    /// Take the bitwise AND of all the input numbers
+    BitAnd,
+    /// Take the bitwise OR of all the input numbers
+    BitOr,
+    /// Concatenate together all the input arrays
+    ArrayConcat,
+    /// Divide the (re-aggregated) sum in the `sum` column by the (re-aggregated) count in the
+    /// `count` column, for `AVG`
+    Avg { sum: Column, count: Column },
+    /// Count the distinct non-null values in the (re-aggregated) array in the `values` column, for
+    /// `COUNT(DISTINCT)`
+    CountDistinct { values: Column },
+    /// Add together the distinct non-null values in the (re-aggregated) array in the `values`
+    /// column, for `SUM(DISTINCT)`
+    SumDistinct { values: Column },
+    /// Average the distinct non-null values in the (re-aggregated) array in the `values` column,
+    /// for `AVG(DISTINCT)`
+    AvgDistinct { values: Column },
+    /// Merge together the input HyperLogLog sketches, for `APPROX_COUNT_DISTINCT`
+    ApproxCountDistinct,
+    /// Merge together the input quantile sketches, for `APPROX_PERCENTILE` of the given percentile
+    /// (a fraction between 0 and 1, as a [`DfValue::Double`])
+    ApproxPercentile { percentile: DfValue },
+}
+
+impl<Column> PostLookupAggregateFunction<Column> {
+    /// Transform all column references in self by applying a function
+    pub fn map_columns<F, C2, E>(self, mut f: F) -> Result<PostLookupAggregateFunction<C2>, E>
+    where
+        F: FnMut(Column) -> Result<C2, E>,
+    {
+        use PostLookupAggregateFunction::*;
+
+        Ok(match self {
+            Sum => Sum,
+            Product => Product,
+            GroupConcat { separator } => GroupConcat { separator },
+            Max => Max,
+            Min => Min,
+            BitAnd => BitAnd,
+            BitOr => BitOr,
+            ArrayConcat => ArrayConcat,
+            Avg { sum, count } => Avg {
+                sum: f(sum)?,
+                count: f(count)?,
+            },
+            CountDistinct { values } => CountDistinct { values: f(values)? },
+            SumDistinct { values } => SumDistinct { values: f(values)? },
+            AvgDistinct { values } => AvgDistinct { values: f(values)? },
+            ApproxCountDistinct => ApproxCountDistinct,
+            ApproxPercentile { percentile } => ApproxPercentile { percentile },
+        })
+    }
+
+    /// Returns the hidden columns that this aggregate function is recomputed from, if any
+    pub fn columns(&self) -> Vec<&Column> {
+        use PostLookupAggregateFunction::*;
+
+        match self {
+            Avg { sum, count } => vec![sum, count],
+            CountDistinct { values } | SumDistinct { values } | AvgDistinct { values } => {
+                vec![values]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Returns the distinct non-null values in the given array of values
+fn distinct_values(values: &DfValue) -> ReadySetResult<HashSet<&DfValue>> {
+    Ok(match values {
+        DfValue::None => HashSet::new(),
+        DfValue::Array(values) => values.values().filter(|v| !v.is_none()).collect(),
+        _ => internal!("Expected an array of values for a distinct aggregate"),
+    })
+}
+
+/// Returns the sum of the given values, or NULL if there are none
+fn sum<'a>(values: impl IntoIterator<Item = &'a DfValue>) -> ReadySetResult<DfValue> {
+    values.into_iter().try_fold(DfValue::None, |acc, v| {
+        if acc.is_none() {
+            Ok(v.clone())
+        } else {
+            &acc + v
+        }
+    })
+}
+
+/// Returns the given sum divided by the given count, or NULL if the count is zero.
+///
+/// As with the `Avg` aggregation in the dataflow graph, this is a DECIMAL value for exact-value
+/// sums, and a DOUBLE value for approximate-value sums.
+fn avg(sum: &DfValue, count: &DfValue) -> ReadySetResult<DfValue> {
+    if sum.is_none() || Decimal::try_from(count)?.is_zero() {
+        return Ok(DfValue::None);
+    }
+    match sum {
+        DfValue::Float(_) | DfValue::Double(_) => {
+            Ok(DfValue::Double(f64::try_from(sum)? / f64::try_from(count)?))
+        }
+        _ => Decimal::try_from(sum)?
+            .checked_div(Decimal::try_from(count)?)
+            .map(DfValue::from)
+            .ok_or_else(|| internal_err!("Overflow computing average")),
+    }
 }
 
 impl PostLookupAggregateFunction {
@@ -45,6 +159,98 @@ impl PostLookupAggregateFunction {
             .into()),
             PostLookupAggregateFunction::Max => Ok(cmp::max(val1, val2).clone()),
             PostLookupAggregateFunction::Min => Ok(cmp::min(val1, val2).clone()),
+            PostLookupAggregateFunction::BitAnd | PostLookupAggregateFunction::BitOr => {
+                let and = matches!(self, PostLookupAggregateFunction::BitAnd);
+                match (val1, val2) {
+                    (DfValue::None, v) | (v, DfValue::None) => Ok(v.clone()),
+                    // Groups without any non-null values have the identity of the operation, so
+                    // they don't affect the result
+                    (DfValue::UnsignedInt(a), DfValue::UnsignedInt(b)) => {
+                        Ok(DfValue::UnsignedInt(if and { a & b } else { a | b }))
+                    }
+                    _ => internal!("BIT_AND and BIT_OR are computed over unsigned integers"),
+                }
+            }
+            PostLookupAggregateFunction::ArrayConcat => match (val1, val2) {
+                (DfValue::None, v) | (v, DfValue::None) => Ok(v.clone()),
+                (DfValue::Array(a1), DfValue::Array(a2)) => Ok(a1
+                    .values()
+                    .chain(a2.values())
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .into()),
+                _ => internal!("Can only concatenate arrays"),
+            },
+            // These are recomputed from their hidden columns in `finalize`, so there's nothing to
+            // do until then
+            PostLookupAggregateFunction::Avg { .. }
+            | PostLookupAggregateFunction::CountDistinct { .. }
+            | PostLookupAggregateFunction::SumDistinct { .. }
+            | PostLookupAggregateFunction::AvgDistinct { .. } => Ok(val1.clone()),
+            PostLookupAggregateFunction::ApproxCountDistinct => {
+                let mut sketch = HyperLogLog::try_from(val1)?;
+                sketch.merge(&HyperLogLog::try_from(val2)?);
+                Ok((&sketch).into())
+            }
+            PostLookupAggregateFunction::ApproxPercentile { .. } => {
+                let mut sketch = QuantileSketch::try_from(val1)?;
+                sketch.merge(&QuantileSketch::try_from(val2)?);
+                Ok((&sketch).into())
+            }
+        }
+    }
+
+    /// Returns true if the values this aggregate function is applied to need to be passed through
+    /// [`Self::finalize`] to get the result of the aggregate
+    pub fn needs_finalize(&self) -> bool {
+        matches!(
+            self,
+            PostLookupAggregateFunction::Avg { .. }
+                | PostLookupAggregateFunction::CountDistinct { .. }
+                | PostLookupAggregateFunction::SumDistinct { .. }
+                | PostLookupAggregateFunction::AvgDistinct { .. }
+                | PostLookupAggregateFunction::ApproxCountDistinct
+                | PostLookupAggregateFunction::ApproxPercentile { .. }
+        )
+    }
+
+    /// Convert the result of merging values with [`Self::apply`] into the final result of the
+    /// aggregate, given the rest of the (merged) row it's in.
+    ///
+    /// This is the identity for everything but the aggregates which are recomputed from hidden
+    /// columns in `row`, and the approximate aggregates, whose values are sketches until they've
+    /// all been merged.
+    pub fn finalize(&self, val: DfValue, row: &[DfValue]) -> ReadySetResult<DfValue> {
+        let column = |idx: usize| {
+            row.get(idx)
+                .ok_or_else(|| internal_err!("Hidden aggregate column {idx} out of bounds"))
+        };
+
+        match self {
+            PostLookupAggregateFunction::Avg { sum, count } => avg(column(*sum)?, column(*count)?),
+            PostLookupAggregateFunction::CountDistinct { values } => Ok(DfValue::from(
+                distinct_values(column(*values)?)?.len() as i64,
+            )),
+            PostLookupAggregateFunction::SumDistinct { values } => {
+                sum(distinct_values(column(*values)?)?)
+            }
+            PostLookupAggregateFunction::AvgDistinct { values } => {
+                let values = distinct_values(column(*values)?)?;
+                avg(
+                    &sum(values.iter().copied())?,
+                    &DfValue::from(values.len() as i64),
+                )
+            }
+            PostLookupAggregateFunction::ApproxCountDistinct => {
+                Ok(DfValue::from(HyperLogLog::try_from(&val)?.estimate() as i64))
+            }
+            PostLookupAggregateFunction::ApproxPercentile { percentile } => {
+                let percentile = f64::try_from(percentile)?;
+                Ok(QuantileSketch::try_from(&val)?
+                    .quantile(percentile)
+                    .map_or(DfValue::None, DfValue::Double))
+            }
+            _ => Ok(val),
         }
     }
 }
@@ -55,7 +261,7 @@ pub struct PostLookupAggregate<Column = usize> {
     /// The column index in the result set containing the already-aggregated values
     pub column: Column,
     /// The aggregate function to perform
-    pub function: PostLookupAggregateFunction,
+    pub function: PostLookupAggregateFunction<Column>,
 }
 
 impl<Column> PostLookupAggregate<Column> {
@@ -66,7 +272,7 @@ impl<Column> PostLookupAggregate<Column> {
     {
         Ok(PostLookupAggregate {
             column: f(self.column)?,
-            function: self.function,
+            function: self.function.map_columns(f)?,
         })
     }
 }
@@ -115,11 +321,13 @@ pub struct ReaderProcessing {
 impl ReaderProcessing {
     /// Constructs a new [`PostLookup`]
     pub fn new(
-        order_by: Option<Vec<(usize, OrderType)>>,
+        order_by: Option<Vec<(usize, OrderType, NullOrder)>>,
         limit: Option<usize>,
         returned_cols: Option<Vec<usize>>,
         default_row: Option<Vec<DfValue>>,
         aggregates: Option<PostLookupAggregates>,
+        projection: Option<Vec<Expr>>,
+        filter: Option<Expr>,
     ) -> ReadySetResult<Self> {
         if let Some(cols) = &returned_cols {
             if cols.iter().enumerate().any(|(i, v)| i != *v) {
@@ -127,12 +335,18 @@ impl ReaderProcessing {
             }
         }
 
+        if aggregates.is_none() && (projection.is_some() || filter.is_some()) {
+            internal!("Post-lookup projections and filters require post-lookup aggregates");
+        }
+
         let post_processing = PostLookup {
             order_by,
             limit,
             returned_cols,
             default_row: default_row.map(|r| Arc::new(r.into_boxed_slice())),
             aggregates,
+            projection,
+            filter,
         };
 
         let pre_processing = PreInsertion {
@@ -163,10 +377,11 @@ impl ReaderProcessing {
 /// the desugared query rather than the original query.
 #[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
 pub struct PostLookup {
-    /// Column indices to order by, and whether or not to reverse order on each index.
+    /// Column indices to order by, whether or not to reverse order on each index, and where to
+    /// place nulls within that order.
     ///
     /// If an empty `Vec` is specified, rows are sorted in lexicographic order.
-    pub order_by: Option<Vec<(usize, OrderType)>>,
+    pub order_by: Option<Vec<(usize, OrderType, NullOrder)>>,
     /// Maximum number of records to return
     pub limit: Option<usize>,
     /// Indices of the columns requested in the query. Reader will filter out all other projected
@@ -179,15 +394,25 @@ pub struct PostLookup {
     /// Note that currently these are only performed on each key individually, not the overall
     /// result set returned by all keys in a multi-key lookup
     pub aggregates: Option<PostLookupAggregates>,
+    /// Expressions to evaluate to get each column of the result set, after performing
+    /// [`Self::aggregates`].
+    ///
+    /// This is used to recompute the values of expressions that depend on the results of
+    /// aggregates, since those results aren't known until the aggregates have been performed
+    pub projection: Option<Vec<Expr>>,
+    /// Residual filter to apply to the result set, after performing [`Self::aggregates`] and
+    /// [`Self::projection`] (eg for predicates in a `HAVING` clause)
+    pub filter: Option<Expr>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
 /// Operations to perform on a row before it is stored in the map in a reader.
 pub struct PreInsertion {
-    /// Column indices to order by, and whether or not to reverse order on each index.
+    /// Column indices to order by, whether or not to reverse order on each index, and where to
+    /// place nulls within that order.
     ///
     /// If an empty `Vec` is specified, rows are sorted in lexicographic order.
-    order_by: Option<Vec<(usize, OrderType)>>,
+    order_by: Option<Vec<(usize, OrderType, NullOrder)>>,
     /// The set of column indices to group the aggregate by, `group_by` takes precedence over
     /// `order_by` when determining row order, so that aggregates are processed one by one.
     group_by: Option<Vec<usize>>,
@@ -214,7 +439,13 @@ impl InsertionOrder<Box<[DfValue]>> for PreInsertion {
             values.binary_search_by(|cur_row| {
                 indices
                     .iter()
-                    .map(|&(idx, order_type)| order_type.apply(cur_row[idx].cmp(&elem[idx])))
+                    .map(|&(idx, order_type, null_order)| {
+                        null_order.apply(
+                            cur_row[idx].is_none(),
+                            elem[idx].is_none(),
+                            order_type.apply(cur_row[idx].cmp(&elem[idx])),
+                        )
+                    })
                     .try_fold(Ordering::Equal, |acc, next| match acc {
                         Ordering::Equal => Ok(next),
                         ord => Err(ord),
@@ -0,0 +1,514 @@
+//! Mergeable sketches backing ReadySet's approximate aggregate functions.
+//!
+//! Sketches trade exactness for a bounded amount of memory per group. Both dataflow state and
+//! reader nodes store them serialized as [`DfValue::ByteArray`]s, so that the sketches for multiple
+//! groups can be merged together after a multi-key lookup before being turned into the final
+//! estimate for the aggregate.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use readyset_data::DfValue;
+use readyset_errors::{internal, internal_err, ReadySetResult};
+
+/// Number of bits of each value's hash used to pick a [`HyperLogLog`] register
+const HLL_PRECISION: u32 = 12;
+
+/// Number of registers in a [`HyperLogLog`], which gives a standard error of about 1.6%
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+/// Number of explicitly stored registers after which a [`HyperLogLog`] switches to storing all of
+/// its registers, since at that point the sparse representation takes up more space
+const HLL_SPARSE_MAX: usize = HLL_REGISTERS / 3;
+
+const HLL_SPARSE_TAG: u8 = 0;
+const HLL_DENSE_TAG: u8 = 1;
+
+/// The registers of a [`HyperLogLog`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Registers {
+    /// Only the non-zero registers, keyed by index
+    Sparse(BTreeMap<u16, u8>),
+    /// All [`HLL_REGISTERS`] registers
+    Dense(Vec<u8>),
+}
+
+/// A [HyperLogLog][] sketch, which estimates the number of distinct values it has seen.
+///
+/// HyperLogLog sketches can't forget values - instead, [`HyperLogLog::unaffected_by_removal`] can
+/// be used to check whether the sketch for a multiset would be any different if a value were
+/// removed from it, and if so the sketch has to be rebuilt from scratch.
+///
+/// [HyperLogLog]: https://en.wikipedia.org/wiki/HyperLogLog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    registers: Registers,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: Registers::Sparse(BTreeMap::new()),
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Returns the index of the register for the given value, and the rank of that value within
+    /// the register
+    fn position(value: &DfValue) -> (u16, u8) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - HLL_PRECISION)) as u16;
+        // Setting the bit just past the ones we're ranking caps the rank at
+        // `64 - HLL_PRECISION + 1`
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+        (index, rank as u8)
+    }
+
+    fn register(&self, index: u16) -> u8 {
+        match &self.registers {
+            Registers::Sparse(registers) => registers.get(&index).copied().unwrap_or(0),
+            Registers::Dense(registers) => registers[index as usize],
+        }
+    }
+
+    /// Raise the register at the given index to at least `rank`
+    fn raise_register(&mut self, index: u16, rank: u8) {
+        match &mut self.registers {
+            Registers::Sparse(registers) => {
+                let register = registers.entry(index).or_default();
+                *register = (*register).max(rank);
+                if registers.len() > HLL_SPARSE_MAX {
+                    let mut dense = vec![0; HLL_REGISTERS];
+                    for (&index, &rank) in registers.iter() {
+                        dense[index as usize] = rank;
+                    }
+                    self.registers = Registers::Dense(dense);
+                }
+            }
+            Registers::Dense(registers) => {
+                let register = &mut registers[index as usize];
+                *register = (*register).max(rank);
+            }
+        }
+    }
+
+    /// Add a value to the sketch
+    pub fn insert(&mut self, value: &DfValue) {
+        let (index, rank) = Self::position(value);
+        self.raise_register(index, rank);
+    }
+
+    /// Returns true if removing one occurrence of `value` from the multiset of values this sketch
+    /// was built from would leave the sketch unchanged.
+    ///
+    /// This is the case if the register for `value` was set by some other value with a higher
+    /// rank. Otherwise, there's no way to know whether another value shares the same rank, so the
+    /// sketch has to be rebuilt without `value`.
+    pub fn unaffected_by_removal(&self, value: &DfValue) -> bool {
+        let (index, rank) = Self::position(value);
+        self.register(index) > rank
+    }
+
+    /// Merge the values seen by `other` into this sketch
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        match &other.registers {
+            Registers::Sparse(registers) => {
+                for (&index, &rank) in registers {
+                    self.raise_register(index, rank);
+                }
+            }
+            Registers::Dense(registers) => {
+                for (index, &rank) in registers.iter().enumerate() {
+                    if rank > 0 {
+                        self.raise_register(index as u16, rank);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Estimate the number of distinct values this sketch has seen
+    pub fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let (zeros, inverse_sum) = match &self.registers {
+            Registers::Sparse(registers) => (
+                HLL_REGISTERS - registers.len(),
+                (HLL_REGISTERS - registers.len()) as f64
+                    + registers
+                        .values()
+                        .map(|&rank| 2f64.powi(-(rank as i32)))
+                        .sum::<f64>(),
+            ),
+            Registers::Dense(registers) => (
+                registers.iter().filter(|&&rank| rank == 0).count(),
+                registers
+                    .iter()
+                    .map(|&rank| 2f64.powi(-(rank as i32)))
+                    .sum::<f64>(),
+            ),
+        };
+
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let raw = alpha * m * m / inverse_sum;
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            // Linear counting is much more accurate for small cardinalities
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+
+    /// Serialize this sketch into a byte array
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match &self.registers {
+            Registers::Sparse(registers) => {
+                let mut bytes = Vec::with_capacity(1 + registers.len() * 3);
+                bytes.push(HLL_SPARSE_TAG);
+                for (index, rank) in registers {
+                    bytes.extend_from_slice(&index.to_le_bytes());
+                    bytes.push(*rank);
+                }
+                bytes
+            }
+            Registers::Dense(registers) => {
+                let mut bytes = Vec::with_capacity(1 + HLL_REGISTERS);
+                bytes.push(HLL_DENSE_TAG);
+                bytes.extend_from_slice(registers);
+                bytes
+            }
+        }
+    }
+
+    /// Deserialize a sketch previously serialized with [`HyperLogLog::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> ReadySetResult<Self> {
+        let registers = match bytes.split_first() {
+            Some((&HLL_SPARSE_TAG, rest)) if rest.len() % 3 == 0 => Registers::Sparse(
+                rest.chunks_exact(3)
+                    .map(|chunk| (u16::from_le_bytes([chunk[0], chunk[1]]), chunk[2]))
+                    .collect(),
+            ),
+            Some((&HLL_DENSE_TAG, rest)) if rest.len() == HLL_REGISTERS => {
+                Registers::Dense(rest.to_vec())
+            }
+            _ => internal!("Invalid HyperLogLog sketch"),
+        };
+        Ok(Self { registers })
+    }
+}
+
+impl From<&HyperLogLog> for DfValue {
+    fn from(sketch: &HyperLogLog) -> Self {
+        DfValue::ByteArray(Arc::new(sketch.to_bytes()))
+    }
+}
+
+impl TryFrom<&DfValue> for HyperLogLog {
+    type Error = readyset_errors::ReadySetError;
+
+    fn try_from(value: &DfValue) -> ReadySetResult<Self> {
+        match value {
+            DfValue::ByteArray(bytes) => Self::from_bytes(bytes),
+            _ => internal!("HyperLogLog sketches must be stored as byte arrays"),
+        }
+    }
+}
+
+/// Relative accuracy of the values estimated by a [`QuantileSketch`]
+const QUANTILE_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Ratio between the bounds of each bucket in a [`QuantileSketch`]
+const QUANTILE_GAMMA: f64 = (1.0 + QUANTILE_RELATIVE_ACCURACY) / (1.0 - QUANTILE_RELATIVE_ACCURACY);
+
+/// Values with a magnitude smaller than this are counted as zero by a [`QuantileSketch`]
+const QUANTILE_MIN_MAGNITUDE: f64 = 1e-9;
+
+/// A [DDSketch][]-style quantile sketch, which estimates the value at any given percentile of the
+/// values it has seen to within 1% of the actual value.
+///
+/// Values are counted in buckets whose bounds grow exponentially, so the size of the sketch only
+/// depends on the range of the values it has seen. Since the sketch only keeps counts, values can
+/// be removed from it again.
+///
+/// [DDSketch]: https://arxiv.org/abs/1908.10693
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuantileSketch {
+    /// Number of values counted as zero
+    zeros: u64,
+    /// Counts of positive values, keyed by bucket
+    positive: BTreeMap<i32, u64>,
+    /// Counts of negative values, keyed by the bucket of their magnitude
+    negative: BTreeMap<i32, u64>,
+}
+
+impl QuantileSketch {
+    fn bucket(magnitude: f64) -> i32 {
+        (magnitude.ln() / QUANTILE_GAMMA.ln()).ceil() as i32
+    }
+
+    /// Returns the value that represents all the values in the given bucket
+    fn bucket_value(bucket: i32) -> f64 {
+        2.0 * QUANTILE_GAMMA.powi(bucket) / (QUANTILE_GAMMA + 1.0)
+    }
+
+    /// Returns the count for the bucket which `value` falls in
+    fn count_for(&mut self, value: f64) -> &mut u64 {
+        if value.abs() < QUANTILE_MIN_MAGNITUDE {
+            &mut self.zeros
+        } else if value > 0.0 {
+            self.positive.entry(Self::bucket(value)).or_default()
+        } else {
+            self.negative.entry(Self::bucket(-value)).or_default()
+        }
+    }
+
+    /// Returns the total number of values in the sketch
+    pub fn count(&self) -> u64 {
+        self.zeros + self.positive.values().sum::<u64>() + self.negative.values().sum::<u64>()
+    }
+
+    /// Add a value to the sketch. NaNs are ignored
+    pub fn insert(&mut self, value: f64) {
+        if !value.is_nan() {
+            *self.count_for(value) += 1;
+        }
+    }
+
+    /// Remove a value that was previously added to the sketch. NaNs are ignored.
+    ///
+    /// Removing a value the sketch hasn't seen (which can happen if a negative arrives before the
+    /// positive it retracts) leaves the count for its bucket at zero, rather than failing.
+    pub fn remove(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let count = self.count_for(value);
+        *count = count.saturating_sub(1);
+        self.positive.retain(|_, count| *count > 0);
+        self.negative.retain(|_, count| *count > 0);
+    }
+
+    /// Merge the values seen by `other` into this sketch
+    pub fn merge(&mut self, other: &QuantileSketch) {
+        self.zeros += other.zeros;
+        for (bucket, count) in &other.positive {
+            *self.positive.entry(*bucket).or_default() += count;
+        }
+        for (bucket, count) in &other.negative {
+            *self.negative.entry(*bucket).or_default() += count;
+        }
+    }
+
+    /// Estimate the value at the given percentile (a fraction between 0 and 1) of the values in
+    /// the sketch, or [`None`] if the sketch is empty.
+    ///
+    /// Like `PERCENTILE_DISC`, this estimates the first value whose position in the sorted values
+    /// is at or after the given percentile.
+    pub fn quantile(&self, percentile: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((percentile * count as f64).ceil() as u64).clamp(1, count);
+
+        let mut seen = 0;
+        for (bucket, bucket_count) in self.negative.iter().rev() {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(-Self::bucket_value(*bucket));
+            }
+        }
+        seen += self.zeros;
+        if seen >= rank {
+            return Some(0.0);
+        }
+        for (bucket, bucket_count) in &self.positive {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(Self::bucket_value(*bucket));
+            }
+        }
+        None
+    }
+
+    /// Serialize this sketch into a byte array
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(8 + 2 * 4 + (self.positive.len() + self.negative.len()) * 12);
+        bytes.extend_from_slice(&self.zeros.to_le_bytes());
+        for buckets in [&self.positive, &self.negative] {
+            bytes.extend_from_slice(&(buckets.len() as u32).to_le_bytes());
+            for (bucket, count) in buckets {
+                bytes.extend_from_slice(&bucket.to_le_bytes());
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Deserialize a sketch previously serialized with [`QuantileSketch::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> ReadySetResult<Self> {
+        fn take<const N: usize>(bytes: &mut &[u8]) -> ReadySetResult<[u8; N]> {
+            if bytes.len() < N {
+                internal!("Invalid quantile sketch");
+            }
+            let (taken, rest) = bytes.split_at(N);
+            *bytes = rest;
+            taken
+                .try_into()
+                .map_err(|_| internal_err!("Invalid quantile sketch"))
+        }
+        fn take_buckets(bytes: &mut &[u8]) -> ReadySetResult<BTreeMap<i32, u64>> {
+            let len = u32::from_le_bytes(take(bytes)?);
+            (0..len)
+                .map(|_| {
+                    let bucket = i32::from_le_bytes(take(bytes)?);
+                    Ok((bucket, u64::from_le_bytes(take(bytes)?)))
+                })
+                .collect()
+        }
+
+        let mut bytes = bytes;
+        let zeros = u64::from_le_bytes(take(&mut bytes)?);
+        let positive = take_buckets(&mut bytes)?;
+        let negative = take_buckets(&mut bytes)?;
+        if !bytes.is_empty() {
+            internal!("Invalid quantile sketch");
+        }
+        Ok(Self {
+            zeros,
+            positive,
+            negative,
+        })
+    }
+}
+
+impl From<&QuantileSketch> for DfValue {
+    fn from(sketch: &QuantileSketch) -> Self {
+        DfValue::ByteArray(Arc::new(sketch.to_bytes()))
+    }
+}
+
+impl TryFrom<&DfValue> for QuantileSketch {
+    type Error = readyset_errors::ReadySetError;
+
+    fn try_from(value: &DfValue) -> ReadySetResult<Self> {
+        match value {
+            DfValue::ByteArray(bytes) => Self::from_bytes(bytes),
+            _ => internal!("Quantile sketches must be stored as byte arrays"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyperloglog_small_cardinalities() {
+        let mut hll = HyperLogLog::default();
+        for i in 0..10 {
+            hll.insert(&DfValue::from(i));
+            hll.insert(&DfValue::from(i));
+        }
+        assert!((9..=11).contains(&hll.estimate()));
+    }
+
+    #[test]
+    fn hyperloglog_large_cardinalities() {
+        let mut hll = HyperLogLog::default();
+        for i in 0..100_000 {
+            hll.insert(&DfValue::from(i));
+        }
+        assert!(matches!(hll.registers, Registers::Dense(_)));
+        let estimate = hll.estimate() as f64;
+        assert!((estimate - 100_000.0).abs() < 5_000.0, "{estimate}");
+    }
+
+    #[test]
+    fn hyperloglog_merge() {
+        let mut evens = HyperLogLog::default();
+        let mut odds = HyperLogLog::default();
+        let mut all = HyperLogLog::default();
+        for i in 0..1000 {
+            if i % 2 == 0 {
+                evens.insert(&DfValue::from(i));
+            } else {
+                odds.insert(&DfValue::from(i));
+            }
+            all.insert(&DfValue::from(i));
+        }
+        evens.merge(&odds);
+        assert_eq!(evens, all);
+    }
+
+    #[test]
+    fn hyperloglog_removal() {
+        let mut hll = HyperLogLog::default();
+        hll.insert(&DfValue::from(1));
+        assert!(!hll.unaffected_by_removal(&DfValue::from(1)));
+    }
+
+    #[test]
+    fn hyperloglog_round_trip() {
+        let mut hll = HyperLogLog::default();
+        for i in 0..10 {
+            hll.insert(&DfValue::from(i));
+        }
+        assert_eq!(HyperLogLog::try_from(&DfValue::from(&hll)).unwrap(), hll);
+        for i in 0..10_000 {
+            hll.insert(&DfValue::from(i));
+        }
+        assert_eq!(HyperLogLog::try_from(&DfValue::from(&hll)).unwrap(), hll);
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut sketch = QuantileSketch::default();
+        assert_eq!(sketch.quantile(0.5), None);
+        for i in 1..=100 {
+            sketch.insert(i as f64);
+        }
+        for (percentile, expected) in [(0.0, 1.0), (0.25, 25.0), (0.5, 50.0), (1.0, 100.0)] {
+            let estimate = sketch.quantile(percentile).unwrap();
+            assert!(
+                (estimate - expected).abs() <= expected * QUANTILE_RELATIVE_ACCURACY,
+                "{percentile}: {estimate} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn quantiles_negative_and_zero() {
+        let mut sketch = QuantileSketch::default();
+        for v in [-10.0, -1.0, 0.0, 1.0, 10.0] {
+            sketch.insert(v);
+        }
+        assert!((sketch.quantile(0.0).unwrap() + 10.0).abs() <= 0.1);
+        assert_eq!(sketch.quantile(0.5), Some(0.0));
+        assert!((sketch.quantile(1.0).unwrap() - 10.0).abs() <= 0.1);
+    }
+
+    #[test]
+    fn quantiles_remove_and_merge() {
+        let mut a = QuantileSketch::default();
+        let mut b = QuantileSketch::default();
+        a.insert(1.0);
+        a.insert(100.0);
+        b.insert(50.0);
+        a.merge(&b);
+        assert_eq!(a.count(), 3);
+        a.remove(100.0);
+        a.remove(50.0);
+        assert_eq!(a.count(), 1);
+        a.remove(50.0);
+        assert_eq!(a.count(), 1);
+        assert_eq!(QuantileSketch::try_from(&DfValue::from(&a)).unwrap(), a);
+    }
+}
@@ -6,7 +6,13 @@
 //! 2. The client returns a 20-byte random response based on the algorithm in [`hash_password`]
 //! 3. The server runs the same algorithm, and checks the response against the result
 //!
+//! When pass-through authentication is enabled (see [`MySqlShim::pass_through_authentication`]),
+//! the client is instead asked for its password in cleartext via the full authentication flow of
+//! [`CACHING_SHA2_PASSWORD_PLUGIN_NAME`]. This is only done over TLS; clients connecting without
+//! TLS are rejected.
+//!
 //! [0]: https://dev.mysql.com/doc/internals/en/secure-password-authentication.html
+//! [`MySqlShim::pass_through_authentication`]: crate::MySqlShim::pass_through_authentication
 
 use getrandom::getrandom;
 use sha1::{Digest, Sha1};
@@ -15,9 +21,21 @@ use crate::error::MsqlSrvError;
 
 pub type AuthData = [u8; 20];
 
-/// The name of the (currently only) supported auth plugin
+/// The name of the auth plugin used to check passwords against
+/// [`MySqlShim::password_for_username`](crate::MySqlShim::password_for_username)
 pub const AUTH_PLUGIN_NAME: &str = "mysql_native_password";
 
+/// The name of the auth plugin used by default by MySQL 8.0 and later
+pub const CACHING_SHA2_PASSWORD_PLUGIN_NAME: &str = "caching_sha2_password";
+
+/// Status tag for an `AuthMoreData` packet
+pub const AUTH_MORE_DATA: u8 = 0x01;
+
+/// Sent by the server in an `AuthMoreData` packet during `caching_sha2_password` authentication
+/// to request the client's full password, which clients only send in cleartext over a secure
+/// connection
+pub const PERFORM_FULL_AUTHENTICATION: u8 = 0x04;
+
 /// Bytewise-XOR b1 with b2 in-place
 fn xor_slice_mut<const N: usize>(b1: &mut [u8; N], b2: &[u8; N]) {
     b1.iter_mut().zip(b2.iter()).for_each(|(x, y)| *x ^= y);
@@ -17,6 +17,8 @@ pub struct ClientHandshake<'a> {
     pub password: &'a [u8],
     pub database: Option<&'a str>,
     pub auth_plugin_name: Option<&'a str>,
+    /// The compression level requested by the client, if it supports zstd compression
+    pub zstd_compression_level: Option<u8>,
 }
 
 /// Parse a "length-encoded integer" as specified by the [mysql binary protocol documentation][docs]
@@ -52,6 +54,18 @@ fn null_terminated_string(i: &[u8]) -> IResult<&[u8], &str> {
     Ok((i, res))
 }
 
+/// Returns `true` if the given packet is an `SSLRequest`, which a client sends in place of its
+/// handshake response to request that the connection be upgraded to TLS.
+///
+/// An `SSLRequest` is the first 32 bytes of a handshake response (with the `CLIENT_SSL` capability
+/// set), without any of the fields that follow the filler.
+pub fn is_ssl_request(i: &[u8]) -> bool {
+    i.len() == 32
+        && le_u32::<_, nom::error::Error<_>>(i).map_or(false, |(_, capabilities)| {
+            CapabilityFlags::from_bits_truncate(capabilities).contains(CapabilityFlags::CLIENT_SSL)
+        })
+}
+
 /// <https://dev.mysql.com/doc/internals/en/connection-phase-packets.html#packet-Protocol::HandshakeResponse41>
 pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
     let (i, capabilities) = map(le_u32, CapabilityFlags::from_bits_truncate)(i)?;
@@ -82,6 +96,24 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
         (i, None)
     };
 
+    // We don't use connection attributes, but we have to skip over them to get to the zstd
+    // compression level
+    let (i, _connect_attrs) = if capabilities.contains(CapabilityFlags::CLIENT_CONNECT_ATTRS) {
+        opt(|i| {
+            let (i, len) = lenenc_int(i)?;
+            take(len as usize)(i)
+        })(i)?
+    } else {
+        (i, None)
+    };
+
+    let (i, zstd_compression_level) =
+        if capabilities.contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM) {
+            opt(le_u8)(i)?
+        } else {
+            (i, None)
+        };
+
     Ok((
         i,
         ClientHandshake {
@@ -92,6 +124,7 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
             password,
             database,
             auth_plugin_name,
+            zstd_compression_level,
         },
     ))
 }
@@ -215,6 +248,29 @@ mod tests {
         assert_eq!(handshake.maxps, 16777216);
     }
 
+    #[test]
+    fn it_detects_ssl_request() {
+        let mut ssl_request = [0u8; 32];
+        ssl_request[..4].copy_from_slice(
+            &(CapabilityFlags::CLIENT_SSL | CapabilityFlags::CLIENT_PROTOCOL_41)
+                .bits()
+                .to_le_bytes(),
+        );
+        ssl_request[4..8].copy_from_slice(&16777216u32.to_le_bytes());
+        ssl_request[8] = UTF8_GENERAL_CI as u8;
+        assert!(is_ssl_request(&ssl_request));
+
+        // Without the SSL capability, it's just a truncated handshake response
+        let mut no_ssl = ssl_request;
+        no_ssl[..4].copy_from_slice(&CapabilityFlags::CLIENT_PROTOCOL_41.bits().to_le_bytes());
+        assert!(!is_ssl_request(&no_ssl));
+
+        // A full handshake response with the SSL capability set is not an SSL request
+        let mut handshake = ssl_request.to_vec();
+        handshake.extend_from_slice(b"jon\0\0");
+        assert!(!is_ssl_request(&handshake));
+    }
+
     #[tokio::test]
     async fn it_parses_request() {
         let data = &[
@@ -164,16 +164,23 @@ use std::io;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use constants::{CLIENT_PLUGIN_AUTH, PROTOCOL_41, RESERVED, SECURE_CONNECTION};
+use constants::{
+    CLIENT_PLUGIN_AUTH, COMPRESS, PROTOCOL_41, RESERVED, SECURE_CONNECTION, SSL,
+    ZSTD_COMPRESSION_ALGORITHM,
+};
 use error::{other_error, OtherErrorKind};
 use mysql_common::constants::CapabilityFlags;
 use readyset_data::DfType;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncWrite, WriteHalf};
 use tokio::net;
+use tokio_native_tls::{TlsAcceptor, TlsStream};
 use tracing::{debug, info, trace};
 use writers::write_err;
 
-use crate::authentication::{generate_auth_data, hash_password, AUTH_PLUGIN_NAME};
+use crate::authentication::{
+    generate_auth_data, hash_password, AuthData, AUTH_MORE_DATA, AUTH_PLUGIN_NAME,
+    CACHING_SHA2_PASSWORD_PLUGIN_NAME, PERFORM_FULL_AUTHENTICATION,
+};
 pub use crate::myc::constants::{ColumnFlags, ColumnType, StatusFlags};
 pub use crate::writers::prepare_column_definitions;
 
@@ -284,6 +291,22 @@ pub trait MySqlShim<W: AsyncWrite + Unpin + Send> {
     fn require_authentication(&self) -> bool {
         true
     }
+
+    /// Return true if clients should be authenticated by passing their cleartext password to
+    /// [`MySqlShim::authenticate_pass_through`], rather than by checking against
+    /// [`MySqlShim::password_for_username`]
+    fn pass_through_authentication(&self) -> bool {
+        false
+    }
+
+    /// Authenticate the user with the given username using the given cleartext password, by some
+    /// means other than [`MySqlShim::password_for_username`]. Only called if
+    /// [`MySqlShim::pass_through_authentication`] returns true.
+    ///
+    /// Returns true if the user was successfully authenticated.
+    async fn authenticate_pass_through(&mut self, _username: &str, _password: &[u8]) -> bool {
+        false
+    }
 }
 
 /// Stores a preencoded result schema for a prepared MySQL statement
@@ -306,6 +329,8 @@ pub struct MySqlIntermediary<B, R: AsyncRead + Unpin, W: AsyncWrite + Unpin> {
     schema_cache: HashMap<u32, CachedSchema>,
     /// Whether to log statements received from a client
     enable_statement_logging: bool,
+    /// Whether the connection to the client is encrypted with TLS
+    secure_transport: bool,
 }
 
 impl<B: MySqlShim<net::tcp::OwnedWriteHalf> + Send>
@@ -325,6 +350,71 @@ impl<B: MySqlShim<net::tcp::OwnedWriteHalf> + Send>
     }
 }
 
+impl<B> MySqlIntermediary<B, net::TcpStream, net::TcpStream>
+where
+    B: MySqlShim<net::tcp::OwnedWriteHalf> + MySqlShim<WriteHalf<TlsStream<net::TcpStream>>> + Send,
+{
+    /// Create a new server over a TCP stream which accepts TLS connections, and process client
+    /// commands until the client disconnects or an error occurs.
+    ///
+    /// The `CLIENT_SSL` capability is advertised in the initial handshake packet, and if the client
+    /// responds with an `SSLRequest` the connection is upgraded to TLS using `tls_acceptor` before
+    /// the client authenticates. If `require_tls` is set, clients that do not request TLS are
+    /// rejected.
+    pub async fn run_on_tls_tcp(
+        shim: B,
+        mut stream: net::TcpStream,
+        enable_statement_logging: bool,
+        tls_acceptor: Arc<TlsAcceptor>,
+        require_tls: bool,
+    ) -> Result<(), io::Error> {
+        stream.set_nodelay(true)?;
+
+        let auth_data =
+            generate_auth_data().map_err(|_| other_error(OtherErrorKind::AuthDataErr))?;
+        let version = MySqlShim::<net::tcp::OwnedWriteHalf>::version(&shim);
+        let mut writer = packet::PacketWriter::new(&mut stream);
+        write_initial_handshake(&mut writer, &version, &auth_data, CAPABILITIES | SSL).await?;
+
+        // The client starts the TLS handshake immediately after sending an `SSLRequest`, so we
+        // can't read from the stream any further than the first packet until we know whether to
+        // hand it off to the TLS acceptor
+        let (seq, handshake) = packet::read_single_packet(&mut stream).await?;
+
+        if commands::is_ssl_request(&handshake) {
+            let stream = tls_acceptor
+                .accept(stream)
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::ConnectionAborted, e))?;
+            debug!("Established TLS connection");
+
+            let (reader, writer) = tokio::io::split(stream);
+            let mut mi = MySqlIntermediary::new(shim, reader, writer, enable_statement_logging);
+            mi.secure_transport = true;
+            let (seq, handshake) = mi.next_handshake_response().await?;
+            let (auth_success, database) = mi.authenticate(&auth_data, seq, &handshake).await?;
+            mi.run_authenticated(auth_success, database).await
+        } else {
+            let (reader, writer) = stream.into_split();
+            let mut mi = MySqlIntermediary::new(shim, reader, writer, enable_statement_logging);
+            if require_tls {
+                debug!("Client did not request TLS, returning authentication error");
+                mi.writer.set_seq(seq + 1);
+                writers::write_err(
+                    ErrorKind::ER_ACCESS_DENIED_ERROR,
+                    b"Connections using insecure transport are prohibited",
+                    &mut mi.writer,
+                )
+                .await?;
+                return mi.writer.flush().await;
+            }
+
+            let (auth_success, database) = mi.authenticate(&auth_data, seq, &handshake).await?;
+            mi.run_authenticated(auth_success, database).await
+        }
+    }
+}
+
 impl<B: MySqlShim<S> + Send, S: AsyncRead + AsyncWrite + Clone + Unpin + Send>
     MySqlIntermediary<B, S, S>
 {
@@ -349,6 +439,36 @@ where
     write_err(error_kind, msg, &mut w).await
 }
 
+/// Send the initial HandshakeV10 packet to the client, advertising the given capabilities
+async fn write_initial_handshake<W: AsyncWrite + Unpin>(
+    writer: &mut packet::PacketWriter<W>,
+    version: &str,
+    auth_data: &AuthData,
+    capabilities: u32,
+) -> io::Result<()> {
+    let mut init_packet = Vec::with_capacity(
+        1 + 16 + 4 + 8 + 1 + 2 + 1 + 2 + 2 + 1 + 6 + 4 + 12 + 1 + AUTH_PLUGIN_NAME.len() + 1,
+    );
+    init_packet.extend_from_slice(&[10]); // protocol 10
+    init_packet.extend_from_slice(version.as_bytes());
+    init_packet.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]); // TODO: connection ID
+    init_packet.extend_from_slice(&auth_data[..8]);
+    init_packet.push(0);
+    init_packet.extend_from_slice(&capabilities.to_le_bytes()[..2]);
+    init_packet.extend_from_slice(&[0x21]); // UTF8_GENERAL_CI
+    init_packet.extend_from_slice(&[0x00, 0x00]); // status flags
+    init_packet.extend_from_slice(&capabilities.to_le_bytes()[2..]);
+    init_packet.extend_from_slice(&[auth_data.len() as u8]);
+    init_packet.extend_from_slice(&[0x00; 10][..]); // filler
+    init_packet.extend_from_slice(&auth_data[8..]);
+    init_packet.push(0);
+    init_packet.extend_from_slice(AUTH_PLUGIN_NAME.as_bytes());
+    init_packet.push(0);
+
+    writer.write_packet(&init_packet).await?;
+    writer.flush().await
+}
+
 #[derive(Default)]
 struct StatementData {
     long_data: HashMap<u16, Vec<u8>>,
@@ -356,7 +476,12 @@ struct StatementData {
     params: u16,
 }
 
-const CAPABILITIES: u32 = PROTOCOL_41 | SECURE_CONNECTION | RESERVED | CLIENT_PLUGIN_AUTH;
+const CAPABILITIES: u32 = PROTOCOL_41
+    | SECURE_CONNECTION
+    | RESERVED
+    | CLIENT_PLUGIN_AUTH
+    | COMPRESS
+    | ZSTD_COMPRESSION_ALGORITHM;
 
 impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
     MySqlIntermediary<B, R, W>
@@ -369,20 +494,34 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         writer: W,
         enable_statement_logging: bool,
     ) -> Result<(), io::Error> {
-        let r = packet::PacketReader::new(reader);
-        let w = packet::PacketWriter::new(writer);
-        let mut mi = MySqlIntermediary {
+        let mut mi = MySqlIntermediary::new(shim, reader, writer, enable_statement_logging);
+        let (auth_success, database) = mi.init().await?;
+        mi.run_authenticated(auth_success, database).await
+    }
+
+    fn new(shim: B, reader: R, writer: W, enable_statement_logging: bool) -> Self {
+        MySqlIntermediary {
             shim,
-            reader: r,
-            writer: w,
+            reader: packet::PacketReader::new(reader),
+            writer: packet::PacketWriter::new(writer),
             schema_cache: HashMap::new(),
             enable_statement_logging,
-        };
-        if let (true, database) = mi.init().await? {
+            secure_transport: false,
+        }
+    }
+
+    /// Process client commands once the handshake has completed, if the client successfully
+    /// authenticated
+    async fn run_authenticated(
+        mut self,
+        auth_success: bool,
+        database: Option<String>,
+    ) -> Result<(), io::Error> {
+        if auth_success {
             if let Some(database) = database {
-                mi.shim.on_init(&database, None).await?;
+                self.shim.on_init(&database, None).await?;
             }
-            mi.run().await?;
+            self.run().await?;
         }
         Ok(())
     }
@@ -401,35 +540,41 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         let auth_data =
             generate_auth_data().map_err(|_| other_error(OtherErrorKind::AuthDataErr))?;
 
-        let mut init_packet = Vec::with_capacity(
-            1 + 16 + 4 + 8 + 1 + 2 + 1 + 2 + 2 + 1 + 6 + 4 + 12 + 1 + AUTH_PLUGIN_NAME.len() + 1,
-        );
-        init_packet.extend_from_slice(&[10]); // protocol 10
-        init_packet.extend_from_slice(self.shim.version().as_bytes());
-        init_packet.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]); // TODO: connection ID
-        init_packet.extend_from_slice(&auth_data[..8]);
-        init_packet.push(0);
-        init_packet.extend_from_slice(&CAPABILITIES.to_le_bytes()[..2]);
-        init_packet.extend_from_slice(&[0x21]); // UTF8_GENERAL_CI
-        init_packet.extend_from_slice(&[0x00, 0x00]); // status flags
-        init_packet.extend_from_slice(&CAPABILITIES.to_le_bytes()[2..]);
-        init_packet.extend_from_slice(&[auth_data.len() as u8]);
-        init_packet.extend_from_slice(&[0x00; 10][..]); // filler
-        init_packet.extend_from_slice(&auth_data[8..]);
-        init_packet.push(0);
-        init_packet.extend_from_slice(AUTH_PLUGIN_NAME.as_bytes());
-        init_packet.push(0);
-
-        self.writer.write_packet(&init_packet).await?;
-        self.writer.flush().await?;
+        write_initial_handshake(
+            &mut self.writer,
+            &self.shim.version(),
+            &auth_data,
+            CAPABILITIES,
+        )
+        .await?;
+
+        let (seq, handshake_bytes) = self.next_handshake_response().await?;
+        self.authenticate(&auth_data, seq, &handshake_bytes).await
+    }
 
+    /// Read the HandshakeResponse packet sent by the client in response to the initial handshake
+    async fn next_handshake_response(&mut self) -> Result<(u8, Vec<u8>), io::Error> {
         let (seq, handshake_bytes) = self.reader.next().await?.ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::ConnectionAborted,
                 "peer terminated connection",
             )
         })?;
-        let handshake = commands::client_handshake(&handshake_bytes)
+        Ok((seq, handshake_bytes.to_vec()))
+    }
+
+    /// Authenticate the client using the HandshakeResponse packet it sent, sending and receiving
+    /// more packets as needed to complete authentication.
+    ///
+    /// Returns a tuple of a boolean to indicate whether authentication was successful, and a
+    /// database name if one was specified by the client in the handshake response.
+    async fn authenticate(
+        &mut self,
+        auth_data: &AuthData,
+        seq: u8,
+        handshake_bytes: &[u8],
+    ) -> Result<(bool, Option<String>), io::Error> {
+        let handshake = commands::client_handshake(handshake_bytes)
             .map_err(|e| match e {
                 nom::Err::Incomplete(_) => io::Error::new(
                     io::ErrorKind::UnexpectedEof,
@@ -454,12 +599,104 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
 
         self.writer.set_seq(seq + 1);
 
+        let compression = if handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM)
+        {
+            Some(packet::Compression::Zstd(
+                handshake
+                    .zstd_compression_level
+                    .unwrap_or(packet::DEFAULT_ZSTD_COMPRESSION_LEVEL),
+            ))
+        } else if handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_COMPRESS)
+        {
+            Some(packet::Compression::Zlib)
+        } else {
+            None
+        };
+
         let username = handshake.username.to_owned();
         let password = handshake.password.to_vec();
         let database = handshake.database.map(String::from);
         let client_auth_plugin = handshake.auth_plugin_name.map(|s| s.to_owned());
 
-        let handshake_password = if client_auth_plugin.iter().all(|apn| apn != AUTH_PLUGIN_NAME)
+        let auth_success = if self.shim.pass_through_authentication() {
+            if !handshake
+                .capabilities
+                .contains(CapabilityFlags::CLIENT_PLUGIN_AUTH)
+            {
+                debug!("Client does not support PLUGIN_AUTH, returning authentication error");
+                writers::write_err(
+                    ErrorKind::ER_NOT_SUPPORTED_AUTH_MODE,
+                    b"Client does not support authentication protocol requested by server; \
+                      consider upgrading MySQL client",
+                    &mut self.writer,
+                )
+                .await?;
+                return Ok((false, database));
+            }
+
+            if !self.secure_transport {
+                // Never ask for the client's password in cleartext over an unencrypted connection
+                debug!("Client did not connect over TLS, returning authentication error");
+                writers::write_err(
+                    ErrorKind::ER_ACCESS_DENIED_ERROR,
+                    b"Authenticating with the upstream database requires a TLS connection",
+                    &mut self.writer,
+                )
+                .await?;
+                self.writer.flush().await?;
+                return Ok((false, database));
+            }
+
+            let password = self.request_cleartext_password(auth_data).await?;
+            self.shim
+                .authenticate_pass_through(&username, &password)
+                .await
+        } else {
+            self.check_password(auth_data, &handshake, &username, password)
+                .await?
+        };
+
+        if auth_success {
+            debug!(%username, "Successfully authenticated client");
+            writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
+        } else {
+            debug!(%username, ?client_auth_plugin, "Received incorrect password");
+            writers::write_err(
+                ErrorKind::ER_ACCESS_DENIED_ERROR,
+                format!("Access denied for user {}", username).as_bytes(),
+                &mut self.writer,
+            )
+            .await?;
+        }
+        self.writer.flush().await?;
+
+        // The client switches to the compressed protocol once it has received the OK packet
+        if auth_success {
+            if let Some(compression) = compression {
+                debug!(?compression, "Enabling compressed protocol");
+                packet::enable_compression(&mut self.reader, &mut self.writer, compression);
+            }
+        }
+
+        Ok((auth_success, database))
+    }
+
+    /// Check the password sent by the client against the password returned by
+    /// [`MySqlShim::password_for_username`], first switching the client to the
+    /// `mysql_native_password` auth plugin if necessary.
+    async fn check_password(
+        &mut self,
+        auth_data: &AuthData,
+        handshake: &commands::ClientHandshake<'_>,
+        username: &str,
+        password: Vec<u8>,
+    ) -> Result<bool, io::Error> {
+        let client_auth_plugin = handshake.auth_plugin_name;
+        let handshake_password = if client_auth_plugin.iter().all(|apn| *apn != AUTH_PLUGIN_NAME)
             // Some clients (at the very least certain versions of PHP's MySQL PDO library) send an
             // empty password response in the initial handshake, even if the auth plugin is set and
             // correct. We want to send a switch-authentication request in that case too
@@ -479,7 +716,7 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                     &mut self.writer,
                 )
                 .await?;
-                return Ok((false, database));
+                return Ok(false);
             }
 
             debug!(
@@ -492,52 +729,79 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
             auth_switch_request_packet.push(0xfe);
             auth_switch_request_packet.extend_from_slice(AUTH_PLUGIN_NAME.as_bytes());
             auth_switch_request_packet.push(0);
-            auth_switch_request_packet.extend_from_slice(&auth_data);
+            auth_switch_request_packet.extend_from_slice(auth_data);
             auth_switch_request_packet.push(0);
             self.writer
                 .write_packet(&auth_switch_request_packet)
                 .await?;
             self.writer.flush().await?;
 
-            let (seq, auth_switch_response) = self.reader.next().await?.ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::ConnectionAborted,
-                    "peer terminated connection",
-                )
-            })?;
-            self.writer.set_seq(seq + 1);
-
-            auth_switch_response.to_vec()
+            self.next_auth_response().await?
         } else {
             password
         };
 
-        let auth_success = !self.shim.require_authentication()
+        Ok(!self.shim.require_authentication()
             || self
                 .shim
-                .password_for_username(&username)
+                .password_for_username(username)
                 .map_or(false, |password| {
-                    let expected = hash_password(&password, &auth_data);
+                    let expected = hash_password(&password, auth_data);
                     let actual = handshake_password.as_slice();
                     trace!(?expected, ?actual);
                     expected == actual
-                });
+                }))
+    }
 
-        if auth_success {
-            debug!(%username, "Successfully authenticated client");
-            writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
-        } else {
-            debug!(%username, ?client_auth_plugin, "Received incorrect password");
-            writers::write_err(
-                ErrorKind::ER_ACCESS_DENIED_ERROR,
-                format!("Access denied for user {}", username).as_bytes(),
-                &mut self.writer,
-            )
+    /// Request the client's password in cleartext, for authentication via
+    /// [`MySqlShim::authenticate_pass_through`]. Must only be called over a TLS connection.
+    ///
+    /// This switches the client to the `caching_sha2_password` auth plugin and then requests full
+    /// authentication, to which clients respond with their cleartext password.
+    async fn request_cleartext_password(
+        &mut self,
+        auth_data: &AuthData,
+    ) -> Result<Vec<u8>, io::Error> {
+        debug!("Requesting cleartext password from client");
+
+        let mut auth_switch_request_packet = Vec::with_capacity(
+            1 + CACHING_SHA2_PASSWORD_PLUGIN_NAME.len() + 1 + auth_data.len() + 1,
+        );
+        auth_switch_request_packet.push(0xfe);
+        auth_switch_request_packet.extend_from_slice(CACHING_SHA2_PASSWORD_PLUGIN_NAME.as_bytes());
+        auth_switch_request_packet.push(0);
+        auth_switch_request_packet.extend_from_slice(auth_data);
+        auth_switch_request_packet.push(0);
+        self.writer
+            .write_packet(&auth_switch_request_packet)
+            .await?;
+        self.writer.flush().await?;
+        // The client responds to the switch request with a scramble of its password, which we
+        // can't check without knowing the password - so ask for the full password instead
+        self.next_auth_response().await?;
+        self.writer
+            .write_packet(&[AUTH_MORE_DATA, PERFORM_FULL_AUTHENTICATION])
             .await?;
-        }
         self.writer.flush().await?;
+        let mut response = self.next_auth_response().await?;
 
-        Ok((auth_success, database))
+        // The password is sent as a null-terminated string
+        if response.last() == Some(&0) {
+            response.pop();
+        }
+        Ok(response)
+    }
+
+    /// Read the next packet sent by the client during authentication
+    async fn next_auth_response(&mut self) -> Result<Vec<u8>, io::Error> {
+        let (seq, response) = self.reader.next().await?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "peer terminated connection",
+            )
+        })?;
+        self.writer.set_seq(seq + 1);
+        Ok(response.to_vec())
     }
 
     async fn run(mut self) -> Result<(), io::Error> {
@@ -1,6 +1,10 @@
-use std::io::{self, IoSlice};
+use std::borrow::Cow;
+use std::io::{self, IoSlice, Read, Write};
+use std::sync::atomic::{self, AtomicU8};
 use std::sync::Arc;
 
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::error::{other_error, OtherErrorKind};
@@ -8,6 +12,130 @@ use crate::resultset::{MAX_POOL_ROWS, MAX_POOL_ROW_CAPACITY};
 
 const U24_MAX: usize = 16_777_215;
 
+/// Payloads shorter than this are sent uncompressed even if compression is enabled, since the
+/// overhead of compressing them outweighs the savings
+const MIN_COMPRESS_LENGTH: usize = 50;
+
+/// The default zstd compression level, used if the client doesn't specify one
+pub const DEFAULT_ZSTD_COMPRESSION_LEVEL: u8 = 3;
+
+/// A compression algorithm used for the compressed protocol, as negotiated with the client during
+/// the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// zlib, negotiated with the `CLIENT_COMPRESS` capability
+    Zlib,
+    /// zstd at the given compression level, negotiated with the
+    /// `CLIENT_ZSTD_COMPRESSION_ALGORITHM` capability
+    Zstd(u8),
+}
+
+impl Compression {
+    fn compress(self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(
+                    Vec::with_capacity(payload.len()),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(payload)?;
+                encoder.finish()
+            }
+            Compression::Zstd(level) => zstd::bulk::compress(payload, level.into()),
+        }
+    }
+
+    /// Decompress the given payload, returning an error if it doesn't decompress to exactly
+    /// `uncompressed_len` bytes
+    fn decompress(self, payload: &[u8], uncompressed_len: usize) -> io::Result<Vec<u8>> {
+        let res = match self {
+            Compression::Zlib => {
+                let mut res = Vec::with_capacity(uncompressed_len);
+                // Read at most one byte more than we expect, so that a payload that decompresses
+                // to more than it claims is detected without decompressing all of it
+                ZlibDecoder::new(payload)
+                    .take(uncompressed_len as u64 + 1)
+                    .read_to_end(&mut res)?;
+                res
+            }
+            // zstd fails if the payload decompresses to more than `uncompressed_len` bytes
+            Compression::Zstd(_) => zstd::bulk::decompress(payload, uncompressed_len)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        };
+        if res.len() != uncompressed_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "compressed payload decompressed to {} bytes, expected {}",
+                    res.len(),
+                    uncompressed_len
+                ),
+            ));
+        }
+        Ok(res)
+    }
+}
+
+/// State for the compressed protocol, shared between the [`PacketReader`] and [`PacketWriter`]
+/// for a connection.
+///
+/// With the compressed protocol, one or more packets (including their headers) are sent as the
+/// payload of a compressed packet, which has its own 7-byte header consisting of the length of the
+/// compressed payload, a sequence id, and the length of the payload before compression (or 0 if
+/// the payload is sent uncompressed).
+#[derive(Debug, Clone)]
+struct CompressionState {
+    compression: Compression,
+    /// The sequence id of the next compressed packet we write. Compressed packets have their own
+    /// sequence ids, which the server continues from the last compressed packet received from the
+    /// client - so this is shared between the reader and the writer.
+    seq: Arc<AtomicU8>,
+}
+
+impl CompressionState {
+    /// Wrap the given payload in a compressed packet, compressing it if it's worth doing so
+    fn compressed_packet(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let (body, uncompressed_len) = if payload.len() < MIN_COMPRESS_LENGTH {
+            (Cow::Borrowed(payload), 0)
+        } else {
+            match self.compression.compress(payload)? {
+                compressed if compressed.len() < payload.len() => {
+                    (Cow::Owned(compressed), payload.len())
+                }
+                // Incompressible payloads are sent as-is
+                _ => (Cow::Borrowed(payload), 0),
+            }
+        };
+
+        let seq = self.seq.fetch_add(1, atomic::Ordering::Relaxed);
+        let mut packet = Vec::with_capacity(7 + body.len());
+        packet.extend_from_slice(&body.len().to_le_bytes()[..3]);
+        packet.push(seq);
+        packet.extend_from_slice(&uncompressed_len.to_le_bytes()[..3]);
+        packet.extend_from_slice(&body);
+        Ok(packet)
+    }
+}
+
+/// Switch both halves of a connection to the compressed protocol. This should be called after
+/// sending the OK packet that completes authentication.
+pub fn enable_compression<R, W>(
+    reader: &mut PacketReader<R>,
+    writer: &mut PacketWriter<W>,
+    compression: Compression,
+) {
+    let state = CompressionState {
+        compression,
+        seq: Arc::new(AtomicU8::new(0)),
+    };
+    reader.decompressor = Some(Decompressor {
+        state: state.clone(),
+        buf: Vec::new(),
+        pos: 0,
+    });
+    writer.compression = Some(state);
+}
+
 pub struct PacketWriter<W> {
     pub seq: u8,
     w: W,
@@ -15,6 +143,9 @@ pub struct PacketWriter<W> {
 
     /// Reusable packets
     preallocated: Vec<QueuedPacket>,
+
+    /// If the compressed protocol is enabled, the state used to compress outgoing packets
+    compression: Option<CompressionState>,
 }
 
 /// Type for packets being enqueued in the packet writer.
@@ -55,6 +186,29 @@ async fn write_all_vectored<'a, W: AsyncWrite + Unpin>(
     }
 }
 
+/// Write the given slices to completion, wrapping them in compressed packets if `compression` is
+/// set.
+async fn write_slices<'a, W: AsyncWrite + Unpin>(
+    w: &'a mut W,
+    compression: Option<&CompressionState>,
+    slices: &'a mut [IoSlice<'a>],
+) -> io::Result<()> {
+    let Some(compression) = compression else {
+        return write_all_vectored(w, slices).await;
+    };
+
+    let mut payload = Vec::with_capacity(slices.iter().map(|s| s.len()).sum());
+    for slice in slices.iter() {
+        payload.extend_from_slice(slice);
+    }
+    // The uncompressed length of a compressed packet must fit in 3 bytes, just like the length of
+    // a regular packet
+    for chunk in payload.chunks(U24_MAX) {
+        w.write_all(&compression.compressed_packet(chunk)?).await?;
+    }
+    Ok(())
+}
+
 // Gets an IoSlice to each of the packets currently enqueued in `queue`.
 fn queued_packet_slices(queue: &[QueuedPacket]) -> Vec<IoSlice<'_>> {
     if queue.is_empty() {
@@ -82,6 +236,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             w,
             queue: Vec::new(),
             preallocated: Vec::new(),
+            compression: None,
         }
     }
 
@@ -131,7 +286,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
     pub async fn write_queued_packets(&mut self) -> Result<(), tokio::io::Error> {
         let mut slices = queued_packet_slices(&self.queue);
         if !slices.is_empty() {
-            write_all_vectored(&mut self.w, &mut slices).await?;
+            write_slices(&mut self.w, self.compression.as_ref(), &mut slices).await?;
             self.return_queued_to_pool();
         }
 
@@ -172,7 +327,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             }
         }
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_slices(&mut self.w, self.compression.as_ref(), &mut slices).await?;
         self.return_queued_to_pool();
 
         Ok(())
@@ -207,7 +362,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             IoSlice::new(packet),
         ]);
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_slices(&mut self.w, self.compression.as_ref(), &mut slices).await?;
 
         self.seq = self.seq.wrapping_add(1);
         Ok(())
@@ -227,11 +382,60 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
     }
 }
 
+/// Reads compressed packets from a stream, and yields the (decompressed) bytes of their payloads
+struct Decompressor {
+    state: CompressionState,
+    /// The payload of the last compressed packet read
+    buf: Vec<u8>,
+    /// The position in `buf` of the next byte to yield
+    pos: usize,
+}
+
+impl Decompressor {
+    /// Read decompressed bytes into `out`, reading another compressed packet from `r` if
+    /// necessary. Returns the number of bytes read, or 0 if the stream has ended.
+    async fn read<R: AsyncRead + Unpin>(&mut self, r: &mut R, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            let mut header = [0u8; 7];
+            let read = r.read(&mut header).await?;
+            if read == 0 {
+                return Ok(0);
+            }
+            r.read_exact(&mut header[read..]).await?;
+
+            let [len0, len1, len2, seq, ulen0, ulen1, ulen2] = header;
+            let len = u32::from_le_bytes([len0, len1, len2, 0]) as usize;
+            let uncompressed_len = u32::from_le_bytes([ulen0, ulen1, ulen2, 0]) as usize;
+            self.state
+                .seq
+                .store(seq.wrapping_add(1), atomic::Ordering::Relaxed);
+
+            let mut payload = vec![0u8; len];
+            r.read_exact(&mut payload).await?;
+            self.buf = if uncompressed_len == 0 {
+                payload
+            } else {
+                self.state
+                    .compression
+                    .decompress(&payload, uncompressed_len)?
+            };
+            self.pos = 0;
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
 pub struct PacketReader<R> {
     bytes: Vec<u8>,
     start: usize,
     remaining: usize,
     r: R,
+    /// If the compressed protocol is enabled, used to decompress incoming packets
+    decompressor: Option<Decompressor>,
 }
 
 impl<R> PacketReader<R> {
@@ -241,6 +445,7 @@ impl<R> PacketReader<R> {
             start: 0,
             remaining: 0,
             r,
+            decompressor: None,
         }
     }
 }
@@ -294,7 +499,10 @@ impl<R: AsyncRead + Unpin> PacketReader<R> {
                         length: new_len,
                     })
                 })?;
-                self.r.read(buf).await?
+                match &mut self.decompressor {
+                    Some(decompressor) => decompressor.read(&mut self.r, buf).await?,
+                    None => self.r.read(buf).await?,
+                }
             };
             self.bytes.truncate(end + read);
             self.remaining = self.bytes.len();
@@ -331,6 +539,22 @@ pub fn onepacket(i: &[u8]) -> nom::IResult<&[u8], (u8, &[u8])> {
     Ok((i, (seq[0], bytes)))
 }
 
+/// Read a single packet from `r`, without reading any bytes past the end of that packet.
+///
+/// This is used during the connection handshake, where the client may start a TLS handshake
+/// immediately after its `SSLRequest` packet - unlike [`PacketReader`], this leaves those bytes in
+/// the stream.
+pub async fn read_single_packet<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 4];
+    r.read_exact(&mut header).await?;
+    let [len0, len1, len2, seq] = header;
+    let len = u32::from_le_bytes([len0, len1, len2, 0]) as usize;
+
+    let mut bytes = vec![0u8; len];
+    r.read_exact(&mut bytes).await?;
+    Ok((seq, bytes))
+}
+
 pub struct Packet<'a>(&'a [u8], Vec<u8>);
 
 impl<'a> Packet<'a> {
@@ -460,6 +684,96 @@ mod tests {
         assert_eq!(&p.1[U24_MAX..], &[0x10]);
     }
 
+    #[tokio::test]
+    async fn test_compressed_round_trip() {
+        for compression in [
+            Compression::Zlib,
+            Compression::Zstd(DEFAULT_ZSTD_COMPRESSION_LEVEL),
+        ] {
+            let (u_out, u_in) = tokio::net::UnixStream::pair().unwrap();
+
+            let packets = vec![
+                vec![0u8; 10],
+                vec![1u8; 4096],
+                (0..=255u8).cycle().take(100_000).collect::<Vec<_>>(),
+            ];
+
+            let p = packets.clone();
+            tokio::spawn(async move {
+                let mut writer = PacketWriter::new(u_out);
+                enable_compression(
+                    &mut PacketReader::new(tokio::io::empty()),
+                    &mut writer,
+                    compression,
+                );
+
+                for packet in &p {
+                    writer.enqueue_packet(packet.clone());
+                }
+                writer.write_queued_packets().await.unwrap();
+
+                for packet in &p {
+                    writer.write_packet(&packet[..]).await.unwrap();
+                }
+                writer.flush().await.unwrap();
+            });
+
+            let mut reader = PacketReader::new(u_in);
+            enable_compression(
+                &mut reader,
+                &mut PacketWriter::new(tokio::io::sink()),
+                compression,
+            );
+
+            for _ in 0..2 {
+                for encoded in &packets {
+                    let decoded = reader.next().await.unwrap().unwrap();
+                    assert_eq!(&decoded.1[..], encoded);
+                }
+            }
+
+            assert!(reader.next().await.unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn small_payloads_are_not_compressed() {
+        let state = CompressionState {
+            compression: Compression::Zlib,
+            seq: Arc::new(AtomicU8::new(3)),
+        };
+        let packet = state.compressed_packet(&[1, 2, 3]).unwrap();
+        assert_eq!(packet, [3, 0, 0, 3, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(state.seq.load(atomic::Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn decompress_rejects_wrong_length() {
+        let payload = vec![7u8; 1000];
+        for compression in [
+            Compression::Zlib,
+            Compression::Zstd(DEFAULT_ZSTD_COMPRESSION_LEVEL),
+        ] {
+            let compressed = compression.compress(&payload).unwrap();
+            assert_eq!(
+                compression.decompress(&compressed, payload.len()).unwrap(),
+                payload
+            );
+
+            // The payload decompresses to more than the header claims
+            let err = compression
+                .decompress(&compressed, payload.len() - 1)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+            // The payload decompresses to less than the header claims
+            let err = compression
+                .decompress(&compressed, payload.len() + 1)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
     #[tokio::test]
     #[slow]
     async fn test_large_packet_write() {
@@ -99,6 +99,16 @@ impl<'a> ReferredColumnsIter<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
+            Stddev { expr, .. }
+            | Variance { expr, .. }
+            | JsonAgg { expr, .. }
+            | ApproxPercentile { expr, .. } => self.visit_expr(expr),
+            BoolAnd(arg)
+            | BoolOr(arg)
+            | BitAnd(arg)
+            | BitOr(arg)
+            | ArrayAgg(arg)
+            | ApproxCountDistinct(arg) => self.visit_expr(arg),
             Call { arguments, .. } => arguments.first().and_then(|first_arg| {
                 if arguments.len() >= 2 {
                     self.exprs_to_visit.extend(arguments.iter().skip(1));
@@ -110,6 +120,12 @@ impl<'a> ReferredColumnsIter<'a> {
                 self.exprs_to_visit.extend(len.iter().map(|e| e.as_ref()));
                 self.visit_expr(string)
             }
+            RowNumber | Rank | DenseRank => None,
+            Lag { expr, .. } | Lead { expr, .. } => self.visit_expr(expr),
+            Over { function, window } => {
+                self.exprs_to_visit.extend(window.exprs());
+                self.visit_function_expression(function)
+            }
         }
     }
 
@@ -210,6 +226,16 @@ impl<'a> ReferredColumnsMut<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
+            Stddev { expr, .. }
+            | Variance { expr, .. }
+            | JsonAgg { expr, .. }
+            | ApproxPercentile { expr, .. } => self.visit_expr(expr),
+            BoolAnd(arg)
+            | BoolOr(arg)
+            | BitAnd(arg)
+            | BitOr(arg)
+            | ArrayAgg(arg)
+            | ApproxCountDistinct(arg) => self.visit_expr(arg),
             Call { arguments, .. } => arguments.split_first_mut().and_then(|(first_arg, args)| {
                 self.exprs_to_visit.extend(args);
                 self.visit_expr(first_arg)
@@ -221,6 +247,12 @@ impl<'a> ReferredColumnsMut<'a> {
                     .extend(len.iter_mut().map(|e| e.as_mut()));
                 self.visit_expr(string)
             }
+            RowNumber | Rank | DenseRank => None,
+            Lag { expr, .. } | Lead { expr, .. } => self.visit_expr(expr),
+            Over { function, window } => {
+                self.exprs_to_visit.extend(window.exprs_mut());
+                self.visit_function_expression(function)
+            }
         }
     }
 
@@ -344,10 +376,27 @@ pub fn is_aggregate(function: &FunctionExpr) -> bool {
         | FunctionExpr::Sum { .. }
         | FunctionExpr::Max(_)
         | FunctionExpr::Min(_)
-        | FunctionExpr::GroupConcat { .. } => true,
+        | FunctionExpr::GroupConcat { .. }
+        | FunctionExpr::Stddev { .. }
+        | FunctionExpr::Variance { .. }
+        | FunctionExpr::BoolAnd(_)
+        | FunctionExpr::BoolOr(_)
+        | FunctionExpr::BitAnd(_)
+        | FunctionExpr::BitOr(_)
+        | FunctionExpr::ArrayAgg(_)
+        | FunctionExpr::JsonAgg { .. }
+        | FunctionExpr::ApproxCountDistinct(_)
+        | FunctionExpr::ApproxPercentile { .. } => true,
         FunctionExpr::Substring { .. }
         // For now, assume all "generic" function calls are not aggregates
-        | FunctionExpr::Call { .. } => false,
+        | FunctionExpr::Call { .. }
+        // Window functions are computed per-row, and don't collapse their input into groups
+        | FunctionExpr::RowNumber
+        | FunctionExpr::Rank
+        | FunctionExpr::DenseRank
+        | FunctionExpr::Lag { .. }
+        | FunctionExpr::Lead { .. }
+        | FunctionExpr::Over { .. } => false,
     }
 }
 
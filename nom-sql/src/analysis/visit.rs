@@ -493,6 +493,16 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
         FunctionExpr::Max(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::Min(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::GroupConcat { expr, .. } => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::Stddev { expr, .. }
+        | FunctionExpr::Variance { expr, .. }
+        | FunctionExpr::BoolAnd(expr)
+        | FunctionExpr::BoolOr(expr)
+        | FunctionExpr::BitAnd(expr)
+        | FunctionExpr::BitOr(expr)
+        | FunctionExpr::ArrayAgg(expr)
+        | FunctionExpr::JsonAgg { expr, .. }
+        | FunctionExpr::ApproxCountDistinct(expr)
+        | FunctionExpr::ApproxPercentile { expr, .. } => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::Call { arguments, .. } => {
             for arg in arguments {
                 visitor.visit_expr(arg)?;
@@ -509,6 +519,20 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
             }
             Ok(())
         }
+        FunctionExpr::RowNumber | FunctionExpr::Rank | FunctionExpr::DenseRank => Ok(()),
+        FunctionExpr::Lag { expr, .. } | FunctionExpr::Lead { expr, .. } => {
+            visitor.visit_expr(expr.as_ref())
+        }
+        FunctionExpr::Over { function, window } => {
+            visitor.visit_function_expr(function.as_ref())?;
+            for expr in &window.partition_by {
+                visitor.visit_expr(expr)?;
+            }
+            if let Some(order) = &window.order {
+                visitor.visit_order_clause(order)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -532,7 +556,11 @@ pub fn walk_common_table_expr<'ast, V: Visitor<'ast>>(
     cte: &'ast CommonTableExpr,
 ) -> Result<(), V::Error> {
     visitor.visit_sql_identifier(&cte.name)?;
-    visitor.visit_select_statement(&cte.statement)
+    visitor.visit_select_statement(&cte.statement)?;
+    if let Some((_, recursive_term)) = &cte.recursive_term {
+        visitor.visit_select_statement(recursive_term)?;
+    }
+    Ok(())
 }
 
 pub fn walk_field_definition_expr<'ast, V: Visitor<'ast>>(
@@ -508,6 +508,16 @@ pub fn walk_function_expr<'ast, V: VisitorMut<'ast>>(
         FunctionExpr::Max(expr) => visitor.visit_expr(expr.as_mut()),
         FunctionExpr::Min(expr) => visitor.visit_expr(expr.as_mut()),
         FunctionExpr::GroupConcat { expr, .. } => visitor.visit_expr(expr.as_mut()),
+        FunctionExpr::Stddev { expr, .. }
+        | FunctionExpr::Variance { expr, .. }
+        | FunctionExpr::BoolAnd(expr)
+        | FunctionExpr::BoolOr(expr)
+        | FunctionExpr::BitAnd(expr)
+        | FunctionExpr::BitOr(expr)
+        | FunctionExpr::ArrayAgg(expr)
+        | FunctionExpr::JsonAgg { expr, .. }
+        | FunctionExpr::ApproxCountDistinct(expr)
+        | FunctionExpr::ApproxPercentile { expr, .. } => visitor.visit_expr(expr.as_mut()),
         FunctionExpr::Call { arguments, .. } => {
             for arg in arguments {
                 visitor.visit_expr(arg)?;
@@ -524,6 +534,20 @@ pub fn walk_function_expr<'ast, V: VisitorMut<'ast>>(
             }
             Ok(())
         }
+        FunctionExpr::RowNumber | FunctionExpr::Rank | FunctionExpr::DenseRank => Ok(()),
+        FunctionExpr::Lag { expr, .. } | FunctionExpr::Lead { expr, .. } => {
+            visitor.visit_expr(expr.as_mut())
+        }
+        FunctionExpr::Over { function, window } => {
+            visitor.visit_function_expr(function.as_mut())?;
+            for expr in &mut window.partition_by {
+                visitor.visit_expr(expr)?;
+            }
+            if let Some(order) = &mut window.order {
+                visitor.visit_order_clause(order)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -547,7 +571,11 @@ pub fn walk_common_table_expr<'ast, V: VisitorMut<'ast>>(
     cte: &'ast mut CommonTableExpr,
 ) -> Result<(), V::Error> {
     visitor.visit_sql_identifier(&mut cte.name)?;
-    visitor.visit_select_statement(&mut cte.statement)
+    visitor.visit_select_statement(&mut cte.statement)?;
+    if let Some((_, recursive_term)) = &mut cte.recursive_term {
+        visitor.visit_select_statement(recursive_term)?;
+    }
+    Ok(())
 }
 
 pub fn walk_field_definition_expr<'ast, V: VisitorMut<'ast>>(
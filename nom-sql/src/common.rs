@@ -21,9 +21,11 @@ use test_strategy::Arbitrary;
 use crate::column::Column;
 use crate::dialect::{Dialect, DialectDisplay};
 use crate::expression::expression;
+use crate::literal::literal;
+use crate::order::order_clause;
 use crate::table::Relation;
 use crate::whitespace::{whitespace0, whitespace1};
-use crate::{Expr, FunctionExpr, Literal, NomSqlResult, SqlIdentifier};
+use crate::{Expr, FunctionExpr, Literal, NomSqlResult, SqlIdentifier, WindowSpecification};
 
 #[cfg(feature = "debug")]
 pub fn debug_print(tag: &str, i: &[u8]) {
@@ -435,6 +437,171 @@ fn agg_fx_args(
     }
 }
 
+/// Parses the parenthesized argument to an aggregate function which takes a single argument and
+/// doesn't support `DISTINCT`
+fn single_agg_fx_arg(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Box<Expr>> {
+    move |i| {
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((i, Box::new(expr)))
+    }
+}
+
+/// Parses PostgreSQL's `STRING_AGG(expr, delimiter)` as the equivalent `GROUP_CONCAT`. Only literal
+/// delimiters are supported
+fn string_agg(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("string_agg")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = ws_sep_comma(i)?;
+        let (i, separator) = map_res(move |i| dialect.string_literal()(i), String::from_utf8)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((
+            i,
+            FunctionExpr::GroupConcat {
+                expr: Box::new(expr),
+                separator: Some(separator),
+            },
+        ))
+    }
+}
+
+/// Parses the statistical, boolean, bitwise, and collecting aggregate functions
+fn other_agg_fx(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        // Unqualified `STDDEV` and `VARIANCE` compute the population statistic in MySQL, but the
+        // sample statistic in PostgreSQL
+        let sample_by_default = dialect == Dialect::PostgreSQL;
+
+        alt((
+            map(
+                preceded(tag_no_case("stddev_samp"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::Stddev { expr, sample: true },
+            ),
+            map(
+                preceded(tag_no_case("stddev_pop"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::Stddev {
+                    expr,
+                    sample: false,
+                },
+            ),
+            map(
+                preceded(tag_no_case("stddev"), single_agg_fx_arg(dialect)),
+                move |expr| FunctionExpr::Stddev {
+                    expr,
+                    sample: sample_by_default,
+                },
+            ),
+            map(
+                preceded(tag_no_case("std"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::Stddev {
+                    expr,
+                    sample: false,
+                },
+            ),
+            map(
+                preceded(tag_no_case("var_samp"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::Variance { expr, sample: true },
+            ),
+            map(
+                preceded(tag_no_case("var_pop"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::Variance {
+                    expr,
+                    sample: false,
+                },
+            ),
+            map(
+                preceded(tag_no_case("variance"), single_agg_fx_arg(dialect)),
+                move |expr| FunctionExpr::Variance {
+                    expr,
+                    sample: sample_by_default,
+                },
+            ),
+            map(
+                preceded(
+                    alt((tag_no_case("bool_and"), tag_no_case("every"))),
+                    single_agg_fx_arg(dialect),
+                ),
+                FunctionExpr::BoolAnd,
+            ),
+            map(
+                preceded(tag_no_case("bool_or"), single_agg_fx_arg(dialect)),
+                FunctionExpr::BoolOr,
+            ),
+            map(
+                preceded(tag_no_case("bit_and"), single_agg_fx_arg(dialect)),
+                FunctionExpr::BitAnd,
+            ),
+            map(
+                preceded(tag_no_case("bit_or"), single_agg_fx_arg(dialect)),
+                FunctionExpr::BitOr,
+            ),
+            map(
+                preceded(tag_no_case("array_agg"), single_agg_fx_arg(dialect)),
+                FunctionExpr::ArrayAgg,
+            ),
+            map(
+                preceded(tag_no_case("json_agg"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::JsonAgg { expr, jsonb: false },
+            ),
+            map(
+                preceded(tag_no_case("jsonb_agg"), single_agg_fx_arg(dialect)),
+                |expr| FunctionExpr::JsonAgg { expr, jsonb: true },
+            ),
+            string_agg(dialect),
+        ))(i)
+    }
+}
+
+/// Parses the ReadySet-specific approximate aggregate functions, `APPROX_COUNT_DISTINCT(expr)` and
+/// `APPROX_PERCENTILE(expr, percentile)`. Only literal percentiles are supported
+fn approx_agg_fx(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        alt((
+            map(
+                preceded(
+                    tag_no_case("approx_count_distinct"),
+                    single_agg_fx_arg(dialect),
+                ),
+                FunctionExpr::ApproxCountDistinct,
+            ),
+            map(
+                tuple((
+                    tag_no_case("approx_percentile"),
+                    whitespace0,
+                    tag("("),
+                    whitespace0,
+                    expression(dialect),
+                    ws_sep_comma,
+                    literal(dialect),
+                    whitespace0,
+                    tag(")"),
+                )),
+                |(_, _, _, _, expr, _, percentile, _, _)| FunctionExpr::ApproxPercentile {
+                    expr: Box::new(expr),
+                    percentile,
+                },
+            ),
+        ))(i)
+    }
+}
+
 fn delim_fx_args(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Vec<Expr>> {
@@ -522,8 +689,94 @@ fn function_call(
     }
 }
 
+fn no_arg_window_fx<'a>(
+    name: &'static str,
+    function: FunctionExpr,
+) -> impl Fn(LocatedSpan<&'a [u8]>) -> NomSqlResult<&'a [u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case(name)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((i, function.clone()))
+    }
+}
+
+fn offset_window_fx_args(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (Box<Expr>, Option<u64>)> {
+    move |i| {
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, offset) = opt(preceded(
+            ws_sep_comma,
+            map_res(
+                map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+                u64::from_str,
+            ),
+        ))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        Ok((i, (Box::new(expr), offset)))
+    }
+}
+
+/// Parses the `OVER (...)` clause of a window function call
+fn window_specification(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], WindowSpecification> {
+    move |i| {
+        let (i, _) = tag_no_case("over")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, partition_by) = opt(preceded(
+            tuple((
+                tag_no_case("partition"),
+                whitespace1,
+                tag_no_case("by"),
+                whitespace1,
+            )),
+            separated_list1(ws_sep_comma, expression(dialect)),
+        ))(i)?;
+        let (i, order) = opt(order_clause(dialect))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((
+            i,
+            WindowSpecification {
+                partition_by: partition_by.unwrap_or_default(),
+                order,
+            },
+        ))
+    }
+}
+
 pub fn function_expr(
     dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, function) = function_expr_without_window(dialect)(i)?;
+        let (i, window) = opt(preceded(whitespace0, window_specification(dialect)))(i)?;
+        Ok((
+            i,
+            match window {
+                Some(window) => FunctionExpr::Over {
+                    function: Box::new(function),
+                    window,
+                },
+                None => function,
+            },
+        ))
+    }
+}
+
+fn function_expr_without_window(
+    dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
     move |i| {
         alt((
@@ -581,7 +834,20 @@ pub fn function_expr(
                     separator,
                 },
             ),
+            other_agg_fx(dialect),
+            approx_agg_fx(dialect),
             substring(dialect),
+            no_arg_window_fx("row_number", FunctionExpr::RowNumber),
+            no_arg_window_fx("rank", FunctionExpr::Rank),
+            no_arg_window_fx("dense_rank", FunctionExpr::DenseRank),
+            map(
+                preceded(tag_no_case("lag"), offset_window_fx_args(dialect)),
+                |(expr, offset)| FunctionExpr::Lag { expr, offset },
+            ),
+            map(
+                preceded(tag_no_case("lead"), offset_window_fx_args(dialect)),
+                |(expr, offset)| FunctionExpr::Lead { expr, offset },
+            ),
             function_call(dialect),
             function_call_without_parens,
         ))(i)
@@ -854,7 +1120,7 @@ pub fn field_reference_list(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{to_nom_result, SqlType};
+    use crate::{to_nom_result, Double, OrderBy, OrderClause, OrderType, SqlType};
 
     fn test_opt_delimited_fn_call(i: &str) -> IResult<&[u8], &[u8]> {
         opt_delimited(tag("("), tag("abc"), tag(")"))(i.as_bytes())
@@ -924,6 +1190,143 @@ mod tests {
         );
     }
 
+    #[test]
+    fn statistical_aggregates() {
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"stddev_samp(x)"),
+            FunctionExpr::Stddev {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: true
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"VAR_POP ( x )"),
+            FunctionExpr::Variance {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: false
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"std(x)"),
+            FunctionExpr::Stddev {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: false
+            }
+        );
+        // Unqualified `STDDEV` and `VARIANCE` differ between dialects
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"variance(x)"),
+            FunctionExpr::Variance {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: false
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"variance(x)"),
+            FunctionExpr::Variance {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: true
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"stddev(x)"),
+            FunctionExpr::Stddev {
+                expr: Box::new(Expr::Column("x".into())),
+                sample: true
+            }
+        );
+    }
+
+    #[test]
+    fn boolean_and_bitwise_aggregates() {
+        let x = || Box::new(Expr::Column("x".into()));
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"bool_and(x)"),
+            FunctionExpr::BoolAnd(x())
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"every(x)"),
+            FunctionExpr::BoolAnd(x())
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"bool_or(x)"),
+            FunctionExpr::BoolOr(x())
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"BIT_AND(x)"),
+            FunctionExpr::BitAnd(x())
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"bit_or(x)"),
+            FunctionExpr::BitOr(x())
+        );
+    }
+
+    #[test]
+    fn approximate_aggregates() {
+        let x = || Box::new(Expr::Column("x".into()));
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"APPROX_COUNT_DISTINCT(x)"),
+            FunctionExpr::ApproxCountDistinct(x())
+        );
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"approx_percentile(x, 0.95)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::ApproxPercentile {
+                expr: x(),
+                percentile: Literal::Double(Double {
+                    value: 0.95,
+                    precision: 2
+                })
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "approx_percentile(\"x\", 0.95)"
+        );
+    }
+
+    #[test]
+    fn collecting_aggregates() {
+        let x = || Box::new(Expr::Column("x".into()));
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"array_agg(x)"),
+            FunctionExpr::ArrayAgg(x())
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"json_agg(x)"),
+            FunctionExpr::JsonAgg {
+                expr: x(),
+                jsonb: false
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"jsonb_agg (x)"),
+            FunctionExpr::JsonAgg {
+                expr: x(),
+                jsonb: true
+            }
+        );
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"string_agg(x, ', ')"),
+            FunctionExpr::GroupConcat {
+                expr: x(),
+                separator: Some(", ".to_owned())
+            }
+        );
+        // Functions which merely start with the name of an aggregate are still generic calls
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"array_agg_foo(x)"),
+            FunctionExpr::Call {
+                name: "array_agg_foo".into(),
+                arguments: vec![Expr::Column("x".into())]
+            }
+        );
+    }
+
     #[test]
     fn simple_generic_function() {
         let qlist = [
@@ -957,6 +1360,90 @@ mod tests {
         )
     }
 
+    #[test]
+    fn row_number_over_partition_and_order() {
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"ROW_NUMBER() OVER (PARTITION BY a, b ORDER BY c DESC)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Over {
+                function: Box::new(FunctionExpr::RowNumber),
+                window: WindowSpecification {
+                    partition_by: vec![Expr::Column("a".into()), Expr::Column("b".into())],
+                    order: Some(OrderClause {
+                        order_by: vec![OrderBy {
+                            field: FieldReference::Expr(Expr::Column("c".into())),
+                            order_type: Some(OrderType::OrderDescending),
+                            null_order: None,
+                        }]
+                    }),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn running_sum_over_order() {
+        let res = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"sum(x) over (order by y)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Over {
+                function: Box::new(FunctionExpr::Sum {
+                    expr: Box::new(Expr::Column("x".into())),
+                    distinct: false,
+                }),
+                window: WindowSpecification {
+                    partition_by: vec![],
+                    order: Some(OrderClause {
+                        order_by: vec![OrderBy {
+                            field: FieldReference::Expr(Expr::Column("y".into())),
+                            order_type: None,
+                            null_order: None,
+                        }]
+                    }),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn lag_with_offset() {
+        let res = test_parse!(
+            function_expr(Dialect::MySQL),
+            b"lag(x, 2) over (partition by y)"
+        );
+        assert_eq!(
+            res,
+            FunctionExpr::Over {
+                function: Box::new(FunctionExpr::Lag {
+                    expr: Box::new(Expr::Column("x".into())),
+                    offset: Some(2),
+                }),
+                window: WindowSpecification {
+                    partition_by: vec![Expr::Column("y".into())],
+                    order: None,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn window_function_display() {
+        let expr = test_parse!(
+            function_expr(Dialect::PostgreSQL),
+            b"lead(x) OVER (PARTITION BY y ORDER BY z ASC)"
+        );
+        assert_eq!(
+            expr.display(Dialect::PostgreSQL).to_string(),
+            "lead(\"x\") OVER (PARTITION BY \"y\" ORDER BY \"z\" ASC)"
+        );
+    }
+
     #[test]
     fn nested_cast() {
         let res = test_parse!(function_expr(Dialect::MySQL), b"max(cast(foo as int))");
@@ -16,7 +16,9 @@ use crate::select::{limit_offset_clause, nested_selection, LimitClause, SelectSt
 use crate::whitespace::{whitespace0, whitespace1};
 use crate::{Dialect, DialectDisplay, NomSqlResult, SelectSpecification};
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize, Arbitrary)]
+#[derive(
+    Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Deserialize, Serialize, Arbitrary,
+)]
 pub enum CompoundSelectOperator {
     Union,
     DistinctUnion,
@@ -27,7 +29,7 @@ pub enum CompoundSelectOperator {
 impl fmt::Display for CompoundSelectOperator {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            CompoundSelectOperator::Union => write!(f, "UNION"),
+            CompoundSelectOperator::Union => write!(f, "UNION ALL"),
             CompoundSelectOperator::DistinctUnion => write!(f, "UNION DISTINCT"),
             CompoundSelectOperator::Intersect => write!(f, "INTERSECT"),
             CompoundSelectOperator::Except => write!(f, "EXCEPT"),
@@ -66,7 +68,7 @@ impl DialectDisplay for CompoundSelectStatement {
 }
 
 // Parse compound operator
-fn compound_op(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CompoundSelectOperator> {
+pub(crate) fn compound_op(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CompoundSelectOperator> {
     alt((
         map(
             preceded(
@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::time::Duration;
 use std::{fmt, str};
 
 use derive_more::From;
@@ -205,6 +206,88 @@ impl DialectDisplay for CacheInner {
     }
 }
 
+/// How long the results of a cache may be served for before they must be recomputed, specified
+/// with `WITH TTL <duration>` in a [`CreateCacheStatement`].
+///
+/// Durations are written as an integer followed by an optional unit, one of `ms`, `s`, `m`, or
+/// `h`. If no unit is given, the duration is in seconds.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
+pub enum CacheExpiration {
+    /// `TTL <duration>`: keys are evicted from the cache once they have been cached for longer
+    /// than the given duration, and are recomputed the next time they are read.
+    Ttl(Duration),
+}
+
+impl CacheExpiration {
+    /// Returns the duration after which keys in the cache expire
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Ttl(d) => *d,
+        }
+    }
+}
+
+impl fmt::Display for CacheExpiration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ttl(_) => write!(f, "TTL ")?,
+        }
+        let d = self.duration();
+        if d.subsec_nanos() == 0 {
+            write!(f, "{}s", d.as_secs())
+        } else {
+            write!(f, "{}ms", d.as_millis())
+        }
+    }
+}
+
+/// The priority of a cache relative to other caches when evicting state, specified with
+/// `WITH PRIORITY <priority>` in a [`CreateCacheStatement`].
+///
+/// When memory needs to be freed, state is only evicted from higher-priority caches if
+/// lower-priority caches don't have enough state to satisfy the eviction.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    Arbitrary,
+)]
+pub enum CachePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl fmt::Display for CachePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Low => write!(f, "LOW"),
+            Self::Normal => write!(f, "NORMAL"),
+            Self::High => write!(f, "HIGH"),
+        }
+    }
+}
+
+/// Format a memory limit in bytes using the largest unit that represents it exactly
+fn fmt_memory_limit(f: &mut fmt::Formatter<'_>, bytes: u64) -> fmt::Result {
+    const UNITS: [(&str, u64); 3] = [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10)];
+    for (unit, size) in UNITS {
+        if bytes >= size && bytes % size == 0 {
+            return write!(f, "{}{unit}", bytes / size);
+        }
+    }
+    write!(f, "{bytes}B")
+}
+
 /// Optional `CREATE CACHE` arguments. This struct is only used for parsing.
 #[derive(Default)]
 struct CreateCacheOptions {
@@ -212,7 +295,23 @@ struct CreateCacheOptions {
     concurrently: bool,
 }
 
-/// `CREATE CACHE [CONCURRENTLY] [ALWAYS] [<name>] FROM ...`
+/// Options given in the `WITH` clause of a `CREATE CACHE` statement. This struct is only used for
+/// parsing.
+#[derive(Default)]
+struct CacheWithOptions {
+    expiration: Option<CacheExpiration>,
+    memory_limit: Option<u64>,
+    priority: Option<CachePriority>,
+}
+
+/// `CREATE CACHE [CONCURRENTLY] [ALWAYS] [<name>] [WITH <option> | WITH (<option>, ...)] FROM ...`
+///
+/// where `<option>` is one of:
+///
+/// * `TTL <duration>` (see [`CacheExpiration`])
+/// * `MEMORY LIMIT <size>`, where `<size>` is an integer followed by an optional unit (one of `B`,
+///   `KB`, `MB`, or `GB`)
+/// * `PRIORITY <priority>` (see [`CachePriority`])
 ///
 /// This is a non-standard ReadySet specific extension to SQL
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
@@ -234,6 +333,15 @@ pub struct CreateCacheStatement {
     pub always: bool,
     /// Whether the CREATE CACHE STATEMENT should block or run concurrently
     pub concurrently: bool,
+    /// If set, results in the cache are only valid for a bounded amount of time, after which they
+    /// are evicted or refreshed
+    pub expiration: Option<CacheExpiration>,
+    /// If set, the maximum number of bytes of state the reader for this cache may hold before
+    /// keys are evicted from it
+    pub memory_limit: Option<u64>,
+    /// The priority of this cache relative to other caches when evicting state. If not set, the
+    /// cache has [`CachePriority::Normal`] priority.
+    pub priority: Option<CachePriority>,
 }
 
 impl DialectDisplay for CreateCacheStatement {
@@ -249,6 +357,24 @@ impl DialectDisplay for CreateCacheStatement {
             if let Some(name) = &self.name {
                 write!(f, "{} ", name.display(dialect))?;
             }
+            let mut options = vec![];
+            if let Some(expiration) = &self.expiration {
+                options.push(expiration.to_string());
+            }
+            if let Some(memory_limit) = self.memory_limit {
+                options.push(format!(
+                    "MEMORY LIMIT {}",
+                    fmt_with(move |f| fmt_memory_limit(f, memory_limit))
+                ));
+            }
+            if let Some(priority) = &self.priority {
+                options.push(format!("PRIORITY {priority}"));
+            }
+            match options.as_slice() {
+                [] => {}
+                [option] => write!(f, "WITH {option} ")?,
+                options => write!(f, "WITH ({}) ", options.join(", "))?,
+            }
             write!(f, "FROM ")?;
             match &self.inner {
                 Ok(inner) => write!(f, "{}", inner.display(dialect)),
@@ -810,6 +936,134 @@ fn cached_query_options(mut i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Create
     Ok((i, opts))
 }
 
+/// Parse a duration, as an integer followed by an optional unit (one of `ms`, `s`, `m`, or `h`).
+/// Durations without a unit are in seconds.
+fn cache_expiration_duration(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Duration> {
+    let (i, n) = map_res(
+        map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+        u64::from_str,
+    )(i)?;
+    let (i, unit) = opt(alt((
+        tag_no_case("ms"),
+        tag_no_case("s"),
+        tag_no_case("m"),
+        tag_no_case("h"),
+    )))(i)?;
+    let duration = match unit.map(|u| u.to_ascii_lowercase()).as_deref() {
+        Some(b"ms") => Duration::from_millis(n),
+        Some(b"m") => Duration::from_secs(n.saturating_mul(60)),
+        Some(b"h") => Duration::from_secs(n.saturating_mul(60 * 60)),
+        _ => Duration::from_secs(n),
+    };
+    Ok((i, duration))
+}
+
+/// Parse a `TTL <duration>` cache option
+fn cache_expiration(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CacheExpiration> {
+    map(
+        preceded(
+            tuple((tag_no_case("ttl"), whitespace1)),
+            cache_expiration_duration,
+        ),
+        CacheExpiration::Ttl,
+    )(i)
+}
+
+/// Parse a memory size in bytes, as an integer followed by an optional unit (one of `B`, `KB`,
+/// `MB`, or `GB`)
+fn cache_memory_size(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], u64> {
+    let (i, n) = map_res(
+        map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+        u64::from_str,
+    )(i)?;
+    let (i, multiplier) = opt(preceded(
+        whitespace0,
+        alt((
+            map(tag_no_case("kb"), |_| 1u64 << 10),
+            map(tag_no_case("mb"), |_| 1 << 20),
+            map(tag_no_case("gb"), |_| 1 << 30),
+            map(tag_no_case("b"), |_| 1),
+        )),
+    ))(i)?;
+    Ok((i, n.saturating_mul(multiplier.unwrap_or(1))))
+}
+
+/// Parse a [`CachePriority`]
+fn cache_priority(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CachePriority> {
+    alt((
+        map(tag_no_case("low"), |_| CachePriority::Low),
+        map(tag_no_case("normal"), |_| CachePriority::Normal),
+        map(tag_no_case("high"), |_| CachePriority::High),
+    ))(i)
+}
+
+/// Parse the `WITH <option>` or `WITH (<option>, ...)` clause of a `CREATE CACHE` statement
+fn cache_with_options(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CacheWithOptions> {
+    // A single option in the WITH clause
+    enum WithOption {
+        Expiration(CacheExpiration),
+        MemoryLimit(u64),
+        Priority(CachePriority),
+    }
+
+    fn option(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], WithOption> {
+        alt((
+            map(cache_expiration, WithOption::Expiration),
+            map(
+                preceded(
+                    tuple((
+                        tag_no_case("memory"),
+                        whitespace1,
+                        tag_no_case("limit"),
+                        whitespace1,
+                    )),
+                    cache_memory_size,
+                ),
+                WithOption::MemoryLimit,
+            ),
+            map(
+                preceded(
+                    tuple((tag_no_case("priority"), whitespace1)),
+                    cache_priority,
+                ),
+                WithOption::Priority,
+            ),
+        ))(i)
+    }
+
+    let start = i;
+    let (i, _) = tag_no_case("with")(i)?;
+    let (i, _) = whitespace1(i)?;
+    let (i, options) = alt((
+        terminated(
+            delimited(
+                terminated(tag("("), whitespace0),
+                separated_list1(ws_sep_comma, option),
+                preceded(whitespace0, tag(")")),
+            ),
+            whitespace0,
+        ),
+        map(terminated(option, whitespace1), |opt| vec![opt]),
+    ))(i)?;
+
+    let mut opts = CacheWithOptions::default();
+    for opt in options {
+        // Error if the same option appears twice.
+        let duplicate = match opt {
+            WithOption::Expiration(e) => opts.expiration.replace(e).is_some(),
+            WithOption::MemoryLimit(l) => opts.memory_limit.replace(l).is_some(),
+            WithOption::Priority(p) => opts.priority.replace(p).is_some(),
+        };
+        if duplicate {
+            return Err(nom::Err::Failure(NomSqlError::from_error_kind(
+                start,
+                ErrorKind::Permutation,
+            )));
+        }
+    }
+    Ok((i, opts))
+}
+
 /// Extract the [`SelectStatement`] or Query ID from a CREATE CACHE statement. Query ID is
 /// parsed as a SqlIdentifier
 pub fn cached_query_inner(
@@ -835,6 +1089,8 @@ pub fn create_cached_query(
         let (i, _) = whitespace1(i)?;
         let (i, opts) = cached_query_options(i)?;
         let (i, name) = opt(terminated(relation(dialect), whitespace1))(i)?;
+        let (i, with_opts) = opt(cache_with_options)(i)?;
+        let with_opts = with_opts.unwrap_or_default();
         let (i, _) = tag_no_case("from")(i)?;
         let (i, _) = whitespace1(i)?;
         let (i, inner) =
@@ -847,6 +1103,9 @@ pub fn create_cached_query(
                 unparsed_create_cache_statement,
                 always: opts.always,
                 concurrently: opts.concurrently,
+                expiration: with_opts.expiration,
+                memory_limit: with_opts.memory_limit,
+                priority: with_opts.priority,
             },
         ))
     }
@@ -1668,6 +1927,120 @@ mod tests {
             );
         }
 
+        #[test]
+        fn create_cached_query_with_ttl() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo WITH TTL 30s FROM SELECT id FROM users WHERE name = ?"
+            );
+            assert_eq!(res.name, Some("foo".into()));
+            assert_eq!(
+                res.expiration,
+                Some(CacheExpiration::Ttl(Duration::from_secs(30)))
+            );
+            assert!(matches!(res.inner, Ok(CacheInner::Statement(_))));
+        }
+
+        #[test]
+        fn create_cached_query_with_max_staleness_is_rejected() {
+            let res = create_cached_query(Dialect::MySQL)(LocatedSpan::new(
+                b"CREATE CACHE ALWAYS WITH MAX STALENESS 500ms FROM q_0123456789ABCDEF",
+            ));
+            assert!(res.is_err());
+        }
+
+        #[test]
+        fn cache_expiration_units() {
+            for (input, expected) in [
+                ("TTL 10", Duration::from_secs(10)),
+                ("TTL 10s", Duration::from_secs(10)),
+                ("TTL 10ms", Duration::from_millis(10)),
+                ("TTL 10m", Duration::from_secs(600)),
+                ("ttl 2H", Duration::from_secs(7200)),
+            ] {
+                assert_eq!(
+                    test_parse!(cache_expiration, input.as_bytes()),
+                    CacheExpiration::Ttl(expected)
+                );
+            }
+        }
+
+        #[test]
+        fn create_cached_query_with_memory_options() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo WITH (MEMORY LIMIT 512MB, PRIORITY high) FROM SELECT id FROM users"
+            );
+            assert_eq!(res.name, Some("foo".into()));
+            assert_eq!(res.memory_limit, Some(512 << 20));
+            assert_eq!(res.priority, Some(CachePriority::High));
+            assert_eq!(res.expiration, None);
+
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE WITH (TTL 10s,MEMORY LIMIT 100, PRIORITY LOW)FROM q_0123456789ABCDEF"
+            );
+            assert_eq!(
+                res.expiration,
+                Some(CacheExpiration::Ttl(Duration::from_secs(10)))
+            );
+            assert_eq!(res.memory_limit, Some(100));
+            assert_eq!(res.priority, Some(CachePriority::Low));
+        }
+
+        #[test]
+        fn create_cached_query_with_duplicate_options() {
+            let res = create_cached_query(Dialect::MySQL)(LocatedSpan::new(
+                b"CREATE CACHE WITH (PRIORITY LOW, PRIORITY HIGH) FROM SELECT id FROM users"
+                    .as_slice(),
+            ));
+            assert!(res.is_err());
+        }
+
+        #[test]
+        fn cache_memory_size_units() {
+            for (input, expected) in [
+                ("100", 100),
+                ("100B", 100),
+                ("4 KB", 4 << 10),
+                ("512mb", 512 << 20),
+                ("2GB", 2 << 30),
+            ] {
+                assert_eq!(test_parse!(cache_memory_size, input.as_bytes()), expected);
+            }
+        }
+
+        #[test]
+        fn display_create_query_cache_with_options() {
+            for (input, expected) in [
+                (
+                    "CREATE CACHE foo WITH TTL 1m FROM SELECT id FROM users",
+                    "CREATE CACHE `foo` WITH TTL 60s FROM SELECT `id` FROM `users`",
+                ),
+                (
+                    "CREATE CACHE WITH TTL 1500ms FROM SELECT id FROM users",
+                    "CREATE CACHE WITH TTL 1500ms FROM SELECT `id` FROM `users`",
+                ),
+                (
+                    "CREATE CACHE WITH (PRIORITY HIGH, MEMORY LIMIT 1024 KB) FROM SELECT id FROM users",
+                    "CREATE CACHE WITH (MEMORY LIMIT 1MB, PRIORITY HIGH) FROM SELECT `id` FROM `users`",
+                ),
+                (
+                    "CREATE CACHE WITH (TTL 5s, MEMORY LIMIT 1000) FROM SELECT id FROM users",
+                    "CREATE CACHE WITH (TTL 5s, MEMORY LIMIT 1000B) FROM SELECT `id` FROM `users`",
+                ),
+            ] {
+                let stmt = test_parse!(create_cached_query(Dialect::MySQL), input.as_bytes());
+                let displayed = stmt.display(Dialect::MySQL).to_string();
+                assert_eq!(displayed, expected);
+                let reparsed =
+                    test_parse!(create_cached_query(Dialect::MySQL), displayed.as_bytes());
+                assert_eq!(reparsed.expiration, stmt.expiration);
+                assert_eq!(reparsed.memory_limit, stmt.memory_limit);
+                assert_eq!(reparsed.priority, stmt.priority);
+            }
+        }
+
         #[test]
         fn lobsters_indexes() {
             let qstring = "CREATE TABLE `comments` (
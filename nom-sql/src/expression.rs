@@ -26,7 +26,8 @@ use crate::set::{variable_scope_prefix, Variable};
 use crate::sql_type::{mysql_int_cast_targets, type_identifier};
 use crate::whitespace::{whitespace0, whitespace1};
 use crate::{
-    Column, Dialect, DialectDisplay, Literal, NomSqlResult, SelectStatement, SqlIdentifier, SqlType,
+    Column, Dialect, DialectDisplay, FieldReference, Literal, NomSqlResult, OrderClause,
+    SelectStatement, SqlIdentifier, SqlType,
 };
 
 /// Function call expressions
@@ -50,12 +51,49 @@ pub enum FunctionExpr {
     /// `MIN` aggregation
     Min(Box<Expr>),
 
-    /// `GROUP_CONCAT` aggregation
+    /// `GROUP_CONCAT` aggregation, or PostgreSQL's `STRING_AGG`
     GroupConcat {
         expr: Box<Expr>,
         separator: Option<String>,
     },
 
+    /// `STDDEV_POP` or `STDDEV_SAMP` aggregation (or one of their aliases). The boolean argument
+    /// is `true` for the sample standard deviation
+    Stddev { expr: Box<Expr>, sample: bool },
+
+    /// `VAR_POP` or `VAR_SAMP` aggregation (or one of their aliases). The boolean argument is
+    /// `true` for the sample variance
+    Variance { expr: Box<Expr>, sample: bool },
+
+    /// `BOOL_AND` (or `EVERY`) aggregation
+    BoolAnd(Box<Expr>),
+
+    /// `BOOL_OR` aggregation
+    BoolOr(Box<Expr>),
+
+    /// `BIT_AND` aggregation
+    BitAnd(Box<Expr>),
+
+    /// `BIT_OR` aggregation
+    BitOr(Box<Expr>),
+
+    /// `ARRAY_AGG` aggregation
+    ArrayAgg(Box<Expr>),
+
+    /// `JSON_AGG` aggregation, or `JSONB_AGG` if `jsonb` is `true`
+    JsonAgg { expr: Box<Expr>, jsonb: bool },
+
+    /// ReadySet-specific `APPROX_COUNT_DISTINCT` aggregation, which estimates the number of
+    /// distinct values using a HyperLogLog sketch
+    ApproxCountDistinct(Box<Expr>),
+
+    /// ReadySet-specific `APPROX_PERCENTILE` aggregation, which estimates the value at the given
+    /// percentile (a literal fraction between 0 and 1) using a quantile sketch
+    ApproxPercentile {
+        expr: Box<Expr>,
+        percentile: Literal,
+    },
+
     /// The SQL `SUBSTRING`/`SUBSTR` function.
     ///
     /// The supported syntax is one of:
@@ -76,6 +114,99 @@ pub enum FunctionExpr {
         name: SqlIdentifier,
         arguments: Vec<Expr>,
     },
+
+    /// The `ROW_NUMBER()` window function
+    RowNumber,
+
+    /// The `RANK()` window function
+    Rank,
+
+    /// The `DENSE_RANK()` window function
+    DenseRank,
+
+    /// The `LAG(expr [, offset])` window function
+    Lag {
+        expr: Box<Expr>,
+        offset: Option<u64>,
+    },
+
+    /// The `LEAD(expr [, offset])` window function
+    Lead {
+        expr: Box<Expr>,
+        offset: Option<u64>,
+    },
+
+    /// A function evaluated over a window of rows, eg:
+    ///
+    /// `ROW_NUMBER() OVER (PARTITION BY x ORDER BY y DESC)`
+    #[weight(0)]
+    Over {
+        function: Box<FunctionExpr>,
+        window: WindowSpecification,
+    },
+}
+
+/// The window specification given in the `OVER` clause of a window function call
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize, Arbitrary)]
+pub struct WindowSpecification {
+    /// The expressions in the `PARTITION BY` clause of the window, if any
+    pub partition_by: Vec<Expr>,
+    /// The `ORDER BY` clause of the window, if any
+    pub order: Option<OrderClause>,
+}
+
+impl WindowSpecification {
+    /// Returns an iterator over all the expressions referenced by this window specification
+    pub fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.partition_by.iter().chain(
+            self.order
+                .iter()
+                .flat_map(|oc| oc.order_by.iter())
+                .filter_map(|ob| match &ob.field {
+                    FieldReference::Expr(expr) => Some(expr),
+                    FieldReference::Numeric(_) => None,
+                }),
+        )
+    }
+
+    /// Returns an iterator over mutable references to all the expressions referenced by this
+    /// window specification
+    pub fn exprs_mut(&mut self) -> impl Iterator<Item = &mut Expr> {
+        self.partition_by.iter_mut().chain(
+            self.order
+                .iter_mut()
+                .flat_map(|oc| oc.order_by.iter_mut())
+                .filter_map(|ob| match &mut ob.field {
+                    FieldReference::Expr(expr) => Some(expr),
+                    FieldReference::Numeric(_) => None,
+                }),
+        )
+    }
+}
+
+impl DialectDisplay for WindowSpecification {
+    fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
+        fmt_with(move |f| {
+            write!(f, "(")?;
+            if !self.partition_by.is_empty() {
+                write!(
+                    f,
+                    "PARTITION BY {}",
+                    self.partition_by
+                        .iter()
+                        .map(|expr| expr.display(dialect))
+                        .join(", ")
+                )?;
+                if self.order.is_some() {
+                    write!(f, " ")?;
+                }
+            }
+            if let Some(order) = &self.order {
+                write!(f, "{}", order.display(dialect))?;
+            }
+            write!(f, ")")
+        })
+    }
 }
 
 impl FunctionExpr {
@@ -89,16 +220,36 @@ impl FunctionExpr {
             | FunctionExpr::Sum { expr: arg, .. }
             | FunctionExpr::Max(arg)
             | FunctionExpr::Min(arg)
-            | FunctionExpr::GroupConcat { expr: arg, .. } => {
+            | FunctionExpr::GroupConcat { expr: arg, .. }
+            | FunctionExpr::Stddev { expr: arg, .. }
+            | FunctionExpr::Variance { expr: arg, .. }
+            | FunctionExpr::BoolAnd(arg)
+            | FunctionExpr::BoolOr(arg)
+            | FunctionExpr::BitAnd(arg)
+            | FunctionExpr::BitOr(arg)
+            | FunctionExpr::ArrayAgg(arg)
+            | FunctionExpr::JsonAgg { expr: arg, .. }
+            | FunctionExpr::ApproxCountDistinct(arg)
+            | FunctionExpr::ApproxPercentile { expr: arg, .. }
+            | FunctionExpr::Lag { expr: arg, .. }
+            | FunctionExpr::Lead { expr: arg, .. } => {
                 concrete_iter!(iter::once(arg.as_ref()))
             }
-            FunctionExpr::CountStar => concrete_iter!(iter::empty()),
+            FunctionExpr::CountStar
+            | FunctionExpr::RowNumber
+            | FunctionExpr::Rank
+            | FunctionExpr::DenseRank => concrete_iter!(iter::empty()),
             FunctionExpr::Call { arguments, .. } => concrete_iter!(arguments),
             FunctionExpr::Substring { string, pos, len } => {
                 concrete_iter!(iter::once(string.as_ref())
                     .chain(pos.iter().map(|p| p.as_ref()))
                     .chain(len.iter().map(|p| p.as_ref())))
             }
+            FunctionExpr::Over { function, window } => {
+                // Boxed, since the iterator type for the inner function is this iterator type
+                concrete_iter!(Box::new(function.arguments().chain(window.exprs()))
+                    as Box<dyn Iterator<Item = &'a Expr> + 'a>)
+            }
         }
     }
 }
@@ -124,6 +275,17 @@ impl DialectDisplay for FunctionExpr {
             FunctionExpr::Sum { expr, .. } => write!(f, "sum({})", expr.display(dialect)),
             FunctionExpr::Max(col) => write!(f, "max({})", col.display(dialect)),
             FunctionExpr::Min(col) => write!(f, "min({})", col.display(dialect)),
+            // `GROUP_CONCAT` without a separator has no exact equivalent in PostgreSQL, so it's
+            // displayed as-is
+            FunctionExpr::GroupConcat {
+                expr,
+                separator: Some(separator),
+            } if dialect == Dialect::PostgreSQL => write!(
+                f,
+                "string_agg({}, '{}')",
+                expr.display(dialect),
+                separator.replace('\'', "''").replace('\\', "\\\\")
+            ),
             FunctionExpr::GroupConcat { expr, separator } => {
                 write!(f, "group_concat({}", expr.display(dialect),)?;
                 if let Some(separator) = separator {
@@ -135,6 +297,38 @@ impl DialectDisplay for FunctionExpr {
                 }
                 write!(f, ")")
             }
+            FunctionExpr::Stddev { expr, sample } => write!(
+                f,
+                "stddev_{}({})",
+                if *sample { "samp" } else { "pop" },
+                expr.display(dialect)
+            ),
+            FunctionExpr::Variance { expr, sample } => write!(
+                f,
+                "var_{}({})",
+                if *sample { "samp" } else { "pop" },
+                expr.display(dialect)
+            ),
+            FunctionExpr::BoolAnd(expr) => write!(f, "bool_and({})", expr.display(dialect)),
+            FunctionExpr::BoolOr(expr) => write!(f, "bool_or({})", expr.display(dialect)),
+            FunctionExpr::BitAnd(expr) => write!(f, "bit_and({})", expr.display(dialect)),
+            FunctionExpr::BitOr(expr) => write!(f, "bit_or({})", expr.display(dialect)),
+            FunctionExpr::ArrayAgg(expr) => write!(f, "array_agg({})", expr.display(dialect)),
+            FunctionExpr::JsonAgg { expr, jsonb } => write!(
+                f,
+                "{}_agg({})",
+                if *jsonb { "jsonb" } else { "json" },
+                expr.display(dialect)
+            ),
+            FunctionExpr::ApproxCountDistinct(expr) => {
+                write!(f, "approx_count_distinct({})", expr.display(dialect))
+            }
+            FunctionExpr::ApproxPercentile { expr, percentile } => write!(
+                f,
+                "approx_percentile({}, {})",
+                expr.display(dialect),
+                percentile.display(dialect)
+            ),
             FunctionExpr::Call { name, arguments } => {
                 write!(
                     f,
@@ -156,6 +350,29 @@ impl DialectDisplay for FunctionExpr {
 
                 write!(f, ")")
             }
+            FunctionExpr::RowNumber => write!(f, "row_number()"),
+            FunctionExpr::Rank => write!(f, "rank()"),
+            FunctionExpr::DenseRank => write!(f, "dense_rank()"),
+            FunctionExpr::Lag { expr, offset } | FunctionExpr::Lead { expr, offset } => {
+                let name = if matches!(self, FunctionExpr::Lag { .. }) {
+                    "lag"
+                } else {
+                    "lead"
+                };
+                write!(f, "{name}({}", expr.display(dialect))?;
+                if let Some(offset) = offset {
+                    write!(f, ", {offset}")?;
+                }
+                write!(f, ")")
+            }
+            FunctionExpr::Over { function, window } => {
+                write!(
+                    f,
+                    "{} OVER {}",
+                    function.display(dialect),
+                    window.display(dialect)
+                )
+            }
         })
     }
 }
@@ -2475,6 +2692,28 @@ mod tests {
             )
         }
 
+        #[test]
+        fn display_string_agg() {
+            assert_eq!(
+                FunctionExpr::GroupConcat {
+                    expr: Box::new(Expr::Column("x".into())),
+                    separator: Some(", ".into())
+                }
+                .display(Dialect::PostgreSQL)
+                .to_string(),
+                "string_agg(\"x\", ', ')"
+            );
+            assert_eq!(
+                FunctionExpr::GroupConcat {
+                    expr: Box::new(Expr::Column("x".into())),
+                    separator: Some("'".into())
+                }
+                .display(Dialect::PostgreSQL)
+                .to_string(),
+                "string_agg(\"x\", '''')"
+            );
+        }
+
         mod precedence {
             use super::tests::precedence::parses_same;
             use crate::Dialect;
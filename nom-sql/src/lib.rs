@@ -21,8 +21,8 @@ pub use self::comment::CommentStatement;
 pub use self::common::{FieldDefinitionExpr, FieldReference, IndexType, TableKey};
 pub use self::compound_select::{CompoundSelectOperator, CompoundSelectStatement};
 pub use self::create::{
-    CacheInner, CreateCacheStatement, CreateTableBody, CreateTableStatement, CreateViewStatement,
-    SelectSpecification,
+    CacheExpiration, CacheInner, CachePriority, CreateCacheStatement, CreateTableBody,
+    CreateTableStatement, CreateViewStatement, SelectSpecification,
 };
 pub use self::create_table_options::CreateTableOption;
 pub use self::delete::DeleteStatement;
@@ -32,7 +32,7 @@ pub use self::drop::{
 };
 pub use self::explain::ExplainStatement;
 pub use self::expression::{
-    BinaryOperator, CaseWhenBranch, Expr, FunctionExpr, InValue, UnaryOperator,
+    BinaryOperator, CaseWhenBranch, Expr, FunctionExpr, InValue, UnaryOperator, WindowSpecification,
 };
 pub use self::insert::InsertStatement;
 pub use self::join::{JoinConstraint, JoinOperator, JoinRightSide};
@@ -40,7 +40,7 @@ pub use self::literal::{
     embedded_literal, literal, raw_string_literal, utf8_string_literal, Double, Float,
     ItemPlaceholder, Literal, QuotingStyle,
 };
-pub use self::order::{OrderBy, OrderClause, OrderType};
+pub use self::order::{NullOrder, OrderBy, OrderClause, OrderType};
 pub use self::parser::*;
 pub use self::select::{
     CommonTableExpr, GroupByClause, JoinClause, LimitClause, LimitValue, SelectStatement,
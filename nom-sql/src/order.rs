@@ -69,6 +69,33 @@ impl NullOrder {
             OrderType::OrderAscending => Self::NullsLast,
         }
     }
+
+    /// Returns the null order which places nulls at the opposite end of the result set
+    #[inline(always)]
+    pub fn reverse(self) -> Self {
+        match self {
+            NullOrder::NullsFirst => NullOrder::NullsLast,
+            NullOrder::NullsLast => NullOrder::NullsFirst,
+        }
+    }
+
+    /// Apply this [`NullOrder`] to the provided [`Ordering`] between two values, given whether
+    /// each of those values is null.
+    ///
+    /// If exactly one of the values is null, the resulting ordering places that value first or
+    /// last according to this null order - otherwise, `ord` is returned unchanged.
+    #[inline(always)]
+    pub fn apply(self, a_is_null: bool, b_is_null: bool, ord: Ordering) -> Ordering {
+        match (a_is_null, b_is_null, self) {
+            (true, false, NullOrder::NullsFirst) | (false, true, NullOrder::NullsLast) => {
+                Ordering::Less
+            }
+            (true, false, NullOrder::NullsLast) | (false, true, NullOrder::NullsFirst) => {
+                Ordering::Greater
+            }
+            _ => ord,
+        }
+    }
 }
 
 impl Display for NullOrder {
@@ -94,6 +121,9 @@ impl OrderBy {
             if let Some(ot) = self.order_type {
                 write!(f, " {}", ot)?;
             }
+            if let Some(no) = self.null_order {
+                write!(f, " {}", no)?;
+            }
 
             Ok(())
         })
@@ -270,5 +300,29 @@ mod tests {
                 "ORDER BY \"t\".\"n\" DESC"
             );
         }
+
+        #[test]
+        fn order_prints_null_order() {
+            let clause = test_parse!(
+                super::super::order_clause(Dialect::PostgreSQL),
+                b"ORDER BY x DESC NULLS LAST, y NULLS FIRST"
+            );
+            assert_eq!(
+                clause.display(Dialect::PostgreSQL).to_string(),
+                "ORDER BY \"x\" DESC NULLS LAST, \"y\" NULLS FIRST"
+            );
+        }
+    }
+
+    #[test]
+    fn apply_null_order() {
+        use Ordering::*;
+
+        assert_eq!(NullOrder::NullsFirst.apply(true, false, Greater), Less);
+        assert_eq!(NullOrder::NullsFirst.apply(false, true, Less), Greater);
+        assert_eq!(NullOrder::NullsLast.apply(true, false, Less), Greater);
+        assert_eq!(NullOrder::NullsLast.apply(false, true, Greater), Less);
+        assert_eq!(NullOrder::NullsLast.apply(true, true, Equal), Equal);
+        assert_eq!(NullOrder::NullsFirst.apply(false, false, Greater), Greater);
     }
 }
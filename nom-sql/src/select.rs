@@ -20,6 +20,7 @@ use crate::common::{
     field_definition_expr, field_list, field_reference_list, terminated_with_statement_terminator,
     ws_sep_comma, FieldDefinitionExpr,
 };
+use crate::compound_select::{compound_op, CompoundSelectOperator};
 use crate::dialect::CommaSeparatedList;
 use crate::expression::expression;
 use crate::join::{join_operator, JoinConstraint, JoinOperator, JoinRightSide};
@@ -80,6 +81,17 @@ impl DialectDisplay for JoinClause {
 pub struct CommonTableExpr {
     pub name: SqlIdentifier,
     pub statement: SelectStatement,
+    /// For common table expressions in a `WITH RECURSIVE` clause, the recursive term of the CTE
+    /// (which may refer to the CTE itself), along with the operator used to combine it with the
+    /// non-recursive term in `statement`
+    pub recursive_term: Option<(CompoundSelectOperator, Box<SelectStatement>)>,
+}
+
+impl CommonTableExpr {
+    /// Returns true if this is a recursive common table expression
+    pub fn is_recursive(&self) -> bool {
+        self.recursive_term.is_some()
+    }
 }
 
 impl DialectDisplay for CommonTableExpr {
@@ -87,10 +99,14 @@ impl DialectDisplay for CommonTableExpr {
         fmt_with(move |f| {
             write!(
                 f,
-                "{} AS ({})",
+                "{} AS ({}",
                 dialect.quote_identifier(&self.name),
                 self.statement.display(dialect)
-            )
+            )?;
+            if let Some((op, recursive_term)) = &self.recursive_term {
+                write!(f, " {} {}", op, recursive_term.display(dialect))?;
+            }
+            write!(f, ")")
         })
     }
 }
@@ -225,6 +241,15 @@ impl LimitClause {
         }
     }
 
+    /// Returns true if this [`LimitClause`] can't be evaluated as part of a cached query, and must
+    /// instead be applied to the (ordered) results of each lookup once the values of its
+    /// parameters are known - either because the `limit` is a placeholder, or because there's an
+    /// `offset` without a `limit`.
+    pub fn requires_lookup_time_pagination(&self) -> bool {
+        matches!(self.limit(), Some(Literal::Placeholder(_)))
+            || (self.limit().is_none() && self.offset().is_some())
+    }
+
     /// Whether this [`LimitClause`] is empty (has no `limit` and no `offset`) or not.
     pub fn is_empty(&self) -> bool {
         match self {
@@ -302,6 +327,16 @@ impl SelectStatement {
                         | FunctionExpr::Max(_)
                         | FunctionExpr::Min(_)
                         | FunctionExpr::GroupConcat { .. }
+                        | FunctionExpr::Stddev { .. }
+                        | FunctionExpr::Variance { .. }
+                        | FunctionExpr::BoolAnd(_)
+                        | FunctionExpr::BoolOr(_)
+                        | FunctionExpr::BitAnd(_)
+                        | FunctionExpr::BitOr(_)
+                        | FunctionExpr::ArrayAgg(_)
+                        | FunctionExpr::JsonAgg { .. }
+                        | FunctionExpr::ApproxCountDistinct(_)
+                        | FunctionExpr::ApproxPercentile { .. }
                 ),
                 Expr::NestedSelect(select) => select.contains_aggregate_select(),
                 _ => false,
@@ -315,9 +350,13 @@ impl DialectDisplay for SelectStatement {
     fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
         fmt_with(move |f| {
             if !self.ctes.is_empty() {
+                write!(f, "WITH ")?;
+                if self.ctes.iter().any(CommonTableExpr::is_recursive) {
+                    write!(f, "RECURSIVE ")?;
+                }
                 write!(
                     f,
-                    "WITH {} ",
+                    "{} ",
                     CommaSeparatedList::from(&self.ctes).display(dialect)
                 )?;
             }
@@ -583,7 +622,24 @@ fn from_clause(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<
     }
 }
 
-fn cte(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CommonTableExpr> {
+fn recursive_term(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], (CompoundSelectOperator, Box<SelectStatement>)>
+{
+    move |i| {
+        let (i, _) = whitespace0(i)?;
+        let (i, op) = compound_op(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, statement) = nested_selection(dialect)(i)?;
+
+        Ok((i, (op, Box::new(statement))))
+    }
+}
+
+fn cte(
+    dialect: Dialect,
+    recursive: bool,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CommonTableExpr> {
     move |i| {
         let (i, name) = dialect.identifier()(i)?;
         let (i, _) = whitespace1(i)?;
@@ -593,10 +649,22 @@ fn cte(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], C
         let (i, _) = tag("(")(i)?;
         let (i, _) = whitespace0(i)?;
         let (i, statement) = nested_selection(dialect)(i)?;
+        let (i, recursive_term) = if recursive {
+            opt(recursive_term(dialect))(i)?
+        } else {
+            (i, None)
+        };
         let (i, _) = whitespace0(i)?;
         let (i, _) = tag(")")(i)?;
 
-        Ok((i, CommonTableExpr { name, statement }))
+        Ok((
+            i,
+            CommonTableExpr {
+                name,
+                statement,
+                recursive_term,
+            },
+        ))
     }
 }
 
@@ -606,7 +674,8 @@ fn ctes(
     move |i| {
         let (i, _) = tag_no_case("with")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, ctes) = separated_list1(ws_sep_comma, cte(dialect))(i)?;
+        let (i, recursive) = opt(terminated(tag_no_case("recursive"), whitespace1))(i)?;
+        let (i, ctes) = separated_list1(ws_sep_comma, cte(dialect, recursive.is_some()))(i)?;
         let (i, _) = whitespace0(i)?;
 
         Ok((i, ctes))
@@ -956,6 +1025,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn limit_clause_requires_lookup_time_pagination() {
+        let requires = |q: &str| {
+            test_parse!(selection(Dialect::PostgreSQL), q.as_bytes())
+                .limit_clause
+                .requires_lookup_time_pagination()
+        };
+
+        assert!(!requires("select * from users"));
+        assert!(!requires("select * from users limit 10"));
+        assert!(!requires("select * from users limit 10 offset $1"));
+        assert!(!requires("select * from users limit all"));
+        assert!(requires("select * from users limit $1"));
+        assert!(requires("select * from users limit $1 offset $2"));
+        assert!(requires("select * from users limit $1 offset 10"));
+        assert!(requires("select * from users offset 10"));
+        assert!(requires("select * from users limit all offset $1"));
+    }
+
     #[test]
     fn table_alias() {
         let qstring1 = "select * from PaperTag as t;";
@@ -1878,6 +1966,39 @@ mod tests {
         assert_eq!(query.ctes[1].name, "min_val");
     }
 
+    #[test]
+    fn recursive_cte() {
+        let qstr = b"WITH RECURSIVE tree AS (
+              SELECT id, parent_id FROM nodes WHERE id = 1
+              UNION SELECT nodes.id, nodes.parent_id FROM nodes JOIN tree ON nodes.parent_id = tree.id
+            )
+            SELECT id FROM tree";
+        let res = selection(Dialect::MySQL)(LocatedSpan::new(qstr));
+        assert!(res.is_ok(), "error parsing query: {}", res.err().unwrap());
+        let (rem, query) = res.unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(query.ctes.len(), 1);
+        let cte = query.ctes.first().unwrap();
+        assert_eq!(cte.name, "tree");
+        assert!(cte.statement.join.is_empty());
+        let (op, recursive_term) = cte.recursive_term.as_ref().unwrap();
+        assert_eq!(*op, CompoundSelectOperator::DistinctUnion);
+        assert_eq!(recursive_term.join.len(), 1);
+    }
+
+    #[test]
+    fn recursive_cte_round_trip() {
+        for dialect in [Dialect::MySQL, Dialect::PostgreSQL] {
+            let qstr = "WITH RECURSIVE tree AS (SELECT id FROM nodes WHERE id = 1 UNION ALL \
+                        SELECT nodes.id FROM nodes JOIN tree ON nodes.parent_id = tree.id) \
+                        SELECT id FROM tree";
+            let query = test_parse!(selection(dialect), qstr.as_bytes());
+            let displayed = query.display(dialect).to_string();
+            assert!(displayed.starts_with("WITH RECURSIVE "), "{displayed}");
+            assert_eq!(test_parse!(selection(dialect), displayed.as_bytes()), query);
+        }
+    }
+
     mod mysql {
         use super::*;
         use crate::column::Column;
@@ -2026,6 +2147,7 @@ mod tests {
                         tables: vec![TableExpr::from(Relation::from("t"))],
                         ..Default::default()
                     },
+                    recursive_term: None,
                 }],
                 fields: vec![FieldDefinitionExpr::Expr {
                     expr: Expr::Column("x".into()),
@@ -2234,6 +2356,7 @@ mod tests {
                         tables: vec![TableExpr::from(Relation::from("t"))],
                         ..Default::default()
                     },
+                    recursive_term: None,
                 }],
                 fields: vec![FieldDefinitionExpr::Expr {
                     expr: Expr::Column("x".into()),
@@ -253,8 +253,8 @@ pub enum NotReplicatedReason {
     /// TableDropped indicates that the table that was snapshotted has been dropped
     /// and because of this it is no longer being replicated.
     TableDropped,
-    /// Partitioned indicates that the table is a partitioned table which are not
-    /// supported by ReadySet.
+    /// Partitioned indicates that the table is a partitioned table, which can only be replicated
+    /// if the upstream database publishes changes to partitions via their partition root.
     Partitioned,
     /// UnsupportedType indicates that a column type in the table is not supported.
     /// This will only reference the first unsupported type. If there are more than
@@ -265,6 +265,9 @@ pub enum NotReplicatedReason {
     OtherError(String),
     /// Default is a generic and is used when one of the above enums are not need.
     Default,
+    /// Partition indicates that the table is a partition of a partitioned table, which is
+    /// replicated as part of its partition root instead.
+    Partition,
 }
 
 impl NotReplicatedReason {
@@ -272,7 +275,7 @@ impl NotReplicatedReason {
         match self {
                 NotReplicatedReason::Configuration => "The table was either excluded from replicated-tables or included in replication-tables-ignore option.".to_string(),
                 NotReplicatedReason::TableDropped => "Table has been dropped.".to_string(),
-                NotReplicatedReason::Partitioned => "Partitioned tables are not supported.".to_string(),
+                NotReplicatedReason::Partitioned => "Partitioned tables are only supported if changes to partitions are published via the partition root.".to_string(),
                 NotReplicatedReason::UnsupportedType(reason) => {
                     let prefix = "Unsupported type:";
                     if let Some(start) = reason.find(prefix) {
@@ -286,6 +289,7 @@ impl NotReplicatedReason {
                 },
                 NotReplicatedReason::OtherError(error) => format!("An unexpected replication error occurred: {}", error),
                 NotReplicatedReason::Default => "No specific reason provided.".to_string(),
+                NotReplicatedReason::Partition => "The table is a partition, and is replicated as part of its partitioned table.".to_string(),
             }
     }
 
@@ -306,6 +310,7 @@ impl Debug for NotReplicatedReason {
             Self::UnsupportedType(s) => write!(f, "UnsupportedType({})", s),
             Self::OtherError(s) => write!(f, "OtherError({})", s),
             Self::Default => write!(f, ""),
+            Self::Partition => write!(f, "Partition"),
         }
     }
 }
@@ -40,12 +40,34 @@ use tokio_native_tls::TlsAcceptor;
 pub use crate::bytes::BytesStr;
 pub use crate::error::Error;
 pub use crate::message::{PsqlSrvRow, TransferFormat};
+pub use crate::scram::{ClientKey, ScramClient, ScramSecret};
 pub use crate::value::PsqlValue;
 
 pub enum CredentialsNeeded {
     None,
     Cleartext,
     ScramSha256,
+    /// Verify the client's credentials using [`PsqlBackend::authenticate_pass_through`] rather
+    /// than [`PsqlBackend::credentials_for_user`].
+    ///
+    /// If [`PsqlBackend::scram_secret_for_user`] returns a secret for the user, the client is
+    /// authenticated against that secret using SCRAM-SHA-256, and the backend is passed the
+    /// [`ClientKey`] recovered from the exchange. Otherwise, the client is asked for its password
+    /// in cleartext - but only if it has connected over TLS.
+    PassThrough,
+}
+
+/// Credentials supplied by a client for pass-through authentication
+#[derive(Debug, Clone, Copy)]
+pub enum PassThroughCredentials<'a> {
+    /// The client's cleartext password
+    CleartextPassword(&'a str),
+    /// The client has proven that it knows the password the given secret was derived from, which
+    /// revealed its [`ClientKey`]
+    ScramClientKey {
+        secret: &'a ScramSecret,
+        client_key: &'a ClientKey,
+    },
 }
 
 /// Authentication credentials required for a given user
@@ -82,6 +104,27 @@ pub trait PsqlBackend {
     /// Look up authentication credentials for the given user
     fn credentials_for_user(&self, user: &str) -> Option<Credentials>;
 
+    /// Look up a SCRAM secret to authenticate the given user against during pass-through
+    /// authentication, such as the one stored for that user by another database. Only called if
+    /// [`on_init`](Self::on_init) returns [`CredentialsNeeded::PassThrough`].
+    async fn scram_secret_for_user(&mut self, _user: &str) -> Result<Option<ScramSecret>, Error> {
+        Ok(None)
+    }
+
+    /// Authenticate the given user with the given credentials by some means other than
+    /// [`credentials_for_user`](Self::credentials_for_user), such as by connecting to another
+    /// database as that user. Only called if [`on_init`](Self::on_init) returns
+    /// [`CredentialsNeeded::PassThrough`].
+    ///
+    /// Returns `true` if the user was successfully authenticated
+    async fn authenticate_pass_through(
+        &mut self,
+        _user: &str,
+        _credentials: PassThroughCredentials<'_>,
+    ) -> Result<bool, Error> {
+        Ok(false)
+    }
+
     /// Performs the specified SQL query.
     ///
     /// * `query` - The sql query to perform.
@@ -20,12 +20,13 @@ use crate::message::TransferFormat::{self, *};
 use crate::message::{CommandCompleteTag, FieldDescription, SaslInitialResponse};
 use crate::response::Response;
 use crate::scram::{
-    ClientChannelBindingSupport, ClientFinalMessage, ClientFirstMessage, ServerFirstMessage,
-    SCRAM_SHA_256_AUTHENTICATION_METHOD, SCRAM_SHA_256_SSL_AUTHENTICATION_METHOD,
+    ClientChannelBindingSupport, ClientFinalMessage, ClientFirstMessage, ScramSecret,
+    ServerFirstMessage, SCRAM_SHA_256_AUTHENTICATION_METHOD,
+    SCRAM_SHA_256_SSL_AUTHENTICATION_METHOD,
 };
 use crate::value::PsqlValue;
 use crate::QueryResponse::*;
-use crate::{Column, Credentials, PrepareResponse, PsqlBackend};
+use crate::{Column, Credentials, PassThroughCredentials, PrepareResponse, PsqlBackend};
 
 const ATTTYPMOD_NONE: i32 = -1;
 const TRANSFER_FORMAT_PLACEHOLDER: TransferFormat = TransferFormat::Text;
@@ -49,12 +50,18 @@ const UNKNOWN_TABLE: u32 = 0;
 /// authentication protocol
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum SaslState {
-    /// The server has requested authentication from the client
-    RequestedAuthentication { user: BytesStr },
+    /// The server has requested authentication from the client. If `pass_through_secret` is set,
+    /// the client is authenticated against that secret, and the backend is then asked to
+    /// authenticate it via [`PsqlBackend::authenticate_pass_through`]
+    RequestedAuthentication {
+        user: BytesStr,
+        pass_through_secret: Option<ScramSecret>,
+    },
     /// The client has sent its initial message, and the server has replied with a challenge
     ChallengeSent {
         user: BytesStr,
-        salted_password: Vec<u8>,
+        secret: ScramSecret,
+        pass_through: bool,
         client_first_message_bare: String,
         server_first_message: String,
         channel_binding_used: bool,
@@ -70,7 +77,9 @@ pub(crate) enum SaslState {
 /// * StartingUp -> Ready
 /// * StartingUp -> AuthenticatingCleartext
 /// * StartingUp -> AuthenticatingSasl
+/// * StartingUp -> AuthenticatingPassThrough
 /// * AuthenticatingCleartext -> Ready
+/// * AuthenticatingPassThrough -> Ready
 /// * AuthenticatingSasl -> AuthenticatingSasl
 /// * AuthenticatingSasl -> Ready
 /// * Ready -> Extended
@@ -84,6 +93,10 @@ pub(crate) enum State {
     /// The client is performing authentication using the cleartext password protocol
     AuthenticatingCleartext { user: BytesStr },
 
+    /// The client is sending a cleartext password, to be verified by the backend via
+    /// [`PsqlBackend::authenticate_pass_through`]
+    AuthenticatingPassThrough { user: BytesStr },
+
     /// The client is performing authentication using the SASL authentication protocol
     AuthenticatingSasl(SaslState),
 
@@ -132,6 +145,9 @@ pub struct Protocol {
     /// Whether to allow TLS connections.
     allow_tls_connections: bool,
 
+    /// Whether the client has connected over TLS
+    tls_established: bool,
+
     /// TLS server endpoint data for channel binding as specified by
     /// [RFC5929](https://www.rfc-editor.org/rfc/rfc5929)
     tls_server_end_point: Option<Vec<u8>>,
@@ -168,6 +184,7 @@ impl Protocol {
             portals: HashMap::new(),
             extended_types: HashMap::new(),
             allow_tls_connections: false,
+            tls_established: false,
             tls_server_end_point: None,
         }
     }
@@ -254,10 +271,33 @@ impl Protocol {
                             };
                             smallvec![AuthenticationCleartextPassword]
                         }
+                        crate::CredentialsNeeded::PassThrough => {
+                            let user = user.ok_or(Error::NoUserSpecified)?;
+                            if let Some(secret) = backend.scram_secret_for_user(&user).await? {
+                                self.state =
+                                    State::AuthenticatingSasl(SaslState::RequestedAuthentication {
+                                        user,
+                                        pass_through_secret: Some(secret),
+                                    });
+                                smallvec![AuthenticationSasl {
+                                    allow_channel_binding: self.allow_tls_connections
+                                }]
+                            } else if self.tls_established {
+                                self.state = State::AuthenticatingPassThrough { user };
+                                smallvec![AuthenticationCleartextPassword]
+                            } else {
+                                // Never ask for a password that would be sent over the wire in
+                                // cleartext
+                                return Err(Error::TlsRequired {
+                                    username: user.to_string(),
+                                });
+                            }
+                        }
                         crate::CredentialsNeeded::ScramSha256 => {
                             self.state =
                                 State::AuthenticatingSasl(SaslState::RequestedAuthentication {
                                     user: user.ok_or(Error::NoUserSpecified)?,
+                                    pass_through_secret: None,
                                 });
                             smallvec![AuthenticationSasl {
                                 allow_channel_binding: self.allow_tls_connections
@@ -298,22 +338,53 @@ impl Protocol {
                 m => Err(Error::UnsupportedMessage(m)),
             },
 
-            State::AuthenticatingSasl(SaslState::RequestedAuthentication { ref user }) => {
+            State::AuthenticatingPassThrough { ref user } => match message {
+                Authenticate { mut body } => {
+                    let password = decoder::decode_password_message_body(&mut body)?;
+                    if !backend
+                        .authenticate_pass_through(
+                            user,
+                            PassThroughCredentials::CleartextPassword(password.borrow()),
+                        )
+                        .await?
+                    {
+                        return Err(Error::AuthenticationFailure {
+                            username: user.to_string(),
+                        });
+                    }
+
+                    self.state = State::Ready;
+
+                    Ok(Response::Messages(get_ready_message(backend.version())))
+                }
+
+                m => Err(Error::UnsupportedMessage(m)),
+            },
+
+            State::AuthenticatingSasl(SaslState::RequestedAuthentication {
+                ref user,
+                ref pass_through_secret,
+            }) => {
                 let Authenticate { mut body } = message else {
                     return Err(Error::UnsupportedMessage(message));
                 };
 
-                let password = match backend.credentials_for_user(user) {
-                    None => {
-                        return Err(Error::AuthenticationFailure {
-                            username: user.to_string(),
-                        })
-                    }
-                    Some(Credentials::Any) => {
-                        self.state = State::Ready;
-                        return Ok(Response::Messages(get_ready_message(backend.version())));
-                    }
-                    Some(Credentials::CleartextPassword(pw)) => pw,
+                let secret = match pass_through_secret {
+                    Some(secret) => secret.clone(),
+                    None => match backend.credentials_for_user(user) {
+                        None => {
+                            return Err(Error::AuthenticationFailure {
+                                username: user.to_string(),
+                            })
+                        }
+                        Some(Credentials::Any) => {
+                            self.state = State::Ready;
+                            return Ok(Response::Messages(get_ready_message(backend.version())));
+                        }
+                        Some(Credentials::CleartextPassword(pw)) => {
+                            ScramSecret::generate(pw.as_bytes())?
+                        }
+                    },
                 };
 
                 let SaslInitialResponse {
@@ -364,13 +435,13 @@ impl Protocol {
                 let channel_binding_used =
                     client_first_message.channel_binding_support().is_required();
 
-                let server_first_message =
-                    ServerFirstMessage::new(client_first_message, password.as_bytes())?;
+                let server_first_message = ServerFirstMessage::new(client_first_message, secret);
                 let sasl_data = server_first_message.to_string();
 
                 self.state = State::AuthenticatingSasl(SaslState::ChallengeSent {
                     user: user.clone(),
-                    salted_password: server_first_message.salted_password().to_owned(),
+                    secret: server_first_message.secret().clone(),
+                    pass_through: pass_through_secret.is_some(),
                     client_first_message_bare,
                     server_first_message: sasl_data.clone(),
                     channel_binding_used,
@@ -385,7 +456,8 @@ impl Protocol {
 
             State::AuthenticatingSasl(SaslState::ChallengeSent {
                 ref user,
-                ref salted_password,
+                ref secret,
+                pass_through,
                 ref client_first_message_bare,
                 ref server_first_message,
                 channel_binding_used,
@@ -395,25 +467,42 @@ impl Protocol {
                 };
 
                 let client_final_message = ClientFinalMessage::parse(&body)?;
-                if let Some(server_final_message) = client_final_message.verify(
-                    salted_password,
+                let Some((server_final_message, client_key)) = client_final_message.verify(
+                    secret.keys(),
                     client_first_message_bare,
                     server_first_message,
                     channel_binding_used
                         .then_some(self.tls_server_end_point.as_deref())
                         .flatten(),
-                )? {
-                    self.state = State::Ready;
-                    let mut messages = vec![BackendMessage::AuthenticationSaslFinal {
-                        sasl_data: server_final_message.to_string().into(),
-                    }];
-                    messages.extend(get_ready_message(backend.version()));
-                    Ok(Response::Messages(messages.into()))
-                } else {
-                    Err(Error::AuthenticationFailure {
+                )?
+                else {
+                    return Err(Error::AuthenticationFailure {
                         username: user.to_string(),
-                    })
+                    });
+                };
+
+                if pass_through
+                    && !backend
+                        .authenticate_pass_through(
+                            user,
+                            PassThroughCredentials::ScramClientKey {
+                                secret,
+                                client_key: &client_key,
+                            },
+                        )
+                        .await?
+                {
+                    return Err(Error::AuthenticationFailure {
+                        username: user.to_string(),
+                    });
                 }
+
+                self.state = State::Ready;
+                let mut messages = vec![BackendMessage::AuthenticationSaslFinal {
+                    sasl_data: server_final_message.to_string().into(),
+                }];
+                messages.extend(get_ready_message(backend.version()));
+                Ok(Response::Messages(messages.into()))
             }
 
             State::Error => match message {
@@ -770,6 +859,7 @@ impl Protocol {
     /// TLS server endpoint for optional use in channel binding later.
     pub fn completed_ssl_handshake(&mut self, server_end_point: Option<Vec<u8>>) {
         self.state = State::StartingUp;
+        self.tls_established = true;
         self.tls_server_end_point = server_end_point;
     }
 }
@@ -939,16 +1029,18 @@ mod tests {
     use std::{io, vec};
 
     use async_trait::async_trait;
-    use bytes::BytesMut;
+    use bytes::{BufMut, BytesMut};
     use futures::task::Context;
     use futures::{stream, TryStreamExt};
     use postgres::error::SqlState;
+    use postgres_protocol::authentication::sasl::{ChannelBinding, ScramSha256};
     use tokio::io::ReadBuf;
     use tokio_test::block_on;
 
     use super::*;
     use crate::bytes::BytesStr;
     use crate::message::{ErrorSeverity, PsqlSrvRow};
+    use crate::scram::ClientKey;
     use crate::value::PsqlValue;
     use crate::{Credentials, CredentialsNeeded, PrepareResponse, QueryResponse};
 
@@ -974,6 +1066,9 @@ mod tests {
         last_execute_params: Option<Vec<PsqlValue>>,
         last_transfer_formats: Option<Vec<TransferFormat>>,
         needed_credentials: Option<Credentials<'static>>,
+        pass_through_password: Option<&'static str>,
+        pass_through_secret: Option<ScramSecret>,
+        pass_through_client_key: Option<ClientKey>,
     }
 
     impl Backend {
@@ -990,6 +1085,9 @@ mod tests {
                 last_execute_params: None,
                 last_transfer_formats: None,
                 needed_credentials: None,
+                pass_through_password: None,
+                pass_through_secret: None,
+                pass_through_client_key: None,
             }
         }
     }
@@ -1004,6 +1102,9 @@ mod tests {
 
         async fn on_init(&mut self, database: &str) -> Result<CredentialsNeeded, Error> {
             self.database = Some(database.to_string());
+            if self.pass_through_password.is_some() || self.pass_through_secret.is_some() {
+                return Ok(CredentialsNeeded::PassThrough);
+            }
             match &self.needed_credentials {
                 Some(_) => Ok(CredentialsNeeded::Cleartext),
                 None => Ok(CredentialsNeeded::None),
@@ -1014,6 +1115,29 @@ mod tests {
             self.needed_credentials
         }
 
+        async fn scram_secret_for_user(
+            &mut self,
+            _user: &str,
+        ) -> Result<Option<ScramSecret>, Error> {
+            Ok(self.pass_through_secret.clone())
+        }
+
+        async fn authenticate_pass_through(
+            &mut self,
+            _user: &str,
+            credentials: PassThroughCredentials<'_>,
+        ) -> Result<bool, Error> {
+            match credentials {
+                PassThroughCredentials::CleartextPassword(password) => {
+                    Ok(self.pass_through_password == Some(password))
+                }
+                PassThroughCredentials::ScramClientKey { client_key, .. } => {
+                    self.pass_through_client_key = Some(client_key.clone());
+                    Ok(true)
+                }
+            }
+        }
+
         async fn on_query(&mut self, query: &str) -> Result<QueryResponse<Self::Resultset>, Error> {
             self.last_query = Some(query.to_string());
             if self.is_query_err {
@@ -1282,6 +1406,150 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pass_through_authentication_flow() {
+        let expected_username = bytes_str("user_name");
+        let mut protocol = Protocol::new();
+        protocol.completed_ssl_handshake(None);
+        let request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(expected_username.clone()),
+            database: Some(bytes_str("database_name")),
+        };
+        let mut backend = Backend::new();
+        backend.pass_through_password = Some("password");
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+        match block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap() {
+            Response::Messages(ms) => assert!(matches!(
+                ms.as_ref(),
+                [BackendMessage::AuthenticationCleartextPassword]
+            )),
+            _ => panic!(),
+        }
+        assert_eq!(
+            protocol.state,
+            State::AuthenticatingPassThrough {
+                user: expected_username.clone()
+            }
+        );
+
+        let auth_request = FrontendMessage::Authenticate {
+            body: "incorrect password\x00".into(),
+        };
+        let output =
+            block_on(protocol.on_request(auth_request, &mut backend, &mut channel)).unwrap_err();
+        assert!(
+            matches!(
+                &output,
+                Error::AuthenticationFailure { username }
+                if *username == expected_username.to_string()
+            ),
+            "output = {output:?}"
+        );
+
+        let auth_request = FrontendMessage::Authenticate {
+            body: "password\x00".into(),
+        };
+        block_on(protocol.on_request(auth_request, &mut backend, &mut channel)).unwrap();
+        assert_eq!(protocol.state, State::Ready);
+    }
+
+    #[test]
+    fn pass_through_authentication_requires_tls() {
+        let mut protocol = Protocol::new();
+        let request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        let mut backend = Backend::new();
+        backend.pass_through_password = Some("password");
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+        let output =
+            block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap_err();
+        assert!(
+            matches!(&output, Error::TlsRequired { username } if username == "user_name"),
+            "output = {output:?}"
+        );
+        assert_eq!(protocol.state, State::StartingUp);
+    }
+
+    #[test]
+    fn scram_pass_through_authentication_flow() {
+        let expected_username = bytes_str("user_name");
+        let secret = ScramSecret::generate(b"password").unwrap();
+        let mut backend = Backend::new();
+        backend.pass_through_secret = Some(secret.clone());
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+
+        let mut authenticate = |password: &[u8]| -> Result<(), Error> {
+            let mut protocol = Protocol::new();
+            let request = FrontendMessage::StartupMessage {
+                protocol_version: 12345,
+                user: Some(expected_username.clone()),
+                database: Some(bytes_str("database_name")),
+            };
+            match block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap() {
+                Response::Messages(ms) => assert!(matches!(
+                    ms.as_ref(),
+                    [BackendMessage::AuthenticationSasl { .. }]
+                )),
+                _ => panic!(),
+            }
+            assert_eq!(
+                protocol.state,
+                State::AuthenticatingSasl(SaslState::RequestedAuthentication {
+                    user: expected_username.clone(),
+                    pass_through_secret: Some(secret.clone()),
+                })
+            );
+
+            let mut client = ScramSha256::new(password, ChannelBinding::unsupported());
+            let mut body = BytesMut::new();
+            body.put_slice(b"SCRAM-SHA-256\0");
+            body.put_i32(client.message().len() as i32);
+            body.put_slice(client.message());
+            let auth_request = FrontendMessage::Authenticate {
+                body: body.freeze(),
+            };
+            let server_first_message =
+                match block_on(protocol.on_request(auth_request, &mut backend, &mut channel))? {
+                    Response::Message(BackendMessage::AuthenticationSaslContinue { sasl_data }) => {
+                        sasl_data
+                    }
+                    _ => panic!(),
+                };
+            client.update(&server_first_message).unwrap();
+
+            let auth_request = FrontendMessage::Authenticate {
+                body: client.message().to_vec().into(),
+            };
+            match block_on(protocol.on_request(auth_request, &mut backend, &mut channel))? {
+                Response::Messages(ms) => match ms.first() {
+                    Some(BackendMessage::AuthenticationSaslFinal { sasl_data }) => {
+                        client.finish(sasl_data).unwrap()
+                    }
+                    _ => panic!(),
+                },
+                _ => panic!(),
+            }
+            assert_eq!(protocol.state, State::Ready);
+            Ok(())
+        };
+
+        let output = authenticate(b"incorrect password").unwrap_err();
+        assert!(
+            matches!(
+                &output,
+                Error::AuthenticationFailure { username }
+                if *username == expected_username.to_string()
+            ),
+            "output = {output:?}"
+        );
+        authenticate(b"password").unwrap();
+        assert!(backend.pass_through_client_key.is_some());
+    }
+
     #[test]
     fn startup_message_without_database() {
         let mut protocol = Protocol::new();
@@ -1,14 +1,18 @@
 //! Server protocol implementation of the SCRAM SASL mechanism, described in [RFC5802][rfc5802]
 //!
+//! Also includes [`ScramClient`], the client side of the mechanism, which authenticates with a
+//! [`ClientKey`] recovered from a client's own authentication exchange rather than with a password.
+//! This is what allows clients to be authenticated against (and connected to) another database
+//! without ReadySet ever learning their password.
+//!
 //! [rfc5802]: https://www.rfc-editor.org/rfc/rfc5802
 
 use std::borrow::Cow;
-use std::fmt::{self, Display};
-use std::str::{self, Utf8Error};
+use std::fmt::{self, Debug, Display};
+use std::str::{self, FromStr, Utf8Error};
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
-use hmac::digest::FixedOutput;
 use hmac::{Hmac, Mac};
 use rand::Rng;
 use sha2::{Digest, Sha256};
@@ -22,6 +26,10 @@ pub const SCRAM_SHA_256_SSL_AUTHENTICATION_METHOD: &str = "SCRAM-SHA-256-PLUS";
 pub const SCRAM_ITERATION_COUNT: u32 = 4096;
 const NONCE_LENGTH: usize = 24;
 const SALT_LENGTH: usize = 12;
+/// Length of the keys, signatures, and proofs used by SCRAM-SHA-256
+const KEY_LENGTH: usize = 32;
+/// Base64 encoding of the GS2 header `n,,`, sent by clients which don't support channel binding
+const GS2_HEADER_WITHOUT_CHANNEL_BINDING: &str = "biws";
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -34,6 +42,18 @@ pub enum Error {
     #[error("Invalid channel binding data")]
     InvalidChannelBindingData,
 
+    #[error("Invalid SCRAM secret")]
+    InvalidSecret,
+
+    #[error("Server nonce does not extend the client nonce")]
+    InvalidServerNonce,
+
+    #[error("Server salt or iteration count do not match the SCRAM secret")]
+    MismatchedSecret,
+
+    #[error("Invalid server signature")]
+    InvalidServerSignature,
+
     #[error(transparent)]
     Utf8(#[from] Utf8Error),
 
@@ -82,6 +102,9 @@ enum ScramMessageAttribute {
     /// > s: This attribute specifies the base64-encoded salt used by the server for this user.
     Salt,
 
+    /// > i: This attribute specifies an iteration count for the selected hash function and user.
+    IterationCount,
+
     /// > p: This attribute specifies a base64-encoded ClientProof.
     ClientProof,
 
@@ -106,6 +129,7 @@ impl From<u8> for ScramMessageAttribute {
             b'r' => Nonce,
             b'c' => Gs2Header,
             b's' => Salt,
+            b'i' => IterationCount,
             b'p' => ClientProof,
             b'v' => ServerSignature,
             b'e' => ErrorOccurred,
@@ -308,6 +332,52 @@ mod parse {
             ))
         })(i)
     }
+
+    /// Parse a server-first-message into its nonce, base64-encoded salt, and iteration count
+    pub(super) fn server_first_message(i: &[u8]) -> IResult<&[u8], (&str, &str, u32)> {
+        all_consuming(|i| {
+            let (i, _) = verify(
+                opt(terminated(
+                    specific_attribute(ScramMessageAttribute::ReservedMext),
+                    tag(","),
+                )),
+                |v| v.is_none(),
+            )(i)?;
+            let (i, nonce) = map_res(
+                specific_attribute(ScramMessageAttribute::Nonce),
+                str::from_utf8,
+            )(i)?;
+            let (i, _) = tag(",")(i)?;
+            let (i, salt_base64) = map_res(
+                specific_attribute(ScramMessageAttribute::Salt),
+                str::from_utf8,
+            )(i)?;
+            let (i, _) = tag(",")(i)?;
+            let (i, iterations) = map_res(
+                map_res(
+                    specific_attribute(ScramMessageAttribute::IterationCount),
+                    str::from_utf8,
+                ),
+                |v: &str| v.parse::<u32>(),
+            )(i)?;
+            let (i, _) = many0(preceded(tag(","), attribute_pair))(i)?;
+
+            Ok((i, (nonce, salt_base64, iterations)))
+        })(i)
+    }
+
+    /// Parse a server-final-message into its base64-encoded server signature
+    pub(super) fn server_final_message(i: &[u8]) -> IResult<&[u8], &str> {
+        all_consuming(|i| {
+            let (i, signature_base64) = map_res(
+                specific_attribute(ScramMessageAttribute::ServerSignature),
+                str::from_utf8,
+            )(i)?;
+            let (i, _) = many0(preceded(tag(","), attribute_pair))(i)?;
+
+            Ok((i, signature_base64))
+        })(i)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -366,15 +436,16 @@ impl<'a> ClientFinalMessage<'a> {
     }
 
     /// Verify the client proof supplied as part of this client-final-message, against the given
-    /// data from the rest of the SCRAM authentication flow. Returns `Some(ServerFinalMessage)` if
-    /// verification succeeds, or `None` if authentication has failed
+    /// data from the rest of the SCRAM authentication flow. Returns the `ServerFinalMessage` to
+    /// send to the client along with the client's [`ClientKey`] if verification succeeds, or
+    /// `None` if authentication has failed
     pub fn verify(
         &self,
-        salted_password: &[u8],
+        keys: &ScramKeys,
         client_first_message_bare: &str,
         server_first_message: &str,
         expected_channel_binding_data: Option<&[u8]>,
-    ) -> Result<Option<ServerFinalMessage>> {
+    ) -> Result<Option<(ServerFinalMessage, ClientKey)>> {
         if let Some(expected_channel_binding_data) = expected_channel_binding_data {
             const CBIND_INPUT_GS2_HEADER: &[u8] = b"p=tls-server-end-point,,";
             let cbind_input = BASE64.decode(self.cbind_input_base64)?;
@@ -388,48 +459,34 @@ impl<'a> ClientFinalMessage<'a> {
             }
         }
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(salted_password)?;
-        hmac.update(b"Client Key");
-        let expected_client_key = hmac.finalize().into_bytes();
-
-        let mut hash = Sha256::default();
-        hash.update(expected_client_key);
-        let expected_stored_key = hash.finalize_fixed();
-
         let auth_message = format!(
             "{client_first_message_bare},{server_first_message},{}",
             self.without_proof
         );
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&expected_stored_key)?;
-        hmac.update(auth_message.as_bytes());
-        let client_signature = hmac.finalize().into_bytes();
-
+        let client_signature = hmac(&keys.stored_key, auth_message.as_bytes())?;
         let client_proof = BASE64.decode(self.proof_base64)?;
+        if client_proof.len() != KEY_LENGTH {
+            return Ok(None);
+        }
+
         let mut client_key = client_signature;
         for (key, proof) in client_key.iter_mut().zip(client_proof) {
             *key ^= proof
         }
 
-        let mut hash = Sha256::default();
-        hash.update(client_key);
-        let stored_key = hash.finalize_fixed();
-
-        if stored_key != expected_stored_key {
+        if sha256(&client_key) != keys.stored_key {
             return Ok(None);
         }
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(salted_password)?;
-        hmac.update(b"Server Key");
-        let server_key = hmac.finalize().into_bytes();
+        let server_signature = hmac(&keys.server_key, auth_message.as_bytes())?;
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&server_key)?;
-        hmac.update(auth_message.as_bytes());
-        let server_signature = hmac.finalize().into_bytes();
-
-        Ok(Some(ServerFinalMessage {
-            signature: server_signature.as_slice().to_owned(),
-        }))
+        Ok(Some((
+            ServerFinalMessage {
+                signature: server_signature.to_vec(),
+            },
+            ClientKey(client_key),
+        )))
     }
 }
 
@@ -473,42 +530,160 @@ fn hi(str: &[u8], salt: &[u8], i: u32) -> Result<[u8; 32]> {
     Ok(hi.into())
 }
 
-/// First message sent by the server as part of the SCRAM authentication flow
+fn hmac(key: &[u8], data: &[u8]) -> Result<[u8; KEY_LENGTH]> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(key)?;
+    hmac.update(data);
+    Ok(hmac.finalize().into_bytes().into())
+}
+
+fn sha256(data: &[u8]) -> [u8; KEY_LENGTH] {
+    Sha256::digest(data).into()
+}
+
+/// Generate a random nonce of printable characters, excluding `,`
+fn nonce() -> String {
+    // rand 0.5's ThreadRng is cryptographically secure
+    let mut rng = rand::thread_rng();
+    (0..NONCE_LENGTH)
+        .map(|_| {
+            let mut v = rng.gen_range(0x21u8..0x7e);
+            if v == 0x2c {
+                v = 0x7e
+            }
+            v as char
+        })
+        .collect()
+}
+
+/// The `StoredKey` and `ServerKey` derived from a user's password, which together are enough to
+/// verify a client's proof, but not to compute one
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ServerFirstMessage {
-    nonce: String,
-    salt: [u8; SALT_LENGTH],
-    salted_password: [u8; 32],
+pub struct ScramKeys {
+    stored_key: [u8; KEY_LENGTH],
+    server_key: [u8; KEY_LENGTH],
 }
 
-impl ServerFirstMessage {
-    pub fn new(client_first_message: ClientFirstMessage, password: &[u8]) -> Result<Self> {
-        // rand 0.5's ThreadRng is cryptographically secure
-        let mut rng = rand::thread_rng();
-        let server_nonce = (0..NONCE_LENGTH)
-            .map(|_| {
-                let mut v = rng.gen_range(0x21u8..0x7e);
-                if v == 0x2c {
-                    v = 0x7e
-                }
-                v as char
-            })
-            .collect::<String>();
+impl ScramKeys {
+    fn from_salted_password(salted_password: &[u8]) -> Result<Self> {
+        Ok(Self {
+            stored_key: sha256(&hmac(salted_password, b"Client Key")?),
+            server_key: hmac(salted_password, b"Server Key")?,
+        })
+    }
+}
+
+/// Everything a server needs to know to authenticate a user with SCRAM-SHA-256, in the format
+/// PostgreSQL stores it in `pg_authid.rolpassword`:
+///
+/// ```text
+/// SCRAM-SHA-256$<iteration count>:<salt>$<StoredKey>:<ServerKey>
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScramSecret {
+    iterations: u32,
+    salt: Vec<u8>,
+    keys: ScramKeys,
+}
 
-        let nonce = format!("{}{}", client_first_message.nonce, server_nonce);
+impl ScramSecret {
+    /// Derive a secret from the given password, with a random salt
+    pub fn generate(password: &[u8]) -> Result<Self> {
         let mut salt = [0u8; SALT_LENGTH];
-        rng.fill(&mut salt);
-        let salted_password = hi(&normalize(password), &salt, SCRAM_ITERATION_COUNT)?;
+        rand::thread_rng().fill(&mut salt);
+        Self::from_password(password, &salt, SCRAM_ITERATION_COUNT)
+    }
 
+    /// Derive a secret from the given password, salt, and iteration count
+    pub fn from_password(password: &[u8], salt: &[u8], iterations: u32) -> Result<Self> {
+        let salted_password = hi(&normalize(password), salt, iterations)?;
         Ok(Self {
-            nonce,
-            salt,
-            salted_password,
+            iterations,
+            salt: salt.to_owned(),
+            keys: ScramKeys::from_salted_password(&salted_password)?,
         })
     }
 
-    pub fn salted_password(&self) -> &[u8] {
-        &self.salted_password
+    pub fn keys(&self) -> &ScramKeys {
+        &self.keys
+    }
+}
+
+impl FromStr for ScramSecret {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (iterations_and_salt, keys) = s
+            .strip_prefix("SCRAM-SHA-256$")
+            .and_then(|s| s.split_once('$'))
+            .ok_or(Error::InvalidSecret)?;
+        let (iterations, salt) = iterations_and_salt
+            .split_once(':')
+            .ok_or(Error::InvalidSecret)?;
+        let (stored_key, server_key) = keys.split_once(':').ok_or(Error::InvalidSecret)?;
+        let decode_key = |key: &str| -> Result<[u8; KEY_LENGTH]> {
+            BASE64
+                .decode(key)?
+                .try_into()
+                .map_err(|_| Error::InvalidSecret)
+        };
+
+        Ok(Self {
+            iterations: iterations.parse().map_err(|_| Error::InvalidSecret)?,
+            salt: BASE64.decode(salt)?,
+            keys: ScramKeys {
+                stored_key: decode_key(stored_key)?,
+                server_key: decode_key(server_key)?,
+            },
+        })
+    }
+}
+
+impl Display for ScramSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SCRAM-SHA-256${}:{}${}:{}",
+            self.iterations,
+            BASE64.encode(&self.salt),
+            BASE64.encode(self.keys.stored_key),
+            BASE64.encode(self.keys.server_key)
+        )
+    }
+}
+
+/// The `ClientKey` of a client, recovered from its proof once it has been verified.
+///
+/// Holding the `ClientKey` is enough to authenticate as the client to any server with the same
+/// [`ScramSecret`] for that client (see [`ScramClient`]), so it must be treated as carefully as a
+/// password.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClientKey([u8; KEY_LENGTH]);
+
+impl Debug for ClientKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ClientKey(<redacted>)")
+    }
+}
+
+/// First message sent by the server as part of the SCRAM authentication flow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerFirstMessage {
+    nonce: String,
+    secret: ScramSecret,
+}
+
+impl ServerFirstMessage {
+    /// Construct a challenge for the client to prove that it knows the password the given secret
+    /// was derived from
+    pub fn new(client_first_message: ClientFirstMessage, secret: ScramSecret) -> Self {
+        Self {
+            nonce: format!("{}{}", client_first_message.nonce, nonce()),
+            secret,
+        }
+    }
+
+    pub fn secret(&self) -> &ScramSecret {
+        &self.secret
     }
 }
 
@@ -518,8 +693,8 @@ impl Display for ServerFirstMessage {
             f,
             "r={},s={},i={}",
             self.nonce,
-            BASE64.encode(self.salt),
-            SCRAM_ITERATION_COUNT,
+            BASE64.encode(&self.secret.salt),
+            self.secret.iterations,
         )
     }
 }
@@ -536,6 +711,86 @@ impl Display for ServerFinalMessage {
     }
 }
 
+/// The client side of the SCRAM authentication flow, authenticating with a [`ClientKey`] rather
+/// than a password.
+///
+/// The server must use the same [`ScramSecret`] that the `ClientKey` was verified against, which
+/// is checked against the salt and iteration count sent by the server. Channel binding is not
+/// supported.
+pub struct ScramClient {
+    client_key: ClientKey,
+    secret: ScramSecret,
+    client_first_message_bare: String,
+    /// The SCRAM `AuthMessage`, once the server-first-message has been received
+    auth_message: Option<String>,
+}
+
+impl ScramClient {
+    pub fn new(client_key: ClientKey, secret: ScramSecret) -> Self {
+        // PostgreSQL ignores the username in the SCRAM exchange in favor of the one in the
+        // startup message, so we don't bother sending one
+        Self {
+            client_key,
+            secret,
+            client_first_message_bare: format!("n=,r={}", nonce()),
+            auth_message: None,
+        }
+    }
+
+    /// Returns the client-first-message to send to the server
+    pub fn client_first_message(&self) -> String {
+        format!("n,,{}", self.client_first_message_bare)
+    }
+
+    /// Process the given server-first-message, and return the client-final-message to send to
+    /// the server in response
+    pub fn handle_server_first_message(&mut self, server_first_message: &[u8]) -> Result<String> {
+        let (nonce, salt_base64, iterations) = parse::server_first_message(server_first_message)
+            .map_err(|_| Error::ParseFail)?
+            .1;
+
+        let client_nonce = &self.client_first_message_bare["n=,r=".len()..];
+        if nonce.len() <= client_nonce.len() || !nonce.starts_with(client_nonce) {
+            return Err(Error::InvalidServerNonce);
+        }
+        if BASE64.decode(salt_base64)? != self.secret.salt || iterations != self.secret.iterations {
+            return Err(Error::MismatchedSecret);
+        }
+
+        let without_proof = format!("c={GS2_HEADER_WITHOUT_CHANNEL_BINDING},r={nonce}");
+        let auth_message = format!(
+            "{},{},{without_proof}",
+            self.client_first_message_bare,
+            str::from_utf8(server_first_message)?
+        );
+
+        let mut client_proof = hmac(&self.secret.keys.stored_key, auth_message.as_bytes())?;
+        for (proof, key) in client_proof.iter_mut().zip(self.client_key.0) {
+            *proof ^= key;
+        }
+
+        self.auth_message = Some(auth_message);
+        Ok(format!("{without_proof},p={}", BASE64.encode(client_proof)))
+    }
+
+    /// Verify the server signature in the given server-final-message, which proves that the
+    /// server also knows the [`ScramSecret`]
+    pub fn verify_server_final_message(&self, server_final_message: &[u8]) -> Result<()> {
+        let auth_message = self.auth_message.as_ref().ok_or(Error::ParseFail)?;
+        let signature_base64 = parse::server_final_message(server_final_message)
+            .map_err(|_| Error::ParseFail)?
+            .1;
+
+        if BASE64.decode(signature_base64)?
+            != hmac(&self.secret.keys.server_key, auth_message.as_bytes())?
+        {
+            return Err(Error::InvalidServerSignature);
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -653,4 +908,143 @@ mod tests {
             )
         }
     }
+
+    mod scram_secret {
+        use super::*;
+
+        #[test]
+        fn from_password_matches_postgres_format() {
+            let expected = "SCRAM-SHA-256$4096:cmVhZHlzZXQtc2FsdA==$gz49sKFpavBhAEdH6rjHrHvLj5R4y0n6zuDeTHCjOI4=:ncBTKNFQqSFj40u023Ta1D1NHKPJvGBEF5ZV7+GMAvg=";
+            let secret = ScramSecret::from_password(b"password", b"readyset-salt", 4096).unwrap();
+            assert_eq!(secret.to_string(), expected);
+            assert_eq!(expected.parse::<ScramSecret>().unwrap(), secret);
+        }
+
+        #[test]
+        fn invalid() {
+            for secret in [
+                "",
+                "md55f4dcc3b5aa765d61d8327deb882cf99",
+                "SCRAM-SHA-256$4096:c2FsdA==",
+                "SCRAM-SHA-256$lots:c2FsdA==$AAAA:AAAA",
+                "SCRAM-SHA-256$4096:c2FsdA==$AAAA:AAAA",
+            ] {
+                secret.parse::<ScramSecret>().unwrap_err();
+            }
+        }
+    }
+
+    mod scram_client {
+        use postgres_protocol::authentication::sasl::{ChannelBinding, ScramSha256};
+
+        use super::*;
+
+        /// Authenticate `postgres_protocol`'s SCRAM client with the given password against the
+        /// given secret, returning the client's recovered [`ClientKey`] if authentication succeeds
+        fn authenticate(password: &[u8], secret: &ScramSecret) -> Option<ClientKey> {
+            let mut client = ScramSha256::new(password, ChannelBinding::unsupported());
+            let client_first_message = ClientFirstMessage::parse(client.message()).unwrap();
+            let client_first_message_bare = client_first_message.bare().to_owned();
+            let server_first_message =
+                ServerFirstMessage::new(client_first_message, secret.clone()).to_string();
+
+            client.update(server_first_message.as_bytes()).unwrap();
+            let (server_final_message, client_key) = ClientFinalMessage::parse(client.message())
+                .unwrap()
+                .verify(
+                    secret.keys(),
+                    &client_first_message_bare,
+                    &server_first_message,
+                    None,
+                )
+                .unwrap()?;
+
+            client
+                .finish(server_final_message.to_string().as_bytes())
+                .unwrap();
+            Some(client_key)
+        }
+
+        #[test]
+        fn wrong_password() {
+            let secret = ScramSecret::generate(b"password").unwrap();
+            assert!(authenticate(b"wrong password", &secret).is_none());
+        }
+
+        #[test]
+        fn authenticates_with_recovered_client_key() {
+            let secret = ScramSecret::generate(b"password").unwrap();
+            let client_key = authenticate(b"password", &secret).unwrap();
+
+            let mut client = ScramClient::new(client_key, secret.clone());
+            let client_first_message = client.client_first_message();
+            let client_first_message =
+                ClientFirstMessage::parse(client_first_message.as_bytes()).unwrap();
+            let client_first_message_bare = client_first_message.bare().to_owned();
+            let server_first_message =
+                ServerFirstMessage::new(client_first_message, secret.clone()).to_string();
+
+            let client_final_message = client
+                .handle_server_first_message(server_first_message.as_bytes())
+                .unwrap();
+            let (server_final_message, _) =
+                ClientFinalMessage::parse(client_final_message.as_bytes())
+                    .unwrap()
+                    .verify(
+                        secret.keys(),
+                        &client_first_message_bare,
+                        &server_first_message,
+                        None,
+                    )
+                    .unwrap()
+                    .unwrap();
+
+            client
+                .verify_server_final_message(server_final_message.to_string().as_bytes())
+                .unwrap();
+        }
+
+        #[test]
+        fn rejects_mismatched_secret() {
+            let secret = ScramSecret::generate(b"password").unwrap();
+            let client_key = authenticate(b"password", &secret).unwrap();
+
+            let mut client = ScramClient::new(client_key, secret);
+            let client_first_message = client.client_first_message();
+            let client_first_message =
+                ClientFirstMessage::parse(client_first_message.as_bytes()).unwrap();
+            let server_first_message = ServerFirstMessage::new(
+                client_first_message,
+                ScramSecret::generate(b"password").unwrap(),
+            )
+            .to_string();
+
+            assert!(matches!(
+                client.handle_server_first_message(server_first_message.as_bytes()),
+                Err(Error::MismatchedSecret)
+            ));
+        }
+
+        #[test]
+        fn rejects_invalid_server_signature() {
+            let secret = ScramSecret::generate(b"password").unwrap();
+            let client_key = authenticate(b"password", &secret).unwrap();
+
+            let mut client = ScramClient::new(client_key, secret.clone());
+            let client_first_message = client.client_first_message();
+            let client_first_message =
+                ClientFirstMessage::parse(client_first_message.as_bytes()).unwrap();
+            let server_first_message =
+                ServerFirstMessage::new(client_first_message, secret).to_string();
+            client
+                .handle_server_first_message(server_first_message.as_bytes())
+                .unwrap();
+
+            let server_final_message = format!("v={}", BASE64.encode([0u8; KEY_LENGTH]));
+            assert!(matches!(
+                client.verify_server_final_message(server_final_message.as_bytes()),
+                Err(Error::InvalidServerSignature)
+            ));
+        }
+    }
 }
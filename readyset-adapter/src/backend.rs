@@ -81,9 +81,9 @@ use futures::future::{self, OptionFuture};
 use lru::LruCache;
 use mysql_common::row::convert::{FromRow, FromRowError};
 use nom_sql::{
-    CacheInner, CreateCacheStatement, DeleteStatement, Dialect, DialectDisplay, DropCacheStatement,
-    InsertStatement, Relation, SelectStatement, SetStatement, ShowStatement, SqlIdentifier,
-    SqlQuery, UpdateStatement, UseStatement,
+    CacheExpiration, CacheInner, CachePriority, CreateCacheStatement, DeleteStatement, Dialect,
+    DialectDisplay, DropCacheStatement, InsertStatement, Relation, SelectStatement, SetStatement,
+    ShowStatement, SqlIdentifier, SqlQuery, UpdateStatement, UseStatement,
 };
 use readyset_client::consensus::{Authority, AuthorityControl, CacheDDLRequest};
 use readyset_client::consistency::Timestamp;
@@ -113,6 +113,7 @@ use crate::metrics_handle::{MetricsHandle, MetricsSummary};
 use crate::query_handler::SetBehavior;
 use crate::query_status_cache::QueryStatusCache;
 use crate::status_reporter::ReadySetStatusReporter;
+use crate::table_privileges::TablePrivileges;
 pub use crate::upstream_database::UpstreamPrepare;
 use crate::utils::create_dummy_column;
 use crate::{create_dummy_schema, QueryHandler, UpstreamDatabase, UpstreamDestination};
@@ -268,6 +269,8 @@ pub struct BackendBuilder {
     dialect: Dialect,
     users: HashMap<String, String>,
     require_authentication: bool,
+    authenticate_with_upstream: bool,
+    table_privileges_refresh_seconds: u64,
     ticket: Option<Timestamp>,
     timestamp_client: Option<TimestampClient>,
     query_log_sender: Option<UnboundedSender<QueryExecutionEvent>>,
@@ -290,6 +293,8 @@ impl Default for BackendBuilder {
             dialect: Dialect::MySQL,
             users: Default::default(),
             require_authentication: true,
+            authenticate_with_upstream: false,
+            table_privileges_refresh_seconds: 60,
             ticket: None,
             timestamp_client: None,
             query_log_sender: None,
@@ -343,6 +348,7 @@ impl BackendBuilder {
                 proxy_state,
                 parsed_query_cache: LruCache::new(10_000.try_into().expect("10000 is not 0")),
                 prepared_statements: Default::default(),
+                table_privileges: None,
                 query_status_cache,
                 ticket: self.ticket,
                 timestamp_client: self.timestamp_client,
@@ -351,6 +357,11 @@ impl BackendBuilder {
                 slowlog: self.slowlog,
                 dialect: self.dialect,
                 require_authentication: self.require_authentication,
+                authenticate_with_upstream: self.authenticate_with_upstream,
+                table_privileges_refresh_interval: Duration::new(
+                    self.table_privileges_refresh_seconds,
+                    0,
+                ),
                 unsupported_set_mode: self.unsupported_set_mode,
                 migration_mode: self.migration_mode,
                 query_max_failure_duration: Duration::new(self.query_max_failure_seconds, 0),
@@ -403,6 +414,23 @@ impl BackendBuilder {
         self
     }
 
+    /// Specifies whether clients should be authenticated by connecting to the upstream database
+    /// with their credentials, rather than by checking against the configured
+    /// [`users`](Self::users). If true, the upstream connection is opened as the authenticated
+    /// user, so that queries proxied upstream run with that user's privileges.
+    pub fn authenticate_with_upstream(mut self, authenticate_with_upstream: bool) -> Self {
+        self.authenticate_with_upstream = authenticate_with_upstream;
+        self
+    }
+
+    /// Specifies how often, in seconds, to reload the table privileges of the authenticated user
+    /// from the upstream database. Only used if clients are
+    /// [authenticated with the upstream](Self::authenticate_with_upstream).
+    pub fn table_privileges_refresh_seconds(mut self, secs: u64) -> Self {
+        self.table_privileges_refresh_seconds = secs;
+        self
+    }
+
     /// Specifies whether RYW consistency should be enabled. If true, RYW consistency
     /// constraints will be enforced on all reads.
     pub fn enable_ryw(mut self, enable_ryw: bool) -> Self {
@@ -576,6 +604,9 @@ where
     parsed_query_cache: LruCache<String, SqlQuery>,
     // all queries previously prepared on noria or upstream, mapped by their ID.
     prepared_statements: Slab<PreparedStatement<DB>>,
+    /// The tables the authenticated user is allowed to read from, if clients are authenticated
+    /// with the upstream database and the privileges have been loaded
+    table_privileges: Option<TablePrivileges>,
     /// Current RYW ticket. `None` if RYW is not enabled. This `ticket` will
     /// be updated as the client makes writes so as to be an accurate low watermark timestamp
     /// required to make RYW-consistent reads. On reads, the client will pass in this ticket to be
@@ -593,6 +624,11 @@ struct BackendSettings {
     dialect: Dialect,
     slowlog: bool,
     require_authentication: bool,
+    /// Whether to authenticate clients by connecting to the upstream database as them
+    authenticate_with_upstream: bool,
+    /// How often to reload the table privileges of the authenticated user from the upstream
+    /// database
+    table_privileges_refresh_interval: Duration,
     /// Whether to log ad-hoc queries by full query text in the query logger.
     query_log_mode: QueryLogMode,
     /// How to behave when receiving unsupported `SET` statements
@@ -1089,7 +1125,21 @@ where
         };
 
         match parse_result {
-            Ok(SqlQuery::Select(stmt)) => self.plan_prepare_select(stmt),
+            Ok(SqlQuery::Select(stmt)) => {
+                if Self::can_read_tables(
+                    self.upstream.as_mut(),
+                    &self.settings,
+                    &mut self.state.table_privileges,
+                    &stmt,
+                    self.noria.schema_search_path(),
+                )
+                .await
+                {
+                    self.plan_prepare_select(stmt)
+                } else {
+                    PrepareMeta::Proxy
+                }
+            }
             Ok(
                 query @ SqlQuery::Insert(_)
                 | query @ SqlQuery::Update(_)
@@ -1462,6 +1512,24 @@ where
         exec_meta: DB::ExecMeta<'_>,
     ) -> Result<QueryResult<'_, DB>, DB::Error> {
         self.last_query = None;
+        let can_read_tables = match self
+            .state
+            .prepared_statements
+            .get(id as _)
+            .and_then(|stmt| stmt.view_request.as_ref())
+        {
+            Some(view_request) => {
+                Self::can_read_tables(
+                    self.upstream.as_mut(),
+                    &self.settings,
+                    &mut self.state.table_privileges,
+                    &view_request.statement,
+                    &view_request.schema_search_path,
+                )
+                .await
+            }
+            None => true,
+        };
         let cached_statement = self
             .state
             .prepared_statements
@@ -1537,7 +1605,9 @@ where
         }
 
         let should_fallback = {
-            if cached_statement.always {
+            if !can_read_tables {
+                true
+            } else if cached_statement.always {
                 false
             } else {
                 let is_recovering = cached_statement.in_fallback_recovery(
@@ -1712,6 +1782,7 @@ where
     }
 
     /// Forwards a `CREATE CACHE` request to ReadySet
+    #[allow(clippy::too_many_arguments)]
     #[instrument(skip(self))]
     async fn create_cached_query(
         &mut self,
@@ -1720,6 +1791,9 @@ where
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
         concurrently: bool,
+        expiration: Option<CacheExpiration>,
+        memory_limit: Option<u64>,
+        priority: Option<CachePriority>,
     ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
         // If we have another query with the same name, drop that query first
         if let Some(name) = name {
@@ -1743,6 +1817,9 @@ where
                 override_schema_search_path,
                 always,
                 concurrently,
+                expiration,
+                memory_limit,
+                priority,
             )
             .await
         {
@@ -2156,6 +2233,9 @@ where
                 always,
                 concurrently,
                 unparsed_create_cache_statement,
+                expiration,
+                memory_limit,
+                priority,
             }) => {
                 let (stmt, search_path) = match inner {
                     Ok(CacheInner::Statement(st)) => Ok((*st.clone(), None)),
@@ -2205,7 +2285,16 @@ where
                 };
 
                 let res = self
-                    .create_cached_query(name.as_ref(), stmt, search_path, *always, *concurrently)
+                    .create_cached_query(
+                        name.as_ref(),
+                        stmt,
+                        search_path,
+                        *always,
+                        *concurrently,
+                        *expiration,
+                        *memory_limit,
+                        *priority,
+                    )
                     .await;
                 // The extend_recipe may have failed, in which case we should remove our intention
                 // to create this cache. Extend recipe waits a bit and then returns an
@@ -2808,12 +2897,20 @@ where
                 }
             }
             Ok(SqlQuery::Select(stmt)) => {
+                let can_read_tables = Self::can_read_tables(
+                    self.upstream.as_mut(),
+                    &self.settings,
+                    &mut self.state.table_privileges,
+                    &stmt,
+                    self.noria.schema_search_path(),
+                )
+                .await;
                 let mut view_request =
                     ViewCreateRequest::new(stmt, self.noria.schema_search_path().to_owned());
                 let (noria_should_try, status, processed_query_params) =
                     self.noria_should_try_select(&mut view_request);
                 let processed_query_params = processed_query_params?;
-                if noria_should_try {
+                if noria_should_try && can_read_tables {
                     event.sql_type = SqlQueryType::Read;
                     if self.settings.query_log_mode.allow_ad_hoc() {
                         event.query =
@@ -2910,6 +3007,80 @@ where
         self.settings.require_authentication
     }
 
+    /// Returns true if clients should be authenticated via
+    /// [`authenticate_with_upstream`](Self::authenticate_with_upstream) rather than by checking
+    /// against [`users`](Self::users)
+    pub fn does_authenticate_with_upstream(&self) -> bool {
+        self.settings.authenticate_with_upstream && self.upstream.is_some()
+    }
+
+    /// Authenticate a client by connecting to the upstream database as the given user with the
+    /// given credentials. On success, the connection to the upstream database used by this backend
+    /// is replaced with the new connection, so all queries proxied upstream run as that user.
+    ///
+    /// Returns an error if there is no upstream database, or if the upstream database rejects the
+    /// credentials.
+    pub async fn authenticate_with_upstream(
+        &mut self,
+        user: &str,
+        credentials: &DB::Credentials,
+    ) -> Result<(), DB::Error> {
+        let Some(upstream) = &mut self.upstream else {
+            return Err(ReadySetError::InvalidUpstreamDatabase.into());
+        };
+        self.state.table_privileges = None;
+        upstream.set_user(user, credentials).await
+    }
+
+    /// Look up the secret the upstream database stores to authenticate the given user, so that
+    /// clients can be authenticated on its behalf. See
+    /// [`UpstreamDatabase::authentication_secret`].
+    pub async fn upstream_authentication_secret(
+        &mut self,
+        user: &str,
+    ) -> Result<Option<String>, DB::Error> {
+        let Some(upstream) = &mut self.upstream else {
+            return Err(ReadySetError::InvalidUpstreamDatabase.into());
+        };
+        upstream.authentication_secret(user).await
+    }
+
+    /// Returns true if the user of this connection is allowed to read from every table referenced
+    /// by the given statement, per that user's privileges in the upstream database.
+    ///
+    /// Reads are only restricted if clients are authenticated with the upstream database -
+    /// otherwise, every connection runs as the same upstream user, and this always returns true.
+    /// Privileges are reloaded from the upstream database once they're older than the configured
+    /// refresh interval, and if loading them fails all tables are treated as unreadable.
+    async fn can_read_tables(
+        upstream: Option<&mut DB>,
+        settings: &BackendSettings,
+        table_privileges: &mut Option<TablePrivileges>,
+        stmt: &SelectStatement,
+        schema_search_path: &[SqlIdentifier],
+    ) -> bool {
+        let Some(upstream) = upstream.filter(|_| settings.authenticate_with_upstream) else {
+            return true;
+        };
+
+        if table_privileges.as_ref().map_or(true, |p| {
+            p.is_stale(settings.table_privileges_refresh_interval)
+        }) {
+            match upstream.table_privileges().await {
+                Ok(tables) => *table_privileges = Some(TablePrivileges::new(tables)),
+                Err(error) => {
+                    warn!(%error, "Failed to load table privileges from the upstream database");
+                    *table_privileges = None;
+                    return false;
+                }
+            }
+        }
+
+        table_privileges
+            .as_ref()
+            .map_or(false, |p| p.can_read_all(stmt, schema_search_path))
+    }
+
     /// Gets a list of all `CREATE CACHE ...` statements
     async fn explain_caches(&mut self) -> ReadySetResult<noria_connector::QueryResult<'static>> {
         let results: Vec<Vec<DfValue>> = self
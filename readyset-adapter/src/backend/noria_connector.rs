@@ -6,13 +6,13 @@ use std::sync::{atomic, Arc};
 
 use itertools::Itertools;
 use nom_sql::{
-    self, ColumnConstraint, DeleteStatement, DialectDisplay, Expr, InsertStatement, Relation,
-    SqlIdentifier, SqlQuery, UnaryOperator, UpdateStatement,
+    self, CacheExpiration, CachePriority, ColumnConstraint, DeleteStatement, DialectDisplay, Expr,
+    InsertStatement, Relation, SqlIdentifier, SqlQuery, UnaryOperator, UpdateStatement,
 };
 use readyset_client::consistency::Timestamp;
 use readyset_client::internal::LocalNodeIndex;
 use readyset_client::query::QueryId;
-use readyset_client::recipe::changelist::{Change, ChangeList, IntoChanges};
+use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache, IntoChanges};
 use readyset_client::recipe::CacheExpr;
 use readyset_client::results::{ResultIterator, Results};
 use readyset_client::{
@@ -499,6 +499,8 @@ impl NoriaConnector {
             ("size_bytes", DfType::BigInt),
             ("partial", DfType::Bool),
             ("indexes", DfType::Array(Box::new(DfType::DEFAULT_TEXT))),
+            ("memory_limit_bytes", DfType::BigInt),
+            ("priority", DfType::DEFAULT_TEXT),
         ];
         let schema = SelectSchema {
             columns: cols.iter().map(|(n, _)| n.into()).collect(),
@@ -533,6 +535,8 @@ impl NoriaConnector {
                         })
                         .collect::<Vec<DfValue>>()
                         .into(),
+                    mi.memory_limit.into(),
+                    mi.priority.map(|p| p.to_string()).into(),
                 ]
             })
             .collect();
@@ -946,6 +950,7 @@ impl NoriaConnector {
     /// Returns Ok(Some(id)) if CREATE CACHE CONCURRENTLY is issued, where id is a unique identifier
     /// that can be used to query the status of the migration. Otherwise, returns Ok(None) on
     /// success and Err(_) on failure.
+    #[allow(clippy::too_many_arguments)]
     pub async fn handle_create_cached_query(
         &mut self,
         name: Option<&Relation>,
@@ -953,6 +958,9 @@ impl NoriaConnector {
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
         concurrently: bool,
+        expiration: Option<CacheExpiration>,
+        memory_limit: Option<u64>,
+        priority: Option<CachePriority>,
     ) -> ReadySetResult<Option<u64>> {
         let name = name
             .cloned()
@@ -960,7 +968,14 @@ impl NoriaConnector {
         let schema_search_path =
             override_schema_search_path.unwrap_or_else(|| self.schema_search_path.clone());
         let changelist = ChangeList::from_change(
-            Change::create_cache(name.clone(), statement.clone(), always),
+            Change::CreateCache(CreateCache {
+                name: Some(name.clone()),
+                statement: Box::new(statement.clone()),
+                always,
+                expiration,
+                memory_limit,
+                priority,
+            }),
             self.dialect,
         )
         .with_schema_search_path(schema_search_path.clone());
@@ -13,6 +13,7 @@ pub mod proxied_queries_reporter;
 mod query_handler;
 pub mod query_status_cache;
 mod status_reporter;
+mod table_privileges;
 pub mod upstream_database;
 mod utils;
 pub mod views_synchronizer;
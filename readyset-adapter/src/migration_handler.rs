@@ -180,6 +180,9 @@ impl MigrationHandler {
                         Some(query.query().schema_search_path.clone()),
                         /* always */ false,
                         /* concurrently */ false,
+                        /* expiration */ None,
+                        /* memory_limit */ None,
+                        /* priority */ None,
                     )
                     .await;
                 // Inform the query status cache of completed migrations
@@ -302,6 +305,9 @@ impl MigrationHandler {
                 Some(view_request.schema_search_path.clone()),
                 false,
                 false,
+                None,
+                None,
+                None,
             )
             .await?;
         Ok(())
@@ -0,0 +1,226 @@
+//! Tracking of the tables in the upstream database which the user of a connection is allowed to
+//! read from.
+//!
+//! When clients are authenticated against the upstream database, each connection runs as a
+//! distinct upstream user - but caches are shared between all connections, so a cache created by
+//! one user could otherwise be used to read data from tables that another user has not been
+//! granted `SELECT` on. To prevent that, backends periodically load the privileges of their user
+//! from the upstream database, and refuse to serve reads from ReadySet for queries that reference
+//! tables the user cannot read.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use nom_sql::analysis::visit::{self, Visitor};
+use nom_sql::{CommonTableExpr, Relation, SelectStatement, SqlIdentifier, TableExpr};
+
+/// The tables in the upstream database visible to a particular user, along with whether that user
+/// is allowed to select from each of them, as of a particular point in time
+#[derive(Debug, Clone)]
+pub(crate) struct TablePrivileges {
+    /// Map from (schema-qualified) table, to whether the user can select from that table
+    tables: HashMap<Relation, bool>,
+    /// The time at which these privileges were loaded from the upstream database
+    loaded_at: Instant,
+}
+
+impl TablePrivileges {
+    pub(crate) fn new<I>(tables: I) -> Self
+    where
+        I: IntoIterator<Item = (Relation, bool)>,
+    {
+        Self {
+            tables: tables.into_iter().collect(),
+            loaded_at: Instant::now(),
+        }
+    }
+
+    /// Returns true if these privileges were loaded more than `refresh_interval` ago, and should
+    /// be loaded again
+    pub(crate) fn is_stale(&self, refresh_interval: Duration) -> bool {
+        self.loaded_at.elapsed() >= refresh_interval
+    }
+
+    /// Returns true if the user can select from the given table.
+    ///
+    /// Tables without a schema are resolved to the first schema in `schema_search_path` which
+    /// contains a table with that name. Tables that can't be found at all are treated as
+    /// unreadable.
+    pub(crate) fn can_read(&self, table: &Relation, schema_search_path: &[SqlIdentifier]) -> bool {
+        match &table.schema {
+            Some(_) => self.tables.get(table).copied().unwrap_or(false),
+            None => schema_search_path
+                .iter()
+                .find_map(|schema| {
+                    self.tables.get(&Relation {
+                        schema: Some(schema.clone()),
+                        name: table.name.clone(),
+                    })
+                })
+                .copied()
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns true if the user can select from every table referenced by the given statement,
+    /// including tables referenced in subqueries
+    pub(crate) fn can_read_all(
+        &self,
+        stmt: &SelectStatement,
+        schema_search_path: &[SqlIdentifier],
+    ) -> bool {
+        referenced_tables(stmt)
+            .iter()
+            .all(|table| self.can_read(table, schema_search_path))
+    }
+}
+
+#[derive(Default)]
+struct ReferencedTablesVisitor<'ast> {
+    tables: HashSet<&'ast Relation>,
+    /// The names of the common table expressions in scope at the current point in the statement
+    cte_names: Vec<&'ast SqlIdentifier>,
+}
+
+impl<'ast> Visitor<'ast> for ReferencedTablesVisitor<'ast> {
+    type Error = !;
+
+    fn visit_table_expr(&mut self, table_expr: &'ast TableExpr) -> Result<(), Self::Error> {
+        if let Some(table) = table_expr.inner.as_table() {
+            if table.schema.is_some() || !self.cte_names.contains(&&table.name) {
+                self.tables.insert(table);
+            }
+        }
+        visit::walk_table_expr(self, table_expr)
+    }
+
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast SelectStatement,
+    ) -> Result<(), Self::Error> {
+        // CTEs declared by this statement go out of scope once we're done with it
+        let outer_scope = self.cte_names.len();
+        if select_statement
+            .ctes
+            .iter()
+            .any(CommonTableExpr::is_recursive)
+        {
+            // Every CTE in a `WITH RECURSIVE` clause is in scope in the bodies of all of them
+            self.cte_names
+                .extend(select_statement.ctes.iter().map(|cte| &cte.name));
+        }
+        visit::walk_select_statement(self, select_statement)?;
+        self.cte_names.truncate(outer_scope);
+        Ok(())
+    }
+
+    fn visit_common_table_expr(&mut self, cte: &'ast CommonTableExpr) -> Result<(), Self::Error> {
+        visit::walk_common_table_expr(self, cte)?;
+        // A (non-recursive) CTE is only in scope after its own body: in the CTEs following it, and
+        // in the rest of the statement that declares it
+        self.cte_names.push(&cte.name);
+        Ok(())
+    }
+}
+
+/// Returns all the tables referenced in the FROM and JOIN clauses of the given statement or any of
+/// its subqueries, excluding references to common table expressions that are in scope where they
+/// are referenced
+fn referenced_tables(stmt: &SelectStatement) -> Vec<&Relation> {
+    let mut visitor = ReferencedTablesVisitor::default();
+    let Ok(()) = visitor.visit_select_statement(stmt);
+    visitor.tables.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_select_statement, Dialect};
+
+    use super::*;
+
+    fn privileges() -> TablePrivileges {
+        TablePrivileges::new(
+            [
+                ("public", "allowed", true),
+                ("public", "denied", false),
+                ("other", "allowed", true),
+            ]
+            .map(|(schema, name, can_select)| {
+                (
+                    Relation {
+                        schema: Some(schema.into()),
+                        name: name.into(),
+                    },
+                    can_select,
+                )
+            }),
+        )
+    }
+
+    fn can_read(query: &str) -> bool {
+        let stmt = parse_select_statement(Dialect::PostgreSQL, query).unwrap();
+        privileges().can_read_all(&stmt, &["public".into()])
+    }
+
+    #[test]
+    fn readable_tables() {
+        assert!(can_read("SELECT * FROM allowed"));
+        assert!(can_read("SELECT * FROM public.allowed"));
+        assert!(can_read(
+            "SELECT * FROM allowed JOIN other.allowed ON allowed.id = other.allowed.id"
+        ));
+    }
+
+    #[test]
+    fn unreadable_tables() {
+        assert!(!can_read("SELECT * FROM denied"));
+        assert!(!can_read(
+            "SELECT * FROM allowed JOIN denied ON allowed.id = denied.id"
+        ));
+        assert!(!can_read(
+            "SELECT * FROM allowed WHERE id IN (SELECT id FROM denied)"
+        ));
+        assert!(!can_read("SELECT * FROM (SELECT * FROM public.denied) sq"));
+    }
+
+    #[test]
+    fn unknown_tables_are_unreadable() {
+        assert!(!can_read("SELECT * FROM missing"));
+        assert!(!can_read("SELECT * FROM other.denied"));
+    }
+
+    #[test]
+    fn ctes_are_not_tables() {
+        assert!(can_read(
+            "WITH cte AS (SELECT * FROM allowed) SELECT * FROM cte"
+        ));
+        assert!(!can_read(
+            "WITH cte AS (SELECT * FROM denied) SELECT * FROM cte"
+        ));
+        assert!(can_read(
+            "WITH a AS (SELECT * FROM allowed), b AS (SELECT * FROM a) SELECT * FROM b"
+        ));
+        assert!(can_read(
+            "WITH cte AS (SELECT * FROM allowed) SELECT * FROM cte WHERE id IN (SELECT id FROM cte)"
+        ));
+    }
+
+    #[test]
+    fn ctes_do_not_shadow_tables_outside_their_scope() {
+        // A non-recursive CTE isn't in scope in its own body
+        assert!(!can_read(
+            "WITH denied AS (SELECT * FROM denied) SELECT * FROM denied"
+        ));
+        // ...nor in the bodies of the CTEs before it
+        assert!(!can_read(
+            "WITH a AS (SELECT * FROM denied), denied AS (SELECT * FROM allowed) SELECT * FROM a"
+        ));
+    }
+
+    #[test]
+    fn staleness() {
+        let privileges = privileges();
+        assert!(!privileges.is_stale(Duration::from_secs(60)));
+        assert!(privileges.is_stale(Duration::ZERO));
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 pub use database_utils::UpstreamConfig;
-use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use nom_sql::{Relation, SqlIdentifier, StartTransactionStatement};
 use readyset_client_metrics::QueryDestination;
 use readyset_data::DfValue;
 use readyset_errors::ReadySetError;
@@ -86,6 +86,10 @@ pub trait UpstreamDatabase: Sized + Send {
     /// noria_client backend.
     type Error: From<ReadySetError> + IsFatalError + Error + Send + Sync + 'static;
 
+    /// Credentials used to authenticate with this database as a particular user, such as a
+    /// password
+    type Credentials: Clone + Send + Sync + 'static;
+
     /// When there's no upstream DB to fetch the version from, default to this value. This features
     /// is only used for tests
     const DEFAULT_DB_VERSION: &'static str;
@@ -98,6 +102,36 @@ pub trait UpstreamDatabase: Sized + Send {
     /// Connect will return an error if the upstream database is running an unsupported version.
     async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Self::Error>;
 
+    /// Create a new connection to this upstream database, authenticating as the given user with
+    /// the given credentials rather than with the credentials included in the upstream database
+    /// URL
+    async fn connect_as(
+        upstream_config: UpstreamConfig,
+        user: &str,
+        credentials: &Self::Credentials,
+    ) -> Result<Self, Self::Error>;
+
+    /// Replace this connection to the upstream database with a new connection, authenticated as
+    /// the given user with the given credentials.
+    ///
+    /// This is used to authenticate clients against the upstream database, so that queries proxied
+    /// upstream on behalf of a client run as that client's user. Returns an error if the upstream
+    /// database rejects the credentials.
+    async fn set_user(
+        &mut self,
+        _user: &str,
+        _credentials: &Self::Credentials,
+    ) -> Result<(), Self::Error> {
+        Err(ReadySetError::Unsupported("Changing the user of an upstream connection".into()).into())
+    }
+
+    /// Query the upstream database for the secret it stores to authenticate the given user, if
+    /// any, such as a password hash. Returns `None` if the user doesn't exist, or if the current
+    /// user isn't allowed to read the secret.
+    async fn authentication_secret(&mut self, _user: &str) -> Result<Option<String>, Self::Error> {
+        Ok(None)
+    }
+
     /// Test the connection with the upstream database
     async fn is_connected(&mut self) -> Result<bool, Self::Error>;
 
@@ -180,18 +214,32 @@ pub trait UpstreamDatabase: Sized + Send {
     /// supports a multi-element schema search path, the concept of "currently connected database"
     /// in MySQL can be thought of as a schema search path that only has one element
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error>;
+
+    /// Query the upstream database for all tables visible to the current user, along with whether
+    /// that user is allowed to `SELECT` from each of them
+    async fn table_privileges(&mut self) -> Result<Vec<(Relation, bool)>, Self::Error>;
 }
 
-pub struct LazyUpstream<U> {
+pub struct LazyUpstream<U>
+where
+    U: UpstreamDatabase,
+{
     upstream: Option<U>,
     upstream_config: UpstreamConfig,
+    /// Username and credentials to connect as, overriding the credentials in the upstream
+    /// database URL
+    credentials: Option<(String, U::Credentials)>,
 }
 
-impl<U> From<UpstreamConfig> for LazyUpstream<U> {
+impl<U> From<UpstreamConfig> for LazyUpstream<U>
+where
+    U: UpstreamDatabase,
+{
     fn from(upstream_config: UpstreamConfig) -> Self {
         Self {
             upstream: None,
             upstream_config,
+            credentials: None,
         }
     }
 }
@@ -202,7 +250,12 @@ where
 {
     async fn connect(&mut self) -> Result<(), U::Error> {
         debug!("LazyUpstream connecting to upstream");
-        self.upstream = Some(U::connect(self.upstream_config.clone()).await?);
+        self.upstream = Some(match &self.credentials {
+            Some((user, credentials)) => {
+                U::connect_as(self.upstream_config.clone(), user, credentials).await?
+            }
+            None => U::connect(self.upstream_config.clone()).await?,
+        });
         Ok(())
     }
 
@@ -225,6 +278,7 @@ where
     type PrepareData<'a> = U::PrepareData<'a>;
     type ExecMeta<'a> = U::ExecMeta<'a>;
     type Error = U::Error;
+    type Credentials = U::Credentials;
 
     const DEFAULT_DB_VERSION: &'static str = U::DEFAULT_DB_VERSION;
     const SQL_DIALECT: nom_sql::Dialect = U::SQL_DIALECT;
@@ -233,9 +287,38 @@ where
         Ok(Self {
             upstream: None,
             upstream_config,
+            credentials: None,
         })
     }
 
+    async fn connect_as(
+        upstream_config: UpstreamConfig,
+        user: &str,
+        credentials: &Self::Credentials,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            upstream: None,
+            upstream_config,
+            credentials: Some((user.to_owned(), credentials.clone())),
+        })
+    }
+
+    /// Unlike the rest of the methods on [`LazyUpstream`], this connects to the upstream database
+    /// eagerly, so that invalid credentials are rejected immediately
+    async fn set_user(
+        &mut self,
+        user: &str,
+        credentials: &Self::Credentials,
+    ) -> Result<(), Self::Error> {
+        self.credentials = Some((user.to_owned(), credentials.clone()));
+        self.upstream = None;
+        self.connect().await
+    }
+
+    async fn authentication_secret(&mut self, user: &str) -> Result<Option<String>, Self::Error> {
+        self.upstream().await?.authentication_secret(user).await
+    }
+
     async fn is_connected(&mut self) -> Result<bool, Self::Error> {
         Ok(self.upstream().await?.is_connected().await?)
     }
@@ -317,4 +400,8 @@ where
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
         self.upstream().await?.schema_search_path().await
     }
+
+    async fn table_privileges(&mut self) -> Result<Vec<(Relation, bool)>, Self::Error> {
+        self.upstream().await?.table_privileges().await
+    }
 }
@@ -14,7 +14,7 @@ use petgraph::graph::NodeIndex;
 use readyset_errors::{
     internal, internal_err, rpc_err, rpc_err_no_downcast, ReadySetError, ReadySetResult,
 };
-use replication_offset::ReplicationOffsets;
+use replication_offset::{ReplicationOffsets, SnapshotProgress};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
@@ -798,6 +798,24 @@ impl ReadySetHandle {
         ) -> ()
     );
 
+    simple_request!(
+        /// Get the chunks copied so far of all tables that are being snapshotted in chunks.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        snapshot_progress() -> HashMap<Relation, SnapshotProgress>
+    );
+
+    simple_request!(
+        /// Set the chunks copied so far of a table that is being snapshotted in chunks, or clear
+        /// them if `progress` is `None`.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        set_snapshot_progress(
+            table: &Relation,
+            progress: Option<&SnapshotProgress>,
+        ) -> ()
+    );
+
     simple_request!(
         /// Fetch a graphviz description of the dataflow graph.
         ///
@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display};
 use std::ops::{AddAssign, Deref};
 
-use nom_sql::Relation;
+use nom_sql::{CachePriority, Relation};
 use petgraph::graph::NodeIndex;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -74,6 +74,11 @@ pub struct MaterializationInfo {
     pub partial: bool,
     /// Set of ways the materialization is indexed
     pub indexes: HashSet<Index>,
+    /// The per-cache memory limit configured for the materialization, in bytes, if any. Only set
+    /// for readers.
+    pub memory_limit: Option<usize>,
+    /// The eviction priority of the materialization. Only set for readers.
+    pub priority: Option<CachePriority>,
 }
 
 impl Display for KeyCount {
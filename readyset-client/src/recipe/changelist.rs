@@ -37,9 +37,9 @@ use std::hash::{Hash, Hasher};
 use dataflow_expression::Dialect;
 use nom_locate::LocatedSpan;
 use nom_sql::{
-    AlterTableStatement, CacheInner, CreateCacheStatement, CreateTableStatement,
-    CreateViewStatement, DropTableStatement, DropViewStatement, NonReplicatedRelation, Relation,
-    SelectStatement, SqlIdentifier, SqlQuery,
+    AlterTableStatement, CacheExpiration, CacheInner, CachePriority, CreateCacheStatement,
+    CreateTableStatement, CreateViewStatement, DropTableStatement, DropViewStatement,
+    NonReplicatedRelation, Relation, SelectStatement, SqlIdentifier, SqlQuery,
 };
 use readyset_data::DfType;
 use readyset_errors::{internal, unsupported, ReadySetError, ReadySetResult};
@@ -201,6 +201,9 @@ impl ChangeList {
                                 name,
                                 inner,
                                 always,
+                                expiration,
+                                memory_limit,
+                                priority,
                                 ..
                             }) => {
                                 let statement = match inner {
@@ -223,6 +226,9 @@ impl ChangeList {
                                     name,
                                     statement,
                                     always,
+                                    expiration,
+                                    memory_limit,
+                                    priority,
                                 }))
                             }
                             SqlQuery::AlterTable(ats) => changes.push(Change::AlterTable(ats)),
@@ -327,6 +333,13 @@ pub struct CreateCache {
     /// If set to `true`, execution of this cache will bypass transaction handling in the
     /// adapter
     pub always: bool,
+    /// If set, keys in the cache expire after a bounded amount of time, and are either evicted
+    /// or refreshed
+    pub expiration: Option<CacheExpiration>,
+    /// If set, the maximum number of bytes of state the reader for this cache may hold
+    pub memory_limit: Option<u64>,
+    /// The priority of this cache relative to other caches when evicting state
+    pub priority: Option<CachePriority>,
 }
 
 /// Metadata about a PostgreSQL table
@@ -422,6 +435,9 @@ impl Change {
             name: Some(name.into()),
             statement: Box::new(statement),
             always,
+            expiration: None,
+            memory_limit: None,
+            priority: None,
         })
     }
 
@@ -512,6 +528,9 @@ impl Change {
                         name,
                         inner,
                         always,
+                        expiration,
+                        memory_limit,
+                        priority,
                         ..
                     }) => {
                         let mut statement = match inner {
@@ -538,6 +557,9 @@ impl Change {
                             name,
                             statement,
                             always,
+                            expiration,
+                            memory_limit,
+                            priority,
                         })
                     }
                     SqlQuery::DropCache(dcs) => Change::Drop {
@@ -691,6 +713,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cache_ddl_request_preserves_expiration() {
+        let ddl_req = CacheDDLRequest {
+            unparsed_stmt: "CREATE CACHE q_0 WITH TTL 30s FROM SELECT a FROM b".into(),
+            schema_search_path: vec![],
+            dialect: Dialect::DEFAULT_MYSQL,
+        };
+
+        let Change::CreateCache(cc) = Change::from_cache_ddl_request(&ddl_req, false).unwrap()
+        else {
+            panic!("Expected a CreateCache change");
+        };
+        assert_eq!(
+            cc.expiration,
+            Some(CacheExpiration::Ttl(std::time::Duration::from_secs(30)))
+        );
+    }
+
+    #[test]
+    fn cache_ddl_request_preserves_memory_options() {
+        let ddl_req = CacheDDLRequest {
+            unparsed_stmt:
+                "CREATE CACHE q_0 WITH (MEMORY LIMIT 512MB, PRIORITY HIGH) FROM SELECT a FROM b"
+                    .into(),
+            schema_search_path: vec![],
+            dialect: Dialect::DEFAULT_MYSQL,
+        };
+
+        let Change::CreateCache(cc) = Change::from_cache_ddl_request(&ddl_req, false).unwrap()
+        else {
+            panic!("Expected a CreateCache change");
+        };
+        assert_eq!(cc.memory_limit, Some(512 * 1024 * 1024));
+        assert_eq!(cc.priority, Some(CachePriority::High));
+    }
+
     mod requires_resnapshot {
         use super::*;
 
@@ -5,7 +5,10 @@ pub mod changelist;
 use std::borrow::Cow;
 use std::fmt::Display;
 
-use nom_sql::{CacheInner, CreateCacheStatement, DialectDisplay, Relation, SelectStatement};
+use nom_sql::{
+    CacheExpiration, CacheInner, CachePriority, CreateCacheStatement, DialectDisplay, Relation,
+    SelectStatement,
+};
 use readyset_errors::ReadySetError;
 use readyset_util::fmt::fmt_with;
 use serde::{Deserialize, Serialize};
@@ -100,6 +103,9 @@ pub struct CacheExpr {
     pub name: Relation,
     pub statement: SelectStatement,
     pub always: bool,
+    pub expiration: Option<CacheExpiration>,
+    pub memory_limit: Option<u64>,
+    pub priority: Option<CachePriority>,
     pub query_id: QueryId,
 }
 
@@ -109,6 +115,9 @@ impl From<CacheExpr> for CreateCacheStatement {
             name: Some(value.name),
             inner: Ok(CacheInner::Statement(Box::new(value.statement))),
             always: value.always,
+            expiration: value.expiration,
+            memory_limit: value.memory_limit,
+            priority: value.priority,
             // CacheExpr represents a migrated query, and the below fields are not relevant for an
             // already-migrated query
             concurrently: false,
@@ -1,13 +1,15 @@
 use std::cmp::Ordering;
+use std::mem;
 use std::sync::Arc;
 
 use dataflow_expression::{Expr, PostLookup, PostLookupAggregates};
-use nom_sql::OrderType;
+use nom_sql::{NullOrder, OrderType};
 use readyset_data::DfValue;
 use readyset_util::nonmaxusize::NonMaxUsize;
 use smallvec::SmallVec;
 use streaming_iterator::StreamingIterator;
 use tournament_kway::{Comparator, StreamingTournament};
+use tracing::warn;
 
 use crate::ReadReplyStats;
 
@@ -123,27 +125,39 @@ struct MergeIterator {
 
 #[derive(Clone, Debug)]
 struct RowComparator {
-    order_by: Arc<[(usize, OrderType)]>,
+    order_by: Arc<[(usize, OrderType, NullOrder)]>,
 }
 
-impl<T> Comparator<[T]> for RowComparator
-where
-    T: Ord,
-{
-    fn cmp(&self, a: &[T], b: &[T]) -> Ordering {
-        self.order_by
-            .iter()
-            .map(|&(idx, order_type)| order_type.apply(a[idx].cmp(&b[idx])))
-            .fold(Ordering::Equal, |acc, next| acc.then(next))
+impl Comparator<[DfValue]> for RowComparator {
+    fn cmp(&self, a: &[DfValue], b: &[DfValue]) -> Ordering {
+        cmp_rows(&self.order_by, a, b)
     }
 }
 
+/// Compare two rows by the given list of column indices, order types, and null orders
+fn cmp_rows(order_by: &[(usize, OrderType, NullOrder)], a: &[DfValue], b: &[DfValue]) -> Ordering {
+    order_by
+        .iter()
+        .map(|&(idx, order_type, null_order)| {
+            null_order.apply(
+                a[idx].is_none(),
+                b[idx].is_none(),
+                order_type.apply(a[idx].cmp(&b[idx])),
+            )
+        })
+        .fold(Ordering::Equal, |acc, next| acc.then(next))
+}
+
 #[derive(Debug)]
 struct AggregateIterator {
     inner: Box<ResultIteratorInner>,
     aggregate: PostLookupAggregates,
     out_row: Option<Vec<DfValue>>,
     filter: Option<Expr>,
+    /// Expressions to project the aggregated rows through
+    projection: Option<Vec<Expr>>,
+    /// A filter expression to ignore aggregated (and projected) rows that don't match
+    residual_filter: Option<Expr>,
 }
 
 impl ResultIterator {
@@ -166,7 +180,8 @@ impl ResultIterator {
             returned_cols,
             aggregates,
             default_row,
-            ..
+            projection,
+            filter: residual_filter,
         } = post_lookup;
 
         let limit = adapter_limit.or(*limit); // Limit specifies total number of results to return
@@ -194,6 +209,8 @@ impl ResultIterator {
                         out_row: None,
                         aggregate: aggregates.clone(),
                         filter: filter.take(),
+                        projection: projection.clone(),
+                        residual_filter: residual_filter.clone(),
                     })
                 } else {
                     // With group by, merge results using a k-way merge iterator on group-by
@@ -202,7 +219,7 @@ impl ResultIterator {
                         order_by: aggregates
                             .group_by
                             .iter()
-                            .map(|&col| (col, OrderType::OrderAscending))
+                            .map(|&col| (col, OrderType::OrderAscending, NullOrder::NullsFirst))
                             .collect(),
                     };
 
@@ -217,6 +234,8 @@ impl ResultIterator {
                         out_row: None,
                         aggregate: aggregates.clone(),
                         filter: filter.take(),
+                        projection: projection.clone(),
+                        residual_filter: residual_filter.clone(),
                     })
                 }
             }
@@ -228,7 +247,7 @@ impl ResultIterator {
                     order_by: aggregates
                         .group_by
                         .iter()
-                        .map(|&col| (col, OrderType::OrderAscending))
+                        .map(|&col| (col, OrderType::OrderAscending, NullOrder::NullsFirst))
                         .collect(),
                 };
 
@@ -244,8 +263,12 @@ impl ResultIterator {
                         out_row: None,
                         aggregate: aggregates.clone(),
                         filter: filter.take(),
+                        projection: projection.clone(),
+                        residual_filter: residual_filter.clone(),
                     }),
-                    limit,
+                    // The results are only in the requested order once they're sorted below, so
+                    // both limit and offset have to be applied afterwards
+                    limit: None,
                     offset: None,
                     default_row: default_row.clone(),
                     non_empty: false,
@@ -254,20 +277,18 @@ impl ResultIterator {
                 };
 
                 let mut results = temp_iter.into_vec();
-                results.sort_by(|a, b| {
-                    order_by
-                        .iter()
-                        .map(|&(idx, order_type)| order_type.apply(a[idx].cmp(&b[idx])))
-                        .fold(Ordering::Equal, |acc, next| acc.then(next))
-                });
+                results.sort_by(|a, b| cmp_rows(order_by, a, b));
 
                 if let Some(offset) = offset {
                     if offset >= results.len() {
                         results.clear();
                     } else {
-                        results.drain(offset..);
+                        results.drain(..offset);
                     }
                 }
+                if let Some(limit) = limit {
+                    results.truncate(limit);
+                }
 
                 return ResultIterator::owned(vec![Results {
                     results,
@@ -491,37 +512,80 @@ impl StreamingIterator for AggregateIterator {
             self.advance_filtered();
         }
 
-        let mut aggregate_row = match self.inner.get() {
-            Some(row) => row.to_vec(),
-            None => {
-                self.out_row = None;
-                return;
-            }
-        };
+        loop {
+            let mut aggregate_row = match self.inner.get() {
+                Some(row) => row.to_vec(),
+                None => {
+                    self.out_row = None;
+                    return;
+                }
+            };
 
-        self.advance_filtered();
-        while let Some(row) = self.inner.get() {
-            if self
-                .aggregate
-                .group_by
-                .iter()
-                .any(|&i| aggregate_row.get(i) != row.get(i))
-            {
-                break;
+            self.advance_filtered();
+            while let Some(row) = self.inner.get() {
+                if self
+                    .aggregate
+                    .group_by
+                    .iter()
+                    .any(|&i| aggregate_row.get(i) != row.get(i))
+                {
+                    break;
+                }
+
+                for agg in &self.aggregate.aggregates {
+                    let col = agg.column;
+                    aggregate_row[col] = agg
+                        .function
+                        .apply(&aggregate_row[col], &row[col])
+                        .expect("no fail");
+                }
+
+                self.advance_filtered();
             }
 
             for agg in &self.aggregate.aggregates {
-                let col = agg.column;
-                aggregate_row[col] = agg
-                    .function
-                    .apply(&aggregate_row[col], &row[col])
-                    .expect("no fail");
+                if agg.function.needs_finalize() {
+                    // As with the projection below, we can't return errors from here, so treat
+                    // aggregates that fail to finalize (such as a malformed sketch) as NULL
+                    let col = agg.column;
+                    aggregate_row[col] = agg
+                        .function
+                        .finalize(mem::take(&mut aggregate_row[col]), &aggregate_row)
+                        .unwrap_or_else(|error| {
+                            warn!(%error, "Failed to finalize post-lookup aggregate");
+                            DfValue::None
+                        });
+                }
             }
 
-            self.advance_filtered();
-        }
+            if let Some(projection) = &self.projection {
+                // We can't return errors from here, so (as with the filters) treat expressions
+                // that fail to evaluate for this row as NULL
+                aggregate_row = projection
+                    .iter()
+                    .map(|expr| {
+                        expr.eval(&aggregate_row).unwrap_or_else(|error| {
+                            warn!(%error, "Failed to evaluate post-lookup projection");
+                            DfValue::None
+                        })
+                    })
+                    .collect();
+            }
 
-        self.out_row = Some(aggregate_row)
+            if let Some(filter) = &self.residual_filter {
+                if !filter
+                    .eval(&aggregate_row)
+                    .map(|r| r.is_truthy())
+                    .unwrap_or(false)
+                {
+                    // This group doesn't pass the filter, so move on to the next one
+                    continue;
+                }
+            }
+
+            self.out_row = Some(aggregate_row);
+            return;
+        }
     }
 
     #[inline(always)]
@@ -481,7 +481,7 @@ impl DeploymentBuilder {
 
         // Depending on what type of upstream we have, use the mysql or posgresql host/ports.
         let (user, pass, host, port) = match database_type {
-            DatabaseType::MySQL => (
+            DatabaseType::MySQL | DatabaseType::MariaDB => (
                 env.mysql_user,
                 env.mysql_password,
                 env.mysql_host,
@@ -795,7 +795,7 @@ impl DeploymentBuilder {
             );
             upstream_addr = Some(format!("{}/{}", &root_addr, &self.name));
             match self.database_type {
-                DatabaseType::MySQL => {
+                DatabaseType::MySQL | DatabaseType::MariaDB => {
                     let opts = mysql_async::Opts::from_url(&root_addr).unwrap();
                     let mut conn = mysql_async::Conn::new(opts).await.unwrap();
                     conn.query_drop(format!(
@@ -1435,7 +1435,7 @@ impl DeploymentHandle {
         }
 
         // Clean up the existing mysql state.
-        if let (Some(upstream_mysql_addr), DatabaseType::MySQL) =
+        if let (Some(upstream_mysql_addr), DatabaseType::MySQL | DatabaseType::MariaDB) =
             (&self.upstream_addr, &self.database_type)
         {
             let opts = mysql_async::Opts::from_url(upstream_mysql_addr).unwrap();
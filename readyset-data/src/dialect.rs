@@ -1,5 +1,6 @@
 use std::fmt;
 
+use nom_sql::{NullOrder, OrderType};
 use serde::{Deserialize, Serialize};
 
 use crate::DfType;
@@ -84,6 +85,23 @@ impl Dialect {
         }
     }
 
+    /// Returns the position of nulls in the result set for an `ORDER BY` with the given
+    /// [`OrderType`] and no explicit `NULLS FIRST` or `NULLS LAST`.
+    pub fn default_null_order(self, order_type: OrderType) -> NullOrder {
+        match (self.engine, order_type) {
+            // https://dev.mysql.com/doc/refman/8.0/en/working-with-null.html
+            // "NULL values are presented first if you do ORDER BY ... ASC and last if you do
+            // ORDER BY ... DESC."
+            (SqlEngine::MySQL, OrderType::OrderAscending) => NullOrder::NullsFirst,
+            (SqlEngine::MySQL, OrderType::OrderDescending) => NullOrder::NullsLast,
+
+            // https://www.postgresql.org/docs/current/queries-order.html
+            // "By default, null values sort as if larger than any non-null value"
+            (SqlEngine::PostgreSQL, OrderType::OrderAscending) => NullOrder::NullsLast,
+            (SqlEngine::PostgreSQL, OrderType::OrderDescending) => NullOrder::NullsFirst,
+        }
+    }
+
     /// Return the [`DfType`] corresponding to the SQL `FLOAT` type for this dialect
     pub(crate) fn float_type(&self) -> DfType {
         match self.engine {
@@ -1,6 +1,7 @@
 pub(crate) mod channel;
 mod domain_metrics;
 mod replay_paths;
+mod timed_purges;
 
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -44,6 +45,7 @@ use vec1::Vec1;
 
 pub(crate) use self::replay_paths::ReplayPath;
 use self::replay_paths::{Destination, ReplayPathSpec, ReplayPaths, Target};
+use self::timed_purges::{TimedPurge, TimedPurges};
 use crate::domain::channel::{ChannelCoordinator, DomainReceiver, DomainSender};
 use crate::node::special::EgressTx;
 use crate::node::{NodeProcessingResult, ProcessEnv};
@@ -397,6 +399,7 @@ impl DomainBuilder {
         readers: Readers,
         channel_coordinator: Arc<ChannelCoordinator>,
         state_size: Arc<AtomicUsize>,
+        over_budget_size: Arc<AtomicUsize>,
         init_state_tx: Sender<MaterializedState>,
     ) -> Domain {
         // initially, all nodes are not ready
@@ -443,6 +446,7 @@ impl DomainBuilder {
             delayed_for_self: Default::default(),
 
             state_size,
+            over_budget_size,
             total_time: Timer::new(),
             total_ptime: Timer::new(),
             wait_time: Timer::new(),
@@ -463,13 +467,6 @@ impl DomainBuilder {
     }
 }
 
-#[derive(Clone, Debug)]
-struct TimedPurge {
-    time: time::Instant,
-    view: LocalNodeIndex,
-    keys: HashSet<KeyComparison>,
-}
-
 /// Mapping, for nodes which [generate columns][], from *upstream* keys, to downstream keys which
 /// have remapped to those upstream keys.
 ///
@@ -642,7 +639,7 @@ pub struct Domain {
     ///
     /// * Each node referenced by a `view` of a TimedPurge must be in `self.nodes`
     /// * Each node referenced by a `view` of a TimedPurge must be a reader node
-    timed_purges: VecDeque<TimedPurge>,
+    timed_purges: TimedPurges,
 
     readers: Readers,
     channel_coordinator: Arc<ChannelCoordinator>,
@@ -650,6 +647,9 @@ pub struct Domain {
     delayed_for_self: VecDeque<Packet>,
 
     state_size: Arc<AtomicUsize>,
+    /// The total number of bytes by which readers in this domain exceed their configured
+    /// per-cache memory limits, as of the last call to [`Self::update_state_sizes`]
+    over_budget_size: Arc<AtomicUsize>,
     total_time: Timer<SimpleTracker, RealTime>,
     total_ptime: Timer<SimpleTracker, ThreadTime>,
     wait_time: Timer<SimpleTracker, RealTime>,
@@ -2503,30 +2503,25 @@ impl Domain {
     }
 
     /// Timed purges happen when [`FrontierStrategy`] is not None, in which case all keys
-    /// are purged from the node after a given amount of time
+    /// are purged from the node after a given amount of time, or for readers with a
+    /// [`CacheExpiration`](nom_sql::CacheExpiration), in which case keys are purged once they
+    /// expire (and recomputed the next time they're read).
     fn handle_timed_purges(&mut self) -> ReadySetResult<()> {
         let mut swap = HashSet::new();
-        while let Some(tp) = self.timed_purges.front() {
-            let now = time::Instant::now();
-            if tp.time <= now {
-                #[allow(clippy::unwrap_used)]
-                // we know it's Some because we check at the head of the while
-                let tp = self.timed_purges.pop_front().unwrap();
-                #[allow(clippy::indexing_slicing)]
-                // nodes in tp.view must reference nodes in self
-                let node = self.nodes[tp.view].borrow_mut();
-                trace!(
-                    node = node.global_addr().index(),
-                    "eagerly purging state from reader"
-                );
-                if let Some(wh) = self.reader_write_handles.get_mut(tp.view) {
-                    for key in tp.keys {
-                        wh.mark_hole(&key)?;
-                    }
-                    swap.insert(tp.view);
+        let now = time::Instant::now();
+        while let Some(tp) = self.timed_purges.pop_expired(now) {
+            #[allow(clippy::indexing_slicing)]
+            // nodes in tp.view must reference nodes in self
+            let node = self.nodes[tp.view].borrow_mut();
+            trace!(
+                node = node.global_addr().index(),
+                "eagerly purging state from reader"
+            );
+            if let Some(wh) = self.reader_write_handles.get_mut(tp.view) {
+                for key in tp.keys {
+                    wh.mark_hole(&key)?;
                 }
-            } else {
-                break;
+                swap.insert(tp.view);
             }
         }
 
@@ -3534,19 +3529,32 @@ impl Domain {
                 }
                 ReplayPieceContext::Partial { for_keys, .. } => {
                     if dst_is_reader {
-                        if self
-                            .nodes
-                            .get(dst)
-                            .ok_or_else(|| ReadySetError::NoSuchNode(dst.id()))?
-                            .borrow()
-                            .beyond_mat_frontier()
-                        {
+                        let (beyond_mat_frontier, expiration) = {
+                            let n = self
+                                .nodes
+                                .get(dst)
+                                .ok_or_else(|| ReadySetError::NoSuchNode(dst.id()))?
+                                .borrow();
+                            (
+                                n.beyond_mat_frontier(),
+                                n.as_reader().and_then(|r| r.expiration()),
+                            )
+                        };
+                        if beyond_mat_frontier {
                             // make sure we eventually evict these from here
-                            self.timed_purges.push_back(TimedPurge {
+                            self.timed_purges.schedule(TimedPurge {
                                 time: time::Instant::now() + time::Duration::from_millis(50),
                                 keys: for_keys,
                                 view: dst,
                             });
+                        } else if let Some(expiration) = expiration {
+                            // the reader is for a cache with a TTL, so make sure we evict these
+                            // once they expire
+                            self.timed_purges.schedule(TimedPurge {
+                                time: time::Instant::now() + expiration.duration(),
+                                keys: for_keys,
+                                view: dst,
+                            });
                         }
                         assert_ne!(finished_partial, 0);
                     } else if !dst_is_sender {
@@ -3971,6 +3979,8 @@ impl Domain {
                 .filter_map(|nd| {
                     let n = &*nd.borrow();
                     let local_index = n.local_addr();
+                    // Only readers can be configured with a priority
+                    let priority = n.as_reader().map(|r| r.priority()).unwrap_or_default();
 
                     if let Some(wh) = reader_write_handles.get(local_index) {
                         if wh.is_partial() {
@@ -3984,12 +3994,27 @@ impl Domain {
                             .filter(|state| state.is_partial())
                             .map(|state| state.deep_size_of())
                     }
-                    .map(|s| (local_index, s))
+                    .map(|s| (local_index, s, priority))
                 })
-                .filter(|&(_, s)| s > 0)
-                .map(|(x, s)| (x, s as usize))
+                .filter(|&(_, s, _)| s > 0)
+                .map(|(x, s, p)| (x, s as usize, p))
                 .collect();
 
+            // only evict from higher-priority nodes if the lower-priority nodes don't hold enough
+            // state to satisfy the eviction on their own
+            candidates.sort_unstable_by_key(|&(_, _, p)| p);
+            let mut cumulative = 0;
+            let cutoff = candidates
+                .iter()
+                .find(|&&(_, s, _)| {
+                    cumulative += s;
+                    cumulative >= *num_bytes
+                })
+                .or(candidates.last())
+                .map(|&(_, _, p)| p);
+            candidates.retain(|&(_, _, p)| Some(p) <= cutoff);
+            let mut candidates: Vec<_> = candidates.into_iter().map(|(x, s, _)| (x, s)).collect();
+
             // we want to spread the eviction across the nodes,
             // rather than emptying out one node completely.
             // -1* so we sort in descending order
@@ -4107,6 +4132,39 @@ impl Domain {
                 self.metrics.rec_eviction_time(start.elapsed(), total_freed);
                 None
             }
+            EvictRequest::OverBudget => {
+                let start = std::time::Instant::now();
+                self.metrics.inc_eviction_requests();
+
+                let mut total_freed = 0;
+                for (node, state) in self.reader_write_handles.iter_mut() {
+                    #[allow(clippy::indexing_slicing)] // reader write handles are for our nodes
+                    let n = self.nodes[node].borrow();
+                    let Some(limit) = n.as_reader().and_then(|r| r.memory_limit()) else {
+                        continue;
+                    };
+                    if n.is_dropped() || !state.is_partial() {
+                        continue;
+                    }
+
+                    let size = state.deep_size_of() as usize;
+                    if size <= limit {
+                        continue;
+                    }
+
+                    let freed = state.evict_bytes(size - limit);
+                    state.swap();
+                    state.notify_readers_of_eviction()?;
+
+                    debug!(%freed, %limit, node = ?n, "evicted from reader over its memory limit");
+                    self.state_size.fetch_sub(freed as usize, Ordering::AcqRel);
+                    total_freed += freed;
+                }
+                self.over_budget_size.store(0, Ordering::Release);
+
+                self.metrics.rec_eviction_time(start.elapsed(), total_freed);
+                None
+            }
             EvictRequest::Keys {
                 link: Link { dst, .. },
                 keys,
@@ -4307,6 +4365,7 @@ impl Domain {
 
     pub fn update_state_sizes(&mut self) {
         let mut reader_size: u64 = 0;
+        let mut over_budget: usize = 0;
         let total: u64 = self
             .nodes
             .values()
@@ -4321,6 +4380,9 @@ impl Domain {
                         if wh.is_partial() {
                             size = wh.deep_size_of();
                             reader_size += size;
+                            if let Some(limit) = n.as_reader().and_then(|r| r.memory_limit()) {
+                                over_budget += (size as usize).saturating_sub(limit);
+                            }
                         }
                     }
                     size
@@ -4353,6 +4415,7 @@ impl Domain {
         );
 
         self.state_size.store(total as usize, Ordering::Release);
+        self.over_budget_size.store(over_budget, Ordering::Release);
         // no response sent, as worker will read the atomic
     }
 
@@ -4416,14 +4479,7 @@ impl Domain {
     /// to happen
     pub fn next_poll_duration(&mut self) -> Option<time::Duration> {
         // when do we need to be woken up again?
-        let now = time::Instant::now();
-        self.timed_purges.front().map(|tp| {
-            if tp.time > now {
-                tp.time - now
-            } else {
-                time::Duration::from_millis(0)
-            }
-        })
+        self.timed_purges.next_poll_duration(time::Instant::now())
     }
 
     /// Handle a single message for this domain
@@ -0,0 +1,128 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use readyset_client::internal::LocalNodeIndex;
+use readyset_client::KeyComparison;
+
+/// A set of keys to purge from a reader node once a given time has passed
+#[derive(Clone, Debug)]
+pub(super) struct TimedPurge {
+    pub(super) time: Instant,
+    pub(super) view: LocalNodeIndex,
+    pub(super) keys: HashSet<KeyComparison>,
+}
+
+/// A queue of [`TimedPurge`]s, kept sorted by the time at which each purge needs to happen.
+///
+/// All methods take the current time explicitly, rather than reading the clock themselves.
+#[derive(Debug, Default)]
+pub(super) struct TimedPurges {
+    purges: VecDeque<TimedPurge>,
+}
+
+impl TimedPurges {
+    /// Schedule the given [`TimedPurge`], after any other purges scheduled for the same time
+    pub(super) fn schedule(&mut self, tp: TimedPurge) {
+        let idx = self.purges.partition_point(|other| other.time <= tp.time);
+        self.purges.insert(idx, tp);
+    }
+
+    /// Remove and return the next purge that needs to happen at or before `now`, if any
+    pub(super) fn pop_expired(&mut self, now: Instant) -> Option<TimedPurge> {
+        if self.purges.front()?.time <= now {
+            self.purges.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// If there is a pending purge, return the duration from `now` until it needs to happen
+    pub(super) fn next_poll_duration(&self, now: Instant) -> Option<Duration> {
+        self.purges
+            .front()
+            .map(|tp| tp.time.saturating_duration_since(now))
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.purges.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vec1::vec1;
+
+    use super::*;
+
+    fn purge(time: Instant, key: i32) -> TimedPurge {
+        TimedPurge {
+            time,
+            view: LocalNodeIndex::make(0),
+            keys: HashSet::from([KeyComparison::from(vec1![key.into()])]),
+        }
+    }
+
+    #[test]
+    fn nothing_expires_early() {
+        let start = Instant::now();
+        let mut purges = TimedPurges::default();
+        purges.schedule(purge(start + Duration::from_secs(30), 1));
+
+        assert!(purges.pop_expired(start).is_none());
+        assert!(purges
+            .pop_expired(start + Duration::from_secs(29))
+            .is_none());
+        assert_eq!(
+            purges.next_poll_duration(start + Duration::from_secs(29)),
+            Some(Duration::from_secs(1))
+        );
+
+        let tp = purges.pop_expired(start + Duration::from_secs(30)).unwrap();
+        assert_eq!(tp.keys, purge(start, 1).keys);
+        assert!(purges.is_empty());
+        assert_eq!(purges.next_poll_duration(start), None);
+    }
+
+    #[test]
+    fn different_expirations_pop_in_deadline_order() {
+        let start = Instant::now();
+        let mut purges = TimedPurges::default();
+        // A long TTL scheduled before a short one (eg a frontier purge) shouldn't hold it up
+        purges.schedule(purge(start + Duration::from_secs(30), 1));
+        purges.schedule(purge(start + Duration::from_millis(50), 2));
+        purges.schedule(purge(start + Duration::from_secs(1), 3));
+
+        assert_eq!(
+            purges.next_poll_duration(start),
+            Some(Duration::from_millis(50))
+        );
+
+        let now = start + Duration::from_secs(1);
+        assert_eq!(purges.pop_expired(now).unwrap().keys, purge(now, 2).keys);
+        assert_eq!(purges.pop_expired(now).unwrap().keys, purge(now, 3).keys);
+        assert!(purges.pop_expired(now).is_none());
+
+        let now = start + Duration::from_secs(31);
+        assert_eq!(purges.next_poll_duration(now), Some(Duration::ZERO));
+        assert_eq!(purges.pop_expired(now).unwrap().keys, purge(now, 1).keys);
+        assert!(purges.is_empty());
+    }
+
+    #[test]
+    fn same_deadline_keeps_scheduling_order() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(1);
+        let mut purges = TimedPurges::default();
+        purges.schedule(purge(deadline, 1));
+        purges.schedule(purge(deadline, 2));
+
+        assert_eq!(
+            purges.pop_expired(deadline).unwrap().keys,
+            purge(deadline, 1).keys
+        );
+        assert_eq!(
+            purges.pop_expired(deadline).unwrap().keys,
+            purge(deadline, 2).keys
+        );
+    }
+}
@@ -371,13 +371,16 @@ impl Node {
                 }
                 NodeOperator::Concat(_) => Some(AuxiliaryNodeState::Concat(Default::default())),
                 NodeOperator::Extremum(_)
+                | NodeOperator::Sketch(_)
                 | NodeOperator::Join(_)
                 | NodeOperator::Paginate(_)
                 | NodeOperator::Project(_)
                 | NodeOperator::Union(_)
                 | NodeOperator::Identity(_)
                 | NodeOperator::Filter(_)
-                | NodeOperator::TopK(_) => None,
+                | NodeOperator::TopK(_)
+                | NodeOperator::Window(_)
+                | NodeOperator::FixedPoint(_) => None,
             },
             NodeType::Ingress
             | NodeType::Base(_)
@@ -3,6 +3,7 @@ use std::time::SystemTime;
 use dataflow_expression::ReaderProcessing;
 use failpoint_macros::failpoint;
 use metrics::histogram;
+use nom_sql::{CacheExpiration, CachePriority};
 use readyset_client::metrics::recorded;
 use readyset_client::{KeyColumnIdx, ViewPlaceholder};
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,17 @@ pub struct Reader {
     ///
     /// The data is stored in this manner instead of in a Hashmap to support ordered iteration.
     placeholder_map: Vec<(ViewPlaceholder, KeyColumnIdx)>,
+
+    /// If set, keys filled in this reader by replays are evicted (or refreshed) once they have
+    /// been in the reader for longer than the configured duration.
+    expiration: Option<CacheExpiration>,
+
+    /// If set, the maximum number of bytes of state this reader may hold before keys are evicted
+    /// from it, independently of the worker-wide memory limit
+    memory_limit: Option<usize>,
+
+    /// The priority of this reader relative to other nodes when choosing which state to evict
+    priority: CachePriority,
 }
 
 impl Clone for Reader {
@@ -34,6 +46,9 @@ impl Clone for Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            expiration: self.expiration,
+            memory_limit: self.memory_limit,
+            priority: self.priority,
         }
     }
 }
@@ -45,6 +60,9 @@ impl Reader {
             reader_processing,
             index: None,
             placeholder_map: Default::default(),
+            expiration: None,
+            memory_limit: None,
+            priority: CachePriority::default(),
         }
     }
 
@@ -60,6 +78,9 @@ impl Reader {
             reader_processing: self.reader_processing.clone(),
             index: self.index.clone(),
             placeholder_map: self.placeholder_map.clone(),
+            expiration: self.expiration,
+            memory_limit: self.memory_limit,
+            priority: self.priority,
         }
     }
 
@@ -109,6 +130,36 @@ impl Reader {
         }
     }
 
+    /// Sets the [`CacheExpiration`] for keys in this reader
+    pub fn set_expiration(&mut self, expiration: CacheExpiration) {
+        self.expiration = Some(expiration);
+    }
+
+    /// Returns the [`CacheExpiration`] for keys in this reader, if any
+    pub fn expiration(&self) -> Option<CacheExpiration> {
+        self.expiration
+    }
+
+    /// Sets the maximum number of bytes of state this reader may hold
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        self.memory_limit = Some(memory_limit);
+    }
+
+    /// Returns the maximum number of bytes of state this reader may hold, if any
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit
+    }
+
+    /// Sets the [`CachePriority`] of this reader when choosing which state to evict
+    pub fn set_priority(&mut self, priority: CachePriority) {
+        self.priority = priority;
+    }
+
+    /// Returns the [`CachePriority`] of this reader when choosing which state to evict
+    pub fn priority(&self) -> CachePriority {
+        self.priority
+    }
+
     /// Returns the mapping from placeholder to reader key column. There is exactly one value for
     /// each reader key column in the map
     pub fn mapping(&self) -> &[(ViewPlaceholder, KeyColumnIdx)] {
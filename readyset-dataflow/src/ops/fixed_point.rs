@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::processing::LookupIndex;
+
+/// Operator which computes the fixed point of a recursive common table expression, by repeatedly
+/// joining rows of the recursive term against the rows that have already been reached.
+///
+/// The single parent of this operator is expected to contain the rows of both the non-recursive
+/// (anchor) term and the recursive (step) term of the CTE, distinguished by the value of the
+/// `anchor` column. Anchor rows are always reached, and a step row is reached if some reached row
+/// has a value in the `key` column equal to the value of the step row's `parent` column. The
+/// output of this operator is the (distinct) set of reached rows, with the `parent` and `anchor`
+/// columns removed.
+///
+/// Inserts are handled incrementally, by propagating newly reached rows through the step rows
+/// whose parent they are. Deletes are handled using delete-rederive: first, every row which could
+/// have been reached via a deleted row is (over-)deleted, then any of those rows which are still
+/// reachable from the remaining rows are rederived, and only the rows which could not be
+/// rederived are retracted.
+///
+/// Since computing the set of reached rows requires all the rows in the parent, this operator
+/// must be fully materialized.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FixedPoint {
+    /// The direct Ingredient or Base ancestor of this node
+    src: IndexPair,
+    /// The index of the column in the parent that step rows are joined on
+    key: usize,
+    /// The index of the column in the parent containing the value of the key column of the
+    /// parent of each step row
+    parent: usize,
+    /// The index of the column in the parent which is truthy for anchor rows, and falsy for step
+    /// rows
+    anchor: usize,
+    /// The indices of the columns in the parent which are emitted by this node
+    emit: Vec<usize>,
+    #[serde(skip)]
+    state: FixedPointState,
+}
+
+/// The state of a [`FixedPoint`] node. All rows in the state are identified by their *output*
+/// columns.
+#[derive(Clone, Default)]
+struct FixedPointState {
+    /// The number of anchor rows in the parent with each value
+    anchors: HashMap<Vec<DfValue>, usize>,
+    /// For each (non-null) parent key value, the set of step rows with that parent
+    steps_by_parent: HashMap<DfValue, HashSet<Vec<DfValue>>>,
+    /// For each step row, the number of rows in the parent with that value for each parent key
+    step_parents: HashMap<Vec<DfValue>, HashMap<DfValue, usize>>,
+    /// The set of rows which have been reached, and hence emitted
+    reached: HashSet<Vec<DfValue>>,
+    /// The number of reached rows with each (non-null) value for the key column
+    reached_keys: HashMap<DfValue, usize>,
+}
+
+impl FixedPoint {
+    /// Construct a new FixedPoint operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - this operator's ancestor
+    /// * `num_columns` - the number of columns in the ancestor
+    /// * `key` - the column that the parent column of step rows refers to
+    /// * `parent` - the column containing the key of the parent of each step row
+    /// * `anchor` - the column indicating whether each row is an anchor or a step row
+    pub fn new(
+        src: NodeIndex,
+        num_columns: usize,
+        key: usize,
+        parent: usize,
+        anchor: usize,
+    ) -> ReadySetResult<Self> {
+        invariant!(
+            key != parent && key != anchor && parent != anchor,
+            "key, parent, and anchor columns of FixedPoint must be distinct"
+        );
+        invariant!(
+            key < num_columns && parent < num_columns && anchor < num_columns,
+            "FixedPoint columns out of bounds"
+        );
+
+        Ok(FixedPoint {
+            src: src.into(),
+            key,
+            parent,
+            anchor,
+            emit: (0..num_columns)
+                .filter(|c| *c != parent && *c != anchor)
+                .collect(),
+            state: Default::default(),
+        })
+    }
+
+    /// Returns the index of the key column in the output of this node
+    fn output_key(&self) -> usize {
+        self.emit
+            .iter()
+            .position(|c| *c == self.key)
+            .expect("key column is always emitted")
+    }
+
+    /// Returns true if the given row has a direct derivation: either it's an anchor row, or it's a
+    /// step row whose parent has been reached
+    fn is_derivable(&self, row: &[DfValue]) -> bool {
+        self.state.anchors.contains_key(row)
+            || self.state.step_parents.get(row).iter().any(|parents| {
+                parents
+                    .keys()
+                    .any(|p| self.state.reached_keys.contains_key(p))
+            })
+    }
+
+    /// Mark the given row as reached, along with any rows which become reachable as a result,
+    /// returning the list of newly reached rows
+    fn reach(&mut self, row: Vec<DfValue>) -> Vec<Vec<DfValue>> {
+        let output_key = self.output_key();
+        let mut reached = vec![];
+        let mut queue = vec![row];
+        while let Some(row) = queue.pop() {
+            if !self.state.reached.insert(row.clone()) {
+                continue;
+            }
+
+            let key = &row[output_key];
+            if !key.is_none() {
+                let count = self.state.reached_keys.entry(key.clone()).or_default();
+                *count += 1;
+                if *count == 1 {
+                    queue.extend(
+                        self.state
+                            .steps_by_parent
+                            .get(key)
+                            .into_iter()
+                            .flatten()
+                            .filter(|r| !self.state.reached.contains(*r))
+                            .cloned(),
+                    );
+                }
+            }
+
+            reached.push(row);
+        }
+        reached
+    }
+
+    /// Un-reach the given row, along with every row which was (or might have been) reached via
+    /// that row, returning the list of rows which are no longer reached
+    fn over_delete(&mut self, row: Vec<DfValue>) -> Vec<Vec<DfValue>> {
+        let output_key = self.output_key();
+        let mut deleted = vec![];
+        let mut queue = vec![row];
+        while let Some(row) = queue.pop() {
+            if !self.state.reached.remove(&row) {
+                continue;
+            }
+
+            let key = &row[output_key];
+            if let Some(count) = self.state.reached_keys.get_mut(key) {
+                *count -= 1;
+                if *count == 0 {
+                    self.state.reached_keys.remove(key);
+                }
+            }
+
+            // Note that we have to delete all the children of this row here, even if there's
+            // another row with the same key, since that row might itself have been reached via
+            // this one
+            queue.extend(
+                self.state
+                    .steps_by_parent
+                    .get(key)
+                    .into_iter()
+                    .flatten()
+                    .filter(|r| self.state.reached.contains(*r))
+                    .cloned(),
+            );
+
+            deleted.push(row);
+        }
+        deleted
+    }
+
+    fn on_positive(&mut self, row: Vec<DfValue>, is_anchor: bool, parent: DfValue) -> Records {
+        if is_anchor {
+            *self.state.anchors.entry(row.clone()).or_default() += 1;
+        } else {
+            *self
+                .state
+                .step_parents
+                .entry(row.clone())
+                .or_default()
+                .entry(parent.clone())
+                .or_default() += 1;
+            self.state
+                .steps_by_parent
+                .entry(parent)
+                .or_default()
+                .insert(row.clone());
+        }
+
+        if self.state.reached.contains(&row) || !self.is_derivable(&row) {
+            return Records::default();
+        }
+
+        self.reach(row).into_iter().map(Record::Positive).collect()
+    }
+
+    fn on_negative(&mut self, row: Vec<DfValue>, is_anchor: bool, parent: DfValue) -> Records {
+        if is_anchor {
+            match self.state.anchors.get_mut(&row) {
+                Some(count) if *count > 1 => *count -= 1,
+                Some(_) => {
+                    self.state.anchors.remove(&row);
+                }
+                None => return Records::default(),
+            }
+        } else {
+            let Some(parents) = self.state.step_parents.get_mut(&row) else {
+                return Records::default();
+            };
+            match parents.get_mut(&parent) {
+                Some(count) if *count > 1 => *count -= 1,
+                Some(_) => {
+                    parents.remove(&parent);
+                    if parents.is_empty() {
+                        self.state.step_parents.remove(&row);
+                    }
+                    if let Some(children) = self.state.steps_by_parent.get_mut(&parent) {
+                        children.remove(&row);
+                        if children.is_empty() {
+                            self.state.steps_by_parent.remove(&parent);
+                        }
+                    }
+                }
+                None => return Records::default(),
+            }
+        }
+
+        if !self.state.reached.contains(&row) {
+            return Records::default();
+        }
+
+        let deleted = self.over_delete(row);
+        let mut rederived = HashSet::new();
+        for row in &deleted {
+            if !self.state.reached.contains(row) && self.is_derivable(row) {
+                rederived.extend(self.reach(row.clone()));
+            }
+        }
+
+        deleted
+            .into_iter()
+            .filter(|row| !rederived.contains(row))
+            .map(Record::Negative)
+            .collect()
+    }
+}
+
+impl Ingredient for FixedPoint {
+    fn ancestors(&self) -> Vec<NodeIndex> {
+        vec![self.src.as_global()]
+    }
+
+    impl_replace_sibling!(src);
+
+    fn on_connected(&mut self, _graph: &Graph) {}
+
+    fn on_commit(&mut self, _us: NodeIndex, remap: &HashMap<NodeIndex, IndexPair>) {
+        self.src.remap(remap);
+    }
+
+    fn on_input(
+        &mut self,
+        from: LocalNodeIndex,
+        rs: Records,
+        _replay: &ReplayContext,
+        _nodes: &DomainNodes,
+        _state: &StateMap,
+        _auxiliary_node_states: &mut AuxiliaryNodeStateMap,
+    ) -> ReadySetResult<ProcessingResult> {
+        debug_assert_eq!(from, *self.src);
+
+        let mut results = Records::default();
+        for r in rs {
+            let (row, positive) = r.extract();
+            let is_anchor = row
+                .get(self.anchor)
+                .ok_or_else(|| internal_err!("Anchor column out of bounds"))?
+                .is_truthy();
+            let parent = row
+                .get(self.parent)
+                .cloned()
+                .ok_or_else(|| internal_err!("Parent column out of bounds"))?;
+            // A NULL parent never compares equal to any key, so step rows with a NULL parent can
+            // never be reached
+            if !is_anchor && parent.is_none() {
+                continue;
+            }
+
+            let row = self.emit.iter().map(|c| row[*c].clone()).collect();
+            results.extend(if positive {
+                self.on_positive(row, is_anchor, parent)
+            } else {
+                self.on_negative(row, is_anchor, parent)
+            });
+        }
+
+        Ok(ProcessingResult {
+            results,
+            ..Default::default()
+        })
+    }
+
+    fn suggest_indexes(&self, this: NodeIndex) -> HashMap<NodeIndex, LookupIndex> {
+        HashMap::from([(
+            this,
+            LookupIndex::Strict(Index::hash_map(vec![self.output_key()])),
+        )])
+    }
+
+    fn column_source(&self, _cols: &[usize]) -> ColumnSource {
+        ColumnSource::RequiresFullReplay(vec1![self.src.as_global()])
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        if !detailed {
+            return "FixedPoint".into();
+        }
+
+        format!(
+            "FixedPoint [{} = {}] anchor: {}",
+            self.parent, self.key, self.anchor
+        )
+    }
+
+    fn requires_full_materialization(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test::MockGraph;
+
+    /// Set up a FixedPoint over a parent with columns `[id, parent_id, __parent, __anchor]`,
+    /// computing the set of rows reachable from the anchor rows by following `__parent` to `id`
+    fn setup() -> MockGraph {
+        let mut g = MockGraph::new();
+        let s = g.add_base("source", &["id", "parent_id", "__parent", "__anchor"]);
+        g.set_op(
+            "fixed_point",
+            &["id", "parent_id"],
+            FixedPoint::new(s.as_global(), 4, 0, 2, 3).unwrap(),
+            true,
+        );
+        g
+    }
+
+    fn anchor(id: i32) -> Vec<DfValue> {
+        vec![id.into(), DfValue::None, DfValue::None, 1.into()]
+    }
+
+    fn step(id: i32, parent_id: i32) -> Vec<DfValue> {
+        vec![id.into(), parent_id.into(), parent_id.into(), 0.into()]
+    }
+
+    fn output(id: i32, parent_id: Option<i32>) -> Vec<DfValue> {
+        vec![id.into(), parent_id.map_or(DfValue::None, Into::into)]
+    }
+
+    fn sorted(rs: Records) -> Vec<Record> {
+        let mut rs = Vec::from(rs);
+        rs.sort();
+        rs
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let mut g = setup();
+        let res = g.narrow_one(vec![step(2, 1), step(3, 2), step(4, 5), anchor(1)], true);
+        assert_eq!(
+            sorted(res),
+            sorted(
+                vec![
+                    Record::Positive(output(1, None)),
+                    Record::Positive(output(2, Some(1))),
+                    Record::Positive(output(3, Some(2))),
+                ]
+                .into()
+            )
+        );
+
+        // Reaching 5 makes 4 reachable
+        let res = g.narrow_one_row(step(5, 3), true);
+        assert_eq!(
+            sorted(res),
+            sorted(
+                vec![
+                    Record::Positive(output(5, Some(3))),
+                    Record::Positive(output(4, Some(5))),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn unreachable_steps() {
+        let mut g = setup();
+        let res = g.narrow_one(vec![step(2, 1), step(3, 2)], true);
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn delete_removes_descendants() {
+        let mut g = setup();
+        g.narrow_one(vec![anchor(1), step(2, 1), step(3, 2), step(4, 1)], true);
+
+        let res = g.narrow_one_row((step(2, 1), false), true);
+        assert_eq!(
+            sorted(res),
+            sorted(
+                vec![
+                    Record::Negative(output(2, Some(1))),
+                    Record::Negative(output(3, Some(2))),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn delete_rederives_alternative_paths() {
+        let mut g = setup();
+        // 3 is reachable via both 2 and 10, with a different parent_id (and hence a different
+        // output row) for each
+        g.narrow_one(
+            vec![anchor(1), anchor(10), step(2, 1), step(3, 2), step(3, 10)],
+            true,
+        );
+
+        // Deleting 2 doesn't retract the row for 3 reached via 10
+        let res = g.narrow_one_row((step(2, 1), false), true);
+        assert_eq!(
+            sorted(res),
+            sorted(
+                vec![
+                    Record::Negative(output(2, Some(1))),
+                    Record::Negative(output(3, Some(2))),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn delete_rederives_duplicate_keys() {
+        let mut g = setup();
+        // Two distinct rows with key 2, each of which can reach 3
+        let alt: Vec<DfValue> = vec![2.into(), 10.into(), 10.into(), 0.into()];
+        g.narrow_one(
+            vec![anchor(1), anchor(10), step(2, 1), alt.clone(), step(3, 2)],
+            true,
+        );
+
+        let res = g.narrow_one_row((step(2, 1), false), true);
+        assert_eq!(res, vec![(output(2, Some(1)), false)].into());
+    }
+
+    #[test]
+    fn delete_breaks_cycles() {
+        let mut g = setup();
+        g.narrow_one(vec![anchor(1), step(2, 1), step(3, 2), step(2, 3)], true);
+
+        // 2 and 3 are reachable from each other, but not from any anchor once 1 is gone
+        let res = g.narrow_one_row((anchor(1), false), true);
+        assert_eq!(
+            sorted(res),
+            sorted(
+                vec![
+                    Record::Negative(output(1, None)),
+                    Record::Negative(output(2, Some(1))),
+                    Record::Negative(output(3, Some(2))),
+                    Record::Negative(output(2, Some(3))),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_anchors() {
+        let mut g = setup();
+        g.narrow_one(vec![anchor(1), step(2, 1)], true);
+
+        let res = g.narrow_one_row(anchor(1), true);
+        assert!(res.is_empty());
+
+        let res = g.narrow_one_row((anchor(1), false), true);
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn null_parents_are_never_reached() {
+        let mut g = setup();
+        let null_parent: Vec<DfValue> = vec![2.into(), DfValue::None, DfValue::None, 0.into()];
+        let res = g.narrow_one(vec![anchor(1), null_parent], true);
+        assert_eq!(res, vec![output(1, None)].into());
+    }
+
+    #[test]
+    fn suggest_indexes() {
+        let g = setup();
+        let res = g.node().suggest_indexes(g.node_index().as_global());
+        assert_eq!(
+            res[&g.node_index().as_global()],
+            LookupIndex::Strict(Index::hash_map(vec![0]))
+        );
+    }
+}
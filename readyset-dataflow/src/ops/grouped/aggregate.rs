@@ -16,15 +16,80 @@ use crate::prelude::*;
 pub enum Aggregation {
     /// Count the number of non-null values.
     Count,
+    /// Count the number of distinct non-null values.
+    CountDistinct,
     /// Sum the value of the `over` column for all records of each group.
     Sum,
+    /// Sum the distinct values of the `over` column for all records of each group.
+    SumDistinct,
     /// Average the value of the `over` column. Maintains count and sum in HashMap
     Avg,
+    /// Average the distinct values of the `over` column. Maintains count and sum of the distinct
+    /// values in HashMap
+    AvgDistinct,
     /// Concatenates using the given separator between values.
     GroupConcat { separator: String },
+    /// The population variance of the `over` column. Maintains count, sum, and sum of squares in
+    /// HashMap
+    VarPop,
+    /// The sample variance of the `over` column. Maintains count, sum, and sum of squares in
+    /// HashMap
+    VarSamp,
+    /// The population standard deviation of the `over` column. Maintains count, sum, and sum of
+    /// squares in HashMap
+    StddevPop,
+    /// The sample standard deviation of the `over` column. Maintains count, sum, and sum of
+    /// squares in HashMap
+    StddevSamp,
+    /// True if all non-null values of the `over` column are true. Maintains the number of true
+    /// values in HashMap
+    BoolAnd,
+    /// True if any non-null value of the `over` column is true. Maintains the number of true
+    /// values in HashMap
+    BoolOr,
+    /// The bitwise AND of all non-null values of the `over` column, or all bits set if there are
+    /// none. Maintains the number of values with each bit set in HashMap
+    BitAnd,
+    /// The bitwise OR of all non-null values of the `over` column, or 0 if there are none.
+    /// Maintains the number of values with each bit set in HashMap
+    BitOr,
+    /// Collects the values into an array.
+    ArrayAgg,
+    /// Collects the values into a JSON array, typed as JSONB if `jsonb` is true.
+    JsonAgg { jsonb: bool },
+    /// Estimates the number of distinct non-null values using a HyperLogLog sketch.
+    ApproxCountDistinct,
+    /// Estimates percentiles of the non-null values using a quantile sketch. The percentile
+    /// itself is only picked once the sketch is finalized post-lookup.
+    ApproxPercentile,
 }
 
 impl Aggregation {
+    /// Returns true if this aggregation only considers each distinct value of its `over` column
+    /// once per group
+    pub fn is_distinct(&self) -> bool {
+        matches!(
+            self,
+            Aggregation::CountDistinct | Aggregation::SumDistinct | Aggregation::AvgDistinct
+        )
+    }
+
+    /// Returns true if this aggregation can't be computed from its current value and a diff alone,
+    /// and instead recomputes its value from running totals kept for each group
+    fn uses_group_totals(&self) -> bool {
+        matches!(
+            self,
+            Aggregation::VarPop
+                | Aggregation::VarSamp
+                | Aggregation::StddevPop
+                | Aggregation::StddevSamp
+                | Aggregation::BoolAnd
+                | Aggregation::BoolOr
+                | Aggregation::BitAnd
+                | Aggregation::BitOr
+        )
+    }
+
     /// Construct a new `Aggregator` that performs this operation.
     ///
     /// The aggregation will aggregate the value in column number `over` from its inputs (i.e.,
@@ -38,11 +103,14 @@ impl Aggregation {
         over_col_ty: &DfType,
     ) -> ReadySetResult<GroupedOperator<Aggregator>> {
         let out_ty = match &self {
-            Aggregation::Count { .. } => DfType::BigInt,
+            Aggregation::Count { .. } | Aggregation::CountDistinct => DfType::BigInt,
             // The SUM() and AVG() functions return a DECIMAL value for exact-value arguments
             // (integer or DECIMAL), and a DOUBLE value for approximate-value arguments (FLOAT or
             // DOUBLE).
-            Aggregation::Sum | Aggregation::Avg => {
+            Aggregation::Sum
+            | Aggregation::SumDistinct
+            | Aggregation::Avg
+            | Aggregation::AvgDistinct => {
                 if over_col_ty.is_any_float() {
                     DfType::Double
                 } else {
@@ -50,6 +118,17 @@ impl Aggregation {
                 }
             }
             Aggregation::GroupConcat { .. } => DfType::Text(/* TODO */ Collation::default()),
+            Aggregation::VarPop
+            | Aggregation::VarSamp
+            | Aggregation::StddevPop
+            | Aggregation::StddevSamp => DfType::Double,
+            Aggregation::BoolAnd | Aggregation::BoolOr => DfType::Bool,
+            Aggregation::BitAnd | Aggregation::BitOr => DfType::UnsignedBigInt,
+            Aggregation::ArrayAgg => DfType::Array(Box::new(over_col_ty.clone())),
+            Aggregation::JsonAgg { jsonb: false } => DfType::Json,
+            Aggregation::JsonAgg { jsonb: true } => DfType::Jsonb,
+            Aggregation::ApproxCountDistinct => DfType::BigInt,
+            Aggregation::ApproxPercentile => DfType::Double,
         };
 
         Ok(GroupedOperator::new(
@@ -125,10 +204,145 @@ impl AverageDataPair {
     }
 }
 
+/// Running totals of the non-null values in a group, for aggregations which recompute their value
+/// from scratch whenever the group changes
+#[derive(Debug, Clone, Default)]
+struct GroupTotals {
+    /// The number of non-null values in the group
+    count: i64,
+    /// The sum of the values, for variance and standard deviation
+    sum: f64,
+    /// The sum of the squares of the values, for variance and standard deviation
+    sum_of_squares: f64,
+    /// The number of true values, for `BOOL_AND` and `BOOL_OR`
+    trues: i64,
+    /// The number of values with each bit set, for `BIT_AND` and `BIT_OR`. Empty until the first
+    /// value is added
+    set_bits: Vec<i64>,
+}
+
+impl GroupTotals {
+    fn apply_diff(&mut self, op: &Aggregation, diff: &NumericalDiff) -> ReadySetResult<()> {
+        if diff.value.is_none() {
+            return Ok(());
+        }
+
+        let sign = if diff.positive { 1 } else { -1 };
+        self.count += sign;
+        match op {
+            Aggregation::VarPop
+            | Aggregation::VarSamp
+            | Aggregation::StddevPop
+            | Aggregation::StddevSamp => {
+                let value = f64::try_from(&diff.value)?;
+                self.sum += sign as f64 * value;
+                self.sum_of_squares += sign as f64 * value * value;
+            }
+            Aggregation::BoolAnd | Aggregation::BoolOr => {
+                if bool::try_from(&diff.value)? {
+                    self.trues += sign;
+                }
+            }
+            Aggregation::BitAnd | Aggregation::BitOr => {
+                // Reinterpret both signed and unsigned values as their 64 bits
+                let bits = i128::try_from(&diff.value)? as u64;
+                if self.set_bits.is_empty() {
+                    self.set_bits = vec![0; u64::BITS as usize];
+                }
+                for (bit, count) in self.set_bits.iter_mut().enumerate() {
+                    if bits & (1 << bit) != 0 {
+                        *count += sign;
+                    }
+                }
+            }
+            _ => internal!("{:?} does not keep group totals", op),
+        }
+
+        Ok(())
+    }
+
+    fn value(&self, op: &Aggregation) -> ReadySetResult<DfValue> {
+        // Bitwise aggregations of no values are their identity, rather than NULL
+        match op {
+            Aggregation::BitAnd if self.count <= 0 => return Ok(DfValue::UnsignedInt(u64::MAX)),
+            Aggregation::BitOr if self.count <= 0 => return Ok(DfValue::UnsignedInt(0)),
+            _ => {}
+        }
+        if self.count <= 0 {
+            return Ok(DfValue::None);
+        }
+
+        Ok(match op {
+            Aggregation::VarPop
+            | Aggregation::VarSamp
+            | Aggregation::StddevPop
+            | Aggregation::StddevSamp => {
+                let sample = matches!(op, Aggregation::VarSamp | Aggregation::StddevSamp);
+                if sample && self.count < 2 {
+                    return Ok(DfValue::None);
+                }
+                let n = self.count as f64;
+                let squared_deviations = self.sum_of_squares - self.sum * self.sum / n;
+                // Rounding error can make the sum of squared deviations very slightly negative
+                let variance = squared_deviations.max(0.0) / if sample { n - 1.0 } else { n };
+                if matches!(op, Aggregation::StddevPop | Aggregation::StddevSamp) {
+                    DfValue::Double(variance.sqrt())
+                } else {
+                    DfValue::Double(variance)
+                }
+            }
+            Aggregation::BoolAnd => (self.trues == self.count).into(),
+            Aggregation::BoolOr => (self.trues > 0).into(),
+            Aggregation::BitAnd | Aggregation::BitOr => {
+                let bits = self
+                    .set_bits
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, count)| match op {
+                        Aggregation::BitAnd => **count == self.count,
+                        _ => **count > 0,
+                    })
+                    .fold(0u64, |bits, (bit, _)| bits | (1 << bit));
+                DfValue::UnsignedInt(bits)
+            }
+            _ => internal!("{:?} does not keep group totals", op),
+        })
+    }
+}
+
+/// The number of records in a group with each distinct value of the `over` column, for distinct
+/// aggregations. This is signed since a negative for a value may be processed before the positive
+/// it retracts
+type Multiplicities = HashMap<DfValue, i64>;
+
 #[derive(Debug, Default)]
 /// Auxiliary State for an Aggregator node, which is owned by a Domain
 pub struct AggregatorState {
     count_sum_map: HashMap<GroupHash, AverageDataPair>,
+    distinct_values: HashMap<GroupHash, Multiplicities>,
+    group_totals: HashMap<GroupHash, GroupTotals>,
+}
+
+/// Record the value in the given diff in the multiplicities of its group, returning true if that
+/// added a new distinct value to the group or removed the last occurrence of one from it
+fn update_multiplicities(
+    distinct_values: &mut HashMap<GroupHash, Multiplicities>,
+    diff: &NumericalDiff,
+) -> bool {
+    let multiplicities = distinct_values.entry(diff.group_hash).or_default();
+    let count = multiplicities.entry(diff.value.clone()).or_default();
+    let was_present = *count > 0;
+    *count += if diff.positive { 1 } else { -1 };
+    let is_present = *count > 0;
+
+    if *count == 0 {
+        multiplicities.remove(&diff.value);
+        if multiplicities.is_empty() {
+            distinct_values.remove(&diff.group_hash);
+        }
+    }
+
+    was_present != is_present
 }
 
 impl Aggregator {
@@ -206,14 +420,51 @@ impl GroupedOperation for Aggregator {
             }
         };
 
-        let count_sum_map = match auxiliary_node_state {
-            Some(AuxiliaryNodeState::Aggregation(ref mut aggregator_state)) => {
-                &mut aggregator_state.count_sum_map
-            }
+        let (count_sum_map, distinct_values, group_totals) = match auxiliary_node_state {
+            Some(AuxiliaryNodeState::Aggregation(AggregatorState {
+                ref mut count_sum_map,
+                ref mut distinct_values,
+                ref mut group_totals,
+            })) => (count_sum_map, distinct_values, group_totals),
             Some(_) => internal!("Incorrect auxiliary state for Aggregation node"),
             None => internal!("Missing auxiliary state for Aggregation node"),
         };
 
+        let mut diffs = diffs.peekable();
+
+        if self.op.uses_group_totals() {
+            let Some(group_hash) = diffs.peek().map(|diff| diff.group_hash) else {
+                return Ok(current.cloned());
+            };
+            let mut totals = match (current, group_totals.remove(&group_hash)) {
+                // We're (re)building the group from scratch
+                (None, _) => GroupTotals::default(),
+                (Some(_), Some(totals)) => totals,
+                // We don't have totals for a group we've emitted a value for, so we need to
+                // rebuild them from all the records in the group
+                (Some(_), None) => return Ok(None),
+            };
+            for diff in diffs {
+                totals.apply_diff(&self.op, &diff)?;
+            }
+            let value = totals.value(&self.op)?;
+            // Once the group has no non-null values left, its value no longer depends on its
+            // totals; if any values are added back to it, we'll rebuild them
+            if totals.count > 0 {
+                group_totals.insert(group_hash, totals);
+            }
+            return Ok(Some(value));
+        }
+
+        if current.is_none() && self.op.is_distinct() {
+            // We're (re)building the group from scratch, so forget about any values we knew about
+            // for it before
+            if let Some(diff) = diffs.peek() {
+                count_sum_map.remove(&diff.group_hash);
+                distinct_values.remove(&diff.group_hash);
+            }
+        }
+
         let mut apply_avg = |_curr, diff: Self::Diff| -> ReadySetResult<DfValue> {
             count_sum_map
                 .entry(diff.group_hash)
@@ -224,21 +475,41 @@ impl GroupedOperation for Aggregator {
                 .apply_diff(diff)
         };
 
-        let apply_diff =
-            |curr: ReadySetResult<DfValue>, diff: Self::Diff| -> ReadySetResult<DfValue> {
-                if diff.value.is_none() {
-                    return curr;
-                }
+        let apply_diff = |curr: ReadySetResult<DfValue>,
+                          diff: Self::Diff|
+         -> ReadySetResult<DfValue> {
+            if diff.value.is_none() {
+                return curr;
+            }
+
+            // Distinct aggregations only change when a value is added to or removed from the
+            // set of distinct values in the group
+            if self.op.is_distinct() && !update_multiplicities(distinct_values, &diff) {
+                return curr;
+            }
 
-                match self.op {
-                    Aggregation::Count { .. } => apply_count(curr?, diff),
-                    Aggregation::Sum => apply_sum(curr?, diff),
-                    Aggregation::Avg => apply_avg(curr?, diff),
-                    Aggregation::GroupConcat { separator: _ } => internal!(
-                        "GroupConcats are separate from the other aggregations in the dataflow."
-                    ),
+            match self.op {
+                Aggregation::Count { .. } | Aggregation::CountDistinct => apply_count(curr?, diff),
+                Aggregation::Sum | Aggregation::SumDistinct => apply_sum(curr?, diff),
+                Aggregation::Avg | Aggregation::AvgDistinct => apply_avg(curr?, diff),
+                Aggregation::GroupConcat { .. }
+                | Aggregation::ArrayAgg
+                | Aggregation::JsonAgg { .. } => internal!(
+                    "GroupConcats are separate from the other aggregations in the dataflow."
+                ),
+                Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile => {
+                    internal!("Sketches are separate from the other aggregations in the dataflow.")
                 }
-            };
+                Aggregation::VarPop
+                | Aggregation::VarSamp
+                | Aggregation::StddevPop
+                | Aggregation::StddevSamp
+                | Aggregation::BoolAnd
+                | Aggregation::BoolOr
+                | Aggregation::BitAnd
+                | Aggregation::BitOr => internal!("Handled above"),
+            }
+        };
 
         diffs
             .fold(Ok(current.cloned().unwrap_or(self.new_data()?)), apply_diff)
@@ -249,19 +520,49 @@ impl GroupedOperation for Aggregator {
         if !detailed {
             return match self.op {
                 Aggregation::Count { .. } => "+".to_owned(),
+                Aggregation::CountDistinct => "+ distinct".to_owned(),
                 Aggregation::Sum => "𝛴".to_owned(),
+                Aggregation::SumDistinct => "𝛴 distinct".to_owned(),
                 Aggregation::Avg => "Avg".to_owned(),
+                Aggregation::AvgDistinct => "Avg distinct".to_owned(),
                 Aggregation::GroupConcat { separator: ref s } => {
                     format!("||({})", s)
                 }
+                Aggregation::VarPop => "VarPop".to_owned(),
+                Aggregation::VarSamp => "VarSamp".to_owned(),
+                Aggregation::StddevPop => "StddevPop".to_owned(),
+                Aggregation::StddevSamp => "StddevSamp".to_owned(),
+                Aggregation::BoolAnd => "BoolAnd".to_owned(),
+                Aggregation::BoolOr => "BoolOr".to_owned(),
+                Aggregation::BitAnd => "BitAnd".to_owned(),
+                Aggregation::BitOr => "BitOr".to_owned(),
+                Aggregation::ArrayAgg => "ArrayAgg".to_owned(),
+                Aggregation::JsonAgg { .. } => "JsonAgg".to_owned(),
+                Aggregation::ApproxCountDistinct => "ApproxCountDistinct".to_owned(),
+                Aggregation::ApproxPercentile => "ApproxPercentile".to_owned(),
             };
         }
 
         let op_string = match self.op {
             Aggregation::Count { .. } => "|*|".to_owned(),
+            Aggregation::CountDistinct => format!("|distinct {}|", self.over),
             Aggregation::Sum => format!("𝛴({})", self.over),
+            Aggregation::SumDistinct => format!("𝛴(distinct {})", self.over),
             Aggregation::Avg => format!("Avg({})", self.over),
+            Aggregation::AvgDistinct => format!("Avg(distinct {})", self.over),
             Aggregation::GroupConcat { separator: ref s } => format!("||({}, {})", s, self.over),
+            Aggregation::VarPop => format!("VarPop({})", self.over),
+            Aggregation::VarSamp => format!("VarSamp({})", self.over),
+            Aggregation::StddevPop => format!("StddevPop({})", self.over),
+            Aggregation::StddevSamp => format!("StddevSamp({})", self.over),
+            Aggregation::BoolAnd => format!("BoolAnd({})", self.over),
+            Aggregation::BoolOr => format!("BoolOr({})", self.over),
+            Aggregation::BitAnd => format!("BitAnd({})", self.over),
+            Aggregation::BitOr => format!("BitOr({})", self.over),
+            Aggregation::ArrayAgg => format!("ArrayAgg({})", self.over),
+            Aggregation::JsonAgg { .. } => format!("JsonAgg({})", self.over),
+            Aggregation::ApproxCountDistinct => format!("ApproxCountDistinct({})", self.over),
+            Aggregation::ApproxPercentile => format!("ApproxPercentile({})", self.over),
         };
         let group_cols = self
             .group
@@ -282,22 +583,28 @@ impl GroupedOperation for Aggregator {
 
     fn empty_value(&self) -> Option<DfValue> {
         match self.op {
-            Aggregation::Count { .. } => Some(0.into()),
+            Aggregation::Count { .. } | Aggregation::CountDistinct => Some(0.into()),
+            Aggregation::BitAnd => Some(DfValue::UnsignedInt(u64::MAX)),
+            Aggregation::BitOr => Some(DfValue::UnsignedInt(0)),
             _ => None,
         }
     }
 
     fn emit_empty(&self) -> bool {
         match self.op {
-            Aggregation::Count { .. } | Aggregation::GroupConcat { .. } => {
-                self.group_by().is_empty()
-            }
+            Aggregation::Count { .. }
+            | Aggregation::CountDistinct
+            | Aggregation::GroupConcat { .. }
+            | Aggregation::BitAnd
+            | Aggregation::BitOr => self.group_by().is_empty(),
             _ => false,
         }
     }
 
     fn can_lose_state(&self) -> bool {
-        false
+        // Group totals aren't persisted, so if we've lost them we have to rebuild them from our
+        // parent
+        self.op.uses_group_totals()
     }
 }
 
@@ -308,6 +615,8 @@ impl GroupedOperation for Aggregator {
 #[cfg(test)]
 #[allow(clippy::unreachable)]
 mod tests {
+    use std::iter;
+
     use super::*;
     use crate::{ops, LookupIndex};
 
@@ -357,6 +666,16 @@ mod tests {
             .over(src, 1, &[2, 0], &DfType::Unknown)
             .unwrap();
         assert_eq!(a.description(true), "Avg(1) γ[2, 0]");
+
+        let c = Aggregation::CountDistinct
+            .over(src, 1, &[0, 2], &DfType::Unknown)
+            .unwrap();
+        assert_eq!(c.description(true), "|distinct 1| γ[0, 2]");
+
+        let v = Aggregation::VarSamp
+            .over(src, 1, &[0], &DfType::Unknown)
+            .unwrap();
+        assert_eq!(v.description(true), "VarSamp(1) γ[0]");
     }
 
     /// Testing count emits correct records with single column group and single over column
@@ -1066,6 +1385,248 @@ mod tests {
         }
     }
 
+    /// Returns the aggregated value of the last positive record in `rs`
+    fn last_value(rs: Records) -> DfValue {
+        rs.into_iter()
+            .filter_map(|r| match r {
+                Record::Positive(r) => Some(r[1].clone()),
+                Record::Negative(_) => None,
+            })
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn count_distinct() {
+        let mut c = setup(Aggregation::CountDistinct, true);
+
+        let rs = c.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(last_value(rs), 1.into());
+
+        // A duplicate value doesn't change the count, but does change the row count
+        let rs = c.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(rs.len(), 2);
+        assert_eq!(last_value(rs), 1.into());
+
+        let rs = c.narrow_one_row(vec![1.into(), 2.into()], true);
+        assert_eq!(last_value(rs), 2.into());
+
+        // Nulls aren't counted
+        let rs = c.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), 2.into());
+
+        // Other groups have their own set of distinct values
+        let rs = c.narrow_one_row(vec![2.into(), 1.into()], true);
+        assert_eq!(last_value(rs), 1.into());
+
+        // Removing one of the duplicates doesn't change the count
+        let rs = c.narrow_one_row((vec![1.into(), 1.into()], false), true);
+        assert_eq!(last_value(rs), 2.into());
+
+        // Removing the last occurrence does
+        let rs = c.narrow_one_row((vec![1.into(), 1.into()], false), true);
+        assert_eq!(last_value(rs), 1.into());
+    }
+
+    #[test]
+    fn count_distinct_batch() {
+        let mut c = setup(Aggregation::CountDistinct, true);
+
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), 1.into()], true),
+                (vec![1.into(), 2.into()], true),
+                (vec![1.into(), 1.into()], true),
+                (vec![1.into(), 3.into()], true),
+                (vec![1.into(), 3.into()], false),
+            ],
+            true,
+        );
+        assert_eq!(last_value(rs), 2.into());
+    }
+
+    #[test]
+    fn sum_distinct() {
+        let mut c = setup(Aggregation::SumDistinct, true);
+
+        let rs = c.narrow_one_row(vec![1.into(), 2.into()], true);
+        assert_eq!(last_value(rs), DfValue::try_from(2.0f64).unwrap());
+
+        let rs = c.narrow_one_row(vec![1.into(), 2.into()], true);
+        assert_eq!(last_value(rs), DfValue::try_from(2.0f64).unwrap());
+
+        let rs = c.narrow_one_row(vec![1.into(), 3.into()], true);
+        assert_eq!(last_value(rs), DfValue::try_from(5.0f64).unwrap());
+
+        let rs = c.narrow_one_row((vec![1.into(), 2.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::try_from(5.0f64).unwrap());
+
+        let rs = c.narrow_one_row((vec![1.into(), 2.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::try_from(3.0f64).unwrap());
+    }
+
+    #[test]
+    fn avg_distinct() {
+        let mut c = setup(Aggregation::AvgDistinct, true);
+
+        c.narrow_one_row(vec![1.into(), 1.into()], true);
+        c.narrow_one_row(vec![1.into(), 1.into()], true);
+        c.narrow_one_row(vec![1.into(), 1.into()], true);
+        let rs = c.narrow_one_row(vec![1.into(), 4.into()], true);
+        assert_eq!(last_value(rs), DfValue::try_from(2.5f64).unwrap());
+
+        let rs = c.narrow_one_row((vec![1.into(), 4.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::try_from(1.0f64).unwrap());
+    }
+
+    #[test]
+    fn variance() {
+        let mut pop = setup(Aggregation::VarPop, true);
+        let mut samp = setup(Aggregation::VarSamp, true);
+
+        // The sample variance of a single value is undefined
+        pop.narrow_one_row(vec![1.into(), 1.into()], true);
+        let rs = samp.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(last_value(rs), DfValue::None);
+
+        for c in [&mut pop, &mut samp] {
+            c.narrow_one(
+                vec![
+                    (vec![1.into(), 2.into()], true),
+                    (vec![1.into(), 3.into()], true),
+                    (vec![1.into(), 4.into()], true),
+                    (vec![1.into(), DfValue::None], true),
+                ],
+                true,
+            );
+        }
+
+        let rs = pop.narrow_one_row(vec![2.into(), 5.into()], true);
+        assert_eq!(last_value(rs), DfValue::Double(0.0));
+        let rs = pop.narrow_one_row((vec![1.into(), 4.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::Double(2.0 / 3.0));
+        let rs = pop.narrow_one_row(vec![1.into(), 4.into()], true);
+        assert_eq!(last_value(rs), DfValue::Double(1.25));
+
+        let rs = samp.narrow_one_row((vec![1.into(), 4.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::Double(1.0));
+    }
+
+    #[test]
+    fn stddev() {
+        let mut c = setup(Aggregation::StddevPop, true);
+        let rs = c.narrow_one(
+            [2, 4, 4, 4, 5, 5, 7, 9]
+                .into_iter()
+                .map(|v| (vec![1.into(), v.into()], true))
+                .collect::<Vec<_>>(),
+            true,
+        );
+        assert_eq!(last_value(rs), DfValue::Double(2.0));
+
+        // Deleting every value in the group leaves no non-null values to compute a value from
+        let rs = c.narrow_one(
+            [2, 4, 4, 4, 5, 5, 7, 9]
+                .into_iter()
+                .map(|v| (vec![1.into(), v.into()], false))
+                .chain(iter::once((vec![1.into(), DfValue::None], true)))
+                .collect::<Vec<_>>(),
+            true,
+        );
+        assert_eq!(last_value(rs), DfValue::None);
+    }
+
+    #[test]
+    fn bool_and_or() {
+        let mut and = setup(Aggregation::BoolAnd, true);
+        let mut or = setup(Aggregation::BoolOr, true);
+
+        let rs = and.narrow_one_row(vec![1.into(), true.into()], true);
+        assert_eq!(last_value(rs), true.into());
+        let rs = or.narrow_one_row(vec![1.into(), false.into()], true);
+        assert_eq!(last_value(rs), false.into());
+
+        let rs = and.narrow_one_row(vec![1.into(), false.into()], true);
+        assert_eq!(last_value(rs), false.into());
+        let rs = or.narrow_one_row(vec![1.into(), true.into()], true);
+        assert_eq!(last_value(rs), true.into());
+
+        // Nulls are ignored
+        let rs = and.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), false.into());
+
+        let rs = and.narrow_one_row((vec![1.into(), false.into()], false), true);
+        assert_eq!(last_value(rs), true.into());
+        let rs = or.narrow_one_row((vec![1.into(), true.into()], false), true);
+        assert_eq!(last_value(rs), false.into());
+    }
+
+    #[test]
+    fn bit_and_or() {
+        let mut and = setup(Aggregation::BitAnd, true);
+        let mut or = setup(Aggregation::BitOr, true);
+
+        for c in [&mut and, &mut or] {
+            c.narrow_one(
+                vec![
+                    (vec![1.into(), 0b110.into()], true),
+                    (vec![1.into(), 0b011.into()], true),
+                ],
+                true,
+            );
+        }
+
+        let rs = and.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b010));
+        let rs = or.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b111));
+
+        let rs = and.narrow_one_row((vec![1.into(), 0b011.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b110));
+        let rs = or.narrow_one_row((vec![1.into(), 0b011.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b110));
+
+        // Negative values are reinterpreted as unsigned
+        let rs = and.narrow_one_row(vec![1.into(), (-1).into()], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b110));
+        let rs = or.narrow_one_row(vec![1.into(), (-8).into()], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(u64::MAX - 1));
+    }
+
+    #[test]
+    fn bit_and_or_of_only_nulls() {
+        let mut and = setup(Aggregation::BitAnd, true);
+        let mut or = setup(Aggregation::BitOr, true);
+        let s = and.narrow_base_id();
+
+        and.seed(s, vec![1.into(), DfValue::None]);
+        let rs = and.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(u64::MAX));
+        let rs = or.narrow_one_row(vec![1.into(), DfValue::None], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0));
+
+        // The group has no totals once it's empty, so they're rebuilt from the parent
+        and.seed(s, vec![1.into(), 0b101.into()]);
+        let rs = and.narrow_one_row(vec![1.into(), 0b101.into()], true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(0b101));
+        let rs = and.narrow_one_row((vec![1.into(), 0b101.into()], false), true);
+        assert_eq!(last_value(rs), DfValue::UnsignedInt(u64::MAX));
+    }
+
+    #[test]
+    fn group_totals_index_parent() {
+        let me = 1.into();
+        let c = setup(Aggregation::VarPop, false);
+        let idx = c.node().suggest_indexes(me);
+
+        // Group totals can be lost, so we need to be able to look up groups in our parent
+        assert_eq!(idx.len(), 2);
+        assert_eq!(
+            idx[&c.narrow_base_id().as_global()],
+            LookupIndex::Strict(Index::hash_map(vec![0]))
+        );
+    }
+
     #[test]
     fn it_suggests_indices() {
         let me = 1.into();
@@ -1,4 +1,4 @@
-//! Kinda (s)crappy group_concat() implementation
+//! Kinda (s)crappy group_concat() implementation, which also backs array_agg() and json_agg()
 
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -17,8 +17,8 @@ use crate::prelude::*;
 /// The last stored state for a given group.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct LastState {
-    /// The string representation we last emitted for this group.
-    string_repr: String,
+    /// The value we last emitted for this group.
+    repr: DfValue,
     /// A vector containing the actual data
     data: Vec<DfValue>,
 }
@@ -26,23 +26,36 @@ struct LastState {
 impl Default for LastState {
     fn default() -> Self {
         Self {
-            string_repr: "".to_owned(),
+            repr: DfValue::None,
             data: vec![],
         }
     }
 }
 
+/// The kind of value a [`GroupConcat`] builds out of the values in each group.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ConcatOutput {
+    /// A string of the values with the given separator between them, for `GROUP_CONCAT` and
+    /// `STRING_AGG`.
+    Text { separator: String },
+    /// An array of the values, for `ARRAY_AGG`.
+    Array { element_type: DfType },
+    /// A JSON array of the values, for `JSON_AGG` and `JSONB_AGG`.
+    Json { element_type: DfType, jsonb: bool },
+}
+
 /// `GroupConcat` partially implements the `GROUP_CONCAT` SQL aggregate function, which
 /// aggregates a set of arbitrary `DfValue`s into a string representation separated by
-/// a user-defined separator.
+/// a user-defined separator. It also implements the `ARRAY_AGG` and `JSON_AGG` aggregate functions,
+/// which collect the same values into an array or JSON array instead.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GroupConcat {
     /// Which column to aggregate.
     source_col: usize,
     /// The columns to group by.
     group_by: Vec<usize>,
-    /// The user-defined separator.
-    separator: String,
+    /// What to build out of the aggregated values.
+    output: ConcatOutput,
 }
 
 fn concat_fmt<F: Write>(f: &mut F, dt: &DfValue) -> ReadySetResult<()> {
@@ -56,6 +69,29 @@ fn concat_fmt<F: Write>(f: &mut F, dt: &DfValue) -> ReadySetResult<()> {
     Ok(())
 }
 
+/// Write the JSON representation of a value of the given type
+fn json_fmt<F: Write>(f: &mut F, dt: &DfValue, ty: &DfType) -> ReadySetResult<()> {
+    match dt {
+        DfValue::None => write!(f, "null").unwrap(),
+        // Booleans are represented as integers
+        _ if ty.is_bool() => write!(f, "{}", bool::try_from(dt)?).unwrap(),
+        // JSON values are embedded as they are
+        _ if ty.is_any_json() => write!(f, "{}", <&str>::try_from(dt)?).unwrap(),
+        DfValue::Int(_)
+        | DfValue::UnsignedInt(_)
+        | DfValue::Float(_)
+        | DfValue::Double(_)
+        | DfValue::Numeric(_) => write!(f, "{}", dt).unwrap(),
+        // Everything else is represented as a JSON string
+        _ => {
+            let mut text = String::new();
+            concat_fmt(&mut text, dt)?;
+            write!(f, "{}", serde_json::Value::from(text)).unwrap();
+        }
+    }
+    Ok(())
+}
+
 impl GroupConcat {
     /// Construct a new `GroupConcat`, aggregating the provided `source_col` and separating
     /// aggregated data with the provided `separator`.
@@ -64,16 +100,57 @@ impl GroupConcat {
         source_col: usize,
         group_by: Vec<usize>,
         separator: String,
+    ) -> ReadySetResult<GroupedOperator<GroupConcat>> {
+        Self::with_output(src, source_col, group_by, ConcatOutput::Text { separator })
+    }
+
+    /// Construct a new `GroupConcat`, aggregating the provided `source_col` into the given kind of
+    /// `output`.
+    pub fn with_output(
+        src: NodeIndex,
+        source_col: usize,
+        group_by: Vec<usize>,
+        output: ConcatOutput,
     ) -> ReadySetResult<GroupedOperator<GroupConcat>> {
         Ok(GroupedOperator::new(
             src,
             GroupConcat {
                 source_col,
                 group_by,
-                separator,
+                output,
             },
         ))
     }
+
+    /// Build the value for a group with the given data
+    fn build(&self, data: &[DfValue]) -> ReadySetResult<DfValue> {
+        match &self.output {
+            ConcatOutput::Text { separator } => {
+                // what I *really* want here is Haskell's "intercalate" ~eta
+                let mut out_str = String::new();
+                for (i, piece) in data.iter().enumerate() {
+                    // TODO(eta): not unwrap, maybe
+                    concat_fmt(&mut out_str, piece)?;
+                    if i < data.len() - 1 {
+                        write!(&mut out_str, "{}", separator).unwrap();
+                    }
+                }
+                Ok(out_str.into())
+            }
+            ConcatOutput::Array { .. } => Ok(data.to_vec().into()),
+            ConcatOutput::Json { element_type, .. } => {
+                let mut out_str = String::from("[");
+                for (i, piece) in data.iter().enumerate() {
+                    if i > 0 {
+                        write!(&mut out_str, ", ").unwrap();
+                    }
+                    json_fmt(&mut out_str, piece, element_type)?;
+                }
+                out_str.push(']');
+                Ok(out_str.into())
+            }
+        }
+    }
 }
 
 pub struct ConcatDiff {
@@ -115,10 +192,6 @@ impl GroupedOperation for GroupConcat {
         diffs: &mut dyn Iterator<Item = Self::Diff>,
         auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
     ) -> ReadySetResult<Option<DfValue>> {
-        let current: Option<&str> = current
-            .filter(|dt| matches!(dt, &DfValue::Text(..) | &DfValue::TinyText(..)))
-            .and_then(|dt| <&str>::try_from(dt).ok());
-
         let mut diffs = diffs.peekable();
 
         let first_diff = diffs
@@ -135,7 +208,7 @@ impl GroupedOperation for GroupConcat {
         let mut ls = last_state.remove(&group);
         let mut prev_state = match current {
             #[allow(clippy::unwrap_used)] // check for is_some() before unwrapping
-            Some(text) if ls.is_some() && text == ls.as_ref().unwrap().string_repr => {
+            Some(value) if ls.is_some() && *value == ls.as_ref().unwrap().repr => {
                 // if state matches, use it
                 ls.take().unwrap()
             }
@@ -153,6 +226,10 @@ impl GroupedOperation for GroupConcat {
         } in diffs
         {
             invariant_eq!(group_by, group);
+            // GROUP_CONCAT and STRING_AGG skip NULLs, but ARRAY_AGG and JSON_AGG include them
+            if value.is_none() && matches!(self.output, ConcatOutput::Text { .. }) {
+                continue;
+            }
             if is_positive {
                 prev_state.data.push(value);
             } else {
@@ -175,29 +252,28 @@ impl GroupedOperation for GroupConcat {
                 prev_state.data.remove(item_pos);
             }
         }
-        // what I *really* want here is Haskell's "intercalate" ~eta
-        let mut out_str = String::new();
-        for (i, piece) in prev_state.data.iter().enumerate() {
-            // TODO(eta): not unwrap, maybe
-            concat_fmt(&mut out_str, piece)?;
-            if i < prev_state.data.len() - 1 {
-                write!(&mut out_str, "{}", self.separator).unwrap();
-            }
-        }
-        prev_state.string_repr = out_str.clone();
+        let out = self.build(&prev_state.data)?;
+        prev_state.repr = out.clone();
         last_state.insert(group, prev_state);
-        Ok(Some(out_str.into()))
+        Ok(Some(out))
     }
 
     fn description(&self, detailed: bool) -> String {
-        if !detailed {
-            return "CONCAT2".into();
+        match &self.output {
+            ConcatOutput::Text { .. } if !detailed => "CONCAT2".into(),
+            ConcatOutput::Text { separator } => format!(
+                "||({}, {:?}) γ{:?}",
+                self.source_col, separator, self.group_by
+            ),
+            ConcatOutput::Array { .. } if !detailed => "ArrayAgg".into(),
+            ConcatOutput::Array { .. } => {
+                format!("ArrayAgg({}) γ{:?}", self.source_col, self.group_by)
+            }
+            ConcatOutput::Json { .. } if !detailed => "JsonAgg".into(),
+            ConcatOutput::Json { .. } => {
+                format!("JsonAgg({}) γ{:?}", self.source_col, self.group_by)
+            }
         }
-
-        format!(
-            "||({}, {:?}) γ{:?}",
-            self.source_col, self.separator, self.group_by
-        )
     }
 
     fn over_column(&self) -> usize {
@@ -205,11 +281,20 @@ impl GroupedOperation for GroupConcat {
     }
 
     fn output_col_type(&self) -> DfType {
-        DfType::Text(/* TODO */ Collation::default())
+        match &self.output {
+            ConcatOutput::Text { .. } => DfType::Text(/* TODO */ Collation::default()),
+            ConcatOutput::Array { element_type } => DfType::Array(Box::new(element_type.clone())),
+            ConcatOutput::Json { jsonb: false, .. } => DfType::Json,
+            ConcatOutput::Json { jsonb: true, .. } => DfType::Jsonb,
+        }
     }
 
     fn empty_value(&self) -> Option<DfValue> {
-        Some("".into())
+        match self.output {
+            ConcatOutput::Text { .. } => Some("".into()),
+            // Like the other aggregates, these are NULL when there are no values to aggregate
+            ConcatOutput::Array { .. } | ConcatOutput::Json { .. } => None,
+        }
     }
 
     fn can_lose_state(&self) -> bool {
@@ -230,15 +315,34 @@ mod tests {
     use crate::{ops, LookupIndex};
 
     fn setup(mat: bool) -> ops::test::MockGraph {
+        setup_with_output(
+            ConcatOutput::Text {
+                separator: String::from("#"),
+            },
+            mat,
+        )
+    }
+
+    fn setup_with_output(output: ConcatOutput, mat: bool) -> ops::test::MockGraph {
         let mut g = ops::test::MockGraph::new();
         let s = g.add_base("source", &["x", "y"]);
 
-        let c = GroupConcat::new(s.as_global(), 1, vec![0], String::from("#")).unwrap();
+        let c = GroupConcat::with_output(s.as_global(), 1, vec![0], output).unwrap();
 
         g.set_op("concat", &["x", "ys"], c, mat);
         g
     }
 
+    fn last_value(rs: Records) -> DfValue {
+        rs.into_iter()
+            .filter_map(|r| match r {
+                Record::Positive(r) => Some(r[1].clone()),
+                Record::Negative(_) => None,
+            })
+            .last()
+            .unwrap()
+    }
+
     #[test]
     fn it_describes() {
         let c = setup(true);
@@ -398,6 +502,78 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn array_agg() {
+        let mut c = setup_with_output(
+            ConcatOutput::Array {
+                element_type: DfType::Int,
+            },
+            true,
+        );
+
+        let rs = c.narrow_one_row(vec![1.into(), 1.into()], true);
+        assert_eq!(last_value(rs), DfValue::from(vec![DfValue::from(1)]));
+
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), 2.into()], true),
+                (vec![1.into(), DfValue::None], true),
+                (vec![1.into(), 1.into()], true),
+            ],
+            true,
+        );
+        assert_eq!(
+            last_value(rs),
+            DfValue::from(vec![1.into(), 2.into(), DfValue::None, 1.into()])
+        );
+
+        // Deleting a value removes only one occurrence of it
+        let rs = c.narrow_one_row((vec![1.into(), 1.into()], false), true);
+        assert_eq!(
+            last_value(rs),
+            DfValue::from(vec![1.into(), 2.into(), DfValue::None])
+        );
+    }
+
+    #[test]
+    fn json_agg() {
+        let mut c = setup_with_output(
+            ConcatOutput::Json {
+                element_type: DfType::Text(Collation::default()),
+                jsonb: false,
+            },
+            true,
+        );
+
+        let rs = c.narrow_one(
+            vec![
+                (vec![1.into(), "a".into()], true),
+                (vec![1.into(), "b\"c".into()], true),
+                (vec![1.into(), DfValue::None], true),
+            ],
+            true,
+        );
+        assert_eq!(last_value(rs), r#"["a", "b\"c", null]"#.into());
+
+        let rs = c.narrow_one_row((vec![1.into(), "a".into()], false), true);
+        assert_eq!(last_value(rs), r#"["b\"c", null]"#.into());
+    }
+
+    #[test]
+    fn json_agg_numbers_and_bools() {
+        let mut s = String::new();
+        json_fmt(&mut s, &DfValue::Double(1.5), &DfType::Double).unwrap();
+        assert_eq!(s, "1.5");
+
+        let mut s = String::new();
+        json_fmt(&mut s, &DfValue::from(true), &DfType::Bool).unwrap();
+        assert_eq!(s, "true");
+
+        let mut s = String::new();
+        json_fmt(&mut s, &DfValue::from(r#"{"a": 1}"#), &DfType::Jsonb).unwrap();
+        assert_eq!(s, r#"{"a": 1}"#);
+    }
+
     #[test]
     fn it_suggests_indices() {
         let me = 1.into();
@@ -16,6 +16,7 @@ use crate::processing::{ColumnSource, IngredientLookupResult, LookupIndex, Looku
 pub mod aggregate;
 pub mod concat;
 pub mod extremum;
+pub mod sketch;
 
 /// Trait for implementing operations that collapse a group of records into a single record.
 ///
@@ -0,0 +1,271 @@
+//! Approximate aggregates, backed by the mergeable sketches in [`dataflow_expression::sketch`].
+
+use dataflow_expression::sketch::{HyperLogLog, QuantileSketch};
+use readyset_data::DfType;
+use readyset_errors::{invalid_query, invariant, ReadySetResult};
+use serde::{Deserialize, Serialize};
+
+use crate::node::AuxiliaryNodeState;
+use crate::ops::grouped::{GroupedOperation, GroupedOperator};
+use crate::prelude::*;
+
+/// Supported kinds of sketch operators.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Sketch {
+    /// A HyperLogLog sketch of the non-null values of the `over` column in each group, for
+    /// `APPROX_COUNT_DISTINCT`.
+    HyperLogLog,
+    /// A quantile sketch of the non-null values of the `over` column in each group, for
+    /// `APPROX_PERCENTILE`.
+    Quantiles,
+}
+
+impl Sketch {
+    /// Construct a new `SketchOperator` that builds this kind of sketch.
+    ///
+    /// The sketch will be built from the values in column number `over` of its inputs (i.e., from
+    /// the `src` node in the graph), and use the columns in the `group_by` array as a group
+    /// identifier.
+    ///
+    /// Quantile sketches can only be built from numeric values, so this returns an error if
+    /// `over_col_ty` is known not to be numeric.
+    ///
+    /// # Invariants
+    ///
+    /// * over argument must always be a valid column in the node.
+    pub fn over(
+        self,
+        src: NodeIndex,
+        over: usize,
+        group_by: &[usize],
+        over_col_ty: &DfType,
+    ) -> ReadySetResult<GroupedOperator<SketchOperator>> {
+        if self == Sketch::Quantiles
+            && over_col_ty.is_known()
+            && !(over_col_ty.is_any_int()
+                || over_col_ty.is_any_float()
+                || matches!(over_col_ty, DfType::Numeric { .. }))
+        {
+            invalid_query!("APPROX_PERCENTILE requires a numeric argument, not {over_col_ty}");
+        }
+
+        Ok(GroupedOperator::new(
+            src,
+            SketchOperator {
+                op: self,
+                over,
+                group: group_by.into(),
+            },
+        ))
+    }
+}
+
+/// `SketchOperator` implements a Dataflow node that maintains a sketch of the values in each
+/// group, for approximate aggregates.
+///
+/// The value emitted for each group is the serialized sketch itself, rather than the estimate
+/// computed from it, so that sketches for multiple groups can be merged after a lookup. Reader
+/// nodes turn sketches into estimates once they've been merged, via
+/// [`PostLookupAggregateFunction::finalize`](dataflow_expression::PostLookupAggregateFunction::finalize).
+/// The output column type is the type of that estimate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SketchOperator {
+    op: Sketch,
+    over: usize,
+    group: Vec<usize>,
+}
+
+pub enum SketchDiff {
+    Insert(DfValue),
+    Remove(DfValue),
+    None,
+}
+
+impl GroupedOperation for SketchOperator {
+    type Diff = SketchDiff;
+
+    fn setup(&mut self, parent: &Node) -> ReadySetResult<()> {
+        invariant!(
+            self.over < parent.columns().len(),
+            "cannot aggregate over non-existing column"
+        );
+        Ok(())
+    }
+
+    fn group_by(&self) -> &[usize] {
+        &self.group[..]
+    }
+
+    fn to_diff(&self, r: &[DfValue], pos: bool) -> ReadySetResult<Self::Diff> {
+        #[allow(clippy::indexing_slicing)] // Invariant documented.
+        let v = &r[self.over];
+        if let DfValue::None = *v {
+            Ok(SketchDiff::None)
+        } else if pos {
+            Ok(SketchDiff::Insert(v.clone()))
+        } else {
+            Ok(SketchDiff::Remove(v.clone()))
+        }
+    }
+
+    fn apply(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = Self::Diff>,
+        _auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
+    ) -> ReadySetResult<Option<DfValue>> {
+        match self.op {
+            Sketch::HyperLogLog => {
+                let mut sketch = current
+                    .map(HyperLogLog::try_from)
+                    .transpose()?
+                    .unwrap_or_default();
+                for d in diffs {
+                    match d {
+                        SketchDiff::Insert(v) => sketch.insert(&v),
+                        // HyperLogLog sketches can't forget values, so if removing this value might
+                        // change the sketch we have to rebuild it from scratch
+                        SketchDiff::Remove(v) if !sketch.unaffected_by_removal(&v) => {
+                            return Ok(None)
+                        }
+                        SketchDiff::Remove(_) | SketchDiff::None => {}
+                    }
+                }
+                Ok(Some((&sketch).into()))
+            }
+            Sketch::Quantiles => {
+                let mut sketch = current
+                    .map(QuantileSketch::try_from)
+                    .transpose()?
+                    .unwrap_or_default();
+                for d in diffs {
+                    match d {
+                        SketchDiff::Insert(v) => sketch.insert(f64::try_from(&v)?),
+                        SketchDiff::Remove(v) => sketch.remove(f64::try_from(&v)?),
+                        SketchDiff::None => {}
+                    }
+                }
+                Ok(Some((&sketch).into()))
+            }
+        }
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        if !detailed {
+            return String::from(match self.op {
+                Sketch::HyperLogLog => "HLL",
+                Sketch::Quantiles => "Quantiles",
+            });
+        }
+
+        let op_string = match self.op {
+            Sketch::HyperLogLog => format!("HLL({})", self.over),
+            Sketch::Quantiles => format!("Quantiles({})", self.over),
+        };
+        let group_cols = self
+            .group
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{} γ[{}]", op_string, group_cols)
+    }
+
+    fn over_column(&self) -> usize {
+        self.over
+    }
+
+    fn output_col_type(&self) -> DfType {
+        match self.op {
+            Sketch::HyperLogLog => DfType::BigInt,
+            Sketch::Quantiles => DfType::Double,
+        }
+    }
+
+    fn can_lose_state(&self) -> bool {
+        self.op == Sketch::HyperLogLog
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops;
+
+    fn setup(op: Sketch) -> ops::test::MockGraph {
+        let mut g = ops::test::MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        g.set_op(
+            "sketch",
+            &["x", "ys"],
+            op.over(s.as_global(), 1, &[0], &DfType::Unknown).unwrap(),
+            true,
+        );
+        g
+    }
+
+    fn last_value(rs: Records) -> DfValue {
+        rs.into_iter()
+            .filter_map(|r| match r {
+                Record::Positive(r) => Some(r[1].clone()),
+                Record::Negative(_) => None,
+            })
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_describes() {
+        let c = setup(Sketch::HyperLogLog);
+        assert_eq!(c.node().description(true), "HLL(1) γ[0]");
+        let c = setup(Sketch::Quantiles);
+        assert_eq!(c.node().description(true), "Quantiles(1) γ[0]");
+    }
+
+    #[test]
+    fn quantiles_over_text_is_rejected() {
+        let text = DfType::Text(Default::default());
+        assert!(Sketch::Quantiles.over(0.into(), 1, &[0], &text).is_err());
+        assert!(Sketch::HyperLogLog.over(0.into(), 1, &[0], &text).is_ok());
+        assert!(Sketch::Quantiles
+            .over(0.into(), 1, &[0], &DfType::Int)
+            .is_ok());
+    }
+
+    #[test]
+    fn hyperloglog() {
+        let mut c = setup(Sketch::HyperLogLog);
+
+        c.narrow_one_row(vec![1.into(), 1.into()], true);
+        c.narrow_one_row(vec![1.into(), 2.into()], true);
+        c.narrow_one_row(vec![1.into(), DfValue::None], true);
+        let rs = c.narrow_one_row(vec![1.into(), 2.into()], true);
+        let sketch = HyperLogLog::try_from(&last_value(rs)).unwrap();
+        assert_eq!(sketch.estimate(), 2);
+
+        let rs = c.narrow_one_row(vec![2.into(), 3.into()], true);
+        let sketch = HyperLogLog::try_from(&last_value(rs)).unwrap();
+        assert_eq!(sketch.estimate(), 1);
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut c = setup(Sketch::Quantiles);
+
+        for y in [10, 20, 30] {
+            c.narrow_one_row(vec![1.into(), y.into()], true);
+        }
+        let rs = c.narrow_one_row(vec![1.into(), DfValue::None], true);
+        let sketch = QuantileSketch::try_from(&last_value(rs)).unwrap();
+        assert_eq!(sketch.count(), 3);
+        let median = sketch.quantile(0.5).unwrap();
+        assert!((median - 20.0).abs() <= 0.2, "{median}");
+
+        let rs = c.narrow_one_row((vec![1.into(), 20.into()], false), true);
+        let sketch = QuantileSketch::try_from(&last_value(rs)).unwrap();
+        assert_eq!(sketch.count(), 2);
+        let median = sketch.quantile(0.5).unwrap();
+        assert!((median - 10.0).abs() <= 0.1, "{median}");
+    }
+}
@@ -23,6 +23,14 @@ pub enum JoinType {
     Left,
     /// Inner join between two views
     Inner,
+    /// Full outer join between two views
+    Full,
+    /// Semi-join between two views, emitting each row in the left view exactly once if it matches
+    /// any rows in the right view
+    Semi,
+    /// Anti-join between two views, emitting each row in the left view exactly once if it matches
+    /// no rows in the right view
+    Anti,
 }
 
 /// Join rows between two nodes based on a (compound) equal join key
@@ -133,6 +141,15 @@ impl Join {
             .collect()
     }
 
+    /// Generate an output row for a semi- or anti-join, which only emits columns from the left
+    /// parent
+    fn generate_left(&self, left: &[DfValue]) -> Vec<DfValue> {
+        self.emit
+            .iter()
+            .map(|&(_, col)| left[col].clone())
+            .collect()
+    }
+
     fn handle_replay_for_generated(
         &self,
         left: Records,
@@ -159,13 +176,15 @@ impl Join {
         Ok(ret.into())
     }
 
+    /// Generate an output row for the given row from the parent on `row_side`, with NULLs in
+    /// place of all the columns from the other parent
     // TODO: make non-allocating
-    fn generate_null(&self, left: &[DfValue]) -> Vec<DfValue> {
+    fn generate_null(&self, row_side: Side, row: &[DfValue]) -> Vec<DfValue> {
         self.emit
             .iter()
             .map(|&(side, col)| {
-                if side == Side::Left {
-                    left[col].clone()
+                if side == row_side {
+                    row[col].clone()
                 } else {
                     DfValue::None
                 }
@@ -176,6 +195,16 @@ impl Join {
     fn resolve_col(&self, col: usize) -> (Option<usize>, Option<usize>) {
         let (side, pcol) = self.emit[col];
 
+        if matches!(self.kind, JoinType::Full | JoinType::Semi | JoinType::Anti) {
+            // In a full join, either side of the join column can be NULL when the other side
+            // isn't, and semi- and anti-joins only ever emit rows from the left, so join columns
+            // can only ever be sourced from the parent they come from
+            return match side {
+                Side::Left => (Some(pcol), None),
+                Side::Right => (None, Some(pcol)),
+            };
+        }
+
         if let Some((on_l, on_r)) = self
             .on
             .iter()
@@ -210,7 +239,18 @@ impl Ingredient for Join {
     }
 
     fn must_replay_among(&self) -> Option<HashSet<NodeIndex>> {
-        Some(Some(self.left.as_global()).into_iter().collect())
+        match self.kind {
+            // Rows in the right parent of a full join that don't match any rows in the left parent
+            // only show up if we replay the right parent too - see the `full_replay` handling in
+            // `on_input`
+            JoinType::Full => Some(HashSet::from([
+                self.left.as_global(),
+                self.right.as_global(),
+            ])),
+            JoinType::Left | JoinType::Inner | JoinType::Semi | JoinType::Anti => {
+                Some(Some(self.left.as_global()).into_iter().collect())
+            }
+        }
     }
 
     fn on_connected(&mut self, _g: &Graph) {}
@@ -335,6 +375,36 @@ impl Ingredient for Join {
             .group_by(|rec| from_key.iter().map(|i| rec[*i].clone()).collect::<Vec<_>>());
 
         let is_replay = replay_key_cols.is_some();
+        let full_replay = matches!(replay, ReplayContext::Full { .. });
+
+        // Outer joins need to know when the number of rows for a join key on the side the update
+        // came from changes to or from zero, so that they can emit or retract NULL-padded rows for
+        // the other side. Semi- and anti-joins similarly emit or retract rows from the left when
+        // the number of matching rows on the right changes to or from zero. Replays through a full
+        // join never retract rows we've emitted before (during a full replay both sides are
+        // replayed separately, and a partial replay is keyed on columns from only one side), so we
+        // only need to do this for regular updates.
+        let track_from_count = match self.kind {
+            JoinType::Inner => false,
+            JoinType::Left | JoinType::Semi | JoinType::Anti => !from_left,
+            JoinType::Full => !is_replay,
+        };
+        // Do we emit a NULL-padded row for records on the side the update came from that don't
+        // match any rows on the other side? For anti-joins, this is the (un-padded) left row.
+        let pad_from = match self.kind {
+            JoinType::Inner | JoinType::Semi => false,
+            JoinType::Left | JoinType::Anti => from_left,
+            JoinType::Full => true,
+        };
+        // During a full replay of a full join, rows that match on both sides are emitted when
+        // replaying the left parent, so we only emit the unmatched rows from the right parent.
+        // Semi- and anti-joins never emit a row per match.
+        let emit_matches = match self.kind {
+            JoinType::Inner | JoinType::Left => true,
+            JoinType::Full => !(full_replay && !from_left),
+            JoinType::Semi | JoinType::Anti => false,
+        };
+        let from_side = if from_left { Side::Left } else { Side::Right };
 
         // Only do a lookup into a weak index if we're processing regular updates,
         // not if we're processing a replay, since regular updates should represent
@@ -358,14 +428,15 @@ impl Ingredient for Join {
             let nulls = join_key.iter().any(|v| v.is_none());
 
             // The difference between a left join and an inner join, is that for the former we must
-            // emit rows with nulls even if we later get no match in the other side.
+            // emit rows with nulls even if we later get no match in the other side. Full joins do
+            // the same for both sides.
 
-            let mut new_right_count = None;
+            let mut new_from_count = None;
 
-            if self.kind == JoinType::Left && !from_left {
+            if track_from_count {
                 let rc = self.lookup(
-                    *self.right,
-                    &self.on_right(),
+                    from,
+                    &from_key,
                     &PointKey::from(join_key.iter().cloned()),
                     nodes,
                     state,
@@ -376,8 +447,8 @@ impl Ingredient for Join {
                     IngredientLookupResult::Records(rc) => {
                         if replay_key_cols.is_some() && !nulls {
                             lookups.push(Lookup {
-                                on: *self.right,
-                                cols: self.on_right(),
+                                on: from,
+                                cols: from_key.clone(),
                                 key: join_key
                                     .clone()
                                     .try_into()
@@ -386,10 +457,10 @@ impl Ingredient for Join {
                         }
 
                         let rc = rc.count();
-                        new_right_count = Some(rc);
+                        new_from_count = Some(rc);
                     }
                     IngredientLookupResult::Miss => {
-                        // we got something from right, but that row's key is not in right??
+                        // we got something from one side, but that row's key is not in that side??
                         //
                         // this *can* happen! imagine if you have two partial indices on right,
                         // one on column a and one on column b. imagine that a is the join key.
@@ -451,11 +522,11 @@ impl Ingredient for Join {
                 rc_diff += if positive { 1 } else { -1 };
 
                 if other_rows.is_empty() {
-                    if self.kind == JoinType::Left && from_left {
-                        // left join, got a thing from left, no rows in right == NULL
-                        ret.push((self.generate_null(&row), positive).into());
+                    if pad_from {
+                        // outer join, got a thing from the padded side, no rows in other == NULL
+                        ret.push((self.generate_null(from_side, &row), positive).into());
                     }
-                } else {
+                } else if emit_matches {
                     for other in other_rows.iter() {
                         if from == *self.left {
                             ret.push((self.generate_row(&row, other), positive).into());
@@ -463,20 +534,29 @@ impl Ingredient for Join {
                             ret.push((self.generate_row(other, &row), positive).into());
                         }
                     }
+                } else if self.kind == JoinType::Semi && from_left {
+                    // semi-join, got a thing from left with at least one match in right
+                    ret.push((self.generate_left(&row), positive).into());
                 }
             }
 
-            // For a left join with updates from the right side, we also have to emit/delete NULL
-            // rows if row count changed to/from zero
-            if let Some(new_rc) = new_right_count {
+            // For a left join with updates from the right side (or a full join with updates from
+            // either side), we also have to emit/delete NULL rows for the other side if row count
+            // changed to/from zero. Anti-joins do the same with the (un-padded) rows from the left,
+            // and semi-joins do the opposite.
+            if let Some(new_rc) = new_from_count {
                 let old_rc = new_rc as isize - rc_diff;
-                if new_rc == 0 && old_rc != 0 {
-                    for other in other_rows.iter() {
-                        ret.push((self.generate_null(other), true).into());
-                    }
-                } else if new_rc != 0 && old_rc == 0 {
+                if (new_rc == 0) != (old_rc == 0) {
+                    let now_empty = new_rc == 0;
                     for other in other_rows.iter() {
-                        ret.push((self.generate_null(other), false).into());
+                        ret.push(match self.kind {
+                            JoinType::Semi => (self.generate_left(other), !now_empty).into(),
+                            JoinType::Anti => (self.generate_left(other), now_empty).into(),
+                            JoinType::Inner | JoinType::Left | JoinType::Full => {
+                                (self.generate_null(from_side.other_side(), other), now_empty)
+                                    .into()
+                            }
+                        });
                     }
                 }
             }
@@ -511,6 +591,9 @@ impl Ingredient for Join {
             return String::from(match self.kind {
                 JoinType::Left => "⋉",
                 JoinType::Inner => "⋈",
+                JoinType::Full => "⟗",
+                JoinType::Semi => "⋈∃",
+                JoinType::Anti => "⋈∄",
             });
         }
 
@@ -530,6 +613,9 @@ impl Ingredient for Join {
         let op = match self.kind {
             JoinType::Left => "⋉",
             JoinType::Inner => "⋈",
+            JoinType::Full => "⟗",
+            JoinType::Semi => "⋈∃",
+            JoinType::Anti => "⋈∄",
         };
 
         format!(
@@ -1150,4 +1236,369 @@ mod tests {
             );
         }
     }
+
+    mod full_join {
+        use super::*;
+
+        fn setup() -> (ops::test::MockGraph, IndexPair, IndexPair) {
+            let mut g = ops::test::MockGraph::new();
+            let l = g.add_base("left", &["l0", "l1"]);
+            let r = g.add_base("right", &["r0", "r1"]);
+
+            let j = Join::new(
+                l.as_global(),
+                r.as_global(),
+                JoinType::Full,
+                vec![(0, 0)],
+                vec![
+                    (Side::Left, 0),
+                    (Side::Left, 1),
+                    (Side::Right, 0),
+                    (Side::Right, 1),
+                ],
+            );
+
+            g.set_op("join", &["j0", "j1", "j2", "j3"], j, false);
+            (g, l, r)
+        }
+
+        #[test]
+        fn it_describes() {
+            let (j, l, r) = setup();
+            assert_eq!(
+                j.node().description(true),
+                format!("[{}:0, {}:1, {}:0, {}:1] {}:(0) ⟗ {}:(0)", l, l, r, r, l, r)
+            );
+        }
+
+        #[test]
+        fn must_replay_among_both_parents() {
+            let (j, l, r) = setup();
+            assert_eq!(
+                j.node().must_replay_among(),
+                Some(HashSet::from([l.as_global(), r.as_global()]))
+            );
+        }
+
+        #[test]
+        fn join_columns_not_resolved_across_sides() {
+            let (j, l, r) = setup();
+            assert_eq!(j.node().parent_columns(0), vec![(l.as_global(), Some(0))]);
+            assert_eq!(j.node().parent_columns(2), vec![(r.as_global(), Some(0))]);
+        }
+
+        #[test]
+        fn null_padding_both_sides() {
+            let (mut j, l, r) = setup();
+
+            // unmatched row from the right is padded with NULLs for the left
+            j.seed(r, vec![1.into(), "x".into()]);
+            let rs = j.one_row(r, vec![1.into(), "x".into()], false);
+            assert_eq!(
+                rs,
+                vec![(
+                    vec![DfValue::None, DfValue::None, 1.into(), "x".into()],
+                    true
+                )]
+                .into()
+            );
+
+            // unmatched row from the left is padded with NULLs for the right
+            j.seed(l, vec![2.into(), "b".into()]);
+            let rs = j.one_row(l, vec![2.into(), "b".into()], false);
+            assert_eq!(
+                rs,
+                vec![(
+                    vec![2.into(), "b".into(), DfValue::None, DfValue::None],
+                    true
+                )]
+                .into()
+            );
+
+            // a matching row from the left replaces the padded right row
+            j.seed(l, vec![1.into(), "a".into()]);
+            let rs = j.one_row(l, vec![1.into(), "a".into()], false);
+            assert_eq!(
+                rs,
+                vec![
+                    (vec![1.into(), "a".into(), 1.into(), "x".into()], true),
+                    (
+                        vec![DfValue::None, DfValue::None, 1.into(), "x".into()],
+                        false
+                    ),
+                ]
+                .into()
+            );
+
+            // a matching row from the right replaces the padded left row
+            j.seed(r, vec![2.into(), "y".into()]);
+            let rs = j.one_row(r, vec![2.into(), "y".into()], false);
+            assert_eq!(
+                rs,
+                vec![
+                    (vec![2.into(), "b".into(), 2.into(), "y".into()], true),
+                    (
+                        vec![2.into(), "b".into(), DfValue::None, DfValue::None],
+                        false
+                    ),
+                ]
+                .into()
+            );
+        }
+
+        #[test]
+        fn delete_restores_null_padding() {
+            let (mut j, l, r) = setup();
+
+            j.seed(r, vec![1.into(), "x".into()]);
+            j.one_row(r, vec![1.into(), "x".into()], false);
+            j.seed(l, vec![1.into(), "a".into()]);
+            j.one_row(l, vec![1.into(), "a".into()], false);
+
+            // deleting the only matching left row retracts the joined row and brings back the
+            // NULL-padded right row
+            j.unseed(l);
+            let rs = j.one_row(l, (vec![1.into(), "a".into()], false), false);
+            assert_eq!(
+                rs,
+                vec![
+                    (vec![1.into(), "a".into(), 1.into(), "x".into()], false),
+                    (
+                        vec![DfValue::None, DfValue::None, 1.into(), "x".into()],
+                        true
+                    ),
+                ]
+                .into()
+            );
+        }
+
+        #[test]
+        fn partial_replay() {
+            let (mut j, l, r) = setup();
+
+            j.seed(l, vec![1.into(), "a".into()]);
+            j.seed(r, vec![1.into(), "x".into()]);
+            j.seed(r, vec![2.into(), "y".into()]);
+
+            let tag = Tag::new(0);
+            let replay_ctx = |keys| ReplayContext::Partial {
+                key_cols: &[0],
+                keys,
+                requesting_shard: 0,
+                requesting_replica: 0,
+                tag,
+                unishard: true,
+            };
+
+            // replaying a key from the left only emits the joined row, and doesn't retract the
+            // NULL-padded right row (which was never emitted for this replay)
+            let keys = HashSet::from([KeyComparison::from(vec1![DfValue::from(1)])]);
+            let res = j.input_raw(
+                l,
+                vec![vec![DfValue::from(1), DfValue::from("a")]],
+                replay_ctx(&keys),
+                false,
+            );
+            match res {
+                RawProcessingResult::Regular(res) => assert_eq!(
+                    res.results,
+                    vec![(vec![1.into(), "a".into(), 1.into(), "x".into()], true)].into()
+                ),
+                _ => unreachable!("expected regular result, got: {:?}", res),
+            }
+
+            // replaying a key from the right with no match on the left emits the NULL-padded row
+            let keys = HashSet::from([KeyComparison::from(vec1![DfValue::from(2)])]);
+            let res = j.input_raw(
+                r,
+                vec![vec![DfValue::from(2), DfValue::from("y")]],
+                replay_ctx(&keys),
+                false,
+            );
+            match res {
+                RawProcessingResult::Regular(res) => assert_eq!(
+                    res.results,
+                    vec![(
+                        vec![DfValue::None, DfValue::None, 2.into(), "y".into()],
+                        true
+                    )]
+                    .into()
+                ),
+                _ => unreachable!("expected regular result, got: {:?}", res),
+            }
+        }
+
+        #[test]
+        fn null_join_keys_never_match() {
+            let (mut j, l, r) = setup();
+
+            j.seed(l, vec![DfValue::None, "a".into()]);
+            j.one_row(l, vec![DfValue::None, "a".into()], false);
+
+            j.seed(r, vec![DfValue::None, "x".into()]);
+            let rs = j.one_row(r, vec![DfValue::None, "x".into()], false);
+            assert_eq!(
+                rs,
+                vec![(
+                    vec![DfValue::None, DfValue::None, DfValue::None, "x".into()],
+                    true
+                )]
+                .into()
+            );
+        }
+    }
+
+    fn setup_left_only(kind: JoinType) -> (ops::test::MockGraph, IndexPair, IndexPair) {
+        let mut g = ops::test::MockGraph::new();
+        let l = g.add_base("left", &["l0", "l1"]);
+        let r = g.add_base("right", &["r0", "r1"]);
+
+        let j = Join::new(
+            l.as_global(),
+            r.as_global(),
+            kind,
+            vec![(0, 0)],
+            vec![(Side::Left, 0), (Side::Left, 1)],
+        );
+
+        g.set_op("join", &["j0", "j1"], j, false);
+        (g, l, r)
+    }
+
+    mod semi_join {
+        use super::*;
+
+        #[test]
+        fn it_describes() {
+            let (j, l, r) = setup_left_only(JoinType::Semi);
+            assert_eq!(
+                j.node().description(true),
+                format!("[{}:0, {}:1] {}:(0) ⋈∃ {}:(0)", l, l, l, r)
+            );
+        }
+
+        #[test]
+        fn emits_left_rows_once_per_match() {
+            let (mut j, l, r) = setup_left_only(JoinType::Semi);
+
+            // no matches in right, so nothing is emitted
+            j.seed(l, vec![1.into(), "a".into()]);
+            let rs = j.one_row(l, vec![1.into(), "a".into()], false);
+            assert!(rs.is_empty());
+
+            // the first match in right emits the left row
+            j.seed(r, vec![1.into(), "x".into()]);
+            let rs = j.one_row(r, vec![1.into(), "x".into()], false);
+            assert_eq!(rs, vec![(vec![1.into(), "a".into()], true)].into());
+
+            // further matches in right don't emit anything
+            j.seed(r, vec![1.into(), "y".into()]);
+            let rs = j.one_row(r, vec![1.into(), "y".into()], false);
+            assert!(rs.is_empty());
+
+            // new rows in left with (multiple) matches in right are emitted once
+            j.seed(l, vec![1.into(), "b".into()]);
+            let rs = j.one_row(l, vec![1.into(), "b".into()], false);
+            assert_eq!(rs, vec![(vec![1.into(), "b".into()], true)].into());
+        }
+
+        #[test]
+        fn deleting_last_match_retracts() {
+            let (mut j, l, r) = setup_left_only(JoinType::Semi);
+
+            j.seed(l, vec![1.into(), "a".into()]);
+            j.one_row(l, vec![1.into(), "a".into()], false);
+            j.seed(r, vec![1.into(), "x".into()]);
+            j.one_row(r, vec![1.into(), "x".into()], false);
+
+            j.unseed(r);
+            let rs = j.one_row(r, (vec![1.into(), "x".into()], false), false);
+            assert_eq!(rs, vec![(vec![1.into(), "a".into()], false)].into());
+        }
+
+        #[test]
+        fn null_join_keys_never_match() {
+            let (mut j, l, r) = setup_left_only(JoinType::Semi);
+
+            j.seed(r, vec![DfValue::None, "x".into()]);
+            j.one_row(r, vec![DfValue::None, "x".into()], false);
+
+            j.seed(l, vec![DfValue::None, "a".into()]);
+            let rs = j.one_row(l, vec![DfValue::None, "a".into()], false);
+            assert!(rs.is_empty());
+        }
+    }
+
+    mod anti_join {
+        use super::*;
+
+        #[test]
+        fn it_describes() {
+            let (j, l, r) = setup_left_only(JoinType::Anti);
+            assert_eq!(
+                j.node().description(true),
+                format!("[{}:0, {}:1] {}:(0) ⋈∄ {}:(0)", l, l, l, r)
+            );
+        }
+
+        #[test]
+        fn emits_left_rows_without_matches() {
+            let (mut j, l, r) = setup_left_only(JoinType::Anti);
+
+            j.seed(r, vec![2.into(), "x".into()]);
+            j.one_row(r, vec![2.into(), "x".into()], false);
+
+            // no matches in right, so the left row is emitted
+            j.seed(l, vec![1.into(), "a".into()]);
+            let rs = j.one_row(l, vec![1.into(), "a".into()], false);
+            assert_eq!(rs, vec![(vec![1.into(), "a".into()], true)].into());
+
+            // a match in right, so nothing is emitted
+            j.seed(l, vec![2.into(), "b".into()]);
+            let rs = j.one_row(l, vec![2.into(), "b".into()], false);
+            assert!(rs.is_empty());
+        }
+
+        #[test]
+        fn matches_flip_membership() {
+            let (mut j, l, r) = setup_left_only(JoinType::Anti);
+
+            j.seed(l, vec![1.into(), "a".into()]);
+            j.one_row(l, vec![1.into(), "a".into()], false);
+
+            // the first match in right retracts the left row
+            j.seed(r, vec![1.into(), "x".into()]);
+            let rs = j.one_row(r, vec![1.into(), "x".into()], false);
+            assert_eq!(rs, vec![(vec![1.into(), "a".into()], false)].into());
+
+            // further matches don't change anything
+            j.seed(r, vec![1.into(), "y".into()]);
+            let rs = j.one_row(r, vec![1.into(), "y".into()], false);
+            assert!(rs.is_empty());
+
+            // deleting all the matches brings the left row back
+            j.unseed(r);
+            let rs = j.one(
+                r,
+                vec![
+                    (vec![1.into(), "x".into()], false),
+                    (vec![1.into(), "y".into()], false),
+                ],
+                false,
+            );
+            assert_eq!(rs, vec![(vec![1.into(), "a".into()], true)].into());
+        }
+
+        #[test]
+        fn null_join_keys_never_match() {
+            let (mut j, l, r) = setup_left_only(JoinType::Anti);
+
+            j.seed(r, vec![DfValue::None, "x".into()]);
+            j.one_row(r, vec![DfValue::None, "x".into()], false);
+
+            j.seed(l, vec![DfValue::None, "a".into()]);
+            let rs = j.one_row(l, vec![DfValue::None, "a".into()], false);
+            assert_eq!(rs, vec![(vec![DfValue::None, "a".into()], true)].into());
+        }
+    }
 }
@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 use crate::prelude::*;
 
 pub mod filter;
+pub mod fixed_point;
 pub mod grouped;
 pub mod identity;
 pub mod join;
@@ -17,6 +18,7 @@ pub mod project;
 pub mod topk;
 pub mod union;
 pub(crate) mod utils;
+pub mod window;
 
 use crate::ops::grouped::concat::GroupConcat;
 use crate::processing::{
@@ -47,6 +49,7 @@ pub enum NodeOperator {
     Aggregation(grouped::GroupedOperator<grouped::aggregate::Aggregator>),
     Extremum(grouped::GroupedOperator<grouped::extremum::ExtremumOperator>),
     Concat(grouped::GroupedOperator<GroupConcat>),
+    Sketch(grouped::GroupedOperator<grouped::sketch::SketchOperator>),
     Join(join::Join),
     Paginate(paginate::Paginate),
     Project(project::Project),
@@ -54,6 +57,8 @@ pub enum NodeOperator {
     Identity(identity::Identity),
     Filter(filter::Filter),
     TopK(topk::TopK),
+    Window(window::Window),
+    FixedPoint(fixed_point::FixedPoint),
 }
 
 impl ToString for NodeOperator {
@@ -62,6 +67,7 @@ impl ToString for NodeOperator {
             NodeOperator::Aggregation(_) => "Aggregation",
             NodeOperator::Extremum(_) => "Extremum",
             NodeOperator::Concat(_) => "Concat",
+            NodeOperator::Sketch(_) => "Sketch",
             NodeOperator::Join(_) => "Join",
             NodeOperator::Paginate(_) => "Paginate",
             NodeOperator::Project(_) => "Project",
@@ -69,6 +75,8 @@ impl ToString for NodeOperator {
             NodeOperator::Identity(_) => "Identity",
             NodeOperator::Filter(_) => "Filter",
             NodeOperator::TopK(_) => "TopK",
+            NodeOperator::Window(_) => "Window",
+            NodeOperator::FixedPoint(_) => "FixedPoint",
         }
         .to_string()
     }
@@ -80,6 +88,7 @@ macro_rules! impl_ingredient_fn_mut {
             NodeOperator::Aggregation(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Extremum(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Concat(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::Sketch(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Join(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Paginate(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Project(ref mut i) => i.$fn($($arg),*),
@@ -87,6 +96,8 @@ macro_rules! impl_ingredient_fn_mut {
             NodeOperator::Identity(ref mut i) => i.$fn($($arg),*),
             NodeOperator::Filter(ref mut i) => i.$fn($($arg),*),
             NodeOperator::TopK(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::Window(ref mut i) => i.$fn($($arg),*),
+            NodeOperator::FixedPoint(ref mut i) => i.$fn($($arg),*),
         }
     }
 }
@@ -97,6 +108,7 @@ macro_rules! impl_ingredient_fn_ref {
             NodeOperator::Aggregation(ref i) => i.$fn($($arg),*),
             NodeOperator::Extremum(ref i) => i.$fn($($arg),*),
             NodeOperator::Concat(ref i) => i.$fn($($arg),*),
+            NodeOperator::Sketch(ref i) => i.$fn($($arg),*),
             NodeOperator::Join(ref i) => i.$fn($($arg),*),
             NodeOperator::Paginate(ref i) => i.$fn($($arg),*),
             NodeOperator::Project(ref i) => i.$fn($($arg),*),
@@ -104,6 +116,8 @@ macro_rules! impl_ingredient_fn_ref {
             NodeOperator::Identity(ref i) => i.$fn($($arg),*),
             NodeOperator::Filter(ref i) => i.$fn($($arg),*),
             NodeOperator::TopK(ref i) => i.$fn($($arg),*),
+            NodeOperator::Window(ref i) => i.$fn($($arg),*),
+            NodeOperator::FixedPoint(ref i) => i.$fn($($arg),*),
         }
     }
 }
@@ -7,7 +7,7 @@ use std::num::NonZeroUsize;
 
 use dataflow_state::PointKey;
 use itertools::Itertools;
-use nom_sql::OrderType;
+use nom_sql::{NullOrder, OrderType};
 use readyset_client::internal;
 use readyset_errors::{internal, internal_err, invariant, ReadySetResult};
 use readyset_util::Indices;
@@ -96,7 +96,7 @@ impl TopK {
     /// * `k` - the maximum number of results per group.
     pub fn new(
         src: NodeIndex,
-        order: Vec<(usize, OrderType)>,
+        order: Vec<(usize, OrderType, NullOrder)>,
         group_by: Vec<usize>,
         k: usize,
     ) -> Self {
@@ -472,9 +472,9 @@ mod tests {
 
     fn setup(reversed: bool) -> (ops::test::MockGraph, IndexPair) {
         let cmp_rows = if reversed {
-            vec![(2, OrderType::OrderDescending)]
+            vec![(2, OrderType::OrderDescending, NullOrder::NullsLast)]
         } else {
-            vec![(2, OrderType::OrderAscending)]
+            vec![(2, OrderType::OrderAscending, NullOrder::NullsFirst)]
         };
 
         let mut g = ops::test::MockGraph::new();
@@ -514,6 +514,40 @@ mod tests {
         assert_eq!(g.states[ni].row_count(), 3);
     }
 
+    #[test]
+    fn it_respects_null_order() {
+        for (null_order, expect_null) in
+            [(NullOrder::NullsFirst, false), (NullOrder::NullsLast, true)]
+        {
+            let mut g = ops::test::MockGraph::new();
+            let s = g.add_base("source", &["x", "y", "z"]);
+            g.set_op(
+                "topk",
+                &["x", "y", "z"],
+                TopK::new(
+                    s.as_global(),
+                    vec![(2, OrderType::OrderAscending, null_order)],
+                    vec![1],
+                    2,
+                ),
+                true,
+            );
+
+            let rnull: Vec<DfValue> = vec![1.into(), "z".into(), DfValue::None];
+            let r10: Vec<DfValue> = vec![2.into(), "z".into(), 10.into()];
+            let r11: Vec<DfValue> = vec![3.into(), "z".into(), 11.into()];
+
+            g.narrow_one_row(r10, true);
+            g.narrow_one_row(r11, true);
+            let a = g.narrow_one_row(rnull.clone(), true);
+            assert_eq!(
+                a.iter().any(|r| r == &(rnull.clone(), true).into()),
+                expect_null,
+                "{null_order}: {a:?}"
+            );
+        }
+    }
+
     #[test]
     fn it_forwards() {
         let (mut g, _) = setup(false);
@@ -5,7 +5,7 @@ use std::ops::Bound;
 
 use itertools::Itertools;
 use readyset_client::KeyComparison;
-use readyset_errors::{invariant, ReadySetResult};
+use readyset_errors::{invariant, invariant_eq, ReadySetResult};
 use readyset_util::hash::hash;
 use readyset_util::intervals::{cmp_endbound, cmp_startbound};
 use readyset_util::Indices;
@@ -37,6 +37,26 @@ pub enum DuplicateMode {
     /// "All" union mode - no duplicate removal is done. Each row in each parent is passed through
     /// unchanged
     UnionAll,
+
+    /// "Distinct" union mode, which implements SQL's `UNION DISTINCT` (or plain `UNION`).
+    ///
+    /// Each distinct row in any parent is emitted exactly once, regardless of how many duplicates
+    /// of that row exist in each parent. Rows are emitted when the total number of copies of that
+    /// row across all parents goes from 0 to 1, and retracted when it goes from 1 to 0.
+    UnionDistinct,
+
+    /// Set intersection mode, which implements SQL's `INTERSECT`.
+    ///
+    /// Each distinct row that exists in *both* parents is emitted exactly once. Only supported for
+    /// Union nodes with exactly two parents
+    Intersect,
+
+    /// Set difference mode, which implements SQL's `EXCEPT`.
+    ///
+    /// Each distinct row that exists in the left parent but not the right parent is emitted
+    /// exactly once. Only supported for Union nodes with exactly two parents, which must be
+    /// constructed with [`Union::new_ordered`] so that the left parent is known
+    Except,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -65,9 +85,9 @@ struct FullWait {
     finished: usize,
     /// The records we've received and buffered as part of this full replay
     buffered: Records,
-    /// State for implementing [`DuplicateMode::BagUnion`]. If this is None, then the duplicate
-    /// mode is [`DuplicateMode::UnionAll`]
-    bag_union_state: Option<BagUnionState>,
+    /// Fresh duplicate state for the duplicate mode of the union, used for the records in this
+    /// full replay. If this is None, then the duplicate mode is [`DuplicateMode::UnionAll`]
+    duplicate_state: Option<DuplicateState>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -143,11 +163,128 @@ impl BagUnionState {
     }
 }
 
+/// The set operations which can be implemented by tracking the number of copies of each row in
+/// each parent
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+enum SetOperation {
+    Union,
+    Intersect,
+    Except,
+}
+
+/// State for [`DuplicateMode::UnionDistinct`], [`DuplicateMode::Intersect`], and
+/// [`DuplicateMode::Except`]
+///
+/// Internally, this is a map from the hash of unique rows (so we don't retain the whole row) to the
+/// number of copies of that row in the left and right parents. Since each of these operations
+/// emits at most one copy of each row, a row only needs to be emitted or retracted when it
+/// transitions between being present and absent in the output, as determined by those counts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SetOperationState {
+    operation: SetOperation,
+    // We skip any state when serializing, as we will deserialize
+    // when recovering.
+    #[serde(skip)]
+    state: HashMap<u64, (usize, usize)>,
+}
+
+impl SetOperationState {
+    fn new(operation: SetOperation) -> Self {
+        Self {
+            operation,
+            state: Default::default(),
+        }
+    }
+
+    /// Returns whether a row with the given counts in the left and right parents should be
+    /// present in the output of the operation
+    fn is_present(&self, (left, right): (usize, usize)) -> bool {
+        match self.operation {
+            SetOperation::Union => left + right > 0,
+            SetOperation::Intersect => left > 0 && right > 0,
+            SetOperation::Except => left > 0 && right == 0,
+        }
+    }
+
+    /// Process a single record through the set operation state, and return the record that
+    /// should be emitted as a result, if any
+    fn process(&mut self, from_side: Side, record: Record) -> Option<Record> {
+        let (row, positive) = record.extract();
+        let row_hash = hash(&row);
+        let entry = self.state.entry(row_hash).or_default();
+        let before = *entry;
+        let count = match from_side {
+            Side::Left => &mut entry.0,
+            Side::Right => &mut entry.1,
+        };
+        if positive {
+            *count += 1;
+        } else {
+            *count = count.saturating_sub(1);
+        }
+        let after = *entry;
+        if after == (0, 0) {
+            self.state.remove(&row_hash);
+        }
+
+        match (self.is_present(before), self.is_present(after)) {
+            (false, true) => Some(Record::Positive(row)),
+            (true, false) => Some(Record::Negative(row)),
+            _ => None,
+        }
+    }
+}
+
+/// State for the duplicate modes of a union which need to keep track of the rows they've seen
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum DuplicateState {
+    Bag(BagUnionState),
+    Set(SetOperationState),
+}
+
+impl DuplicateState {
+    fn new(duplicate_mode: DuplicateMode) -> Option<Self> {
+        match duplicate_mode {
+            DuplicateMode::UnionAll => None,
+            DuplicateMode::BagUnion => Some(Self::Bag(BagUnionState::default())),
+            DuplicateMode::UnionDistinct => {
+                Some(Self::Set(SetOperationState::new(SetOperation::Union)))
+            }
+            DuplicateMode::Intersect => {
+                Some(Self::Set(SetOperationState::new(SetOperation::Intersect)))
+            }
+            DuplicateMode::Except => Some(Self::Set(SetOperationState::new(SetOperation::Except))),
+        }
+    }
+
+    /// Construct a new, empty state for the same duplicate mode as this state
+    fn empty(&self) -> Self {
+        match self {
+            Self::Bag(_) => Self::Bag(BagUnionState::default()),
+            Self::Set(state) => Self::Set(SetOperationState::new(state.operation)),
+        }
+    }
+
+    fn process(&mut self, from_side: Side, records: Records) -> Records {
+        match self {
+            Self::Bag(state) => records
+                .into_iter()
+                .filter(|rec| state.process(from_side, rec))
+                .collect(),
+            Self::Set(state) => records
+                .into_iter()
+                .filter_map(|rec| state.process(from_side, rec))
+                .collect(),
+        }
+    }
+}
+
 fn process_records(
     from: LocalNodeIndex,
     rs: Records,
     emit: &Emit,
-    bag_union_state: Option<&mut BagUnionState>,
+    left_parent: Option<IndexPair>,
+    duplicate_state: Option<&mut DuplicateState>,
 ) -> ReadySetResult<Records> {
     let mut results = match emit {
         Emit::AllFrom(..) => rs,
@@ -171,15 +308,19 @@ fn process_records(
         }
     };
 
-    if let Some(bus) = bag_union_state {
+    if let Some(state) = duplicate_state {
         if let Some(parent) = match emit {
             Emit::Project { cols_l, .. } => {
-                let first_parent = cols_l.keys().next().ok_or_else(|| {
-                    internal_err!(
-                        "Union node with DuplicateMode::BagUnion must have exactly 2 parents",
-                    )
-                })?;
-                if from == *first_parent {
+                let left_parent = match left_parent {
+                    Some(left_parent) => *left_parent,
+                    None => *cols_l.keys().next().ok_or_else(|| {
+                        internal_err!(
+                            "Union node with a duplicate mode other than UnionAll must have \
+                             exactly 2 parents",
+                        )
+                    })?,
+                };
+                if from == left_parent {
                     Some(Side::Left)
                 } else {
                     Some(Side::Right)
@@ -187,7 +328,7 @@ fn process_records(
             }
             _ => None,
         } {
-            results.retain(|rec| bus.process(parent, rec));
+            results = state.process(parent, results);
         }
     }
 
@@ -238,9 +379,13 @@ impl PartialOrd for BufferedReplayKey {
 pub struct Union {
     emit: Emit,
 
-    /// State for implementing [`DuplicateMode::BagUnion`]. If this is None, then the duplicate
-    /// mode is [`DuplicateMode::UnionAll`]
-    bag_union_state: Option<BagUnionState>,
+    /// State for implementing every [`DuplicateMode`] other than [`DuplicateMode::UnionAll`]. If
+    /// this is None, then the duplicate mode is [`DuplicateMode::UnionAll`]
+    duplicate_state: Option<DuplicateState>,
+
+    /// The parent which is on the left-hand side of the operation, for duplicate modes where the
+    /// order of the parents matters (see [`Union::new_ordered`])
+    left_parent: Option<IndexPair>,
 
     /// This is a map from (Tag, LocalNodeIndex) to ColumnList
     #[serde(with = "serde_with::rust::hashmap_as_tuple_list")]
@@ -320,7 +465,8 @@ impl Clone for Union {
     fn clone(&self) -> Self {
         Union {
             emit: self.emit.clone(),
-            bag_union_state: self.bag_union_state.clone(),
+            duplicate_state: self.duplicate_state.clone(),
+            left_parent: self.left_parent,
             required: self.required,
             replay_key: Default::default(),
             replay_pieces: Default::default(),
@@ -344,8 +490,46 @@ impl Union {
     pub fn new(
         emit: HashMap<NodeIndex, Vec<usize>>,
         duplicate_mode: DuplicateMode,
+    ) -> ReadySetResult<Union> {
+        Self::new_inner(emit, None, duplicate_mode)
+    }
+
+    /// Construct a new union operator whose parents are ordered, with the first parent in `emit`
+    /// being the left-hand side of the operation.
+    ///
+    /// This must be used to construct unions with [`DuplicateMode::Except`], since the result of
+    /// that operation depends on which parent is on the left.
+    pub fn new_ordered(
+        emit: Vec<(NodeIndex, Vec<usize>)>,
+        duplicate_mode: DuplicateMode,
+    ) -> ReadySetResult<Union> {
+        let left_parent = emit.first().map(|(parent, _)| *parent);
+        Self::new_inner(emit.into_iter().collect(), left_parent, duplicate_mode)
+    }
+
+    fn new_inner(
+        emit: HashMap<NodeIndex, Vec<usize>>,
+        left_parent: Option<NodeIndex>,
+        duplicate_mode: DuplicateMode,
     ) -> ReadySetResult<Union> {
         invariant!(!emit.is_empty());
+        if matches!(
+            duplicate_mode,
+            DuplicateMode::Intersect | DuplicateMode::Except
+        ) {
+            invariant_eq!(
+                emit.len(),
+                2,
+                "{:?} unions must have exactly 2 parents",
+                duplicate_mode
+            );
+        }
+        if duplicate_mode == DuplicateMode::Except {
+            invariant!(
+                left_parent.is_some(),
+                "Except unions must be constructed with Union::new_ordered"
+            );
+        }
         for emit in emit.values() {
             let mut last = &emit[0];
             for i in emit {
@@ -366,10 +550,8 @@ impl Union {
                 cols: HashMap::new(),
                 cols_l: BTreeMap::new(),
             },
-            bag_union_state: match duplicate_mode {
-                DuplicateMode::BagUnion => Some(BagUnionState::default()),
-                DuplicateMode::UnionAll => None,
-            },
+            duplicate_state: DuplicateState::new(duplicate_mode),
+            left_parent: left_parent.map(IndexPair::from),
             required: parents,
             replay_key: Default::default(),
             replay_pieces: Default::default(),
@@ -383,7 +565,8 @@ impl Union {
         let shards = sharding.shards().unwrap();
         Union {
             emit: Emit::AllFrom(parent.into(), sharding),
-            bag_union_state: None,
+            duplicate_state: None,
+            left_parent: None,
             required: shards,
             replay_key: Default::default(),
             replay_pieces: Default::default(),
@@ -395,6 +578,44 @@ impl Union {
     pub fn is_shard_merger(&self) -> bool {
         matches!(self.emit, Emit::AllFrom(..))
     }
+
+    /// Process the records from all parents for a partial replay that's ready to be released.
+    fn process_replay_pieces(
+        &mut self,
+        pieces: HashMap<LocalNodeIndex, Records>,
+        replay: &ReplayContext,
+        n: &DomainNodes,
+        s: &StateMap,
+        ans: &mut AuxiliaryNodeStateMap,
+    ) -> ReadySetResult<Records> {
+        let mut res = Records::default();
+        if let Some(DuplicateState::Set(state)) = &mut self.duplicate_state {
+            // The replay contains *every* copy of the rows for the replayed keys in each of
+            // our parents, so rather than applying the replayed rows on top of the counts we
+            // might already have for those rows (for example from a previous replay of the
+            // same key, which has since been evicted downstream), compute the output of the
+            // replay from scratch, then take the counts for those rows from the replay.
+            let mut replay_state = DuplicateState::Set(SetOperationState::new(state.operation));
+            for (from, rs) in pieces {
+                res.extend(process_records(
+                    from,
+                    rs,
+                    &self.emit,
+                    self.left_parent,
+                    Some(&mut replay_state),
+                )?);
+            }
+            if let DuplicateState::Set(replay_state) = replay_state {
+                state.state.extend(replay_state.state);
+            }
+            return Ok(res);
+        }
+
+        for (from, rs) in pieces {
+            res.extend(self.on_input(from, rs, replay, n, s, ans)?.results);
+        }
+        Ok(res)
+    }
 }
 
 impl Ingredient for Union {
@@ -440,6 +661,11 @@ impl Ingredient for Union {
                 if let Some(val) = cols.remove(&from_idx.into()) {
                     cols.insert(to_idx.into(), val);
                 }
+                if let Some(left_parent) = &mut self.left_parent {
+                    if left_parent.as_global() == from_idx {
+                        *left_parent = to_idx.into();
+                    }
+                }
             }
         }
     }
@@ -471,6 +697,9 @@ impl Ingredient for Union {
                     .collect();
                 *emit = mapped_emit;
                 *cols = mapped_cols;
+                if let Some(left_parent) = &mut self.left_parent {
+                    left_parent.remap(remap);
+                }
             }
             Emit::AllFrom(ref mut p, _) => {
                 p.remap(remap);
@@ -487,7 +716,13 @@ impl Ingredient for Union {
         _: &StateMap,
         _: &mut AuxiliaryNodeStateMap,
     ) -> ReadySetResult<ProcessingResult> {
-        let results = process_records(from, rs, &self.emit, self.bag_union_state.as_mut())?;
+        let results = process_records(
+            from,
+            rs,
+            &self.emit,
+            self.left_parent,
+            self.duplicate_state.as_mut(),
+        )?;
 
         Ok(ProcessingResult {
             results,
@@ -730,7 +965,7 @@ impl Ingredient for Union {
                         if self.required == 1 {
                             // no need to ever buffer
                             return Ok(RawProcessingResult::FullReplay(
-                                process_records(from, rs, &self.emit, None)?,
+                                process_records(from, rs, &self.emit, self.left_parent, None)?,
                                 last,
                             ));
                         }
@@ -740,17 +975,22 @@ impl Ingredient for Union {
                             1, self.required
                         );
 
-                        let mut bag_union_state =
-                            self.bag_union_state.is_some().then(BagUnionState::default);
-                        let buffered =
-                            process_records(from, rs, &self.emit, bag_union_state.as_mut())?;
+                        let mut duplicate_state =
+                            self.duplicate_state.as_ref().map(DuplicateState::empty);
+                        let buffered = process_records(
+                            from,
+                            rs,
+                            &self.emit,
+                            self.left_parent,
+                            duplicate_state.as_mut(),
+                        )?;
 
                         // we need to hold this back until we've received one from every ancestor
                         e.insert(FullWait {
                             started: HashSet::from([from]),
                             finished: usize::from(last),
                             buffered,
-                            bag_union_state,
+                            duplicate_state,
                         });
                         Ok(RawProcessingResult::CapturedFull)
                     }
@@ -759,13 +999,19 @@ impl Ingredient for Union {
                             started,
                             finished,
                             buffered,
-                            bag_union_state,
+                            duplicate_state,
                         } = wait.get_mut();
                         if last {
                             *finished += 1;
                         }
 
-                        let rs = process_records(from, rs, &self.emit, bag_union_state.as_mut())?;
+                        let rs = process_records(
+                            from,
+                            rs,
+                            &self.emit,
+                            self.left_parent,
+                            duplicate_state.as_mut(),
+                        )?;
 
                         if *finished == self.required {
                             // we can just send everything and we're done!
@@ -774,7 +1020,7 @@ impl Ingredient for Union {
                             debug!("union releasing end of full replay");
                             let res =
                                 RawProcessingResult::FullReplay(buffered.split_off(0).into(), true);
-                            self.bag_union_state = bag_union_state.take();
+                            self.duplicate_state = duplicate_state.take();
                             wait.remove();
                             Ok(res)
                         } else {
@@ -782,7 +1028,7 @@ impl Ingredient for Union {
                                 if started.insert(from) && started.len() == self.required {
                                     // we can release all buffered replays!
                                     debug!("union releasing full replay");
-                                    self.bag_union_state = bag_union_state.take();
+                                    self.duplicate_state = duplicate_state.take();
                                     buffered.extend(rs);
                                     return Ok(RawProcessingResult::FullReplay(
                                         buffered.split_off(0).into(),
@@ -965,17 +1211,13 @@ impl Ingredient for Union {
                                 }
                             }
                         })
-                        .flat_map(|(key, pieces)| {
+                        .map(|(key, pieces)| {
                             if pieces.evict {
                                 // TODO XXX TODO XXX TODO XXX TODO
                                 error!("!!! need to issue an eviction after replaying key");
                             }
                             released.insert(key.clone());
-                            pieces.buffered.into_iter()
-                        })
-                        .map(|(from, rs)| {
-                            Ok(self.on_input(from, rs, &replay, n, s, ans)?
-                                .results)
+                            self.process_replay_pieces(pieces.buffered, &replay, n, s, ans)
                         })
                         // FIXME(eta): this iterator / result stuff makes me sad, and is probably
                         // inefficient...
@@ -1063,10 +1305,14 @@ impl Ingredient for Union {
             Emit::AllFrom(..) => "⊍".to_string(),
             Emit::Project { .. } if !detailed => String::from("⋃"),
             Emit::Project { ref emit, .. } => {
-                let symbol = if self.bag_union_state.is_none() {
-                    '⋃' // DuplicateMode::UnionAll
-                } else {
-                    '⊎' // DuplicateMode::BagUnion
+                let symbol = match &self.duplicate_state {
+                    None => '⋃', // DuplicateMode::UnionAll
+                    Some(DuplicateState::Bag(_)) => '⊎',
+                    Some(DuplicateState::Set(state)) => match state.operation {
+                        SetOperation::Union => '∪',
+                        SetOperation::Intersect => '∩',
+                        SetOperation::Except => '∖',
+                    },
                 };
                 if detailed {
                     emit.iter()
@@ -1622,4 +1868,289 @@ mod tests {
             assert_eq!(u.one_row(r, right_row, false), vec![left_row].into());
         }
     }
+
+    mod set_operations {
+        use test_strategy::{proptest, Arbitrary};
+        use vec1::vec1;
+
+        use super::*;
+
+        /// Set up a union of the "left" and "right" bases, with the *right* base on the left-hand
+        /// side of the operation so that we know ordered unions don't just use the order of the
+        /// parents' indices
+        fn setup_ordered(
+            duplicate_mode: DuplicateMode,
+        ) -> (ops::test::MockGraph, IndexPair, IndexPair) {
+            let mut g = ops::test::MockGraph::new();
+            let l = g.add_base("left", &["l0", "l1"]);
+            let r = g.add_base("right", &["r0", "r1", "r2"]);
+
+            g.set_op(
+                "union",
+                &["u0", "u1"],
+                Union::new_ordered(
+                    vec![(r.as_global(), vec![0, 2]), (l.as_global(), vec![0, 1])],
+                    duplicate_mode,
+                )
+                .unwrap(),
+                false,
+            );
+            (g, r, l)
+        }
+
+        #[test]
+        fn it_describes() {
+            let (u, l, r) = setup(DuplicateMode::UnionDistinct);
+            assert_eq!(
+                u.node().description(true),
+                format!("{}:[0, 1] ∪ {}:[0, 2]", l, r)
+            );
+
+            let (u, l, r) = setup(DuplicateMode::Intersect);
+            assert_eq!(
+                u.node().description(true),
+                format!("{}:[0, 1] ∩ {}:[0, 2]", l, r)
+            );
+        }
+
+        #[test]
+        fn except_requires_ordered_parents() {
+            let mut emits = HashMap::new();
+            emits.insert(0.into(), vec![0]);
+            emits.insert(1.into(), vec![0]);
+            assert!(Union::new(emits, DuplicateMode::Except).is_err());
+        }
+
+        #[test]
+        fn intersect_requires_two_parents() {
+            let emits = vec![
+                (0.into(), vec![0]),
+                (1.into(), vec![0]),
+                (2.into(), vec![0]),
+            ];
+            assert!(Union::new_ordered(emits, DuplicateMode::Intersect).is_err());
+        }
+
+        #[test]
+        fn union_distinct() {
+            let (mut u, l, r) = setup(DuplicateMode::UnionDistinct);
+
+            let left_row = vec![1.into(), "a".into()];
+            let right_row = vec![1.into(), "skipped".into(), "a".into()];
+
+            // The first copy of a row is emitted, but no further copies from either side
+            assert_eq!(
+                u.one_row(l, left_row.clone(), false),
+                vec![left_row.clone()].into()
+            );
+            assert_eq!(u.one_row(l, left_row.clone(), false), Records::default());
+            assert_eq!(u.one_row(r, right_row.clone(), false), Records::default());
+
+            // The row is only retracted once every copy of it is gone
+            assert_eq!(
+                u.one_row(l, (left_row.clone(), false), false),
+                Records::default()
+            );
+            assert_eq!(
+                u.one_row(l, (left_row.clone(), false), false),
+                Records::default()
+            );
+            assert_eq!(
+                u.one_row(r, (right_row, false), false),
+                vec![(left_row, false)].into()
+            );
+        }
+
+        #[test]
+        fn intersect() {
+            let (mut u, l, r) = setup(DuplicateMode::Intersect);
+
+            let left_row = vec![1.into(), "a".into()];
+            let right_row = vec![1.into(), "skipped".into(), "a".into()];
+
+            // Rows only on one side aren't emitted
+            assert_eq!(u.one_row(l, left_row.clone(), false), Records::default());
+            assert_eq!(u.one_row(l, left_row.clone(), false), Records::default());
+
+            // Once the row is on both sides, it's emitted exactly once
+            assert_eq!(
+                u.one_row(r, right_row.clone(), false),
+                vec![left_row.clone()].into()
+            );
+            assert_eq!(u.one_row(r, right_row.clone(), false), Records::default());
+
+            // And it's retracted once it's gone from either side
+            assert_eq!(
+                u.one_row(l, (left_row.clone(), false), false),
+                Records::default()
+            );
+            assert_eq!(
+                u.one_row(l, (left_row.clone(), false), false),
+                vec![(left_row, false)].into()
+            );
+        }
+
+        #[test]
+        fn except() {
+            // `left` here is the right-hand side of the operation, and vice versa
+            let (mut u, left, right) = setup_ordered(DuplicateMode::Except);
+
+            let left_row = vec![1.into(), "skipped".into(), "a".into()];
+            let right_row = vec![1.into(), "a".into()];
+            let output_row: Vec<DfValue> = vec![1.into(), "a".into()];
+
+            // Rows on the left are emitted once
+            assert_eq!(
+                u.one_row(left, left_row.clone(), false),
+                vec![output_row.clone()].into()
+            );
+            assert_eq!(u.one_row(left, left_row.clone(), false), Records::default());
+
+            // Adding the row on the right retracts it
+            assert_eq!(
+                u.one_row(right, right_row.clone(), false),
+                vec![(output_row.clone(), false)].into()
+            );
+            assert_eq!(
+                u.one_row(right, right_row.clone(), false),
+                Records::default()
+            );
+
+            // And removing it from the right brings it back
+            assert_eq!(
+                u.one_row(right, (right_row.clone(), false), false),
+                Records::default()
+            );
+            assert_eq!(
+                u.one_row(right, (right_row, false), false),
+                vec![output_row].into()
+            );
+        }
+
+        #[test]
+        fn except_repeated_partial_replay() {
+            let (mut g, left, right) = setup_ordered(DuplicateMode::Except);
+            let key: KeyComparison = vec1![1.into()].into();
+            let keys = HashSet::from([key.clone()]);
+
+            for tag in [Tag::new(0), Tag::new(1)] {
+                let replay_ctx = || ReplayContext::Partial {
+                    key_cols: &[0],
+                    keys: &keys,
+                    requesting_shard: 0,
+                    requesting_replica: 0,
+                    tag,
+                    unishard: false,
+                };
+
+                let res = g.input_raw(
+                    left,
+                    vec![vec![
+                        DfValue::from(1),
+                        DfValue::from("skipped"),
+                        DfValue::from("a"),
+                    ]],
+                    replay_ctx(),
+                    false,
+                );
+                assert!(matches!(
+                    res,
+                    RawProcessingResult::ReplayPiece { ref rows, .. } if rows.is_empty()
+                ));
+
+                let res = g.input_raw(right, Records::default(), replay_ctx(), false);
+                match res {
+                    RawProcessingResult::ReplayPiece { rows, keys, .. } => {
+                        // Replaying the same key more than once (eg after it's been evicted
+                        // downstream) should produce the same results each time
+                        assert_eq!(
+                            rows,
+                            vec![vec![DfValue::from(1), DfValue::from("a")]].into()
+                        );
+                        assert_eq!(keys, HashSet::from([key.clone()]));
+                    }
+                    _ => unreachable!("Expected replay piece, got: {:?}", res),
+                }
+            }
+
+            // And since the replays didn't double-count the row, a single deletion from the left
+            // removes it
+            assert_eq!(
+                g.one_row(
+                    left,
+                    (
+                        vec![
+                            DfValue::from(1),
+                            DfValue::from("skipped"),
+                            DfValue::from("a")
+                        ],
+                        false
+                    ),
+                    false
+                ),
+                vec![(vec![DfValue::from(1), DfValue::from("a")], false)].into()
+            );
+        }
+
+        #[derive(Debug, Arbitrary, PartialEq, Eq, Clone, Copy)]
+        enum Op {
+            Union,
+            Intersect,
+            Except,
+        }
+
+        #[derive(Debug, Arbitrary, PartialEq, Eq, Clone, Copy)]
+        enum Input {
+            PosLeft,
+            NegLeft,
+            PosRight,
+            NegRight,
+        }
+
+        #[proptest]
+        fn set_operation_state_returns_correct_state_size(op: Op, inputs: Vec<Input>) {
+            let mut state = SetOperationState::new(match op {
+                Op::Union => SetOperation::Union,
+                Op::Intersect => SetOperation::Intersect,
+                Op::Except => SetOperation::Except,
+            });
+            let row = vec![DfValue::from(1i32)];
+            let (mut lefts, mut rights) = (0_usize, 0_usize);
+            let mut state_size = 0_isize;
+
+            for input in inputs {
+                let (side, rec) = match input {
+                    Input::PosLeft => {
+                        lefts += 1;
+                        (Side::Left, Record::Positive(row.clone()))
+                    }
+                    Input::NegLeft if lefts > 0 => {
+                        lefts -= 1;
+                        (Side::Left, Record::Negative(row.clone()))
+                    }
+                    Input::PosRight => {
+                        rights += 1;
+                        (Side::Right, Record::Positive(row.clone()))
+                    }
+                    Input::NegRight if rights > 0 => {
+                        rights -= 1;
+                        (Side::Right, Record::Negative(row.clone()))
+                    }
+                    // Parents can't retract rows they don't have
+                    _ => continue,
+                };
+
+                if let Some(rec) = state.process(side, rec) {
+                    state_size += if rec.is_positive() { 1 } else { -1 };
+                }
+
+                let expected = match op {
+                    Op::Union => lefts + rights > 0,
+                    Op::Intersect => lefts > 0 && rights > 0,
+                    Op::Except => lefts > 0 && rights == 0,
+                };
+                assert_eq!(state_size, isize::from(expected));
+            }
+        }
+    }
 }
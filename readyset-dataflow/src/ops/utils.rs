@@ -2,20 +2,21 @@ use std::cmp::Ordering;
 use std::fmt::Display;
 
 use itertools::Itertools;
-use nom_sql::OrderType;
+use nom_sql::{NullOrder, OrderType};
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::DfValue;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub(crate) struct Order(Vec<(usize, OrderType)>);
+pub(crate) struct Order(Vec<(usize, OrderType, NullOrder)>);
 impl Order {
     pub(crate) fn cmp(&self, a: &[DfValue], b: &[DfValue]) -> Ordering {
-        for &(c, ref order_type) in &self.0 {
-            let result = match *order_type {
-                OrderType::OrderAscending => a[c].cmp(&b[c]),
-                OrderType::OrderDescending => b[c].cmp(&a[c]),
-            };
+        for &(c, order_type, null_order) in &self.0 {
+            let result = null_order.apply(
+                a[c].is_none(),
+                b[c].is_none(),
+                order_type.apply(a[c].cmp(&b[c])),
+            );
             if result != Ordering::Equal {
                 return result;
             }
@@ -24,8 +25,8 @@ impl Order {
     }
 }
 
-impl From<Vec<(usize, OrderType)>> for Order {
-    fn from(other: Vec<(usize, OrderType)>) -> Self {
+impl From<Vec<(usize, OrderType, NullOrder)>> for Order {
+    fn from(other: Vec<(usize, OrderType, NullOrder)>) -> Self {
         Order(other)
     }
 }
@@ -37,14 +38,18 @@ impl Display for Order {
             "{}",
             self.0
                 .iter()
-                .map(|(c, dir)| {
+                .map(|(c, dir, nulls)| {
                     format!(
-                        "{}{}",
+                        "{}{}{}",
                         match dir {
                             OrderType::OrderAscending => "<",
                             OrderType::OrderDescending => ">",
                         },
                         c,
+                        match nulls {
+                            NullOrder::NullsFirst => " nulls first",
+                            NullOrder::NullsLast => " nulls last",
+                        },
                     )
                 })
                 .join(", "),
@@ -0,0 +1,726 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt::{self, Display};
+
+use dataflow_state::PointKey;
+use itertools::Itertools;
+use nom_sql::{NullOrder, OrderType};
+use readyset_data::DfType;
+use readyset_util::Indices;
+use serde::{Deserialize, Serialize};
+
+use crate::ops::utils::Order;
+use crate::prelude::*;
+use crate::processing::{ColumnMiss, LookupIndex};
+
+/// Supported window functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowFunction {
+    /// The (1-based) position of each row within its partition
+    RowNumber,
+    /// The (1-based) rank of each row within its partition, with gaps for rows that compare
+    /// equal according to the window's ordering
+    Rank,
+    /// The (1-based) rank of each row within its partition, without gaps
+    DenseRank,
+    /// The value of the `over` column `offset` rows before the current row within its partition
+    Lag { offset: usize },
+    /// The value of the `over` column `offset` rows after the current row within its partition
+    Lead { offset: usize },
+    /// The running sum of the `over` column, up to and including all peers of the current row
+    Sum,
+    /// The running count of non-null values of the `over` column (or of all rows, if there is no
+    /// `over` column), up to and including all peers of the current row
+    Count,
+}
+
+impl WindowFunction {
+    /// Returns the type of the column emitted by a window computing this function over a column
+    /// with the given type
+    pub fn output_col_type(&self, over_col_ty: Option<&DfType>) -> DfType {
+        match self {
+            WindowFunction::RowNumber
+            | WindowFunction::Rank
+            | WindowFunction::DenseRank
+            | WindowFunction::Count => DfType::BigInt,
+            WindowFunction::Lag { .. } | WindowFunction::Lead { .. } => {
+                over_col_ty.cloned().unwrap_or(DfType::Unknown)
+            }
+            // Same rules as for the SUM() aggregate: DECIMAL for exact-value arguments, and DOUBLE
+            // for approximate-value arguments.
+            WindowFunction::Sum => {
+                if over_col_ty.iter().any(|ty| ty.is_any_float()) {
+                    DfType::Double
+                } else {
+                    DfType::DEFAULT_NUMERIC
+                }
+            }
+        }
+    }
+
+    /// Returns true if this window function needs a column to compute its value over
+    pub fn requires_over_column(&self) -> bool {
+        matches!(
+            self,
+            WindowFunction::Lag { .. } | WindowFunction::Lead { .. } | WindowFunction::Sum
+        )
+    }
+}
+
+impl Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunction::RowNumber => write!(f, "row_number"),
+            WindowFunction::Rank => write!(f, "rank"),
+            WindowFunction::DenseRank => write!(f, "dense_rank"),
+            WindowFunction::Lag { offset } => write!(f, "lag[{offset}]"),
+            WindowFunction::Lead { offset } => write!(f, "lead[{offset}]"),
+            WindowFunction::Sum => write!(f, "𝛴"),
+            WindowFunction::Count => write!(f, "|*|"),
+        }
+    }
+}
+
+/// Window provides an operator that computes a window function over the rows in each partition
+/// (group) of its input, ordered by a set of columns, emitting the result of that function for
+/// each row as an additional column.
+///
+/// Like [`Paginate`], this operator keeps every row of each partition it has seen in its own
+/// state, and recomputes the window function over the whole partition whenever a row in that
+/// partition is added or removed, emitting a negative and a positive for every row whose value
+/// changed as a result.
+///
+/// [`Paginate`]: crate::ops::paginate::Paginate
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Window {
+    /// The direct Ingredient or Base ancestor of this node
+    src: IndexPair,
+    /// The index of this node. Used to look up into our own state
+    our_index: Option<IndexPair>,
+    /// The column index of the window function result column emitted by this node
+    ///
+    /// This is always equal to the number of columns in the parent node (there is one more output
+    /// column than input columns)
+    ///
+    /// Set during [`Ingredient::on_connected`]
+    output_col: Option<usize>,
+    /// The list of column indices that we're partitioning by
+    group_by: Vec<usize>,
+    /// The ordering for rows within each partition
+    order: Order,
+    /// The window function to compute
+    function: WindowFunction,
+    /// The column the window function is computed over, if any
+    over: Option<usize>,
+    /// The type of the window function result column
+    out_ty: DfType,
+}
+
+impl Window {
+    /// Construct a new Window operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - this operator's ancestor
+    /// * `function` - the window function to compute
+    /// * `over` - the column to compute the window function over, if the function takes one
+    /// * `over_col_ty` - the type of the `over` column, if any
+    /// * `order` - the list of columns to order rows within each partition by
+    /// * `group_by` - the columns that partition the rows. Must not be empty
+    pub fn new(
+        src: NodeIndex,
+        function: WindowFunction,
+        over: Option<usize>,
+        over_col_ty: Option<&DfType>,
+        order: Vec<(usize, OrderType, NullOrder)>,
+        group_by: Vec<usize>,
+    ) -> ReadySetResult<Self> {
+        invariant!(
+            over.is_some() || !function.requires_over_column(),
+            "window function {} requires a column to compute over",
+            function
+        );
+        invariant!(
+            !group_by.is_empty(),
+            "need bogokey for Window without partition columns"
+        );
+
+        Ok(Window {
+            src: src.into(),
+            our_index: None,
+            output_col: None,
+            group_by,
+            order: order.into(),
+            function,
+            over,
+            out_ty: function.output_col_type(over_col_ty),
+        })
+    }
+
+    /// Project the columns we are grouping by out of the given record
+    fn project_group<'rec, R>(&self, rec: &'rec R) -> ReadySetResult<Vec<&'rec DfValue>>
+    where
+        R: Indices<'static, usize, Output = DfValue> + ?Sized,
+    {
+        rec.indices(self.group_by.clone())
+            .map_err(|_| ReadySetError::InvalidRecordLength)
+    }
+
+    /// Return the column index of the window function result column output by this node (which
+    /// will always be the last column)
+    ///
+    /// Returns an error if called before [`Ingredient::on_connected`]
+    fn output_column(&self) -> ReadySetResult<usize> {
+        self.output_col
+            .ok_or_else(|| internal_err!("output_column called before Ingredient::on_connected"))
+    }
+
+    /// Compare two rows of the same partition, breaking ties in the window's ordering by the
+    /// values of the rest of the row (up to the result column at index `out`) so that the order
+    /// of peers (and hence eg their row numbers) is stable across recomputations
+    fn cmp_rows(&self, out: usize, a: &[DfValue], b: &[DfValue]) -> Ordering {
+        self.order
+            .cmp(a, b)
+            .then_with(|| a[..out.min(a.len())].cmp(&b[..out.min(b.len())]))
+    }
+
+    /// Returns the value of the `over` column in the given row, or [`DfValue::None`] if this
+    /// window has no `over` column
+    fn over_value<'a>(&self, row: &'a [DfValue]) -> Cow<'a, DfValue> {
+        match self.over {
+            Some(over) => Cow::Borrowed(&row[over]),
+            None => Cow::Owned(DfValue::None),
+        }
+    }
+
+    fn zero(&self) -> ReadySetResult<DfValue> {
+        match &self.out_ty {
+            DfType::BigInt => Ok(DfValue::Int(Default::default())),
+            DfType::Double => Ok(DfValue::Double(Default::default())),
+            DfType::Numeric { .. } => Ok(DfValue::Numeric(Default::default())),
+            _ => internal!(
+                "Unexpected output type for window function: {}",
+                self.out_ty
+            ),
+        }
+    }
+
+    /// Compute the value of the window function for each row in `rows`, which must be sorted
+    /// according to [`Self::cmp_rows`]
+    fn compute(&self, rows: &[Cow<[DfValue]>]) -> ReadySetResult<Vec<DfValue>> {
+        let is_peer = |i: usize, j: usize| self.order.cmp(&rows[i], &rows[j]) == Ordering::Equal;
+
+        let mut values = Vec::with_capacity(rows.len());
+        match self.function {
+            WindowFunction::RowNumber => {
+                values.extend((1..=rows.len()).map(|n| DfValue::from(n as i64)));
+            }
+            WindowFunction::Rank | WindowFunction::DenseRank => {
+                let mut rank = 0i64;
+                let mut dense_rank = 0i64;
+                for i in 0..rows.len() {
+                    if i == 0 || !is_peer(i - 1, i) {
+                        rank = i as i64 + 1;
+                        dense_rank += 1;
+                    }
+                    values.push(DfValue::from(if self.function == WindowFunction::Rank {
+                        rank
+                    } else {
+                        dense_rank
+                    }));
+                }
+            }
+            WindowFunction::Lag { offset } => {
+                for i in 0..rows.len() {
+                    values.push(match i.checked_sub(offset) {
+                        Some(j) => self.over_value(&rows[j]).into_owned(),
+                        None => DfValue::None,
+                    });
+                }
+            }
+            WindowFunction::Lead { offset } => {
+                for i in 0..rows.len() {
+                    values.push(match rows.get(i + offset) {
+                        Some(row) => self.over_value(row).into_owned(),
+                        None => DfValue::None,
+                    });
+                }
+            }
+            WindowFunction::Sum | WindowFunction::Count => {
+                // The default window frame when there's an ORDER BY is `RANGE BETWEEN UNBOUNDED
+                // PRECEDING AND CURRENT ROW`, which includes all the peers of the current row - so
+                // we accumulate a whole set of peers at a time, then emit the running total for
+                // each of them.
+                let mut acc = self.zero()?;
+                let mut peers_start = 0;
+                while peers_start < rows.len() {
+                    let mut peers_end = peers_start + 1;
+                    while peers_end < rows.len() && is_peer(peers_start, peers_end) {
+                        peers_end += 1;
+                    }
+
+                    for row in &rows[peers_start..peers_end] {
+                        let value = self.over_value(row);
+                        if self.function == WindowFunction::Count {
+                            if self.over.is_none() || !value.is_none() {
+                                acc = (&acc + &DfValue::Int(1))?;
+                            }
+                        } else if !value.is_none() {
+                            acc = (&acc + value.as_ref())?;
+                        }
+                    }
+
+                    values.extend((peers_start..peers_end).map(|_| acc.clone()));
+                    peers_start = peers_end;
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Called inside of on_input after processing an individual partition of input records, to
+    /// recompute the window function for that partition and emit the rows whose result changed
+    /// into `out`.
+    ///
+    /// Rows loaded from our state will have the previous result of the window function as their
+    /// last column, whereas new rows received in the batch will be one column shorter.
+    fn post_group(
+        &self,
+        out: &mut Vec<Record>,
+        current_group: &mut Vec<Cow<[DfValue]>>,
+    ) -> ReadySetResult<()> {
+        let output_column = self.output_column()?;
+        current_group.sort_by(|a, b| self.cmp_rows(output_column, a, b));
+        let values = self.compute(current_group)?;
+
+        for (mut row, value) in current_group.drain(..).zip(values) {
+            match row.get(output_column) {
+                Some(previous) if *previous == value => {}
+                Some(_) => {
+                    out.push(Record::Negative(row.clone().into()));
+                    row.to_mut()[output_column] = value;
+                    out.push(Record::Positive(row.into()));
+                }
+                None => {
+                    row.to_mut().push(value);
+                    out.push(Record::Positive(row.into()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Ingredient for Window {
+    fn ancestors(&self) -> Vec<NodeIndex> {
+        vec![self.src.as_global()]
+    }
+
+    impl_replace_sibling!(src);
+
+    fn on_connected(&mut self, graph: &Graph) {
+        self.output_col = Some(graph[self.src.as_global()].columns().len());
+    }
+
+    fn on_commit(&mut self, us: NodeIndex, remap: &HashMap<NodeIndex, IndexPair>) {
+        self.src.remap(remap);
+        self.our_index = Some(remap[&us]);
+    }
+
+    fn on_input(
+        &mut self,
+        from: LocalNodeIndex,
+        rs: Records,
+        replay: &ReplayContext,
+        _nodes: &DomainNodes,
+        state: &StateMap,
+        _auxiliary_node_states: &mut AuxiliaryNodeStateMap,
+    ) -> ReadySetResult<ProcessingResult> {
+        debug_assert_eq!(from, *self.src);
+
+        if rs.is_empty() {
+            return Ok(ProcessingResult {
+                results: rs,
+                ..Default::default()
+            });
+        }
+
+        // Chunk the records by partition, making sure to keep the relative order of records
+        // within each partition so that a positive followed by a negative for the same row in
+        // one batch cancel out
+        let mut rs = Vec::from(rs);
+        rs.sort_by(|a: &Record, b: &Record| {
+            self.project_group(&***a)
+                .unwrap_or_default()
+                .cmp(&self.project_group(&***b).unwrap_or_default())
+        });
+
+        let us = self.our_index.unwrap();
+        let db = state
+            .get(*us)
+            .ok_or_else(|| internal_err!("window must have its own state materialized"))?;
+        let output_column = self.output_column()?;
+
+        let mut current_group_key: Vec<DfValue> = vec![];
+        let mut current_group: Vec<Cow<[DfValue]>> = vec![];
+        let mut group_missed = false;
+
+        let mut out = vec![];
+        let mut lookups = vec![];
+        let mut misses = vec![];
+
+        for r in rs {
+            let record_group = self.project_group(r.rec())?;
+            if current_group_key.iter().cmp(record_group.iter().copied()) != Ordering::Equal {
+                // New partition!
+                if !current_group_key.is_empty() && !group_missed {
+                    self.post_group(&mut out, &mut current_group)?;
+                }
+                current_group.clear();
+
+                // Clear and extend to reuse the allocation
+                current_group_key.clear();
+                current_group_key.extend(record_group.into_iter().cloned());
+
+                // Load the whole partition into memory
+                match db.lookup(&self.group_by, &PointKey::from(current_group_key.clone())) {
+                    LookupResult::Some(local_records) => {
+                        if replay.is_partial() {
+                            lookups.push(Lookup {
+                                on: *us,
+                                cols: self.group_by.clone(),
+                                key: current_group_key
+                                    .clone()
+                                    .try_into()
+                                    .map_err(|_| internal_err!("Empty group"))?,
+                            });
+                        }
+
+                        group_missed = false;
+                        current_group.extend(local_records);
+                    }
+                    LookupResult::Missing => {
+                        group_missed = true;
+                    }
+                }
+            }
+
+            if group_missed {
+                misses.push(
+                    Miss::builder()
+                        .on(*us)
+                        .lookup_idx(self.group_by.clone())
+                        .lookup_key(self.group_by.clone())
+                        .replay(replay)
+                        .record(r.into_row())
+                        .build(),
+                );
+                continue;
+            }
+
+            match r {
+                Record::Positive(r) => current_group.push(Cow::Owned(r)),
+                Record::Negative(r) => {
+                    if let Some(pos) = current_group
+                        .iter()
+                        .position(|row| row[..output_column.min(row.len())] == *r)
+                    {
+                        let row = current_group.remove(pos);
+                        // Rows we haven't emitted yet (which don't have a result column) don't
+                        // need to be retracted
+                        if row.len() > output_column {
+                            out.push(Record::Negative(row.into()));
+                        }
+                    }
+                }
+            }
+        }
+
+        if !current_group_key.is_empty() && !group_missed {
+            self.post_group(&mut out, &mut current_group)?;
+        }
+
+        Ok(ProcessingResult {
+            results: out.into(),
+            lookups,
+            misses,
+        })
+    }
+
+    fn suggest_indexes(&self, this: NodeIndex) -> HashMap<NodeIndex, LookupIndex> {
+        HashMap::from([(
+            this,
+            LookupIndex::Strict(Index::hash_map(self.group_by.clone())),
+        )])
+    }
+
+    fn column_source(&self, cols: &[usize]) -> ColumnSource {
+        let output_column = self.output_col;
+        if output_column.map_or(false, |out| cols.contains(&out)) {
+            if cols.len() == 1 {
+                // Lookups on only the window function result require a full replay
+                return ColumnSource::RequiresFullReplay(vec1![self.src.as_global()]);
+            }
+
+            let columns = cols
+                .iter()
+                .copied()
+                .filter(|c| Some(*c) != output_column)
+                .collect::<Vec<_>>();
+            ColumnSource::GeneratedFromColumns(vec1![ColumnRef {
+                node: self.our_index.unwrap().as_global(),
+                columns,
+            }])
+        } else {
+            ColumnSource::ExactCopy(ColumnRef {
+                node: self.src.as_global(),
+                columns: cols.to_vec(),
+            })
+        }
+    }
+
+    fn handle_upquery(&mut self, miss: ColumnMiss) -> ReadySetResult<Vec<ColumnMiss>> {
+        let window_output_column = self.output_column()?;
+        let output_column = miss
+            .column_indices
+            .iter()
+            .position(|ci| *ci == window_output_column)
+            .ok_or_else(|| internal_err!("Window upquery did not miss on the result column"))?;
+
+        let missed_keys = miss.missed_keys.try_mapped(|k| {
+            let mut res = Ok(());
+            let k = k.map_endpoints(|mut r| {
+                if r.remove(output_column).is_err() {
+                    res = Err(internal_err!(
+                        "Window upquery missed only on the result column"
+                    ));
+                }
+                r
+            });
+            res.map(|()| k)
+        })?;
+
+        Ok(vec![ColumnMiss {
+            node: *self.our_index.unwrap(),
+            column_indices: self.group_by.clone(),
+            missed_keys,
+        }])
+    }
+
+    fn description(&self, detailed: bool) -> String {
+        if !detailed {
+            return "Window".into();
+        }
+
+        format!(
+            "Window {}({}) γ[{}] o[{}]",
+            self.function,
+            self.over.iter().join(""),
+            self.group_by.iter().join(", "),
+            self.order
+        )
+    }
+
+    fn is_selective(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test::MockGraph;
+
+    fn setup(function: WindowFunction) -> MockGraph {
+        let mut g = MockGraph::new();
+        let s = g.add_base("source", &["x", "y"]);
+
+        // function(x) OVER (PARTITION BY y ORDER BY x)
+        g.set_op(
+            "window",
+            &["x", "y", "w"],
+            Window::new(
+                s.as_global(),
+                function,
+                Some(0),
+                Some(&DfType::BigInt),
+                vec![(0, OrderType::OrderAscending, NullOrder::NullsFirst)],
+                vec![1],
+            )
+            .unwrap(),
+            true,
+        );
+        g
+    }
+
+    fn with_value<V: Into<DfValue>>(row: &[DfValue], value: V) -> Vec<DfValue> {
+        let mut res = row.to_vec();
+        res.push(value.into());
+        res
+    }
+
+    #[test]
+    fn row_number_single_row() {
+        let mut g = setup(WindowFunction::RowNumber);
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let res = g.narrow_one_row(r1a.clone(), true);
+        assert_eq!(res, vec![with_value(&r1a, 1)].into());
+    }
+
+    #[test]
+    fn row_number_multiple_groups() {
+        let mut g = setup(WindowFunction::RowNumber);
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+        let r1b: Vec<DfValue> = vec![1.into(), "b".into()];
+        let res = g.narrow_one(vec![r2a.clone(), r1b.clone(), r1a.clone()], true);
+        assert_eq!(
+            res,
+            vec![
+                with_value(&r1a, 1),
+                with_value(&r2a, 2),
+                with_value(&r1b, 1),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn row_number_insert_shifts_later_rows() {
+        let mut g = setup(WindowFunction::RowNumber);
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+        let r3a: Vec<DfValue> = vec![3.into(), "a".into()];
+        g.narrow_one(vec![r1a.clone(), r3a.clone()], true);
+
+        let res = g.narrow_one_row(r2a.clone(), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_value(&r2a, 2), true),
+                (with_value(&r3a, 2), false),
+                (with_value(&r3a, 3), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn row_number_delete_shifts_later_rows() {
+        let mut g = setup(WindowFunction::RowNumber);
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+        let r3a: Vec<DfValue> = vec![3.into(), "a".into()];
+        g.narrow_one(vec![r1a.clone(), r2a.clone(), r3a.clone()], true);
+
+        let res = g.narrow_one_row((r1a.clone(), false), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_value(&r1a, 1), false),
+                (with_value(&r2a, 2), false),
+                (with_value(&r2a, 1), true),
+                (with_value(&r3a, 3), false),
+                (with_value(&r3a, 2), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn rank_and_dense_rank_with_ties() {
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r1a_dup: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+
+        let mut g = setup(WindowFunction::Rank);
+        let res = g.narrow_one(vec![r1a.clone(), r1a_dup.clone(), r2a.clone()], true);
+        assert_eq!(
+            res,
+            vec![
+                with_value(&r1a, 1),
+                with_value(&r1a_dup, 1),
+                with_value(&r2a, 3),
+            ]
+            .into()
+        );
+
+        let mut g = setup(WindowFunction::DenseRank);
+        let res = g.narrow_one(vec![r1a.clone(), r1a_dup.clone(), r2a.clone()], true);
+        assert_eq!(
+            res,
+            vec![
+                with_value(&r1a, 1),
+                with_value(&r1a_dup, 1),
+                with_value(&r2a, 2),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn lag_and_lead() {
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+
+        let mut g = setup(WindowFunction::Lag { offset: 1 });
+        let res = g.narrow_one(vec![r1a.clone(), r2a.clone()], true);
+        assert_eq!(
+            res,
+            vec![with_value(&r1a, DfValue::None), with_value(&r2a, 1)].into()
+        );
+
+        let mut g = setup(WindowFunction::Lead { offset: 1 });
+        let res = g.narrow_one(vec![r1a.clone(), r2a.clone()], true);
+        assert_eq!(
+            res,
+            vec![with_value(&r1a, 2), with_value(&r2a, DfValue::None)].into()
+        );
+    }
+
+    #[test]
+    fn running_count() {
+        let mut g = setup(WindowFunction::Count);
+        let r1a: Vec<DfValue> = vec![1.into(), "a".into()];
+        let r2a: Vec<DfValue> = vec![2.into(), "a".into()];
+        let r3a: Vec<DfValue> = vec![3.into(), "a".into()];
+        g.narrow_one(vec![r1a.clone(), r3a.clone()], true);
+
+        let res = g.narrow_one_row(r2a.clone(), true);
+        assert_eq!(
+            res,
+            vec![
+                (with_value(&r2a, 2), true),
+                (with_value(&r3a, 2), false),
+                (with_value(&r3a, 3), true),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn column_source_for_window_result() {
+        let g = setup(WindowFunction::RowNumber);
+        assert_eq!(
+            g.node().column_source(&[1, 2]),
+            ColumnSource::GeneratedFromColumns(vec1![ColumnRef {
+                node: g.node_index().as_global(),
+                columns: vec![1],
+            }])
+        );
+    }
+
+    #[test]
+    fn suggest_indexes() {
+        let g = setup(WindowFunction::RowNumber);
+        let res = g.node().suggest_indexes(g.node_index().as_global());
+        assert_eq!(
+            res[&g.node_index().as_global()],
+            LookupIndex::Strict(Index::hash_map(vec![1]))
+        );
+    }
+}
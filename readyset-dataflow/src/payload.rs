@@ -243,6 +243,10 @@ pub enum EvictRequest {
     ///
     /// Note: this variant is intended for tests, and not practical for production.
     SingleKey { tag: Tag, key: Option<Vec<DfValue>> },
+
+    /// Evict from every reader in the target domain whose state has grown beyond its configured
+    /// per-cache memory limit, until each of those readers is back under its limit.
+    OverBudget,
 }
 
 /// A request issued to a domain through the worker RPC interface.
@@ -632,6 +632,15 @@ pub enum ReadySetError {
     #[error("Resnapshot needed")]
     ResnapshotNeeded,
 
+    /// The contents of a table changed upstream without those changes being replicated, so its
+    /// contents have to be snapshotted again. This triggers a partial resnapshot, which replaces
+    /// the contents of the table in place.
+    #[error("Resnapshot of table {} needed", table.display_unquoted())]
+    TableResnapshotNeeded {
+        /// The table to resnapshot
+        table: Relation,
+    },
+
     /// An unrecoverable error occurred, requiring a full resnapshot
     #[error("Fatal error requires full resnapshot")]
     FullResnapshotNeeded,
@@ -129,7 +129,7 @@ pub(crate) async fn recreate_test_database(url: &DatabaseURL) -> anyhow::Result<
     let mut admin_url = url.clone();
     admin_url.set_db_name(match url.database_type() {
         DatabaseType::PostgreSQL => "postgres".to_owned(),
-        DatabaseType::MySQL => "mysql".to_owned(),
+        DatabaseType::MySQL | DatabaseType::MariaDB => "mysql".to_owned(),
     });
     let mut admin_conn = admin_url
         .connect(None)
@@ -491,6 +491,7 @@ impl TestScript {
             builder.set_allow_mixed_comparisons(true);
             builder.set_allow_straddled_joins(true);
             builder.set_allow_post_lookup(true);
+            builder.set_allow_window_functions(true);
 
             if run_opts.enable_reuse {
                 builder.set_reuse(Some(ReuseConfigType::Finkelstein))
@@ -563,11 +564,13 @@ impl TestScript {
                 view_cache.new_local(),
                 ReadBehavior::Blocking,
                 match database_type {
-                    DatabaseType::MySQL => readyset_data::Dialect::DEFAULT_MYSQL,
+                    DatabaseType::MySQL | DatabaseType::MariaDB => {
+                        readyset_data::Dialect::DEFAULT_MYSQL
+                    }
                     DatabaseType::PostgreSQL => readyset_data::Dialect::DEFAULT_POSTGRESQL,
                 },
                 match database_type {
-                    DatabaseType::MySQL => nom_sql::Dialect::MySQL,
+                    DatabaseType::MySQL | DatabaseType::MariaDB => nom_sql::Dialect::MySQL,
                     DatabaseType::PostgreSQL => nom_sql::Dialect::PostgreSQL,
                 },
                 Default::default(),
@@ -613,7 +616,7 @@ impl TestScript {
             }
 
             match database_type {
-                DatabaseType::MySQL => MySqlIntermediary::run_on_tcp(
+                DatabaseType::MySQL | DatabaseType::MariaDB => MySqlIntermediary::run_on_tcp(
                     readyset_mysql::Backend {
                         noria: make_backend!(MySqlUpstream, MySqlQueryHandler, Dialect::MySQL,),
                         enable_statement_logging: false,
@@ -642,7 +645,9 @@ impl TestScript {
         (
             task,
             match database_type {
-                DatabaseType::MySQL => mysql::OptsBuilder::default().tcp_port(addr.port()).into(),
+                DatabaseType::MySQL | DatabaseType::MariaDB => {
+                    mysql::OptsBuilder::default().tcp_port(addr.port()).into()
+                }
                 DatabaseType::PostgreSQL => {
                     let mut config = pgsql::Config::default();
                     config.host("localhost");
@@ -211,21 +211,39 @@ impl MirGraph {
                 order_by,
                 returned_cols,
                 aggregates,
+                projection,
+                filter,
                 ..
             } => {
                 let mut columns = self.columns(node);
                 columns.extend(
                     keys.iter()
                         .map(|(c, _)| c.clone())
-                        .chain(order_by.iter().flatten().map(|(c, _)| c.clone()))
+                        .chain(order_by.iter().flatten().map(|(c, _, _)| c.clone()))
                         .chain(returned_cols.iter().flatten().cloned())
                         .chain(aggregates.iter().flat_map(|aggs| {
-                            aggs.group_by
-                                .clone()
-                                .into_iter()
-                                .chain(aggs.aggregates.iter().map(|agg| agg.column.clone()))
-                        })),
+                            aggs.group_by.clone().into_iter().chain(
+                                aggs.aggregates.iter().flat_map(|agg| {
+                                    // Include the hidden columns that some aggregates are
+                                    // recomputed from
+                                    iter::once(&agg.column)
+                                        .chain(agg.function.columns())
+                                        .cloned()
+                                }),
+                            )
+                        }))
+                        .chain(projection.iter().flatten().map(|(c, _)| c.clone())),
                 );
+                for c in projection
+                    .iter()
+                    .flatten()
+                    .flat_map(|(_, expr)| expr.referred_columns())
+                    .chain(filter.iter().flat_map(|expr| expr.referred_columns()))
+                {
+                    if !columns.iter().any(|col| col == c) {
+                        columns.push(c.clone().into())
+                    }
+                }
                 columns
             }
             MirNodeInner::Filter { conditions } => {
@@ -237,7 +255,43 @@ impl MirGraph {
                 }
                 columns
             }
-            MirNodeInner::Join { on, project } | MirNodeInner::LeftJoin { on, project } => {
+            MirNodeInner::Window {
+                order,
+                group_by,
+                on,
+                output_column,
+                ..
+            } => {
+                // Windows need all the columns of their parent (which they pass through), but not
+                // the column they compute themselves
+                let mut columns = self.columns(node);
+                columns.retain(|c| c != output_column);
+                for c in group_by
+                    .iter()
+                    .chain(order.iter().flatten().map(|(c, _, _)| c))
+                    .chain(on)
+                {
+                    if !columns.contains(c) {
+                        columns.push(c.clone())
+                    }
+                }
+                columns
+            }
+            MirNodeInner::FixedPoint { parent, anchor, .. } => {
+                // Fixed points need all the columns of their parent, including the ones they don't
+                // emit
+                let mut columns = self.columns(node);
+                columns.push(parent.clone());
+                columns.push(anchor.clone());
+                columns
+            }
+            MirNodeInner::Join { on, project }
+            | MirNodeInner::LeftJoin { on, project }
+            | MirNodeInner::FullJoin { on, project }
+            | MirNodeInner::SemiJoin { on, project }
+            | MirNodeInner::AntiJoin { on, project }
+            | MirNodeInner::DependentSemiJoin { on, project }
+            | MirNodeInner::DependentAntiJoin { on, project } => {
                 let mut columns = project.clone();
                 for c in on.iter().flat_map(|(lc, rc)| [lc, rc]) {
                     if !columns.iter().any(|col| col == c) {
@@ -292,8 +346,13 @@ impl MirGraph {
                 .collect(),
             MirNodeInner::Join { project, .. }
             | MirNodeInner::LeftJoin { project, .. }
+            | MirNodeInner::FullJoin { project, .. }
             | MirNodeInner::DependentJoin { project, .. }
-            | MirNodeInner::DependentLeftJoin { project, .. } => project.clone(),
+            | MirNodeInner::DependentLeftJoin { project, .. }
+            | MirNodeInner::SemiJoin { project, .. }
+            | MirNodeInner::AntiJoin { project, .. }
+            | MirNodeInner::DependentSemiJoin { project, .. }
+            | MirNodeInner::DependentAntiJoin { project, .. } => project.clone(),
             MirNodeInner::JoinAggregates => {
                 let cols = self
                     // see note [edge-ordering]
@@ -324,6 +383,14 @@ impl MirGraph {
                 .into_iter()
                 .chain(iter::once(MirColumn::named(&*PAGE_NUMBER_COL)))
                 .collect(),
+            MirNodeInner::Window { output_column, .. } => parent_columns()
+                .into_iter()
+                .chain(iter::once(output_column.clone()))
+                .collect(),
+            MirNodeInner::FixedPoint { parent, anchor, .. } => parent_columns()
+                .into_iter()
+                .filter(|c| c != parent && c != anchor)
+                .collect(),
             MirNodeInner::Distinct { group_by } => group_by
                 .iter()
                 .cloned()
@@ -346,6 +413,16 @@ impl MirGraph {
             }
         }
 
+        if self.graph[node].inner.is_semi_or_anti_join() {
+            // Semi- and anti-joins can only ever project columns from their left parent
+            // see note [edge-ordering]
+            return self.columns(node).contains(column)
+                || self
+                    .sorted_ancestors(node)
+                    .next()
+                    .map_or(false, |left| self.provides_column(left, column));
+        }
+
         self.columns(node).contains(column)
             || self
                 .graph
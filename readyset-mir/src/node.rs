@@ -122,7 +122,8 @@ mod tests {
         use dataflow::ops::grouped::aggregate::Aggregation;
         use dataflow::ops::grouped::extremum::Extremum;
         use dataflow::ops::union::DuplicateMode;
-        use nom_sql::{BinaryOperator, ColumnSpecification, Expr, OrderType, SqlType};
+        use dataflow::ops::window::WindowFunction;
+        use nom_sql::{BinaryOperator, ColumnSpecification, Expr, NullOrder, OrderType, SqlType};
         use readyset_client::ViewPlaceholder;
 
         use super::*;
@@ -358,6 +359,8 @@ mod tests {
                 returned_cols: None,
                 default_row: None,
                 aggregates: None,
+                projection: None,
+                filter: None,
             })
         }
 
@@ -367,6 +370,7 @@ mod tests {
                 order: Some(vec![(
                     Column::new(Some("base"), "a"),
                     OrderType::OrderAscending,
+                    NullOrder::NullsLast,
                 )]),
                 group_by: vec![Column::new(Some("base"), "b")],
                 limit: 3,
@@ -380,6 +384,7 @@ mod tests {
                     order: Some(vec![(
                         Column::new(Some("base"), "a"),
                         OrderType::OrderAscending,
+                        NullOrder::NullsLast,
                     )]),
                     group_by: vec![Column::new(Some("base"), "b")],
                     limit: 3,
@@ -392,6 +397,28 @@ mod tests {
             )
         }
 
+        #[test]
+        fn window() {
+            has_columns_single_parent(
+                MirNodeInner::Window {
+                    order: Some(vec![(
+                        Column::new(Some("base"), "a"),
+                        OrderType::OrderAscending,
+                        NullOrder::NullsLast,
+                    )]),
+                    group_by: vec![Column::new(Some("base"), "b")],
+                    on: None,
+                    output_column: Column::named("row_number"),
+                    kind: WindowFunction::RowNumber,
+                },
+                vec![
+                    Column::new(Some("base"), "a"),
+                    Column::new(Some("base"), "b"),
+                    Column::named("row_number"),
+                ],
+            )
+        }
+
         #[test]
         fn join_aggregates() {
             let mut graph = MirGraph::new();
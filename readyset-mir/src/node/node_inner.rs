@@ -4,11 +4,13 @@ use common::{DfValue, IndexType};
 use dataflow::ops::grouped::aggregate::Aggregation;
 use dataflow::ops::grouped::extremum::Extremum;
 use dataflow::ops::union;
+use dataflow::ops::window::WindowFunction;
 use dataflow::PostLookupAggregates;
 use derive_more::From;
 use itertools::Itertools;
 use nom_sql::{
-    BinaryOperator, ColumnSpecification, DialectDisplay, Expr, OrderType, Relation, SqlIdentifier,
+    BinaryOperator, ColumnSpecification, DialectDisplay, Expr, NullOrder, OrderType, Relation,
+    SqlIdentifier,
 };
 use readyset_client::{PlaceholderIdx, ViewPlaceholder};
 use readyset_errors::{internal, ReadySetResult};
@@ -172,6 +174,21 @@ pub enum MirNodeInner {
         /// Columns (from both parents) to project in the output.
         project: Vec<Column>,
     },
+    /// Node which computes a *full outer* join on its two parents, emitting all rows from both
+    /// parents, padded with NULLs for rows in either parent that have no matching rows in the
+    /// other
+    ///
+    /// Converted to [`Join`] with [`JoinType::Full`] when lowering to dataflow.
+    ///
+    /// [`Join`]: dataflow::ops::join::Join
+    /// [`JoinType::Full`]: dataflow::ops::join::JoinType::Full
+    FullJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from both parents) to project in the output.
+        project: Vec<Column>,
+    },
     /// Join where nodes in the right-hand side depend on columns in the left-hand side
     /// (referencing tables in `dependent_tables`). These are created during compilation for
     /// correlated subqueries, and must be removed entirely by rewrite passes before lowering
@@ -206,6 +223,60 @@ pub enum MirNodeInner {
         /// Columns (from both parents) to project in the output.
         project: Vec<Column>,
     },
+    /// Node which computes a *semi*-join on its two parents, emitting each row in the left parent
+    /// exactly once if there are any rows in the right where the values in `on_right` are equal to
+    /// the values of `on_left` on the left. Used to compile `EXISTS` subqueries.
+    ///
+    /// Converted to [`Join`] with [`JoinType::Semi`] when lowering to dataflow.
+    ///
+    /// [`Join`]: dataflow::ops::join::Join
+    /// [`JoinType::Semi`]: dataflow::ops::join::JoinType::Semi
+    SemiJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from the left parent only) to project in the output.
+        project: Vec<Column>,
+    },
+    /// Node which computes an *anti*-join on its two parents, emitting each row in the left parent
+    /// exactly once if there are *no* rows in the right where the values in `on_right` are equal
+    /// to the values of `on_left` on the left. Used to compile `NOT EXISTS` subqueries.
+    ///
+    /// Converted to [`Join`] with [`JoinType::Anti`] when lowering to dataflow.
+    ///
+    /// [`Join`]: dataflow::ops::join::Join
+    /// [`JoinType::Anti`]: dataflow::ops::join::JoinType::Anti
+    AntiJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from the left parent only) to project in the output.
+        project: Vec<Column>,
+    },
+    /// Semi-join where nodes in the right-hand side depend on columns in the left-hand side. Like
+    /// [`DependentJoin`], these must be removed entirely by rewrite passes before lowering to
+    /// dataflow.
+    ///
+    /// [`DependentJoin`]: MirNodeInner::DependentJoin
+    DependentSemiJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from the left parent only) to project in the output.
+        project: Vec<Column>,
+    },
+    /// Anti-join where nodes in the right-hand side depend on columns in the left-hand side. Like
+    /// [`DependentJoin`], these must be removed entirely by rewrite passes before lowering to
+    /// dataflow.
+    ///
+    /// [`DependentJoin`]: MirNodeInner::DependentJoin
+    DependentAntiJoin {
+        /// Columns to use as the join keys. Each tuple corresponds to a column in the left parent
+        /// and column in the right parent.
+        on: Vec<(Column, Column)>,
+        /// Columns (from the left parent only) to project in the output.
+        project: Vec<Column>,
+    },
     /// Represents view key placeholders in a query that have not yet been added to the [`Leaf`][]
     /// node of the query.
     ///
@@ -254,7 +325,7 @@ pub enum MirNodeInner {
     /// [`Paginate`]: dataflow::ops::paginate::Paginate
     Paginate {
         /// Set of columns used for ordering the results
-        order: Option<Vec<(Column, OrderType)>>,
+        order: Option<Vec<(Column, OrderType, NullOrder)>>,
         /// Set of columns that are indexed to form a unique grouping of results
         group_by: Vec<Column>,
         /// How many rows per page
@@ -267,13 +338,48 @@ pub enum MirNodeInner {
     /// [`TopK`]: dataflow::ops::topk::TopK
     TopK {
         /// Set of columns used for ordering the results
-        order: Option<Vec<(Column, OrderType)>>,
+        order: Option<Vec<(Column, OrderType, NullOrder)>>,
         /// Set of columns that are indexed to form a unique grouping of results
         group_by: Vec<Column>,
         /// Numeric literal that determines the number of results stored per group. Taken from the
         /// LIMIT clause
         limit: usize,
     },
+    /// Node which computes a window function over the rows in each partition, ordered by a set of
+    /// columns, and emits the result for each row as an extra column named by `output_column`.
+    ///
+    /// Converted to [`Window`] when lowering to dataflow.
+    ///
+    /// [`Window`]: dataflow::ops::window::Window
+    Window {
+        /// Set of columns used for ordering the rows within each partition
+        order: Option<Vec<(Column, OrderType, NullOrder)>>,
+        /// Set of columns that partition the rows (the `PARTITION BY` clause)
+        group_by: Vec<Column>,
+        /// The column the window function is computed over, if any
+        on: Option<Column>,
+        /// The column emitted by this node for the result of the window function
+        output_column: Column,
+        /// The window function to compute
+        kind: WindowFunction,
+    },
+    /// Node which computes the fixed point of a recursive common table expression, given a parent
+    /// containing the rows of both the non-recursive and recursive terms of the CTE. Emits all the
+    /// columns of its parent except for `parent` and `anchor`.
+    ///
+    /// Converted to [`FixedPoint`] when lowering to dataflow.
+    ///
+    /// [`FixedPoint`]: dataflow::ops::fixed_point::FixedPoint
+    FixedPoint {
+        /// The column that rows from the recursive term are joined to their parent on
+        key: Column,
+        /// The column containing the value of the `key` column of the parent of each row from the
+        /// recursive term
+        parent: Column,
+        /// The column which is true for rows from the non-recursive term of the CTE, and false for
+        /// rows from the recursive term
+        anchor: Column,
+    },
     /// Node which emits only distinct rows per some group.
     ///
     /// Converted to [`Aggregator`] with [`Aggregation::Count`] when lowering to dataflow.
@@ -301,7 +407,7 @@ pub enum MirNodeInner {
         lowered_to_df: bool,
 
         /// Optional set of columns and direction to order the results of lookups to this leaf
-        order_by: Option<Vec<(Column, OrderType)>>,
+        order_by: Option<Vec<(Column, OrderType, NullOrder)>>,
         /// Optional limit for the set of results to lookups to this leaf
         limit: Option<usize>,
         /// Optional set of expression columns requested in the original query
@@ -311,6 +417,12 @@ pub enum MirNodeInner {
         default_row: Option<Vec<DfValue>>,
         /// Aggregates to perform in the reader on result sets for keys after performing the lookup
         aggregates: Option<PostLookupAggregates<Column>>,
+        /// Expressions to recompute the values of columns with in the reader, after performing
+        /// `aggregates`
+        projection: Option<Vec<(Column, Expr)>>,
+        /// Filter to apply in the reader to result sets for keys, after performing `aggregates`
+        /// and `projection`
+        filter: Option<Expr>,
     },
 }
 
@@ -327,6 +439,8 @@ impl MirNodeInner {
             returned_cols: None,
             default_row: None,
             aggregates: None,
+            projection: None,
+            filter: None,
         }
     }
 
@@ -352,8 +466,13 @@ impl MirNodeInner {
             }
             MirNodeInner::Join { project, .. }
             | MirNodeInner::LeftJoin { project, .. }
+            | MirNodeInner::FullJoin { project, .. }
             | MirNodeInner::DependentJoin { project, .. }
-            | MirNodeInner::DependentLeftJoin { project, .. } => {
+            | MirNodeInner::DependentLeftJoin { project, .. }
+            | MirNodeInner::SemiJoin { project, .. }
+            | MirNodeInner::AntiJoin { project, .. }
+            | MirNodeInner::DependentSemiJoin { project, .. }
+            | MirNodeInner::DependentAntiJoin { project, .. } => {
                 if !project.contains(&c) {
                     project.push(c);
                 }
@@ -385,14 +504,32 @@ impl MirNodeInner {
         }
     }
 
-    /// Returns `true` if self is a [`DependentJoin`] or [`DependentLeftJoin`].
+    /// Returns `true` if self is a [`DependentJoin`], [`DependentLeftJoin`],
+    /// [`DependentSemiJoin`], or [`DependentAntiJoin`].
     ///
     /// [`DependentJoin`]: MirNodeInner::DependentJoin
     /// [`DependentLeftJoin`]: MirNodeInner::DependentLeftJoin
+    /// [`DependentSemiJoin`]: MirNodeInner::DependentSemiJoin
+    /// [`DependentAntiJoin`]: MirNodeInner::DependentAntiJoin
     pub fn is_dependent_join(&self) -> bool {
         matches!(
             self,
-            Self::DependentJoin { .. } | Self::DependentLeftJoin { .. }
+            Self::DependentJoin { .. }
+                | Self::DependentLeftJoin { .. }
+                | Self::DependentSemiJoin { .. }
+                | Self::DependentAntiJoin { .. }
+        )
+    }
+
+    /// Returns `true` if self is a (possibly dependent) semi-join or anti-join, which only emit
+    /// columns from their left parent
+    pub fn is_semi_or_anti_join(&self) -> bool {
+        matches!(
+            self,
+            Self::SemiJoin { .. }
+                | Self::AntiJoin { .. }
+                | Self::DependentSemiJoin { .. }
+                | Self::DependentAntiJoin { .. }
         )
     }
 
@@ -413,11 +550,37 @@ impl MirNodeInner {
             } => {
                 let op_string = match *kind {
                     Aggregation::Count { .. } => format!("|*|({})", on.name.as_str()),
+                    Aggregation::CountDistinct => {
+                        format!("|*|(DISTINCT {})", on.name.as_str())
+                    }
                     Aggregation::Sum => format!("𝛴({})", on.name.as_str()),
+                    Aggregation::SumDistinct => format!("𝛴(DISTINCT {})", on.name.as_str()),
                     Aggregation::Avg => format!("AVG({})", on.name.as_str()),
+                    Aggregation::AvgDistinct => format!("AVG(DISTINCT {})", on.name.as_str()),
                     Aggregation::GroupConcat { separator: ref s } => {
                         format!("||([{}], \"{}\")", on.name.as_str(), s.as_str())
                     }
+                    Aggregation::VarPop => format!("VAR_POP({})", on.name.as_str()),
+                    Aggregation::VarSamp => format!("VAR_SAMP({})", on.name.as_str()),
+                    Aggregation::StddevPop => format!("STDDEV_POP({})", on.name.as_str()),
+                    Aggregation::StddevSamp => format!("STDDEV_SAMP({})", on.name.as_str()),
+                    Aggregation::BoolAnd => format!("BOOL_AND({})", on.name.as_str()),
+                    Aggregation::BoolOr => format!("BOOL_OR({})", on.name.as_str()),
+                    Aggregation::BitAnd => format!("BIT_AND({})", on.name.as_str()),
+                    Aggregation::BitOr => format!("BIT_OR({})", on.name.as_str()),
+                    Aggregation::ArrayAgg => format!("ARRAY_AGG({})", on.name.as_str()),
+                    Aggregation::JsonAgg { jsonb: false } => {
+                        format!("JSON_AGG({})", on.name.as_str())
+                    }
+                    Aggregation::JsonAgg { jsonb: true } => {
+                        format!("JSONB_AGG({})", on.name.as_str())
+                    }
+                    Aggregation::ApproxCountDistinct => {
+                        format!("APPROX_COUNT_DISTINCT({})", on.name.as_str())
+                    }
+                    Aggregation::ApproxPercentile => {
+                        format!("APPROX_PERCENTILE({})", on.name.as_str())
+                    }
                 };
                 let group_cols = group_by
                     .iter()
@@ -519,6 +682,26 @@ impl MirNodeInner {
                     jc
                 )
             }
+            MirNodeInner::FullJoin {
+                ref on,
+                ref project,
+                ..
+            } => {
+                let jc = on
+                    .iter()
+                    .map(|(l, r)| format!("{}:{}", l.name, r.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "⟗ [{} on {}]",
+                    project
+                        .iter()
+                        .map(|c| c.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    jc
+                )
+            }
             MirNodeInner::DependentJoin {
                 ref on,
                 ref project,
@@ -545,6 +728,37 @@ impl MirNodeInner {
                         .join(", ")
                 )
             }
+            MirNodeInner::SemiJoin {
+                ref on,
+                ref project,
+            }
+            | MirNodeInner::AntiJoin {
+                ref on,
+                ref project,
+            }
+            | MirNodeInner::DependentSemiJoin {
+                ref on,
+                ref project,
+            }
+            | MirNodeInner::DependentAntiJoin {
+                ref on,
+                ref project,
+            } => {
+                let op = match self {
+                    MirNodeInner::SemiJoin { .. } => "⋈∃",
+                    MirNodeInner::AntiJoin { .. } => "⋈∄",
+                    MirNodeInner::DependentSemiJoin { .. } => "⋈∃D",
+                    _ => "⋈∄D",
+                };
+                format!(
+                    "{} | {} on: {}",
+                    op,
+                    project.iter().map(|c| &c.name).join(", "),
+                    on.iter()
+                        .map(|(l, r)| format!("{}:{}", l.name, r.name))
+                        .join(", ")
+                )
+            }
             MirNodeInner::Project { ref emit } => format!("π [{}]", emit.iter().join(", ")),
             MirNodeInner::Distinct { ref group_by } => {
                 let key_cols = group_by
@@ -568,6 +782,28 @@ impl MirNodeInner {
             } => {
                 format!("TopK [k: {}, {:?}]", limit, order)
             }
+            MirNodeInner::Window {
+                ref order,
+                ref group_by,
+                ref on,
+                ref kind,
+                ..
+            } => {
+                format!(
+                    "Window [{}({}) γ: {}, {:?}]",
+                    kind,
+                    on.iter().map(|c| &c.name).join(""),
+                    group_by.iter().map(|c| &c.name).join(", "),
+                    order
+                )
+            }
+            MirNodeInner::FixedPoint {
+                ref key,
+                ref parent,
+                ..
+            } => {
+                format!("FixedPoint [{} = {}]", parent.name, key.name)
+            }
             MirNodeInner::Union {
                 ref emit,
                 ref duplicate_mode,
@@ -575,6 +811,9 @@ impl MirNodeInner {
                 let symbol = match duplicate_mode {
                     union::DuplicateMode::BagUnion => '⊎',
                     union::DuplicateMode::UnionAll => '⋃',
+                    union::DuplicateMode::UnionDistinct => '∪',
+                    union::DuplicateMode::Intersect => '∩',
+                    union::DuplicateMode::Except => '∖',
                 };
                 emit.iter()
                     .map(|c| {
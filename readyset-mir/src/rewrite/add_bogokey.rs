@@ -30,9 +30,11 @@ pub(crate) fn add_bogokey_if_necessary(query: &mut MirQuery<'_>) -> ReadySetResu
 
     // Find the node we're going to insert the bogokey project node above
     //
-    // Usually this'll be the first leaf project node, but in the case of topk or paginate with an
-    // empty group_by we insert above those instead, since those both happen to need a group_by.
+    // Usually this'll be the first leaf project node, but in the case of topk, paginate, or window
+    // nodes with an empty group_by we insert above those instead, since those all happen to need a
+    // group_by.
     let mut node_to_insert_above = query.leaf();
+    let mut lifted_over = vec![];
     while let Some(parent) = query
         .ancestors(node_to_insert_above)?
         .first()
@@ -43,11 +45,13 @@ pub(crate) fn add_bogokey_if_necessary(query: &mut MirQuery<'_>) -> ReadySetResu
                     inner,
                     MirNodeInner::TopK { group_by, .. }
                     | MirNodeInner::Paginate { group_by, .. }
+                    | MirNodeInner::Window { group_by, .. }
                     if group_by.is_empty()
                 )
         })
     {
         node_to_insert_above = *parent;
+        lifted_over.push(node_to_insert_above);
         invariant_eq!(query.ancestors(node_to_insert_above)?.len(), 1);
     }
     trace!(
@@ -83,10 +87,12 @@ pub(crate) fn add_bogokey_if_necessary(query: &mut MirQuery<'_>) -> ReadySetResu
         keys.push((Column::named("bogokey"), ViewPlaceholder::Generated))
     }
 
-    if let MirNodeInner::TopK { group_by, .. } =
-        &mut query.get_node_mut(node_to_insert_above).unwrap().inner
-    {
-        group_by.push(Column::named("bogokey"))
+    for node in lifted_over {
+        if let MirNodeInner::TopK { group_by, .. } | MirNodeInner::Window { group_by, .. } =
+            &mut query.get_node_mut(node).unwrap().inner
+        {
+            group_by.push(Column::named("bogokey"))
+        }
     }
 
     Ok(())
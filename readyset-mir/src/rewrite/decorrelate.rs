@@ -19,6 +19,11 @@ use crate::{Column, NodeIndex};
 /// - [`Project`], [`Join`], [`LeftJoin`], and dependent left or inner joins *other* than the one
 ///   this filter depends on are all totally commutative with filters, so can be swapped in position
 ///   with those filters with impunity
+/// - Semi- and anti-joins other than the one this filter depends on are commutative with filters on
+///   their left-hand side
+/// - Equality filters can be turned into join keys of the dependent semi- or anti-join they depend
+///   on, but any other dependent filters can't be lifted above those joins (since they don't
+///   project any columns from the right-hand side)
 /// - Grouped nodes ([`Aggregation`], [`Extremum`] and [`Distinct`]) require adding any *non*
 ///   dependent columns mentioned in the filter to the group-by of the node.
 /// - All other nodes currently return an [unsupported error][] - it *is* theoretically possible to
@@ -82,6 +87,8 @@ fn push_dependent_filter(
         node_idx.index(),
         child_idx.index()
     );
+    let from_left_parent = query.ancestors(child_idx)?.first() == Some(&node_idx);
+
     let should_insert = match &mut query.get_node_mut(child_idx).unwrap().inner {
         MirNodeInner::DependentJoin { on, .. } | MirNodeInner::DependentLeftJoin { on, .. }
             if child_idx == dependent_join_idx =>
@@ -97,6 +104,36 @@ fn push_dependent_filter(
                 DependentCondition::FullyDependent { .. } => true,
             }
         }
+        MirNodeInner::DependentSemiJoin { on, .. } | MirNodeInner::DependentAntiJoin { on, .. }
+            if child_idx == dependent_join_idx =>
+        {
+            match dependency {
+                DependentCondition::JoinKey { lhs, rhs } => {
+                    trace!("Adding {} = {} to ON for {}", lhs, rhs, child_idx.index(),);
+                    // No need to add the columns to the projection, since semi- and anti-joins
+                    // only project columns from the left - they'll be pulled into the parents by
+                    // `pull_all_required_columns` since they're referenced by the join
+                    on.push((lhs, rhs));
+                    false
+                }
+                DependentCondition::FullyDependent { .. } => unsupported!(
+                    "Only equality comparisons between columns are supported in correlated EXISTS \
+                     subqueries"
+                ),
+            }
+        }
+        MirNodeInner::SemiJoin { .. }
+        | MirNodeInner::AntiJoin { .. }
+        | MirNodeInner::DependentSemiJoin { .. }
+        | MirNodeInner::DependentAntiJoin { .. } => {
+            if !from_left_parent {
+                unsupported!(
+                    "Don't know how to push filter below the right-hand side of a semi- or \
+                     anti-join to decorrelate"
+                );
+            }
+            true
+        }
         MirNodeInner::Project { .. }
         | MirNodeInner::Filter { .. }
         | MirNodeInner::Join { .. }
@@ -126,8 +163,9 @@ fn push_dependent_filter(
     Ok(())
 }
 
-/// A MIR rewrite pass that attempts to eliminate all dependent [inner][dependent inner join] and
-/// [left][dependent left join] joins by algebraically pushing any dependent filters below the join.
+/// A MIR rewrite pass that attempts to eliminate all dependent [inner][dependent inner join],
+/// [left][dependent left join], [semi][dependent semi join] and [anti][dependent anti join] joins
+/// by algebraically pushing any dependent filters below the join.
 ///
 /// This approach is roughly based on the papers [The Complete Story of Joins (In HyPer)][NKL17],
 /// [Unnesting Arbitrary Queries][NK15], and [Orthogonal Optimization of Subqueries and
@@ -149,6 +187,8 @@ fn push_dependent_filter(
 ///
 /// [dependent inner join]: MirNodeInner::DependentJoin
 /// [dependent left join]: MirNodeInner::DependentLeftJoin
+/// [dependent semi join]: MirNodeInner::DependentSemiJoin
+/// [dependent anti join]: MirNodeInner::DependentAntiJoin
 /// [NLK17]: http://btw2017.informatik.uni-stuttgart.de/slidesandpapers/F1-10-37/paper_web.pdf
 /// [NK15]: https://cs.emis.de/LNI/Proceedings/Proceedings241/383.pdf
 /// [GJ01]: http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.563.8492&rep=rep1&type=pdf
@@ -282,6 +322,14 @@ pub(crate) fn eliminate_dependent_joins(query: &mut MirQuery<'_>) -> ReadySetRes
                     on: on.clone(),
                     project: project.clone(),
                 },
+                MirNodeInner::DependentSemiJoin { on, project } => MirNodeInner::SemiJoin {
+                    on: on.clone(),
+                    project: project.clone(),
+                },
+                MirNodeInner::DependentAntiJoin { on, project } => MirNodeInner::AntiJoin {
+                    on: on.clone(),
+                    project: project.clone(),
+                },
                 _ => unreachable!("Already checked is_dependent_join above"),
             };
             query.get_node_mut(join).unwrap().inner = new_inner;
@@ -1005,4 +1053,149 @@ mod tests {
 
         pull_all_required_columns(&mut query).unwrap();
     }
+
+    /// Build a graph for a query that looks something like:
+    ///
+    /// ```sql
+    /// SELECT t1.a FROM t1 WHERE [NOT] EXISTS (SELECT * FROM t2 WHERE t2.a = t1.a)
+    /// ```
+    ///
+    /// compiled to a semi- or anti-join with the given `join_inner`, returning the graph, the
+    /// join node, the filter node in the subquery, and the leaf
+    fn exists_via_join(
+        join_inner: MirNodeInner,
+    ) -> (MirGraph, Relation, NodeIndex, NodeIndex, NodeIndex) {
+        let mut graph = MirGraph::new();
+        let query_name = Relation::from("q");
+
+        let t2 = graph.add_node(MirNode::new(
+            "t2".into(),
+            MirNodeInner::Base {
+                column_specs: vec![ColumnSpecification {
+                    column: nom_sql::Column::from("t2.a"),
+                    sql_type: SqlType::Int(None),
+                    constraints: vec![],
+                    comment: None,
+                }],
+                primary_key: Some([Column::new(Some("t2"), "a")].into()),
+                unique_keys: Default::default(),
+            },
+        ));
+        graph[t2].add_owner(query_name.clone());
+
+        // -> σ[t2.a = t1.a]
+        let t2_filter = graph.add_node(MirNode::new(
+            "t2_filter".into(),
+            MirNodeInner::Filter {
+                conditions: Expr::BinaryOp {
+                    lhs: Box::new(Expr::Column("t2.a".into())),
+                    op: BinaryOperator::Equal,
+                    rhs: Box::new(Expr::Column("t1.a".into())),
+                },
+            },
+        ));
+        graph[t2_filter].add_owner(query_name.clone());
+        graph.add_edge(t2, t2_filter, 0);
+
+        // -> AliasTable
+        let t2_alias_table = graph.add_node(MirNode::new(
+            "alias_table".into(),
+            MirNodeInner::AliasTable {
+                table: "rhs".into(),
+            },
+        ));
+        graph[t2_alias_table].add_owner(query_name.clone());
+        graph.add_edge(t2_filter, t2_alias_table, 0);
+
+        let t1 = graph.add_node(MirNode::new(
+            "t1".into(),
+            MirNodeInner::Base {
+                column_specs: vec![ColumnSpecification {
+                    column: nom_sql::Column::from("t1.a"),
+                    sql_type: SqlType::Int(None),
+                    constraints: vec![],
+                    comment: None,
+                }],
+                primary_key: Some([Column::from("a")].into()),
+                unique_keys: Default::default(),
+            },
+        ));
+        graph[t1].add_owner(query_name.clone());
+
+        let exists_join = graph.add_node(MirNode::new("exists_join".into(), join_inner));
+        graph[exists_join].add_owner(query_name.clone());
+        graph.add_edge(t1, exists_join, 0);
+        graph.add_edge(t2_alias_table, exists_join, 1);
+
+        let leaf = graph.add_node(MirNode::new(
+            "q".into(),
+            MirNodeInner::leaf(vec![], IndexType::HashMap),
+        ));
+        graph[leaf].add_owner(query_name.clone());
+        graph.add_edge(exists_join, leaf, 0);
+
+        (graph, query_name, exists_join, t2_filter, leaf)
+    }
+
+    #[test]
+    fn exists_via_semi_join() {
+        readyset_tracing::init_test_logging();
+        let (mut graph, query_name, exists_join, t2_filter, leaf) =
+            exists_via_join(MirNodeInner::DependentSemiJoin {
+                on: vec![],
+                project: vec![Column::new(Some("t1"), "a")],
+            });
+        let mut query = MirQuery::new(query_name, leaf, &mut graph);
+
+        eliminate_dependent_joins(&mut query).unwrap();
+
+        let MirNodeInner::SemiJoin { on, project } = &query.graph[exists_join].inner else {
+            panic!(
+                "should have rewritten dependent to non-dependent semi-join (got: {})",
+                query.graph[exists_join].inner.description()
+            );
+        };
+        assert_eq!(
+            *on,
+            vec![(Column::new(Some("t1"), "a"), Column::new(Some("rhs"), "a"))]
+        );
+        assert_eq!(*project, vec![Column::new(Some("t1"), "a")]);
+        assert!(
+            !query.graph.contains_node(t2_filter),
+            "t2_filter should be removed"
+        );
+
+        pull_all_required_columns(&mut query).unwrap();
+        assert_eq!(
+            query.graph.columns(exists_join),
+            vec![Column::new(Some("t1"), "a")],
+            "semi-join shouldn't project columns from the right"
+        );
+    }
+
+    #[test]
+    fn not_exists_via_anti_join() {
+        readyset_tracing::init_test_logging();
+        let (mut graph, query_name, exists_join, _, leaf) =
+            exists_via_join(MirNodeInner::DependentAntiJoin {
+                on: vec![],
+                project: vec![Column::new(Some("t1"), "a")],
+            });
+        let mut query = MirQuery::new(query_name, leaf, &mut graph);
+
+        eliminate_dependent_joins(&mut query).unwrap();
+
+        let MirNodeInner::AntiJoin { on, .. } = &query.graph[exists_join].inner else {
+            panic!(
+                "should have rewritten dependent to non-dependent anti-join (got: {})",
+                query.graph[exists_join].inner.description()
+            );
+        };
+        assert_eq!(
+            *on,
+            vec![(Column::new(Some("t1"), "a"), Column::new(Some("rhs"), "a"))]
+        );
+
+        pull_all_required_columns(&mut query).unwrap();
+    }
 }
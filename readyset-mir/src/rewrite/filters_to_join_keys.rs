@@ -62,7 +62,8 @@ pub(crate) fn convert_filters_to_join_keys(query: &mut MirQuery<'_>) -> ReadySet
                 | MirNodeInner::Extremum { group_by, .. }
                 | MirNodeInner::Distinct { group_by, .. }
                 | MirNodeInner::Paginate { group_by, .. }
-                | MirNodeInner::TopK { group_by, .. } => {
+                | MirNodeInner::TopK { group_by, .. }
+                | MirNodeInner::Window { group_by, .. } => {
                     if !(group_by.contains(&c1) && group_by.contains(&c2)) {
                         trace!(
                             "Columns in filter not in group_by of ancestor grouped node; can't \
@@ -99,14 +100,22 @@ pub(crate) fn convert_filters_to_join_keys(query: &mut MirQuery<'_>) -> ReadySet
 
                     trace!(c1 = %c1, c2 = %c2, "Remapped columns through AliasTable ancestor");
                 }
-                MirNodeInner::LeftJoin { .. } => {
-                    // TODO: figure out what to do about left joins
+                MirNodeInner::LeftJoin { .. }
+                | MirNodeInner::FullJoin { .. }
+                | MirNodeInner::SemiJoin { .. }
+                | MirNodeInner::AntiJoin { .. } => {
+                    // TODO: figure out what to do about outer, semi- and anti-joins
                     continue 'filter;
                 }
                 MirNodeInner::Union { .. } => {
                     // TODO: figure out what to do about unions
                     continue 'filter;
                 }
+                MirNodeInner::FixedPoint { .. } => {
+                    // Filters can't be moved above a fixed point, since they'd change which rows
+                    // are reached
+                    continue 'filter;
+                }
                 MirNodeInner::Join { .. } => {
                     let join_parents = query.ancestors(ancestor_idx)?;
                     let left_parent = *join_parents
@@ -146,6 +155,8 @@ pub(crate) fn convert_filters_to_join_keys(query: &mut MirQuery<'_>) -> ReadySet
                 | MirNodeInner::JoinAggregates
                 | MirNodeInner::DependentJoin { .. }
                 | MirNodeInner::DependentLeftJoin { .. }
+                | MirNodeInner::DependentSemiJoin { .. }
+                | MirNodeInner::DependentAntiJoin { .. }
                 | MirNodeInner::ViewKey { .. }
                 | MirNodeInner::Project { .. }
                 | MirNodeInner::Leaf { .. } => {}
@@ -15,6 +15,7 @@ fn commutes_with(conditions: &Expr, inner: &MirNodeInner) -> bool {
         MirNodeInner::Aggregation { group_by, .. }
         | MirNodeInner::Paginate { group_by, .. }
         | MirNodeInner::TopK { group_by, .. }
+        | MirNodeInner::Window { group_by, .. }
         | MirNodeInner::Distinct { group_by, .. }
         | MirNodeInner::Extremum { group_by, .. } => conditions
             .referred_columns()
@@ -32,7 +33,13 @@ fn commutes_with(conditions: &Expr, inner: &MirNodeInner) -> bool {
         | MirNodeInner::Leaf { .. } => true,
 
         MirNodeInner::Base { .. }
+        | MirNodeInner::FixedPoint { .. }
         | MirNodeInner::LeftJoin { .. }
+        | MirNodeInner::FullJoin { .. }
+        | MirNodeInner::SemiJoin { .. }
+        | MirNodeInner::AntiJoin { .. }
+        | MirNodeInner::DependentSemiJoin { .. }
+        | MirNodeInner::DependentAntiJoin { .. }
         | MirNodeInner::DependentLeftJoin { .. } => false,
     }
 }
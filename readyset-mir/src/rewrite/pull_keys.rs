@@ -29,6 +29,8 @@ fn push_view_key(query: &mut MirQuery<'_>, node_idx: NodeIndex) -> ReadySetResul
         }
     }
 
+    let from_left_parent = query.ancestors(child_idx)?.first() == Some(&node_idx);
+
     let key = match &query.get_node_mut(node_idx).unwrap().inner {
         MirNodeInner::ViewKey { key } => key.clone(),
         _ => internal!("The node passed to push_view_key must be a view_key"),
@@ -39,7 +41,8 @@ fn push_view_key(query: &mut MirQuery<'_>, node_idx: NodeIndex) -> ReadySetResul
         | MirNodeInner::Extremum { group_by, .. }
         | MirNodeInner::Distinct { group_by }
         | MirNodeInner::Paginate { group_by, .. }
-        | MirNodeInner::TopK { group_by, .. } => {
+        | MirNodeInner::TopK { group_by, .. }
+        | MirNodeInner::Window { group_by, .. } => {
             for ViewKeyColumn { column, op, .. } in &key {
                 invariant_eq!(
                     *op,
@@ -55,6 +58,27 @@ fn push_view_key(query: &mut MirQuery<'_>, node_idx: NodeIndex) -> ReadySetResul
         MirNodeInner::LeftJoin { .. } => unsupported!(
             "Parameters in subqueries on the right-hand side of LEFT JOIN not supported"
         ),
+        MirNodeInner::FullJoin { .. } => {
+            unsupported!("Parameters in subqueries on either side of FULL JOIN not supported")
+        }
+        // Semi- and anti-joins only project columns from the left
+        MirNodeInner::SemiJoin { .. }
+        | MirNodeInner::AntiJoin { .. }
+        | MirNodeInner::DependentSemiJoin { .. }
+        | MirNodeInner::DependentAntiJoin { .. } => {
+            if !from_left_parent {
+                unsupported!("Parameters in EXISTS subqueries not supported");
+            }
+            trace!(
+                "Pushing `{}` below `{}`",
+                node_idx.index(),
+                child_idx.index()
+            );
+            query.swap_with_child(node_idx)?;
+        }
+        MirNodeInner::FixedPoint { .. } => {
+            unsupported!("Parameters in recursive common table expressions not supported")
+        }
         // TODO: we might support this already? Will have to see
         MirNodeInner::Union { .. } => {
             unsupported!("Parameters on one side of a UNION not yet supported")
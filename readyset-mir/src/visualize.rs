@@ -159,11 +159,29 @@ impl GraphViz for MirNodeInner {
             } => {
                 let op_string = match kind {
                     AggregationKind::Count { .. } => format!("\\|*\\|({})", on),
+                    AggregationKind::CountDistinct => format!("\\|*\\|(DISTINCT {})", on),
                     AggregationKind::Sum => format!("𝛴({})", on),
+                    AggregationKind::SumDistinct => format!("𝛴(DISTINCT {})", on),
                     AggregationKind::Avg => format!("AVG({})", on),
+                    AggregationKind::AvgDistinct => format!("AVG(DISTINCT {})", on),
                     AggregationKind::GroupConcat { separator: s } => {
                         format!("\\|\\|({}, \\\"{}\\\")", on, s)
                     }
+                    AggregationKind::VarPop => format!("VAR_POP({})", on),
+                    AggregationKind::VarSamp => format!("VAR_SAMP({})", on),
+                    AggregationKind::StddevPop => format!("STDDEV_POP({})", on),
+                    AggregationKind::StddevSamp => format!("STDDEV_SAMP({})", on),
+                    AggregationKind::BoolAnd => format!("BOOL_AND({})", on),
+                    AggregationKind::BoolOr => format!("BOOL_OR({})", on),
+                    AggregationKind::BitAnd => format!("BIT_AND({})", on),
+                    AggregationKind::BitOr => format!("BIT_OR({})", on),
+                    AggregationKind::ArrayAgg => format!("ARRAY_AGG({})", on),
+                    AggregationKind::JsonAgg { jsonb: false } => format!("JSON_AGG({})", on),
+                    AggregationKind::JsonAgg { jsonb: true } => format!("JSONB_AGG({})", on),
+                    AggregationKind::ApproxCountDistinct => {
+                        format!("APPROX_COUNT_DISTINCT({})", on)
+                    }
+                    AggregationKind::ApproxPercentile => format!("APPROX_PERCENTILE({})", on),
                 };
                 let group_cols = group_by.iter().join(", ");
                 write!(f, "{} | γ: {}", op_string, group_cols)
@@ -224,6 +242,8 @@ impl GraphViz for MirNodeInner {
                 limit,
                 returned_cols,
                 aggregates,
+                projection,
+                filter,
                 ..
             } => {
                 write!(f, "Leaf | ⚷: {index_type:?}[")?;
@@ -250,7 +270,7 @@ impl GraphViz for MirNodeInner {
                         "\\norder_by: {}",
                         order_by
                             .iter()
-                            .map(|(col, ot)| format!("{} {}", col, ot))
+                            .map(|(col, ot, no)| format!("{} {} {}", col, ot, no))
                             .join(", ")
                     )?;
                 }
@@ -278,6 +298,18 @@ impl GraphViz for MirNodeInner {
                                     PostLookupAggregateFunction::GroupConcat { .. } => "GC",
                                     PostLookupAggregateFunction::Max => "Max",
                                     PostLookupAggregateFunction::Min => "Min",
+                                    PostLookupAggregateFunction::BitAnd => "&",
+                                    PostLookupAggregateFunction::BitOr => "|",
+                                    PostLookupAggregateFunction::ArrayConcat => "Concat",
+                                    PostLookupAggregateFunction::Avg { .. } => "Avg",
+                                    PostLookupAggregateFunction::CountDistinct { .. } =>
+                                        "CountDistinct",
+                                    PostLookupAggregateFunction::SumDistinct { .. } => "ΣDistinct",
+                                    PostLookupAggregateFunction::AvgDistinct { .. } =>
+                                        "AvgDistinct",
+                                    PostLookupAggregateFunction::ApproxCountDistinct => "HLL",
+                                    PostLookupAggregateFunction::ApproxPercentile { .. } =>
+                                        "Quantile",
                                 },
                                 &aggregate.column
                             ))
@@ -286,12 +318,35 @@ impl GraphViz for MirNodeInner {
                     )?;
                 }
 
+                if let Some(projection) = projection {
+                    write!(
+                        f,
+                        "\\nprojection: {}",
+                        projection
+                            .iter()
+                            .map(|(col, expr)| format!(
+                                "{} = {}",
+                                col,
+                                expr.display(nom_sql::Dialect::MySQL)
+                            ))
+                            .join(", ")
+                    )?;
+                }
+
+                if let Some(filter) = filter {
+                    write!(f, "\\nfilter: {}", filter.display(nom_sql::Dialect::MySQL))?;
+                }
+
                 Ok(())
             }
             MirNodeInner::LeftJoin { ref on, .. } => {
                 let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
                 write!(f, "⟕ | on: {}", jc)
             }
+            MirNodeInner::FullJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⟗ | on: {}", jc)
+            }
             MirNodeInner::DependentJoin { ref on, .. } => {
                 write!(
                     f,
@@ -306,6 +361,22 @@ impl GraphViz for MirNodeInner {
                     on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ")
                 )
             }
+            MirNodeInner::SemiJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⋈∃ | on: {}", jc)
+            }
+            MirNodeInner::AntiJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⋈∄ | on: {}", jc)
+            }
+            MirNodeInner::DependentSemiJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⋈∃D | on: {}", jc)
+            }
+            MirNodeInner::DependentAntiJoin { ref on, .. } => {
+                let jc = on.iter().map(|(l, r)| format!("{}:{}", l, r)).join(", ");
+                write!(f, "⋈∄D | on: {}", jc)
+            }
             MirNodeInner::Project { ref emit } => {
                 write!(f, "π: {}", emit.iter().join(", "))
             }
@@ -322,7 +393,7 @@ impl GraphViz for MirNodeInner {
                     .as_ref()
                     .map(|v| {
                         v.iter()
-                            .map(|(c, o)| format!("{}: {}", c.name.as_str(), o))
+                            .map(|(c, o, n)| format!("{}: {} {}", c.name.as_str(), o, n))
                             .collect::<Vec<_>>()
                             .join(", ")
                     })
@@ -338,13 +409,45 @@ impl GraphViz for MirNodeInner {
                     .as_ref()
                     .map(|v| {
                         v.iter()
-                            .map(|(c, o)| format!("{}: {}", c.name.as_str(), o))
+                            .map(|(c, o, n)| format!("{}: {} {}", c.name.as_str(), o, n))
                             .collect::<Vec<_>>()
                             .join(", ")
                     })
                     .unwrap_or_else(|| "".into());
                 write!(f, "TopK [k: {}; {}]", limit, order)
             }
+            MirNodeInner::Window {
+                ref order,
+                ref group_by,
+                ref on,
+                ref kind,
+                ..
+            } => {
+                let order = order
+                    .as_ref()
+                    .map(|v| {
+                        v.iter()
+                            .map(|(c, o, n)| format!("{}: {} {}", c.name.as_str(), o, n))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .unwrap_or_else(|| "".into());
+                write!(
+                    f,
+                    "Window | {}({}) | γ: {} | {}",
+                    kind,
+                    on.iter().join(""),
+                    group_by.iter().join(", "),
+                    order
+                )
+            }
+            MirNodeInner::FixedPoint {
+                ref key,
+                ref parent,
+                ref anchor,
+            } => {
+                write!(f, "FixedPoint | {} = {} | anchor: {}", parent, key, anchor)
+            }
             MirNodeInner::Union {
                 ref emit,
                 ref duplicate_mode,
@@ -352,6 +455,9 @@ impl GraphViz for MirNodeInner {
                 let symbol = match duplicate_mode {
                     union::DuplicateMode::BagUnion => '⊎',
                     union::DuplicateMode::UnionAll => '⋃',
+                    union::DuplicateMode::UnionDistinct => '∪',
+                    union::DuplicateMode::Intersect => '∩',
+                    union::DuplicateMode::Except => '∖',
                 };
                 let cols = emit
                     .iter()
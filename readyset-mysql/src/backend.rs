@@ -26,7 +26,7 @@ use readyset_errors::{internal, ReadySetError};
 use readyset_util::redacted::Sensitive;
 use streaming_iterator::StreamingIterator;
 use tokio::io::{self, AsyncWrite};
-use tracing::{error, info, trace};
+use tracing::{error, info, trace, warn};
 use upstream::StatementMeta;
 
 use crate::constants::DEFAULT_CHARACTER_SET;
@@ -719,6 +719,28 @@ where
         self.does_require_authentication()
     }
 
+    fn pass_through_authentication(&self) -> bool {
+        self.does_authenticate_with_upstream()
+    }
+
+    async fn authenticate_pass_through(&mut self, username: &str, password: &[u8]) -> bool {
+        let Ok(password) = String::from_utf8(password.to_vec()) else {
+            warn!(%username, "Received non-UTF-8 password");
+            return false;
+        };
+        match self
+            .noria
+            .authenticate_with_upstream(username, &password)
+            .await
+        {
+            Ok(()) => true,
+            Err(error) => {
+                warn!(%username, %error, "Failed to authenticate with the upstream database");
+                false
+            }
+        }
+    }
+
     fn version(&self) -> String {
         self.noria.version()
     }
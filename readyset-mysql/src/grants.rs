@@ -0,0 +1,233 @@
+//! Determining which tables a MySQL user can select from, using the output of `SHOW GRANTS`.
+//!
+//! `SHOW GRANTS` (unlike the privilege tables in `information_schema`) includes privileges that a
+//! user has been granted through roles, as long as those roles are named in its `USING` clause.
+//!
+//! Only `SELECT` privileges granted globally, on a database, or on a whole table are taken into
+//! account. A user who has only been granted `SELECT` on some of the columns of a table is treated
+//! as being unable to read that table, so that their queries against it are proxied to the
+//! upstream database, which enforces column privileges itself.
+
+use std::collections::HashSet;
+
+use dataflow_expression::like::{CaseSensitivityMode, LikePattern};
+
+/// The level at which a privilege was granted or revoked
+#[derive(Debug, PartialEq, Eq)]
+enum Level {
+    /// `ON *.*`
+    Global,
+    /// `ON db.*`, where `db` may contain `LIKE` wildcards
+    Database(String),
+    /// `ON db.table`
+    Table(String, String),
+}
+
+/// The `SELECT` privileges held by a user, as parsed from the output of `SHOW GRANTS`
+#[derive(Default)]
+pub(crate) struct SelectGrants {
+    global: bool,
+    /// Databases from which the global privilege has been partially revoked
+    revoked_databases: HashSet<String>,
+    databases: Vec<LikePattern>,
+    tables: HashSet<(String, String)>,
+}
+
+impl SelectGrants {
+    /// Build a [`SelectGrants`] from the rows returned by `SHOW GRANTS`. Any statement that
+    /// doesn't grant or revoke `SELECT` on tables is ignored.
+    pub(crate) fn new<I, S>(grants: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut res = Self::default();
+        for grant in grants {
+            match parse_select_grant(grant.as_ref()) {
+                Some((true, Level::Global)) => res.global = true,
+                Some((true, Level::Database(db))) => res
+                    .databases
+                    .push(LikePattern::new(&db, CaseSensitivityMode::CaseSensitive)),
+                Some((true, Level::Table(db, table))) => {
+                    res.tables.insert((db, table));
+                }
+                // Partial revokes can only be made on whole databases
+                Some((false, Level::Database(db))) => {
+                    res.revoked_databases.insert(db);
+                }
+                Some((false, _)) | None => {}
+            }
+        }
+        res
+    }
+
+    /// Returns true if these grants allow selecting from the given table
+    pub(crate) fn can_select(&self, schema: &str, table: &str) -> bool {
+        (self.global && !self.revoked_databases.contains(schema))
+            || self.databases.iter().any(|db| db.matches(schema))
+            || self.tables.contains(&(schema.to_owned(), table.to_owned()))
+    }
+}
+
+/// Parse a single statement returned by `SHOW GRANTS`, returning whether it grants (rather than
+/// revokes) a privilege and the level it applies at, if it grants or revokes `SELECT` on tables
+fn parse_select_grant(stmt: &str) -> Option<(bool, Level)> {
+    let (grant, rest) = if let Some(rest) = stmt.strip_prefix("GRANT ") {
+        (true, rest)
+    } else {
+        (false, stmt.strip_prefix("REVOKE ")?)
+    };
+    // Grants of roles don't have an `ON` clause
+    let (privileges, on) = split_top_level(rest, " ON ")?;
+    if !split_all_top_level(privileges, ",").any(|privilege| {
+        let privilege = privilege.trim();
+        privilege.eq_ignore_ascii_case("SELECT")
+            || privilege.eq_ignore_ascii_case("ALL")
+            || privilege.eq_ignore_ascii_case("ALL PRIVILEGES")
+    }) {
+        return None;
+    }
+
+    let on = on.strip_prefix("TABLE ").unwrap_or(on);
+    let (db, rest) = parse_ident(on)?;
+    let (table, _) = parse_ident(rest.strip_prefix('.')?)?;
+    let level = match (db, table) {
+        (None, None) => Level::Global,
+        (Some(db), None) => Level::Database(db),
+        (Some(db), Some(table)) => Level::Table(db, table),
+        (None, Some(_)) => return None,
+    };
+    Some((grant, level))
+}
+
+/// Split `s` at the first occurrence of `sep` that isn't within backticks or parentheses
+fn split_top_level<'a>(s: &'a str, sep: &str) -> Option<(&'a str, &'a str)> {
+    let mut quoted = false;
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '`' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth = depth.saturating_sub(1),
+            _ if !quoted && depth == 0 && s[i..].starts_with(sep) => {
+                return Some((&s[..i], &s[(i + sep.len())..]))
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split `s` at every occurrence of `sep` that isn't within backticks or parentheses
+fn split_all_top_level<'a>(mut s: &'a str, sep: &'a str) -> impl Iterator<Item = &'a str> {
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        match split_top_level(s, sep) {
+            Some((item, rest)) => {
+                s = rest;
+                Some(item)
+            }
+            None => {
+                done = true;
+                Some(s)
+            }
+        }
+    })
+}
+
+/// Parse either `*` (returning `None`) or a backtick-quoted identifier from the start of `s`,
+/// returning the remainder of `s` along with it
+fn parse_ident(s: &str) -> Option<(Option<String>, &str)> {
+    if let Some(rest) = s.strip_prefix('*') {
+        return Some((None, rest));
+    }
+
+    let body = s.strip_prefix('`')?;
+    let mut ident = String::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '`' {
+            ident.push(c);
+        } else if chars.next_if(|(_, c)| *c == '`').is_some() {
+            ident.push('`');
+        } else {
+            return Some((Some(ident), &body[(i + 1)..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_grants() {
+        assert_eq!(
+            parse_select_grant("GRANT SELECT, INSERT ON *.* TO `u`@`%`"),
+            Some((true, Level::Global))
+        );
+        assert_eq!(
+            parse_select_grant("GRANT ALL PRIVILEGES ON `db`.* TO `u`@`%` WITH GRANT OPTION"),
+            Some((true, Level::Database("db".into())))
+        );
+        assert_eq!(
+            parse_select_grant("GRANT SELECT ON `d``b`.`t.1` TO `u`@`%`"),
+            Some((true, Level::Table("d`b".into(), "t.1".into())))
+        );
+        assert_eq!(
+            parse_select_grant("REVOKE SELECT ON `db`.* FROM `u`@`%`"),
+            Some((false, Level::Database("db".into())))
+        );
+    }
+
+    #[test]
+    fn parse_non_select_grants() {
+        assert_eq!(parse_select_grant("GRANT USAGE ON *.* TO `u`@`%`"), None);
+        assert_eq!(
+            parse_select_grant("GRANT INSERT, UPDATE (`select`) ON `db`.`t` TO `u`@`%`"),
+            None
+        );
+        assert_eq!(
+            parse_select_grant("GRANT SELECT (`a`, `b`) ON `db`.`t` TO `u`@`%`"),
+            None
+        );
+        assert_eq!(
+            parse_select_grant("GRANT `reader ON db`@`%` TO `u`@`%`"),
+            None
+        );
+        assert_eq!(
+            parse_select_grant("GRANT EXECUTE ON PROCEDURE `db`.`p` TO `u`@`%`"),
+            None
+        );
+    }
+
+    #[test]
+    fn can_select() {
+        let grants = SelectGrants::new([
+            "GRANT USAGE ON *.* TO `u`@`%`",
+            "GRANT SELECT ON `app\\_%`.* TO `u`@`%`",
+            "GRANT SELECT ON `other`.`t1` TO `u`@`%`",
+            "GRANT SELECT (`a`) ON `other`.`t2` TO `u`@`%`",
+            "GRANT `reader`@`%` TO `u`@`%`",
+        ]);
+        assert!(grants.can_select("app_1", "t"));
+        assert!(!grants.can_select("appx1", "t"));
+        assert!(grants.can_select("other", "t1"));
+        assert!(!grants.can_select("other", "t2"));
+        assert!(!grants.can_select("secret", "t1"));
+    }
+
+    #[test]
+    fn partial_revokes() {
+        let grants = SelectGrants::new([
+            "GRANT SELECT ON *.* TO `u`@`%`",
+            "REVOKE SELECT ON `secret`.* FROM `u`@`%`",
+        ]);
+        assert!(grants.can_select("app", "t"));
+        assert!(!grants.can_select("secret", "t"));
+    }
+}
@@ -1,6 +1,7 @@
 mod backend;
 mod constants;
 mod error;
+mod grants;
 mod query_handler;
 mod schema;
 mod upstream;
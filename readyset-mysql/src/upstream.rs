@@ -9,7 +9,7 @@ use mysql_async::prelude::Queryable;
 use mysql_async::{
     Column, Conn, Opts, OptsBuilder, ResultSetStream, Row, SslOpts, TxOpts, UrlError,
 };
-use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use nom_sql::{Relation, SqlIdentifier, StartTransactionStatement};
 use pin_project::pin_project;
 use readyset_adapter::upstream_database::UpstreamDestination;
 use readyset_adapter::{UpstreamConfig, UpstreamDatabase, UpstreamPrepare};
@@ -18,6 +18,7 @@ use readyset_data::DfValue;
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
 use tracing::{debug, error, info_span, Instrument};
 
+use crate::grants::SelectGrants;
 use crate::Error;
 
 type StatementID = u32;
@@ -26,6 +27,13 @@ type StatementID = u32;
 /// during connection phase if the version for the upstream server is too low.
 const MIN_UPSTREAM_VERSION: u16 = 8;
 
+/// Query for all tables visible to the current user
+const TABLES_QUERY: &str = r#"
+SELECT TABLE_SCHEMA, TABLE_NAME
+FROM information_schema.TABLES
+WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')
+"#;
+
 fn dt_to_value_params(dt: &[DfValue]) -> ReadySetResult<Vec<mysql_async::Value>> {
     dt.iter().map(|v| v.try_into()).collect()
 }
@@ -170,6 +178,7 @@ macro_rules! handle_query_result {
 impl MySqlUpstream {
     async fn connect_inner(
         upstream_config: UpstreamConfig,
+        credentials: Option<(&str, &str)>,
     ) -> Result<(Conn, HashMap<StatementID, mysql_async::Statement>), Error> {
         // CLIENT_SESSION_TRACK is required for GTID information to be sent in OK packets on commits
         // GTID information is used for RYW
@@ -186,6 +195,13 @@ impl MySqlUpstream {
             opts = OptsBuilder::from_opts(opts).ssl_opts(ssl_opts).into();
         }
 
+        if let Some((user, password)) = credentials {
+            opts = OptsBuilder::from_opts(opts)
+                .user(Some(user))
+                .pass(Some(password))
+                .into();
+        }
+
         let span = info_span!(
             "Connecting to MySQL upstream",
             host = %opts.ip_or_hostname(),
@@ -229,11 +245,26 @@ impl UpstreamDatabase for MySqlUpstream {
     type PrepareData<'a> = ();
     type ExecMeta<'a> = ();
     type Error = Error;
+    /// The user's password
+    type Credentials = String;
     const DEFAULT_DB_VERSION: &'static str = "8.0.26-readyset\0";
     const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::MySQL;
 
     async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Error> {
-        let (conn, prepared_statements) = Self::connect_inner(upstream_config).await?;
+        let (conn, prepared_statements) = Self::connect_inner(upstream_config, None).await?;
+        Ok(Self {
+            conn,
+            prepared_statements,
+        })
+    }
+
+    async fn connect_as(
+        upstream_config: UpstreamConfig,
+        user: &str,
+        password: &String,
+    ) -> Result<Self, Error> {
+        let (conn, prepared_statements) =
+            Self::connect_inner(upstream_config, Some((user, password.as_str()))).await?;
         Ok(Self {
             conn,
             prepared_statements,
@@ -385,6 +416,33 @@ impl UpstreamDatabase for MySqlUpstream {
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
         Ok(self.database().into_iter().map(|s| s.into()).collect())
     }
+
+    async fn table_privileges(&mut self) -> Result<Vec<(Relation, bool)>, Self::Error> {
+        // Privileges granted through roles are only listed by `SHOW GRANTS` if those roles are
+        // named in its `USING` clause, so name every role that's active for this connection
+        // (`CURRENT_ROLE()` returns them in the syntax that clause expects)
+        let roles: Option<Option<String>> = self.conn.query_first("SELECT CURRENT_ROLE()").await?;
+        let show_grants = match roles.flatten().filter(|roles| roles != "NONE") {
+            Some(roles) => format!("SHOW GRANTS FOR CURRENT_USER() USING {roles}"),
+            None => "SHOW GRANTS FOR CURRENT_USER()".to_owned(),
+        };
+        let grants = SelectGrants::new(self.conn.query::<String, _>(show_grants).await?);
+
+        let tables: Vec<(String, String)> = self.conn.query(TABLES_QUERY).await?;
+        Ok(tables
+            .into_iter()
+            .map(|(schema, name)| {
+                let can_select = grants.can_select(&schema, &name);
+                (
+                    Relation {
+                        schema: Some(schema.into()),
+                        name: name.into(),
+                    },
+                    can_select,
+                )
+            })
+            .collect())
+    }
 }
 
 impl Drop for MySqlUpstream {
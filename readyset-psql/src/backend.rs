@@ -13,12 +13,13 @@ use readyset_adapter::backend as cl;
 use readyset_adapter::upstream_database::LazyUpstream;
 use readyset_data::DfValue;
 use thiserror::Error;
+use tracing::warn;
 
 use crate::error::Error;
 use crate::query_handler::PostgreSqlQueryHandler;
 use crate::response::{PrepareResponse, QueryResponse};
 use crate::resultset::Resultset;
-use crate::PostgreSqlUpstream;
+use crate::{PostgreSqlUpstream, UpstreamCredentials};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum AuthenticationMethod {
@@ -124,7 +125,9 @@ impl ps::PsqlBackend for Backend {
     }
 
     async fn on_init(&mut self, _database: &str) -> Result<ps::CredentialsNeeded, ps::Error> {
-        if self.does_require_authentication() {
+        if self.does_authenticate_with_upstream() {
+            Ok(ps::CredentialsNeeded::PassThrough)
+        } else if self.does_require_authentication() {
             match self.authentication_method {
                 AuthenticationMethod::Cleartext => Ok(ps::CredentialsNeeded::Cleartext),
                 AuthenticationMethod::ScramSha256 => Ok(ps::CredentialsNeeded::ScramSha256),
@@ -134,6 +137,50 @@ impl ps::PsqlBackend for Backend {
         }
     }
 
+    async fn scram_secret_for_user(
+        &mut self,
+        user: &str,
+    ) -> Result<Option<ps::ScramSecret>, ps::Error> {
+        let secret = match self.inner.upstream_authentication_secret(user).await {
+            Ok(secret) => secret,
+            Err(error) => {
+                warn!(%user, %error, "Failed to look up password secret in the upstream database");
+                None
+            }
+        };
+        // Users whose passwords are stored as MD5 hashes can't be authenticated using SCRAM
+        Ok(secret.and_then(|secret| secret.parse().ok()))
+    }
+
+    async fn authenticate_pass_through(
+        &mut self,
+        user: &str,
+        credentials: ps::PassThroughCredentials<'_>,
+    ) -> Result<bool, ps::Error> {
+        let credentials = match credentials {
+            ps::PassThroughCredentials::CleartextPassword(password) => {
+                UpstreamCredentials::Password(password.to_owned())
+            }
+            ps::PassThroughCredentials::ScramClientKey { secret, client_key } => {
+                UpstreamCredentials::ScramClientKey {
+                    secret: secret.clone(),
+                    client_key: client_key.clone(),
+                }
+            }
+        };
+        match self
+            .inner
+            .authenticate_with_upstream(user, &credentials)
+            .await
+        {
+            Ok(()) => Ok(true),
+            Err(error) => {
+                warn!(%user, %error, "Failed to authenticate with the upstream database");
+                Ok(false)
+            }
+        }
+    }
+
     async fn on_query(&mut self, query: &str) -> Result<ps::QueryResponse<Resultset>, ps::Error> {
         self.query(query).await?.try_into()
     }
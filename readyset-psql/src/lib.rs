@@ -5,10 +5,11 @@ mod query_handler;
 mod response;
 mod resultset;
 mod schema;
+mod scram_pass_through;
 mod upstream;
 mod value;
 
 pub use crate::backend::{AuthenticationMethod, Backend, ParamRef};
 pub use crate::error::Error;
 pub use crate::query_handler::PostgreSqlQueryHandler;
-pub use crate::upstream::PostgreSqlUpstream;
+pub use crate::upstream::{PostgreSqlUpstream, UpstreamCredentials};
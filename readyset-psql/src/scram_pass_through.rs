@@ -0,0 +1,244 @@
+//! Connecting to the upstream database as a user that was authenticated using SCRAM
+//! pass-through - with the [`ClientKey`] recovered from the user's authentication with ReadySet,
+//! rather than with their password.
+//!
+//! `tokio_postgres` can only authenticate with a password, so we run the startup and
+//! authentication exchange with the upstream database ourselves, then hand `tokio_postgres` a
+//! stream on which the upstream database appears to have accepted its startup message without
+//! asking for a password.
+
+use std::fmt::Display;
+use std::io;
+
+use psql_srv::{ClientKey, ScramClient, ScramSecret};
+use readyset_errors::{internal_err, unsupported, ReadySetError};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream};
+use tokio::net::TcpStream;
+use tokio_postgres::config::{Host, SslMode};
+use tokio_postgres::Config;
+use tracing::debug;
+
+use crate::Error;
+
+const PROTOCOL_VERSION: i32 = 196608;
+const SSL_REQUEST_CODE: i32 = 80877103;
+const DEFAULT_PORT: u16 = 5432;
+const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+/// Size of the buffer between `tokio_postgres` and the upstream database
+const BUFFER_SIZE: usize = 64 * 1024;
+
+const AUTHENTICATION_OK: i32 = 0;
+const AUTHENTICATION_SASL: i32 = 10;
+const AUTHENTICATION_SASL_CONTINUE: i32 = 11;
+const AUTHENTICATION_SASL_FINAL: i32 = 12;
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<S> Stream for S where S: AsyncRead + AsyncWrite + Unpin + Send {}
+
+/// Connect and authenticate to the upstream database described by `config` as the user in
+/// `config`, using the given [`ClientKey`] and the [`ScramSecret`] it was verified against.
+///
+/// Returns a stream to pass to [`Config::connect_raw`] (with TLS disabled, since TLS with the
+/// upstream database is handled here), which discards the startup message that `tokio_postgres`
+/// sends and replays the upstream database's response to our own.
+pub(crate) async fn connect(
+    config: &Config,
+    tls: native_tls::TlsConnector,
+    secret: &ScramSecret,
+    client_key: &ClientKey,
+) -> Result<DuplexStream, Error> {
+    let mut upstream = connect_tls(config, tls).await?;
+
+    let user = config
+        .get_user()
+        .ok_or_else(|| internal_err!("No user to authenticate with the upstream database as"))?;
+    let mut params = vec![("client_encoding", "UTF8"), ("user", user)];
+    if let Some(dbname) = config.get_dbname() {
+        params.push(("database", dbname));
+    }
+    if let Some(options) = config.get_options() {
+        params.push(("options", options));
+    }
+    if let Some(application_name) = config.get_application_name() {
+        params.push(("application_name", application_name));
+    }
+    let mut startup_message = PROTOCOL_VERSION.to_be_bytes().to_vec();
+    for (name, value) in params {
+        put_cstr(&mut startup_message, name.as_bytes());
+        put_cstr(&mut startup_message, value.as_bytes());
+    }
+    startup_message.push(0);
+    write_message(&mut upstream, None, &startup_message).await?;
+
+    let mut scram = ScramClient::new(client_key.clone(), secret.clone());
+    // Messages from the upstream database to replay to `tokio_postgres`, starting with
+    // AuthenticationOk
+    let mut replay = vec![];
+    loop {
+        let (tag, body) = read_message(&mut upstream).await?;
+        match tag {
+            b'R' => {}
+            b'E' => return Err(upstream_error(&body).into()),
+            _ => return Err(unexpected_message(tag).into()),
+        }
+
+        let (code, data) = body.split_at(4.min(body.len()));
+        match i32::from_be_bytes(code.try_into().map_err(|_| unexpected_message(tag))?) {
+            AUTHENTICATION_OK => {
+                put_message(&mut replay, Some(tag), &body);
+                break;
+            }
+            AUTHENTICATION_SASL => {
+                if !data
+                    .split(|b| *b == 0)
+                    .any(|mechanism| mechanism == SCRAM_SHA_256.as_bytes())
+                {
+                    unsupported!("Upstream database does not support {SCRAM_SHA_256}");
+                }
+                let client_first_message = scram.client_first_message();
+                let mut response = vec![];
+                put_cstr(&mut response, SCRAM_SHA_256.as_bytes());
+                response.extend((client_first_message.len() as i32).to_be_bytes());
+                response.extend(client_first_message.as_bytes());
+                write_message(&mut upstream, Some(b'p'), &response).await?;
+            }
+            AUTHENTICATION_SASL_CONTINUE => {
+                let client_final_message = scram
+                    .handle_server_first_message(data)
+                    .map_err(scram_error)?;
+                write_message(&mut upstream, Some(b'p'), client_final_message.as_bytes()).await?;
+            }
+            AUTHENTICATION_SASL_FINAL => scram
+                .verify_server_final_message(data)
+                .map_err(scram_error)?,
+            _ => unsupported!("Upstream database requested authentication other than SCRAM"),
+        }
+    }
+
+    // Buffer the rest of the upstream database's startup messages, up to and including
+    // ReadyForQuery, so that errors are reported here rather than by `tokio_postgres`
+    loop {
+        let (tag, body) = read_message(&mut upstream).await?;
+        if tag == b'E' {
+            return Err(upstream_error(&body).into());
+        }
+        put_message(&mut replay, Some(tag), &body);
+        if tag == b'Z' {
+            break;
+        }
+    }
+
+    let (stream, mut proxy) = tokio::io::duplex(BUFFER_SIZE);
+    tokio::spawn(async move {
+        let res: io::Result<_> = async {
+            // Discard `tokio_postgres`'s startup message, since we've already sent our own
+            let len = proxy.read_i32().await?;
+            let mut startup_message = vec![0; message_body_len(len)?];
+            proxy.read_exact(&mut startup_message).await?;
+
+            proxy.write_all(&replay).await?;
+            tokio::io::copy_bidirectional(&mut proxy, &mut upstream).await
+        }
+        .await;
+        if let Err(error) = res {
+            debug!(%error, "Connection to upstream database closed");
+        }
+    });
+
+    Ok(stream)
+}
+
+/// Open a connection to the (first) upstream host in `config`, using TLS unless it's disabled
+async fn connect_tls(
+    config: &Config,
+    tls: native_tls::TlsConnector,
+) -> Result<Box<dyn Stream>, Error> {
+    let host = match config.get_hosts().first() {
+        Some(Host::Tcp(host)) => host.as_str(),
+        Some(_) => unsupported!("SCRAM pass-through authentication over a Unix socket"),
+        None => "localhost",
+    };
+    let port = config.get_ports().first().copied().unwrap_or(DEFAULT_PORT);
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    if config.get_ssl_mode() == SslMode::Disable {
+        return Ok(Box::new(stream));
+    }
+
+    let mut ssl_request = vec![];
+    put_message(&mut ssl_request, None, &SSL_REQUEST_CODE.to_be_bytes());
+    stream.write_all(&ssl_request).await?;
+    if stream.read_u8().await? != b'S' {
+        if config.get_ssl_mode() == SslMode::Require {
+            return Err(internal_err!("Upstream database does not support TLS").into());
+        }
+        return Ok(Box::new(stream));
+    }
+
+    let stream = tokio_native_tls::TlsConnector::from(tls)
+        .connect(host, stream)
+        .await
+        .map_err(|e| internal_err!("TLS handshake with upstream database failed: {e}"))?;
+    Ok(Box::new(stream))
+}
+
+fn put_cstr(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend(s);
+    buf.push(0);
+}
+
+/// Append a message with the given tag (if any) and body to `buf`, prefixed with its length
+fn put_message(buf: &mut Vec<u8>, tag: Option<u8>, body: &[u8]) {
+    buf.extend(tag);
+    buf.extend(((body.len() + 4) as i32).to_be_bytes());
+    buf.extend(body);
+}
+
+async fn write_message<S>(stream: &mut S, tag: Option<u8>, body: &[u8]) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let mut buf = vec![];
+    put_message(&mut buf, tag, body);
+    stream.write_all(&buf).await
+}
+
+fn message_body_len(len: i32) -> io::Result<usize> {
+    usize::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_sub(4))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid message length"))
+}
+
+/// Read a message from the upstream database, returning its tag and body
+async fn read_message<S>(stream: &mut S) -> io::Result<(u8, Vec<u8>)>
+where
+    S: AsyncRead + Unpin,
+{
+    let tag = stream.read_u8().await?;
+    let len = stream.read_i32().await?;
+    let mut body = vec![0; message_body_len(len)?];
+    stream.read_exact(&mut body).await?;
+    Ok((tag, body))
+}
+
+/// Build an error from the body of an ErrorResponse sent by the upstream database
+fn upstream_error(body: &[u8]) -> ReadySetError {
+    let message = body
+        .split(|b| *b == 0)
+        .find_map(|field| field.strip_prefix(b"M"))
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    internal_err!("Upstream database rejected authentication: {message}")
+}
+
+fn scram_error(error: impl Display) -> ReadySetError {
+    internal_err!("SCRAM authentication with upstream database failed: {error}")
+}
+
+fn unexpected_message(tag: u8) -> ReadySetError {
+    internal_err!(
+        "Unexpected message from upstream database during authentication: {}",
+        tag as char
+    )
+}
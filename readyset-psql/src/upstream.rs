@@ -5,26 +5,61 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::StreamExt;
-use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use nom_sql::{Relation, SqlIdentifier, StartTransactionStatement};
 use pgsql::types::Type;
 use pgsql::{GenericResult, ResultStream, Row, SimpleQueryMessage};
 use postgres_types::Kind;
-use psql_srv::{Column, TransferFormat};
+use psql_srv::{ClientKey, Column, ScramSecret, TransferFormat};
 use readyset_adapter::upstream_database::UpstreamDestination;
 use readyset_adapter::{UpstreamConfig, UpstreamDatabase, UpstreamPrepare};
 use readyset_client_metrics::recorded;
 use readyset_data::DfValue;
 use readyset_errors::{internal_err, invariant_eq, unsupported, ReadySetError, ReadySetResult};
 use tokio_postgres as pgsql;
+use tokio_postgres::config::SslMode;
 use tracing::{debug, info_span};
 use tracing_futures::Instrument;
 
-use crate::Error;
+use crate::{scram_pass_through, Error};
 
 /// Indicates the minimum upstream server version that we currently support. Used to error out
 /// during connection phase if the version for the upstream server is too low.
 const MIN_UPSTREAM_VERSION: u16 = 13;
 
+/// Query for all relations visible to the current user which can be selected from, along with
+/// whether the user has `SELECT` privileges on each relation per its ACL
+const TABLE_PRIVILEGES_QUERY: &str = r#"
+SELECT n.nspname::text, c.relname::text, has_table_privilege(c.oid, 'SELECT')
+FROM pg_catalog.pg_class c
+JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+WHERE c.relkind IN ('r', 'p', 'v', 'm', 'f')
+AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+AND n.nspname NOT LIKE 'pg_toast%'
+"#;
+
+/// Query for the password secret of a login user, unless it has expired. Only superusers can read
+/// `pg_authid`.
+const AUTHENTICATION_SECRET_QUERY: &str = r#"
+SELECT rolpassword
+FROM pg_catalog.pg_authid
+WHERE rolname = $1
+AND rolcanlogin
+AND (rolvaliduntil IS NULL OR rolvaliduntil > now())
+"#;
+
+/// Credentials used to authenticate with the upstream database as a particular user
+#[derive(Debug, Clone)]
+pub enum UpstreamCredentials {
+    /// The user's password
+    Password(String),
+    /// The [`ClientKey`] recovered from a client's SCRAM authentication with ReadySet, against the
+    /// upstream database's [`ScramSecret`] for that user
+    ScramClientKey {
+        secret: ScramSecret,
+        client_key: ClientKey,
+    },
+}
+
 /// A connector to an underlying PostgreSQL database
 pub struct PostgreSqlUpstream {
     /// This is the underlying (regular) PostgreSQL client
@@ -145,23 +180,23 @@ fn convert_params_for_upstream(params: &[DfValue], types: &[Type]) -> ReadySetRe
         .collect()
 }
 
-#[async_trait]
-impl UpstreamDatabase for PostgreSqlUpstream {
-    type StatementMeta = StatementMeta;
-    type QueryResult<'a> = QueryResult;
-    type PrepareData<'a> = &'a [Type];
-    type ExecMeta<'a> = &'a [TransferFormat];
-    type Error = Error;
-    const DEFAULT_DB_VERSION: &'static str = "13.4 (ReadySet)";
-    const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::PostgreSQL;
-
-    async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Error> {
+impl PostgreSqlUpstream {
+    async fn connect_inner(
+        upstream_config: UpstreamConfig,
+        credentials: Option<(&str, &UpstreamCredentials)>,
+    ) -> Result<Self, Error> {
         let url = upstream_config
             .upstream_db_url
             .as_ref()
             .ok_or(ReadySetError::InvalidUpstreamDatabase)?;
 
-        let pg_config = pgsql::Config::from_str(url)?;
+        let mut pg_config = pgsql::Config::from_str(url)?;
+        if let Some((user, credentials)) = credentials {
+            pg_config.user(user);
+            if let UpstreamCredentials::Password(password) = credentials {
+                pg_config.password(password);
+            }
+        }
         let user = pg_config.get_user().map(|s| s.to_owned());
         let connector = {
             let mut builder = native_tls::TlsConnector::builder();
@@ -173,15 +208,34 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             }
             builder.build().unwrap() // Never returns an error
         };
-        let tls = postgres_native_tls::MakeTlsConnector::new(connector);
         let span = info_span!(
             "Connecting to PostgreSQL upstream",
             host = ?pg_config.get_hosts(),
             port = ?pg_config.get_ports()
         );
         span.in_scope(|| debug!("Establishing connection"));
-        let (client, connection) = pg_config.connect(tls).instrument(span.clone()).await?;
-        let version = connection.parameter("server_version").ok_or_else(|| {
+        let (client, version, _connection_handle) = match credentials {
+            Some((_, UpstreamCredentials::ScramClientKey { secret, client_key })) => {
+                let stream = scram_pass_through::connect(&pg_config, connector, secret, client_key)
+                    .instrument(span.clone())
+                    .await?;
+                let (client, connection) = pg_config
+                    .clone()
+                    .ssl_mode(SslMode::Disable)
+                    .connect_raw(stream, pgsql::NoTls)
+                    .instrument(span.clone())
+                    .await?;
+                let version = connection.parameter("server_version").map(str::to_owned);
+                (client, version, tokio::spawn(connection))
+            }
+            _ => {
+                let tls = postgres_native_tls::MakeTlsConnector::new(connector);
+                let (client, connection) = pg_config.connect(tls).instrument(span.clone()).await?;
+                let version = connection.parameter("server_version").map(str::to_owned);
+                (client, version, tokio::spawn(connection))
+            }
+        };
+        let version = version.ok_or_else(|| {
             ReadySetError::Internal("Upstream database failed to send server version".to_string())
         })?;
         let (major, minor) = version
@@ -198,7 +252,6 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             }));
         }
         let version = format!("{version} ReadySet");
-        let _connection_handle = tokio::spawn(connection);
         span.in_scope(|| debug!("Established connection to upstream"));
         metrics::increment_gauge!(recorded::CLIENT_UPSTREAM_CONNECTIONS, 1.0);
 
@@ -211,6 +264,30 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             version,
         })
     }
+}
+
+#[async_trait]
+impl UpstreamDatabase for PostgreSqlUpstream {
+    type StatementMeta = StatementMeta;
+    type QueryResult<'a> = QueryResult;
+    type PrepareData<'a> = &'a [Type];
+    type ExecMeta<'a> = &'a [TransferFormat];
+    type Error = Error;
+    type Credentials = UpstreamCredentials;
+    const DEFAULT_DB_VERSION: &'static str = "13.4 (ReadySet)";
+    const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::PostgreSQL;
+
+    async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Error> {
+        Self::connect_inner(upstream_config, None).await
+    }
+
+    async fn connect_as(
+        upstream_config: UpstreamConfig,
+        user: &str,
+        credentials: &UpstreamCredentials,
+    ) -> Result<Self, Error> {
+        Self::connect_inner(upstream_config, Some((user, credentials))).await
+    }
 
     async fn is_connected(&mut self) -> Result<bool, Self::Error> {
         Ok(!self.client.simple_query("select 1").await?.is_empty())
@@ -375,6 +452,36 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             })
             .collect())
     }
+
+    async fn table_privileges(&mut self) -> Result<Vec<(Relation, bool)>, Self::Error> {
+        Ok(self
+            .client
+            .query(TABLE_PRIVILEGES_QUERY, &[])
+            .await?
+            .into_iter()
+            .map(|row| {
+                (
+                    Relation {
+                        schema: Some(row.get::<_, String>(0).into()),
+                        name: row.get::<_, String>(1).into(),
+                    },
+                    row.get(2),
+                )
+            })
+            .collect())
+    }
+
+    async fn authentication_secret(&mut self, user: &str) -> Result<Option<String>, Self::Error> {
+        match self
+            .client
+            .query_opt(AUTHENTICATION_SECRET_QUERY, &[&user])
+            .await
+        {
+            Ok(row) => Ok(row.and_then(|row| row.get(0))),
+            Err(e) if e.code() == Some(&pgsql::error::SqlState::INSUFFICIENT_PRIVILEGE) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
 
 impl Drop for PostgreSqlUpstream {
@@ -2546,3 +2546,82 @@ async fn drop_and_recreate_demo_cache() {
 
     shutdown_tx.shutdown().await;
 }
+
+/// Create (or recreate) a login role upstream with the given password, hashed using
+/// `password_encryption`
+async fn create_upstream_role(name: &str, password: &str, password_encryption: &str) {
+    let upstream = connect(upstream_config()).await;
+    upstream
+        .simple_query(&format!(
+            "SET password_encryption = '{password_encryption}';
+             DROP ROLE IF EXISTS {name};
+             CREATE ROLE {name} LOGIN PASSWORD '{password}';"
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+#[slow]
+async fn authenticate_with_upstream_scram_pass_through() {
+    create_upstream_role("scram_pass_through", "scram_password", "scram-sha-256").await;
+
+    let (mut config, _handle, shutdown_tx) =
+        TestBuilder::new(BackendBuilder::new().authenticate_with_upstream(true))
+            .fallback(true)
+            .build::<PostgreSQLAdapter>()
+            .await;
+    config.user("scram_pass_through");
+
+    let client = connect(config.clone().password("scram_password").clone()).await;
+    // Proxied upstream, so this tells us which user the upstream connection is authenticated as
+    let res = client
+        .query_one("SELECT current_user::text", &[])
+        .await
+        .unwrap();
+    assert_eq!(res.get::<_, String>(0), "scram_pass_through");
+
+    let err = config
+        .clone()
+        .password("wrong_password")
+        .connect(tokio_postgres::NoTls)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.code(),
+        Some(&tokio_postgres::error::SqlState::INVALID_PASSWORD)
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+#[slow]
+async fn authenticate_with_upstream_cleartext_requires_tls() {
+    // An md5 password can't be used to verify a SCRAM exchange, so we'd need the cleartext
+    // password - which we refuse to ask for over an unencrypted connection
+    create_upstream_role("md5_pass_through", "md5_password", "md5").await;
+
+    let (mut config, _handle, shutdown_tx) =
+        TestBuilder::new(BackendBuilder::new().authenticate_with_upstream(true))
+            .fallback(true)
+            .build::<PostgreSQLAdapter>()
+            .await;
+
+    let err = config
+        .user("md5_pass_through")
+        .password("md5_password")
+        .connect(tokio_postgres::NoTls)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.code(),
+        Some(&tokio_postgres::error::SqlState::INVALID_AUTHORIZATION_SPECIFICATION)
+    );
+
+    shutdown_tx.shutdown().await;
+}
@@ -134,7 +134,7 @@ impl ReplContext {
         let mut url = format!(
             "{}://",
             match self.options.database_url.database_type() {
-                DatabaseType::MySQL => "mysql",
+                DatabaseType::MySQL | DatabaseType::MariaDB => "mysql",
                 DatabaseType::PostgreSQL => "postgresql",
             }
         );
@@ -62,6 +62,10 @@ impl Builder {
                 opts.memory_limit,
                 Duration::from_secs(opts.memory_check_freq),
             );
+        } else {
+            // Even without a global memory limit, we still need to periodically check for caches
+            // that have grown beyond their own memory limits
+            builder.set_memory_check_frequency(Duration::from_secs(opts.memory_check_freq));
         }
         builder.set_eviction_kind(opts.eviction_kind);
 
@@ -89,6 +93,7 @@ impl Builder {
         builder.set_allow_mixed_comparisons(opts.enable_experimental_mixed_comparisons);
         builder.set_allow_straddled_joins(opts.enable_experimental_straddled_joins);
         builder.set_allow_post_lookup(opts.enable_experimental_post_lookup);
+        builder.set_allow_window_functions(opts.enable_experimental_window_functions);
         builder.set_worker_timeout(Duration::from_secs(opts.worker_request_timeout_seconds));
         builder.set_background_recovery_interval(Duration::from_secs(
             opts.background_recovery_interval_seconds,
@@ -124,6 +129,7 @@ impl Builder {
         builder.set_abort_on_task_failure(false);
         builder.allow_full_materialization();
         builder.set_allow_post_lookup(true);
+        builder.set_allow_window_functions(true);
         builder
     }
 
@@ -189,6 +195,13 @@ impl Builder {
         self.memory_check_frequency = Some(check_freq);
     }
 
+    /// Set how often the worker checks memory usage, without setting a global memory limit. This
+    /// is used to enforce the memory limits of individual caches.
+    pub fn set_memory_check_frequency(&mut self, check_freq: time::Duration) {
+        assert_ne!(check_freq, time::Duration::from_millis(0));
+        self.memory_check_frequency = Some(check_freq);
+    }
+
     /// Set the IP address that the worker should use for listening.
     pub fn set_listen_addr(&mut self, listen_addr: IpAddr) {
         self.listen_addr = listen_addr;
@@ -228,6 +241,11 @@ impl Builder {
         self.config.mir_config.allow_post_lookup = allow_post_lookup;
     }
 
+    /// Set the value of [`controller::sql::Config::allow_window_functions`]
+    pub fn set_allow_window_functions(&mut self, allow_window_functions: bool) {
+        self.config.mir_config.allow_window_functions = allow_window_functions;
+    }
+
     /// Set the value of [`controller::sql::Config::worker_request_timeout`]
     pub fn set_worker_timeout(&mut self, worker_request_timeout: Duration) {
         self.config.worker_request_timeout = worker_request_timeout;
@@ -636,6 +636,17 @@ impl Leader {
                 self.dataflow_state_handle.commit(writer, authority).await?;
                 return_serialized!(ReadySetResult::Ok(()));
             }
+            (&Method::POST, "/snapshot_progress") => {
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.snapshot_progress().clone());
+            }
+            (&Method::POST, "/set_snapshot_progress") => {
+                let (table, progress) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                writer.as_mut().set_snapshot_progress(table, progress);
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(ReadySetResult::Ok(()));
+            }
             (&Method::POST, "/remove_node") => {
                 require_leader_ready()?;
                 let body = bincode::deserialize(&body)?;
@@ -594,7 +594,7 @@ fn trace(
 mod tests {
     use dataflow::utils::make_columns;
     use dataflow::{node, ops, Expr};
-    use nom_sql::OrderType;
+    use nom_sql::{NullOrder, OrderType};
     use readyset_data::DfType;
 
     use super::*;
@@ -1149,7 +1149,7 @@ mod tests {
             make_columns(&["a1", "a2", "__page_number"]),
             ops::NodeOperator::Paginate(ops::paginate::Paginate::new(
                 a,
-                vec![(0, OrderType::OrderAscending)],
+                vec![(0, OrderType::OrderAscending, NullOrder::NullsFirst)],
                 vec![1],
                 3,
             )),
@@ -39,7 +39,7 @@ use dataflow::node::Column;
 use dataflow::prelude::*;
 use dataflow::{node, DomainRequest, ReaderProcessing};
 use metrics::{counter, histogram};
-use nom_sql::Relation;
+use nom_sql::{CacheExpiration, CachePriority, Relation};
 use readyset_client::metrics::recorded;
 use readyset_client::{KeyColumnIdx, ViewPlaceholder};
 use readyset_data::{DfType, Dialect};
@@ -949,6 +949,43 @@ impl<'df> Migration<'df> {
         r.set_mapping(placeholder_map);
     }
 
+    /// Configure the reader added in this migration for the given node to evict or refresh keys
+    /// once they have been cached for longer than the duration of the given [`CacheExpiration`].
+    ///
+    /// Does nothing if no reader was added for the node in this migration.
+    pub fn set_reader_expiration(&mut self, n: NodeIndex, expiration: CacheExpiration) {
+        if let Some(r) = self.added_reader_for_mut(n) {
+            r.set_expiration(expiration);
+        }
+    }
+
+    /// Configure the reader added in this migration for the given node with a per-cache memory
+    /// limit and eviction priority. If `memory_limit` is set, the worker evicts keys from the
+    /// reader whenever its state grows beyond that many bytes.
+    ///
+    /// Does nothing if no reader was added for the node in this migration.
+    pub fn set_reader_memory_budget(
+        &mut self,
+        n: NodeIndex,
+        memory_limit: Option<u64>,
+        priority: CachePriority,
+    ) {
+        if let Some(r) = self.added_reader_for_mut(n) {
+            if let Some(memory_limit) = memory_limit {
+                r.set_memory_limit(usize::try_from(memory_limit).unwrap_or(usize::MAX));
+            }
+            r.set_priority(priority);
+        }
+    }
+
+    /// Returns a mutable reference to the reader added in this migration for the given node, if
+    /// any
+    fn added_reader_for_mut(&mut self, n: NodeIndex) -> Option<&mut node::special::Reader> {
+        let ri = *self.readers.get(&n)?;
+        #[allow(clippy::indexing_slicing)] // NodeIndex must exist in ingredients
+        self.dataflow_state.ingredients[ri].as_mut_reader()
+    }
+
     /// Build a `MigrationPlan` for this migration, and apply it if the planning stage succeeds.
     pub(super) async fn commit(self, dry_run: bool) -> ReadySetResult<()> {
         let start = self.start;
@@ -1326,6 +1363,19 @@ fn plan_add_nodes(
             &dmp,
         )?;
 
+        // Readers only expire keys that were filled by a partial replay, so an expiration on a
+        // fully materialized reader would never take effect
+        for &ni in &new_nodes {
+            #[allow(clippy::indexing_slicing)] // new_nodes must be in the graph
+            if let Some(r) = dataflow_state.ingredients[ni].as_reader() {
+                if r.expiration().is_some() && !dataflow_state.materializations.is_partial(ni) {
+                    unsupported!(
+                        "Cache expiration is only supported for partially materialized caches"
+                    );
+                }
+            }
+        }
+
         // Check to see if we've just tried to add a fully materialized node below an existing
         // partially materialized node
         if let Some(InvalidEdge { parent, child }) = dataflow_state
@@ -12,9 +12,11 @@ use std::iter;
 
 use common::DfValue;
 use dataflow::node::Column as DfColumn;
-use dataflow::ops::grouped::concat::GroupConcat;
+use dataflow::ops::grouped::concat::{ConcatOutput, GroupConcat};
+use dataflow::ops::grouped::sketch::Sketch;
 use dataflow::ops::join::{Join, JoinType};
 use dataflow::ops::project::Project;
+use dataflow::ops::window::WindowFunction;
 use dataflow::ops::Side;
 use dataflow::{node, ops, Expr as DfExpr, PostLookupAggregates, ReaderProcessing};
 use itertools::Itertools;
@@ -23,7 +25,7 @@ use mir::node::node_inner::MirNodeInner;
 use mir::node::{GroupedNodeType, ProjectExpr, ViewKeyColumn};
 use mir::query::MirQuery;
 use mir::{Column, DfNodeIndex, NodeIndex as MirNodeIndex};
-use nom_sql::{ColumnConstraint, ColumnSpecification, Expr, OrderType, Relation};
+use nom_sql::{ColumnConstraint, ColumnSpecification, Expr, NullOrder, OrderType, Relation};
 use petgraph::graph::NodeIndex;
 use petgraph::Direction;
 use readyset_client::internal::{Index, IndexType};
@@ -194,7 +196,10 @@ pub(super) fn mir_node_to_flow_parts(
                         mig,
                     )?)
                 }
-                MirNodeInner::DependentJoin { .. } | MirNodeInner::DependentLeftJoin { .. } => {
+                MirNodeInner::DependentJoin { .. }
+                | MirNodeInner::DependentLeftJoin { .. }
+                | MirNodeInner::DependentSemiJoin { .. }
+                | MirNodeInner::DependentAntiJoin { .. } => {
                     // See the docstring for MirNodeInner::DependentJoin
                     internal!("Encountered dependent join when lowering to dataflow")
                 }
@@ -216,6 +221,8 @@ pub(super) fn mir_node_to_flow_parts(
                     ref returned_cols,
                     ref default_row,
                     ref aggregates,
+                    ref projection,
+                    ref filter,
                     ..
                 } => {
                     if !lowered_to_df {
@@ -229,6 +236,10 @@ pub(super) fn mir_node_to_flow_parts(
                             returned_cols,
                             default_row.clone(),
                             aggregates,
+                            projection,
+                            filter,
+                            custom_types,
+                            mig,
                         )?;
                         materialize_leaf_node(
                             graph,
@@ -263,6 +274,69 @@ pub(super) fn mir_node_to_flow_parts(
                         mig,
                     )?)
                 }
+                MirNodeInner::FullJoin {
+                    ref on,
+                    ref project,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 2);
+                    let left = ancestors[0];
+                    let right = ancestors[1];
+                    Some(make_join_node(
+                        graph,
+                        name,
+                        left,
+                        right,
+                        &graph.columns(mir_node),
+                        on,
+                        project,
+                        JoinType::Full,
+                        custom_types,
+                        mig,
+                    )?)
+                }
+                MirNodeInner::SemiJoin {
+                    ref on,
+                    ref project,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 2);
+                    let left = ancestors[0];
+                    let right = ancestors[1];
+                    Some(make_join_node(
+                        graph,
+                        name,
+                        left,
+                        right,
+                        &graph.columns(mir_node),
+                        on,
+                        project,
+                        JoinType::Semi,
+                        custom_types,
+                        mig,
+                    )?)
+                }
+                MirNodeInner::AntiJoin {
+                    ref on,
+                    ref project,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 2);
+                    let left = ancestors[0];
+                    let right = ancestors[1];
+                    Some(make_join_node(
+                        graph,
+                        name,
+                        left,
+                        right,
+                        &graph.columns(mir_node),
+                        on,
+                        project,
+                        JoinType::Anti,
+                        custom_types,
+                        mig,
+                    )?)
+                }
                 MirNodeInner::Project { ref emit } => {
                     invariant_eq!(ancestors.len(), 1);
                     let parent = ancestors[0];
@@ -330,6 +404,45 @@ pub(super) fn mir_node_to_flow_parts(
                         mig,
                     )?)
                 }
+                MirNodeInner::Window {
+                    ref order,
+                    ref group_by,
+                    ref on,
+                    kind,
+                    ..
+                } => {
+                    invariant_eq!(ancestors.len(), 1);
+                    let parent = ancestors[0];
+                    Some(make_window_node(
+                        graph,
+                        name,
+                        parent,
+                        &graph.columns(mir_node),
+                        order,
+                        group_by,
+                        on.as_ref(),
+                        kind,
+                        mig,
+                    )?)
+                }
+                MirNodeInner::FixedPoint {
+                    ref key,
+                    ref parent,
+                    ref anchor,
+                } => {
+                    invariant_eq!(ancestors.len(), 1);
+                    let src = ancestors[0];
+                    Some(make_fixed_point_node(
+                        graph,
+                        name,
+                        src,
+                        &graph.columns(mir_node),
+                        key,
+                        parent,
+                        anchor,
+                        mig,
+                    )?)
+                }
                 MirNodeInner::AliasTable { .. } => None,
             };
 
@@ -426,7 +539,7 @@ fn make_union_node(
     duplicate_mode: ops::union::DuplicateMode,
     mig: &mut Migration<'_>,
 ) -> ReadySetResult<DfNodeIndex> {
-    let mut emit_column_id: HashMap<NodeIndex, Vec<usize>> = HashMap::new();
+    let mut emit_column_id: Vec<(NodeIndex, Vec<usize>)> = Vec::with_capacity(ancestors.len());
 
     let mut cols = Vec::with_capacity(
         emit.first()
@@ -465,14 +578,14 @@ fn make_union_node(
                 .collect::<ReadySetResult<Vec<_>>>()?;
         }
 
-        emit_column_id.insert(ni.address(), emit_cols);
+        emit_column_id.push((ni.address(), emit_cols));
     }
     set_names(&column_names(columns), &mut cols)?;
 
     let node = mig.add_ingredient(
         name,
         cols,
-        ops::union::Union::new(emit_column_id, duplicate_mode)?,
+        ops::union::Union::new_ordered(emit_column_id, duplicate_mode)?,
     );
 
     Ok(DfNodeIndex::new(node))
@@ -573,6 +686,47 @@ fn make_grouped_node(
             set_names(&column_names(columns), &mut cols)?;
             mig.add_ingredient(name, cols, gc)
         }
+        GroupedNodeType::Aggregation(
+            agg @ (Aggregation::ArrayAgg | Aggregation::JsonAgg { .. }),
+        ) => {
+            let output = match agg {
+                Aggregation::JsonAgg { jsonb } => ConcatOutput::Json {
+                    element_type: over_col_ty.clone(),
+                    jsonb,
+                },
+                _ => ConcatOutput::Array {
+                    element_type: over_col_ty.clone(),
+                },
+            };
+            let gc = GroupConcat::with_output(
+                parent_na.address(),
+                over_col_indx,
+                group_col_indx,
+                output,
+            )?;
+            let agg_col = make_agg_col(gc.output_col_type());
+            cols.push(agg_col);
+            set_names(&column_names(columns), &mut cols)?;
+            mig.add_ingredient(name, cols, gc)
+        }
+        GroupedNodeType::Aggregation(
+            agg @ (Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile),
+        ) => {
+            let sketch = match agg {
+                Aggregation::ApproxCountDistinct => Sketch::HyperLogLog,
+                _ => Sketch::Quantiles,
+            };
+            let grouped = sketch.over(
+                parent_na.address(),
+                over_col_indx,
+                group_col_indx.as_slice(),
+                over_col_ty,
+            )?;
+            let agg_col = make_agg_col(grouped.output_col_type());
+            cols.push(agg_col);
+            set_names(&column_names(columns), &mut cols)?;
+            mig.add_ingredient(name, cols, grouped)
+        }
         GroupedNodeType::Aggregation(agg) => {
             let grouped = agg.over(
                 parent_na.address(),
@@ -1031,7 +1185,7 @@ fn make_paginate_or_topk_node(
     name: Relation,
     parent: MirNodeIndex,
     columns: &[Column],
-    order: &Option<Vec<(Column, OrderType)>>,
+    order: &Option<Vec<(Column, OrderType, NullOrder)>>,
     group_by: &[Column],
     limit: usize,
     is_topk: bool,
@@ -1072,16 +1226,17 @@ fn make_paginate_or_topk_node(
     let cmp_rows = match *order {
         Some(ref o) => {
             o.iter()
-                .map(|(c, order_type)| {
+                .map(|(c, order_type, null_order)| {
                     // SQL and Soup disagree on what ascending and descending order means, so do the
-                    // conversion here.
+                    // conversion here. Since this reverses the whole order, nulls have to move to
+                    // the other end too.
                     let reversed_order_type = match *order_type {
                         OrderType::OrderAscending => OrderType::OrderDescending,
                         OrderType::OrderDescending => OrderType::OrderAscending,
                     };
                     graph
                         .column_id_for_column(parent, c)
-                        .map(|id| (id, reversed_order_type))
+                        .map(|id| (id, reversed_order_type, null_order.reverse()))
                 })
                 .collect::<ReadySetResult<Vec<_>>>()?
         }
@@ -1105,21 +1260,159 @@ fn make_paginate_or_topk_node(
     Ok(DfNodeIndex::new(na))
 }
 
+#[allow(clippy::too_many_arguments)]
+fn make_window_node(
+    graph: &MirGraph,
+    name: Relation,
+    parent: MirNodeIndex,
+    columns: &[Column],
+    order: &Option<Vec<(Column, OrderType, NullOrder)>>,
+    group_by: &[Column],
+    on: Option<&Column>,
+    kind: WindowFunction,
+    mig: &mut Migration<'_>,
+) -> ReadySetResult<DfNodeIndex> {
+    let parent_na = graph.resolve_dataflow_node(parent).ok_or_else(|| {
+        ReadySetError::MirNodeMustHaveDfNodeAssigned {
+            mir_node_index: parent.index(),
+        }
+    })?;
+    let mut parent_cols = mig.dataflow_state.ingredients[parent_na.address()]
+        .columns()
+        .to_vec();
+
+    invariant!(
+        !group_by.is_empty(),
+        "need bogokey for Window without group columns"
+    );
+
+    let group_by_indx = group_by
+        .iter()
+        .map(|c| graph.column_id_for_column(parent, c))
+        .collect::<ReadySetResult<Vec<_>>>()?;
+
+    let over_col_indx = on
+        .map(|c| graph.column_id_for_column(parent, c))
+        .transpose()?;
+    let over_col_ty = over_col_indx
+        .map(|i| {
+            parent_cols
+                .get(i)
+                .map(|c| c.ty().clone())
+                .ok_or_else(|| internal_err!("Invalid index"))
+        })
+        .transpose()?;
+
+    // Unlike TopK and Paginate, Window sorts rows directly using the order given, so we don't
+    // need to reverse the order types here.
+    let cmp_rows = match *order {
+        Some(ref o) => o
+            .iter()
+            .map(|(c, order_type, null_order)| {
+                graph
+                    .column_id_for_column(parent, c)
+                    .map(|id| (id, *order_type, *null_order))
+            })
+            .collect::<ReadySetResult<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    // set names using MIR columns to ensure aliases are used
+    let column_names = column_names(columns);
+    #[allow(clippy::unwrap_used)] // column_names must be populated
+    parent_cols.push(DfColumn::new(
+        column_names.last().unwrap().into(),
+        kind.output_col_type(over_col_ty.as_ref()),
+        Some(name.clone()),
+    ));
+    set_names(&column_names, &mut parent_cols)?;
+
+    let na = mig.add_ingredient(
+        name,
+        parent_cols,
+        ops::window::Window::new(
+            parent_na.address(),
+            kind,
+            over_col_indx,
+            over_col_ty.as_ref(),
+            cmp_rows,
+            group_by_indx,
+        )?,
+    );
+    Ok(DfNodeIndex::new(na))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn make_fixed_point_node(
+    graph: &MirGraph,
+    name: Relation,
+    src: MirNodeIndex,
+    columns: &[Column],
+    key: &Column,
+    parent: &Column,
+    anchor: &Column,
+    mig: &mut Migration<'_>,
+) -> ReadySetResult<DfNodeIndex> {
+    let src_na = graph.resolve_dataflow_node(src).ok_or_else(|| {
+        ReadySetError::MirNodeMustHaveDfNodeAssigned {
+            mir_node_index: src.index(),
+        }
+    })?;
+    let src_cols = mig.dataflow_state.ingredients[src_na.address()]
+        .columns()
+        .to_vec();
+
+    let key_idx = graph.column_id_for_column(src, key)?;
+    let parent_idx = graph.column_id_for_column(src, parent)?;
+    let anchor_idx = graph.column_id_for_column(src, anchor)?;
+
+    let num_columns = src_cols.len();
+    let mut cols = src_cols
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| *i != parent_idx && *i != anchor_idx)
+        .map(|(_, c)| c)
+        .collect::<Vec<_>>();
+    set_names(&column_names(columns), &mut cols)?;
+
+    let na = mig.add_ingredient(
+        name,
+        cols,
+        ops::fixed_point::FixedPoint::new(
+            src_na.address(),
+            num_columns,
+            key_idx,
+            parent_idx,
+            anchor_idx,
+        )?,
+    );
+    Ok(DfNodeIndex::new(na))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn make_reader_processing(
     graph: &MirGraph,
     parent: &MirNodeIndex,
-    order_by: &Option<Vec<(Column, OrderType)>>,
+    order_by: &Option<Vec<(Column, OrderType, NullOrder)>>,
     limit: Option<usize>,
     returned_cols: &Option<Vec<Column>>,
     default_row: Option<Vec<DfValue>>,
     aggregates: &Option<PostLookupAggregates<Column>>,
+    projection: &Option<Vec<(Column, Expr)>>,
+    filter: &Option<Expr>,
+    custom_types: &HashMap<Relation, DfType>,
+    mig: &Migration<'_>,
 ) -> ReadySetResult<ReaderProcessing> {
     let order_by = if let Some(order) = order_by.as_ref() {
         Some(
             order
                 .iter()
-                .map(|(col, ot)| graph.column_id_for_column(*parent, col).map(|id| (id, *ot)))
-                .collect::<ReadySetResult<Vec<(usize, OrderType)>>>()?,
+                .map(|(col, ot, no)| {
+                    graph
+                        .column_id_for_column(*parent, col)
+                        .map(|id| (id, *ot, *no))
+                })
+                .collect::<ReadySetResult<Vec<(usize, OrderType, NullOrder)>>>()?,
         )
     } else {
         None
@@ -1144,7 +1437,72 @@ fn make_reader_processing(
         .map(|aggs| aggs.map_columns(|col| graph.column_id_for_column(*parent, &col)))
         .transpose()?;
 
-    ReaderProcessing::new(order_by, limit, returned_cols, default_row, aggregates)
+    let parent_cols = if projection.is_some() || filter.is_some() {
+        let parent_na = graph.resolve_dataflow_node(*parent).ok_or_else(|| {
+            ReadySetError::MirNodeMustHaveDfNodeAssigned {
+                mir_node_index: parent.index(),
+            }
+        })?;
+        mig.dataflow_state.ingredients[parent_na.address()]
+            .columns()
+            .to_vec()
+    } else {
+        vec![]
+    };
+
+    // Columns which aren't recomputed are projected as-is
+    let projection = projection
+        .as_ref()
+        .map(|projection| -> ReadySetResult<_> {
+            let mut exprs = parent_cols
+                .iter()
+                .enumerate()
+                .map(|(index, col)| DfExpr::Column {
+                    index,
+                    ty: col.ty().clone(),
+                })
+                .collect::<Vec<_>>();
+            for (col, expr) in projection {
+                let index = graph.column_id_for_column(*parent, col)?;
+                let expr = lower_expression(
+                    graph,
+                    *parent,
+                    expr.clone(),
+                    &parent_cols,
+                    custom_types,
+                    mig.dialect,
+                )?;
+                *exprs
+                    .get_mut(index)
+                    .ok_or_else(|| internal_err!("Projected column {col} out of bounds"))? = expr;
+            }
+            Ok(exprs)
+        })
+        .transpose()?;
+
+    let filter = filter
+        .as_ref()
+        .map(|filter| {
+            lower_expression(
+                graph,
+                *parent,
+                filter.clone(),
+                &parent_cols,
+                custom_types,
+                mig.dialect,
+            )
+        })
+        .transpose()?;
+
+    ReaderProcessing::new(
+        order_by,
+        limit,
+        returned_cols,
+        default_row,
+        aggregates,
+        projection,
+        filter,
+    )
 }
 
 fn materialize_leaf_node(
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 
 use dataflow::{PostLookupAggregate, PostLookupAggregateFunction, PostLookupAggregates};
@@ -6,13 +7,17 @@ use mir::node::ProjectExpr;
 use mir::{Column, NodeIndex};
 use nom_sql::analysis::ReferredColumns;
 use nom_sql::FunctionExpr::*;
-use nom_sql::{self, DialectDisplay, Expr, FieldDefinitionExpr, Relation, SqlIdentifier};
-use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
+use nom_sql::{
+    self, BinaryOperator, DialectDisplay, Expr, FieldDefinitionExpr, FunctionExpr, Relation,
+    SqlIdentifier,
+};
+use readyset_data::DfValue;
+use readyset_errors::{internal_err, invalid_query, unsupported, ReadySetError, ReadySetResult};
 use readyset_sql_passes::is_aggregate;
 
 use crate::controller::sql::mir::join::make_joins_for_aggregates;
 use crate::controller::sql::mir::SqlToMirConverter;
-use crate::controller::sql::query_graph::QueryGraph;
+use crate::controller::sql::query_graph::{ExprColumn, OutputColumn, QueryGraph};
 
 // Move predicates above grouped_by nodes
 pub(super) fn make_predicates_above_grouped<'a>(
@@ -261,9 +266,76 @@ fn joinable_aggregate_nodes(
         .collect()
 }
 
+/// Returns the aggregates that the post-lookup aggregate for the given aggregate function is
+/// recomputed from, if it can't be re-aggregated from its own results.
+///
+/// An average can be recomputed from the sum and count of the values it's averaging, and a
+/// distinct aggregate from (all of) the values it's aggregating.
+fn hidden_aggregates(function: &FunctionExpr) -> Vec<FunctionExpr> {
+    match function {
+        Avg {
+            expr,
+            distinct: false,
+        } => vec![
+            Sum {
+                expr: expr.clone(),
+                distinct: false,
+            },
+            Count {
+                expr: expr.clone(),
+                distinct: false,
+            },
+        ],
+        Count {
+            expr,
+            distinct: true,
+        }
+        | Sum {
+            expr,
+            distinct: true,
+        }
+        | Avg {
+            expr,
+            distinct: true,
+        } => vec![ArrayAgg(expr.clone())],
+        _ => vec![],
+    }
+}
+
+/// Add the aggregates that the post-lookup aggregates for the given query are recomputed from to
+/// the query, if they're not already part of it.
+///
+/// These are projected as hidden columns in the leaf of the query, to be re-aggregated
+/// post-lookup along with the other aggregates, so this must be called (on the query graph used
+/// to construct the rest of the query) whenever the query requires post-lookup aggregation.
+pub(super) fn with_hidden_aggregates(query_graph: &QueryGraph) -> Cow<'_, QueryGraph> {
+    let hidden = query_graph
+        .aggregates
+        .keys()
+        .flat_map(hidden_aggregates)
+        .filter(|f| !query_graph.aggregates.contains_key(f))
+        .collect::<Vec<_>>();
+
+    // Post-lookup aggregation for DISTINCT queries ignores their aggregates entirely (see
+    // `post_lookup_aggregates`)
+    if hidden.is_empty() || query_graph.distinct {
+        return Cow::Borrowed(query_graph);
+    }
+
+    let mut query_graph = query_graph.clone();
+    for function in hidden {
+        let alias = function.display(nom_sql::Dialect::MySQL).to_string().into();
+        query_graph.aggregates.insert(function, alias);
+    }
+    Cow::Owned(query_graph)
+}
+
 /// Build up the set of [`PostLookupAggregates`] for the given query, given as both the query
 /// graph itself and the select statement that the query is built from.
 ///
+/// Any hidden aggregates the post-lookup aggregates are recomputed from must already have been
+/// added to the query graph with [`with_hidden_aggregates`].
+///
 /// This function is *not* responsible for determining whether the query *requires* post-lookup
 /// aggregation - that's the responsibility of the caller. This function will only return [`None`]
 /// if the query contains no aggregates.
@@ -302,23 +374,96 @@ pub(super) fn post_lookup_aggregates(
         return Ok(None);
     }
 
+    let hidden_column = |function: FunctionExpr| -> ReadySetResult<Column> {
+        let alias = query_graph.aggregates.get(&function).ok_or_else(|| {
+            internal_err!(
+                "Hidden aggregate {} missing from query",
+                function.display(nom_sql::Dialect::MySQL)
+            )
+        })?;
+        Ok(Column::named(alias.clone()).aliased_as_table(query_name.clone()))
+    };
+
     let mut aggregates = vec![];
     for (function, alias) in &query_graph.aggregates {
         aggregates.push(PostLookupAggregate {
             column: Column::named(alias.clone()).aliased_as_table(query_name.clone()),
             function: match function {
-                Avg { .. } => {
-                    unsupported!("Average is not supported as a post-lookup aggregate")
-                }
+                Avg {
+                    expr,
+                    distinct: false,
+                } => PostLookupAggregateFunction::Avg {
+                    sum: hidden_column(Sum {
+                        expr: expr.clone(),
+                        distinct: false,
+                    })?,
+                    count: hidden_column(Count {
+                        expr: expr.clone(),
+                        distinct: false,
+                    })?,
+                },
+                Count {
+                    expr,
+                    distinct: true,
+                } => PostLookupAggregateFunction::CountDistinct {
+                    values: hidden_column(ArrayAgg(expr.clone()))?,
+                },
+                Sum {
+                    expr,
+                    distinct: true,
+                } => PostLookupAggregateFunction::SumDistinct {
+                    values: hidden_column(ArrayAgg(expr.clone()))?,
+                },
+                Avg {
+                    expr,
+                    distinct: true,
+                } => PostLookupAggregateFunction::AvgDistinct {
+                    values: hidden_column(ArrayAgg(expr.clone()))?,
+                },
                 // Count and sum are handled the same way, as re-aggregating counts is
                 // done by just summing the numbers together
                 Count { .. } | CountStar | Sum { .. } => PostLookupAggregateFunction::Sum,
                 Max(_) => PostLookupAggregateFunction::Max,
                 Min(_) => PostLookupAggregateFunction::Min,
+                // A group's booleans are all true iff the smallest of them is true, and any of them
+                // is true iff the largest of them is true
+                BoolAnd(_) => PostLookupAggregateFunction::Min,
+                BoolOr(_) => PostLookupAggregateFunction::Max,
+                Stddev { .. } | Variance { .. } => unsupported!(
+                    "Standard deviation and variance are not supported as post-lookup aggregates"
+                ),
+                BitAnd(_) => PostLookupAggregateFunction::BitAnd,
+                BitOr(_) => PostLookupAggregateFunction::BitOr,
+                ArrayAgg(_) => PostLookupAggregateFunction::ArrayConcat,
+                JsonAgg { .. } => {
+                    unsupported!("JSON_AGG is not supported as a post-lookup aggregate")
+                }
+                ApproxCountDistinct(_) => PostLookupAggregateFunction::ApproxCountDistinct,
+                ApproxPercentile { percentile, .. } => {
+                    let fraction = DfValue::try_from(percentile)
+                        .and_then(|v| f64::try_from(&v))
+                        .ok()
+                        .filter(|f| (0.0..=1.0).contains(f));
+                    match fraction {
+                        Some(f) => PostLookupAggregateFunction::ApproxPercentile {
+                            percentile: DfValue::Double(f),
+                        },
+                        None => invalid_query!(
+                            "The percentile for APPROX_PERCENTILE must be a number between 0 and 1"
+                        ),
+                    }
+                }
                 GroupConcat { separator, .. } => PostLookupAggregateFunction::GroupConcat {
                     separator: separator.clone().unwrap_or_else(|| ",".to_owned()),
                 },
-                Call { .. } | Substring { .. } => continue,
+                Call { .. }
+                | Substring { .. }
+                | RowNumber
+                | Rank
+                | DenseRank
+                | Lag { .. }
+                | Lead { .. }
+                | Over { .. } => continue,
             },
         });
     }
@@ -338,3 +483,100 @@ pub(super) fn post_lookup_aggregates(
         aggregates,
     }))
 }
+
+/// Returns the projected expressions in the given query which depend on the results of its
+/// aggregates, along with the columns they're projected as.
+///
+/// If the aggregates are re-aggregated post-lookup, the values of these expressions computed in the
+/// dataflow graph are stale, and they have to be recomputed post-lookup.
+pub(super) fn post_lookup_projection(query_graph: &QueryGraph) -> Option<Vec<(Column, Expr)>> {
+    let aliases = query_graph.aggregates.values().collect::<HashSet<_>>();
+    let projection = query_graph
+        .columns
+        .iter()
+        .filter_map(|oc| match oc {
+            OutputColumn::Expr(ExprColumn {
+                name, expression, ..
+            }) if expression
+                .referred_columns()
+                .any(|col| col.table.is_none() && aliases.contains(&col.name)) =>
+            {
+                Some((Column::named(name.clone()), expression.clone()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    (!projection.is_empty()).then_some(projection)
+}
+
+/// Returns the conjunction of the HAVING predicates in the given query, if any.
+///
+/// If the query's aggregates are re-aggregated post-lookup, these have to be evaluated post-lookup
+/// too, rather than in the dataflow graph.
+pub(super) fn post_lookup_filter(query_graph: &QueryGraph) -> Option<Expr> {
+    query_graph
+        .having_predicates
+        .iter()
+        .cloned()
+        .reduce(|lhs, rhs| Expr::BinaryOp {
+            lhs: Box::new(lhs),
+            op: BinaryOperator::And,
+            rhs: Box::new(rhs),
+        })
+}
+
+/// Returns true if the given query contains any approximate aggregates, whose values are sketches
+/// that have to be turned into the results of the aggregates post-lookup
+pub(super) fn has_approximate_aggregates(query_graph: &QueryGraph) -> bool {
+    query_graph
+        .aggregates
+        .keys()
+        .any(|f| matches!(f, ApproxCountDistinct(_) | ApproxPercentile { .. }))
+}
+
+/// Check that the results of the approximate aggregates in the given query, if any, are only
+/// projected directly out of the query. Nothing in the dataflow graph can make sense of the
+/// sketches those aggregates are represented as, so they can't be used in subqueries, expressions,
+/// or predicates.
+pub(super) fn check_approximate_aggregates(
+    query_graph: &QueryGraph,
+    is_leaf: bool,
+) -> ReadySetResult<()> {
+    let aliases = query_graph
+        .aggregates
+        .iter()
+        .filter(|(f, _)| matches!(f, ApproxCountDistinct(_) | ApproxPercentile { .. }))
+        .map(|(_, alias)| alias)
+        .collect::<Vec<_>>();
+    if aliases.is_empty() {
+        return Ok(());
+    }
+
+    if !is_leaf {
+        unsupported!("Approximate aggregates are not supported in subqueries");
+    }
+    if query_graph.distinct {
+        unsupported!("Approximate aggregates are not supported in queries with DISTINCT");
+    }
+
+    let refers_to_sketch = |expr: &Expr| {
+        expr.referred_columns()
+            .any(|col| aliases.contains(&&col.name))
+    };
+    if query_graph.having_predicates.iter().any(refers_to_sketch)
+        || query_graph.columns.iter().any(|oc| match oc {
+            OutputColumn::Expr(ExprColumn { expression, .. }) => refers_to_sketch(expression),
+            _ => false,
+        })
+        || query_graph
+            .pagination
+            .iter()
+            .flat_map(|pagination| pagination.order.iter().flatten())
+            .any(|(expr, _, _)| refers_to_sketch(expr))
+    {
+        unsupported!("The results of approximate aggregates can only be projected directly");
+    }
+
+    Ok(())
+}
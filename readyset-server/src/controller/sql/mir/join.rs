@@ -48,6 +48,7 @@ pub(super) fn make_joins(
         let (mut join_kind, jps, left_preds, right_preds) =
             match &qg.edges[&(jref.src.clone(), jref.dst.clone())] {
                 QueryGraphEdge::Join { on } => (JoinKind::Inner, on, None, None),
+                QueryGraphEdge::FullJoin { on } => (JoinKind::Full, on, None, None),
                 QueryGraphEdge::LeftJoin {
                     on,
                     left_local_preds,
@@ -81,7 +82,19 @@ pub(super) fn make_joins(
                 JoinKind::Inner => {
                     join_kind = JoinKind::DependentInner;
                 }
-                JoinKind::DependentInner | JoinKind::DependentLeft => {}
+                JoinKind::Full => {
+                    unsupported!("Correlated subqueries on the right-hand side of FULL JOIN")
+                }
+                JoinKind::Semi => {
+                    join_kind = JoinKind::DependentSemi;
+                }
+                JoinKind::Anti => {
+                    join_kind = JoinKind::DependentAnti;
+                }
+                JoinKind::DependentInner
+                | JoinKind::DependentLeft
+                | JoinKind::DependentSemi
+                | JoinKind::DependentAnti => {}
             }
         }
 
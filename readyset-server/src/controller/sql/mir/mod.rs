@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::iter;
@@ -8,6 +9,7 @@ use catalog_tables::is_catalog_table;
 use common::IndexType;
 use dataflow::ops::grouped::aggregate::Aggregation;
 use dataflow::ops::union;
+use dataflow::ops::window::WindowFunction;
 use lazy_static::lazy_static;
 use mir::graph::MirGraph;
 use mir::node::node_inner::MirNodeInner;
@@ -19,8 +21,8 @@ use nom_sql::analysis::ReferredColumns;
 use nom_sql::{
     BinaryOperator, CaseWhenBranch, ColumnSpecification, CompoundSelectOperator, CreateTableBody,
     DialectDisplay, Expr, FieldDefinitionExpr, FieldReference, FunctionExpr, InValue, LimitClause,
-    Literal, NonReplicatedRelation, OrderBy, OrderClause, OrderType, Relation, SelectStatement,
-    SqlIdentifier, TableKey, UnaryOperator,
+    Literal, NonReplicatedRelation, NullOrder, OrderBy, OrderClause, OrderType, Relation,
+    SelectStatement, SqlIdentifier, TableKey, UnaryOperator,
 };
 use petgraph::visit::Reversed;
 use petgraph::Direction;
@@ -29,18 +31,19 @@ use readyset_errors::{
     internal, internal_err, invalid_query, invalid_query_err, invariant, invariant_eq, unsupported,
     ReadySetError, ReadySetResult,
 };
-use readyset_sql_passes::is_correlated;
+use readyset_sql_passes::{is_correlated, is_correlated_by_equalities_only};
 use readyset_util::redacted::Sensitive;
 use tracing::{debug, trace};
 
-use super::query_graph::{extract_limit_offset, JoinPredicate};
+use super::query_graph::{extract_limit_offset, resolve_null_order, JoinPredicate};
 use crate::controller::sql::mir::grouped::{
-    make_expressions_above_grouped, make_grouped, make_predicates_above_grouped,
-    post_lookup_aggregates,
+    check_approximate_aggregates, has_approximate_aggregates, make_expressions_above_grouped,
+    make_grouped, make_predicates_above_grouped, post_lookup_aggregates, post_lookup_filter,
+    post_lookup_projection, with_hidden_aggregates,
 };
 use crate::controller::sql::mir::join::{make_cross_joins, make_joins};
 use crate::controller::sql::query_graph::{
-    to_query_graph, ExprColumn, OutputColumn, Pagination, QueryGraph,
+    to_query_graph, ExprColumn, OutputColumn, Pagination, QueryGraph, ViewKey,
 };
 use crate::controller::sql::query_signature::Signature;
 
@@ -49,6 +52,12 @@ mod join;
 
 lazy_static! {
     pub static ref PAGE_NUMBER_COL: SqlIdentifier = "__page_number".into();
+    /// Column added to both terms of a recursive common table expression, containing the value of
+    /// the key column of the parent of each row of the recursive term
+    pub static ref RECURSIVE_PARENT_COL: SqlIdentifier = "__recursive_parent".into();
+    /// Column added to both terms of a recursive common table expression, which is true for rows
+    /// of the non-recursive term
+    pub static ref RECURSIVE_ANCHOR_COL: SqlIdentifier = "__recursive_anchor".into();
 }
 
 fn value_columns_needed_for_predicates(
@@ -104,10 +113,20 @@ pub enum JoinKind {
     Inner,
     /// Left joins - see [`MirNodeInner::LeftJoin`]
     Left,
+    /// Full outer joins - see [`MirNodeInner::FullJoin`]
+    Full,
+    /// Semi-joins - see [`MirNodeInner::SemiJoin`]
+    Semi,
+    /// Anti-joins - see [`MirNodeInner::AntiJoin`]
+    Anti,
     /// Dependent inner joins - see [`MirNodeInner::DependentJoin`]
     DependentInner,
     /// Dependent left joins - see [`MirNodeInner::DependentLeftJoin`]
     DependentLeft,
+    /// Dependent semi-joins - see [`MirNodeInner::DependentSemiJoin`]
+    DependentSemi,
+    /// Dependent anti-joins - see [`MirNodeInner::DependentAntiJoin`]
+    DependentAnti,
 }
 
 /// Specification for how to treat the leaf node of a query when converting it to MIR
@@ -164,6 +183,13 @@ pub(crate) struct Config {
     /// reader)
     #[serde(default)]
     pub(crate) allow_post_lookup: bool,
+
+    /// Enable support for window functions (such as `ROW_NUMBER() OVER (...)`), which are
+    /// lowered to [`Window`][] nodes
+    ///
+    /// [`Window`]: MirNodeInner::Window
+    #[serde(default)]
+    pub(crate) allow_window_functions: bool,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -240,12 +266,17 @@ impl SqlToMirConverter {
     pub(super) fn compound_query_to_mir(
         &mut self,
         query_name: &Relation,
-        subquery_leaves: Vec<NodeIndex>,
-        op: CompoundSelectOperator,
+        subquery_leaves: Vec<(Option<CompoundSelectOperator>, NodeIndex)>,
         order: &Option<OrderClause>,
         limit_clause: &LimitClause,
         leaf_behavior: LeafBehavior,
     ) -> ReadySetResult<NodeIndex> {
+        if limit_clause.requires_lookup_time_pagination() {
+            unsupported!(
+                "Parametrized LIMIT and OFFSET without LIMIT are not supported in compound queries"
+            );
+        }
+
         let has_limit = matches!(
             limit_clause,
             LimitClause::OffsetCommaLimit { .. } | LimitClause::LimitOffset { limit: Some(_), .. }
@@ -255,15 +286,7 @@ impl SqlToMirConverter {
         } else {
             format!("{}_union", query_name.display_unquoted()).into()
         };
-        let mut final_node = match op {
-            CompoundSelectOperator::Union => self.make_union_node(
-                query_name,
-                name,
-                subquery_leaves.as_slice(),
-                union::DuplicateMode::UnionAll,
-            )?,
-            _ => internal!(),
-        };
+        let mut final_node = self.make_compound_select_nodes(query_name, name, subquery_leaves)?;
 
         if let Some((limit, offset)) = extract_limit_offset(limit_clause)? {
             let make_topk = offset.is_none();
@@ -299,13 +322,8 @@ impl SqlToMirConverter {
                                      }| {
                                         let order_type =
                                             order_type.unwrap_or(OrderType::OrderAscending);
-                                        if let Some(null_order) = null_order {
-                                            if !null_order.is_default_for(order_type) {
-                                                unsupported!(
-                                                 "Non-default NULLS FIRST/LAST is not yet supported"
-                                             );
-                                            }
-                                        }
+                                        let null_order =
+                                            resolve_null_order(order_type, *null_order);
                                         Ok((
                                             match field {
                                                 FieldReference::Numeric(_) => internal!(
@@ -314,6 +332,7 @@ impl SqlToMirConverter {
                                                 FieldReference::Expr(e) => e.clone(),
                                             },
                                             order_type,
+                                            null_order,
                                         ))
                                     },
                                 )
@@ -704,6 +723,106 @@ impl SqlToMirConverter {
         ))
     }
 
+    /// Make the union nodes combining the leaves of the subqueries of a compound select statement
+    /// with the operators between them, returning the last node, which will be named `name`.
+    ///
+    /// Operators are applied from left to right, with runs of the same kind of `UNION` combined
+    /// into a single union node.
+    fn make_compound_select_nodes(
+        &mut self,
+        query_name: &Relation,
+        name: Relation,
+        subquery_leaves: Vec<(Option<CompoundSelectOperator>, NodeIndex)>,
+    ) -> ReadySetResult<NodeIndex> {
+        fn duplicate_mode(op: CompoundSelectOperator) -> union::DuplicateMode {
+            match op {
+                CompoundSelectOperator::Union => union::DuplicateMode::UnionAll,
+                CompoundSelectOperator::DistinctUnion => union::DuplicateMode::UnionDistinct,
+                CompoundSelectOperator::Intersect => union::DuplicateMode::Intersect,
+                CompoundSelectOperator::Except => union::DuplicateMode::Except,
+            }
+        }
+
+        let mut subquery_leaves = subquery_leaves.into_iter();
+        let (_, first_leaf) = subquery_leaves
+            .next()
+            .ok_or_else(|| internal_err!("Compound select statement has no subqueries"))?;
+        let mut operands = vec![first_leaf];
+        let mut current_op = None;
+        for (i, (op, leaf)) in subquery_leaves.enumerate() {
+            let op = op.ok_or_else(|| {
+                internal_err!("Missing operator between subqueries of compound select statement")
+            })?;
+
+            if let Some(current_op) = current_op {
+                // INTERSECT binds more tightly than UNION and EXCEPT, which we don't (yet) take
+                // into account when combining operators from left to right
+                if op == CompoundSelectOperator::Intersect && current_op != op {
+                    unsupported!("INTERSECT following UNION or EXCEPT is not yet supported");
+                }
+
+                let combines_with_current = op == current_op
+                    && matches!(
+                        op,
+                        CompoundSelectOperator::Union | CompoundSelectOperator::DistinctUnion
+                    );
+                if !combines_with_current {
+                    let node = self.make_union_node(
+                        query_name,
+                        format!("{}_compound_{}", query_name.display_unquoted(), i).into(),
+                        &operands,
+                        duplicate_mode(current_op),
+                    )?;
+                    operands = vec![node];
+                }
+            }
+
+            current_op = Some(op);
+            operands.push(leaf);
+        }
+
+        let op = current_op.ok_or_else(|| {
+            internal_err!("Compound select statement must have more than one subquery")
+        })?;
+        self.make_union_node(query_name, name, &operands, duplicate_mode(op))
+    }
+
+    /// Make the nodes computing the rows of a recursive common table expression, given the leaves
+    /// of its non-recursive (`anchor`) and recursive (`step`) terms, returning the last node.
+    ///
+    /// Both leaves must have the same column names, including a [`RECURSIVE_PARENT_COL`] containing
+    /// the value of `key` for the parent of each row of the recursive term, and a
+    /// [`RECURSIVE_ANCHOR_COL`] which is true for the rows of the non-recursive term.
+    pub(super) fn make_fixed_point_nodes(
+        &mut self,
+        query_name: &Relation,
+        anchor: NodeIndex,
+        step: NodeIndex,
+        key: Column,
+    ) -> ReadySetResult<NodeIndex> {
+        let union_name = self.generate_label(&"recursive_union".into());
+        let union = self.make_union_node(
+            query_name,
+            union_name,
+            &[anchor, step],
+            union::DuplicateMode::UnionAll,
+        )?;
+
+        let name = self.generate_label(&"fixed_point".into());
+        Ok(self.add_query_node(
+            query_name.clone(),
+            MirNode::new(
+                name,
+                MirNodeInner::FixedPoint {
+                    key,
+                    parent: Column::named(&*RECURSIVE_PARENT_COL),
+                    anchor: Column::named(&*RECURSIVE_ANCHOR_COL),
+                },
+            ),
+            &[union],
+        ))
+    }
+
     fn make_union_from_same_base(
         &mut self,
         query_name: &Relation,
@@ -818,43 +937,58 @@ impl SqlToMirConverter {
 
         let mut out_nodes = Vec::new();
 
-        let mknode = |over: Column, t: GroupedNodeType, distinct: bool| {
-            if distinct {
-                let new_name = format!("{}_d{}", name.display_unquoted(), out_nodes.len()).into();
-                let mut dist_col = vec![over.clone()];
-                dist_col.extend(group_cols.clone());
-                let node = self.make_distinct_node(query_name, new_name, parent, dist_col.clone());
-                out_nodes.push(node);
-                out_nodes.push(self.make_grouped_node(
-                    query_name,
-                    name,
-                    func_col,
-                    (node, over),
-                    group_cols,
-                    t,
-                ));
+        let mknode = |over: Column, t: GroupedNodeType| {
+            out_nodes.push(self.make_grouped_node(
+                query_name,
+                name,
+                func_col,
+                (parent, over),
+                group_cols,
+                t,
+            ));
+            out_nodes
+        };
+
+        let sum = |distinct| {
+            GroupedNodeType::Aggregation(if distinct {
+                Aggregation::SumDistinct
             } else {
-                out_nodes.push(self.make_grouped_node(
-                    query_name,
-                    name,
-                    func_col,
-                    (parent, over),
-                    group_cols,
-                    t,
-                ));
+                Aggregation::Sum
+            })
+        };
+        let count = |distinct| {
+            GroupedNodeType::Aggregation(if distinct {
+                Aggregation::CountDistinct
+            } else {
+                Aggregation::Count
+            })
+        };
+        let avg = |distinct| {
+            GroupedNodeType::Aggregation(if distinct {
+                Aggregation::AvgDistinct
+            } else {
+                Aggregation::Avg
+            })
+        };
+        // The column to aggregate over for the argument to an aggregate function, which is either
+        // a column itself or has been projected by the parent of the aggregate node
+        let over_column = |expr: &Expr| -> ReadySetResult<Column> {
+            match expr {
+                Expr::Column(col) => Ok(Column::from(col.clone())),
+                expr => Ok(Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                )),
             }
-            out_nodes
         };
 
         Ok(match function {
             Sum {
                 expr: box Expr::Column(col),
                 distinct,
-            } => mknode(
-                Column::from(col),
-                GroupedNodeType::Aggregation(Aggregation::Sum),
-                distinct,
-            ),
+            } => mknode(Column::from(col), sum(distinct)),
             Sum { expr, distinct } => mknode(
                 Column::named(
                     projected_exprs
@@ -862,18 +996,13 @@ impl SqlToMirConverter {
                         .cloned()
                         .ok_or_else(|| mk_error!(&*expr))?,
                 ),
-                GroupedNodeType::Aggregation(Aggregation::Sum),
-                distinct,
+                sum(distinct),
             ),
             CountStar => internal!("Handled earlier"),
             Count {
                 expr: box Expr::Column(col),
                 distinct,
-            } => mknode(
-                Column::from(col),
-                GroupedNodeType::Aggregation(Aggregation::Count),
-                distinct,
-            ),
+            } => mknode(Column::from(col), count(distinct)),
             Count { ref expr, distinct } => mknode(
                 Column::named(
                     projected_exprs
@@ -881,17 +1010,12 @@ impl SqlToMirConverter {
                         .cloned()
                         .ok_or_else(|| mk_error!(expr))?,
                 ),
-                GroupedNodeType::Aggregation(Aggregation::Count),
-                distinct,
+                count(distinct),
             ),
             Avg {
                 expr: box Expr::Column(col),
                 distinct,
-            } => mknode(
-                Column::from(col),
-                GroupedNodeType::Aggregation(Aggregation::Avg),
-                distinct,
-            ),
+            } => mknode(Column::from(col), avg(distinct)),
             Avg { ref expr, distinct } => mknode(
                 Column::named(
                     projected_exprs
@@ -899,14 +1023,11 @@ impl SqlToMirConverter {
                         .cloned()
                         .ok_or_else(|| mk_error!(expr))?,
                 ),
-                GroupedNodeType::Aggregation(Aggregation::Avg),
-                distinct,
-            ),
-            Max(box Expr::Column(col)) => mknode(
-                Column::from(col),
-                GroupedNodeType::Extremum(Extremum::Max),
-                false,
+                avg(distinct),
             ),
+            Max(box Expr::Column(col)) => {
+                mknode(Column::from(col), GroupedNodeType::Extremum(Extremum::Max))
+            }
             Max(ref expr) => mknode(
                 Column::named(
                     projected_exprs
@@ -915,13 +1036,10 @@ impl SqlToMirConverter {
                         .ok_or_else(|| mk_error!(expr))?,
                 ),
                 GroupedNodeType::Extremum(Extremum::Max),
-                false,
-            ),
-            Min(box Expr::Column(col)) => mknode(
-                Column::from(col),
-                GroupedNodeType::Extremum(Extremum::Min),
-                false,
             ),
+            Min(box Expr::Column(col)) => {
+                mknode(Column::from(col), GroupedNodeType::Extremum(Extremum::Min))
+            }
             Min(ref expr) => mknode(
                 Column::named(
                     projected_exprs
@@ -930,7 +1048,6 @@ impl SqlToMirConverter {
                         .ok_or_else(|| mk_error!(expr))?,
                 ),
                 GroupedNodeType::Extremum(Extremum::Min),
-                false,
             ),
             GroupConcat {
                 expr: box Expr::Column(col),
@@ -940,7 +1057,54 @@ impl SqlToMirConverter {
                 GroupedNodeType::Aggregation(Aggregation::GroupConcat {
                     separator: separator.unwrap_or_else(|| ",".to_owned()),
                 }),
-                false,
+            ),
+            Stddev { ref expr, sample } => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(if sample {
+                    Aggregation::StddevSamp
+                } else {
+                    Aggregation::StddevPop
+                }),
+            ),
+            Variance { ref expr, sample } => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(if sample {
+                    Aggregation::VarSamp
+                } else {
+                    Aggregation::VarPop
+                }),
+            ),
+            BoolAnd(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::BoolAnd),
+            ),
+            BoolOr(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::BoolOr),
+            ),
+            BitAnd(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::BitAnd),
+            ),
+            BitOr(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::BitOr),
+            ),
+            ArrayAgg(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::ArrayAgg),
+            ),
+            JsonAgg { ref expr, jsonb } => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::JsonAgg { jsonb }),
+            ),
+            ApproxCountDistinct(ref expr) => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct),
+            ),
+            ApproxPercentile { ref expr, .. } => mknode(
+                over_column(expr)?,
+                GroupedNodeType::Aggregation(Aggregation::ApproxPercentile),
             ),
             _ => {
                 internal!("not an aggregate: {:?}", Sensitive(&function));
@@ -997,11 +1161,19 @@ impl SqlToMirConverter {
         // actually needs; at a minimum, we could start with just the join columns, relying on the
         // automatic column pull-down to retrieve the remaining columns required.
         let projected_cols_left = self.mir_graph.columns(left_node);
-        let projected_cols_right = self.mir_graph.columns(right_node);
-        let mut project = projected_cols_left
-            .into_iter()
-            .chain(projected_cols_right)
-            .collect::<Vec<Column>>();
+        let mut project = if matches!(
+            kind,
+            JoinKind::Semi | JoinKind::Anti | JoinKind::DependentSemi | JoinKind::DependentAnti
+        ) {
+            // Semi- and anti-joins only ever project columns from the left
+            projected_cols_left
+        } else {
+            let projected_cols_right = self.mir_graph.columns(right_node);
+            projected_cols_left
+                .into_iter()
+                .chain(projected_cols_right)
+                .collect::<Vec<Column>>()
+        };
 
         // join columns need us to generate join group configs for the operator
         let mut on = Vec::new();
@@ -1044,8 +1216,13 @@ impl SqlToMirConverter {
         let inner = match kind {
             JoinKind::Inner => MirNodeInner::Join { on, project },
             JoinKind::Left => MirNodeInner::LeftJoin { on, project },
+            JoinKind::Full => MirNodeInner::FullJoin { on, project },
+            JoinKind::Semi => MirNodeInner::SemiJoin { on, project },
+            JoinKind::Anti => MirNodeInner::AntiJoin { on, project },
             JoinKind::DependentInner => MirNodeInner::DependentJoin { on, project },
             JoinKind::DependentLeft => MirNodeInner::DependentLeftJoin { on, project },
+            JoinKind::DependentSemi => MirNodeInner::DependentSemiJoin { on, project },
+            JoinKind::DependentAnti => MirNodeInner::DependentAntiJoin { on, project },
         };
         trace!(?inner, "Added join node");
         Ok(self.add_query_node(
@@ -1281,7 +1458,7 @@ impl SqlToMirConverter {
         name: SqlIdentifier,
         mut parent: NodeIndex,
         group_by: Vec<Column>,
-        order: &Option<Vec<(Expr, OrderType)>>,
+        order: &Option<Vec<(Expr, OrderType, NullOrder)>>,
         limit: usize,
         is_topk: bool,
     ) -> ReadySetResult<Vec<NodeIndex>> {
@@ -1295,7 +1472,7 @@ impl SqlToMirConverter {
         let mut exprs_to_project = vec![];
         let order = order.as_ref().map(|oc| {
             oc.iter()
-                .map(|(expr, ot)| {
+                .map(|(expr, ot, no)| {
                     (
                         match expr {
                             Expr::Column(col) => Column::from(col),
@@ -1320,6 +1497,7 @@ impl SqlToMirConverter {
                             }
                         },
                         *ot,
+                        *no,
                     )
                 })
                 .collect()
@@ -1377,6 +1555,155 @@ impl SqlToMirConverter {
         Ok(nodes)
     }
 
+    /// Make a chain of [`Window`] nodes below `parent`, one for each of the window functions in
+    /// the given query graph. The columns of `view_key`, which the query's view is keyed on, are
+    /// added to the partition of each window so that lookups into the window can be satisfied
+    /// from a single partition.
+    ///
+    /// This is only correct if every lookup is for a single value of each key column, so range
+    /// keys are rejected (as are parameterized IN lists, by the adapter).
+    ///
+    /// [`Window`]: MirNodeInner::Window
+    fn make_window_nodes(
+        &mut self,
+        query_name: &Relation,
+        query_graph: &QueryGraph,
+        mut parent: NodeIndex,
+        view_key: &ViewKey,
+    ) -> ReadySetResult<Vec<NodeIndex>> {
+        if !self.config.allow_window_functions {
+            unsupported!("Window functions are not supported");
+        }
+        if view_key.index_type != IndexType::HashMap {
+            unsupported!("Window functions in queries with range parameters are not supported");
+        }
+        let key_columns = view_key
+            .columns
+            .iter()
+            .filter(|(col, _)| col.name != *PAGE_NUMBER_COL)
+            .map(|(col, _)| col.clone())
+            .collect::<Vec<_>>();
+
+        let window_column = |expr: &Expr| -> ReadySetResult<Column> {
+            match expr {
+                Expr::Column(col) => Ok(Column::from(col)),
+                _ => unsupported!(
+                    "Only columns are supported as arguments to window functions, got: {}",
+                    // FIXME(REA-2168): Use correct dialect.
+                    expr.display(nom_sql::Dialect::MySQL)
+                ),
+            }
+        };
+
+        // Sort the window functions to ensure a deterministic node order
+        let mut window_functions = query_graph.window_functions.iter().collect::<Vec<_>>();
+        window_functions.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        let mut nodes = Vec::with_capacity(window_functions.len());
+        for (function, alias) in window_functions {
+            let FunctionExpr::Over { function, window } = function else {
+                internal!("Window function must be an OVER expression")
+            };
+
+            let (kind, on) = match function.as_ref() {
+                FunctionExpr::RowNumber => (WindowFunction::RowNumber, None),
+                FunctionExpr::Rank => (WindowFunction::Rank, None),
+                FunctionExpr::DenseRank => (WindowFunction::DenseRank, None),
+                FunctionExpr::Lag { expr, offset } => (
+                    WindowFunction::Lag {
+                        offset: offset.unwrap_or(1) as usize,
+                    },
+                    Some(window_column(expr)?),
+                ),
+                FunctionExpr::Lead { expr, offset } => (
+                    WindowFunction::Lead {
+                        offset: offset.unwrap_or(1) as usize,
+                    },
+                    Some(window_column(expr)?),
+                ),
+                FunctionExpr::Sum {
+                    expr,
+                    distinct: false,
+                } => (WindowFunction::Sum, Some(window_column(expr)?)),
+                FunctionExpr::Count {
+                    expr,
+                    distinct: false,
+                } => (WindowFunction::Count, Some(window_column(expr)?)),
+                FunctionExpr::CountStar => (WindowFunction::Count, None),
+                f => unsupported!(
+                    "Unsupported window function: {}",
+                    // FIXME(REA-2168): Use correct dialect.
+                    f.display(nom_sql::Dialect::MySQL)
+                ),
+            };
+
+            let mut group_by = window
+                .partition_by
+                .iter()
+                .map(window_column)
+                .collect::<ReadySetResult<Vec<_>>>()?;
+            for col in &key_columns {
+                if !group_by.contains(col) {
+                    group_by.push(col.clone());
+                }
+            }
+
+            let order = window
+                .order
+                .as_ref()
+                .map(|order| {
+                    order
+                        .order_by
+                        .iter()
+                        .map(
+                            |OrderBy {
+                                 field,
+                                 order_type,
+                                 null_order,
+                             }| {
+                                let order_type = order_type.unwrap_or(OrderType::OrderAscending);
+                                let null_order = resolve_null_order(order_type, *null_order);
+                                match field {
+                                    FieldReference::Expr(expr) => {
+                                        Ok((window_column(expr)?, order_type, null_order))
+                                    }
+                                    FieldReference::Numeric(_) => unsupported!(
+                                        "Numeric field references are not supported in window \
+                                         ORDER BY"
+                                    ),
+                                }
+                            },
+                        )
+                        .collect::<ReadySetResult<Vec<_>>>()
+                })
+                .transpose()?;
+
+            let node = self.add_query_node(
+                query_name.clone(),
+                MirNode::new(
+                    format!(
+                        "q_{:x}_n{}",
+                        query_graph.signature().hash,
+                        self.mir_graph.node_count()
+                    )
+                    .into(),
+                    MirNodeInner::Window {
+                        order,
+                        group_by,
+                        on,
+                        output_column: Column::named(alias.clone()),
+                        kind,
+                    },
+                ),
+                &[parent],
+            );
+            nodes.push(node);
+            parent = node;
+        }
+
+        Ok(nodes)
+    }
+
     fn make_predicate_nodes(
         &mut self,
         query_name: &Relation,
@@ -1460,92 +1787,131 @@ impl SqlToMirConverter {
                     LeafBehavior::Anonymous,
                 )?;
 
-                // -> π[lit: 0, lit: 0]
-                let group_proj = self.make_project_node(
-                    query_name,
-                    format!("{}_prj_hlpr", name.display_unquoted()).into(),
-                    subquery_leaf,
-                    vec![
-                        ProjectExpr::Expr {
-                            alias: "__count_val".into(),
-                            expr: Expr::Literal(0u32.into()),
-                        },
-                        ProjectExpr::Expr {
-                            alias: "__count_grp".into(),
-                            expr: Expr::Literal(0u32.into()),
-                        },
-                    ],
-                );
-                // -> [0, 0] for each row
-
-                // -> |0| γ[1]
-                let exists_count_col = Column::named("__exists_count");
-                let exists_count_node = self.make_grouped_node(
-                    query_name,
-                    format!("{}_count", name.display_unquoted()).into(),
-                    exists_count_col,
-                    (group_proj, Column::named("__count_val")),
-                    vec![Column::named("__count_grp")],
-                    GroupedNodeType::Aggregation(Aggregation::Count),
-                );
-                // -> [0, <count>] for each row
-
-                // -> σ[c1 > 0]
-                let gt_0_filter = self.make_filter_node(
-                    query_name,
-                    format!("{}_count_gt_0", name.display_unquoted()).into(),
-                    exists_count_node,
-                    Expr::BinaryOp {
-                        lhs: Box::new(Expr::Column("__exists_count".into())),
-                        op: BinaryOperator::Greater,
-                        rhs: Box::new(Expr::Literal(Literal::Integer(0))),
-                    },
-                );
-
-                // left -> π[...left, lit: 0]
-                let parent_columns = self.mir_graph.columns(parent);
-                let left_literal_join_key_proj = self.make_project_node(
-                    query_name,
-                    format!("{}_join_key", name.display_unquoted()).into(),
-                    parent,
-                    parent_columns
-                        .into_iter()
-                        .map(ProjectExpr::Column)
-                        .chain(iter::once(ProjectExpr::Expr {
-                            alias: "__exists_join_key".into(),
-                            expr: Expr::Literal(0u32.into()),
-                        }))
-                        .collect(),
-                );
-
-                let join_preds = [JoinPredicate {
-                    left: "__exists_join_key".into(),
-                    right: "__count_grp".into(),
-                }];
-
-                if negated {
-                    self.make_antijoin(
+                if query_graph.parameters().is_empty() && is_correlated_by_equalities_only(subquery)
+                {
+                    //     σ[EXISTS (R₂)](R₁)
+                    //
+                    // is compiled like
+                    //
+                    //     R₁ ⋈∃ R₂
+                    //
+                    // and
+                    //
+                    //     σ[NOT EXISTS (R₂)](R₁)
+                    //
+                    // is compiled like
+                    //
+                    //     R₁ ⋈∄ R₂
+                    //
+                    // If the subquery is correlated, the join starts out dependent, and the
+                    // equality predicates in R₂ that reference columns in R₁ are turned into join
+                    // keys when decorrelating.
+                    let (kind, suffix) = match (negated, is_correlated(subquery)) {
+                        (false, false) => (JoinKind::Semi, "semijoin"),
+                        (false, true) => (JoinKind::DependentSemi, "semijoin"),
+                        (true, false) => (JoinKind::Anti, "antijoin"),
+                        (true, true) => (JoinKind::DependentAnti, "antijoin"),
+                    };
+                    self.make_join_node(
                         query_name,
-                        format!("{}_antijoin", name.display_unquoted()).into(),
-                        &join_preds,
-                        left_literal_join_key_proj,
-                        gt_0_filter,
-                        /* dependent = */ is_correlated(subquery),
+                        format!("{}_{}", name.display_unquoted(), suffix).into(),
+                        &[],
+                        parent,
+                        subquery_leaf,
+                        kind,
                     )?
                 } else {
-                    // -> ⋈ on: l.__exists_join_key ≡ r.__count_grp
-                    self.make_join_node(
+                    // Semi- and anti-joins can't project the columns in the subquery that
+                    // placeholders in the subquery are keyed on, and any correlated predicates
+                    // other than equalities can't be lifted above them when decorrelating, so
+                    // instead join against the count of rows in the subquery
+                    // -> π[lit: 0, lit: 0]
+                    let group_proj = self.make_project_node(
                         query_name,
-                        format!("{}_join", name.display_unquoted()).into(),
-                        &join_preds,
-                        left_literal_join_key_proj,
-                        gt_0_filter,
-                        if is_correlated(subquery) {
-                            JoinKind::DependentInner
-                        } else {
-                            JoinKind::Inner
+                        format!("{}_prj_hlpr", name.display_unquoted()).into(),
+                        subquery_leaf,
+                        vec![
+                            ProjectExpr::Expr {
+                                alias: "__count_val".into(),
+                                expr: Expr::Literal(0u32.into()),
+                            },
+                            ProjectExpr::Expr {
+                                alias: "__count_grp".into(),
+                                expr: Expr::Literal(0u32.into()),
+                            },
+                        ],
+                    );
+                    // -> [0, 0] for each row
+
+                    // -> |0| γ[1]
+                    let exists_count_col = Column::named("__exists_count");
+                    let exists_count_node = self.make_grouped_node(
+                        query_name,
+                        format!("{}_count", name.display_unquoted()).into(),
+                        exists_count_col,
+                        (group_proj, Column::named("__count_val")),
+                        vec![Column::named("__count_grp")],
+                        GroupedNodeType::Aggregation(Aggregation::Count),
+                    );
+                    // -> [0, <count>] for each row
+
+                    // -> σ[c1 > 0]
+                    let gt_0_filter = self.make_filter_node(
+                        query_name,
+                        format!("{}_count_gt_0", name.display_unquoted()).into(),
+                        exists_count_node,
+                        Expr::BinaryOp {
+                            lhs: Box::new(Expr::Column("__exists_count".into())),
+                            op: BinaryOperator::Greater,
+                            rhs: Box::new(Expr::Literal(Literal::Integer(0))),
                         },
-                    )?
+                    );
+
+                    // left -> π[...left, lit: 0]
+                    let parent_columns = self.mir_graph.columns(parent);
+                    let left_literal_join_key_proj = self.make_project_node(
+                        query_name,
+                        format!("{}_join_key", name.display_unquoted()).into(),
+                        parent,
+                        parent_columns
+                            .into_iter()
+                            .map(ProjectExpr::Column)
+                            .chain(iter::once(ProjectExpr::Expr {
+                                alias: "__exists_join_key".into(),
+                                expr: Expr::Literal(0u32.into()),
+                            }))
+                            .collect(),
+                    );
+
+                    let join_preds = [JoinPredicate {
+                        left: "__exists_join_key".into(),
+                        right: "__count_grp".into(),
+                    }];
+
+                    if negated {
+                        self.make_antijoin(
+                            query_name,
+                            format!("{}_antijoin", name.display_unquoted()).into(),
+                            &join_preds,
+                            left_literal_join_key_proj,
+                            gt_0_filter,
+                            /* dependent = */ is_correlated(subquery),
+                        )?
+                    } else {
+                        // -> ⋈ on: l.__exists_join_key ≡ r.__count_grp
+                        self.make_join_node(
+                            query_name,
+                            format!("{}_join", name.display_unquoted()).into(),
+                            &join_preds,
+                            left_literal_join_key_proj,
+                            gt_0_filter,
+                            if is_correlated(subquery) {
+                                JoinKind::DependentInner
+                            } else {
+                                JoinKind::Inner
+                            },
+                        )?
+                    }
                 }
             }
             Expr::Call(_) => {
@@ -1785,6 +2151,13 @@ impl SqlToMirConverter {
         //  Reuse should just require that we add the query name to the "owners" hashset in the
         //  reused nodes if the node properties are identical.
 
+        if query_graph.lookup_time_pagination && !leaf_behavior.should_make_leaf() {
+            unsupported!(
+                "Parametrized LIMIT and OFFSET without LIMIT are only supported in the outermost \
+                 query"
+            );
+        }
+
         // Canonical operator order: B-J-F-G-P-R
         // (Base, Join, Filter, GroupBy, Project, Reader)
         let leaf = {
@@ -1795,6 +2168,27 @@ impl SqlToMirConverter {
             // lookup key if a leaf node is attached.
             let view_key = query_graph.view_key(self.config())?;
 
+            // Approximate aggregates always need to be finalized post-lookup, even when there's
+            // only a single key to look up
+            let needs_post_lookup_aggregates = leaf_behavior.should_make_leaf()
+                && (view_key.index_type != IndexType::HashMap
+                    || has_approximate_aggregates(query_graph));
+
+            // Some aggregates are recomputed post-lookup from other aggregates, which need to be
+            // projected as hidden columns in the leaf
+            let query_graph = if needs_post_lookup_aggregates {
+                with_hidden_aggregates(query_graph)
+            } else {
+                Cow::Borrowed(query_graph)
+            };
+            let query_graph = &*query_graph;
+
+            // If the results of the aggregates are re-aggregated post-lookup, anything that
+            // depends on those results has to be computed post-lookup as well
+            let reaggregates_post_lookup = needs_post_lookup_aggregates
+                && !query_graph.distinct
+                && !query_graph.aggregates.is_empty();
+
             // 0. Base nodes (always reused)
             let mut base_nodes: Vec<NodeIndex> = Vec::new();
             let mut sorted_rels: Vec<&Relation> = query_graph.relations.keys().collect();
@@ -2004,21 +2398,36 @@ impl SqlToMirConverter {
                 &expressions_above_grouped,
             )?;
 
-            // 9. Add predicate nodes for HAVING after GROUP BY nodes
-            for (i, p) in query_graph.having_predicates.iter().enumerate() {
-                let hp_name = format!(
-                    "q_{:x}_h{}_{}",
-                    query_graph.signature().hash,
-                    self.mir_graph.node_count(),
-                    i
-                )
-                .into();
-                let subquery_leaf = self.make_predicate_nodes(query_name, hp_name, prev_node, p)?;
+            // 9. Add predicate nodes for HAVING after GROUP BY nodes, unless they're evaluated
+            // post-lookup
+            if !reaggregates_post_lookup {
+                for (i, p) in query_graph.having_predicates.iter().enumerate() {
+                    let hp_name = format!(
+                        "q_{:x}_h{}_{}",
+                        query_graph.signature().hash,
+                        self.mir_graph.node_count(),
+                        i
+                    )
+                    .into();
+                    let subquery_leaf =
+                        self.make_predicate_nodes(query_name, hp_name, prev_node, p)?;
 
-                prev_node = subquery_leaf;
+                    prev_node = subquery_leaf;
+                }
             }
 
-            // 10. Get the final node
+            // 10. Add window function nodes after GROUP BY and HAVING, since window functions
+            // are evaluated over the grouped and filtered result set
+            if !query_graph.window_functions.is_empty() {
+                let window_nodes =
+                    self.make_window_nodes(query_name, query_graph, prev_node, &view_key)?;
+                func_nodes.extend(window_nodes.iter().copied());
+                if let Some(last) = window_nodes.last() {
+                    prev_node = *last;
+                }
+            }
+
+            // 11. Get the final node
             let mut final_node = prev_node;
 
             if let Some(Pagination {
@@ -2061,7 +2470,9 @@ impl SqlToMirConverter {
                 final_node = *paginate_nodes.last().unwrap();
             }
 
-            // 10. Generate leaf views that expose the query result
+            // 12. Generate leaf views that expose the query result
+
+            check_approximate_aggregates(query_graph, leaf_behavior.should_make_leaf())?;
 
             // We may already have added some of the expression and literal columns
             let (_, mut already_computed): (Vec<_>, Vec<_>) = value_columns_needed_for_predicates(
@@ -2238,16 +2649,27 @@ impl SqlToMirConverter {
                     project_order,
                 );
 
-                let aggregates = if view_key.index_type != IndexType::HashMap {
+                let aggregates = if needs_post_lookup_aggregates {
                     post_lookup_aggregates(query_graph, query_name)?
                 } else {
                     None
                 };
 
-                let order_by = query_graph
-                    .order
-                    .as_ref()
-                    .map(|order| order.iter().map(|(c, ot)| (Column::from(c), *ot)).collect());
+                let (projection, filter) = if reaggregates_post_lookup {
+                    (
+                        post_lookup_projection(query_graph),
+                        post_lookup_filter(query_graph),
+                    )
+                } else {
+                    (None, None)
+                };
+
+                let order_by = query_graph.order.as_ref().map(|order| {
+                    order
+                        .iter()
+                        .map(|(c, ot, no)| (Column::from(c), *ot, *no))
+                        .collect()
+                });
 
                 let limit = query_graph.pagination.as_ref().map(|p| p.limit);
 
@@ -2270,6 +2692,8 @@ impl SqlToMirConverter {
                             returned_cols: Some(returned_cols),
                             default_row: query_graph.default_row.clone(),
                             aggregates,
+                            projection,
+                            filter,
                         },
                     ),
                     &[leaf_project_reorder_node],
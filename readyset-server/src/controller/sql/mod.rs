@@ -5,10 +5,12 @@ use std::vec::Vec;
 use ::mir::visualize::GraphViz;
 use ::mir::DfNodeIndex;
 use ::serde::{Deserialize, Serialize};
+use nom_sql::analysis::ReferredColumns;
 use nom_sql::{
-    CompoundSelectOperator, CompoundSelectStatement, CreateTableBody, DialectDisplay,
-    FieldDefinitionExpr, NonReplicatedRelation, NotReplicatedReason, Relation, SelectSpecification,
-    SelectStatement, SqlIdentifier, SqlType, TableExpr,
+    BinaryOperator, CacheExpiration, CachePriority, CompoundSelectOperator,
+    CompoundSelectStatement, CreateTableBody, DialectDisplay, Expr, FieldDefinitionExpr,
+    JoinConstraint, JoinRightSide, Literal, NonReplicatedRelation, NotReplicatedReason, Relation,
+    SelectSpecification, SelectStatement, SqlIdentifier, SqlType, TableExpr,
 };
 use petgraph::graph::NodeIndex;
 use readyset_client::query::QueryId;
@@ -16,8 +18,8 @@ use readyset_client::recipe::changelist::{AlterTypeChange, Change, PostgresTable
 use readyset_client::recipe::ChangeList;
 use readyset_data::{DfType, Dialect, PgEnumMetadata};
 use readyset_errors::{
-    internal, internal_err, invalid_query_err, invariant, unsupported, ReadySetError,
-    ReadySetResult,
+    internal, internal_err, invalid_query, invalid_query_err, invariant, unsupported,
+    ReadySetError, ReadySetResult,
 };
 use readyset_sql_passes::alias_removal::TableAliasRewrite;
 use readyset_sql_passes::{AliasRemoval, DetectUnsupportedPlaceholders, Rewrite, RewriteContext};
@@ -25,7 +27,10 @@ use readyset_util::redacted::Sensitive;
 use tracing::{debug, error, info, trace, warn};
 use vec1::Vec1;
 
-use self::mir::{LeafBehavior, NodeIndex as MirNodeIndex, SqlToMirConverter};
+use self::mir::{
+    Column, LeafBehavior, NodeIndex as MirNodeIndex, SqlToMirConverter, RECURSIVE_ANCHOR_COL,
+    RECURSIVE_PARENT_COL,
+};
 use self::query_graph::to_query_graph;
 pub(crate) use self::recipe::{ExprId, Recipe, Schema};
 use self::registry::ExprRegistry;
@@ -83,6 +88,21 @@ pub(crate) struct BaseSchema {
     pub pg_meta: Option<PostgresTableMetadata>,
 }
 
+/// Options for a cached query added with [`SqlIncorporator::add_query`]
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CacheOptions {
+    /// If set to `true`, execution of the cache will bypass transaction handling in the adapter
+    pub always: bool,
+    /// If set, keys in the reader for the cache will be evicted once they have been cached for
+    /// longer than the given duration
+    pub expiration: Option<CacheExpiration>,
+    /// If set, keys will be evicted from the reader for the cache whenever its state grows beyond
+    /// this many bytes
+    pub memory_limit: Option<u64>,
+    /// Controls which caches are evicted from first when memory needs to be freed
+    pub priority: Option<CachePriority>,
+}
+
 /// Long-lived struct that holds information about the SQL queries (tables, views, and caches) that
 /// have been incorporated into the dataflow graph.
 ///
@@ -317,6 +337,16 @@ impl SqlIncorporator {
                         reason: NotReplicatedReason::Partitioned,
                     });
                 }
+                Change::AddNonReplicatedRelation(NonReplicatedRelation {
+                    name,
+                    reason: NotReplicatedReason::Partition,
+                }) => {
+                    debug!(name = %name.display_unquoted(), "Adding non-replicated relation with Partition");
+                    self.add_non_replicated_relation(NonReplicatedRelation {
+                        name,
+                        reason: NotReplicatedReason::Partition,
+                    });
+                }
                 Change::AddNonReplicatedRelation(NonReplicatedRelation {
                     name,
                     reason: NotReplicatedReason::TableDropped,
@@ -367,7 +397,18 @@ impl SqlIncorporator {
                     self.add_view(stmt.name, definition, schema_search_path.clone())?;
                 }
                 Change::CreateCache(cc) => {
-                    self.add_query(cc.name, *cc.statement, cc.always, &schema_search_path, mig)?;
+                    self.add_query(
+                        cc.name,
+                        *cc.statement,
+                        CacheOptions {
+                            always: cc.always,
+                            expiration: cc.expiration,
+                            memory_limit: cc.memory_limit,
+                            priority: cc.priority,
+                        },
+                        &schema_search_path,
+                        mig,
+                    )?;
                 }
                 Change::AlterTable(_) => {
                     // The only ALTER TABLE changes that can end up here (currently) are ones that
@@ -605,14 +646,23 @@ impl SqlIncorporator {
     ///
     /// If `name` is provided, will use that as the name for the query to add, otherwise a unique
     /// name will be generated from the query. In either case, returns the name of the added query.
+    ///
+    /// The expiration, memory limit, and priority in `options` are applied to the reader for the
+    /// query (see [`CacheOptions`]).
     pub(crate) fn add_query(
         &mut self,
         name: Option<Relation>,
         mut stmt: SelectStatement,
-        always: bool,
+        options: CacheOptions,
         schema_search_path: &[SqlIdentifier],
         mig: &mut Migration<'_>,
     ) -> ReadySetResult<Relation> {
+        let CacheOptions {
+            always,
+            expiration,
+            memory_limit,
+            priority,
+        } = options;
         let name = name.unwrap_or_else(|| format!("q_{}", self.num_queries).into());
         let query_id = QueryId::from_select(&stmt, schema_search_path);
 
@@ -649,6 +699,12 @@ impl SqlIncorporator {
                 if caches.is_empty() {
                     // Can't reuse anything. Return the error.
                     return Err(err);
+                } else if expiration.is_some() {
+                    // Expirations are configured on the reader for the query, and we don't add a
+                    // reader if we're reusing existing caches
+                    unsupported!(
+                        "Cache expiration is not supported for queries that reuse an existing cache"
+                    );
                 } else {
                     #[allow(clippy::unwrap_used)]
                     // we checked that caches is not empty
@@ -670,10 +726,32 @@ impl SqlIncorporator {
             Err(err) => Err(err),
         }?;
 
+        // If the query is already cached, the new cache is aliased to the existing one and reads
+        // from its reader, so the expiration of the existing cache is the one that applies
+        if let (
+            Some(expiration),
+            Some(RecipeExpr::Cache {
+                name: existing_name,
+                expiration: existing_expiration,
+                ..
+            }),
+        ) = (expiration, self.registry.get_expression(&stmt))
+        {
+            if *existing_expiration != Some(expiration) {
+                unsupported!(
+                    "Query is already cached as {} with a different expiration",
+                    existing_name.display_unquoted()
+                );
+            }
+        }
+
         let aliased = !self.registry.add_query(RecipeExpr::Cache {
             name: name.clone(),
             statement: stmt,
             always,
+            expiration,
+            memory_limit,
+            priority,
             query_id,
         })?;
         self.registry
@@ -686,6 +764,12 @@ impl SqlIncorporator {
         // We don't add a leaf if we're reusing a query
         if let Some(mir_query) = mir_query {
             let leaf = self.mir_to_dataflow(name.clone(), mir_query, mig)?;
+            if let Some(expiration) = expiration {
+                mig.set_reader_expiration(leaf, expiration);
+            }
+            if memory_limit.is_some() || priority.is_some() {
+                mig.set_reader_memory_budget(leaf, memory_limit, priority.unwrap_or_default());
+            }
             self.leaf_addresses.insert(name.clone(), leaf);
         }
 
@@ -966,16 +1050,19 @@ impl SqlIncorporator {
         mig: &mut Migration<'_>,
     ) -> ReadySetResult<MirNodeIndex> {
         let mut subqueries = Vec::with_capacity(query.selects.len());
-        for (_, stmt) in &mut query.selects {
+        for (op, stmt) in &mut query.selects {
             let mut tables = invalidating_tables.is_some().then(Vec::new);
-            subqueries.push(self.select_query_to_mir(
-                query_name.clone(),
-                stmt,
-                search_path,
-                tables.as_mut(),
-                LeafBehavior::Anonymous,
-                mig,
-            )?);
+            subqueries.push((
+                *op,
+                self.select_query_to_mir(
+                    query_name.clone(),
+                    stmt,
+                    search_path,
+                    tables.as_mut(),
+                    LeafBehavior::Anonymous,
+                    mig,
+                )?,
+            ));
             if let Some(ts) = tables {
                 if let Some(its) = invalidating_tables.as_mut() {
                     its.extend(ts);
@@ -986,7 +1073,6 @@ impl SqlIncorporator {
         self.mir_converter.compound_query_to_mir(
             &query_name,
             subqueries,
-            CompoundSelectOperator::Union,
             &query.order,
             &query.limit_clause,
             leaf_behavior,
@@ -1076,6 +1162,14 @@ impl SqlIncorporator {
     ) -> ReadySetResult<MirNodeIndex> {
         // FIXME(REA-2168): Use correct dialect.
         trace!(stmt = %stmt.display(nom_sql::Dialect::MySQL), "Adding select query");
+        // The recursive terms of recursive CTEs refer to the CTE itself, which the rewrite passes
+        // can't resolve - so take them out of the statement before rewriting it, and compile them
+        // separately once we get to the CTE below.
+        let mut recursive_terms = stmt
+            .ctes
+            .iter_mut()
+            .filter_map(|cte| Some((cte.name.clone(), cte.recursive_term.take()?)))
+            .collect::<HashMap<_, _>>();
         *stmt = self.rewrite(stmt.clone(), search_path, mig.dialect, invalidating_tables)?;
 
         self.num_queries += 1;
@@ -1107,18 +1201,29 @@ impl SqlIncorporator {
                     anon_queries.insert(to_view, subquery_leaf);
                 }
                 TableAliasRewrite::Cte {
+                    from,
                     to_view,
                     mut for_statement,
-                    ..
                 } => {
-                    let subquery_leaf = self.select_query_to_mir(
-                        query_name.clone(),
-                        for_statement.as_mut(),
-                        search_path,
-                        None,
-                        LeafBehavior::Anonymous,
-                        mig,
-                    )?;
+                    let subquery_leaf = match recursive_terms.remove(&from) {
+                        Some((operator, recursive_term)) => self.recursive_cte_to_mir(
+                            query_name,
+                            &from,
+                            for_statement.as_mut(),
+                            operator,
+                            *recursive_term,
+                            search_path,
+                            mig,
+                        )?,
+                        None => self.select_query_to_mir(
+                            query_name.clone(),
+                            for_statement.as_mut(),
+                            search_path,
+                            None,
+                            LeafBehavior::Anonymous,
+                            mig,
+                        )?,
+                    };
                     anon_queries.insert(to_view, subquery_leaf);
                 }
                 TableAliasRewrite::Table { .. } => {}
@@ -1138,6 +1243,222 @@ impl SqlIncorporator {
         )
     }
 
+    /// Compile a recursive common table expression named `cte_name`, with the given non-recursive
+    /// (`anchor`) and recursive terms, to MIR, returning the node containing the rows of the CTE.
+    ///
+    /// Currently, only recursive terms of the form:
+    ///
+    /// ```sql
+    /// SELECT <exprs> FROM <table> JOIN <cte> ON <table>.<col> = <cte>.<col> [WHERE <cond>]
+    /// ```
+    ///
+    /// combined with the anchor using `UNION`, where `<exprs>` and `<cond>` only refer to columns
+    /// in `<table>`, are supported. These are compiled to a [`FixedPoint`] node over the union of
+    /// both terms.
+    ///
+    /// [`FixedPoint`]: ::mir::node::MirNodeInner::FixedPoint
+    #[allow(clippy::too_many_arguments)]
+    fn recursive_cte_to_mir(
+        &mut self,
+        query_name: &Relation,
+        cte_name: &SqlIdentifier,
+        anchor: &mut SelectStatement,
+        operator: CompoundSelectOperator,
+        recursive_term: SelectStatement,
+        search_path: &[SqlIdentifier],
+        mig: &mut Migration<'_>,
+    ) -> ReadySetResult<MirNodeIndex> {
+        if operator != CompoundSelectOperator::DistinctUnion {
+            unsupported!(
+                "Only UNION is supported between the terms of a recursive common table expression"
+            );
+        }
+
+        let SelectStatement {
+            ctes,
+            distinct,
+            fields,
+            tables,
+            join,
+            where_clause,
+            group_by,
+            having,
+            order,
+            limit_clause,
+        } = recursive_term;
+        if !ctes.is_empty()
+            || distinct
+            || group_by.is_some()
+            || having.is_some()
+            || order.is_some()
+            || limit_clause.limit().is_some()
+            || limit_clause.offset().is_some()
+        {
+            unsupported!(
+                "The recursive term of a recursive common table expression must be a simple join"
+            );
+        }
+
+        let ([table], [join]) = (tables.as_slice(), join.as_slice()) else {
+            unsupported!(
+                "The recursive term of a recursive common table expression must join exactly one \
+                 table to the CTE"
+            );
+        };
+        let (JoinRightSide::Table(right), true) = (&join.right, join.operator.is_inner_join())
+        else {
+            unsupported!(
+                "The recursive term of a recursive common table expression must inner join one \
+                 table to the CTE"
+            );
+        };
+        let JoinConstraint::On(Expr::BinaryOp {
+            lhs,
+            op: BinaryOperator::Equal,
+            rhs,
+        }) = &join.constraint
+        else {
+            unsupported!(
+                "The recursive term of a recursive common table expression must join on a single \
+                 equality condition"
+            );
+        };
+        let (Expr::Column(lhs), Expr::Column(rhs)) = (lhs.as_ref(), rhs.as_ref()) else {
+            unsupported!(
+                "The recursive term of a recursive common table expression must join on a single \
+                 equality condition"
+            );
+        };
+
+        let is_cte = |table: &TableExpr| {
+            table
+                .inner
+                .as_table()
+                .map_or(false, |t| t.schema.is_none() && t.name == *cte_name)
+        };
+        let name_of = |table: &TableExpr| -> Option<SqlIdentifier> {
+            table
+                .alias
+                .clone()
+                .or_else(|| table.inner.as_table().map(|t| t.name.clone()))
+        };
+        let (step_table, cte_ref) = match (is_cte(table), is_cte(right)) {
+            (false, true) => (table.clone(), name_of(right)),
+            (true, false) => (right.clone(), name_of(table)),
+            _ => unsupported!(
+                "The recursive term of a recursive common table expression must join exactly one \
+                 table to the CTE"
+            ),
+        };
+        let refers_to_cte =
+            |col: &nom_sql::Column| col.table.as_ref().map(|t| &t.name) == cte_ref.as_ref();
+        let (step_col, cte_col) = match (refers_to_cte(lhs), refers_to_cte(rhs)) {
+            (false, true) => (lhs.clone(), rhs.clone()),
+            (true, false) => (rhs.clone(), lhs.clone()),
+            _ => unsupported!(
+                "The join condition in the recursive term of a recursive common table expression \
+                 must compare a column of the CTE to a column of the joined table"
+            ),
+        };
+
+        let mut step_fields = vec![];
+        for field in fields {
+            let FieldDefinitionExpr::Expr { expr, .. } = field else {
+                unsupported!(
+                    "Wildcards in the recursive term of a recursive common table expression are \
+                     not supported"
+                );
+            };
+            step_fields.push(expr);
+        }
+        if step_fields
+            .iter()
+            .chain(&where_clause)
+            .flat_map(|expr| expr.referred_columns())
+            .any(refers_to_cte)
+        {
+            unsupported!(
+                "The recursive term of a recursive common table expression may only refer to the \
+                 CTE in its join condition"
+            );
+        }
+
+        anchor.fields.extend([
+            FieldDefinitionExpr::Expr {
+                expr: Expr::Literal(Literal::Null),
+                alias: Some(RECURSIVE_PARENT_COL.clone()),
+            },
+            FieldDefinitionExpr::Expr {
+                expr: Expr::Literal(Literal::Boolean(true)),
+                alias: Some(RECURSIVE_ANCHOR_COL.clone()),
+            },
+        ]);
+        let anchor_leaf = self.select_query_to_mir(
+            query_name.clone(),
+            anchor,
+            search_path,
+            None,
+            LeafBehavior::Anonymous,
+            mig,
+        )?;
+
+        // The columns of a CTE are named by its non-recursive term, so alias all the expressions
+        // in the recursive term to match
+        let anchor_columns = self.mir_converter.columns(anchor_leaf);
+        let Some(key) = anchor_columns
+            .iter()
+            .find(|c| c.name == cte_col.name)
+            .map(|c| Column::named(c.name.clone()))
+        else {
+            invalid_query!(
+                "Column {} not found in recursive common table expression {}",
+                cte_col.name,
+                cte_name
+            );
+        };
+        if anchor_columns.len() != step_fields.len() + 2 {
+            invalid_query!(
+                "The terms of recursive common table expression {} have different numbers of \
+                 columns",
+                cte_name
+            );
+        }
+        let mut step = SelectStatement {
+            fields: step_fields
+                .into_iter()
+                .zip(&anchor_columns)
+                .map(|(expr, col)| FieldDefinitionExpr::Expr {
+                    expr,
+                    alias: Some(col.name.clone()),
+                })
+                .chain([
+                    FieldDefinitionExpr::Expr {
+                        expr: Expr::Column(step_col),
+                        alias: Some(RECURSIVE_PARENT_COL.clone()),
+                    },
+                    FieldDefinitionExpr::Expr {
+                        expr: Expr::Literal(Literal::Boolean(false)),
+                        alias: Some(RECURSIVE_ANCHOR_COL.clone()),
+                    },
+                ])
+                .collect(),
+            tables: vec![step_table],
+            where_clause,
+            ..Default::default()
+        };
+        let step_leaf = self.select_query_to_mir(
+            query_name.clone(),
+            &mut step,
+            search_path,
+            None,
+            LeafBehavior::Anonymous,
+            mig,
+        )?;
+
+        self.mir_converter
+            .make_fixed_point_nodes(query_name, anchor_leaf, step_leaf, key)
+    }
+
     /// Compile the given uncompiled view all the way to dataflow
     fn compile_uncompiled_view(
         &mut self,
@@ -12,8 +12,8 @@ use nom_sql::analysis::ReferredColumns;
 use nom_sql::{
     BinaryOperator, Column, DialectDisplay, Expr, FieldDefinitionExpr, FieldReference,
     FunctionExpr, InValue, ItemPlaceholder, JoinConstraint, JoinOperator, JoinRightSide,
-    LimitClause, Literal, OrderBy, OrderType, Relation, SelectStatement, SqlIdentifier, TableExpr,
-    TableExprInner,
+    LimitClause, Literal, NullOrder, OrderBy, OrderType, Relation, SelectStatement, SqlIdentifier,
+    TableExpr, TableExprInner,
 };
 use readyset_client::{PlaceholderIdx, ViewPlaceholder};
 use readyset_errors::{
@@ -230,11 +230,14 @@ pub enum QueryGraphEdge {
         /// Parameters mentioned in the ON clause of the join
         params: Vec<Parameter>,
     },
+    FullJoin {
+        on: Vec<JoinPredicate>,
+    },
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pagination {
-    pub order: Option<Vec<(Expr, OrderType)>>,
+    pub order: Option<Vec<(Expr, OrderType, NullOrder)>>,
     pub limit: usize,
     pub offset: Option<ViewPlaceholder>,
 }
@@ -268,6 +271,12 @@ pub struct QueryGraph {
     /// If a single aggregate is projected as multiple aliases, only one will appear in this map,
     /// but both will appear in `self.columns` as [`OutputColumn::Data`] referencing that alias
     pub aggregates: HashMap<FunctionExpr, SqlIdentifier>,
+    /// Window functions in the query (each an [`FunctionExpr::Over`]), represented as a map from
+    /// the window function to the alias for its result
+    ///
+    /// Like aggregates, window functions are projected in `self.columns` as
+    /// [`OutputColumn::Data`] referencing that alias
+    pub window_functions: HashMap<FunctionExpr, SqlIdentifier>,
     /// Set of expressions that appear in the GROUP BY clause
     pub group_by: HashSet<Expr>,
     /// Final set of projected columns in this query; may include literals in addition to the
@@ -286,10 +295,19 @@ pub struct QueryGraph {
     pub global_predicates: Vec<Expr>,
     /// HAVING predicates (like global predicates, but applied after aggregate functions)
     pub having_predicates: Vec<Expr>,
-    /// The list of columns and directionsj that the query is ordering by, if any
-    pub order: Option<Vec<(Column, OrderType)>>,
+    /// The list of columns, directions, and null orders that the query is ordering by, if any
+    pub order: Option<Vec<(Column, OrderType, NullOrder)>>,
     /// The pagination (order, limit, offset) for the query, if any
     pub pagination: Option<Pagination>,
+    /// True if the LIMIT and OFFSET of the query are applied to the results of each lookup using
+    /// the values of parameters bound when the query is executed, rather than in the dataflow
+    /// graph. This is the case for parametrized LIMITs, OFFSETs without a LIMIT, and any OFFSET in
+    /// a query with range parameters.
+    ///
+    /// Unless `pagination` is also set (which is only the case for a range query whose LIMIT and
+    /// OFFSET are both literals), nothing bounds the number of rows kept for each key, so the
+    /// reader materializes the full result set of every key that is looked up
+    pub lookup_time_pagination: bool,
     /// True if the query is correlated (is a subquery that refers to columns in an outer query)
     pub is_correlated: bool,
 }
@@ -422,7 +440,7 @@ impl QueryGraph {
 
             if let Some(offset) = offset {
                 if index_type == Some(IndexType::BTreeMap) {
-                    unsupported!("ReadySet does not support Pagination and range queries")
+                    internal!("Range queries with an OFFSET should be paginated at lookup time")
                 } else {
                     columns.push((mir::Column::named(PAGE_NUMBER_COL.clone()), offset));
                 }
@@ -461,6 +479,10 @@ impl Hash for QueryGraph {
         aggregates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
         aggregates.hash(state);
 
+        let mut window_functions = self.window_functions.iter().collect::<Vec<_>>();
+        window_functions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        window_functions.hash(state);
+
         // these fields are Vecs, so already ordered
         self.columns.hash(state);
         self.fields.hash(state);
@@ -470,6 +492,7 @@ impl Hash for QueryGraph {
         self.having_predicates.hash(state);
         self.order.hash(state);
         self.pagination.hash(state);
+        self.lookup_time_pagination.hash(state);
         self.is_correlated.hash(state);
     }
 }
@@ -775,13 +798,31 @@ fn extract_having_aggregates(
     having_predicates
 }
 
+/// Returns the position of nulls for an `ORDER BY` with the given order type and (optional)
+/// explicit null order.
+///
+/// The null order is made explicit for the dialect of the query by the `normalize_null_order`
+/// rewrite pass, so the fallback here only applies to queries which bypass rewriting.
+pub(crate) fn resolve_null_order(
+    order_type: OrderType,
+    null_order: Option<NullOrder>,
+) -> NullOrder {
+    // FIXME(REA-2168): Use correct dialect.
+    null_order
+        .unwrap_or_else(|| readyset_data::Dialect::DEFAULT_MYSQL.default_null_order(order_type))
+}
+
 /// Convert limit and offset fields to an optional constant numeric limit and optional placeholder
-/// for the offset
+/// for the offset.
+///
+/// Returns `None` if the query has no LIMIT, or if the LIMIT and OFFSET are instead applied to the
+/// results of each lookup using the values of the parameters bound when the query is executed (see
+/// [`LimitClause::requires_lookup_time_pagination`]).
 pub(crate) fn extract_limit_offset(
     limit_clause: &LimitClause,
 ) -> ReadySetResult<Option<(usize, Option<ViewPlaceholder>)>> {
-    if limit_clause.limit().is_none() && limit_clause.offset().is_some() {
-        unsupported!("ReadySet does not support OFFSET without LIMIT");
+    if limit_clause.requires_lookup_time_pagination() {
+        return Ok(None);
     }
 
     let limit = if let Some(limit) = limit_clause.limit() {
@@ -794,9 +835,6 @@ pub(crate) fn extract_limit_offset(
         Literal::UnsignedInteger(val) => *val,
         Literal::Integer(val) => u64::try_from(*val)
             .map_err(|_| unsupported_err!("LIMIT field cannot have a negative value"))?,
-        Literal::Placeholder(_) => {
-            unsupported!("ReadySet does not support parametrized LIMIT fields")
-        }
         _ => unsupported!("Invalid LIMIT statement"),
     };
 
@@ -821,6 +859,36 @@ pub(crate) fn extract_limit_offset(
     Ok(Some((limit as _, offset)))
 }
 
+/// Returns the number of rows each key of a range query with an OFFSET needs to keep in the
+/// dataflow graph, if it can be known before the query is executed.
+///
+/// No more than `LIMIT + OFFSET` rows for any one key can make it into the merged results of a
+/// lookup, so if both are literals the rows for each key can be bounded with a TopK node. If either
+/// is parametrized this returns `None`, and every row for each key is kept in the reader.
+fn range_topk_limit(limit_clause: &LimitClause) -> ReadySetResult<Option<usize>> {
+    if limit_clause.requires_lookup_time_pagination() {
+        return Ok(None);
+    }
+
+    fn literal_value(lit: &Literal) -> ReadySetResult<Option<usize>> {
+        match lit {
+            Literal::UnsignedInteger(val) => Ok(Some(*val as _)),
+            Literal::Integer(val) => usize::try_from(*val)
+                .map(Some)
+                .map_err(|_| unsupported_err!("LIMIT and OFFSET cannot have negative values")),
+            _ => Ok(None),
+        }
+    }
+
+    let (Some(limit), Some(offset)) = (limit_clause.limit(), limit_clause.offset()) else {
+        return Ok(None);
+    };
+
+    Ok(literal_value(limit)?
+        .zip(literal_value(offset)?)
+        .map(|(limit, offset)| limit.saturating_add(offset)))
+}
+
 fn table_expr_name(table_expr: &TableExpr) -> ReadySetResult<Relation> {
     match &table_expr.inner {
         TableExprInner::Table(t) => Ok(t.clone()),
@@ -833,6 +901,13 @@ fn table_expr_name(table_expr: &TableExpr) -> ReadySetResult<Relation> {
     }
 }
 
+/// Returns true if the given expression is, or contains, a window function
+fn contains_window_function(expr: &Expr) -> bool {
+    iter::once(expr)
+        .chain(expr.recursive_subexpressions())
+        .any(|e| matches!(e, Expr::Call(FunctionExpr::Over { .. })))
+}
+
 fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
     // If this is an aggregated query AND it does not contain a GROUP BY clause,
     // set default values based on the aggregation (or lack thereof) on each
@@ -855,7 +930,24 @@ fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
                     FunctionExpr::Max(..) => DfValue::None,
                     FunctionExpr::Min(..) => DfValue::None,
                     FunctionExpr::GroupConcat { .. } => DfValue::None,
-                    FunctionExpr::Call { .. } | FunctionExpr::Substring { .. } => DfValue::None,
+                    FunctionExpr::Stddev { .. }
+                    | FunctionExpr::Variance { .. }
+                    | FunctionExpr::BoolAnd(_)
+                    | FunctionExpr::BoolOr(_)
+                    | FunctionExpr::BitAnd(_)
+                    | FunctionExpr::BitOr(_)
+                    | FunctionExpr::ArrayAgg(_)
+                    | FunctionExpr::JsonAgg { .. }
+                    | FunctionExpr::ApproxPercentile { .. } => DfValue::None,
+                    FunctionExpr::ApproxCountDistinct(_) => DfValue::Int(0),
+                    FunctionExpr::Call { .. }
+                    | FunctionExpr::Substring { .. }
+                    | FunctionExpr::RowNumber
+                    | FunctionExpr::Rank
+                    | FunctionExpr::DenseRank
+                    | FunctionExpr::Lag { .. }
+                    | FunctionExpr::Lead { .. }
+                    | FunctionExpr::Over { .. } => DfValue::None,
                 },
                 _ => DfValue::None,
             })
@@ -1132,6 +1224,18 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     }
                     QueryGraphEdge::Join { on }
                 }
+                JoinOperator::FullJoin | JoinOperator::FullOuterJoin => {
+                    if !extra_preds.is_empty() {
+                        unsupported!(
+                            "Only equality predicates between columns are supported in the ON \
+                             clause of FULL JOIN"
+                        );
+                    }
+                    if on.is_empty() {
+                        unsupported!("FULL JOIN without a join condition is not supported");
+                    }
+                    QueryGraphEdge::FullJoin { on }
+                }
                 _ => unsupported!("join operator not supported"),
             });
         }
@@ -1196,6 +1300,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         vec![]
     };
 
+    let mut window_functions = HashMap::new();
     let mut columns = Vec::with_capacity(stmt.fields.len());
     for field in stmt.fields.iter() {
         match field {
@@ -1219,6 +1324,21 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                             column: c.clone(),
                         });
                     }
+                    Expr::Call(function @ FunctionExpr::Over { .. }) => {
+                        let window_name = window_functions
+                            .entry(function.clone())
+                            .or_insert_with(|| name.clone())
+                            .clone();
+                        // Like aggregates, the results of window functions will have already been
+                        // projected by the time we project the result set columns
+                        columns.push(OutputColumn::Data {
+                            alias: alias.clone().unwrap_or(name),
+                            column: Column {
+                                name: window_name,
+                                table: None,
+                            },
+                        })
+                    }
                     Expr::Call(function) if is_aggregate(function) => {
                         let agg_name = aggregates
                             .entry(function.clone())
@@ -1240,6 +1360,13 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                         })
                     }
                     _ => {
+                        if contains_window_function(expr) {
+                            unsupported!(
+                                "Window functions are only supported at the top level of the \
+                                 SELECT list"
+                            );
+                        }
+
                         let mut expr = expr.clone();
                         let aggs = map_aggregates(&mut expr);
                         aggregates.extend(aggs);
@@ -1271,6 +1398,12 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
     };
 
     if let Some(ref order) = stmt.order {
+        if order.order_by.iter().any(|OrderBy { field, .. }| {
+            matches!(field, FieldReference::Expr(expr) if contains_window_function(expr))
+        }) {
+            unsupported!("Window functions in ORDER BY are not supported");
+        }
+
         // For each column in the `ORDER BY` clause, check if it needs to be projected
         order
             .order_by
@@ -1335,11 +1468,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                          null_order,
                      }| {
                         let order_type = order_type.unwrap_or(OrderType::OrderAscending);
-                        if let Some(null_order) = null_order {
-                            if !null_order.is_default_for(order_type) {
-                                unsupported!("Non-default NULLS FIRST/LAST is not yet supported");
-                            }
-                        }
+                        let null_order = resolve_null_order(order_type, null_order);
 
                         Ok((
                             match field {
@@ -1354,6 +1483,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                                 }
                             },
                             order_type,
+                            null_order,
                         ))
                     },
                 )
@@ -1361,8 +1491,26 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         })
         .transpose()?;
 
+    // A range lookup covers many keys, each of which is ordered (and limited) separately in the
+    // dataflow graph, so any OFFSET can only be applied to the merged results of the lookup. Those
+    // keys are still bounded by a TopK of `LIMIT + OFFSET` if both are literals (see
+    // `range_topk_limit`), but a parametrized OFFSET leaves every row for each key in the reader.
+    let has_range_parameters = relations
+        .values()
+        .flat_map(|rel| rel.parameters.iter())
+        .any(|param| IndexType::for_operator(param.op) == Some(IndexType::BTreeMap));
+    let lookup_time_pagination = stmt.limit_clause.requires_lookup_time_pagination()
+        || (has_range_parameters && stmt.limit_clause.offset().is_some());
+
     // Extract pagination parameters
-    let pagination = extract_limit_offset(&stmt.limit_clause)?
+    let limit_offset = if !lookup_time_pagination {
+        extract_limit_offset(&stmt.limit_clause)?
+    } else if has_range_parameters {
+        range_topk_limit(&stmt.limit_clause)?.map(|limit| (limit, None))
+    } else {
+        None
+    };
+    let pagination = limit_offset
         .map(|(limit, offset)| -> ReadySetResult<Pagination> {
             Ok(Pagination {
                 order: stmt
@@ -1380,13 +1528,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                                  }| {
                                     let order_type =
                                         order_type.unwrap_or(OrderType::OrderAscending);
-                                    if let Some(null_order) = null_order {
-                                        if !null_order.is_default_for(order_type) {
-                                            unsupported!(
-                                                "Non-default NULLS FIRST/LAST is not yet supported"
-                                            );
-                                        }
-                                    }
+                                    let null_order = resolve_null_order(order_type, null_order);
                                     Ok((
                                         match field {
                                             FieldReference::Numeric(_) => {
@@ -1397,6 +1539,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                                             FieldReference::Expr(expr) => expr,
                                         },
                                         order_type,
+                                        null_order,
                                     ))
                                 },
                             )
@@ -1430,6 +1573,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         relations,
         edges,
         aggregates,
+        window_functions,
         group_by,
         columns,
         fields: stmt.fields.clone(),
@@ -1438,6 +1582,7 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         global_predicates,
         having_predicates,
         pagination,
+        lookup_time_pagination,
         order,
         is_correlated,
     })
@@ -1480,6 +1625,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn window_functions() {
+        let qg = make_query_graph(
+            "SELECT t1.x, row_number() OVER (PARTITION BY t1.y ORDER BY t1.x) AS rn FROM t1",
+        );
+        assert!(qg.aggregates.is_empty());
+        assert_eq!(qg.window_functions.len(), 1);
+        assert_eq!(
+            qg.window_functions.values().next().unwrap(),
+            &SqlIdentifier::from("rn")
+        );
+        assert!(qg.columns.iter().any(|c| matches!(
+            c,
+            OutputColumn::Data { alias, column } if alias == "rn" && column.name == "rn"
+        )));
+    }
+
+    #[test]
+    fn nested_window_function_unsupported() {
+        let query = parse_select_statement(
+            Dialect::MySQL,
+            "SELECT row_number() OVER (ORDER BY t1.x) + 1 FROM t1",
+        )
+        .unwrap();
+        to_query_graph(query).unwrap_err();
+    }
+
     #[test]
     fn aggregates_with_alias() {
         let qg = make_query_graph("SELECT max(t1.x) AS max_x FROM t1 JOIN t2 ON t1.id = t2.id");
@@ -1720,10 +1892,38 @@ mod tests {
                 assert_eq!(*global_preds, vec![]);
                 assert_eq!(*params, vec![]);
             }
-            QueryGraphEdge::Join { .. } => panic!("Expected left join, got {join:?}"),
+            QueryGraphEdge::Join { .. } | QueryGraphEdge::FullJoin { .. } => {
+                panic!("Expected left join, got {join:?}")
+            }
         }
     }
 
+    #[test]
+    fn full_join() {
+        let qg = make_query_graph("SELECT t1.x, t2.y FROM t1 FULL OUTER JOIN t2 ON t1.z = t2.z");
+
+        let join = qg.edges.get(&("t1".into(), "t2".into())).unwrap();
+        assert_eq!(
+            *join,
+            QueryGraphEdge::FullJoin {
+                on: vec![JoinPredicate {
+                    left: Column::from("t1.z"),
+                    right: Column::from("t2.z")
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn full_join_with_local_pred_unsupported() {
+        let query = parse_select_statement(
+            Dialect::MySQL,
+            "SELECT t1.x FROM t1 FULL JOIN t2 ON t1.z = t2.z AND t2.x = 4",
+        )
+        .unwrap();
+        assert!(to_query_graph(query).unwrap_err().is_unsupported());
+    }
+
     mod view_key {
         use super::*;
 
@@ -1999,5 +2199,54 @@ mod tests {
                 ]
             );
         }
+
+        #[test]
+        fn paginated_range() {
+            let qg = make_query_graph(
+                "SELECT t.x FROM t WHERE t.x > $1 ORDER BY t.x ASC LIMIT 3 OFFSET $2",
+            );
+            assert!(qg.lookup_time_pagination);
+            assert!(qg.pagination.is_none());
+
+            let key = qg
+                .view_key(&mir::Config {
+                    allow_paginate: true,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            assert_eq!(key.index_type, IndexType::BTreeMap);
+            assert_eq!(
+                key.columns,
+                vec![(
+                    mir::Column::new(Some("t"), "x"),
+                    ViewPlaceholder::OneToOne(1, BinaryOperator::Greater)
+                )]
+            );
+        }
+
+        #[test]
+        fn range_literal_offset_topk() {
+            let qg = make_query_graph(
+                "SELECT t.x FROM t WHERE t.x > $1 ORDER BY t.x ASC LIMIT 3 OFFSET 2",
+            );
+            assert!(qg.lookup_time_pagination);
+            let pagination = qg.pagination.as_ref().unwrap();
+            assert_eq!(pagination.limit, 5);
+            assert!(pagination.offset.is_none());
+
+            let key = qg.view_key(&Default::default()).unwrap();
+            assert_eq!(key.index_type, IndexType::BTreeMap);
+        }
+
+        #[test]
+        fn range_topk() {
+            let qg = make_query_graph("SELECT t.x FROM t WHERE t.x > $1 ORDER BY t.x ASC LIMIT 3");
+            assert!(!qg.lookup_time_pagination);
+            assert_eq!(qg.pagination.as_ref().map(|p| p.limit), Some(3));
+
+            let key = qg.view_key(&Default::default()).unwrap();
+            assert_eq!(key.index_type, IndexType::BTreeMap);
+        }
     }
 }
@@ -75,7 +75,7 @@ impl Signature for QueryGraph {
 
         for e in self.edges.values() {
             match e {
-                QueryGraphEdge::Join { on } => {
+                QueryGraphEdge::Join { on } | QueryGraphEdge::FullJoin { on } => {
                     on.iter()
                         .flat_map(|p| vec![&p.left, &p.right])
                         .for_each(&mut record_column);
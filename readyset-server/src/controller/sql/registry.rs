@@ -3,8 +3,8 @@ use std::collections::{HashMap, HashSet};
 
 use nom_sql::analysis::visit::{self, Visitor};
 use nom_sql::{
-    CreateTableBody, CreateTableStatement, CreateViewStatement, ItemPlaceholder, Literal, Relation,
-    SelectSpecification, SelectStatement, SqlType,
+    CacheExpiration, CachePriority, CreateTableBody, CreateTableStatement, CreateViewStatement,
+    ItemPlaceholder, Literal, Relation, SelectSpecification, SelectStatement, SqlType,
 };
 use readyset_client::query::QueryId;
 use readyset_client::recipe::changelist::PostgresTableMetadata;
@@ -38,6 +38,9 @@ pub(crate) enum RecipeExpr {
         name: Relation,
         statement: SelectStatement,
         always: bool,
+        expiration: Option<CacheExpiration>,
+        memory_limit: Option<u64>,
+        priority: Option<CachePriority>,
         query_id: QueryId,
     },
 }
@@ -431,6 +434,15 @@ impl ExprRegistry {
         self.expressions.get(query_id)
     }
 
+    /// Retrieves the [`RecipeExpr`] that the given expression would be aliased to if it were
+    /// added to `self`, if any
+    pub(super) fn get_expression<E>(&self, expression: E) -> Option<&RecipeExpr>
+    where
+        E: Into<ExprId>,
+    {
+        self.expressions.get(&expression.into())
+    }
+
     /// Returns true if the given expression exists in `self`
     pub(super) fn contains<E>(&self, expression: E) -> bool
     where
@@ -684,6 +696,9 @@ mod tests {
         RecipeExpr::Cache {
             name: name.into(),
             always: false,
+            expiration: None,
+            memory_limit: None,
+            priority: None,
             query_id: QueryId::from_select(&statement, &[]),
             statement,
         }
@@ -1108,6 +1123,9 @@ mod tests {
                     query_id: QueryId::from_select(&statement, &[]),
                     statement: statement.clone(),
                     always: false,
+                    expiration: None,
+                    memory_limit: None,
+                    priority: None,
                 })
                 .unwrap());
 
@@ -55,7 +55,7 @@ use readyset_data::{DfValue, Dialect};
 use readyset_errors::{
     internal, internal_err, invariant_eq, NodeType, ReadySetError, ReadySetResult,
 };
-use replication_offset::{ReplicationOffset, ReplicationOffsets};
+use replication_offset::{ReplicationOffset, ReplicationOffsets, SnapshotProgress};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
@@ -118,6 +118,14 @@ pub struct DfState {
     pub(super) recipe: Recipe,
     /// Latest replication position for the schema if from replica or binlog
     schema_replication_offset: Option<ReplicationOffset>,
+    /// The chunks copied so far of tables that are being snapshotted in chunks, so that the
+    /// replicator can resume snapshotting them if it restarts.
+    ///
+    /// This isn't written to the authority, since the rows in those chunks are written to base
+    /// tables without going through the WAL, and so are only guaranteed to survive a restart of
+    /// the server once the table has finished snapshotting.
+    #[serde(skip)]
+    snapshot_progress: HashMap<Relation, SnapshotProgress>,
     /// Placement restrictions for nodes and the domains they are placed into.
     #[serde(with = "serde_with::rust::hashmap_as_tuple_list")]
     pub(super) node_restrictions: HashMap<NodeRestrictionKey, DomainPlacementRestriction>,
@@ -168,6 +176,7 @@ impl DfState {
             materializations,
             recipe,
             schema_replication_offset,
+            snapshot_progress: Default::default(),
             node_restrictions,
             domains: Default::default(),
             domain_nodes: Default::default(),
@@ -285,11 +294,17 @@ impl DfState {
                             name,
                             statement,
                             always,
+                            expiration,
+                            memory_limit,
+                            priority,
                             query_id,
                         } => Some(CacheExpr {
                             name,
                             statement,
                             always,
+                            expiration,
+                            memory_limit,
+                            priority,
                             query_id,
                         }),
                         _ => None,
@@ -794,6 +809,8 @@ impl DfState {
                         .indexes_for(ni)
                         .expect("Node index came from materializations")
                         .clone(),
+                    None,
+                    None,
                 )
             })
             .chain(self.ingredients.node_references().filter_map(|(ni, n)| {
@@ -803,17 +820,23 @@ impl DfState {
                         n.name().clone(),
                         n.description(true),
                         HashSet::from([idx.clone()]),
+                        r.memory_limit(),
+                        Some(r.priority()),
                     )
                 })
             }))
             .map(
-                |(node_index, node_name, node_description, indexes)| MaterializationInfo {
-                    node_index,
-                    node_name,
-                    node_description,
-                    size: sizes.get(&node_index).cloned().unwrap_or_default(),
-                    partial: self.materializations.is_partial(node_index),
-                    indexes,
+                |(node_index, node_name, node_description, indexes, memory_limit, priority)| {
+                    MaterializationInfo {
+                        node_index,
+                        node_name,
+                        node_description,
+                        size: sizes.get(&node_index).cloned().unwrap_or_default(),
+                        partial: self.materializations.is_partial(node_index),
+                        indexes,
+                        memory_limit,
+                        priority,
+                    }
                 },
             )
             .collect())
@@ -1409,6 +1432,25 @@ impl DfState {
         self.schema_replication_offset = offset;
     }
 
+    pub(super) fn snapshot_progress(&self) -> &HashMap<Relation, SnapshotProgress> {
+        &self.snapshot_progress
+    }
+
+    pub(super) fn set_snapshot_progress(
+        &mut self,
+        table: Relation,
+        progress: Option<SnapshotProgress>,
+    ) {
+        match progress {
+            Some(progress) => {
+                self.snapshot_progress.insert(table, progress);
+            }
+            None => {
+                self.snapshot_progress.remove(&table);
+            }
+        }
+    }
+
     pub(super) async fn flush_partial(&mut self) -> ReadySetResult<u64> {
         // get statistics for current domain sizes
         // and evict all state from partial nodes
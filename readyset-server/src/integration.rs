@@ -28,11 +28,12 @@ use dataflow::{
 use futures::{join, StreamExt};
 use itertools::Itertools;
 use nom_sql::{
-    parse_create_table, parse_create_view, parse_query, parse_select_statement, OrderType,
-    Relation, SqlQuery,
+    parse_create_table, parse_create_view, parse_query, parse_select_statement, CachePriority,
+    NullOrder, OrderType, Relation, SqlQuery,
 };
 use readyset_client::consensus::{Authority, LocalAuthority, LocalAuthorityStore};
 use readyset_client::consistency::Timestamp;
+use readyset_client::debug::info::KeyCount;
 use readyset_client::internal::LocalNodeIndex;
 use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache};
 use readyset_client::{KeyComparison, Modification, SchemaType, ViewPlaceholder, ViewQuery};
@@ -49,7 +50,7 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use vec1::vec1;
 
-use crate::controller::sql::SqlIncorporator;
+use crate::controller::sql::{CacheOptions, SqlIncorporator};
 use crate::integration_utils::*;
 use crate::{get_col, Builder};
 
@@ -1540,7 +1541,8 @@ async fn mutator_churn() {
             mig.maintain_anonymous_with_reader_processing(
                 vc,
                 &Index::hash_map(vec![0]),
-                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None).unwrap(),
+                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None, None, None)
+                    .unwrap(),
             );
             vote
         })
@@ -2704,7 +2706,8 @@ async fn cascading_replays_with_sharding() {
             mig.maintain_anonymous_with_reader_processing(
                 end,
                 &Index::hash_map(vec![0]),
-                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None).unwrap(),
+                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None, None, None)
+                    .unwrap(),
             );
             (j, end)
         })
@@ -2833,7 +2836,8 @@ async fn full_aggregation_with_bogokey() {
             mig.maintain_anonymous_with_reader_processing(
                 agg,
                 &Index::hash_map(vec![0]),
-                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None).unwrap(),
+                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None, None, None)
+                    .unwrap(),
             );
             agg
         })
@@ -3435,7 +3439,8 @@ async fn live_writes() {
             mig.maintain_anonymous_with_reader_processing(
                 vc,
                 &Index::hash_map(vec![0]),
-                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None).unwrap(),
+                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None, None, None)
+                    .unwrap(),
             );
             (vote, vc)
         })
@@ -3478,7 +3483,8 @@ async fn live_writes() {
             mig.maintain_anonymous_with_reader_processing(
                 vc2,
                 &Index::hash_map(vec![0]),
-                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None).unwrap(),
+                ReaderProcessing::new(None, None, Some(vec![0, 1]), None, None, None, None)
+                    .unwrap(),
             );
             vc2
         })
@@ -3676,7 +3682,8 @@ async fn finkelstein1982_queries() {
                         .unwrap();
                 }
                 SqlQuery::Select(stmt) => {
-                    inc.add_query(None, stmt, false, &[], mig).unwrap();
+                    inc.add_query(None, stmt, CacheOptions::default(), &[], mig)
+                        .unwrap();
                 }
                 _ => panic!("unexpected query type"),
             }
@@ -5196,7 +5203,9 @@ async fn post_read_ilike() {
                 a,
                 &Index::btree_map(vec![0]),
                 ReaderProcessing::new(
-                    Some(vec![(1, OrderType::OrderAscending)]),
+                    Some(vec![(1, OrderType::OrderAscending, NullOrder::NullsFirst)]),
+                    None,
+                    None,
                     None,
                     None,
                     None,
@@ -5362,6 +5371,79 @@ async fn aggregate_missing_columns() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn cache_ttl_rejected_for_full_materialization() {
+    let mut b = Builder::for_tests();
+    b.disable_partial();
+    b.set_sharding(None);
+    let (mut g, shutdown_tx) = b.start_local().await.unwrap();
+
+    g.extend_recipe(
+        ChangeList::from_str("CREATE TABLE t (id INT);", Dialect::DEFAULT_MYSQL).unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let res = g
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE CACHE q WITH TTL 10s FROM SELECT id FROM t WHERE id = ?",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await;
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("partially materialized"));
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cache_ttl_rejected_for_aliased_cache() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("cache_ttl_rejected_for_aliased_cache").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id INT);
+             CREATE CACHE q1 FROM SELECT id FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let res = g
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE CACHE q2 WITH TTL 10s FROM SELECT id FROM t WHERE id = ?",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await;
+    assert!(res
+        .unwrap_err()
+        .to_string()
+        .contains("different expiration"));
+
+    // Caching the same query again with the same (lack of) expiration still aliases it
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE CACHE q3 FROM SELECT id FROM t WHERE id = ?",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn post_join_filter() {
     let (mut g, shutdown_tx) = start_simple_unsharded("post_join_filter").await;
@@ -7307,6 +7389,166 @@ async fn range_query_through_union() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn range_query_with_avg_and_distinct_aggregates() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) =
+        start_simple_unsharded("range_query_with_avg_and_distinct_aggregates").await;
+
+    // None of these can be re-aggregated post-lookup from their own values, so they're recomputed
+    // from hidden aggregates that aren't part of the query's results
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (a int, b int);
+         CREATE CACHE q FROM
+         SELECT avg(a) AS avg_a, count(distinct a) AS count_a, sum(distinct a) AS sum_a,
+                avg(distinct a) AS avg_distinct_a
+         FROM t WHERE b > ?",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+
+    t.insert_many(vec![
+        vec![DfValue::from(2), DfValue::from(1)],
+        vec![DfValue::from(2), DfValue::from(2)],
+        vec![DfValue::from(4), DfValue::from(2)],
+        vec![DfValue::from(6), DfValue::from(3)],
+        vec![DfValue::from(100), DfValue::from(0)],
+    ])
+    .await
+    .unwrap();
+
+    let rows = q
+        .multi_lookup(
+            vec![KeyComparison::Range((
+                Bound::Excluded(vec1![0.into()]),
+                Bound::Unbounded,
+            ))],
+            true,
+        )
+        .await
+        .unwrap()
+        .into_vec();
+
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row.len(), 4);
+    assert_eq!(get_col!(q, row, "avg_a", Decimal), Decimal::new(35, 1));
+    assert_eq!(get_col!(q, row, "count_a", i64), 3);
+    assert_eq!(get_col!(q, row, "sum_a", i64), 12);
+    assert_eq!(
+        get_col!(q, row, "avg_distinct_a", Decimal),
+        Decimal::from(4)
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn range_query_with_pagination() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("range_query_with_pagination").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE posts (id int, created_at int);
+         CREATE CACHE top FROM
+         SELECT id, created_at FROM posts WHERE created_at > $1 ORDER BY created_at LIMIT 3;
+         CREATE CACHE paginated FROM
+         SELECT id, created_at FROM posts WHERE created_at > $1
+         ORDER BY created_at LIMIT 3 OFFSET $2;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut posts = g.table("posts").await.unwrap();
+    posts
+        .insert_many(
+            (1..=9)
+                .rev()
+                .map(|i| vec![DfValue::from(i), DfValue::from(i * 10)]),
+        )
+        .await
+        .unwrap();
+    // Duplicate values of the range column end up in the same group of the topk
+    posts
+        .insert(vec![DfValue::from(10), DfValue::from(30)])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let range = || {
+        vec![KeyComparison::Range((
+            Bound::Excluded(vec1![DfValue::from(20)]),
+            Bound::Unbounded,
+        ))]
+    };
+
+    let mut top = g.view("top").await.unwrap().into_reader_handle().unwrap();
+    let res = top
+        .multi_lookup(range(), true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| get_col!(top, r, "created_at", i32))
+        .collect::<Vec<_>>();
+    assert_eq!(res, vec![30, 30, 40]);
+
+    let mut paginated = g
+        .view("paginated")
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    for (offset, expected) in [(0, vec![30, 30, 40]), (3, vec![50, 60, 70]), (7, vec![90])] {
+        let res = paginated
+            .raw_lookup(ViewQuery {
+                key_comparisons: range(),
+                block: true,
+                filter: None,
+                timestamp: None,
+                limit: Some(3),
+                offset: Some(offset),
+            })
+            .await
+            .unwrap()
+            .into_vec()
+            .into_iter()
+            .map(|r| get_col!(paginated, r, "created_at", i32))
+            .collect::<Vec<_>>();
+        assert_eq!(res, expected);
+    }
+
+    // Removing a row from the first page of a range pulls the next row into it
+    posts
+        .delete_row(vec![DfValue::from(10), DfValue::from(30)])
+        .await
+        .unwrap();
+
+    sleep().await;
+
+    let res = top
+        .multi_lookup(range(), true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| get_col!(top, r, "created_at", i32))
+        .collect::<Vec<_>>();
+    assert_eq!(res, vec![30, 40, 50]);
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn mixed_inclusive_range_and_equality() {
     readyset_tracing::init_test_logging();
@@ -8836,6 +9078,9 @@ async fn multiple_simultaneous_migrations() {
                         .unwrap()
                 ),
                 always: false,
+                expiration: None,
+                memory_limit: None,
+                priority: None,
             }),
             Dialect::DEFAULT_MYSQL
         )),
@@ -8847,6 +9092,9 @@ async fn multiple_simultaneous_migrations() {
                         .unwrap()
                 ),
                 always: false,
+                expiration: None,
+                memory_limit: None,
+                priority: None,
             }),
             Dialect::DEFAULT_MYSQL
         ))
@@ -9321,6 +9569,118 @@ async fn evict_single() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn per_cache_memory_limit_evicts_from_reader() {
+    readyset_tracing::init_test_logging();
+    // Set a global memory limit that will never be reached, so that only the per-cache memory
+    // limit triggers evictions
+    let (mut g, shutdown_tx) = build(
+        "per_cache_memory_limit_evicts_from_reader",
+        None,
+        Some((usize::MAX, Duration::from_millis(10))),
+    )
+    .await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t1 (x int, y int);
+             CREATE CACHE q WITH (MEMORY LIMIT 1B, PRIORITY LOW)
+                 FROM SELECT x FROM t1 where y = ?;
+             CREATE CACHE r FROM SELECT y FROM t1 where x = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t1").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let mut r = g.view("r").await.unwrap().into_reader_handle().unwrap();
+    t.insert_many((0i32..10).map(|i| vec![i.into(), i.into()]))
+        .await
+        .unwrap();
+    for i in 0i32..10 {
+        let res = q.lookup(&[i.into()], true).await.unwrap().into_vec();
+        assert_eq!(res, vec![vec![DfValue::from(i)]]);
+        let res = r.lookup(&[i.into()], true).await.unwrap().into_vec();
+        assert_eq!(res, vec![vec![DfValue::from(i)]]);
+    }
+
+    eventually!(run_test: {
+        g.materialization_info().await.unwrap()
+    }, then_assert: |materializations| {
+        let readers = materializations
+            .iter()
+            .filter(|mi| mi.priority.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(readers.len(), 2);
+
+        let limited = readers.iter().find(|mi| mi.memory_limit.is_some()).unwrap();
+        assert_eq!(limited.memory_limit, Some(1));
+        assert_eq!(limited.priority, Some(CachePriority::Low));
+        assert_eq!(limited.size.key_count, KeyCount::ExactKeyCount(0));
+
+        let unlimited = readers.iter().find(|mi| mi.memory_limit.is_none()).unwrap();
+        assert_eq!(unlimited.priority, Some(CachePriority::Normal));
+        assert_eq!(unlimited.size.key_count, KeyCount::ExactKeyCount(10));
+    });
+
+    // Keys evicted for exceeding the memory limit are recomputed on the next read
+    let res = q.lookup(&[3.into()], true).await.unwrap().into_vec();
+    assert_eq!(res, vec![vec![DfValue::from(3)]]);
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn per_cache_memory_limit_survives_disabling_memory_checks() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = build(
+        "per_cache_memory_limit_survives_disabling_memory_checks",
+        None,
+        Some((usize::MAX, Duration::from_millis(10))),
+    )
+    .await;
+
+    // Turning off the global memory limit check must not stop the memory limits of individual
+    // caches from being enforced
+    g.set_memory_limit(None, None).await.unwrap();
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t1 (x int, y int);
+             CREATE CACHE q WITH MEMORY LIMIT 1B FROM SELECT x FROM t1 where y = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t1").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    t.insert_many((0i32..10).map(|i| vec![i.into(), i.into()]))
+        .await
+        .unwrap();
+    for i in 0i32..10 {
+        let res = q.lookup(&[i.into()], true).await.unwrap().into_vec();
+        assert_eq!(res, vec![vec![DfValue::from(i)]]);
+    }
+
+    eventually!(run_test: {
+        g.materialization_info().await.unwrap()
+    }, then_assert: |materializations| {
+        let limited = materializations
+            .iter()
+            .find(|mi| mi.memory_limit.is_some())
+            .unwrap();
+        assert_eq!(limited.size.key_count, KeyCount::ExactKeyCount(0));
+    });
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn evict_single_intermediate_state() {
     readyset_tracing::init_test_logging();
@@ -632,6 +632,10 @@ pub struct WorkerOptions {
     #[arg(long, env = "EXPERIMENTAL_POST_LOOKUP_SUPPORT", hide = true)]
     pub enable_experimental_post_lookup: bool,
 
+    /// Enable experimental support for window functions (such as `ROW_NUMBER() OVER (...)`)
+    #[arg(long, env = "EXPERIMENTAL_WINDOW_FUNCTION_SUPPORT", hide = true)]
+    pub enable_experimental_window_functions: bool,
+
     /// Directory in which to store replicated table data. If not specified, defaults to the
     /// current working directory.
     #[arg(long, env = "STORAGE_DIR", conflicts_with = "db_dir")]
@@ -48,6 +48,10 @@ mod replica;
 /// Timeout for requests made from the controller to the server
 const CONTROLLER_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
+/// How often the memory limits of individual caches are enforced if no memory check frequency is
+/// configured for the worker
+const DEFAULT_CACHE_MEMORY_LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Some kind of request for a running ReadySet worker.
 ///
 /// Most of these requests return `()`, apart from `DomainRequest`.
@@ -91,7 +95,9 @@ pub enum WorkerRequestKind {
 
     /// Set the memory limit for this worker
     SetMemoryLimit {
-        /// The period with which eviction check will be performed
+        /// The period with which eviction check will be performed. If `None`, the global memory
+        /// limit is no longer checked. The memory limits of individual caches are enforced
+        /// regardless.
         period: Option<Duration>,
         /// The limit in bytes
         limit: Option<usize>,
@@ -206,6 +212,9 @@ pub struct Worker {
     election_state: Option<WorkerElectionState>,
     /// A timer for doing evictions.
     evict_interval: Option<Interval>,
+    /// A timer for enforcing the memory limits of individual caches. Unlike `evict_interval`,
+    /// this always runs.
+    cache_memory_limit_interval: Interval,
     /// A memory limit for state, in bytes.
     memory_limit: Option<usize>,
     /// Channel through which worker requests are received.
@@ -218,6 +227,9 @@ pub struct Worker {
     domain_external: IpAddr,
     /// A store of the current state size of each domain, used for eviction purposes.
     state_sizes: Arc<Mutex<HashMap<ReplicaAddress, Arc<AtomicUsize>>>>,
+    /// A store of the number of bytes by which the readers in each domain exceed their per-cache
+    /// memory limits, used to enforce those limits independently of `memory_limit`.
+    over_budget_sizes: Arc<Mutex<HashMap<ReplicaAddress, Arc<AtomicUsize>>>>,
     /// Read handles.
     readers: Readers,
     /// Handles to domains currently being run by this worker.
@@ -245,12 +257,16 @@ impl Worker {
             election_state: None,
             // this initial duration doesn't matter; it gets set upon worker registration
             evict_interval: memory_check_frequency.map(|f| tokio::time::interval(f)),
+            cache_memory_limit_interval: tokio::time::interval(
+                memory_check_frequency.unwrap_or(DEFAULT_CACHE_MEMORY_LIMIT_CHECK_INTERVAL),
+            ),
             memory_limit,
             rx: worker_rx,
             coord: Arc::new(Default::default()),
             domain_bind: listen_addr,
             domain_external: external_addr.ip(),
             state_sizes: Default::default(),
+            over_budget_sizes: Default::default(),
             readers,
             domains: Default::default(),
             memory: MemoryTracker::new()?,
@@ -270,6 +286,12 @@ impl Worker {
         ));
     }
 
+    fn process_cache_memory_limits(&mut self) {
+        let coord = self.coord.clone();
+        let over_budget_sizes = Arc::clone(&self.over_budget_sizes);
+        tokio::spawn(async move { evict_over_budget(&coord, &over_budget_sizes).await });
+    }
+
     async fn process_worker_request(&mut self, req: WorkerRequest) {
         let ret = self.handle_worker_request(req.kind).await;
         if let Err(ref e) = ret {
@@ -327,10 +349,12 @@ impl Worker {
                 let (init_state_tx, init_state_rx) = tokio::sync::mpsc::channel(1);
 
                 let state_size = Arc::new(AtomicUsize::new(0));
+                let over_budget_size = Arc::new(AtomicUsize::new(0));
                 let domain = builder.build(
                     self.readers.clone(),
                     self.coord.clone(),
                     state_size.clone(),
+                    over_budget_size.clone(),
                     init_state_tx,
                 );
 
@@ -351,6 +375,10 @@ impl Worker {
                     .lock()
                     .await
                     .insert(replica_addr, state_size);
+                self.over_budget_sizes
+                    .lock()
+                    .await
+                    .insert(replica_addr, over_budget_size);
 
                 let replica = Replica::new(
                     domain,
@@ -500,6 +528,9 @@ impl Worker {
                 _ = eviction => {
                     self.process_eviction();
                 }
+                _ = self.cache_memory_limit_interval.tick() => {
+                    self.process_cache_memory_limits();
+                }
                 Some((result, replica_address)) = self.domain_wait_queue.next() => {
                     if let Err(error) = self.handle_domain_future_completion(
                         replica_address,
@@ -677,6 +708,45 @@ async fn do_eviction(
     }
 }
 
+/// Ask every domain with readers over their per-cache memory limits to evict from those readers
+#[allow(clippy::type_complexity)]
+async fn evict_over_budget(
+    coord: &ChannelCoordinator,
+    over_budget_sizes: &Mutex<HashMap<ReplicaAddress, Arc<AtomicUsize>>>,
+) -> ReadySetResult<()> {
+    let targets = over_budget_sizes
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, size_atom)| size_atom.load(Ordering::Acquire) > 0)
+        .map(|(replica_addr, _)| *replica_addr)
+        .collect::<Vec<_>>();
+
+    for target in targets {
+        debug!(domain = %target, "domain has readers over their memory limit; evicting");
+        counter!(
+            recorded::EVICTION_WORKER_EVICTIONS_REQUESTED,
+            1,
+            "domain" => target.domain_index.index().to_string(),
+        );
+
+        let mut tx = tokio::task::block_in_place(|| {
+            coord.builder_for(&target)?.build_async().map_err(|e| {
+                internal_err!(
+                    "an error occurred while trying to create a domain connection: '{}'",
+                    e
+                )
+            })
+        })?;
+        if let Err(error) = tx.send(Packet::Evict(EvictRequest::OverBudget)).await {
+            // probably exiting?
+            warn!(domain = %target, %error, "failed to evict readers over their memory limit");
+        }
+    }
+
+    Ok(())
+}
+
 impl Drop for Worker {
     /// This is only implemented for the sake of RocksDB that doesn't really
     /// like having its thread being destroyed while it is still open, so
@@ -7,8 +7,8 @@ use std::{iter, mem};
 use itertools::{Either, Itertools};
 use nom_sql::analysis::visit_mut::{self, VisitorMut};
 use nom_sql::{
-    BinaryOperator, DialectDisplay, Expr, InValue, ItemPlaceholder, LimitClause, Literal,
-    SelectStatement,
+    BinaryOperator, DialectDisplay, Expr, FieldDefinitionExpr, FunctionExpr, InValue,
+    ItemPlaceholder, LimitClause, Literal, SelectStatement,
 };
 use readyset_data::{DfType, DfValue};
 use readyset_errors::{
@@ -35,19 +35,16 @@ pub struct ProcessedQueryParams {
 struct AdapterPaginationParams {
     /// The values of `LIMIT` and `OFFSET` in the original query
     limit_clause: LimitClause,
-    force_paginate_in_adapter: bool,
+    /// Whether the values of `LIMIT` and `OFFSET` are passed along with each lookup (and applied
+    /// to its results), rather than being part of the lookup key
+    paginate_at_lookup: bool,
 }
 
 /// This method checks if readyset-server is configured to handle LIMIT/OFFSET queries at the
 /// dataflow level. If not then LIMIT and OFFSET will be stripped and executed in the
 /// post-processing path.
-fn use_fallback_pagination(server_supports_pagination: bool, limit_clause: &LimitClause) -> bool {
-    if server_supports_pagination &&
-        // Can't handle parameterized LIMIT even if support is enabled
-        !matches!(limit_clause.limit(), Some(Literal::Placeholder(_))) &&
-        // Can't handle bare OFFSET
-        !(limit_clause.limit().is_none() && limit_clause.offset().is_some())
-    {
+fn use_fallback_pagination(server_supports_pagination: bool) -> bool {
+    if server_supports_pagination {
         return false;
     }
 
@@ -62,7 +59,9 @@ fn use_fallback_pagination(server_supports_pagination: bool, limit_clause: &Limi
 ///   dataflow representation of the query. Note that this pass may not replace all literals and is
 ///   therefore cannot guarantee that the rewritten query is free of user PII.
 /// - Collapses 'WHERE <expr> IN ?, ... ?' to 'WHERE <expr> = ?'
-/// - Removes `OFFSET ?` if there isn't a `LIMIT`
+/// - Extracts the values of a parametrized `LIMIT` (or of an `OFFSET` without a `LIMIT`, or in a
+///   range query) so they can be applied to the results of each lookup, rather than becoming part
+///   of the lookup key
 pub fn process_query(
     query: &mut SelectStatement,
     server_supports_pagination: bool,
@@ -71,24 +70,38 @@ pub fn process_query(
 
     let limit_clause = mem::take(&mut query.limit_clause);
 
-    let force_paginate_in_adapter =
-        use_fallback_pagination(server_supports_pagination, &query.limit_clause);
+    let force_paginate_in_adapter = use_fallback_pagination(server_supports_pagination);
+    // Range lookups merge the results for many keys, so an OFFSET can't be part of their key.
+    // Unless the LIMIT and OFFSET are both literals (in which case the server still keeps at most
+    // LIMIT + OFFSET rows per key), the reader keeps every row for each key in the range
+    let paginate_at_lookup = force_paginate_in_adapter
+        || limit_clause.requires_lookup_time_pagination()
+        || (limit_clause.offset().is_some() && has_range_placeholders(query));
 
-    if !force_paginate_in_adapter {
+    if !paginate_at_lookup {
         // If adapter pagination shouldn't be used reinstate the limit clause
         query.limit_clause.clone_from(&limit_clause);
     }
 
     let auto_parameters = auto_parametrize_query(query);
     let rewritten_in_conditions = collapse_where_in(query)?;
+
+    if paginate_at_lookup && !force_paginate_in_adapter {
+        // The server paginates the results of each lookup using the values we pass along with it,
+        // so reinstate the limit clause only once the placeholders which make up the lookup key
+        // have been processed
+        query.limit_clause.clone_from(&limit_clause);
+    }
+
     number_placeholders(query)?;
+
     Ok(ProcessedQueryParams {
         reordered_placeholders,
         rewritten_in_conditions,
         auto_parameters,
         pagination_parameters: AdapterPaginationParams {
             limit_clause,
-            force_paginate_in_adapter,
+            paginate_at_lookup,
         },
     })
 }
@@ -139,7 +152,7 @@ impl ProcessedQueryParams {
 
         let AdapterPaginationParams {
             limit_clause,
-            force_paginate_in_adapter,
+            paginate_at_lookup,
         } = &self.pagination_parameters;
 
         let (limit, offset) = match limit_clause {
@@ -156,7 +169,7 @@ impl ProcessedQueryParams {
             }
         };
 
-        if *force_paginate_in_adapter || limit == Some(0) {
+        if *paginate_at_lookup || limit == Some(0) {
             Ok((limit, offset))
         } else {
             Ok((None, None))
@@ -177,12 +190,12 @@ impl ProcessedQueryParams {
 
         let AdapterPaginationParams {
             limit_clause,
-            force_paginate_in_adapter,
+            paginate_at_lookup,
         } = &self.pagination_parameters;
 
-        if *force_paginate_in_adapter {
-            // When fallback pagination is used, remove the parameters for offset and limit from the
-            // list
+        if *paginate_at_lookup {
+            // When pagination happens at lookup time, remove the parameters for offset and limit
+            // from the list
             if matches!(limit_clause.offset(), Some(Literal::Placeholder(_))) {
                 // Skip parameter for offset
                 params = &params[..params.len() - 1];
@@ -336,6 +349,7 @@ fn collapse_where_in(query: &mut SelectStatement) -> ReadySetResult<Vec<Rewritte
     let mut res = vec![];
     let distinct = query.distinct;
     let has_aggregates = query.contains_aggregate_select();
+    let has_window_functions = has_window_functions(query);
 
     if let Some(ref mut w) = query.where_clause {
         let mut visitor = CollapseWhereInVisitor::default();
@@ -353,6 +367,11 @@ fn collapse_where_in(query: &mut SelectStatement) -> ReadySetResult<Vec<Rewritte
             if distinct {
                 unsupported!("DISTINCT with parameterized IN is not supported");
             }
+            // Windows are partitioned by the lookup key, so each key would only see the rows for
+            // that key rather than all the rows matching any of the values in the IN list
+            if has_window_functions {
+                unsupported!("Window functions with parameterized IN are not supported");
+            }
         }
     }
     Ok(res)
@@ -648,13 +667,20 @@ impl<'ast> VisitorMut<'ast> for AutoParametrizeVisitor {
     }
 }
 
-/// Replace all literals that are in positions we support parameters in the given query with
-/// parameters, and return the values for those parameters alongside the index in the parameter list
-/// where they appear as a tuple of (placeholder position, value).
-pub fn auto_parametrize_query(query: &mut SelectStatement) -> Vec<(usize, Literal)> {
-    // Don't try to auto-parametrize equal-queries that already contain range params for now, since
-    // we don't yet allow mixing range and equal parameters in the same query
-    if query.where_clause.iter().any(|expr| {
+/// Returns true if any of the fields projected by the given query contain a window function
+fn has_window_functions(query: &SelectStatement) -> bool {
+    query.fields.iter().any(|field| match field {
+        FieldDefinitionExpr::Expr { expr, .. } => iter::once(expr)
+            .chain(expr.recursive_subexpressions())
+            .any(|subexpr| matches!(subexpr, Expr::Call(FunctionExpr::Over { .. }))),
+        _ => false,
+    })
+}
+
+/// Returns true if the WHERE clause of the given query compares anything to a placeholder using a
+/// range operator (`<`, `>`, `<=`, `>=`, or `BETWEEN`)
+fn has_range_placeholders(query: &SelectStatement) -> bool {
+    query.where_clause.iter().any(|expr| {
         iter::once(expr)
             .chain(expr.recursive_subexpressions())
             .any(|subexpr| {
@@ -676,7 +702,16 @@ pub fn auto_parametrize_query(query: &mut SelectStatement) -> Vec<(usize, Litera
                     }
                 )
             })
-    }) {
+    })
+}
+
+/// Replace all literals that are in positions we support parameters in the given query with
+/// parameters, and return the values for those parameters alongside the index in the parameter list
+/// where they appear as a tuple of (placeholder position, value).
+pub fn auto_parametrize_query(query: &mut SelectStatement) -> Vec<(usize, Literal)> {
+    // Don't try to auto-parametrize equal-queries that already contain range params for now, since
+    // we don't yet allow mixing range and equal parameters in the same query
+    if has_range_placeholders(query) {
         return vec![];
     }
 
@@ -839,6 +874,19 @@ mod tests {
             );
         }
 
+        #[test]
+        fn window_functions_with_placeholder_in() {
+            let mut q = parse_select_statement_mysql(
+                "SELECT id, row_number() OVER (ORDER BY score) FROM x WHERE x.y IN (?, ?)",
+            );
+            collapse_where_in(&mut q).unwrap_err();
+
+            let mut q = parse_select_statement_mysql(
+                "SELECT id, row_number() OVER (ORDER BY score) FROM x WHERE x.y IN (1, 2)",
+            );
+            assert_eq!(collapse_where_in(&mut q).unwrap(), vec![]);
+        }
+
         #[test]
         fn collapsed_where_literals() {
             let mut q = parse_select_statement_mysql("SELECT * FROM x WHERE x.y IN (1, 2, 3)");
@@ -1312,9 +1360,18 @@ mod tests {
             query: &str,
             params: Vec<DfValue>,
             dialect: nom_sql::Dialect,
+        ) -> (Vec<Vec<DfValue>>, SelectStatement) {
+            process_and_make_keys_with_pagination(query, params, dialect, false)
+        }
+
+        fn process_and_make_keys_with_pagination(
+            query: &str,
+            params: Vec<DfValue>,
+            dialect: nom_sql::Dialect,
+            server_supports_pagination: bool,
         ) -> (Vec<Vec<DfValue>>, SelectStatement) {
             let mut query = parse_select_statement(query, dialect);
-            let processed = process_query(&mut query, false).unwrap();
+            let processed = process_query(&mut query, server_supports_pagination).unwrap();
             (
                 processed
                     .make_keys(&params)
@@ -1907,5 +1964,123 @@ mod tests {
                 vec![vec![1.into(), 1.into()], vec![1.into(), 2.into()]]
             );
         }
+
+        #[test]
+        fn parametrized_limit_with_server_pagination() {
+            let (keys, query) = process_and_make_keys_with_pagination(
+                "SELECT * FROM t WHERE x = ? AND y = 1 ORDER BY z LIMIT ?",
+                vec![1.into(), 10.into()],
+                nom_sql::Dialect::MySQL,
+                true,
+            );
+
+            assert_eq!(
+                query,
+                parse_select_statement_mysql(
+                    "SELECT * FROM t WHERE x = $1 AND y = $2 ORDER BY z LIMIT $3"
+                ),
+                "{}",
+                query.display(nom_sql::Dialect::MySQL)
+            );
+            assert_eq!(keys, vec![vec![1.into(), 1.into()]]);
+        }
+
+        #[test]
+        fn parametrized_limit_and_offset_with_server_pagination() {
+            let mut query = parse_select_statement_postgres(
+                "SELECT * FROM t WHERE x = $3 ORDER BY z LIMIT $1 OFFSET 5",
+            );
+            let processed = process_query(&mut query, true).unwrap();
+
+            assert_eq!(
+                query,
+                parse_select_statement_postgres(
+                    "SELECT * FROM t WHERE x = $1 ORDER BY z LIMIT $2 OFFSET 5"
+                ),
+                "{}",
+                query.display(nom_sql::Dialect::PostgreSQL)
+            );
+
+            let params: Vec<DfValue> = vec![10.into(), 2.into(), 1.into()];
+            assert_eq!(
+                processed.make_keys(&params).unwrap(),
+                vec![Cow::<[DfValue]>::Owned(vec![1.into()])]
+            );
+            assert_eq!(
+                processed.limit_offset_params(&params).unwrap(),
+                (Some(10), Some(5))
+            );
+        }
+
+        #[test]
+        fn bare_offset_with_server_pagination() {
+            let mut query =
+                parse_select_statement_postgres("SELECT * FROM t WHERE x = $2 OFFSET $1");
+            let processed = process_query(&mut query, true).unwrap();
+
+            assert_eq!(
+                query,
+                parse_select_statement_postgres("SELECT * FROM t WHERE x = $1 OFFSET $2"),
+                "{}",
+                query.display(nom_sql::Dialect::PostgreSQL)
+            );
+
+            let params: Vec<DfValue> = vec![15.into(), 1.into()];
+            assert_eq!(
+                processed.make_keys(&params).unwrap(),
+                vec![Cow::<[DfValue]>::Owned(vec![1.into()])]
+            );
+            assert_eq!(
+                processed.limit_offset_params(&params).unwrap(),
+                (None, Some(15))
+            );
+        }
+
+        #[test]
+        fn literal_limit_with_server_pagination() {
+            let mut query =
+                parse_select_statement_mysql("SELECT * FROM t WHERE x = ? LIMIT 10 OFFSET ?");
+            let processed = process_query(&mut query, true).unwrap();
+
+            assert_eq!(
+                query,
+                parse_select_statement_mysql("SELECT * FROM t WHERE x = $1 LIMIT 10 OFFSET $2"),
+                "{}",
+                query.display(nom_sql::Dialect::MySQL)
+            );
+            assert_eq!(
+                processed
+                    .limit_offset_params(&[1.into(), 20.into()])
+                    .unwrap(),
+                (None, None)
+            );
+        }
+
+        #[test]
+        fn range_offset_with_server_pagination() {
+            let mut query = parse_select_statement_mysql(
+                "SELECT * FROM t WHERE x > ? ORDER BY x LIMIT 10 OFFSET ?",
+            );
+            let processed = process_query(&mut query, true).unwrap();
+
+            assert_eq!(
+                query,
+                parse_select_statement_mysql(
+                    "SELECT * FROM t WHERE x > $1 ORDER BY x LIMIT 10 OFFSET $2"
+                ),
+                "{}",
+                query.display(nom_sql::Dialect::MySQL)
+            );
+
+            let params: Vec<DfValue> = vec![1.into(), 20.into()];
+            assert_eq!(
+                processed.make_keys(&params).unwrap(),
+                vec![Cow::<[DfValue]>::Owned(vec![1.into()])]
+            );
+            assert_eq!(
+                processed.limit_offset_params(&params).unwrap(),
+                (Some(10), Some(20))
+            );
+        }
     }
 }
@@ -103,7 +103,7 @@ impl<'ast, 'a> VisitorMut<'ast> for RemoveAliasesVisitor<'a> {
                         .collect(),
                 })
                 .chain(select_statement.ctes.drain(..).map(
-                    |CommonTableExpr { name, statement }| TableAliasRewrite::Cte {
+                    |CommonTableExpr { name, statement, .. }| TableAliasRewrite::Cte {
                         to_view: format!("__{}__{}", self.query_name, name).into(),
                         from: name,
                         for_statement: Box::new(statement),
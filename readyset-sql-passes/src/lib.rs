@@ -18,11 +18,13 @@ pub mod expr;
 mod implied_tables;
 mod inline_literals;
 mod key_def_coalescing;
+mod normalize_null_order;
 mod normalize_topk_with_aggregate;
 mod order_limit_removal;
 mod remove_numeric_field_references;
 mod resolve_schemas;
 mod rewrite_between;
+mod rewrite_right_joins;
 mod star_expansion;
 mod strip_literals;
 mod strip_post_filters;
@@ -46,16 +48,19 @@ pub use crate::expr::ScalarOptimizeExpressions;
 pub use crate::implied_tables::ImpliedTableExpansion;
 pub use crate::inline_literals::InlineLiterals;
 pub use crate::key_def_coalescing::KeyDefinitionCoalescing;
+pub use crate::normalize_null_order::NormalizeNullOrder;
 pub use crate::normalize_topk_with_aggregate::NormalizeTopKWithAggregate;
 pub use crate::order_limit_removal::OrderLimitRemoval;
 pub use crate::remove_numeric_field_references::RemoveNumericFieldReferences;
 pub use crate::resolve_schemas::ResolveSchemas;
 pub use crate::rewrite_between::RewriteBetween;
+pub use crate::rewrite_right_joins::RewriteRightJoins;
 pub use crate::star_expansion::StarExpansion;
 pub use crate::strip_literals::{SelectStatementSkeleton, StripLiterals};
 pub use crate::strip_post_filters::StripPostFilters;
 pub use crate::util::{
-    is_correlated, is_logical_op, is_predicate, map_aggregates, outermost_table_exprs, LogicalOp,
+    is_correlated, is_correlated_by_equalities_only, is_logical_op, is_predicate, map_aggregates,
+    outermost_table_exprs, LogicalOp,
 };
 
 /// Context provided to all query rewriting passes.
@@ -169,7 +174,9 @@ impl Rewrite for SelectStatement {
                 context.invalidating_tables.as_deref_mut(),
             )?
             .expand_stars(context.view_schemas, context.non_replicated_relations)?
+            .rewrite_right_joins()?
             .expand_implied_tables(context.view_schemas)?
+            .normalize_null_order(context.dialect)
             .normalize_topk_with_aggregate()?
             .detect_problematic_self_joins()?
             .remove_numeric_field_references()?
@@ -179,13 +186,14 @@ impl Rewrite for SelectStatement {
 
 impl Rewrite for CompoundSelectStatement {
     fn rewrite(self, context: &mut RewriteContext) -> ReadySetResult<Self> {
+        let stmt = self.normalize_null_order(context.dialect);
         Ok(CompoundSelectStatement {
-            selects: self
+            selects: stmt
                 .selects
                 .into_iter()
                 .map(|(op, sq)| Ok((op, sq.rewrite(context)?)))
                 .collect::<ReadySetResult<_>>()?,
-            ..self
+            ..stmt
         })
     }
 }
@@ -0,0 +1,142 @@
+use dataflow_expression::Dialect;
+use nom_sql::analysis::visit_mut::{self, VisitorMut};
+use nom_sql::{CompoundSelectStatement, OrderBy, OrderType, SelectStatement, SqlQuery};
+
+pub trait NormalizeNullOrder: Sized {
+    /// Recursively make the position of nulls explicit in all `ORDER BY` clauses in the given
+    /// query, by filling in the default `NULLS FIRST` or `NULLS LAST` for the given [`Dialect`]
+    /// wherever it was omitted. For example, in MySQL the following query:
+    ///
+    /// ```sql
+    /// SELECT x FROM t ORDER BY x DESC
+    /// ```
+    ///
+    /// becomes:
+    ///
+    /// ```sql
+    /// SELECT x FROM t ORDER BY x DESC NULLS LAST
+    /// ```
+    ///
+    /// whereas in PostgreSQL it becomes:
+    ///
+    /// ```sql
+    /// SELECT x FROM t ORDER BY x DESC NULLS FIRST
+    /// ```
+    ///
+    /// Invariant: after this pass runs, every [`OrderBy`] in the query has a `null_order`
+    #[must_use]
+    fn normalize_null_order(self, dialect: Dialect) -> Self;
+}
+
+struct NormalizeNullOrderVisitor {
+    dialect: Dialect,
+}
+
+impl<'ast> VisitorMut<'ast> for NormalizeNullOrderVisitor {
+    type Error = !;
+
+    fn visit_order_by(&mut self, order_by: &'ast mut OrderBy) -> Result<(), Self::Error> {
+        if order_by.null_order.is_none() {
+            order_by.null_order = Some(
+                self.dialect
+                    .default_null_order(order_by.order_type.unwrap_or(OrderType::OrderAscending)),
+            );
+        }
+        visit_mut::walk_order_by(self, order_by)
+    }
+}
+
+impl NormalizeNullOrder for SelectStatement {
+    fn normalize_null_order(mut self, dialect: Dialect) -> Self {
+        let Ok(()) = NormalizeNullOrderVisitor { dialect }.visit_select_statement(&mut self);
+        self
+    }
+}
+
+impl NormalizeNullOrder for CompoundSelectStatement {
+    fn normalize_null_order(mut self, dialect: Dialect) -> Self {
+        let Ok(()) =
+            NormalizeNullOrderVisitor { dialect }.visit_compound_select_statement(&mut self);
+        self
+    }
+}
+
+impl NormalizeNullOrder for SqlQuery {
+    fn normalize_null_order(self, dialect: Dialect) -> Self {
+        match self {
+            SqlQuery::Select(stmt) => SqlQuery::Select(stmt.normalize_null_order(dialect)),
+            SqlQuery::CompoundSelect(stmt) => {
+                SqlQuery::CompoundSelect(stmt.normalize_null_order(dialect))
+            }
+            _ => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_query, DialectDisplay};
+
+    use super::*;
+
+    #[track_caller]
+    fn rewrites_to(dialect: Dialect, source: &str, expected: &str) {
+        let q = parse_query(dialect.into(), source).unwrap();
+        let expected = parse_query(nom_sql::Dialect::PostgreSQL, expected).unwrap();
+        let res = q.normalize_null_order(dialect);
+        assert_eq!(
+            res,
+            expected,
+            "{} != {}",
+            res.display(nom_sql::Dialect::PostgreSQL),
+            expected.display(nom_sql::Dialect::PostgreSQL)
+        );
+    }
+
+    #[test]
+    fn mysql_defaults() {
+        rewrites_to(
+            Dialect::DEFAULT_MYSQL,
+            "SELECT x FROM t ORDER BY x, y ASC, z DESC",
+            "SELECT x FROM t ORDER BY x NULLS FIRST, y ASC NULLS FIRST, z DESC NULLS LAST",
+        );
+    }
+
+    #[test]
+    fn postgres_defaults() {
+        rewrites_to(
+            Dialect::DEFAULT_POSTGRESQL,
+            "SELECT x FROM t ORDER BY x, y ASC, z DESC",
+            "SELECT x FROM t ORDER BY x NULLS LAST, y ASC NULLS LAST, z DESC NULLS FIRST",
+        );
+    }
+
+    #[test]
+    fn explicit_null_order_unchanged() {
+        rewrites_to(
+            Dialect::DEFAULT_POSTGRESQL,
+            "SELECT x FROM t ORDER BY x ASC NULLS FIRST, y DESC NULLS LAST",
+            "SELECT x FROM t ORDER BY x ASC NULLS FIRST, y DESC NULLS LAST",
+        );
+    }
+
+    #[test]
+    fn subqueries_and_windows() {
+        rewrites_to(
+            Dialect::DEFAULT_POSTGRESQL,
+            "SELECT sq.x, row_number() OVER (ORDER BY sq.x DESC) FROM \
+             (SELECT x FROM t ORDER BY x LIMIT 3) sq",
+            "SELECT sq.x, row_number() OVER (ORDER BY sq.x DESC NULLS FIRST) FROM \
+             (SELECT x FROM t ORDER BY x NULLS LAST LIMIT 3) sq",
+        );
+    }
+
+    #[test]
+    fn compound_select() {
+        rewrites_to(
+            Dialect::DEFAULT_POSTGRESQL,
+            "SELECT x FROM t1 UNION SELECT x FROM t2 ORDER BY x DESC",
+            "SELECT x FROM t1 UNION SELECT x FROM t2 ORDER BY x DESC NULLS FIRST",
+        );
+    }
+}
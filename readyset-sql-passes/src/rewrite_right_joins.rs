@@ -0,0 +1,147 @@
+use std::mem;
+
+use nom_sql::analysis::visit_mut::{self, VisitorMut};
+use nom_sql::{JoinOperator, JoinRightSide, SelectStatement, SqlQuery};
+use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
+
+pub trait RewriteRightJoins: Sized {
+    /// Recursively rewrite all RIGHT [OUTER] JOINs in the given query into LEFT JOINs by swapping
+    /// the operands of the join. For example, the following query:
+    ///
+    /// ```sql
+    /// SELECT t1.x, t2.y FROM t1 RIGHT JOIN t2 ON t1.id = t2.t1_id
+    /// ```
+    ///
+    /// becomes:
+    ///
+    /// ```sql
+    /// SELECT t1.x, t2.y FROM t2 LEFT JOIN t1 ON t1.id = t2.t1_id
+    /// ```
+    ///
+    /// Since this changes the order of the tables in the query, this pass must run *after* star
+    /// expansion, so that the order of the columns in the result set is preserved.
+    ///
+    /// Currently, this is only supported if the RIGHT JOIN is the first join in the query, and
+    /// both sides of the join are single tables - otherwise, this returns an unsupported error.
+    fn rewrite_right_joins(self) -> ReadySetResult<Self>;
+}
+
+struct RewriteRightJoinsVisitor;
+
+impl<'ast> VisitorMut<'ast> for RewriteRightJoinsVisitor {
+    type Error = ReadySetError;
+
+    fn visit_select_statement(
+        &mut self,
+        select_statement: &'ast mut SelectStatement,
+    ) -> Result<(), Self::Error> {
+        for (i, join) in select_statement.join.iter_mut().enumerate() {
+            if !join.operator.is_right_join() {
+                continue;
+            }
+
+            if i != 0 {
+                unsupported!("RIGHT JOIN is only supported as the first join in a query");
+            }
+
+            let [left] = select_statement.tables.as_mut_slice() else {
+                unsupported!("RIGHT JOIN is only supported with a single table in the FROM clause");
+            };
+            let JoinRightSide::Table(right) = &mut join.right else {
+                unsupported!("RIGHT JOIN is only supported with a single table on the right");
+            };
+
+            mem::swap(left, right);
+            join.operator = JoinOperator::LeftJoin;
+        }
+
+        visit_mut::walk_select_statement(self, select_statement)
+    }
+}
+
+impl RewriteRightJoins for SelectStatement {
+    fn rewrite_right_joins(mut self) -> ReadySetResult<Self> {
+        RewriteRightJoinsVisitor.visit_select_statement(&mut self)?;
+        Ok(self)
+    }
+}
+
+impl RewriteRightJoins for SqlQuery {
+    fn rewrite_right_joins(self) -> ReadySetResult<Self> {
+        match self {
+            SqlQuery::Select(stmt) => Ok(SqlQuery::Select(stmt.rewrite_right_joins()?)),
+            _ => Ok(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::{parse_query, Dialect, DialectDisplay};
+
+    use super::*;
+
+    #[track_caller]
+    fn rewrites_to(source: &str, expected: &str) {
+        let q = parse_query(Dialect::MySQL, source).unwrap();
+        let expected = parse_query(Dialect::MySQL, expected).unwrap();
+        let res = q.rewrite_right_joins().unwrap();
+        assert_eq!(
+            res,
+            expected,
+            "{} != {}",
+            res.display(Dialect::MySQL),
+            expected.display(Dialect::MySQL)
+        );
+    }
+
+    #[test]
+    fn right_join() {
+        rewrites_to(
+            "SELECT t1.x, t2.y FROM t1 RIGHT JOIN t2 ON t1.id = t2.t1_id",
+            "SELECT t1.x, t2.y FROM t2 LEFT JOIN t1 ON t1.id = t2.t1_id",
+        );
+    }
+
+    #[test]
+    fn right_outer_join_with_aliases() {
+        rewrites_to(
+            "SELECT a.x, b.y FROM t1 a RIGHT OUTER JOIN t2 b USING (id)",
+            "SELECT a.x, b.y FROM t2 b LEFT JOIN t1 a USING (id)",
+        );
+    }
+
+    #[test]
+    fn right_join_followed_by_other_joins() {
+        rewrites_to(
+            "SELECT t1.x FROM t1 RIGHT JOIN t2 ON t1.id = t2.t1_id JOIN t3 ON t3.id = t1.id",
+            "SELECT t1.x FROM t2 LEFT JOIN t1 ON t1.id = t2.t1_id JOIN t3 ON t3.id = t1.id",
+        );
+    }
+
+    #[test]
+    fn right_join_in_subquery() {
+        rewrites_to(
+            "SELECT sq.x FROM (SELECT t1.x FROM t1 RIGHT JOIN t2 ON t1.id = t2.id) sq",
+            "SELECT sq.x FROM (SELECT t1.x FROM t2 LEFT JOIN t1 ON t1.id = t2.id) sq",
+        );
+    }
+
+    #[test]
+    fn left_join_unchanged() {
+        rewrites_to(
+            "SELECT t1.x FROM t1 LEFT JOIN t2 ON t1.id = t2.t1_id",
+            "SELECT t1.x FROM t1 LEFT JOIN t2 ON t1.id = t2.t1_id",
+        );
+    }
+
+    #[test]
+    fn right_join_after_other_join_unsupported() {
+        let q = parse_query(
+            Dialect::MySQL,
+            "SELECT t1.x FROM t1 JOIN t2 ON t1.id = t2.id RIGHT JOIN t3 ON t3.id = t1.id",
+        )
+        .unwrap();
+        assert!(q.rewrite_right_joins().unwrap_err().is_unsupported());
+    }
+}
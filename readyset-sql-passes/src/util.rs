@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::iter;
 
 use itertools::Either;
-use nom_sql::analysis::is_aggregate;
+use nom_sql::analysis::{is_aggregate, ReferredColumns};
 use nom_sql::{
     BinaryOperator, Column, CommonTableExpr, DialectDisplay, Expr, FieldDefinitionExpr,
     FunctionExpr, InValue, JoinClause, JoinRightSide, Relation, SelectStatement, SqlIdentifier,
@@ -44,6 +44,46 @@ pub fn is_correlated(statement: &SelectStatement) -> bool {
         .any(|col| col.table.iter().any(|tbl| !tables.contains(tbl)))
 }
 
+/// Returns true if every reference the given select statement makes to tables not explicitly
+/// mentioned in the query (see [`is_correlated`]) is in an equality comparison between a column in
+/// an outer query and a column in the statement itself, at the top level of its `WHERE` clause.
+///
+/// This is trivially true for uncorrelated statements.
+pub fn is_correlated_by_equalities_only(statement: &SelectStatement) -> bool {
+    let tables: HashSet<_> = outermost_named_tables(statement).collect();
+    let is_outer = |col: &Column| col.table.iter().any(|tbl| !tables.contains(tbl));
+
+    let mut without_where = statement.clone();
+    without_where.where_clause = None;
+    if is_correlated(&without_where) {
+        return false;
+    }
+
+    fn conjuncts(expr: &Expr) -> Box<dyn Iterator<Item = &Expr> + '_> {
+        match expr {
+            Expr::BinaryOp {
+                lhs,
+                op: BinaryOperator::And,
+                rhs,
+            } => Box::new(conjuncts(lhs).chain(conjuncts(rhs))),
+            _ => Box::new(iter::once(expr)),
+        }
+    }
+
+    statement
+        .where_clause
+        .iter()
+        .flat_map(conjuncts)
+        .all(|conjunct| match conjunct {
+            Expr::BinaryOp {
+                lhs: box Expr::Column(lhs),
+                op: BinaryOperator::Equal,
+                rhs: box Expr::Column(rhs),
+            } => !(is_outer(lhs) && is_outer(rhs)),
+            _ => !conjunct.referred_columns().any(is_outer),
+        })
+}
+
 fn field_names(statement: &SelectStatement) -> impl Iterator<Item = &SqlIdentifier> {
     statement.fields.iter().filter_map(|field| match &field {
         FieldDefinitionExpr::Expr {
@@ -235,4 +275,39 @@ mod tests {
             assert!(is_correlated(&query));
         }
     }
+
+    mod is_correlated_by_equalities_only {
+        use super::*;
+
+        #[test]
+        fn uncorrelated_query() {
+            let query = parse_select_statement("SELECT * FROM t WHERE t.x = t.y AND t.z > 4");
+            assert!(is_correlated_by_equalities_only(&query));
+        }
+
+        #[test]
+        fn equality_correlations() {
+            let query =
+                parse_select_statement("SELECT * FROM t WHERE t.x = u.a AND t.z > 4 AND u.b = t.y");
+            assert!(is_correlated_by_equalities_only(&query));
+        }
+
+        #[test]
+        fn inequality_correlation() {
+            let query = parse_select_statement("SELECT * FROM t WHERE t.x = u.a AND t.z > u.b");
+            assert!(!is_correlated_by_equalities_only(&query));
+        }
+
+        #[test]
+        fn correlation_under_or() {
+            let query = parse_select_statement("SELECT * FROM t WHERE t.x = u.a OR t.z = 4");
+            assert!(!is_correlated_by_equalities_only(&query));
+        }
+
+        #[test]
+        fn correlation_outside_where() {
+            let query = parse_select_statement("SELECT u.a FROM t WHERE t.x = u.a");
+            assert!(!is_correlated_by_equalities_only(&query));
+        }
+    }
 }
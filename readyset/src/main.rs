@@ -9,13 +9,15 @@ use readyset::{NoriaAdapter, Options};
 fn main() -> anyhow::Result<()> {
     let options = Options::parse();
     match options.database_type()? {
-        DatabaseType::MySQL => NoriaAdapter {
+        database_type @ (DatabaseType::MySQL | DatabaseType::MariaDB) => NoriaAdapter {
             description: "MySQL adapter for ReadySet.",
             default_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 3307),
-            connection_handler: MySqlHandler {
+            connection_handler: MySqlHandler::new(readyset::mysql::Config {
+                options: options.mysql_options.clone(),
+                tls_options: options.tls_options.clone(),
                 enable_statement_logging: options.tracing.statement_logging,
-            },
-            database_type: DatabaseType::MySQL,
+            })?,
+            database_type,
             parse_dialect: nom_sql::Dialect::MySQL,
             expr_dialect: readyset_data::Dialect::DEFAULT_MYSQL,
         }
@@ -25,6 +27,7 @@ fn main() -> anyhow::Result<()> {
             default_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433),
             connection_handler: PsqlHandler::new(readyset::psql::Config {
                 options: options.psql_options.clone(),
+                tls_options: options.tls_options.clone(),
                 enable_statement_logging: options.tracing.statement_logging,
             })?,
             database_type: DatabaseType::PostgreSQL,
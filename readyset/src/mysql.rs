@@ -1,16 +1,52 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use clap::Parser;
 use mysql_srv::MySqlIntermediary;
 use readyset_adapter::upstream_database::LazyUpstream;
+use readyset_errors::ReadySetResult;
 use readyset_mysql::{MySqlQueryHandler, MySqlUpstream};
 use tokio::net::TcpStream;
+use tokio_native_tls::TlsAcceptor;
 use tracing::{error, instrument};
 
-use crate::ConnectionHandler;
+use crate::{tls, ConnectionHandler};
+
+// readyset-mysql specific options
+#[derive(Clone, Debug, Parser)]
+pub struct Options {
+    /// Reject MySQL clients that do not connect over TLS.
+    ///
+    /// Requires `--readyset-identity-file` to be set.
+    #[arg(long, env = "MYSQL_REQUIRE_TLS", requires = "readyset_identity_file")]
+    mysql_require_tls: bool,
+}
 
-#[derive(Clone, Copy)]
+/// Contains mysql-srv specific `Options`, TLS options, and whether to enable statement logging.
+pub struct Config {
+    pub options: Options,
+    pub tls_options: tls::Options,
+    pub enable_statement_logging: bool,
+}
+
+#[derive(Clone)]
 pub struct MySqlHandler {
     /// Whether to log statements received by the client
     pub enable_statement_logging: bool,
+    /// Optional struct to accept a TLS handshake and return a `TlsConnection`.
+    pub tls_acceptor: Option<Arc<TlsAcceptor>>,
+    /// Whether to reject clients that do not request TLS. Only used if `tls_acceptor` is set.
+    pub require_tls: bool,
+}
+
+impl MySqlHandler {
+    pub fn new(config: Config) -> ReadySetResult<MySqlHandler> {
+        Ok(MySqlHandler {
+            enable_statement_logging: config.enable_statement_logging,
+            tls_acceptor: tls::tls_acceptor(&config.tls_options)?,
+            require_tls: config.options.mysql_require_tls,
+        })
+    }
 }
 
 #[async_trait]
@@ -24,16 +60,27 @@ impl ConnectionHandler for MySqlHandler {
         stream: TcpStream,
         backend: readyset_adapter::Backend<LazyUpstream<MySqlUpstream>, MySqlQueryHandler>,
     ) {
-        if let Err(e) = MySqlIntermediary::run_on_tcp(
-            readyset_mysql::Backend {
-                noria: backend,
-                enable_statement_logging: self.enable_statement_logging,
-            },
-            stream,
-            self.enable_statement_logging,
-        )
-        .await
-        {
+        let backend = readyset_mysql::Backend {
+            noria: backend,
+            enable_statement_logging: self.enable_statement_logging,
+        };
+        let res = match &self.tls_acceptor {
+            Some(tls_acceptor) => {
+                MySqlIntermediary::run_on_tls_tcp(
+                    backend,
+                    stream,
+                    self.enable_statement_logging,
+                    tls_acceptor.clone(),
+                    self.require_tls,
+                )
+                .await
+            }
+            None => {
+                MySqlIntermediary::run_on_tcp(backend, stream, self.enable_statement_logging).await
+            }
+        };
+
+        if let Err(e) = res {
             error!(err = %e, "connection lost");
         }
     }
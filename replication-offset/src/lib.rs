@@ -1,5 +1,6 @@
 //! Data types for implementing snapshot and streaming replication from an upstream database.
 
+pub mod mariadb;
 pub mod mysql;
 pub mod postgres;
 
@@ -9,7 +10,8 @@ use std::collections::HashMap;
 use std::fmt;
 use std::hash::Hash;
 
-use mysql::MySqlPosition;
+use mariadb::MariaDbGtidPosition;
+use mysql::{MySqlGtidPosition, MySqlPosition};
 use nom_sql::Relation;
 use postgres::PostgresPosition;
 use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
@@ -27,6 +29,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ReplicationOffset {
     MySql(MySqlPosition),
+    MySqlGtid(MySqlGtidPosition),
+    MariaDbGtid(MariaDbGtidPosition),
     Postgres(PostgresPosition),
 }
 
@@ -38,7 +42,7 @@ impl TryFrom<ReplicationOffset> for MySqlPosition {
             Ok(offset)
         } else {
             Err(internal_err!(
-                "cannot extract MySqlPosition from Postgres ReplicationOffset"
+                "cannot extract MySqlPosition from non-binlog-position ReplicationOffset"
             ))
         }
     }
@@ -52,6 +56,50 @@ impl TryFrom<&ReplicationOffset> for MySqlPosition {
     }
 }
 
+impl TryFrom<ReplicationOffset> for MySqlGtidPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: ReplicationOffset) -> Result<Self, Self::Error> {
+        if let ReplicationOffset::MySqlGtid(offset) = offset {
+            Ok(offset)
+        } else {
+            Err(internal_err!(
+                "cannot extract MySqlGtidPosition from non-GTID ReplicationOffset"
+            ))
+        }
+    }
+}
+
+impl TryFrom<&ReplicationOffset> for MySqlGtidPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: &ReplicationOffset) -> Result<Self, Self::Error> {
+        offset.clone().try_into()
+    }
+}
+
+impl TryFrom<ReplicationOffset> for MariaDbGtidPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: ReplicationOffset) -> Result<Self, Self::Error> {
+        if let ReplicationOffset::MariaDbGtid(offset) = offset {
+            Ok(offset)
+        } else {
+            Err(internal_err!(
+                "cannot extract MariaDbGtidPosition from non-MariaDB ReplicationOffset"
+            ))
+        }
+    }
+}
+
+impl TryFrom<&ReplicationOffset> for MariaDbGtidPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: &ReplicationOffset) -> Result<Self, Self::Error> {
+        offset.clone().try_into()
+    }
+}
+
 impl TryFrom<ReplicationOffset> for PostgresPosition {
     type Error = ReadySetError;
 
@@ -78,6 +126,8 @@ impl fmt::Display for ReplicationOffset {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::MySql(pos) => write!(f, "{pos}"),
+            Self::MySqlGtid(pos) => write!(f, "{pos}"),
+            Self::MariaDbGtid(pos) => write!(f, "{pos}"),
             Self::Postgres(pos) => write!(f, "{pos}"),
         }
     }
@@ -87,6 +137,8 @@ impl PartialOrd for ReplicationOffset {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
             (Self::MySql(pos), Self::MySql(other_pos)) => pos.partial_cmp(other_pos),
+            (Self::MySqlGtid(pos), Self::MySqlGtid(other_pos)) => pos.partial_cmp(other_pos),
+            (Self::MariaDbGtid(pos), Self::MariaDbGtid(other_pos)) => pos.partial_cmp(other_pos),
             (Self::Postgres(pos), Self::Postgres(other_pos)) => pos.partial_cmp(other_pos),
             _ => None,
         }
@@ -116,9 +168,15 @@ impl ReplicationOffset {
             (Self::MySql(offset), Self::MySql(other_offset)) => {
                 offset.try_partial_cmp(other_offset)
             }
+            (Self::MySqlGtid(offset), Self::MySqlGtid(other_offset)) => {
+                offset.try_partial_cmp(other_offset)
+            }
+            (Self::MariaDbGtid(offset), Self::MariaDbGtid(other_offset)) => {
+                offset.try_partial_cmp(other_offset)
+            }
             (Self::Postgres(offset), Self::Postgres(other_offset)) => Ok(offset.cmp(other_offset)),
             _ => Err(internal_err!(
-                "Cannot compare replication offsets from different database backends"
+                "Cannot compare replication offsets of different kinds ({self} and {other})"
             )),
         }
     }
@@ -279,6 +337,78 @@ impl ReplicationOffsets {
     }
 }
 
+/// A range of values of a table's primary key that was copied to ReadySet as a single chunk while
+/// snapshotting the table
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SnapshotChunk {
+    /// The first value of the key in the chunk
+    pub start: i64,
+    /// The last value of the key in the chunk (inclusive)
+    pub end: i64,
+    /// The replication offset of the upstream snapshot the chunk was copied from
+    pub offset: ReplicationOffset,
+}
+
+/// The chunks of a table which have been copied so far while snapshotting it in chunks of the
+/// values of its primary key.
+///
+/// If snapshotting the table is interrupted, the replicator resumes from these chunks rather than
+/// snapshotting the whole table again. Chunks copied after resuming come from a later snapshot than
+/// the ones before, so replication has to start from the earliest of their offsets, and skip
+/// changes to rows in each chunk up to that chunk's offset.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SnapshotProgress {
+    /// The index of the primary key column the table is split into chunks by
+    pub key: usize,
+    /// The chunks that have been copied
+    pub chunks: Vec<SnapshotChunk>,
+}
+
+impl SnapshotProgress {
+    /// Create a new [`SnapshotProgress`] for a table split into chunks by the given column, with no
+    /// chunks copied yet
+    pub fn new(key: usize) -> Self {
+        Self {
+            key,
+            chunks: vec![],
+        }
+    }
+
+    /// Returns the minimum of the offsets of all chunks, from which replication of the table has to
+    /// start once it's been snapshotted, or [`None`] if no chunks have been copied
+    pub fn min_offset(&self) -> ReadySetResult<Option<&ReplicationOffset>> {
+        let mut res: Option<&ReplicationOffset> = None;
+        for chunk in &self.chunks {
+            res = Some(match res {
+                Some(offset) => ReplicationOffset::try_min(offset, &chunk.offset)?,
+                None => &chunk.offset,
+            });
+        }
+        Ok(res)
+    }
+
+    /// Returns the maximum of the offsets of all chunks, after which changes to the table no longer
+    /// need to be checked against the chunks, or [`None`] if no chunks have been copied
+    pub fn max_offset(&self) -> ReadySetResult<Option<&ReplicationOffset>> {
+        let mut res: Option<&ReplicationOffset> = None;
+        for chunk in &self.chunks {
+            match res {
+                Some(offset) if chunk.offset.try_partial_cmp(offset)?.is_le() => {}
+                _ => res = Some(&chunk.offset),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Returns the offset of the chunk containing the given value of the key, if any
+    pub fn offset_for_key(&self, key: i64) -> Option<&ReplicationOffset> {
+        self.chunks
+            .iter()
+            .find(|chunk| (chunk.start..=chunk.end).contains(&key))
+            .map(|chunk| &chunk.offset)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,4 +530,41 @@ mod tests {
             assert!(res.is_none());
         }
     }
+
+    mod snapshot_progress {
+        use super::*;
+
+        fn chunk(start: i64, end: i64, position: u64) -> SnapshotChunk {
+            SnapshotChunk {
+                start,
+                end,
+                offset: MySqlPosition::from_file_name_and_position("test.00001".into(), position)
+                    .unwrap()
+                    .into(),
+            }
+        }
+
+        #[test]
+        fn offsets() {
+            let progress = SnapshotProgress {
+                key: 0,
+                chunks: vec![chunk(1, 10, 3), chunk(11, 20, 1), chunk(21, 30, 2)],
+            };
+            let min: MySqlPosition = progress.min_offset().unwrap().unwrap().try_into().unwrap();
+            assert_eq!(min.position, 1);
+            let max: MySqlPosition = progress.max_offset().unwrap().unwrap().try_into().unwrap();
+            assert_eq!(max.position, 3);
+
+            let offset: MySqlPosition = progress.offset_for_key(11).unwrap().try_into().unwrap();
+            assert_eq!(offset.position, 1);
+            assert!(progress.offset_for_key(31).is_none());
+        }
+
+        #[test]
+        fn no_chunks() {
+            let progress = SnapshotProgress::new(0);
+            assert!(progress.min_offset().unwrap().is_none());
+            assert!(progress.max_offset().unwrap().is_none());
+        }
+    }
 }
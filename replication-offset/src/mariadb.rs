@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use readyset_errors::{ReadySetError, ReadySetResult};
+use serde::{Deserialize, Serialize};
+
+use crate::ReplicationOffset;
+
+/// A MariaDB global transaction identifier.
+///
+/// Unlike MySQL, MariaDB identifies transactions by a replication domain, the id of the server the
+/// transaction originated on, and a sequence number. Sequence numbers are strictly increasing
+/// within a replication domain, regardless of which server a transaction originated on.
+///
+/// The textual representation matches MariaDB's, eg `0-1-100`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MariaDbGtid {
+    /// The replication domain the transaction belongs to
+    pub domain_id: u32,
+    /// The id of the server the transaction originated on
+    pub server_id: u32,
+    /// The sequence number of the transaction within its replication domain
+    pub sequence_number: u64,
+}
+
+impl fmt::Display for MariaDbGtid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}",
+            self.domain_id, self.server_id, self.sequence_number
+        )
+    }
+}
+
+impl FromStr for MariaDbGtid {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReadySetError::ReplicationFailed(format!("Invalid MariaDB GTID {s}"));
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(invalid);
+        Ok(Self {
+            domain_id: next()?.parse().map_err(|_| invalid())?,
+            server_id: next()?.parse().map_err(|_| invalid())?,
+            sequence_number: next()?.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Represents a position within the MariaDB binlog in terms of GTIDs, rather than binlog file names
+/// and offsets.
+///
+/// Since transactions within a replication domain are totally ordered, the set of transactions
+/// replicated so far is described by the last transaction replicated in each domain, which is
+/// the same format MariaDB uses for `gtid_binlog_pos` and for telling the server where to start
+/// sending us the binlog from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MariaDbGtidPosition {
+    /// The last transaction that has been completely replicated in each replication domain, keyed
+    /// by domain id
+    executed: BTreeMap<u32, MariaDbGtid>,
+    /// The transaction currently being replicated, if any, along with the number of replication
+    /// events within that transaction that have been replicated so far
+    pub current: Option<(MariaDbGtid, u64)>,
+}
+
+impl fmt::Display for MariaDbGtidPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, gtid) in self.executed.values().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{gtid}")?;
+        }
+        if let Some((gtid, events)) = &self.current {
+            write!(f, " ({gtid}#{events})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma-separated list of GTIDs, such as the value of `gtid_binlog_pos`. If the list
+/// contains more than one GTID for the same domain (which `gtid_binlog_pos` does if transactions in
+/// that domain originated on more than one server), the one with the highest sequence number is
+/// kept.
+impl FromStr for MariaDbGtidPosition {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut res = Self::default();
+        for gtid in s.split(',').map(str::trim).filter(|gtid| !gtid.is_empty()) {
+            res.insert(gtid.parse()?);
+        }
+        Ok(res)
+    }
+}
+
+impl MariaDbGtidPosition {
+    /// Returns `true` if no transactions have been replicated as of this position
+    pub fn is_empty(&self) -> bool {
+        self.executed.is_empty() && self.current.is_none()
+    }
+
+    /// Returns an iterator over the last transaction that has been completely replicated in each
+    /// replication domain
+    pub fn executed(&self) -> impl Iterator<Item = &MariaDbGtid> {
+        self.executed.values()
+    }
+
+    fn insert(&mut self, gtid: MariaDbGtid) {
+        let last = self.executed.entry(gtid.domain_id).or_insert(gtid);
+        if gtid.sequence_number > last.sequence_number {
+            *last = gtid;
+        }
+    }
+
+    /// Returns `true` if the given transaction has been completely replicated as of this position
+    pub fn contains(&self, gtid: &MariaDbGtid) -> bool {
+        self.executed
+            .get(&gtid.domain_id)
+            .map_or(false, |last| gtid.sequence_number <= last.sequence_number)
+    }
+
+    /// Record that the transaction with the given [`MariaDbGtid`] has started. Any transaction that
+    /// was already in progress is considered complete.
+    pub fn start_transaction(&mut self, gtid: MariaDbGtid) {
+        self.commit_transaction();
+        self.current = Some((gtid, 0));
+    }
+
+    /// Record that another replication event within the current transaction has been replicated
+    pub fn advance(&mut self) {
+        if let Some((_, events)) = &mut self.current {
+            *events += 1;
+        }
+    }
+
+    /// Record that the current transaction, if any, is complete
+    pub fn commit_transaction(&mut self) {
+        if let Some((gtid, _)) = self.current.take() {
+            self.insert(gtid);
+        }
+    }
+
+    /// Returns `true` if everything replicated as of `self` has also been replicated as of `other`
+    fn is_before_or_at(&self, other: &Self) -> bool {
+        if !self.executed.values().all(|gtid| other.contains(gtid)) {
+            return false;
+        }
+        match &self.current {
+            None => true,
+            Some((gtid, events)) => {
+                other.contains(gtid)
+                    || matches!(
+                        &other.current,
+                        Some((other_gtid, other_events))
+                            if other_gtid == gtid && events <= other_events
+                    )
+            }
+        }
+    }
+
+    /// This method compares `self` and `other`, returning an [`Ordering`] if the two items are
+    /// comparable and an error otherwise.
+    pub fn try_partial_cmp(&self, other: &Self) -> ReadySetResult<Ordering> {
+        self.partial_cmp(other).ok_or_else(|| {
+            ReadySetError::Internal(format!(
+                "Cannot compare MariaDB GTID positions {self} and {other}"
+            ))
+        })
+    }
+}
+
+impl PartialOrd for MariaDbGtidPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // Positions are ordered by inclusion of the replicated transactions, so two positions
+        // that are each ahead of the other in some replication domain are not comparable
+        match (self.is_before_or_at(other), other.is_before_or_at(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl From<&MariaDbGtidPosition> for ReplicationOffset {
+    fn from(value: &MariaDbGtidPosition) -> Self {
+        ReplicationOffset::MariaDbGtid(value.to_owned())
+    }
+}
+
+impl From<MariaDbGtidPosition> for ReplicationOffset {
+    fn from(value: MariaDbGtidPosition) -> Self {
+        ReplicationOffset::MariaDbGtid(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gtid(domain_id: u32, server_id: u32, sequence_number: u64) -> MariaDbGtid {
+        MariaDbGtid {
+            domain_id,
+            server_id,
+            sequence_number,
+        }
+    }
+
+    #[test]
+    fn gtid_parse_and_display() {
+        assert_eq!("0-1-100".parse::<MariaDbGtid>().unwrap(), gtid(0, 1, 100));
+        assert_eq!(gtid(0, 1, 100).to_string(), "0-1-100");
+        "0-1".parse::<MariaDbGtid>().unwrap_err();
+        "0-1-x".parse::<MariaDbGtid>().unwrap_err();
+    }
+
+    #[test]
+    fn position_parse_keeps_last_gtid_per_domain() {
+        let pos: MariaDbGtidPosition = "0-1-100, 0-2-50,1-1-7".parse().unwrap();
+        assert_eq!(pos.to_string(), "0-1-100,1-1-7");
+        assert!(pos.contains(&gtid(0, 2, 99)));
+        assert!(!pos.contains(&gtid(0, 2, 101)));
+        assert!(!pos.contains(&gtid(2, 1, 1)));
+        assert!("".parse::<MariaDbGtidPosition>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn position_ordering() {
+        let mut pos = MariaDbGtidPosition::default();
+        pos.start_transaction(gtid(0, 1, 1));
+        let started = pos.clone();
+        pos.advance();
+        let first_event = pos.clone();
+        pos.commit_transaction();
+        let committed = pos.clone();
+        pos.start_transaction(gtid(0, 2, 2));
+        pos.commit_transaction();
+        let next_transaction = pos.clone();
+
+        assert!(MariaDbGtidPosition::default() < started);
+        assert!(started < first_event);
+        assert!(first_event < committed);
+        assert!(committed < next_transaction);
+        assert_eq!(
+            committed.partial_cmp(&committed.clone()),
+            Some(Ordering::Equal)
+        );
+
+        // Positions that are each ahead of the other in a different domain are not comparable
+        let mut other = committed.clone();
+        other.start_transaction(gtid(1, 1, 1));
+        other.commit_transaction();
+        assert!(other.partial_cmp(&next_transaction).is_none());
+        other.try_partial_cmp(&next_transaction).unwrap_err();
+    }
+
+    #[test]
+    fn position_serde_round_trip() {
+        let mut pos: MariaDbGtidPosition = "0-1-100,1-1-7".parse().unwrap();
+        pos.start_transaction(gtid(0, 1, 101));
+        pos.advance();
+        let offset = ReplicationOffset::from(pos);
+
+        let serialized = serde_json::to_string(&offset).unwrap();
+        let deserialized: ReplicationOffset = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, offset);
+    }
+}
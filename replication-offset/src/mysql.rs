@@ -1,9 +1,11 @@
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::str::FromStr;
 
 use readyset_errors::{ReadySetError, ReadySetResult};
 use readyset_util::fmt::fmt_with;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::ReplicationOffset;
 
@@ -107,10 +109,301 @@ impl From<MySqlPosition> for ReplicationOffset {
     }
 }
 
+/// The UUID of the MySQL server a transaction originated on, which forms the first half of a
+/// [`Gtid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(pub [u8; 16]);
+
+impl fmt::Display for SourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SourceId {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReadySetError::ReplicationFailed(format!("Invalid GTID source id {s}"));
+        let digits = s.trim().bytes().filter(|b| *b != b'-').collect::<Vec<_>>();
+        if digits.len() != 32 {
+            return Err(invalid());
+        }
+
+        let mut res = [0; 16];
+        for (byte, pair) in res.iter_mut().zip(digits.chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(res))
+    }
+}
+
+// Source ids are (de)serialized as their string representation, so that they can be used as map
+// keys in formats like JSON
+impl Serialize for SourceId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A MySQL global transaction identifier, which uniquely identifies a transaction across all
+/// servers in a replication topology.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Gtid {
+    /// The server the transaction originated on
+    pub source_id: SourceId,
+    /// The sequence number of the transaction on its source server, starting at 1
+    pub transaction_id: u64,
+}
+
+impl fmt::Display for Gtid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.source_id, self.transaction_id)
+    }
+}
+
+/// A set of MySQL [`Gtid`]s, such as the set of transactions executed by a server.
+///
+/// The textual representation matches MySQL's, eg
+/// `3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7,4f2c1a38-71ca-11e1-9e33-c80aa9429562:1-3`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GtidSet {
+    /// A map from source id to a sorted list of disjoint, non-adjacent, inclusive ranges of
+    /// transaction ids from that source
+    intervals: BTreeMap<SourceId, Vec<(u64, u64)>>,
+}
+
+impl fmt::Display for GtidSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (source_id, intervals)) in self.intervals.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{source_id}")?;
+            for (start, end) in intervals {
+                if start == end {
+                    write!(f, ":{start}")?;
+                } else {
+                    write!(f, ":{start}-{end}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GtidSet {
+    type Err = ReadySetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReadySetError::ReplicationFailed(format!("Invalid GTID set {s}"));
+        let mut res = Self::default();
+        for sid in s.split(',').map(str::trim).filter(|sid| !sid.is_empty()) {
+            let mut parts = sid.split(':');
+            let source_id = parts.next().ok_or_else(invalid)?.parse::<SourceId>()?;
+            for interval in parts {
+                let (start, end) = interval.split_once('-').unwrap_or((interval, interval));
+                let start = start.trim().parse::<u64>().map_err(|_| invalid())?;
+                let end = end.trim().parse::<u64>().map_err(|_| invalid())?;
+                if start == 0 || start > end {
+                    return Err(invalid());
+                }
+                res.insert_interval(source_id, start, end);
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl GtidSet {
+    /// Returns `true` if this set contains no transactions
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Add the given transaction to this set
+    pub fn insert(&mut self, gtid: Gtid) {
+        self.insert_interval(gtid.source_id, gtid.transaction_id, gtid.transaction_id)
+    }
+
+    fn insert_interval(&mut self, source_id: SourceId, start: u64, end: u64) {
+        let intervals = self.intervals.entry(source_id).or_default();
+        intervals.push((start, end));
+        intervals.sort_unstable();
+
+        // Merge overlapping or adjacent intervals
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+        for &(start, end) in intervals.iter() {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= last_end.saturating_add(1) => {
+                    *last_end = (*last_end).max(end);
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+        *intervals = merged;
+    }
+
+    /// Returns `true` if this set contains the given transaction
+    pub fn contains(&self, gtid: &Gtid) -> bool {
+        self.intervals
+            .get(&gtid.source_id)
+            .map_or(false, |intervals| {
+                intervals
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&gtid.transaction_id))
+            })
+    }
+
+    /// Returns `true` if every transaction in this set is also in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.intervals.iter().all(|(source_id, intervals)| {
+            let Some(other_intervals) = other.intervals.get(source_id) else {
+                return false;
+            };
+            intervals.iter().all(|&(start, end)| {
+                other_intervals
+                    .iter()
+                    .any(|&(other_start, other_end)| other_start <= start && end <= other_end)
+            })
+        })
+    }
+
+    /// Returns an iterator over the source ids in this set, along with the (inclusive) ranges of
+    /// transaction ids from each source
+    pub fn iter(&self) -> impl Iterator<Item = (&SourceId, &[(u64, u64)])> {
+        self.intervals
+            .iter()
+            .map(|(source_id, intervals)| (source_id, intervals.as_slice()))
+    }
+}
+
+/// Represents a position within the MySQL binlog in terms of GTIDs, rather than binlog file names
+/// and offsets. Since GTIDs are preserved when transactions are replicated, unlike a
+/// [`MySqlPosition`] a [`MySqlGtidPosition`] remains valid when replicating from a different
+/// server in the same replication topology, eg after a replica is promoted to primary.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MySqlGtidPosition {
+    /// The set of transactions that have been completely replicated
+    pub executed: GtidSet,
+    /// The transaction currently being replicated, if any, along with the number of replication
+    /// events within that transaction that have been replicated so far
+    pub current: Option<(Gtid, u64)>,
+}
+
+impl fmt::Display for MySqlGtidPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.executed)?;
+        if let Some((gtid, events)) = &self.current {
+            write!(f, " ({gtid}#{events})")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<GtidSet> for MySqlGtidPosition {
+    fn from(executed: GtidSet) -> Self {
+        Self {
+            executed,
+            current: None,
+        }
+    }
+}
+
+impl MySqlGtidPosition {
+    /// Record that the transaction with the given [`Gtid`] has started. Any transaction that was
+    /// already in progress is considered complete.
+    pub fn start_transaction(&mut self, gtid: Gtid) {
+        self.commit_transaction();
+        self.current = Some((gtid, 0));
+    }
+
+    /// Record that another replication event within the current transaction has been replicated
+    pub fn advance(&mut self) {
+        if let Some((_, events)) = &mut self.current {
+            *events += 1;
+        }
+    }
+
+    /// Record that the current transaction, if any, is complete
+    pub fn commit_transaction(&mut self) {
+        if let Some((gtid, _)) = self.current.take() {
+            self.executed.insert(gtid);
+        }
+    }
+
+    /// Returns `true` if everything replicated as of `self` has also been replicated as of `other`
+    fn is_before_or_at(&self, other: &Self) -> bool {
+        if !self.executed.is_subset(&other.executed) {
+            return false;
+        }
+        match &self.current {
+            None => true,
+            Some((gtid, events)) => {
+                other.executed.contains(gtid)
+                    || matches!(
+                        &other.current,
+                        Some((other_gtid, other_events))
+                            if other_gtid == gtid && events <= other_events
+                    )
+            }
+        }
+    }
+
+    /// This method compares `self` and `other`, returning an [`Ordering`] if the two items are
+    /// comparable and an error otherwise.
+    pub fn try_partial_cmp(&self, other: &Self) -> ReadySetResult<Ordering> {
+        self.partial_cmp(other).ok_or_else(|| {
+            ReadySetError::Internal(format!(
+                "Cannot compare MySQL GTID positions {self} and {other}"
+            ))
+        })
+    }
+}
+
+impl PartialOrd for MySqlGtidPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // GTID positions are ordered by inclusion of the replicated transactions, so two positions
+        // that have each replicated a transaction the other hasn't are not comparable
+        match (self.is_before_or_at(other), other.is_before_or_at(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl From<&MySqlGtidPosition> for ReplicationOffset {
+    fn from(value: &MySqlGtidPosition) -> Self {
+        ReplicationOffset::MySqlGtid(value.to_owned())
+    }
+}
+
+impl From<MySqlGtidPosition> for ReplicationOffset {
+    fn from(value: MySqlGtidPosition) -> Self {
+        ReplicationOffset::MySqlGtid(value)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::MySqlPosition;
-    use crate::ReplicationOffset;
+    use super::*;
 
     #[test]
     fn test_partial_ord() {
@@ -188,4 +481,113 @@ mod test {
             })
         );
     }
+
+    fn gtid(source: u8, transaction_id: u64) -> Gtid {
+        Gtid {
+            source_id: SourceId([source; 16]),
+            transaction_id,
+        }
+    }
+
+    #[test]
+    fn source_id_round_trip() {
+        let s = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+        let source_id: SourceId = s.parse().unwrap();
+        assert_eq!(source_id.to_string(), s);
+        assert_eq!(
+            "3E11FA4771CA11E19E33C80AA9429562"
+                .parse::<SourceId>()
+                .unwrap(),
+            source_id
+        );
+        "3e11fa47-71ca".parse::<SourceId>().unwrap_err();
+    }
+
+    #[test]
+    fn gtid_set_parse_and_display() {
+        let s = "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7,\n\
+                 4f2c1a38-71ca-11e1-9e33-c80aa9429562:1-3";
+        let set: GtidSet = s.parse().unwrap();
+        assert_eq!(
+            set.to_string(),
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7,4f2c1a38-71ca-11e1-9e33-c80aa9429562:1-3"
+        );
+        assert!("".parse::<GtidSet>().unwrap().is_empty());
+        "3e11fa47-71ca-11e1-9e33-c80aa9429562:5-1"
+            .parse::<GtidSet>()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn gtid_set_insert_merges_intervals() {
+        let mut set = GtidSet::default();
+        for transaction_id in [1, 2, 5, 4] {
+            set.insert(gtid(1, transaction_id));
+        }
+        assert_eq!(set.iter().next().unwrap().1, &[(1, 2), (4, 5)]);
+        set.insert(gtid(1, 3));
+        assert_eq!(set.iter().next().unwrap().1, &[(1, 5)]);
+
+        assert!(set.contains(&gtid(1, 3)));
+        assert!(!set.contains(&gtid(1, 6)));
+        assert!(!set.contains(&gtid(2, 3)));
+    }
+
+    #[test]
+    fn gtid_set_subset() {
+        let mut small = GtidSet::default();
+        small.insert(gtid(1, 2));
+        let mut large = small.clone();
+        large.insert(gtid(1, 1));
+        large.insert(gtid(2, 1));
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(GtidSet::default().is_subset(&small));
+    }
+
+    #[test]
+    fn gtid_position_ordering() {
+        let mut pos = MySqlGtidPosition::default();
+        pos.start_transaction(gtid(1, 1));
+        let started = pos.clone();
+        pos.advance();
+        let first_event = pos.clone();
+        pos.advance();
+        let second_event = pos.clone();
+        pos.commit_transaction();
+        let committed = pos.clone();
+
+        assert!(MySqlGtidPosition::default() < started);
+        assert!(started < first_event);
+        assert!(first_event < second_event);
+        assert!(second_event < committed);
+        assert_eq!(
+            committed.partial_cmp(&committed.clone()),
+            Some(Ordering::Equal)
+        );
+
+        // A position on a different transaction is not comparable to one that hasn't seen it
+        let mut other = MySqlGtidPosition::default();
+        other.start_transaction(gtid(2, 1));
+        other.commit_transaction();
+        assert!(other.partial_cmp(&committed).is_none());
+        other.try_partial_cmp(&committed).unwrap_err();
+    }
+
+    #[test]
+    fn gtid_position_serde_round_trip() {
+        let mut pos = MySqlGtidPosition::from(
+            "3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5"
+                .parse::<GtidSet>()
+                .unwrap(),
+        );
+        pos.start_transaction(gtid(1, 1));
+        pos.advance();
+        let offset = ReplicationOffset::from(pos);
+
+        let serialized = serde_json::to_string(&offset).unwrap();
+        let deserialized: ReplicationOffset = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, offset);
+    }
 }
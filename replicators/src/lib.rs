@@ -10,6 +10,7 @@ pub mod db_util;
 pub(crate) mod mysql_connector;
 pub(crate) mod noria_adapter;
 pub(crate) mod postgres_connector;
+pub(crate) mod snapshot_chunks;
 pub(crate) mod table_filter;
 
 use std::time::Duration;
@@ -1,8 +1,10 @@
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryInto;
 use std::io;
 
 use async_trait::async_trait;
 use binlog::consts::{BinlogChecksumAlg, EventType};
+use database_utils::DatabaseType;
+use itertools::Itertools;
 use metrics::counter;
 use mysql::binlog::events::StatusVarVal;
 use mysql::binlog::jsonb::{self, JsonbToJsonError};
@@ -16,13 +18,18 @@ use readyset_client::metrics::recorded;
 use readyset_client::recipe::ChangeList;
 use readyset_data::{DfValue, Dialect};
 use readyset_errors::{internal, internal_err, ReadySetError, ReadySetResult};
-use replication_offset::mysql::MySqlPosition;
+use replication_offset::mariadb::MariaDbGtidPosition;
+use replication_offset::mysql::{Gtid, MySqlGtidPosition, MySqlPosition, SourceId};
 use replication_offset::ReplicationOffset;
 use tracing::{error, info, warn};
 
+use super::mariadb;
 use crate::noria_adapter::{Connector, ReplicationAction};
 
 const CHECKSUM_QUERY: &str = "SET @master_binlog_checksum='CRC32'";
+/// Tells a MariaDB server that we understand its GTID events, rather than having it replace them
+/// with MySQL-compatible events
+const MARIADB_CAPABILITY_QUERY: &str = "SET @mariadb_slave_capability=4";
 const DEFAULT_SERVER_ID: u32 = u32::MAX - 55;
 
 /// A connector that connects to a MySQL server and starts reading binlogs from a given position.
@@ -39,6 +46,9 @@ const DEFAULT_SERVER_ID: u32 = u32::MAX - 55;
 /// * `REPLICATION CLIENT` - to use SHOW MASTER STATUS, SHOW SLAVE STATUS, and SHOW BINARY LOGS;
 ///
 /// The connector must also be assigned a unique `server_id` value
+///
+/// MariaDB is supported as well, in which case the MariaDB-specific (including compressed) events
+/// are handled, and a snapshot records its position in the binlog using MariaDB's GTIDs.
 pub(crate) struct MySqlBinlogConnector {
     /// This is the underlying (regular) MySQL connection
     connection: mysql::Conn,
@@ -47,8 +57,18 @@ pub(crate) struct MySqlBinlogConnector {
     /// The binlog "slave" must be assigned a unique `server_id` in the replica topology
     /// if one is not assigned we will use (u32::MAX - 55)
     server_id: Option<u32>,
-    /// If we just want to continue reading the binlog from a previous point
-    next_position: MySqlPosition,
+    /// If we just want to continue reading the binlog from a previous point. When replicating
+    /// from a GTID set, this is `None` until the server tells us which binlog file it is reading
+    /// from.
+    next_position: Option<MySqlPosition>,
+    /// If replicating using GTIDs, the set of transactions we have replicated so far, including
+    /// our progress through the current transaction
+    gtid_position: Option<MySqlGtidPosition>,
+    /// If replicating from MariaDB using GTIDs, the last transaction we have replicated in each
+    /// replication domain, including our progress through the current transaction
+    mariadb_gtid_position: Option<MariaDbGtidPosition>,
+    /// Whether we are replicating from MySQL or MariaDB
+    database_type: DatabaseType,
     /// The GTID of the current transaction. Table modification events will have
     /// the current GTID attached if enabled in mysql.
     current_gtid: Option<u64>,
@@ -68,6 +88,9 @@ impl MySqlBinlogConnector {
     /// but others use CRC32 🤷‍♂️
     async fn register_as_replica(&mut self) -> mysql::Result<()> {
         self.connection.query_drop(CHECKSUM_QUERY).await?;
+        if self.database_type == DatabaseType::MariaDB {
+            self.connection.query_drop(MARIADB_CAPABILITY_QUERY).await?;
+        }
 
         let cmd = mysql_common::packets::ComRegisterSlave::new(self.server_id());
         self.connection.write_command(&cmd).await?;
@@ -78,18 +101,32 @@ impl MySqlBinlogConnector {
 
     /// After we have registered as a replica, we can request the binlog
     async fn request_binlog(&mut self) -> mysql::Result<()> {
-        info!(next_position = %self.next_position, "Starting binlog replication");
-        let filename = self.next_position.binlog_file_name().to_string();
+        if let Some(gtid_position) = &self.gtid_position {
+            return self.request_binlog_gtid(gtid_position.clone()).await;
+        }
+        if let Some(gtid_position) = &self.mariadb_gtid_position {
+            return self
+                .request_binlog_mariadb_gtid(gtid_position.clone())
+                .await;
+        }
+
+        let Some(next_position) = &self.next_position else {
+            return Err(mysql_async::Error::Other(Box::new(internal_err!(
+                "No binlog position to start replicating from"
+            ))));
+        };
+        info!(%next_position, "Starting binlog replication");
+        let filename = next_position.binlog_file_name().to_string();
 
         // If the next position is greater than u32::MAX, we need to re-snapshot
-        if self.next_position.position > u64::from(u32::MAX) {
+        if next_position.position > u64::from(u32::MAX) {
             Err(mysql_async::Error::Other(Box::new(
                 ReadySetError::FullResnapshotNeeded,
             )))?;
         }
         let cmd = mysql_common::packets::ComBinlogDump::new(self.server_id())
             .with_pos(
-                self.next_position
+                next_position
                     .position
                     .try_into()
                     .expect("Impossible binlog start position. Please re-snapshot."),
@@ -101,6 +138,111 @@ impl MySqlBinlogConnector {
         Ok(())
     }
 
+    /// Request the binlog using `COM_BINLOG_DUMP_GTID`, which makes the server send us every
+    /// transaction that is not in the set of transactions we've already replicated. Unlike
+    /// requesting the binlog by file name and position, this works against any server in the
+    /// replication topology, such as a replica that has been promoted to primary.
+    ///
+    /// If we were partway through a transaction, the server will send it to us again from the
+    /// start, and the already-replicated events will be skipped since their offsets are not
+    /// greater than the offset we are resuming from.
+    async fn request_binlog_gtid(&mut self, gtid_position: MySqlGtidPosition) -> mysql::Result<()> {
+        info!(%gtid_position, "Starting binlog replication from GTID set");
+
+        let sids = gtid_position
+            .executed
+            .iter()
+            .map(|(source_id, intervals)| {
+                mysql_common::packets::Sid::new(source_id.0).with_intervals(
+                    intervals
+                        .iter()
+                        // `GnoInterval`s are half-open, but the intervals in a `GtidSet` are
+                        // inclusive
+                        .map(|&(start, end)| {
+                            mysql_common::packets::GnoInterval::new(start, end + 1)
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let cmd = mysql_common::packets::ComBinlogDumpGtid::new(self.server_id()).with_sids(&sids);
+
+        self.connection.write_command(&cmd).await?;
+        self.connection.read_packet().await?;
+        Ok(())
+    }
+
+    /// Request the binlog from a MariaDB server, starting after the last transaction we've
+    /// replicated in each replication domain. MariaDB takes the GTID position to start from as a
+    /// user variable rather than as part of the dump command.
+    ///
+    /// As with MySQL GTIDs, a transaction we were partway through is sent to us again from the
+    /// start.
+    async fn request_binlog_mariadb_gtid(
+        &mut self,
+        gtid_position: MariaDbGtidPosition,
+    ) -> mysql::Result<()> {
+        info!(%gtid_position, "Starting binlog replication from MariaDB GTID position");
+
+        let connect_state = gtid_position.executed().join(",");
+        self.connection
+            .query_drop(format!("SET @slave_connect_state='{connect_state}'"))
+            .await?;
+        self.connection
+            .query_drop("SET @slave_gtid_strict_mode=0")
+            .await?;
+        self.connection
+            .query_drop("SET @slave_gtid_ignore_duplicates=0")
+            .await?;
+
+        let cmd = mysql_common::packets::ComBinlogDump::new(self.server_id()).with_pos(4);
+        self.connection.write_command(&cmd).await?;
+        self.connection.read_packet().await?;
+        Ok(())
+    }
+
+    /// Returns the offset of the last replicated event: the GTID position if replicating from a
+    /// GTID set, or the binlog file and position otherwise
+    fn current_offset(&self) -> mysql::Result<ReplicationOffset> {
+        if let Some(gtid_position) = &self.gtid_position {
+            return Ok(gtid_position.into());
+        }
+        if let Some(gtid_position) = &self.mariadb_gtid_position {
+            return Ok(gtid_position.into());
+        }
+
+        self.next_position
+            .as_ref()
+            .map(ReplicationOffset::from)
+            .ok_or_else(|| {
+                mysql_async::Error::Other(Box::new(internal_err!(
+                    "Binlog position not known before the first ROTATE_EVENT"
+                )))
+            })
+    }
+
+    /// Record that we have replicated another event within the current transaction, and return
+    /// the resulting offset
+    fn advance_offset(&mut self) -> mysql::Result<ReplicationOffset> {
+        if let Some(gtid_position) = &mut self.gtid_position {
+            gtid_position.advance();
+        }
+        if let Some(gtid_position) = &mut self.mariadb_gtid_position {
+            gtid_position.advance();
+        }
+        self.current_offset()
+    }
+
+    /// Record that the current transaction has been committed
+    fn commit_transaction(&mut self) {
+        if let Some(gtid_position) = &mut self.gtid_position {
+            gtid_position.commit_transaction();
+        }
+        if let Some(gtid_position) = &mut self.mariadb_gtid_position {
+            gtid_position.commit_transaction();
+        }
+    }
+
     /// Compute the checksum of the event and compare to the supplied checksum
     fn validate_event_checksum(event: &binlog::events::Event) -> bool {
         if let Ok(Some(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32)) =
@@ -116,18 +258,44 @@ impl MySqlBinlogConnector {
         true
     }
 
-    /// Connect to a given MySQL database and subscribe to the binlog
+    /// Connect to a given MySQL (or MariaDB, depending on `database_type`) database and subscribe
+    /// to the binlog, starting from either a binlog file and position or a GTID set depending
+    /// on the kind of `offset`
     pub(crate) async fn connect<O: Into<mysql::Opts>>(
         mysql_opts: O,
-        next_position: MySqlPosition,
+        offset: ReplicationOffset,
         server_id: Option<u32>,
         enable_statement_logging: bool,
+        database_type: DatabaseType,
     ) -> ReadySetResult<Self> {
+        let (next_position, gtid_position, mariadb_gtid_position) = match offset {
+            ReplicationOffset::MySql(pos) => (Some(pos), None, None),
+            ReplicationOffset::MySqlGtid(mut pos) => {
+                // The server will resend any transaction we were partway through from the start,
+                // so we begin tracking it again from its GTID_EVENT
+                pos.current = None;
+                (None, Some(pos), None)
+            }
+            ReplicationOffset::MariaDbGtid(mut pos) => {
+                if database_type != DatabaseType::MariaDB {
+                    internal!("Cannot replicate from MySQL starting at a MariaDB GTID position")
+                }
+                pos.current = None;
+                (None, None, Some(pos))
+            }
+            ReplicationOffset::Postgres(_) => {
+                internal!("Cannot replicate from MySQL starting at a Postgres offset")
+            }
+        };
+
         let mut connector = MySqlBinlogConnector {
             connection: mysql::Conn::new(mysql_opts).await?,
             reader: binlog::EventStreamReader::new(binlog::consts::BinlogVersion::Version4),
             server_id,
             next_position,
+            gtid_position,
+            mariadb_gtid_position,
+            database_type,
             current_gtid: None,
             enable_statement_logging,
         };
@@ -181,6 +349,15 @@ impl MySqlBinlogConnector {
         }
         let event = self.reader.read(&packet[1..])?;
         assert!(Self::validate_event_checksum(&event)); // TODO: definitely should never fail a CRC check, but what to do if we do?
+
+        if self.database_type == DatabaseType::MariaDB {
+            // Compressed events are turned into their uncompressed equivalent, so they can be
+            // handled just like if compression was disabled
+            if let Some(raw) = mariadb::decompress_event(&event, &packet[1..])? {
+                return Ok(self.reader.read(&raw[..])?);
+            }
+        }
+
         Ok(event)
     }
 
@@ -193,20 +370,35 @@ impl MySqlBinlogConnector {
     pub(crate) async fn next_action_inner(
         &mut self,
         until: Option<&ReplicationOffset>,
-    ) -> mysql::Result<(ReplicationAction, &MySqlPosition)> {
+    ) -> mysql::Result<(ReplicationAction, ReplicationOffset)> {
         use mysql_common::binlog::events;
 
         loop {
             let binlog_event = self.next_event().await?;
 
-            if u64::from(binlog_event.header().log_pos()) < self.next_position.position
-                && self.next_position.position + u64::from(binlog_event.header().event_size())
-                    > u64::from(u32::MAX)
+            if let Some(next_position) = &mut self.next_position {
+                if u64::from(binlog_event.header().log_pos()) < next_position.position
+                    && next_position.position + u64::from(binlog_event.header().event_size())
+                        > u64::from(u32::MAX)
+                {
+                    next_position.position =
+                        u64::from(u32::MAX) + 1 + u64::from(binlog_event.header().log_pos());
+                } else {
+                    next_position.position = u64::from(binlog_event.header().log_pos());
+                }
+            }
+
+            // MariaDB-specific events are unknown to `mysql_common`, so they have to be handled
+            // separately
+            if self.database_type == DatabaseType::MariaDB
+                && binlog_event.header().event_type_raw()
+                    >= mariadb::event_type::ANNOTATE_ROWS_EVENT
             {
-                self.next_position.position =
-                    u64::from(u32::MAX) + 1 + u64::from(binlog_event.header().log_pos());
-            } else {
-                self.next_position.position = u64::from(binlog_event.header().log_pos());
+                self.handle_mariadb_event(&binlog_event)?;
+                if let Some(offset) = self.reached_until(until) {
+                    return Ok((ReplicationAction::LogPosition, offset));
+                }
+                continue;
             }
 
             match binlog_event.header().event_type().map_err(|ev| {
@@ -225,17 +417,19 @@ impl MySqlBinlogConnector {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
 
-                    self.next_position = MySqlPosition::from_file_name_and_position(
-                        ev.name().to_string(),
-                        ev.position(),
-                    )
-                    .map_err(|e| {
-                        mysql_async::Error::Other(Box::new(internal_err!(
-                            "Failed to create MySqlPosition: {e}"
-                        )))
-                    })?;
+                    self.next_position = Some(
+                        MySqlPosition::from_file_name_and_position(
+                            ev.name().to_string(),
+                            ev.position(),
+                        )
+                        .map_err(|e| {
+                            mysql_async::Error::Other(Box::new(internal_err!(
+                                "Failed to create MySqlPosition: {e}"
+                            )))
+                        })?,
+                    );
 
-                    return Ok((ReplicationAction::LogPosition, &self.next_position));
+                    return Ok((ReplicationAction::LogPosition, self.current_offset()?));
                 }
 
                 EventType::QUERY_EVENT => {
@@ -245,6 +439,12 @@ impl MySqlBinlogConnector {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
 
+                    // Transactions on non-transactional storage engines end with a COMMIT query
+                    // rather than an XID_EVENT
+                    if ev.query_raw() == b"COMMIT" {
+                        self.commit_transaction();
+                    }
+
                     let schema = match ev
                         .status_vars()
                         .get_status_var(binlog::consts::StatusVarKey::UpdatedDbNames)
@@ -273,9 +473,11 @@ impl MySqlBinlogConnector {
                         }
                     };
 
+                    // DDL statements are always committed implicitly, in their own transaction
+                    self.commit_transaction();
                     return Ok((
                         ReplicationAction::DdlChange { schema, changes },
-                        &self.next_position,
+                        self.current_offset()?,
                     ));
                 }
 
@@ -296,9 +498,10 @@ impl MySqlBinlogConnector {
                     }
                 }
 
-                EventType::WRITE_ROWS_EVENT => {
-                    // This is the event we get on `INSERT INTO`
-                    let ev: events::WriteRowsEvent = binlog_event.read_event()?;
+                EventType::WRITE_ROWS_EVENT | EventType::WRITE_ROWS_EVENT_V1 => {
+                    // This is the event we get on `INSERT INTO`. The V1 event numbers are used
+                    // from 5.1.16 until mysql-5.6, and are still used by MariaDB.
+                    let ev = read_rows_event(&binlog_event)?;
                     if self.enable_statement_logging {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
@@ -335,13 +538,13 @@ impl MySqlBinlogConnector {
                             actions: inserted_rows,
                             txid: self.current_gtid,
                         },
-                        &self.next_position,
+                        self.advance_offset()?,
                     ));
                 }
 
-                EventType::UPDATE_ROWS_EVENT => {
+                EventType::UPDATE_ROWS_EVENT | EventType::UPDATE_ROWS_EVENT_V1 => {
                     // This is the event we get on `UPDATE`
-                    let ev: events::UpdateRowsEvent = binlog_event.read_event()?;
+                    let ev = read_rows_event(&binlog_event)?;
                     if self.enable_statement_logging {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
@@ -394,13 +597,13 @@ impl MySqlBinlogConnector {
                             actions: updated_rows,
                             txid: self.current_gtid,
                         },
-                        &self.next_position,
+                        self.advance_offset()?,
                     ));
                 }
 
-                EventType::DELETE_ROWS_EVENT => {
+                EventType::DELETE_ROWS_EVENT | EventType::DELETE_ROWS_EVENT_V1 => {
                     // This is the event we get on `ALTER TABLE`
-                    let ev: events::DeleteRowsEvent = binlog_event.read_event()?;
+                    let ev = read_rows_event(&binlog_event)?;
                     if self.enable_statement_logging {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
@@ -438,19 +641,10 @@ impl MySqlBinlogConnector {
                             actions: deleted_rows,
                             txid: self.current_gtid,
                         },
-                        &self.next_position,
+                        self.advance_offset()?,
                     ));
                 }
 
-                EventType::WRITE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
-                EventType::UPDATE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
-                EventType::DELETE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
                 EventType::GTID_EVENT => {
                     // GTID stands for Global Transaction Identifier It is composed of two parts:
                     // SID for Source Identifier, and GNO for Group Number. The basic idea is to
@@ -465,19 +659,27 @@ impl MySqlBinlogConnector {
                         info!(target: "replicator_statement", "{:?}", ev);
                     }
                     self.current_gtid = Some(ev.gno());
+                    if let Some(gtid_position) = &mut self.gtid_position {
+                        gtid_position.start_transaction(Gtid {
+                            source_id: SourceId(ev.sid()),
+                            transaction_id: ev.gno(),
+                        });
+                    }
+                }
+
+                EventType::XID_EVENT => {
+                    // Generated for a commit of a transaction that modifies one or more tables of
+                    // an XA-capable storage engine, such as InnoDB
+                    if self.enable_statement_logging {
+                        info!(target: "replicator_statement", "{:?}", binlog_event);
+                    }
+                    self.commit_transaction();
                 }
 
                 /*
 
                 EventType::ANONYMOUS_GTID_EVENT => {}
 
-                EventType::XID_EVENT => {
-                    // Generated for a commit of a transaction that modifies one or more tables of an XA-capable
-                    // storage engine. Normal transactions are implemented by sending a QUERY_EVENT containing a
-                    // BEGIN statement and a QUERY_EVENT containing a COMMIT statement
-                    // (or a ROLLBACK statement if the transaction is rolled back).
-                }
-
                 EventType::START_EVENT_V3 // Old version of FORMAT_DESCRIPTION_EVENT
                 | EventType::FORMAT_DESCRIPTION_EVENT // A descriptor event that is written to the beginning of each binary log file. This event is used as of MySQL 5.0; it supersedes START_EVENT_V3.
                 | EventType::STOP_EVENT // Written when mysqld stops
@@ -522,13 +724,70 @@ impl MySqlBinlogConnector {
 
             // We didn't get an actionable event, but we still need to check that we haven't reached
             // the until limit
-            if let Some(limit) = until {
-                let limit = MySqlPosition::try_from(limit).expect("Valid binlog limit");
-                if self.next_position >= limit {
-                    return Ok((ReplicationAction::LogPosition, &self.next_position));
+            if let Some(offset) = self.reached_until(until) {
+                return Ok((ReplicationAction::LogPosition, offset));
+            }
+        }
+    }
+
+    /// If `until` is set and we have reached it, returns the current offset
+    fn reached_until(&self, until: Option<&ReplicationOffset>) -> Option<ReplicationOffset> {
+        let limit = until?;
+        // The position is unknown until the first ROTATE_EVENT, which is always sent first
+        let offset = self.current_offset().ok()?;
+        (offset >= *limit).then_some(offset)
+    }
+
+    /// Handle one of the events that only exist in the MariaDB binlog. None of them are actionable
+    /// on their own.
+    fn handle_mariadb_event(&mut self, binlog_event: &binlog::events::Event) -> mysql::Result<()> {
+        match binlog_event.header().event_type_raw() {
+            mariadb::event_type::GTID_EVENT => {
+                // Starts every transaction, and every DDL statement, which is committed
+                // implicitly. Unlike with MySQL, the transaction is also preceded by a BEGIN
+                // query.
+                let gtid = mariadb::read_gtid_event(binlog_event)?;
+                if self.enable_statement_logging {
+                    info!(target: "replicator_statement", %gtid, "MariaDB GTID_EVENT");
+                }
+                self.current_gtid = Some(gtid.sequence_number);
+                if let Some(gtid_position) = &mut self.mariadb_gtid_position {
+                    gtid_position.start_transaction(gtid);
+                }
+            }
+            mariadb::event_type::ANNOTATE_ROWS_EVENT => {
+                // The statement that caused the following rows events
+                if self.enable_statement_logging {
+                    info!(
+                        target: "replicator_statement",
+                        "MariaDB ANNOTATE_ROWS_EVENT: {}",
+                        String::from_utf8_lossy(binlog_event.data())
+                    );
+                }
+            }
+            // BINLOG_CHECKPOINT_EVENT, GTID_LIST_EVENT and START_ENCRYPTION_EVENT only matter to
+            // the server itself
+            ev => {
+                if self.enable_statement_logging {
+                    info!(target: "replicator_statement", "unhandled MariaDB event: {}", ev);
                 }
             }
         }
+        Ok(())
+    }
+}
+
+/// Read any of the rows events, which have different representations in different versions of the
+/// binlog but can all be handled the same way
+fn read_rows_event(
+    binlog_event: &binlog::events::Event,
+) -> mysql::Result<binlog::events::RowsEventData<'_>> {
+    match binlog_event.read_data()? {
+        Some(binlog::events::EventData::RowsEvent(ev)) => Ok(ev),
+        ev => Err(mysql_async::Error::Other(Box::new(internal_err!(
+            "Expected a rows event, got {:?}",
+            ev
+        )))),
     }
 }
 
@@ -642,7 +901,6 @@ impl Connector for MySqlBinlogConnector {
         _: &ReplicationOffset,
         until: Option<&ReplicationOffset>,
     ) -> ReadySetResult<(ReplicationAction, ReplicationOffset)> {
-        let (action, pos) = self.next_action_inner(until).await?;
-        Ok((action, pos.into()))
+        Ok(self.next_action_inner(until).await?)
     }
 }
@@ -0,0 +1,369 @@
+//! Support for the parts of the MariaDB binlog that differ from MySQL's.
+//!
+//! MariaDB's binlog is mostly compatible with MySQL's, but it has its own GTID format, and a few
+//! event types that MySQL doesn't know about (and that `mysql_common` can therefore not parse),
+//! most notably its own `GTID_EVENT` and compressed versions of the query and rows events.
+
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+use mysql_common::binlog;
+use mysql_common::binlog::consts::EventType;
+use replication_offset::mariadb::MariaDbGtid;
+
+/// Length of the common header of every binlog event
+const EVENT_HEADER_LEN: usize = 19;
+/// Offset of the event type within the common event header
+const EVENT_TYPE_OFFSET: usize = 4;
+/// Offset of the event size within the common event header
+const EVENT_SIZE_OFFSET: usize = 9;
+/// Length of the checksum at the end of an event, if checksums are enabled
+const EVENT_CHECKSUM_LEN: usize = 4;
+
+/// The most we allocate up front for decompressed event data. The length the data is supposed to
+/// decompress to is read from the event itself, so it can't be trusted to size the buffer.
+const MAX_DECOMPRESSED_PREALLOC: usize = 1 << 20;
+
+/// Length of the fixed part of a `QUERY_EVENT`, up to and including the length of the status vars
+const QUERY_POST_HEADER_LEN: usize = 13;
+/// Length of the fixed part of a V1 rows event: the table id and the flags
+const ROWS_V1_POST_HEADER_LEN: usize = 8;
+/// Length of the fixed part of a V2 rows event: the table id, the flags, and the length of the
+/// extra data
+const ROWS_V2_POST_HEADER_LEN: usize = 10;
+
+/// Event types that only exist in the MariaDB binlog. See
+/// <https://mariadb.com/kb/en/2-binlog-event-header/> for their descriptions.
+pub(crate) mod event_type {
+    /// The statement that caused the following rows events
+    pub(crate) const ANNOTATE_ROWS_EVENT: u8 = 160;
+    /// Marks the binlog files that are still needed for crash recovery
+    pub(crate) const BINLOG_CHECKPOINT_EVENT: u8 = 161;
+    /// Starts a new transaction (or a standalone statement) and carries its GTID
+    pub(crate) const GTID_EVENT: u8 = 162;
+    /// The GTIDs of the last transactions in each replication domain written to previous binlogs
+    pub(crate) const GTID_LIST_EVENT: u8 = 163;
+    /// Marks the start of the encrypted part of a binlog file
+    pub(crate) const START_ENCRYPTION_EVENT: u8 = 164;
+    pub(crate) const QUERY_COMPRESSED_EVENT: u8 = 165;
+    pub(crate) const WRITE_ROWS_COMPRESSED_EVENT_V1: u8 = 166;
+    pub(crate) const UPDATE_ROWS_COMPRESSED_EVENT_V1: u8 = 167;
+    pub(crate) const DELETE_ROWS_COMPRESSED_EVENT_V1: u8 = 168;
+    pub(crate) const WRITE_ROWS_COMPRESSED_EVENT: u8 = 169;
+    pub(crate) const UPDATE_ROWS_COMPRESSED_EVENT: u8 = 170;
+    pub(crate) const DELETE_ROWS_COMPRESSED_EVENT: u8 = 171;
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Read the GTID of the transaction started by a MariaDB `GTID_EVENT`.
+///
+/// The event data starts with the sequence number and the replication domain of the transaction,
+/// while the id of the server the transaction originated on is the one in the event header.
+pub(crate) fn read_gtid_event(event: &binlog::events::Event) -> io::Result<MariaDbGtid> {
+    let data = event.data();
+    if data.len() < 12 {
+        return Err(invalid_data("MariaDB GTID_EVENT is too short"));
+    }
+    let mut sequence_number = [0; 8];
+    sequence_number.copy_from_slice(&data[..8]);
+    let mut domain_id = [0; 4];
+    domain_id.copy_from_slice(&data[8..12]);
+
+    Ok(MariaDbGtid {
+        domain_id: u32::from_le_bytes(domain_id),
+        server_id: event.header().server_id(),
+        sequence_number: u64::from_le_bytes(sequence_number),
+    })
+}
+
+/// Decompress a buffer compressed by MariaDB's `binlog_buf_compress`.
+///
+/// The first byte has its high bit set, the compression algorithm (of which only zlib exists) in
+/// the next three bits, and the number of bytes used to store the uncompressed length in the low
+/// three bits. It is followed by the uncompressed length in big endian, and then the compressed
+/// data itself.
+fn decompress(buf: &[u8]) -> io::Result<Vec<u8>> {
+    let (&flags, rest) = buf
+        .split_first()
+        .ok_or_else(|| invalid_data("Empty compressed binlog data"))?;
+    if flags & 0x80 == 0 {
+        return Err(invalid_data("Binlog data is not compressed"));
+    }
+    if (flags & 0x70) >> 4 != 0 {
+        return Err(invalid_data(format!(
+            "Unknown binlog compression algorithm {}",
+            (flags & 0x70) >> 4
+        )));
+    }
+    let len_len = usize::from(flags & 0x07);
+    if !(1..=4).contains(&len_len) || rest.len() < len_len {
+        return Err(invalid_data("Invalid compressed binlog data length"));
+    }
+    let len = rest[..len_len]
+        .iter()
+        .fold(0usize, |len, &b| len << 8 | usize::from(b));
+
+    // Reading one byte more than expected is enough to tell that the data is too long
+    let mut res = Vec::with_capacity(len.min(MAX_DECOMPRESSED_PREALLOC));
+    ZlibDecoder::new(&rest[len_len..])
+        .take(len as u64 + 1)
+        .read_to_end(&mut res)?;
+    if res.len() != len {
+        return Err(invalid_data(format!(
+            "Decompressed binlog data has length {}, expected {len}",
+            res.len()
+        )));
+    }
+    Ok(res)
+}
+
+/// Returns the length of a length-encoded integer at the start of `buf`, and its value
+fn read_lenenc_int(buf: &[u8]) -> io::Result<(usize, u64)> {
+    let len = match buf.first() {
+        Some(0xfc) => 3,
+        Some(0xfd) => 4,
+        Some(0xfe) => 9,
+        Some(&b) if b < 0xfb => return Ok((1, u64::from(b))),
+        _ => return Err(invalid_data("Invalid length-encoded integer")),
+    };
+    if buf.len() < len {
+        return Err(invalid_data("Truncated length-encoded integer"));
+    }
+    let mut val = [0; 8];
+    val[..len - 1].copy_from_slice(&buf[1..len]);
+    Ok((len, u64::from_le_bytes(val)))
+}
+
+/// Returns the length of the part of the data of a rows event that precedes the rows themselves,
+/// ie the post-header, the number of columns, and the bitmaps of the columns that are present in
+/// the before and/or after images.
+fn rows_event_prefix_len(data: &[u8], v2: bool, update: bool) -> io::Result<usize> {
+    let mut len = if v2 {
+        if data.len() < ROWS_V2_POST_HEADER_LEN {
+            return Err(invalid_data("Truncated rows event"));
+        }
+        // The length of the extra data includes the two bytes used to store it
+        let extra_len = u16::from_le_bytes([data[8], data[9]]);
+        ROWS_V2_POST_HEADER_LEN + usize::from(extra_len).saturating_sub(2)
+    } else {
+        ROWS_V1_POST_HEADER_LEN
+    };
+
+    let (num_columns_len, num_columns) = read_lenenc_int(data.get(len..).unwrap_or_default())?;
+    let bitmap_len = (num_columns as usize + 7) / 8;
+    len += num_columns_len + if update { 2 * bitmap_len } else { bitmap_len };
+
+    if data.len() < len {
+        return Err(invalid_data("Truncated rows event"));
+    }
+    Ok(len)
+}
+
+/// If `event` is one of MariaDB's compressed events, returns the raw bytes of the equivalent
+/// uncompressed event, which can then be read like any other event. `raw` must be the raw bytes
+/// `event` was read from.
+///
+/// Only the query of a `QUERY_COMPRESSED_EVENT` and the rows of a compressed rows event are
+/// compressed, everything that precedes them is left as is.
+///
+/// The checksum of the resulting event, if any, is left zeroed, so the checksum of the compressed
+/// event should be validated instead.
+pub(crate) fn decompress_event(
+    event: &binlog::events::Event,
+    raw: &[u8],
+) -> io::Result<Option<Vec<u8>>> {
+    use event_type::*;
+
+    let data = event.data();
+    let (uncompressed_type, prefix_len) = match event.header().event_type_raw() {
+        QUERY_COMPRESSED_EVENT => {
+            if data.len() < QUERY_POST_HEADER_LEN {
+                return Err(invalid_data("Truncated QUERY_COMPRESSED_EVENT"));
+            }
+            let db_len = usize::from(data[8]);
+            let status_vars_len = usize::from(u16::from_le_bytes([data[11], data[12]]));
+            // The schema name is followed by a NUL byte
+            let prefix_len = QUERY_POST_HEADER_LEN + status_vars_len + db_len + 1;
+            (EventType::QUERY_EVENT, prefix_len)
+        }
+        WRITE_ROWS_COMPRESSED_EVENT_V1 => (
+            EventType::WRITE_ROWS_EVENT_V1,
+            rows_event_prefix_len(data, false, false)?,
+        ),
+        UPDATE_ROWS_COMPRESSED_EVENT_V1 => (
+            EventType::UPDATE_ROWS_EVENT_V1,
+            rows_event_prefix_len(data, false, true)?,
+        ),
+        DELETE_ROWS_COMPRESSED_EVENT_V1 => (
+            EventType::DELETE_ROWS_EVENT_V1,
+            rows_event_prefix_len(data, false, false)?,
+        ),
+        WRITE_ROWS_COMPRESSED_EVENT => (
+            EventType::WRITE_ROWS_EVENT,
+            rows_event_prefix_len(data, true, false)?,
+        ),
+        UPDATE_ROWS_COMPRESSED_EVENT => (
+            EventType::UPDATE_ROWS_EVENT,
+            rows_event_prefix_len(data, true, true)?,
+        ),
+        DELETE_ROWS_COMPRESSED_EVENT => (
+            EventType::DELETE_ROWS_EVENT,
+            rows_event_prefix_len(data, true, false)?,
+        ),
+        _ => return Ok(None),
+    };
+
+    if data.len() < prefix_len || raw.len() < EVENT_HEADER_LEN {
+        return Err(invalid_data("Truncated compressed event"));
+    }
+    let uncompressed = decompress(&data[prefix_len..])?;
+
+    let checksum_len = if event.checksum().is_some() {
+        EVENT_CHECKSUM_LEN
+    } else {
+        0
+    };
+    let event_size = EVENT_HEADER_LEN + prefix_len + uncompressed.len() + checksum_len;
+    let event_size = u32::try_from(event_size)
+        .map_err(|_| invalid_data("Decompressed binlog event is too large"))?;
+
+    let mut res = Vec::with_capacity(event_size as usize);
+    res.extend_from_slice(&raw[..EVENT_HEADER_LEN]);
+    res[EVENT_TYPE_OFFSET] = uncompressed_type as u8;
+    res[EVENT_SIZE_OFFSET..EVENT_SIZE_OFFSET + 4].copy_from_slice(&event_size.to_le_bytes());
+    res.extend_from_slice(&data[..prefix_len]);
+    res.extend_from_slice(&uncompressed);
+    res.resize(event_size as usize, 0);
+    Ok(Some(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    /// Compress `buf` the way MariaDB's `binlog_buf_compress` does
+    fn compress(buf: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(buf).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut res = vec![0x80 | 4];
+        res.extend_from_slice(&(buf.len() as u32).to_be_bytes());
+        res.extend(compressed);
+        res
+    }
+
+    /// Build the raw bytes of an event without a checksum
+    fn raw_event(event_type: u8, server_id: u32, data: &[u8]) -> Vec<u8> {
+        let mut res = vec![0; 4];
+        res.push(event_type);
+        res.extend_from_slice(&server_id.to_le_bytes());
+        res.extend_from_slice(&((EVENT_HEADER_LEN + data.len()) as u32).to_le_bytes());
+        res.extend_from_slice(&[0; 6]);
+        res.extend_from_slice(data);
+        res
+    }
+
+    fn read_event(raw: &[u8]) -> binlog::events::Event {
+        binlog::EventStreamReader::new(binlog::consts::BinlogVersion::Version4)
+            .read(raw)
+            .unwrap()
+    }
+
+    #[test]
+    fn decompress_round_trip() {
+        let data = b"INSERT INTO t VALUES (1), (2), (3)".repeat(10);
+        assert_eq!(decompress(&compress(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn decompress_rejects_uncompressed_data() {
+        decompress(b"\x04abcd").unwrap_err();
+        decompress(&[]).unwrap_err();
+    }
+
+    #[test]
+    fn decompress_rejects_wrong_length() {
+        let data = b"INSERT INTO t VALUES (1), (2), (3)".repeat(10);
+        let mut compressed = compress(&data);
+        // Claim the data is much longer than it is
+        compressed[1..5].copy_from_slice(&u32::MAX.to_be_bytes());
+        decompress(&compressed).unwrap_err();
+        // ...or shorter
+        compressed[1..5].copy_from_slice(&10u32.to_be_bytes());
+        decompress(&compressed).unwrap_err();
+    }
+
+    #[test]
+    fn gtid_event() {
+        let mut data = 100u64.to_le_bytes().to_vec();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(0);
+        let event = read_event(&raw_event(event_type::GTID_EVENT, 3, &data));
+
+        assert_eq!(
+            read_gtid_event(&event).unwrap(),
+            MariaDbGtid {
+                domain_id: 2,
+                server_id: 3,
+                sequence_number: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn decompress_query_event() {
+        let query = b"CREATE TABLE t (x int)";
+        let mut data = vec![0; QUERY_POST_HEADER_LEN];
+        // Schema name length
+        data[8] = 2;
+        data.extend_from_slice(b"db\0");
+        let prefix_len = data.len();
+        data.extend(compress(query));
+        let raw = raw_event(event_type::QUERY_COMPRESSED_EVENT, 1, &data);
+        let event = read_event(&raw);
+
+        let decompressed = decompress_event(&event, &raw).unwrap().unwrap();
+        let event = read_event(&decompressed);
+        assert_eq!(event.header().event_type().unwrap(), EventType::QUERY_EVENT);
+        assert_eq!(&event.data()[..prefix_len], &data[..prefix_len]);
+        assert_eq!(&event.data()[prefix_len..], query);
+    }
+
+    #[test]
+    fn decompress_rows_event_prefix() {
+        let rows = b"some row data";
+        // Table id, flags, the number of columns, and the bitmaps of the before and after images
+        let mut data = vec![1, 0, 0, 0, 0, 0, 0, 0, 3, 0b111, 0b111];
+        let prefix_len = data.len();
+        data.extend(compress(rows));
+        let raw = raw_event(event_type::UPDATE_ROWS_COMPRESSED_EVENT_V1, 1, &data);
+        let event = read_event(&raw);
+
+        let decompressed = decompress_event(&event, &raw).unwrap().unwrap();
+        assert_eq!(
+            decompressed[EVENT_TYPE_OFFSET],
+            EventType::UPDATE_ROWS_EVENT_V1 as u8
+        );
+        assert_eq!(
+            &decompressed[EVENT_HEADER_LEN..EVENT_HEADER_LEN + prefix_len],
+            &data[..prefix_len]
+        );
+        assert_eq!(&decompressed[EVENT_HEADER_LEN + prefix_len..], rows);
+    }
+
+    #[test]
+    fn uncompressed_events_are_left_alone() {
+        let raw = raw_event(EventType::XID_EVENT as u8, 1, &[0; 8]);
+        let event = read_event(&raw);
+        assert!(decompress_event(&event, &raw).unwrap().is_none());
+    }
+}
@@ -1,4 +1,5 @@
 mod connector;
+mod mariadb;
 mod snapshot;
 
 pub(crate) use connector::MySqlBinlogConnector;
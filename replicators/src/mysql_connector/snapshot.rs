@@ -1,12 +1,14 @@
+use std::collections::{HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{self, Display};
 use std::future;
 use std::time::Instant;
 
+use database_utils::DatabaseType;
 use futures::future::TryFutureExt;
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use itertools::Itertools;
 use metrics::register_gauge;
 use mysql::prelude::Queryable;
@@ -16,14 +18,19 @@ use nom_sql::{DialectDisplay, NonReplicatedRelation, NotReplicatedReason, Relati
 use readyset_client::metrics::recorded;
 use readyset_client::recipe::changelist::{Change, ChangeList};
 use readyset_data::Dialect;
-use readyset_errors::{internal_err, ReadySetResult};
-use replication_offset::mysql::MySqlPosition;
-use replication_offset::{ReplicationOffset, ReplicationOffsets};
+use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
+use replication_offset::mariadb::MariaDbGtidPosition;
+use replication_offset::mysql::{GtidSet, MySqlGtidPosition, MySqlPosition};
+use replication_offset::{ReplicationOffset, ReplicationOffsets, SnapshotProgress};
 use tokio::task::JoinHandle;
-use tracing::{debug, error, info, info_span, warn};
+use tracing::{debug, error, info, info_span, trace, warn};
 use tracing_futures::Instrument;
 
 use crate::db_util::DatabaseSchemas;
+use crate::snapshot_chunks::{
+    key_ranges, remaining_ranges, roll_back_chunk, ChunkProgress, KeyRange, MAX_CHUNK_ATTEMPTS,
+    MAX_CONCURRENT_CHUNKS, MIN_ROWS_TO_CHUNK,
+};
 use crate::table_filter::TableFilter;
 
 const BATCH_SIZE: usize = 1000; // How many queries to buffer before pushing to ReadySet
@@ -40,6 +47,19 @@ fn log_err<E: Error>(err: E) -> E {
     err
 }
 
+/// How to split a table into chunks of the values of its primary key when snapshotting it
+#[derive(Debug)]
+struct TableChunks {
+    /// The name of the primary key column
+    key: String,
+    /// The index of the primary key column within the table's rows
+    key_idx: usize,
+    /// The ranges of values of the primary key to copy in each chunk
+    ranges: Vec<KeyRange>,
+    /// An estimate of the number of rows in the table
+    rows: u64,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum TableKind {
     BaseTable,
@@ -51,6 +71,8 @@ pub(crate) struct MySqlReplicator {
     pub(crate) pool: mysql::Pool,
     /// Filters out the desired tables to snapshot and replicate
     pub(crate) table_filter: TableFilter,
+    /// Whether we are replicating from MySQL or MariaDB
+    pub(crate) database_type: DatabaseType,
 }
 
 /// Get the list of tables defined in the database
@@ -301,7 +323,7 @@ impl MySqlReplicator {
         let binlog_position = self.get_binlog_position().await?;
 
         noria
-            .set_schema_replication_offset(Some(&binlog_position.into()))
+            .set_schema_replication_offset(Some(&binlog_position))
             .await?;
 
         let table_list = replicated_tables
@@ -346,9 +368,20 @@ impl MySqlReplicator {
         })
     }
 
+    /// Returns `true` if the server has `gtid_mode` set to `ON`, meaning every transaction in the
+    /// binlog is assigned a GTID.
+    async fn gtid_mode_enabled(conn: &mut mysql::Conn) -> mysql::Result<bool> {
+        Ok(conn
+            .query_first::<String, _>("SELECT @@GLOBAL.gtid_mode")
+            .await?
+            .map_or(false, |mode| mode.eq_ignore_ascii_case("ON")))
+    }
+
     /// Use the SHOW MASTER STATUS statement to determine the current binary log
-    /// file name and position.
-    async fn get_binlog_position(&self) -> mysql::Result<MySqlPosition> {
+    /// file name and position, or the set of executed GTIDs if GTID mode is enabled. On MariaDB,
+    /// the position is always determined by `gtid_binlog_pos`, the GTID of the last transaction
+    /// written to the binlog in each replication domain.
+    async fn get_binlog_position(&self) -> mysql::Result<ReplicationOffset> {
         let mut conn = self.pool.get_conn().await?;
         let query = "SHOW MASTER STATUS";
         let pos: mysql::Row = conn.query_first(query).await?.ok_or_else(|| {
@@ -359,10 +392,35 @@ impl MySqlReplicator {
             )))
         })?;
 
+        if self.database_type == DatabaseType::MariaDB {
+            let gtid_pos: String = conn
+                .query_first("SELECT @@GLOBAL.gtid_binlog_pos")
+                .await?
+                .unwrap_or_default();
+            return gtid_pos
+                .parse::<MariaDbGtidPosition>()
+                .map(Into::into)
+                .map_err(|err| mysql_async::Error::Other(Box::new(err)));
+        }
+
+        // MariaDB (handled above) uses a different GTID format, and doesn't have this variable
+        if Self::gtid_mode_enabled(&mut conn).await? {
+            let gtid_set: String = pos.get("Executed_Gtid_Set").ok_or_else(|| {
+                mysql_async::Error::Other(Box::new(internal_err!(
+                    "SHOW MASTER STATUS did not return an Executed_Gtid_Set column"
+                )))
+            })?;
+            let gtid_set = gtid_set
+                .parse::<GtidSet>()
+                .map_err(|err| mysql_async::Error::Other(Box::new(err)))?;
+            return Ok(MySqlGtidPosition::from(gtid_set).into());
+        }
+
         let file: String = pos.get(0).expect("Binlog file name");
         let offset: u64 = pos.get(1).expect("Binlog offset");
 
         MySqlPosition::from_file_name_and_position(file, offset)
+            .map(Into::into)
             .map_err(|err| mysql_async::Error::Other(Box::new(err)))
     }
 
@@ -377,6 +435,92 @@ impl MySqlReplicator {
         Ok(conn)
     }
 
+    /// If `table` is large enough to be worth snapshotting in chunks and its primary key is made up
+    /// of a single integer column, returns how to split it into chunks of that column's values.
+    ///
+    /// This must be called while holding the lock on the table, using the connection that holds it,
+    /// so that the values we see are the same as the ones seen by the transactions that copy the
+    /// chunks.
+    ///
+    /// If the table was already part-way through being snapshotted in chunks, it's always split by
+    /// the same key, and only the ranges which weren't copied yet are returned.
+    async fn chunk_ranges(
+        conn: &mut mysql::Conn,
+        table: &Relation,
+        progress: Option<&SnapshotProgress>,
+    ) -> ReadySetResult<Option<TableChunks>> {
+        let Some(schema) = &table.schema else {
+            return Ok(None);
+        };
+
+        // Only an estimate, but that's all we need to decide whether chunking is worthwhile
+        let rows = conn
+            .exec_first::<Option<u64>, _, _>(
+                "SELECT TABLE_ROWS FROM information_schema.TABLES
+                 WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?",
+                (schema.as_str(), table.name.as_str()),
+            )
+            .await?
+            .flatten()
+            .unwrap_or(0);
+        if progress.is_none() && rows < MIN_ROWS_TO_CHUNK {
+            return Ok(None);
+        }
+
+        let key_columns: Vec<(String, u64, String, String)> = conn
+            .exec(
+                "SELECT k.COLUMN_NAME, c.ORDINAL_POSITION, c.DATA_TYPE, c.COLUMN_TYPE
+                 FROM information_schema.KEY_COLUMN_USAGE k
+                 JOIN information_schema.COLUMNS c
+                 ON c.TABLE_SCHEMA = k.TABLE_SCHEMA
+                 AND c.TABLE_NAME = k.TABLE_NAME
+                 AND c.COLUMN_NAME = k.COLUMN_NAME
+                 WHERE k.TABLE_SCHEMA = ? AND k.TABLE_NAME = ? AND k.CONSTRAINT_NAME = 'PRIMARY'",
+                (schema.as_str(), table.name.as_str()),
+            )
+            .await?;
+        let [(key, position, data_type, column_type)] = key_columns.as_slice() else {
+            return Ok(None);
+        };
+        // Unsigned bigint values don't necessarily fit in the signed integers we split tables by
+        let is_integer = match data_type.to_ascii_lowercase().as_str() {
+            "tinyint" | "smallint" | "mediumint" | "int" => true,
+            "bigint" => !column_type.to_ascii_lowercase().contains("unsigned"),
+            _ => false,
+        };
+        if !is_integer
+            || *position == 0
+            || progress.is_some_and(|progress| progress.key != *position as usize - 1)
+        {
+            return Ok(None);
+        }
+
+        let query = format!(
+            "SELECT CAST(MIN(`{key}`) AS SIGNED), CAST(MAX(`{key}`) AS SIGNED) FROM {}",
+            table.display(nom_sql::Dialect::MySQL)
+        );
+        let ranges = match conn
+            .query_first::<(Option<i64>, Option<i64>), _>(query)
+            .await?
+        {
+            Some((Some(min), Some(max))) => key_ranges(min, max, rows),
+            _ => vec![],
+        };
+
+        let ranges = match progress {
+            Some(progress) => remaining_ranges(ranges, &progress.chunks),
+            None if ranges.len() < 2 => return Ok(None),
+            None => ranges,
+        };
+
+        Ok(Some(TableChunks {
+            key: key.clone(),
+            key_idx: *position as usize - 1,
+            ranges,
+            rows,
+        }))
+    }
+
     /// Replicate a single table from the provided TableDumper and into ReadySet by
     /// converting every MySQL row into ReadySet row and calling `insert_many` in batches
     async fn replicate_table(
@@ -461,6 +605,182 @@ impl MySqlReplicator {
         Ok(())
     }
 
+    /// Replicate a single table into ReadySet in chunks of the values of its primary key (see
+    /// [`Self::chunk_ranges`]), using each of the provided `dumpers` to copy one chunk at a time.
+    /// All of the dumpers must have been created while holding the same lock on the table, so that
+    /// they all see the same view of it.
+    ///
+    /// If copying a chunk fails, the rows it inserted are removed and it is retried using another
+    /// dumper (up to [`MAX_CHUNK_ATTEMPTS`] times), without having to copy the chunks that already
+    /// finished again. Each chunk is recorded in `progress` once it finishes, so that if the
+    /// replicator restarts it can resume from there.
+    ///
+    /// Returns the replication offset to set for the table (see [`ChunkProgress::finish`]).
+    async fn replicate_table_in_chunks(
+        dumpers: Vec<TableDumper>,
+        chunks: TableChunks,
+        mut progress: ChunkProgress,
+        mut table_mutator: readyset_client::Table,
+        snapshot_report_interval_secs: u16,
+        repl_offset: &ReplicationOffset,
+    ) -> ReadySetResult<ReplicationOffset> {
+        let TableChunks {
+            key,
+            key_idx,
+            ranges,
+            rows: nrows,
+        } = chunks;
+        let mut cnt = 0;
+
+        info!(
+            rows = %nrows,
+            chunks = ranges.len(),
+            copied_chunks = progress.chunks().len(),
+            "Snapshotting started"
+        );
+
+        // Entering snapshot mode clears out the table, so we can't do that if we're resuming from
+        // the chunks we already copied
+        if progress.chunks().is_empty() {
+            table_mutator.set_snapshot_mode(true).await?;
+        }
+        let progress_percentage_metric: metrics::Gauge = register_gauge!(
+            recorded::REPLICATOR_SNAPSHOT_PERCENT,
+            "name" => table_mutator.table_name().display(nom_sql::Dialect::MySQL).to_string(),
+        );
+
+        let start_time = Instant::now();
+        let mut last_report_time = start_time;
+        let snapshot_report_interval_secs = snapshot_report_interval_secs as u64;
+
+        let mut idle_dumpers = dumpers;
+        let mut pending: VecDeque<(KeyRange, usize)> =
+            ranges.into_iter().map(|range| (range, 1)).collect();
+        let mut copying = FuturesUnordered::new();
+        loop {
+            while let Some((range, attempt)) = pending.pop_front() {
+                let Some(dumper) = idle_dumpers.pop() else {
+                    pending.push_front((range, attempt));
+                    break;
+                };
+                copying.push(
+                    Self::replicate_chunk(dumper, &key, key_idx, range, table_mutator.clone())
+                        .map(move |(dumper, res)| (dumper, range, attempt, res)),
+                );
+            }
+
+            let Some((dumper, range, attempt, res)) = copying.next().await else {
+                if pending.is_empty() {
+                    break;
+                }
+                progress_percentage_metric.set(0.0);
+                return Err(log_err(ReadySetError::ReplicationFailed(format!(
+                    "No transactions left to copy {} remaining chunks of table {} with",
+                    pending.len(),
+                    table_mutator.table_name().display(nom_sql::Dialect::MySQL)
+                ))));
+            };
+            match res {
+                Ok(Ok(rows)) => {
+                    debug!(?range, %rows, "Finished copying chunk");
+                    cnt += rows;
+                    idle_dumpers.push(dumper);
+                    progress.record(range, repl_offset).await?;
+                }
+                Ok(Err(error)) if attempt < MAX_CHUNK_ATTEMPTS => {
+                    // The dumper's transaction might not be usable anymore, and we can't start a
+                    // new one with the same view of the table, so retry the chunk using one of
+                    // the others
+                    warn!(%error, ?range, %attempt, "Retrying chunk");
+                    drop(dumper);
+                    pending.push_back((range, attempt + 1));
+                }
+                Ok(Err(error)) | Err(error) => {
+                    progress_percentage_metric.set(0.0);
+                    return Err(log_err(error));
+                }
+            }
+
+            if snapshot_report_interval_secs != 0
+                && last_report_time.elapsed().as_secs() > snapshot_report_interval_secs
+            {
+                last_report_time = Instant::now();
+                crate::log_snapshot_progress(
+                    start_time.elapsed(),
+                    cnt as i64,
+                    nrows as i64,
+                    &progress_percentage_metric,
+                );
+            }
+        }
+
+        info!(rows_replicated = %cnt, "Snapshotting finished");
+        progress_percentage_metric.set(100.0);
+
+        progress.finish(repl_offset).await
+    }
+
+    /// Copy the rows of a table whose `key` column is within `range` to ReadySet using `dumper`,
+    /// returning the dumper along with the number of rows copied.
+    ///
+    /// If copying the chunk fails but the rows it inserted could be removed again, returns the
+    /// error in an inner `Err` so that the chunk can be retried.
+    async fn replicate_chunk(
+        mut dumper: TableDumper,
+        key: &str,
+        key_idx: usize,
+        range: KeyRange,
+        mut table_mutator: readyset_client::Table,
+    ) -> (TableDumper, ReadySetResult<ReadySetResult<u64>>) {
+        let mut keys = vec![];
+        let res = match Self::replicate_chunk_rows(
+            &mut dumper,
+            key,
+            key_idx,
+            range,
+            &mut table_mutator,
+            &mut keys,
+        )
+        .await
+        {
+            Ok(rows) => Ok(Ok(rows)),
+            Err(error) => roll_back_chunk(&mut table_mutator, range, keys, error).await,
+        };
+        (dumper, res)
+    }
+
+    /// Copy the rows for a single chunk (see [`Self::replicate_chunk`]), adding the key of each
+    /// row to `keys` before it's inserted
+    async fn replicate_chunk_rows(
+        dumper: &mut TableDumper,
+        key: &str,
+        key_idx: usize,
+        range: KeyRange,
+        table_mutator: &mut readyset_client::Table,
+        keys: &mut Vec<readyset_data::DfValue>,
+    ) -> ReadySetResult<u64> {
+        let mut cnt = 0;
+        let mut row_stream = dumper.stream_chunk(key, range).await?;
+        let mut rows = Vec::with_capacity(BATCH_SIZE);
+
+        while let Some(row) = row_stream.next().await? {
+            keys.extend(row.get(key_idx).cloned());
+            rows.push(row);
+            cnt += 1;
+
+            if rows.len() == BATCH_SIZE {
+                let send_rows = std::mem::replace(&mut rows, Vec::with_capacity(BATCH_SIZE));
+                table_mutator.insert_many(send_rows).await?;
+            }
+        }
+
+        if !rows.is_empty() {
+            table_mutator.insert_many(rows).await?;
+        }
+
+        Ok(cnt)
+    }
+
     /// This function replicates an entire MySQL database into a clean
     /// ReadySet deployment.
     ///
@@ -472,13 +792,15 @@ impl MySqlReplicator {
     /// * `extend_recipe`: Replicate and install the recipe (`CREATE TABLE` ...; `CREATE VIEW` ...;)
     ///   in addition to the rows
     /// If `full_snapshot` is set to `true`, *all* tables will be snapshotted, even those that
-    /// already have replication offsets in ReadySet.
+    /// already have replication offsets in ReadySet. Otherwise, the tables in `resnapshot_tables`
+    /// are snapshotted again (replacing their contents) along with any that don't.
     pub(crate) async fn snapshot_to_noria(
         mut self,
         noria: &mut readyset_client::ReadySetHandle,
         db_schemas: &mut DatabaseSchemas,
         snapshot_report_interval_secs: u16,
         full_snapshot: bool,
+        resnapshot_tables: &HashSet<Relation>,
     ) -> ReadySetResult<()> {
         let result = self
             .replicate_to_noria_with_table_locks(
@@ -486,6 +808,7 @@ impl MySqlReplicator {
                 db_schemas,
                 snapshot_report_interval_secs,
                 full_snapshot,
+                resnapshot_tables,
             )
             .await;
 
@@ -505,6 +828,7 @@ impl MySqlReplicator {
         db_schemas: &mut DatabaseSchemas,
         snapshot_report_interval_secs: u16,
         full_snapshot: bool,
+        resnapshot_tables: &HashSet<Relation>,
     ) -> ReadySetResult<()> {
         // NOTE: There are two ways to prevent DDL changes in MySQL:
         // `FLUSH TABLES WITH READ LOCK` or `LOCK INSTANCE FOR BACKUP`. Both are not
@@ -535,6 +859,14 @@ impl MySqlReplicator {
             self.drop_all_tables(noria).await?;
         }
 
+        // Tables that were part-way through being snapshotted in chunks can carry on from where
+        // they left off, unless we've dropped them or need to replace their contents
+        for table in noria.snapshot_progress().await?.into_keys() {
+            if full_snapshot || resnapshot_tables.contains(&table) {
+                noria.set_snapshot_progress(&table, None).await?;
+            }
+        }
+
         let (_meta_lock, table_list) = self
             .load_recipe_with_meta_lock(noria, db_schemas)
             .await
@@ -547,19 +879,24 @@ impl MySqlReplicator {
             noria,
             table_list,
             &replication_offsets,
+            resnapshot_tables,
             snapshot_report_interval_secs,
         )
         .await
     }
 
     /// Spawns a new tokio task that replicates a given table to noria, returning
-    /// the join handle
+    /// the join handle. The task returns the replication offset to set for the table once it's
+    /// done.
+    ///
+    /// If `truncate` is set, the table's existing contents are replaced.
     async fn dumper_task_for_table(
         &mut self,
         noria: &mut readyset_client::ReadySetHandle,
         table: Relation,
+        truncate: bool,
         snapshot_report_interval_secs: u16,
-    ) -> ReadySetResult<JoinHandle<(Relation, ReplicationOffset, ReadySetResult<()>)>> {
+    ) -> ReadySetResult<JoinHandle<(Relation, ReadySetResult<ReplicationOffset>)>> {
         let span = info_span!(
             "Snapshotting table",
             table = %table.display(nom_sql::Dialect::MySQL)
@@ -568,26 +905,74 @@ impl MySqlReplicator {
         let mut read_lock = self.lock_table(&table).await?;
         // We acquire the position for each table individually, since it changes from
         // one lock to the other
-        let repl_offset = ReplicationOffset::from(self.get_binlog_position().await?);
+        let repl_offset = self.get_binlog_position().await?;
         span.in_scope(|| info!("Snapshotting table"));
 
+        let progress = noria.snapshot_progress().await?.remove(&table);
+        let chunks = Self::chunk_ranges(&mut read_lock, &table, progress.as_ref())
+            .instrument(span.clone())
+            .await?;
         let dumper = self.dump_table(&table).instrument(span.clone()).await?;
+        // Large tables are copied in chunks by several transactions at once, all of which need to
+        // be started while we still hold the lock so that they see the same view of the table
+        let mut chunk_dumpers = vec![];
+        if let Some(chunks) = &chunks {
+            span.in_scope(|| info!(chunks = chunks.ranges.len(), "Snapshotting table in chunks"));
+            for _ in 1..MAX_CONCURRENT_CHUNKS.min(chunks.ranges.len()) {
+                chunk_dumpers.push(self.dump_table(&table).instrument(span.clone()).await?);
+            }
+        }
 
-        // At this point we have a transaction that will see *that* table at *this* binlog
+        // At this point we have transactions that will see *that* table at *this* binlog
         // position, so we can drop the read lock
         read_lock.query_drop("UNLOCK TABLES").await?;
         span.in_scope(|| info!("Read lock released"));
 
-        let table_mutator = noria.table(table.clone()).instrument(span.clone()).await?;
+        let mut table_mutator = noria.table(table.clone()).instrument(span.clone()).await?;
+        if truncate {
+            // The table is being resnapshotted in place, so clear out its old contents first
+            span.in_scope(|| trace!("Truncating table"));
+            table_mutator.truncate().await?;
+        }
 
+        let mut noria = noria.clone();
         Ok(tokio::spawn(async move {
-            (
-                table,
-                repl_offset,
-                Self::replicate_table(dumper, table_mutator, snapshot_report_interval_secs)
+            let res = match chunks {
+                Some(chunks) => {
+                    chunk_dumpers.push(dumper);
+                    let progress = ChunkProgress::new(
+                        noria,
+                        table.clone(),
+                        progress.unwrap_or_else(|| SnapshotProgress::new(chunks.key_idx)),
+                    );
+                    Self::replicate_table_in_chunks(
+                        chunk_dumpers,
+                        chunks,
+                        progress,
+                        table_mutator,
+                        snapshot_report_interval_secs,
+                        &repl_offset,
+                    )
                     .instrument(span)
-                    .await,
-            )
+                    .await
+                }
+                None => {
+                    async {
+                        if progress.is_some() {
+                            // The table can't be split by the same key as before anymore, so we
+                            // have to start over
+                            table_mutator.truncate().await?;
+                            noria.set_snapshot_progress(&table, None).await?;
+                        }
+                        Self::replicate_table(dumper, table_mutator, snapshot_report_interval_secs)
+                            .await?;
+                        Ok(repl_offset)
+                    }
+                    .instrument(span)
+                    .await
+                }
+            };
+            (table, res)
         }))
     }
 
@@ -597,6 +982,7 @@ impl MySqlReplicator {
         noria: &mut readyset_client::ReadySetHandle,
         mut table_list: Vec<Relation>,
         replication_offsets: &ReplicationOffsets,
+        resnapshot_tables: &HashSet<Relation>,
         snapshot_report_interval_secs: u16,
     ) -> ReadySetResult<()> {
         let mut replication_tasks = FuturesUnordered::new();
@@ -606,15 +992,21 @@ impl MySqlReplicator {
         // We pop front because we add the tables before the views, and the views depend on the
         // tables. TODO: do we need to fully finish tables before views?
         while let Some(table) = table_list.pop() {
-            if replication_offsets.has_table(&table) {
+            let resnapshot = resnapshot_tables.contains(&table);
+            if replication_offsets.has_table(&table) && !resnapshot {
                 info!(
                     table = %table.display(nom_sql::Dialect::MySQL),
                     "Replication offset already exists for table, skipping snapshot"
                 );
             } else {
                 replication_tasks.push(
-                    self.dumper_task_for_table(noria, table, snapshot_report_interval_secs)
-                        .await?,
+                    self.dumper_task_for_table(
+                        noria,
+                        table,
+                        resnapshot,
+                        snapshot_report_interval_secs,
+                    )
+                    .await?,
                 );
             }
 
@@ -626,7 +1018,7 @@ impl MySqlReplicator {
         while let Some(task_result) = replication_tasks.next().await {
             // The unwrap is for the join handle in that case
             match task_result.unwrap() {
-                (table, repl_offset, Ok(())) => {
+                (table, Ok(repl_offset)) => {
                     let mut noria_table = noria.table(table.clone()).await?;
                     compacting_tasks.push(tokio::spawn(async move {
                         let span = info_span!(
@@ -651,15 +1043,22 @@ impl MySqlReplicator {
                         ReadySetResult::Ok(())
                     }));
                 }
-                (table, _, Err(err)) => {
+                (table, Err(err)) => {
+                    // Any chunks that were already copied are kept, so the retry resumes from
+                    // them
                     error!(
                         table = %table.display(nom_sql::Dialect::MySQL),
                         error = %err,
                         "Replication failed, retrying"
                     );
                     replication_tasks.push(
-                        self.dumper_task_for_table(noria, table, snapshot_report_interval_secs)
-                            .await?,
+                        self.dumper_task_for_table(
+                            noria,
+                            table,
+                            false,
+                            snapshot_report_interval_secs,
+                        )
+                        .await?,
                     );
                 }
             }
@@ -667,15 +1066,21 @@ impl MySqlReplicator {
             // If still have tables to snapshot add them to the task list
             while replication_tasks.len() < MAX_SNAPSHOT_BATCH && !table_list.is_empty() {
                 let table = table_list.pop().expect("Not empty");
-                if replication_offsets.has_table(&table) {
+                let resnapshot = resnapshot_tables.contains(&table);
+                if replication_offsets.has_table(&table) && !resnapshot {
                     info!(
                         table = %table.display(nom_sql::Dialect::MySQL),
                         "Replication offset already exists for table, skipping snapshot"
                     );
                 } else {
                     replication_tasks.push(
-                        self.dumper_task_for_table(noria, table, snapshot_report_interval_secs)
-                            .await?,
+                        self.dumper_task_for_table(
+                            noria,
+                            table,
+                            resnapshot,
+                            snapshot_report_interval_secs,
+                        )
+                        .await?,
                     );
                 }
             }
@@ -730,6 +1135,21 @@ impl TableDumper {
             query: self.tx.exec_iter(&self.query, ()).await?,
         })
     }
+
+    /// Get a stream of the rows whose `key` column is within `range`
+    pub(crate) async fn stream_chunk(
+        &mut self,
+        key: &str,
+        range: KeyRange,
+    ) -> mysql::Result<TableStream<'_>> {
+        let query = format!(
+            "{} where `{key}` between {} and {}",
+            self.query, range.start, range.end
+        );
+        Ok(TableStream {
+            query: self.tx.exec_iter(query, ()).await?,
+        })
+    }
 }
 
 // Just another helper struct to make it streamable
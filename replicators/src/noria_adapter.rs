@@ -3,7 +3,7 @@ use std::mem;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use database_utils::{DatabaseURL, UpstreamConfig};
+use database_utils::{DatabaseType, DatabaseURL, UpstreamConfig};
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
 use failpoint_macros::set_failpoint;
 use metrics::{counter, histogram};
@@ -22,7 +22,7 @@ use readyset_client::{ReadySetHandle, Table, TableOperation};
 use readyset_data::Dialect;
 use readyset_errors::{internal_err, set_failpoint_return_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::{TelemetryBuilder, TelemetryEvent, TelemetrySender};
-use replication_offset::{ReplicationOffset, ReplicationOffsets};
+use replication_offset::{ReplicationOffset, ReplicationOffsets, SnapshotProgress};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 use {mysql_async as mysql, tokio_postgres as pgsql};
@@ -33,6 +33,7 @@ use crate::postgres_connector::{
     drop_publication, drop_readyset_schema, drop_replication_slot, PostgresReplicator,
     PostgresWalConnector, PUBLICATION_NAME, REPLICATION_SLOT,
 };
+use crate::snapshot_chunks::included_in_snapshot;
 use crate::table_filter::TableFilter;
 use crate::{ControllerMessage, ReplicatorMessage};
 
@@ -57,6 +58,12 @@ pub(crate) enum ReplicationAction {
         schema: String,
         changes: Vec<Change>,
     },
+    /// The contents of the given table changed upstream without those changes being replicated
+    /// (such as when partitions are attached to or detached from a partitioned table), so the
+    /// table has to be snapshotted again
+    ResnapshotTable {
+        table: Relation,
+    },
     LogPosition,
 }
 
@@ -152,6 +159,12 @@ pub struct NoriaAdapter {
     /// replication at the *minimum* replication offset, but ignore any replication events that
     /// come before the offset for that table
     replication_offsets: ReplicationOffsets,
+    /// The chunks copied of tables that were snapshotted in chunks from more than one snapshot of
+    /// the upstream database, obtained from the controller once snapshotting is done.
+    ///
+    /// Until replication catches up with the latest of a table's chunks, changes to rows in each
+    /// chunk are skipped if that chunk was copied after them (see [`included_in_snapshot`])
+    snapshot_progress: HashMap<Relation, SnapshotProgress>,
     /// Filters out changes we are not interested in
     table_filter: TableFilter,
     /// If the connector can partially resnapshot a database
@@ -172,6 +185,9 @@ impl NoriaAdapter {
         // replication-tables config parameter.
         let mut resnapshot = server_startup;
         let mut full_snapshot = false;
+        // Tables whose contents have to be replaced by the next partial resnapshot, even though
+        // they already have a replication offset
+        let mut resnapshot_tables = HashSet::new();
         let url: DatabaseURL = config
             .upstream_db_url
             .take()
@@ -197,6 +213,7 @@ impl NoriaAdapter {
                     &telemetry_sender,
                     enable_statement_logging,
                     full_snapshot,
+                    &mut resnapshot_tables,
                 )
                 .await
             }
@@ -241,6 +258,7 @@ impl NoriaAdapter {
                     pool,
                     repl_slot_name,
                     enable_statement_logging,
+                    &mut resnapshot_tables,
                 )
                 .await
             }
@@ -252,6 +270,13 @@ impl NoriaAdapter {
                     resnapshot = true;
                     full_snapshot = false;
                 }
+                ReadySetError::TableResnapshotNeeded { table } => {
+                    set_failpoint!(failpoints::POSTGRES_PARTIAL_RESNAPSHOT);
+                    tokio::time::sleep(WAIT_BEFORE_RESNAPSHOT).await;
+                    resnapshot = true;
+                    full_snapshot = false;
+                    resnapshot_tables.insert(table);
+                }
                 ReadySetError::FullResnapshotNeeded => {
                     set_failpoint!(failpoints::POSTGRES_FULL_RESNAPSHOT);
                     tokio::time::sleep(WAIT_BEFORE_RESNAPSHOT).await;
@@ -290,9 +315,8 @@ impl NoriaAdapter {
         telemetry_sender: &TelemetrySender,
         enable_statement_logging: bool,
         full_snapshot: bool,
+        resnapshot_tables: &mut HashSet<Relation>,
     ) -> ReadySetResult<!> {
-        use replication_offset::mysql::MySqlPosition;
-
         if let Some(cert_path) = config.ssl_root_cert.clone() {
             let ssl_opts = SslOpts::default().with_root_cert_path(Some(cert_path));
             mysql_options = OptsBuilder::from_opts(mysql_options)
@@ -322,6 +346,15 @@ impl NoriaAdapter {
 
         let mut db_schemas = DatabaseSchemas::new();
 
+        // MariaDB's binlog differs from MySQL's in a few ways (most notably its GTIDs), so we need
+        // to know which one we are replicating from. Both use `mysql://` URLs, so we have to ask.
+        let mut conn = mysql::Conn::new(mysql_options.clone()).await?;
+        let database_type = DatabaseType::detect_mysql(&mut conn).await?;
+        conn.disconnect().await?;
+        if database_type == DatabaseType::MariaDB {
+            info!("Replicating from MariaDB");
+        }
+
         let pos = match (replication_offsets.max_offset()?, resnapshot) {
             (None, _) | (_, true) => {
                 let span = info_span!("taking database snapshot");
@@ -355,6 +388,7 @@ impl NoriaAdapter {
                 let replicator = MySqlReplicator {
                     pool,
                     table_filter: table_filter.clone(),
+                    database_type,
                 };
 
                 let snapshot_start = Instant::now();
@@ -371,6 +405,7 @@ impl NoriaAdapter {
                         &mut db_schemas,
                         config.snapshot_report_interval_secs,
                         full_snapshot,
+                        resnapshot_tables,
                     )
                     .instrument(span.clone())
                     .await;
@@ -388,6 +423,7 @@ impl NoriaAdapter {
                 );
 
                 snapshot_result?;
+                resnapshot_tables.clear();
 
                 // Get updated offsets, after potential replication happened
                 replication_offsets = noria.replication_offsets().await?;
@@ -401,11 +437,10 @@ impl NoriaAdapter {
                 // can do this "catching up" by just starting replication at
                 // the old offset. Note that at the very least we will
                 // always have the schema offset for the minimum.
-                let pos: MySqlPosition = replication_offsets
+                let pos = replication_offsets
                     .min_present_offset()?
                     .expect("Minimal offset must be present after snapshot")
-                    .clone()
-                    .try_into()?;
+                    .clone();
 
                 span.in_scope(|| info!("Snapshot finished"));
                 histogram!(
@@ -417,7 +452,7 @@ impl NoriaAdapter {
                 let _ = telemetry_sender.send_event_with_payload(
                     TelemetryEvent::SnapshotComplete,
                     TelemetryBuilder::new()
-                        .db_backend("mysql")
+                        .db_backend(database_type.to_string())
                         .db_version(db_version)
                         .build(),
                 );
@@ -425,15 +460,19 @@ impl NoriaAdapter {
 
                 pos
             }
-            (Some(pos), _) => pos.clone().try_into()?,
+            (Some(pos), _) => pos.clone(),
         };
 
+        let snapshot_progress =
+            Self::load_snapshot_progress(&mut noria, &replication_offsets).await?;
+
         let connector = Box::new(
             MySqlBinlogConnector::connect(
                 mysql_options.clone(),
                 pos.clone(),
                 config.replication_server_id,
                 enable_statement_logging,
+                database_type,
             )
             .await?,
         );
@@ -442,6 +481,7 @@ impl NoriaAdapter {
             noria: noria.clone(),
             connector,
             replication_offsets,
+            snapshot_progress,
             mutator_map: HashMap::new(),
             warned_missing_tables: HashSet::new(),
             table_filter,
@@ -449,7 +489,7 @@ impl NoriaAdapter {
             dialect: Dialect::DEFAULT_MYSQL,
         };
 
-        let mut current_pos: ReplicationOffset = pos.into();
+        let mut current_pos = pos;
 
         // At this point it is possible that we just finished replication, but
         // our schema and our tables are taken at different position in the binlog.
@@ -507,6 +547,7 @@ impl NoriaAdapter {
         pool: deadpool_postgres::Pool,
         repl_slot_name: String,
         enable_statement_logging: bool,
+        resnapshot_tables: &mut HashSet<Relation>,
     ) -> ReadySetResult<!> {
         set_failpoint_return_err!(failpoints::START_INNER_POSTGRES);
 
@@ -528,10 +569,30 @@ impl NoriaAdapter {
         )
         .await?;
 
-        let pos = replication_offsets
-            .max_offset()?
-            .map(TryInto::try_into)
-            .transpose()?;
+        // If snapshotting was interrupted part-way through copying some tables in chunks, we can
+        // resume it without recreating our replication slot (which would mean snapshotting every
+        // table again), by replicating from the earliest offset any table or chunk was copied at
+        let snapshot_progress = noria.snapshot_progress().await?;
+        let resume_snapshot = !full_resnapshot
+            && replication_offsets.has_schema()
+            && replication_offsets.max_offset()?.is_none()
+            && !snapshot_progress.is_empty();
+        let pos = if resume_snapshot {
+            let mut pos = replication_offsets.min_present_offset()?;
+            for offset in snapshot_progress.values() {
+                if let Some(offset) = offset.min_offset()? {
+                    pos = Some(match pos {
+                        Some(pos) => ReplicationOffset::try_min(pos, offset)?,
+                        None => offset,
+                    });
+                }
+            }
+            pos
+        } else {
+            replication_offsets.max_offset()?
+        }
+        .map(TryInto::try_into)
+        .transpose()?;
         let snapshot_report_interval_secs = config.snapshot_report_interval_secs;
 
         let table_filter = TableFilter::try_new(
@@ -569,6 +630,7 @@ impl NoriaAdapter {
                 enable_statement_logging,
                 full_resnapshot,
                 noria.clone(),
+                version_num,
             )
             .await?,
         );
@@ -595,8 +657,8 @@ impl NoriaAdapter {
                 })?;
 
             if readyset_slot_exists {
-                info!(%full_resnapshot, %resnapshot, pos=?pos, "readyset_slot_exists");
-                if full_resnapshot || resnapshot || pos.is_none() {
+                info!(%full_resnapshot, %resnapshot, %resume_snapshot, pos=?pos, "readyset_slot_exists");
+                if full_resnapshot || resnapshot || resume_snapshot || pos.is_none() {
                     // This is not an initial connection but we need to resnapshot the latest
                     // schema, therefore we create a new replication slot, just
                     // so we can get a consistent snapshot with a WAL position
@@ -645,9 +707,14 @@ impl NoriaAdapter {
                 .and_then(|row| row.try_get::<_, String>(0))
                 .unwrap_or_else(|_| "unknown".to_owned());
 
-            let mut replicator =
-                PostgresReplicator::new(&mut client, pool, &mut noria, table_filter.clone())
-                    .await?;
+            let mut replicator = PostgresReplicator::new(
+                &mut client,
+                pool,
+                &mut noria,
+                table_filter.clone(),
+                connector.standby,
+            )
+            .await?;
 
             let snapshot_result = replicator
                 .snapshot_to_noria(
@@ -661,6 +728,7 @@ impl NoriaAdapter {
                     //  tables we do have a replication offset for that happened while we weren't
                     //  running.
                     pos.is_none() || full_resnapshot,
+                    resnapshot_tables,
                 )
                 .await;
 
@@ -677,6 +745,7 @@ impl NoriaAdapter {
             );
 
             snapshot_result?;
+            resnapshot_tables.clear();
 
             info!("Snapshot finished");
             histogram!(
@@ -706,6 +775,8 @@ impl NoriaAdapter {
 
         let replication_offsets = noria.replication_offsets().await?;
         trace!(?replication_offsets, "Loaded replication offsets");
+        let snapshot_progress =
+            Self::load_snapshot_progress(&mut noria, &replication_offsets).await?;
         let mut min_pos = replication_offsets
             .min_present_offset()?
             .expect("Minimal offset must be present after snapshot")
@@ -719,6 +790,7 @@ impl NoriaAdapter {
             noria,
             connector,
             replication_offsets,
+            snapshot_progress,
             mutator_map: HashMap::new(),
             warned_missing_tables: HashSet::new(),
             table_filter,
@@ -751,6 +823,31 @@ impl NoriaAdapter {
         unreachable!("`main_loop` will never stop with an Ok status if `until = None`");
     }
 
+    /// Load the chunks copied of any tables that were snapshotted in chunks from more than one
+    /// snapshot, which changes to those tables have to be checked against until replication catches
+    /// up with all of them. The chunks recorded for any other tables are cleared.
+    async fn load_snapshot_progress(
+        noria: &mut ReadySetHandle,
+        replication_offsets: &ReplicationOffsets,
+    ) -> ReadySetResult<HashMap<Relation, SnapshotProgress>> {
+        let mut res = HashMap::new();
+        for (table, progress) in noria.snapshot_progress().await? {
+            let needed = match (
+                replication_offsets.tables.get(&table),
+                progress.max_offset()?,
+            ) {
+                (Some(Some(offset)), Some(max)) => max.try_partial_cmp(offset)?.is_gt(),
+                _ => false,
+            };
+            if needed {
+                res.insert(table, progress);
+            } else {
+                noria.set_snapshot_progress(&table, None).await?;
+            }
+        }
+        Ok(res)
+    }
+
     /// Apply a DDL string to noria with the current log position
     async fn handle_ddl_change(
         &mut self,
@@ -906,6 +1003,31 @@ impl NoriaAdapter {
         txid: Option<u64>,
         pos: ReplicationOffset,
     ) -> ReadySetResult<()> {
+        // Until we've caught up with the latest chunk the table was snapshotted in, skip any
+        // changes that were already included in the chunks they affect
+        if let Some(progress) = self.snapshot_progress.get(&table) {
+            let caught_up = match progress.max_offset()? {
+                Some(max) => pos.try_partial_cmp(max)?.is_gt(),
+                None => true,
+            };
+            if caught_up {
+                self.snapshot_progress.remove(&table);
+                self.noria.set_snapshot_progress(&table, None).await?;
+            } else {
+                let mut remaining = Vec::with_capacity(actions.len());
+                for action in actions {
+                    match included_in_snapshot(progress, &action, &pos)? {
+                        Some(true) => {}
+                        Some(false) => remaining.push(action),
+                        // We can't tell which of the changes the snapshot already includes, so
+                        // the table's contents have to be replaced
+                        None => return Err(ReadySetError::TableResnapshotNeeded { table }),
+                    }
+                }
+                actions = remaining;
+            }
+        }
+
         // Send the rows as are
         let table_mutator = if let Some(table) = self.mutator_for_table(&table).await? {
             table
@@ -957,17 +1079,17 @@ impl NoriaAdapter {
         // First check if we should skip this action due to insufficient log position or lack of
         // interest
         match &action {
-            ReplicationAction::DdlChange { .. } | ReplicationAction::LogPosition => {
-                match &self.replication_offsets.schema {
-                    Some(cur) if pos <= *cur => {
-                        if !catchup {
-                            warn!(%pos, %cur, "Skipping schema update for earlier entry");
-                        }
-                        return Ok(());
+            ReplicationAction::DdlChange { .. }
+            | ReplicationAction::ResnapshotTable { .. }
+            | ReplicationAction::LogPosition => match &self.replication_offsets.schema {
+                Some(cur) if pos <= *cur => {
+                    if !catchup {
+                        warn!(%pos, %cur, "Skipping schema update for earlier entry");
                     }
-                    _ => {}
+                    return Ok(());
                 }
-            }
+                _ => {}
+            },
             ReplicationAction::TableAction { table, .. } => {
                 match self.replication_offsets.tables.get(table) {
                     Some(Some(cur)) if pos <= *cur => {
@@ -1012,10 +1134,38 @@ impl NoriaAdapter {
                 actions,
                 txid,
             } => self.handle_table_actions(table, actions, txid, pos).await,
+            ReplicationAction::ResnapshotTable { table } => self.resnapshot_table(table).await,
             ReplicationAction::LogPosition => self.handle_log_position(pos).await,
         }
     }
 
+    /// Trigger a partial resnapshot which replaces the contents of the given table in place, by
+    /// returning [`ReadySetError::TableResnapshotNeeded`]. The table itself isn't dropped, so any
+    /// caches that depend on it are kept.
+    async fn resnapshot_table(&mut self, table: Relation) -> ReadySetResult<()> {
+        if !self.supports_resnapshot || !self.replication_offsets.tables.contains_key(&table) {
+            // Either we can't resnapshot, or we aren't replicating the table in the first place
+            warn!(
+                table = %table.display(nom_sql::Dialect::PostgreSQL),
+                "Not resnapshotting table"
+            );
+            return Ok(());
+        }
+
+        info!(
+            table = %table.display(nom_sql::Dialect::PostgreSQL),
+            "Table contents changed upstream, will resnapshot"
+        );
+
+        // Forward all positions to the maximum position (the one prior to this event) to avoid
+        // needless replay later
+        if let Some(pos) = self.replication_offsets.max_offset()?.cloned() {
+            self.handle_log_position(pos).await?;
+        }
+
+        Err(ReadySetError::TableResnapshotNeeded { table })
+    }
+
     /// Loop over the actions. `until` may be passed to set a replication offset to stop
     /// replicating at.
     async fn main_loop(
@@ -1063,7 +1213,10 @@ impl NoriaAdapter {
             trace!(?action);
 
             if let Err(err) = self.handle_action(action, pos, until.is_some()).await {
-                if matches!(err, ReadySetError::ResnapshotNeeded) {
+                if matches!(
+                    err,
+                    ReadySetError::ResnapshotNeeded | ReadySetError::TableResnapshotNeeded { .. }
+                ) {
                     info!("Change in DDL requires partial resnapshot");
                 } else {
                     error!(error = %err, "Aborting replication task on error");
@@ -39,6 +39,11 @@ use crate::noria_adapter::{Connector, ReplicationAction};
 /// `CREATE` - To create a publication, the user must have the CREATE privilege in the database. To
 /// add tables to a publication, the user must have ownership rights on the table. To create a
 /// publication that publishes all tables automatically, the user must be a superuser.
+///
+/// From PostgreSQL 16 on, the connector can also replicate from a physical standby, which it
+/// creates its replication slot on. Since a standby is read-only, the publication (and DDL
+/// replication) then have to be set up on the primary instead. Slots on a standby survive its
+/// promotion, so once promoted we just keep replicating from the same slot.
 pub struct PostgresWalConnector {
     /// This is the underlying (regular) PostgreSQL client
     client: pgsql::Client,
@@ -63,6 +68,11 @@ pub struct PostgresWalConnector {
     controller: ReadySetHandle,
     /// The interval on which we should send status updates to the upstream Postgres instance
     status_update_interval: Duration,
+    /// Whether the upstream server is a standby (i.e. was in recovery when we connected to it)
+    pub(crate) standby: bool,
+    /// The configuration for connecting to the primary server, if the upstream server is a standby
+    /// and we were given one. See [`Self::request_standby_snapshots`]
+    standby_primary: Option<(pgsql::Config, MakeTlsConnector)>,
 }
 
 /// The decoded response to `IDENTIFY_SYSTEM`
@@ -108,11 +118,15 @@ impl PostgresWalConnector {
     /// we receive many consecutive events that share the same LSN.
     const MAX_QUEUED_INDEPENDENT_ACTIONS: usize = 100;
 
+    /// How often we ask the primary to log a standby snapshot (or, if we can't, remind the user
+    /// that we're waiting for one) while creating a replication slot on a standby
+    const STANDBY_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
     /// Connects to postgres and if needed creates a new replication slot for itself with an
     /// exported snapshot.
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn connect<S: AsRef<str>>(
-        mut pg_config: pgsql::Config,
+        pg_config: pgsql::Config,
         dbname: S,
         config: UpstreamConfig,
         next_position: Option<Lsn>,
@@ -121,14 +135,15 @@ impl PostgresWalConnector {
         enable_statement_logging: bool,
         full_resnapshot: bool,
         controller: ReadySetHandle,
+        version: u32,
     ) -> ReadySetResult<Self> {
-        if !config.disable_setup_ddl_replication {
-            setup_ddl_replication(pg_config.clone(), tls_connector.clone()).await?;
-        }
-        pg_config.dbname(dbname.as_ref()).set_replication_database();
+        let mut repl_config = pg_config.clone();
+        repl_config
+            .dbname(dbname.as_ref())
+            .set_replication_database();
 
-        let (client, connection) = pg_config
-            .connect(tls_connector)
+        let (client, connection) = repl_config
+            .connect(tls_connector.clone())
             .await
             .map_err(|e| ReadySetError::ReplicationFailed(format!("Failed to connect: {e}")))?;
         let connection_handle = tokio::spawn(connection);
@@ -149,28 +164,76 @@ impl PostgresWalConnector {
                 - status_update_interval
                 - Duration::from_secs(1),
             // We'll never start replicating in the middle of a transaction, since Postgres's
-            // logical replication protocol only streams whole transactions to us at a time. This
-            // means we know that when we first initiate a replication connection to the upstream
-            // database, we'll always start replicating outside of a transaction
+            // logical replication protocol only streams whole transactions to us at a time (or,
+            // for transactions streamed while in progress, the `WalReader` buffers them until they
+            // commit). This means we know that when we first initiate a replication connection to
+            // the upstream database, we'll always start replicating outside of a transaction
             in_transaction: false,
             controller,
             status_update_interval,
+            standby: false,
+            standby_primary: None,
         };
 
+        let [in_recovery] = connector
+            .one_row_query::<1>("SELECT pg_is_in_recovery()")
+            .await?;
+        connector.standby = in_recovery == "t";
+        connector.standby_primary = if connector.standby {
+            if version < 160000 {
+                return Err(ReadySetError::ReplicationFailed(
+                    "Replicating from a standby requires PostgreSQL 16 or above".into(),
+                ));
+            }
+            info!("Upstream database is a standby");
+
+            config
+                .standby_primary_db_url
+                .as_ref()
+                .map(|url| {
+                    url.parse::<pgsql::Config>().map_err(|e| {
+                        ReadySetError::UrlParseFailed(format!(
+                            "Invalid URL supplied to --standby-primary-db-url: {e}"
+                        ))
+                    })
+                })
+                .transpose()?
+                .map(|primary_config| (primary_config, tls_connector.clone()))
+        } else {
+            None
+        };
+
+        if !config.disable_setup_ddl_replication {
+            if connector.standby {
+                // A standby is read-only, but it replays everything DDL replication does on the
+                // primary, so that's where it has to be set up
+                warn!(
+                    "Not setting up DDL replication, since the upstream database is a standby. \
+                     It has to be set up on the primary for schema changes to be replicated"
+                );
+            } else {
+                setup_ddl_replication(pg_config, tls_connector).await?;
+            }
+        }
+
         if full_resnapshot || next_position.is_none() {
             // If we don't have a consistent replication offset to start replicating from or if we
             // need to perform a full resnapshot, drop and recreate our replication slot.
             //
             // Note that later on, this means we'll need to make sure we resnapshot *all* tables!
             connector
-                .create_publication_and_slot(repl_slot_name)
+                .create_publication_and_slot(repl_slot_name, version)
                 .await?;
         }
 
         Ok(connector)
     }
 
-    async fn create_publication_and_slot(&mut self, repl_slot_name: &str) -> ReadySetResult<()> {
+    async fn create_publication_and_slot(
+        &mut self,
+        repl_slot_name: &str,
+        version: u32,
+    ) -> ReadySetResult<()> {
         let system = self.identify_system().await?;
         debug!(
             id = %system.id,
@@ -179,20 +242,30 @@ impl PostgresWalConnector {
             dbname = ?system.dbname
         );
 
-        match self.create_publication(PUBLICATION_NAME).await {
-            Ok(()) => {
-                // Created a new publication, everything is good
-            }
-            Err(err)
-                if err.to_string().contains("publication")
-                    && err.to_string().contains("already exists") =>
-            {
-                // This is an existing publication we are going to use
-            }
-            Err(err) if err.to_string().contains("permission denied") => {
-                error!("Insufficient permissions to create publication FOR ALL TABLES");
+        // Publishing changes to partitions as changes to their partition root (which is what lets
+        // us replicate partitioned tables as a single table) is only supported in PostgreSQL 13 and
+        // above
+        let via_partition_root = version >= 130000;
+
+        if self.standby {
+            // We can't create the publication on a standby, but it replays the publication created
+            // on the primary
+            let [exists] = self
+                .one_row_query::<1>(&format!(
+                    "SELECT EXISTS(SELECT 1 FROM pg_publication WHERE pubname = {})",
+                    escape_literal(PUBLICATION_NAME)
+                ))
+                .await?;
+            if exists != "t" {
+                return Err(ReadySetError::ReplicationFailed(format!(
+                    "Publication {PUBLICATION_NAME} does not exist on the standby. Create it on \
+                     the primary with CREATE PUBLICATION {PUBLICATION_NAME} FOR ALL TABLES WITH \
+                     (publish_via_partition_root = true)"
+                )));
             }
-            Err(err) => return Err(err),
+        } else {
+            self.create_or_update_publication(via_partition_root)
+                .await?;
         }
 
         // Drop the existing slot if any
@@ -213,6 +286,40 @@ impl PostgresWalConnector {
         Ok(())
     }
 
+    async fn create_or_update_publication(
+        &mut self,
+        via_partition_root: bool,
+    ) -> ReadySetResult<()> {
+        match self
+            .create_publication(PUBLICATION_NAME, via_partition_root)
+            .await
+        {
+            Ok(()) => {
+                // Created a new publication, everything is good
+            }
+            Err(err)
+                if err.to_string().contains("publication")
+                    && err.to_string().contains("already exists") =>
+            {
+                // This is an existing publication we are going to use, so make sure it publishes
+                // changes to partitions the same way a new one would. If we can't, we'll keep
+                // replicating partitions rather than partitioned tables, which the snapshot checks
+                // for itself.
+                if via_partition_root {
+                    if let Err(error) = self.publish_via_partition_root(PUBLICATION_NAME).await {
+                        warn!(%error, "Failed to set publish_via_partition_root on existing publication");
+                    }
+                }
+            }
+            Err(err) if err.to_string().contains("permission denied") => {
+                error!("Insufficient permissions to create publication FOR ALL TABLES");
+            }
+            Err(err) => return Err(err),
+        }
+
+        Ok(())
+    }
+
     /// Waits and returns the next WAL event, while monitoring the connection
     /// handle for errors.
     async fn next_event(&mut self) -> ReadySetResult<WalEvent> {
@@ -267,14 +374,41 @@ impl PostgresWalConnector {
 
     /// Creates a new `PUBLICATION name FOR ALL TABLES`, to be able to receive WAL on that slot.
     /// The user must have superuser privileges for that to work.
-    async fn create_publication(&mut self, name: &str) -> ReadySetResult<()> {
-        let query = format!("CREATE PUBLICATION {} FOR ALL TABLES", name);
+    ///
+    /// If `via_partition_root` is set, changes to partitions are published as changes to their
+    /// partition root.
+    async fn create_publication(
+        &mut self,
+        name: &str,
+        via_partition_root: bool,
+    ) -> ReadySetResult<()> {
+        let query = if via_partition_root {
+            format!(
+                "CREATE PUBLICATION {} FOR ALL TABLES WITH (publish_via_partition_root = true)",
+                name
+            )
+        } else {
+            format!("CREATE PUBLICATION {} FOR ALL TABLES", name)
+        };
         self.simple_query(&query).await.map_err(|e| {
             ReadySetError::ReplicationFailed(format!("Failed to create publication: {e}"))
         })?;
         Ok(())
     }
 
+    /// Configures the existing publication `name` to publish changes to partitions as changes to
+    /// their partition root
+    async fn publish_via_partition_root(&mut self, name: &str) -> ReadySetResult<()> {
+        let query = format!(
+            "ALTER PUBLICATION {} SET (publish_via_partition_root = true)",
+            name
+        );
+        self.simple_query(&query).await.map_err(|e| {
+            ReadySetError::ReplicationFailed(format!("Failed to alter publication: {e}"))
+        })?;
+        Ok(())
+    }
+
     /// Creates a new replication slot on the primary.
     /// The command format for PostgreSQL is as follows:
     ///
@@ -288,6 +422,9 @@ impl PostgresWalConnector {
     /// `pgoutput` - the plugin to use for logical decoding, always available from PG > 10
     /// `EXPORT_SNAPSHOT` -  we want the operation to export a snapshot that can be then used for
     /// snapshotting
+    ///
+    /// On a standby, this blocks until the primary logs a standby snapshot, which we try to speed
+    /// up using [`Self::request_standby_snapshots`].
     pub(crate) async fn create_replication_slot(
         &mut self,
         name: &str,
@@ -299,10 +436,15 @@ impl PostgresWalConnector {
             if temporary { "TEMPORARY" } else { "" }
         );
 
-        let [slot_name, consistent_point_str, snapshot_name, output_plugin] =
-            self.one_row_query::<4>(&query).await.map_err(|e| {
-                ReadySetError::ReplicationFailed(format!("Failed to create replication slot: {e}"))
-            })?;
+        let standby_snapshots = self.standby.then(|| self.request_standby_snapshots());
+        let res = self.one_row_query::<4>(&query).await;
+        if let Some(standby_snapshots) = standby_snapshots {
+            standby_snapshots.abort();
+        }
+
+        let [slot_name, consistent_point_str, snapshot_name, output_plugin] = res.map_err(|e| {
+            ReadySetError::ReplicationFailed(format!("Failed to create replication slot: {e}"))
+        })?;
         let consistent_point = consistent_point_str.parse()?;
 
         debug!(
@@ -318,6 +460,57 @@ impl PostgresWalConnector {
         })
     }
 
+    /// Spawn a task that periodically asks the primary to log a standby snapshot, until it's
+    /// aborted.
+    ///
+    /// Creating a logical replication slot on a standby has to wait until the standby replays a
+    /// record of the transactions running on the primary, which the primary only logs every 15
+    /// seconds, and not at all while it's idle. `pg_log_standby_snapshot()` makes the primary log
+    /// one right away. If we weren't given a way to connect to the primary, all we can do is let
+    /// the user know what we're waiting for.
+    fn request_standby_snapshots(&self) -> tokio::task::JoinHandle<()> {
+        let standby_primary = self.standby_primary.clone();
+        tokio::spawn(async move {
+            let primary = match standby_primary {
+                Some((config, tls_connector)) => match config.connect(tls_connector).await {
+                    Ok((client, connection)) => {
+                        tokio::spawn(connection);
+                        Some(client)
+                    }
+                    Err(error) => {
+                        warn!(
+                            %error,
+                            "Failed to connect to the primary to request standby snapshots"
+                        );
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            let mut interval = tokio::time::interval(Self::STANDBY_SNAPSHOT_INTERVAL);
+            loop {
+                interval.tick().await;
+                match &primary {
+                    Some(client) => {
+                        debug!("Requesting a standby snapshot from the primary");
+                        if let Err(error) = client
+                            .simple_query("SELECT pg_log_standby_snapshot()")
+                            .await
+                        {
+                            warn!(%error, "Failed to request a standby snapshot from the primary");
+                        }
+                    }
+                    None => info!(
+                        "Waiting for the primary to log a standby snapshot to create the \
+                         replication slot. Run `SELECT pg_log_standby_snapshot()` on the primary, \
+                         or pass --standby-primary-db-url, to speed this up"
+                    ),
+                }
+            }
+        })
+    }
+
     /// Begin replication on the `slot` and `publication`. The `publication` must be present on
     /// the server, and can be created using: `CREATE PUBLICATION publication FOR ALL TABLES;`
     pub(crate) async fn start_replication(
@@ -329,15 +522,34 @@ impl PostgresWalConnector {
         set_failpoint_return_err!(failpoints::POSTGRES_START_REPLICATION);
 
         // Load the confirmed flush LSN for this replication slot so we can log it before starting
-        // replication, and get the WAL status so we can make sure our replication slot is healthy
-        let [confirmed_flush_lsn, wal_status] = self
-                .one_row_query::<2>(&format!(
-                    "SELECT confirmed_flush_lsn, wal_status FROM pg_replication_slots WHERE slot_name = {}",
+        // replication, and get the WAL status so we can make sure our replication slot is healthy.
+        // From PostgreSQL 16 on, slots can also be invalidated by conflicts with recovery on a
+        // standby
+        let conflicting = if version >= 160000 {
+            "coalesce(conflicting, false)"
+        } else {
+            "false"
+        };
+        let [confirmed_flush_lsn, wal_status, conflicting] = self
+                .one_row_query::<3>(&format!(
+                    "SELECT confirmed_flush_lsn, wal_status, {conflicting} FROM pg_replication_slots WHERE slot_name = {}",
                     escape_literal(slot),
                 ))
                 .await?;
 
-        tracing::info!(?confirmed_flush_lsn, ?wal_status);
+        tracing::info!(?confirmed_flush_lsn, ?wal_status, ?conflicting);
+
+        if conflicting == "t" {
+            // The standby had to remove catalog rows our slot still needed to decode changes
+            // (which `hot_standby_feedback` on the standby prevents), or the primary's `wal_level`
+            // was lowered below `logical`
+            error!(
+                "Our replication slot has been invalidated by a conflict with recovery on the \
+                 standby, so a full resnapshot is necessary"
+            );
+
+            return Err(ReadySetError::FullResnapshotNeeded);
+        }
 
         if wal_status == "unreserved" {
             // If the WAL status is "unreserved," it means Postgres has marked WAL files we need as
@@ -367,14 +579,24 @@ impl PostgresWalConnector {
         } else {
             ""
         };
+        // From protocol version 2 on (supported by PostgreSQL 14 and above), the server can stream
+        // large transactions to us while they're still in progress, rather than spilling them to
+        // disk and only sending them once they commit. The `WalReader` buffers the changes for
+        // those until they commit, so we still only ever see whole transactions here.
+        let (proto_version, streaming) = if version >= 140000 {
+            ("2", ", \"streaming\" 'on'")
+        } else {
+            ("1", "")
+        };
 
         debug!(%wal_position, %slot, postgres_version = %version, %confirmed_flush_lsn, "Starting replication");
 
         let query = format!(
             "START_REPLICATION SLOT {slot} LOGICAL {wal_position} (
-                \"proto_version\" '1',
+                \"proto_version\" '{proto_version}',
                 \"publication_names\" '{publication}'
                 {messages_support}
+                {streaming}
             )",
         );
 
@@ -770,13 +992,7 @@ impl Connector for PostgresWalConnector {
             match event {
                 WalEvent::DdlEvent { ddl_event, lsn } => {
                     if actions.is_empty() {
-                        return Ok((
-                            ReplicationAction::DdlChange {
-                                schema: ddl_event.schema().to_string(),
-                                changes: vec![ddl_event.into_change()],
-                            },
-                            cur_pos.with_lsn(lsn).into(),
-                        ));
+                        return Ok((ddl_event.into_action(), cur_pos.with_lsn(lsn).into()));
                     } else {
                         self.peek = Some(Ok(WalEvent::DdlEvent { ddl_event, lsn }));
                         return Ok((
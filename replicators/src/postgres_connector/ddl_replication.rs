@@ -35,6 +35,11 @@
 //!   to construct a full `ALTER TABLE` statement, `ALTER TABLE` events are replicated as a `CREATE
 //!   TABLE` statement - ReadySet will then know that a `CREATE TABLE` statement for a table that
 //!   already exists should be treated as an alter table.
+//! * If our publication publishes changes to partitions via their partition root, partitioned
+//!   tables are replicated as a single table, and their partitions aren't replicated at all.
+//!   Attaching or detaching partitions changes the contents of the partition root without writing
+//!   any changes to the WAL, so those `ALTER TABLE` events are replicated as a request to
+//!   resnapshot the partition root.
 //!
 //! [dialect]: nom_sql::Dialect
 
@@ -52,6 +57,8 @@ use serde::{Deserialize, Deserializer};
 use tokio_postgres as pgsql;
 use tracing::info;
 
+use crate::noria_adapter::ReplicationAction;
+
 /// Setup everything in the database that's necessary for DDL replication.
 ///
 /// This makes a new connection to the database, since the main connection created for the
@@ -154,12 +161,21 @@ pub(crate) enum DdlEventData {
     },
     AddNonReplicatedTable {
         name: String,
+        /// Whether the table is a partition that's replicated as part of its partition root,
+        /// rather than a partitioned table we can't replicate
+        #[serde(default)]
+        partition: bool,
     },
     AlterTable {
         name: String,
         #[serde(deserialize_with = "parse_alter_table_statement")]
         statement: Result<AlterTableStatement, String>,
     },
+    /// Partitions were attached to or detached from the (root) partitioned table with the given
+    /// name
+    AlterPartitions {
+        name: String,
+    },
     CreateView(#[serde(deserialize_with = "parse_create_view_statement")] CreateViewStatement),
     Drop(String),
     CreateType {
@@ -195,6 +211,22 @@ where
 }
 
 impl DdlEvent {
+    /// Convert this [`DdlEvent`] into the [`ReplicationAction`] that applies it to ReadySet
+    pub(crate) fn into_action(self) -> ReplicationAction {
+        match self.data {
+            DdlEventData::AlterPartitions { name } => ReplicationAction::ResnapshotTable {
+                table: Relation {
+                    schema: Some(self.schema.into()),
+                    name: name.into(),
+                },
+            },
+            _ => ReplicationAction::DdlChange {
+                schema: self.schema.clone(),
+                changes: vec![self.into_change()],
+            },
+        }
+    }
+
     /// Convert this [`DdlEvent`] into a SQL DDL statement that can be sent to ReadySet directly
     /// (using the ReadySet-native SQL dialect, not the postgresql dialect!)
     pub(crate) fn into_change(self) -> Change {
@@ -265,13 +297,31 @@ impl DdlEvent {
                     }),
                 }
             }
-            DdlEventData::AddNonReplicatedTable { name } => {
+            DdlEventData::AddNonReplicatedTable { name, partition } => {
                 Change::AddNonReplicatedRelation(NonReplicatedRelation {
                     name: Relation {
                         schema: Some(self.schema.into()),
                         name: name.into(),
                     },
-                    reason: NotReplicatedReason::Partitioned,
+                    reason: if partition {
+                        NotReplicatedReason::Partition
+                    } else {
+                        NotReplicatedReason::Partitioned
+                    },
+                })
+            }
+            DdlEventData::AlterPartitions { name } => {
+                // Changing a table's partitions doesn't change its schema, only its contents,
+                // which we can only pick up by resnapshotting it (see `into_action`). If we get
+                // here anyway, fall back to treating this as an alter table we can't parse, which
+                // triggers a resnapshot of the whole schema.
+                Change::AlterTable(AlterTableStatement {
+                    table: Relation {
+                        schema: Some(self.schema.into()),
+                        name: name.into(),
+                    },
+                    definitions: Err("ATTACH/DETACH PARTITION".to_owned()),
+                    only: false,
                 })
             }
             DdlEventData::AlterTable { name, statement } => {
@@ -648,11 +698,74 @@ mod tests {
             .unwrap();
 
         match ddl.data {
-            DdlEventData::AddNonReplicatedTable { name } => assert_eq!(name, "t1"),
+            DdlEventData::AddNonReplicatedTable { name, partition } => {
+                assert_eq!(name, "t1");
+                assert!(!partition);
+            }
             data => panic!("Unexpected DDL event data: {data:?}"),
         }
     }
 
+    #[parallel_group(GROUP)]
+    #[tokio::test]
+    async fn partitioned_table_via_partition_root() {
+        readyset_tracing::init_test_logging();
+        let client = setup("partitioned_table_via_partition_root").await;
+
+        client
+            .simple_query(
+                "create publication readyset for all tables
+                 with (publish_via_partition_root = true)",
+            )
+            .await
+            .unwrap();
+
+        client
+            .simple_query("create table t (key int, val int) partition by range (key)")
+            .await
+            .unwrap();
+        let ddl = get_last_ddl(&client, "partitioned_table_via_partition_root")
+            .await
+            .unwrap();
+        match ddl.data {
+            DdlEventData::CreateTable { name, .. } => assert_eq!(name, "t"),
+            data => panic!("Unexpected DDL event data: {data:?}"),
+        }
+
+        client
+            .simple_query("create table t_low partition of t for values from (0) to (10)")
+            .await
+            .unwrap();
+        let ddl = get_last_ddl(&client, "partitioned_table_via_partition_root")
+            .await
+            .unwrap();
+        match ddl.data {
+            DdlEventData::AddNonReplicatedTable { name, partition } => {
+                assert_eq!(name, "t_low");
+                assert!(partition);
+            }
+            data => panic!("Unexpected DDL event data: {data:?}"),
+        }
+
+        client
+            .simple_query("create table t_high (key int, val int)")
+            .await
+            .unwrap();
+        let _ = get_last_ddl(&client, "partitioned_table_via_partition_root").await;
+
+        client
+            .simple_query("alter table t attach partition t_high for values from (10) to (20)")
+            .await
+            .unwrap();
+        let ddl = get_last_ddl(&client, "partitioned_table_via_partition_root")
+            .await
+            .unwrap();
+        assert_eq!(ddl.schema, "public");
+        assert_eq!(ddl.data, DdlEventData::AlterPartitions { name: "t".into() });
+
+        client.teardown().await;
+    }
+
     #[parallel_group(GROUP)]
     #[tokio::test]
     async fn alter_table() {
@@ -10,6 +10,24 @@ BEGIN
     SELECT current_setting('server_version_num') INTO ver;
     RETURN ver < 140000;
 END $$;
+
+-- Whether our publication publishes changes to partitions as changes to their
+-- partition root, in which case we replicate partitioned tables rather than
+-- their partitions
+CREATE OR REPLACE FUNCTION readyset.publishes_via_partition_root()
+RETURNS boolean
+LANGUAGE plpgsql
+AS $$
+    DECLARE via_root boolean;
+BEGIN
+    -- `pubviaroot` only exists in PostgreSQL 13 and above, so go through
+    -- jsonb to avoid referencing it directly
+    SELECT (to_jsonb(p) ->> 'pubviaroot')::boolean
+    INTO via_root
+    FROM pg_catalog.pg_publication p
+    WHERE p.pubname = 'readyset';
+    RETURN coalesce(via_root, false);
+END $$;
 ----
 
 DO $$
@@ -29,6 +47,7 @@ DECLARE
     create_message text;
     needs_replica_identity bool;
     alter_stmt record;
+    via_partition_root bool := readyset.publishes_via_partition_root();
 BEGIN
     SELECT count(*) = 0
     INTO needs_replica_identity
@@ -63,8 +82,23 @@ BEGIN
     END IF;
 
     SELECT
-    CASE cls.relkind
-    WHEN 'r' THEN
+    CASE
+    WHEN cls.relispartition AND via_partition_root THEN
+        json_build_object(
+            'schema', object.schema_name,
+            'data', json_build_object('AddNonReplicatedTable', json_build_object(
+                'name', cls.relname,
+                'partition', true
+            ))
+        )
+    WHEN cls.relkind = 'p' AND NOT via_partition_root THEN
+         json_build_object(
+            'schema', object.schema_name,
+            'data', json_build_object('AddNonReplicatedTable', json_build_object(
+                'name', cls.relname
+            ))
+         )
+    ELSE
         json_build_object(
             'schema', object.schema_name,
             'data', json_build_object('CreateTable', json_build_object(
@@ -110,13 +144,6 @@ BEGIN
                 )
             ))
         )
-    WHEN 'p' THEN
-         json_build_object(
-            'schema', object.schema_name,
-            'data', json_build_object('AddNonReplicatedTable', json_build_object(
-                'name', cls.relname
-            ))
-         )
     END
     INTO create_message
     FROM pg_event_trigger_ddl_commands() object
@@ -156,6 +183,37 @@ BEGIN
 
     SELECT current_query() INTO query;
 
+    -- Attaching or detaching partitions changes the contents of the partition
+    -- root without writing those changes to the WAL, so if we're replicating
+    -- the partition root it has to be resnapshotted
+    IF readyset.publishes_via_partition_root()
+       AND query ~* '\m(ATTACH|DETACH)\s+PARTITION\M' THEN
+        SELECT
+        json_build_object(
+            'schema', root_ns.nspname,
+            'data', json_build_object(
+                'AlterPartitions',
+                json_build_object('name', root.relname)
+            )
+        )
+        INTO alter_message
+        FROM pg_event_trigger_ddl_commands() object
+        JOIN pg_catalog.pg_class root
+          ON root.oid = pg_partition_root(object.objid)
+        JOIN pg_catalog.pg_namespace root_ns ON root.relnamespace = root_ns.oid
+        WHERE object.object_type = 'table'
+        LIMIT 1;
+
+        IF alter_message IS NOT NULL THEN
+            IF readyset.is_pre14() THEN
+                UPDATE readyset.ddl_replication_log SET "ddl" = alter_message;
+            ELSE
+                PERFORM pg_logical_emit_message(true, 'readyset', alter_message);
+            END IF;
+            RETURN;
+        END IF;
+    END IF;
+
     SELECT
     json_build_object(
         'schema', object.schema_name,
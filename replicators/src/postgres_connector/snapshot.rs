@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
 use std::fmt::{self, Display};
@@ -7,7 +7,7 @@ use std::time::Instant;
 
 use failpoint_macros::set_failpoint;
 use futures::stream::FuturesUnordered;
-use futures::{pin_mut, StreamExt, TryFutureExt};
+use futures::{pin_mut, FutureExt, StreamExt, TryFutureExt};
 use itertools::Itertools;
 use metrics::register_gauge;
 use nom_sql::{
@@ -24,12 +24,17 @@ use readyset_client::TableOperation;
 use readyset_data::{DfType, DfValue, Dialect as DataDialect, PgEnumMetadata};
 use readyset_errors::{internal, internal_err, unsupported, ReadySetError, ReadySetResult};
 use replication_offset::postgres::PostgresPosition;
-use replication_offset::ReplicationOffset;
+use replication_offset::{ReplicationOffset, SnapshotProgress};
 use tokio_postgres as pgsql;
 use tracing::{debug, info, info_span, trace, warn, Instrument};
 
 use super::connector::CreatedSlot;
+use super::PUBLICATION_NAME;
 use crate::db_util::CreateSchema;
+use crate::snapshot_chunks::{
+    key_ranges, remaining_ranges, roll_back_chunk, ChunkProgress, KeyRange, MAX_CHUNK_ATTEMPTS,
+    MAX_CONCURRENT_CHUNKS, MIN_ROWS_TO_CHUNK,
+};
 use crate::table_filter::TableFilter;
 
 const BATCH_SIZE: usize = 1024; // How many queries to buffer before pushing to ReadySet
@@ -51,6 +56,8 @@ pub struct PostgresReplicator<'a> {
     pub(crate) noria: &'a mut readyset_client::ReadySetHandle,
     /// Filters out tables we are not interested in
     pub(crate) table_filter: TableFilter,
+    /// Whether we're snapshotting from a standby, which we can't write anything to
+    pub(crate) standby: bool,
 }
 
 #[derive(Debug)]
@@ -73,6 +80,10 @@ struct TableEntry {
     schema: String,
     name: String,
     oid: u32,
+    /// Whether this is a partitioned table (`PARTITION BY`)
+    partitioned: bool,
+    /// Whether this table is a partition of another table (`PARTITION OF`)
+    partition: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +92,9 @@ struct TableDescription {
     name: Relation,
     columns: Vec<ColumnEntry>,
     constraints: Vec<ConstraintEntry>,
+    /// Whether this is a partitioned table, whose contents are the union of the contents of its
+    /// partitions
+    partitioned: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -229,6 +243,8 @@ impl TryFrom<pgsql::Row> for TableEntry {
             schema: row.try_get(0)?,
             oid: row.try_get(1)?,
             name: row.try_get(2)?,
+            partitioned: row.try_get::<_, i8>(3)? == b'p' as i8,
+            partition: row.try_get(4)?,
         })
     }
 }
@@ -382,6 +398,7 @@ impl TableEntry {
             },
             columns,
             constraints,
+            partitioned: self.partitioned,
         })
     }
 
@@ -476,40 +493,129 @@ impl TableDescription {
         })
     }
 
-    /// Copy a table's contents from PostgreSQL to ReadySet
-    async fn dump<'a>(
+    /// Fetch an approximate count of the rows in the table
+    async fn approximate_rows<'a>(
         &self,
         transaction: &'a deadpool_postgres::Transaction<'a>,
-        mut noria_table: readyset_client::Table,
-        snapshot_report_interval_secs: u16,
-        wal_position: &ReplicationOffset,
-    ) -> ReadySetResult<()> {
-        let mut cnt = 0;
-
-        let approximate_rows = transaction
+    ) -> ReadySetResult<i64> {
+        Ok(transaction
             .query_one(
                 // Fetch an *approximate estimate* of the number of rows in the table, rather than
                 // an exact count (the latter is *significantly* more expensive, especially for
-                // large tables). We're only using this for reporting snapshotting progress, so an
-                // approximate row count should be fine.
+                // large tables). We're only using this for reporting snapshotting progress and to
+                // decide whether to snapshot the table in chunks, so an approximate row count
+                // should be fine.
                 // Note that sometimes `c.reltuples` can be `-1` if the table is very new and
                 // hasn't been analyzed yet, so we `greatest` it with `1` to make
                 // sure we always have a positive integer (to avoid panics when subtracting
-                // durations)
-                "SELECT greatest(c.reltuples::bigint, 1) AS approximate_nrows
-                 FROM pg_class c JOIN pg_namespace n ON c.relnamespace = n.oid
-                 WHERE c.relname = $1 AND n.nspname = $2",
-                &[&self.name.name.as_str(), &self.schema()?.as_str()],
+                // durations).
+                // Partitioned tables don't have any rows of their own, so for those we add up the
+                // estimates for each of their (transitive) partitions.
+                "SELECT greatest(sum(greatest(c.reltuples, 0))::bigint, 1) AS approximate_nrows
+                 FROM pg_class c
+                 WHERE c.oid = $1
+                 OR (c.relispartition AND c.oid IN (
+                     WITH RECURSIVE partitions(oid) AS (
+                         SELECT $1::oid
+                         UNION
+                         SELECT i.inhrelid
+                         FROM pg_inherits i
+                         JOIN partitions p ON i.inhparent = p.oid
+                     )
+                     SELECT oid FROM partitions
+                 ))",
+                &[&self.oid],
             )
             .await?
-            .try_get::<_, i64>("approximate_nrows")?;
+            .try_get::<_, i64>("approximate_nrows")?)
+    }
+
+    /// Returns the index of the column to split the table into chunks by when snapshotting it, if
+    /// the table's primary key is made up of a single integer column
+    fn chunk_key(&self) -> Option<usize> {
+        let columns = self.constraints.iter().find_map(|c| match &c.definition {
+            TableKey::PrimaryKey { columns, .. } => Some(columns),
+            _ => None,
+        })?;
+        let [column] = columns.as_slice() else {
+            return None;
+        };
+        self.columns.iter().position(|c| {
+            column.name == *c.name && [Type::INT2, Type::INT4, Type::INT8].contains(&c.pg_type)
+        })
+    }
+
+    /// If the table is large enough to be worth snapshotting in chunks and has a key we can split
+    /// it by (see [`Self::chunk_key`]), returns the index of that key column along with the ranges
+    /// of its values to copy in each chunk.
+    ///
+    /// If the table was already part-way through being snapshotted in chunks, it's always split by
+    /// the same key, and only the ranges which weren't copied yet are returned.
+    async fn chunk_ranges<'a>(
+        &self,
+        transaction: &'a deadpool_postgres::Transaction<'a>,
+        approximate_rows: i64,
+        progress: Option<&SnapshotProgress>,
+    ) -> ReadySetResult<Option<(usize, Vec<KeyRange>)>> {
+        if progress.is_none() && (approximate_rows as u64) < MIN_ROWS_TO_CHUNK {
+            return Ok(None);
+        }
+        let Some((key, column)) = self
+            .chunk_key()
+            .filter(|key| progress.iter().all(|progress| progress.key == *key))
+            .and_then(|key| Some((key, self.columns.get(key)?)))
+        else {
+            return Ok(None);
+        };
 
-        // The most efficient way to copy an entire table is COPY BINARY
         let query = format!(
-            "COPY \"{}\".\"{}\" TO stdout BINARY",
+            "SELECT min(\"{col}\")::bigint AS min, max(\"{col}\")::bigint AS max \
+             FROM \"{}\".\"{}\"",
             self.schema()?,
-            self.name.name
+            self.name.name,
+            col = column.name
         );
+        let row = transaction.query_one(query.as_str(), &[]).await?;
+        let ranges = match (
+            row.try_get::<_, Option<i64>>("min")?,
+            row.try_get::<_, Option<i64>>("max")?,
+        ) {
+            (Some(min), Some(max)) => key_ranges(min, max, approximate_rows as u64),
+            _ => vec![],
+        };
+
+        match progress {
+            Some(progress) => Ok(Some((key, remaining_ranges(ranges, &progress.chunks)))),
+            None => Ok((ranges.len() > 1).then_some((key, ranges))),
+        }
+    }
+
+    /// Copy a table's contents from PostgreSQL to ReadySet
+    async fn dump<'a>(
+        &self,
+        transaction: &'a deadpool_postgres::Transaction<'a>,
+        mut noria_table: readyset_client::Table,
+        approximate_rows: i64,
+        snapshot_report_interval_secs: u16,
+        wal_position: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        let mut cnt = 0;
+
+        // The most efficient way to copy an entire table is COPY BINARY. Partitioned tables can
+        // only be copied from via a query, which returns the rows in all of their partitions
+        let query = if self.partitioned {
+            format!(
+                "COPY (SELECT * FROM \"{}\".\"{}\") TO stdout BINARY",
+                self.schema()?,
+                self.name.name
+            )
+        } else {
+            format!(
+                "COPY \"{}\".\"{}\" TO stdout BINARY",
+                self.schema()?,
+                self.name.name
+            )
+        };
         let rows = transaction.copy_out(query.as_str()).await?;
 
         let type_map: Vec<_> = self.columns.iter().map(|c| c.pg_type.clone()).collect();
@@ -617,6 +723,212 @@ impl TableDescription {
 
         Ok(())
     }
+
+    /// Copy a table's contents from PostgreSQL to ReadySet in chunks of the values of its `key`
+    /// column, several of which are copied at once.
+    ///
+    /// Each chunk is copied using its own connection and transaction, all of which use the same
+    /// snapshot. If copying a chunk fails, the rows it inserted are removed and it is retried (up
+    /// to [`MAX_CHUNK_ATTEMPTS`] times), without having to copy the chunks that already finished
+    /// again. Each chunk is recorded in `progress` once it finishes, so that if the replicator
+    /// restarts it can resume from there.
+    #[allow(clippy::too_many_arguments)]
+    async fn dump_chunks(
+        &self,
+        pool: &deadpool_postgres::Pool,
+        snapshot_name: &str,
+        key: usize,
+        ranges: Vec<KeyRange>,
+        mut progress: ChunkProgress,
+        mut noria_table: readyset_client::Table,
+        approximate_rows: i64,
+        snapshot_report_interval_secs: u16,
+        wal_position: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        info!(
+            %approximate_rows,
+            chunks = ranges.len(),
+            copied_chunks = progress.chunks().len(),
+            "Snapshotting started"
+        );
+        let progress_percentage_metric: metrics::Gauge = register_gauge!(
+            recorded::REPLICATOR_SNAPSHOT_PERCENT,
+            "schema" => self.schema()?.to_string(),
+            "name" => self.name.name.to_string()
+        );
+        let start_time = Instant::now();
+        let mut last_report_time = start_time;
+        let snapshot_report_interval_secs = snapshot_report_interval_secs as u64;
+        let mut cnt = 0;
+
+        let mut pending: VecDeque<(KeyRange, usize)> =
+            ranges.into_iter().map(|range| (range, 1)).collect();
+        let mut copying = FuturesUnordered::new();
+        loop {
+            while copying.len() < MAX_CONCURRENT_CHUNKS {
+                let Some((range, attempt)) = pending.pop_front() else {
+                    break;
+                };
+                copying.push(
+                    self.copy_chunk(pool, snapshot_name, key, range, noria_table.clone())
+                        .map(move |res| (range, attempt, res)),
+                );
+            }
+
+            let Some((range, attempt, res)) = copying.next().await else {
+                break;
+            };
+            match res {
+                Ok(Ok(rows)) => {
+                    debug!(?range, %rows, "Finished copying chunk");
+                    cnt += rows;
+                    progress.record(range, wal_position).await?;
+                }
+                Ok(Err(error)) if attempt < MAX_CHUNK_ATTEMPTS => {
+                    warn!(%error, ?range, %attempt, "Retrying chunk");
+                    pending.push_back((range, attempt + 1));
+                }
+                Ok(Err(error)) | Err(error) => {
+                    progress_percentage_metric.set(0.0);
+                    return Err(error);
+                }
+            }
+
+            if snapshot_report_interval_secs != 0
+                && last_report_time.elapsed().as_secs() > snapshot_report_interval_secs
+            {
+                last_report_time = Instant::now();
+                crate::log_snapshot_progress(
+                    start_time.elapsed(),
+                    cnt as i64,
+                    approximate_rows,
+                    &progress_percentage_metric,
+                );
+            }
+        }
+
+        let replication_offset = progress.finish(wal_position).await?;
+        info!(
+            table = %noria_table.table_name().display(Dialect::PostgreSQL),
+            %replication_offset,
+            "Setting replication offset and compacting table"
+        );
+        noria_table
+            .perform_all([
+                TableOperation::SetReplicationOffset(replication_offset),
+                TableOperation::SetSnapshotMode(false),
+            ])
+            .await?;
+
+        info!(rows_replicated = %cnt, "Snapshotting finished");
+        progress_percentage_metric.set(100.0);
+
+        Ok(())
+    }
+
+    /// Copy the rows of the table whose `key` column is within `range` to ReadySet, returning the
+    /// number of rows copied.
+    ///
+    /// If copying the chunk fails but the rows it inserted could be removed again, returns the
+    /// error in an inner `Err` so that the chunk can be retried.
+    async fn copy_chunk(
+        &self,
+        pool: &deadpool_postgres::Pool,
+        snapshot_name: &str,
+        key: usize,
+        range: KeyRange,
+        mut noria_table: readyset_client::Table,
+    ) -> ReadySetResult<ReadySetResult<u64>> {
+        let mut keys = vec![];
+        match self
+            .copy_chunk_rows(pool, snapshot_name, key, range, &mut noria_table, &mut keys)
+            .await
+        {
+            Ok(rows) => Ok(Ok(rows)),
+            Err(error) => roll_back_chunk(&mut noria_table, range, keys, error).await,
+        }
+    }
+
+    /// Copy the rows for a single chunk (see [`Self::copy_chunk`]), adding the key of each row to
+    /// `keys` before it's inserted
+    async fn copy_chunk_rows(
+        &self,
+        pool: &deadpool_postgres::Pool,
+        snapshot_name: &str,
+        key: usize,
+        range: KeyRange,
+        noria_table: &mut readyset_client::Table,
+        keys: &mut Vec<DfValue>,
+    ) -> ReadySetResult<u64> {
+        let column = self
+            .columns
+            .get(key)
+            .ok_or_else(|| internal_err!("Chunk key column out of bounds"))?;
+
+        let mut client = pool.get().await?;
+        let transaction = snapshot_transaction(&mut client, snapshot_name).await?;
+        let query = format!(
+            "COPY (SELECT * FROM \"{}\".\"{}\" WHERE \"{}\" BETWEEN {} AND {}) TO stdout BINARY",
+            self.schema()?,
+            self.name.name,
+            column.name,
+            range.start,
+            range.end
+        );
+        let rows = transaction.copy_out(query.as_str()).await?;
+
+        let type_map: Vec<_> = self.columns.iter().map(|c| c.pg_type.clone()).collect();
+        let binary_row_batches =
+            pgsql::binary_copy::BinaryCopyOutStream::new(rows, &type_map).chunks(BATCH_SIZE);
+        pin_mut!(binary_row_batches);
+
+        let mut cnt = 0;
+        while let Some(batch) = binary_row_batches.next().await {
+            let noria_rows = batch
+                .into_iter()
+                .map(|row| {
+                    let row = row?;
+                    (0..type_map.len())
+                        .map(|i| row.try_get::<DfValue>(i))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| {
+                            ReadySetError::ReplicationFailed(format!(
+                                "Failed converting to DfValue, table: {}, chunk: {:?}, err: {}",
+                                noria_table.table_name().display(Dialect::PostgreSQL),
+                                range,
+                                err
+                            ))
+                        })
+                })
+                .collect::<ReadySetResult<Vec<_>>>()?;
+
+            keys.extend(noria_rows.iter().filter_map(|row| row.get(key).cloned()));
+            cnt += noria_rows.len() as u64;
+            noria_table.insert_many(noria_rows).await?;
+        }
+
+        Ok(cnt)
+    }
+}
+
+/// Start a read-only transaction on `client` that reads from the snapshot named `snapshot_name`,
+/// so that everything we copy from upstream is consistent with everything else
+async fn snapshot_transaction<'a>(
+    client: &'a mut deadpool_postgres::Object,
+    snapshot_name: &str,
+) -> ReadySetResult<deadpool_postgres::Transaction<'a>> {
+    let transaction = client
+        .build_transaction()
+        .deferrable(true)
+        .isolation_level(pgsql::IsolationLevel::RepeatableRead)
+        .read_only(true)
+        .start()
+        .await?;
+
+    let query = format!("SET TRANSACTION SNAPSHOT '{}'", snapshot_name);
+    transaction.query(query.as_str(), &[]).await?;
+
+    Ok(transaction)
 }
 
 impl<'a> PostgresReplicator<'a> {
@@ -625,6 +937,7 @@ impl<'a> PostgresReplicator<'a> {
         pool: deadpool_postgres::Pool,
         noria: &'a mut readyset_client::ReadySetHandle,
         table_filter: TableFilter,
+        standby: bool,
     ) -> ReadySetResult<PostgresReplicator<'a>> {
         let transaction = Some(
             client
@@ -640,45 +953,83 @@ impl<'a> PostgresReplicator<'a> {
             pool,
             noria,
             table_filter,
+            standby,
         })
     }
 
+    /// Snapshot a single table. If `progress` is set, the table was already part-way through
+    /// being snapshotted in chunks, and only the remaining chunks are copied.
+    #[allow(clippy::too_many_arguments)]
     async fn snapshot_table(
         pool: deadpool_postgres::Pool,
+        mut noria: readyset_client::ReadySetHandle,
         span: tracing::Span,
         table: TableDescription,
-        noria_table: readyset_client::Table,
+        mut noria_table: readyset_client::Table,
+        progress: Option<SnapshotProgress>,
         snapshot_report_interval_secs: u16,
         snapshot_name: String,
         wal_position: &ReplicationOffset,
     ) -> ReadySetResult<()> {
-        let mut client = pool.get().await?;
-
-        let transaction = client
-            .build_transaction()
-            .deferrable(true)
-            .isolation_level(pgsql::IsolationLevel::RepeatableRead)
-            .read_only(true)
-            .start()
-            .await?;
+        async {
+            let mut client = pool.get().await?;
+            let transaction = snapshot_transaction(&mut client, &snapshot_name).await?;
 
-        // Ensure each table has a consistent view by using the same snapshot
-        let query = format!("SET TRANSACTION SNAPSHOT '{}'", snapshot_name);
-        transaction.query(query.as_str(), &[]).await?;
-
-        table
-            .dump(
-                &transaction,
-                noria_table,
-                snapshot_report_interval_secs,
-                wal_position,
-            )
-            .instrument(span.clone())
-            .await
-            .map_err(|e| ReadySetError::TableError {
-                table: table.name.clone(),
-                source: Box::new(e),
-            })
+            let approximate_rows = table.approximate_rows(&transaction).await?;
+            match table
+                .chunk_ranges(&transaction, approximate_rows, progress.as_ref())
+                .await?
+            {
+                Some((key, ranges)) => {
+                    // Each chunk is copied using a connection of its own, so give this one back to
+                    // the pool first
+                    transaction.rollback().await?;
+                    drop(client);
+                    let progress = ChunkProgress::new(
+                        noria,
+                        table.name.clone(),
+                        progress.unwrap_or_else(|| SnapshotProgress::new(key)),
+                    );
+                    table
+                        .dump_chunks(
+                            &pool,
+                            &snapshot_name,
+                            key,
+                            ranges,
+                            progress,
+                            noria_table,
+                            approximate_rows,
+                            snapshot_report_interval_secs,
+                            wal_position,
+                        )
+                        .await
+                }
+                None => {
+                    if progress.is_some() {
+                        // The table can't be split by the same key as before anymore, so we have
+                        // to start over
+                        noria_table.truncate().await?;
+                        noria_table.set_snapshot_mode(true).await?;
+                        noria.set_snapshot_progress(&table.name, None).await?;
+                    }
+                    table
+                        .dump(
+                            &transaction,
+                            noria_table,
+                            approximate_rows,
+                            snapshot_report_interval_secs,
+                            wal_position,
+                        )
+                        .await
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await
+        .map_err(|e| ReadySetError::TableError {
+            table: table.name.clone(),
+            source: Box::new(e),
+        })
     }
 
     /// Snapshot the contents of the upstream database to ReadySet, starting with the DDL, followed
@@ -692,29 +1043,42 @@ impl<'a> PostgresReplicator<'a> {
         create_schema: &mut CreateSchema,
         snapshot_report_interval_secs: u16,
         full_snapshot: bool,
+        resnapshot_tables: &HashSet<Relation>,
     ) -> ReadySetResult<()> {
         let wal_position = PostgresPosition::commit_end(replication_slot.consistent_point).into();
         self.set_snapshot(&replication_slot.snapshot_name).await?;
 
         let table_list = self.get_table_list(TableKind::RegularTable).await?;
+        let partitioned_tables = self.get_table_list(TableKind::PartitionedTable).await?;
         let view_list = self.get_table_list(TableKind::View).await?;
         let custom_types = self.get_custom_types().await?;
 
-        let (table_list, mut non_replicated) =
-            table_list.into_iter().partition::<Vec<_>, _>(|tbl| {
-                self.table_filter
+        // If our publication publishes changes to partitions via their partition root, we
+        // replicate each partitioned table as a single table, in place of its partitions.
+        // Otherwise, we can only replicate the partitions themselves.
+        let via_partition_root = self.publishes_via_partition_root().await?;
+
+        let mut non_replicated = vec![];
+        let table_list = table_list
+            .into_iter()
+            .chain(partitioned_tables)
+            .filter_map(|tbl| {
+                let reason = if tbl.partition && via_partition_root {
+                    NotReplicatedReason::Partition
+                } else if tbl.partitioned && !via_partition_root {
+                    NotReplicatedReason::Partitioned
+                } else if !self
+                    .table_filter
                     .should_be_processed(tbl.schema.as_str(), tbl.name.as_str())
-            });
-
-        // We don't support partitioned tables (only the partitions themselves) so mark those as
-        // non-replicated as well
-        let partitioned_tables = self.get_table_list(TableKind::PartitionedTable).await?;
-        let partitioned_identifiers: HashSet<_> = partitioned_tables
-            .iter()
-            .map(|te| format!("{}.{}", te.schema, te.name))
-            .collect();
-
-        non_replicated.extend(partitioned_tables);
+                {
+                    NotReplicatedReason::Configuration
+                } else {
+                    return Some(tbl);
+                };
+                non_replicated.push((tbl, reason));
+                None
+            })
+            .collect::<Vec<_>>();
 
         // We don't filter the view list by schemas since a view could be in schema 1 (that may not
         // be replicated), but refer to only tables in schema 2 that are all replicated. If we try
@@ -732,20 +1096,13 @@ impl<'a> PostgresReplicator<'a> {
             .extend_recipe_no_leader_ready(ChangeList::from_changes(
                 non_replicated
                     .into_iter()
-                    .map(|te| {
-                        let te_identifier = format!("{}.{}", te.schema, te.name);
-                        let not_replicated_reason: NotReplicatedReason =
-                            if partitioned_identifiers.contains(&te_identifier) {
-                                NotReplicatedReason::Partitioned
-                            } else {
-                                NotReplicatedReason::Configuration
-                            };
+                    .map(|(te, reason)| {
                         Change::AddNonReplicatedRelation(NonReplicatedRelation {
                             name: Relation {
                                 schema: Some(te.schema.into()),
                                 name: te.name.into(),
                             },
-                            reason: not_replicated_reason,
+                            reason,
                         })
                     })
                     .collect::<Vec<_>>(),
@@ -905,9 +1262,25 @@ impl<'a> PostgresReplicator<'a> {
 
         let replication_offsets = self.noria.replication_offsets().await?;
 
+        // Tables that were part-way through being snapshotted in chunks can carry on from where
+        // they left off, unless we've dropped them or need to replace their contents
+        let mut snapshot_progress = self.noria.snapshot_progress().await?;
+        for table in snapshot_progress.keys() {
+            if full_snapshot || resnapshot_tables.contains(table) {
+                self.noria.set_snapshot_progress(table, None).await?;
+            }
+        }
+        if full_snapshot {
+            snapshot_progress.clear();
+        } else {
+            snapshot_progress.retain(|table, _| !resnapshot_tables.contains(table));
+        }
+
         if !full_snapshot {
             tables
-                .extract_if(|t| replication_offsets.has_table(&t.name))
+                .extract_if(|t| {
+                    replication_offsets.has_table(&t.name) && !resnapshot_tables.contains(&t.name)
+                })
                 .for_each(|t| {
                     debug!(
                         table = %t.name.display(Dialect::PostgreSQL),
@@ -918,9 +1291,12 @@ impl<'a> PostgresReplicator<'a> {
 
         // Emit a no-op replication message: If there isn't any traffic since we started
         // snapshotting, we may be stuck 'catching up' because the lsn of a re-snapshot slot can be
-        // higher than the main slot.
-        let query = "SELECT pg_logical_emit_message(true, 'readyset_snapshot_done', '')";
-        get_transaction!(self).query(query, &[]).await?;
+        // higher than the main slot. A standby can't write to the WAL, so there we have to wait for
+        // the primary to write something instead.
+        if !self.standby {
+            let query = "SELECT pg_logical_emit_message(true, 'readyset_snapshot_done', '')";
+            get_transaction!(self).query(query, &[]).await?;
+        }
 
         // Commit the transaction we were using to snapshot the schema. This is important since that
         // transaction holds onto locks for tables which we now need to load data from.
@@ -938,9 +1314,21 @@ impl<'a> PostgresReplicator<'a> {
                 .table(table.name.clone())
                 .instrument(span.clone())
                 .await?;
-            span.in_scope(|| trace!("Setting snapshot mode"));
-            noria_table.set_snapshot_mode(true).await?;
-            span.in_scope(|| trace!("Set snapshot mode"));
+            if resnapshot_tables.contains(&table.name) {
+                // The table is being resnapshotted in place, so clear out its old contents first
+                span.in_scope(|| trace!("Truncating table"));
+                noria_table.truncate().await?;
+            }
+            let progress = snapshot_progress.remove(&table.name);
+            if progress.is_none() {
+                // Entering snapshot mode clears out the table, so we can't do that if we're
+                // resuming from the chunks we already copied
+                span.in_scope(|| trace!("Setting snapshot mode"));
+                noria_table.set_snapshot_mode(true).await?;
+                span.in_scope(|| trace!("Set snapshot mode"));
+            } else {
+                span.in_scope(|| info!("Resuming snapshot from previously copied chunks"));
+            }
 
             let pool = self.pool.clone();
 
@@ -948,9 +1336,11 @@ impl<'a> PostgresReplicator<'a> {
             let table = table.clone();
             snapshotting_tables.push(Self::snapshot_table(
                 pool,
+                self.noria.clone(),
                 span,
                 table,
                 noria_table,
+                progress,
                 snapshot_report_interval_secs,
                 snapshot_name,
                 &wal_position,
@@ -963,9 +1353,15 @@ impl<'a> PostgresReplicator<'a> {
         while let Some(res) = snapshotting_tables.next().await {
             if let Err(e) = res {
                 match e {
-                    ReadySetError::TableError { ref table, .. } => {
+                    // If we lost our connection, we can resume snapshotting the table once we
+                    // reconnect
+                    ReadySetError::TableError {
+                        ref table,
+                        ref source,
+                    } if !source.is_networking_related() => {
                         warn!(%e, table=%table.display(Dialect::PostgreSQL), "Error snapshotting, table will not be used");
                         tables.retain(|t| t.name != *table);
+                        self.noria.set_snapshot_progress(table, None).await?;
                         self.noria
                             .extend_recipe_no_leader_ready(ChangeList::from_changes(
                                 vec![
@@ -1009,7 +1405,7 @@ impl<'a> PostgresReplicator<'a> {
         // with replication when the column count on an insert doesnt match the column count
         // of the table.
         let query = r"
-        SELECT n.nspname, c.oid, c.relname, c.relkind
+        SELECT n.nspname, c.oid, c.relname, c.relkind, c.relispartition
         FROM pg_catalog.pg_class c
         LEFT JOIN pg_catalog.pg_namespace n
         ON n.oid = c.relnamespace
@@ -1044,6 +1440,26 @@ impl<'a> PostgresReplicator<'a> {
         res.into_iter().map(TryInto::try_into).collect()
     }
 
+    /// Returns true if our publication publishes changes to partitions as changes to their
+    /// partition root, which is what lets us replicate partitioned tables
+    async fn publishes_via_partition_root(&mut self) -> Result<bool, pgsql::Error> {
+        // `pubviaroot` only exists in PostgreSQL 13 and above, so go through jsonb to avoid
+        // referencing it directly
+        let query = r"
+            SELECT (to_jsonb(p) ->> 'pubviaroot')::boolean
+            FROM pg_catalog.pg_publication p
+            WHERE p.pubname = $1
+        ";
+        let row = get_transaction!(self)
+            .query_opt(query, &[&PUBLICATION_NAME])
+            .await?;
+        Ok(row
+            .map(|row| row.try_get::<_, Option<bool>>(0))
+            .transpose()?
+            .flatten()
+            .unwrap_or(false))
+    }
+
     /// Assign the specific snapshot to the underlying transaction
     async fn set_snapshot(&mut self, name: &str) -> Result<(), pgsql::Error> {
         let query = format!("SET TRANSACTION SNAPSHOT '{}'", name);
@@ -1120,14 +1536,25 @@ impl<'a> PostgresReplicator<'a> {
     ) -> ReadySetResult<()> {
         let tables_needing_replica_identity = get_transaction!(self)
             .query(
-                // Find all tables that are in the table list, and don't already have a primary key
-                // or a non-default replica identity set
-                "select n.nspname as schema, c.relname as name from pg_class c
+                // Find all tables that are in the table list (or are partitions of partitioned
+                // tables in the table list, since changes to partitions are written to the WAL
+                // with the replica identity of the partition), and don't already have a primary
+                // key or a non-default replica identity set
+                "with recursive tables(oid) as (
+                     select unnest($1::oid[])
+                     union
+                     select i.inhrelid from pg_inherits i
+                     join tables t on i.inhparent = t.oid
+                     join pg_class p on p.oid = i.inhparent
+                     where p.relkind = 'p'
+                 )
+                 select n.nspname as schema, c.relname as name from pg_class c
                  join pg_namespace n
                  on n.oid = c.relnamespace
                  where c.oid not in (select indrelid from pg_index where indisprimary)
                  and c.relreplident = 'd'
-                 and c.oid = any ($1::oid[])",
+                 and c.relkind = 'r'
+                 and c.oid in (select oid from tables)",
                 &[&table_list.iter().map(|t| t.oid).collect::<Vec<_>>()],
             )
             .await?;
@@ -1135,6 +1562,16 @@ impl<'a> PostgresReplicator<'a> {
         for table_row in tables_needing_replica_identity {
             let schema: String = table_row.get("schema");
             let name: String = table_row.get("name");
+            if self.standby {
+                warn!(
+                    %schema,
+                    %name,
+                    "Table has no primary key or replica identity, and we can't set one on a \
+                     standby. Run ALTER TABLE ... REPLICA IDENTITY FULL on the primary for \
+                     updates and deletes to be replicated"
+                );
+                continue;
+            }
             trace!(%schema, %name, "Setting REPLICA IDENTITY FULL for table");
             get_transaction!(self)
                 .execute(
@@ -1204,6 +1641,7 @@ mod tests {
                 },
                 kind: Some(ConstraintKind::PrimaryKey),
             }],
+            partitioned: false,
         };
         let res = parse_query(Dialect::PostgreSQL, desc.to_string());
         assert!(res.is_ok(), "{}", res.err().unwrap());
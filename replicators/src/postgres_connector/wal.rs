@@ -28,6 +28,7 @@ pub enum WalError {
     CorruptDelete,
     CorruptTruncate,
     CorruptMessage,
+    CorruptStream,
     TryFromSliceError,
     ReadySetError(ReadySetError),
     ConnectionLost(String),
@@ -303,6 +304,49 @@ pub enum WalRecord {
         /// The content of the logical decoding message.
         payload: Bytes,
     },
+    /// Sent to indicate the start of a chunk of changes from a streamed (in-progress) transaction.
+    /// The records in the chunk are wrapped in `WalRecord::Streamed`.
+    ///
+    /// Only sent if streaming was requested, which requires protocol version 2 or above.
+    StreamStart {
+        /// Xid of the transaction.
+        xid: i32,
+        /// Whether this is the first chunk of changes streamed for the transaction.
+        first_segment: bool,
+    },
+    /// Sent to indicate the end of a chunk of changes from a streamed transaction
+    StreamStop,
+    /// Sent to indicate that a streamed transaction has committed, and that all of the changes
+    /// streamed for it should be applied
+    StreamCommit {
+        /// Xid of the transaction.
+        xid: i32,
+        /// Flags; currently unused (must be 0).
+        flags: u8,
+        /// The LSN of the commit.
+        lsn: CommitLsn,
+        /// The end LSN of the transaction.
+        end_lsn: Lsn,
+        /// Commit timestamp of the transaction. The value is in number of microseconds since
+        /// PostgreSQL epoch (2000-01-01).
+        timestamp: i64,
+    },
+    /// Sent to indicate that a streamed transaction, or one of its subtransactions, has aborted,
+    /// and that the changes streamed for it should be discarded
+    StreamAbort {
+        /// Xid of the transaction.
+        xid: i32,
+        /// Xid of the subtransaction (will be same as xid of the transaction for top-level
+        /// transactions).
+        subxid: i32,
+    },
+    /// A record sent between a `StreamStart` and a `StreamStop`, which belongs to a streamed
+    /// transaction
+    Streamed {
+        /// Xid of the (sub)transaction the record belongs to.
+        xid: i32,
+        record: Box<WalRecord>,
+    },
     Unknown(Bytes),
 }
 
@@ -338,15 +382,8 @@ pub enum TupleEntry {
 impl TryFrom<Bytes> for WalData {
     type Error = WalError;
 
-    // The kind of `WalData` is identified by the value of the first byte
     fn try_from(b: Bytes) -> Result<Self, Self::Error> {
-        match *b.first().ok_or(WalError::Empty)? {
-            b'k' => WalData::keepalive(b),
-            b'w' => WalData::xlog_data(b),
-            b'r' => WalData::standby_update(b),
-            b'h' => WalData::hot_standby_feedback(b),
-            _ => Ok(WalData::Unknown(b)),
-        }
+        WalData::parse(b, false)
     }
 }
 
@@ -365,12 +402,30 @@ impl TryFrom<Bytes> for WalRecord {
             b'D' => WalRecord::delete(b),
             b'T' => WalRecord::truncate(b),
             b'M' => WalRecord::message(b),
+            b'S' => WalRecord::stream_start(b),
+            b'E' => WalRecord::stream_stop(b),
+            b'c' => WalRecord::stream_commit(b),
+            b'A' => WalRecord::stream_abort(b),
             _ => Ok(WalRecord::Unknown(b)),
         }
     }
 }
 
 impl WalData {
+    /// Parse a `WalData` message. `in_stream` should be set if the message was sent between a
+    /// `WalRecord::StreamStart` and a `WalRecord::StreamStop`, since the records in those are
+    /// formatted differently.
+    pub(crate) fn parse(b: Bytes, in_stream: bool) -> Result<Self, WalError> {
+        // The kind of `WalData` is identified by the value of the first byte
+        match *b.first().ok_or(WalError::Empty)? {
+            b'k' => WalData::keepalive(b),
+            b'w' => WalData::xlog_data(b, in_stream),
+            b'r' => WalData::standby_update(b),
+            b'h' => WalData::hot_standby_feedback(b),
+            _ => Ok(WalData::Unknown(b)),
+        }
+    }
+
     /// Parse as `Keepalive`, assumes b[0] == 'k'
     fn keepalive(b: Bytes) -> Result<Self, WalError> {
         if b.len() != 18 {
@@ -384,7 +439,7 @@ impl WalData {
     }
 
     /// Parse as `XLogData`, assumes b[0] == 'w'
-    fn xlog_data(mut b: Bytes) -> Result<Self, WalError> {
+    fn xlog_data(mut b: Bytes, in_stream: bool) -> Result<Self, WalError> {
         if b.len() < 25 {
             return Err(WalError::IncorrectLen(b[0]));
         }
@@ -392,7 +447,11 @@ impl WalData {
         let start = i64::from_be_bytes(b[1..9].try_into()?).into();
         let end = i64::from_be_bytes(b[9..17].try_into()?).into();
         let time = i64::from_be_bytes(b[17..25].try_into()?);
-        let data = b.split_off(25).try_into()?;
+        let data = if in_stream {
+            WalRecord::streamed(b.split_off(25))?
+        } else {
+            b.split_off(25).try_into()?
+        };
 
         Ok(WalData::XLogData {
             start,
@@ -482,6 +541,94 @@ impl WalRecord {
         })
     }
 
+    /// Parse a record sent as part of a streamed transaction. Records that modify data (or describe
+    /// the relations and types of the data they modify) are prefixed with the xid of the
+    /// (sub)transaction they belong to, and are returned as `Streamed`.
+    fn streamed(b: Bytes) -> Result<Self, WalError> {
+        let kind = *b.first().ok_or(WalError::Empty)?;
+        if !matches!(kind, b'R' | b'Y' | b'I' | b'U' | b'D' | b'T' | b'M') {
+            return b.try_into();
+        }
+
+        if b.len() < 5 {
+            return Err(WalError::IncorrectLen(kind));
+        }
+        let xid = i32::from_be_bytes(b[1..5].try_into()?);
+
+        // Skip over the xid, which leaves the rest of the record where the parsers below expect it
+        // to be (none of them look at the first byte, which identifies the kind of record)
+        let b = b.slice(4..);
+        let record = match kind {
+            b'R' => WalRecord::relation(b),
+            b'Y' => WalRecord::type_(b),
+            b'I' => WalRecord::insert(b),
+            b'U' => WalRecord::update(b),
+            b'D' => WalRecord::delete(b),
+            b'T' => WalRecord::truncate(b),
+            _ => WalRecord::message(b),
+        }?;
+
+        Ok(WalRecord::Streamed {
+            xid,
+            record: Box::new(record),
+        })
+    }
+
+    /// Parse as `StreamStart`, assumes b[0] == 'S'
+    fn stream_start(b: Bytes) -> Result<Self, WalError> {
+        if b.len() != 6 {
+            return Err(WalError::IncorrectLen(b[0]));
+        }
+
+        let xid = i32::from_be_bytes(b[1..5].try_into()?);
+        let first_segment = b[5] == 1;
+
+        Ok(WalRecord::StreamStart { xid, first_segment })
+    }
+
+    /// Parse as `StreamStop`, assumes b[0] == 'E'
+    fn stream_stop(b: Bytes) -> Result<Self, WalError> {
+        if b.len() != 1 {
+            return Err(WalError::IncorrectLen(b[0]));
+        }
+
+        Ok(WalRecord::StreamStop)
+    }
+
+    /// Parse as `StreamCommit`, assumes b[0] == 'c'
+    fn stream_commit(b: Bytes) -> Result<Self, WalError> {
+        if b.len() != 30 {
+            return Err(WalError::IncorrectLen(b[0]));
+        }
+
+        let xid = i32::from_be_bytes(b[1..5].try_into()?);
+        let flags = b[5];
+        let lsn = i64::from_be_bytes(b[6..14].try_into()?).into();
+        let end_lsn = i64::from_be_bytes(b[14..22].try_into()?).into();
+        let timestamp = i64::from_be_bytes(b[22..30].try_into()?);
+
+        Ok(WalRecord::StreamCommit {
+            xid,
+            flags,
+            lsn,
+            end_lsn,
+            timestamp,
+        })
+    }
+
+    /// Parse as `StreamAbort`, assumes b[0] == 'A'
+    fn stream_abort(b: Bytes) -> Result<Self, WalError> {
+        // Protocol version 4 adds the LSN and timestamp of the abort, which we don't need
+        if b.len() < 9 {
+            return Err(WalError::CorruptStream);
+        }
+
+        let xid = i32::from_be_bytes(b[1..5].try_into()?);
+        let subxid = i32::from_be_bytes(b[5..9].try_into()?);
+
+        Ok(WalRecord::StreamAbort { xid, subxid })
+    }
+
     /// Finds the first occurrence of a null, and splits the buffer at that position
     /// the returned value contains all the bytes up to the null, and the input buffer
     /// references all the bytes past the null
@@ -866,6 +1013,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn wal_parse_stream_start() {
+        let wal: WalData = Bytes::copy_from_slice(
+            b"w\0\0\0\0\x01l\xafx\0\0\0\0\x01l\xafx\0\x02g?\x9e\xc7y\xbcS\0\0\x02\xe7\x01",
+        )
+        .try_into()
+        .unwrap();
+
+        assert_eq!(
+            wal,
+            WalData::XLogData {
+                start: 23900024.into(),
+                end: 23900024.into(),
+                time: 676472897894844,
+                data: WalRecord::StreamStart {
+                    xid: 743,
+                    first_segment: true
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn wal_parse_streamed_insert() {
+        let wal = WalData::parse(
+            Bytes::copy_from_slice(
+                b"w\0\0\0\0\x01l\xafx\0\0\0\0\x01l\xafx\0\x02g?\x9e\xc7y\xbcI\0\0\x02\xe8\0\0@/N\0\x02t\0\0\0\x0210t\0\0\0\x04Drop",
+            ),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            wal,
+            WalData::XLogData {
+                start: 23900024.into(),
+                end: 23900024.into(),
+                time: 676472897894844,
+                data: WalRecord::Streamed {
+                    xid: 744,
+                    record: Box::new(WalRecord::Insert {
+                        relation_id: 16431,
+                        new_tuple: TupleData {
+                            n_cols: 2,
+                            cols: vec![
+                                TupleEntry::Text(Bytes::copy_from_slice(b"10")),
+                                TupleEntry::Text(Bytes::copy_from_slice(b"Drop")),
+                            ]
+                        }
+                    })
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn wal_parse_stream_commit() {
+        let wal = WalData::parse(
+            Bytes::copy_from_slice(
+                b"w\0\0\0\0\x01l\xafx\0\0\0\0\x01l\xafx\0\x02g?\x9e\xc7y\xbcc\0\0\x02\xe7\0\0\0\0\0\x01l\xafx\0\0\0\0\x01l\xb0\0\0\x02g?\x9e\xc7y\xbc",
+            ),
+            // Stream commits are sent outside of streamed chunks, but shouldn't be affected by
+            // whether or not we're in one
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            wal,
+            WalData::XLogData {
+                start: 23900024.into(),
+                end: 23900024.into(),
+                time: 676472897894844,
+                data: WalRecord::StreamCommit {
+                    xid: 743,
+                    flags: 0,
+                    lsn: 23900024.into(),
+                    end_lsn: 23900160.into(),
+                    timestamp: 676472897894844,
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn wal_parse_stream_abort() {
+        let wal: WalData = Bytes::copy_from_slice(
+            b"w\0\0\0\0\x01l\xafx\0\0\0\0\x01l\xafx\0\x02g?\x9e\xc7y\xbcA\0\0\x02\xe7\0\0\x02\xe8",
+        )
+        .try_into()
+        .unwrap();
+
+        assert_eq!(
+            wal,
+            WalData::XLogData {
+                start: 23900024.into(),
+                end: 23900024.into(),
+                time: 676472897894844,
+                data: WalRecord::StreamAbort {
+                    xid: 743,
+                    subxid: 744
+                }
+            }
+        );
+    }
+
     #[test]
     fn wal_parse_type() {
         let wal: WalData = Bytes::copy_from_slice(
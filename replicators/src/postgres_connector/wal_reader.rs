@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::sync::Arc;
 
@@ -34,6 +34,37 @@ pub struct WalReader {
     relations: HashMap<i32, Relation>,
     /// Keeps track of the OIDs of all custom types we've seen
     custom_types: HashSet<u32>,
+    /// The transactions that are being streamed to us while they're still in progress, by xid
+    streams: HashMap<i32, StreamedTransaction>,
+    /// The xid of the transaction whose changes are currently being streamed to us, if any
+    stream_xid: Option<i32>,
+    /// The events of a streamed transaction that has committed, which we have yet to return
+    committed: VecDeque<WalEvent>,
+}
+
+/// The changes streamed to us so far for a transaction that is still in progress. These are
+/// buffered until the transaction commits, at which point they're applied all at once.
+#[derive(Default)]
+struct StreamedTransaction {
+    /// The relation mappings sent as part of the transaction. These only apply to the
+    /// transaction's own changes until it commits.
+    relations: HashMap<i32, Relation>,
+    /// The events for each of the transaction's changes, along with the xid of the
+    /// (sub)transaction each of them belongs to
+    events: Vec<(i32, WalEvent)>,
+}
+
+impl StreamedTransaction {
+    /// Discards the changes made by the subtransaction with xid `subxid`, which has aborted.
+    ///
+    /// Like Postgres's own logical replication workers, this discards everything from the first
+    /// change made by the subtransaction onwards, since any later changes belong to either the
+    /// subtransaction or to subtransactions nested within it.
+    fn abort_subtransaction(&mut self, subxid: i32) {
+        if let Some(pos) = self.events.iter().position(|(xid, _)| *xid == subxid) {
+            self.events.truncate(pos);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -112,6 +143,9 @@ impl WalReader {
         WalReader {
             relations: Default::default(),
             custom_types: Default::default(),
+            streams: Default::default(),
+            stream_xid: None,
+            committed: Default::default(),
             wal,
         }
     }
@@ -142,15 +176,24 @@ impl WalReader {
             wal,
             relations,
             custom_types,
+            streams,
+            stream_xid,
+            committed,
         } = self;
 
         loop {
+            if let Some(event) = committed.pop_front() {
+                return Ok(event);
+            }
+
             let data: WalData = match wal
                 .next()
                 .await
                 .map_err(|e| WalError::ReadySetError(e.into()))?
             {
-                pgsql::Message::CopyData(body) => body.into_bytes().try_into()?,
+                pgsql::Message::CopyData(body) => {
+                    WalData::parse(body.into_bytes(), stream_xid.is_some())?
+                }
                 _ => {
                     return Err(WalError::ReadySetError(ReadySetError::ReplicationFailed(
                         "Unexpected message during WAL replication".to_string(),
@@ -173,318 +216,380 @@ impl WalReader {
             trace!(?lsn, ?record);
 
             match record {
-                WalRecord::Begin { final_lsn, .. } => return Ok(WalEvent::Begin { final_lsn }),
-                WalRecord::Commit { lsn, end_lsn, .. } => {
-                    return Ok(WalEvent::Commit { lsn, end_lsn })
-                }
-                WalRecord::Relation(mapping) => {
-                    // Store the relation in the hash map for future use
-                    let id = mapping.id;
-                    let schema = String::from_utf8(mapping.schema.to_vec()).map_err(|v| {
-                        ReadySetError::ReplicationFailed(format!(
-                            "Non UTF8 name {:?}",
-                            v.as_bytes()
-                        ))
-                    })?;
-                    let table = String::from_utf8(mapping.name.to_vec()).map_err(|v| {
-                        ReadySetError::ReplicationFailed(format!(
-                            "Non UTF8 name {:?}",
-                            v.as_bytes()
-                        ))
-                    })?;
-                    relations.insert(
-                        id,
-                        Relation {
-                            schema,
-                            table,
-                            mapping,
-                        },
-                    );
+                WalRecord::StreamStart { xid, first_segment } => {
+                    if first_segment {
+                        streams.insert(xid, Default::default());
+                    } else {
+                        streams.entry(xid).or_default();
+                    }
+                    *stream_xid = Some(xid);
                 }
-                WalRecord::Insert {
-                    relation_id,
-                    new_tuple,
+                WalRecord::StreamStop => *stream_xid = None,
+                WalRecord::Streamed {
+                    xid: subxid,
+                    record,
                 } => {
-                    if let Some(Relation {
-                        schema,
-                        table,
-                        mapping,
-                    }) = relations.get(&relation_id)
+                    let stream = stream_xid
+                        .and_then(|xid| streams.get_mut(&xid))
+                        .ok_or(WalError::CorruptStream)?;
+                    if let Some(event) =
+                        Self::record_event(*record, lsn, &mut stream.relations, custom_types)?
                     {
-                        return Ok(
-                            WalEvent::Insert {
-                                schema: schema.clone(),
-                                table: table.clone(),
-                                tuple: new_tuple
-                                    .into_noria_vec(mapping, custom_types, false)?
-                                    .into_iter()
-                                    .collect::<Option<Vec<_>>>()
-                                    // Insert records should never have "unchanged" fields... unchanged from what?
-                                    .ok_or_else(|| WalError::TableError {
-                                        kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                            reason: "WalRecord::Insert::new_tuple should never contain TupleEntry::Unchanged",
-                                        },
-                                        schema: schema.clone(),
-                                        table: table.clone(),
-                                    })?,
-                                lsn,
-                            },
-                       );
-                    } else {
-                        debug!(
-                            relation_id,
-                            "Ignoring WAL insert event for unknown relation"
-                        );
+                        stream.events.push((subxid, event));
                     }
                 }
-                WalRecord::Update {
-                    relation_id,
-                    key_tuple,
-                    old_tuple,
-                    new_tuple,
+                WalRecord::StreamCommit {
+                    xid, lsn, end_lsn, ..
                 } => {
-                    let Relation {
+                    // Now that the transaction has committed, replay all the changes that were
+                    // streamed for it as if it had been sent to us as a whole
+                    let stream = streams.remove(&xid).unwrap_or_default();
+                    relations.extend(stream.relations);
+                    committed.push_back(WalEvent::Begin { final_lsn: lsn });
+                    committed.extend(stream.events.into_iter().map(|(_, event)| event));
+                    committed.push_back(WalEvent::Commit { lsn, end_lsn });
+                }
+                WalRecord::StreamAbort { xid, subxid } => {
+                    if xid == subxid {
+                        streams.remove(&xid);
+                    } else if let Some(stream) = streams.get_mut(&xid) {
+                        stream.abort_subtransaction(subxid);
+                    }
+                }
+                record => {
+                    if let Some(event) = Self::record_event(record, lsn, relations, custom_types)? {
+                        return Ok(event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts a WAL record into the event it represents, if any, using (and updating) the
+    /// relation mappings in `relations`
+    fn record_event(
+        record: WalRecord,
+        lsn: Lsn,
+        relations: &mut HashMap<i32, Relation>,
+        custom_types: &mut HashSet<u32>,
+    ) -> Result<Option<WalEvent>, WalError> {
+        match record {
+            WalRecord::Begin { final_lsn, .. } => return Ok(Some(WalEvent::Begin { final_lsn })),
+            WalRecord::Commit { lsn, end_lsn, .. } => {
+                return Ok(Some(WalEvent::Commit { lsn, end_lsn }))
+            }
+            WalRecord::Relation(mapping) => {
+                // Store the relation in the hash map for future use
+                let id = mapping.id;
+                let schema = String::from_utf8(mapping.schema.to_vec()).map_err(|v| {
+                    ReadySetError::ReplicationFailed(format!("Non UTF8 name {:?}", v.as_bytes()))
+                })?;
+                let table = String::from_utf8(mapping.name.to_vec()).map_err(|v| {
+                    ReadySetError::ReplicationFailed(format!("Non UTF8 name {:?}", v.as_bytes()))
+                })?;
+                relations.insert(
+                    id,
+                    Relation {
                         schema,
                         table,
                         mapping,
-                    } = match relations.get(&relation_id) {
-                        None => continue,
-                        Some(relation) => relation,
+                    },
+                );
+            }
+            WalRecord::Insert {
+                relation_id,
+                new_tuple,
+            } => {
+                if let Some(Relation {
+                    schema,
+                    table,
+                    mapping,
+                }) = relations.get(&relation_id)
+                {
+                    return Ok(Some(
+                        WalEvent::Insert {
+                            schema: schema.clone(),
+                            table: table.clone(),
+                            tuple: new_tuple
+                                .into_noria_vec(mapping, custom_types, false)?
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                // Insert records should never have "unchanged" fields... unchanged from what?
+                                .ok_or_else(|| WalError::TableError {
+                                    kind: TableErrorKind::UnexpectedUnchangedEntry {
+                                        reason: "WalRecord::Insert::new_tuple should never contain TupleEntry::Unchanged",
+                                    },
+                                    schema: schema.clone(),
+                                    table: table.clone(),
+                                })?,
+                            lsn,
+                        },
+                    ));
+                } else {
+                    debug!(
+                        relation_id,
+                        "Ignoring WAL insert event for unknown relation"
+                    );
+                }
+            }
+            WalRecord::Update {
+                relation_id,
+                key_tuple,
+                old_tuple,
+                new_tuple,
+            } => {
+                let Relation {
+                    schema,
+                    table,
+                    mapping,
+                } = match relations.get(&relation_id) {
+                    None => return Ok(None),
+                    Some(relation) => relation,
+                };
+
+                if schema == DDL_REPLICATION_LOG_SCHEMA && table == DDL_REPLICATION_LOG_TABLE {
+                    // This is a special update message for the DDL replication table, convert
+                    // that to the same format as if it were a message record
+                    let ddl_data = match new_tuple.cols.first() {
+                        Some(TupleEntry::Text(data)) => data,
+                        _ => {
+                            error!("Error fetching DDL event from update record");
+                            return Ok(None);
+                        }
                     };
 
-                    if schema == DDL_REPLICATION_LOG_SCHEMA && table == DDL_REPLICATION_LOG_TABLE {
-                        // This is a special update message for the DDL replication table, convert
-                        // that to the same format as if it were a message record
-                        let ddl_data = match new_tuple.cols.first() {
-                            Some(TupleEntry::Text(data)) => data,
-                            _ => {
-                                error!("Error fetching DDL event from update record");
-                                continue;
-                            }
-                        };
-
-                        let ddl_event: Box<DdlEvent> = match serde_json::from_slice(ddl_data) {
-                            Err(err) => {
-                                error!(
-                                    ?err,
-                                    "Error parsing DDL event, table or view will not be used"
-                                );
-                                continue;
-                            }
-                            Ok(ddl_event) => ddl_event,
-                        };
+                    let ddl_event: Box<DdlEvent> = match serde_json::from_slice(ddl_data) {
+                        Err(err) => {
+                            error!(
+                                ?err,
+                                "Error parsing DDL event, table or view will not be used"
+                            );
+                            return Ok(None);
+                        }
+                        Ok(ddl_event) => ddl_event,
+                    };
 
-                        return Ok(WalEvent::DdlEvent { ddl_event, lsn });
+                    return Ok(Some(WalEvent::DdlEvent { ddl_event, lsn }));
+                }
+                // We only ever going to have a `key_tuple` *OR* `old_tuple` *OR* neither
+                if let Some(old_tuple) = old_tuple {
+                    // This happens when there is no key defined for the table and `REPLICA
+                    // IDENTITY` is set to `FULL`
+
+                    // Replace TupleEntry::Unchanged in new_tuple by the corresponding value in
+                    // old_tuple
+                    let mut new_tuple = new_tuple;
+                    while let Some(pos) = new_tuple
+                        .cols
+                        .iter()
+                        .position(|x| *x == TupleEntry::Unchanged)
+                    {
+                        new_tuple.cols[pos] = old_tuple.cols[pos].clone();
                     }
-                    // We only ever going to have a `key_tuple` *OR* `old_tuple` *OR* neither
+
+                    return Ok(Some(
+                        WalEvent::UpdateRow {
+                            schema: schema.clone(),
+                            table: table.clone(),
+                            old_tuple: old_tuple
+                                .into_noria_vec(mapping, custom_types, false)?
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                // The old row must always be complete, or we won't be able to delete (a copy of) it
+                                .ok_or_else(|| WalError::TableError {
+                                    kind: TableErrorKind::UnexpectedUnchangedEntry {
+                                        reason: "WalRecord::Update::old_tuple should never contain TupleEntry::Unchanged"
+                                    },
+                                    schema: schema.clone(),
+                                    table: table.clone(),
+                                })?,
+                            new_tuple: new_tuple
+                                .into_noria_vec(mapping, custom_types, false)?
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                // We should have filled in any "unchanged" entries in the new row above
+                                .ok_or_else(|| WalError::TableError {
+                                    kind: TableErrorKind::UnexpectedUnchangedEntry {
+                                        reason: "All instances of TupleEntry::Unchanged in WalRecord::Update::new_tuple should have been replaced"
+                                    },
+                                    schema: schema.clone(),
+                                    table: table.clone(),
+                                })?,
+                            lsn,
+                        },
+                    ));
+                } else if let Some(key_tuple) = key_tuple {
+                    // This happens when the update is modifying the key column
+                    return Ok(Some(
+                        WalEvent::UpdateByKey {
+                            schema: schema.clone(),
+                            table: table.clone(),
+                            key: key_tuple
+                                .into_noria_vec(mapping, custom_types, true)?
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                // The key must always be complete, or we won't be able to look up the row
+                                .ok_or_else(|| WalError::TableError {
+                                    kind: TableErrorKind::UnexpectedUnchangedEntry {
+                                        reason: "WalRecord::Update::key_tuple should never contain TupleEntry::Unchanged",
+                                    },
+                                    schema: schema.clone(),
+                                    table: table.clone(),
+                                })?,
+                            set: new_tuple
+                                .into_noria_vec(mapping, custom_types, false)?
+                                .into_iter()
+                                .map(Into::into)
+                                .collect(),
+                            lsn,
+                        },
+                    ));
+                } else {
+                    // This happens when the update is not modifying the key column and
+                    // therefore it is possible to extract the
+                    // key value from the tuple as is
+                    return Ok(Some(
+                        WalEvent::UpdateByKey {
+                            schema: schema.clone(),
+                            table: table.clone(),
+                            key: new_tuple
+                                .clone()
+                                .into_noria_vec(mapping, custom_types, true)?
+                                .into_iter()
+                                .collect::<Option<Vec<_>>>()
+                                // The key must always be complete, or we won't be able to look up the row
+                                .ok_or_else(|| WalError::TableError {
+                                    kind: TableErrorKind::UnexpectedUnchangedEntry {
+                                        reason: "When key_tuple is not present, the key columns in WalRecord::Update::new_tuple should never contain TupleEntry::Unchanged",
+                                    },
+                                    schema: schema.clone(),
+                                    table: table.clone(),
+                                })?,
+                            set: new_tuple
+                                .into_noria_vec(mapping, custom_types, false)?
+                                .into_iter()
+                                .map(Into::into)
+                                .collect(),
+                            lsn,
+                        },
+                    ));
+                }
+            }
+            WalRecord::Delete {
+                relation_id,
+                key_tuple,
+                old_tuple,
+            } => {
+                if let Some(Relation {
+                    schema,
+                    table,
+                    mapping,
+                }) = relations.get(&relation_id)
+                {
+                    // We only ever going to have a `key_tuple` *OR* `old_tuple`
                     if let Some(old_tuple) = old_tuple {
                         // This happens when there is no key defined for the table and `REPLICA
                         // IDENTITY` is set to `FULL`
-
-                        // Replace TupleEntry::Unchanged in new_tuple by the corresponding value in
-                        // old_tuple
-                        let mut new_tuple = new_tuple;
-                        while let Some(pos) = new_tuple
-                            .cols
-                            .iter()
-                            .position(|x| *x == TupleEntry::Unchanged)
-                        {
-                            new_tuple.cols[pos] = old_tuple.cols[pos].clone();
-                        }
-
-                        return Ok(
-                            WalEvent::UpdateRow {
+                        return Ok(Some(
+                            WalEvent::DeleteRow {
                                 schema: schema.clone(),
                                 table: table.clone(),
-                                old_tuple: old_tuple
+                                tuple: old_tuple
                                     .into_noria_vec(mapping, custom_types, false)?
                                     .into_iter()
                                     .collect::<Option<Vec<_>>>()
                                     // The old row must always be complete, or we won't be able to delete (a copy of) it
                                     .ok_or_else(|| WalError::TableError {
                                         kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                            reason: "WalRecord::Update::old_tuple should never contain TupleEntry::Unchanged"
-                                        },
-                                        schema: schema.clone(),
-                                        table: table.clone(),
-                                    })?,
-                                new_tuple: new_tuple
-                                    .into_noria_vec(mapping, custom_types, false)?
-                                    .into_iter()
-                                    .collect::<Option<Vec<_>>>()
-                                    // We should have filled in any "unchanged" entries in the new row above
-                                    .ok_or_else(|| WalError::TableError {
-                                        kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                            reason: "All instances of TupleEntry::Unchanged in WalRecord::Update::new_tuple should have been replaced"
+                                            reason: "WalRecord::Delete::old_tuple should never contain TupleEntry::Unchanged",
                                         },
                                         schema: schema.clone(),
                                         table: table.clone(),
                                     })?,
                                 lsn,
                             },
-                        );
+                        ));
                     } else if let Some(key_tuple) = key_tuple {
-                        // This happens when the update is modifying the key column
-                        return Ok(
-                            WalEvent::UpdateByKey {
+                        return Ok(Some(
+                            WalEvent::DeleteByKey {
                                 schema: schema.clone(),
                                 table: table.clone(),
                                 key: key_tuple
                                     .into_noria_vec(mapping, custom_types, true)?
                                     .into_iter()
                                     .collect::<Option<Vec<_>>>()
-                                    // The key must always be complete, or we won't be able to look up the row
+                                    // The key must always be complete, or we won't be able to look up the row to delete it
                                     .ok_or_else(|| WalError::TableError {
                                         kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                            reason: "WalRecord::Update::key_tuple should never contain TupleEntry::Unchanged",
+                                            reason: "WalRecord::Delete::key_tuple should never contain TupleEntry::Unchanged",
                                         },
                                         schema: schema.clone(),
                                         table: table.clone(),
                                     })?,
-                                set: new_tuple
-                                    .into_noria_vec(mapping, custom_types, false)?
-                                    .into_iter()
-                                    .map(Into::into)
-                                    .collect(),
-                                lsn,
+                                lsn
                             },
-                        );
-                    } else {
-                        // This happens when the update is not modifying the key column and
-                        // therefore it is possible to extract the
-                        // key value from the tuple as is
-                        return Ok(
-                            WalEvent::UpdateByKey {
-                                schema: schema.clone(),
-                                table: table.clone(),
-                                key: new_tuple
-                                    .clone()
-                                    .into_noria_vec(mapping, custom_types, true)?
-                                    .into_iter()
-                                    .collect::<Option<Vec<_>>>()
-                                    // The key must always be complete, or we won't be able to look up the row
-                                    .ok_or_else(|| WalError::TableError {
-                                        kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                            reason: "When key_tuple is not present, the key columns in WalRecord::Update::new_tuple should never contain TupleEntry::Unchanged",
-                                        },
-                                        schema: schema.clone(),
-                                        table: table.clone(),
-                                    })?,
-                                set: new_tuple
-                                    .into_noria_vec(mapping, custom_types, false)?
-                                    .into_iter()
-                                    .map(Into::into)
-                                    .collect(),
-                                lsn,
-                            },
-                        );
+                        ));
                     }
                 }
-                WalRecord::Delete {
-                    relation_id,
-                    key_tuple,
-                    old_tuple,
-                } => {
-                    if let Some(Relation {
-                        schema,
-                        table,
-                        mapping,
-                    }) = relations.get(&relation_id)
-                    {
-                        // We only ever going to have a `key_tuple` *OR* `old_tuple`
-                        if let Some(old_tuple) = old_tuple {
-                            // This happens when there is no key defined for the table and `REPLICA
-                            // IDENTITY` is set to `FULL`
-                            return Ok(
-                                WalEvent::DeleteRow {
-                                    schema: schema.clone(),
-                                    table: table.clone(),
-                                    tuple: old_tuple
-                                        .into_noria_vec(mapping, custom_types, false)?
-                                        .into_iter()
-                                        .collect::<Option<Vec<_>>>()
-                                        // The old row must always be complete, or we won't be able to delete (a copy of) it
-                                        .ok_or_else(|| WalError::TableError {
-                                            kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                                reason: "WalRecord::Delete::old_tuple should never contain TupleEntry::Unchanged",
-                                            },
-                                            schema: schema.clone(),
-                                            table: table.clone(),
-                                        })?,
-                                    lsn,
-                                },
-                            );
-                        } else if let Some(key_tuple) = key_tuple {
-                            return Ok(
-                                WalEvent::DeleteByKey {
-                                    schema: schema.clone(),
-                                    table: table.clone(),
-                                    key: key_tuple
-                                        .into_noria_vec(mapping, custom_types, true)?
-                                        .into_iter()
-                                        .collect::<Option<Vec<_>>>()
-                                        // The key must always be complete, or we won't be able to look up the row to delete it
-                                        .ok_or_else(|| WalError::TableError {
-                                            kind: TableErrorKind::UnexpectedUnchangedEntry {
-                                                reason: "WalRecord::Delete::key_tuple should never contain TupleEntry::Unchanged",
-                                            },
-                                            schema: schema.clone(),
-                                            table: table.clone(),
-                                        })?,
-                                    lsn
-                                },
-                            );
-                        }
+            }
+            WalRecord::Message {
+                prefix,
+                payload,
+                lsn,
+                ..
+            } if prefix == b"readyset".as_slice() => {
+                let ddl_event = match serde_json::from_slice(&payload) {
+                    Err(err) => {
+                        error!(
+                            ?err,
+                            "Error parsing DDL event, table or view will not be used"
+                        );
+                        return Ok(None);
                     }
-                }
-                WalRecord::Message {
-                    prefix,
-                    payload,
-                    lsn,
-                    ..
-                } if prefix == b"readyset".as_slice() => {
-                    let ddl_event = match serde_json::from_slice(&payload) {
-                        Err(err) => {
-                            error!(
-                                ?err,
-                                "Error parsing DDL event, table or view will not be used"
-                            );
-                            continue;
-                        }
-                        Ok(ddl_event) => ddl_event,
-                    };
-                    return Ok(WalEvent::DdlEvent { ddl_event, lsn });
-                }
-                WalRecord::Message { prefix, .. } => {
-                    debug!("Message with ignored prefix {prefix:?}")
-                }
-                WalRecord::Type { id, .. } => {
-                    custom_types.insert(id as _);
-                }
-                WalRecord::Truncate {
-                    n_relations,
-                    relation_ids,
-                    ..
-                } => {
-                    let mut tables = Vec::with_capacity(n_relations as _);
-                    for relation_id in relation_ids {
-                        if let Some(Relation { schema, table, .. }) = relations.get(&relation_id) {
-                            tables.push((schema.clone(), table.clone()))
-                        } else {
-                            debug!(%relation_id, "Ignoring WAL event for unknown relation");
-                        }
+                    Ok(ddl_event) => ddl_event,
+                };
+                return Ok(Some(WalEvent::DdlEvent { ddl_event, lsn }));
+            }
+            WalRecord::Message { prefix, .. } => {
+                debug!("Message with ignored prefix {prefix:?}")
+            }
+            WalRecord::Type { id, .. } => {
+                custom_types.insert(id as _);
+            }
+            WalRecord::Truncate {
+                n_relations,
+                relation_ids,
+                ..
+            } => {
+                let mut tables = Vec::with_capacity(n_relations as _);
+                for relation_id in relation_ids {
+                    if let Some(Relation { schema, table, .. }) = relations.get(&relation_id) {
+                        tables.push((schema.clone(), table.clone()))
+                    } else {
+                        debug!(%relation_id, "Ignoring WAL event for unknown relation");
                     }
-
-                    return Ok(WalEvent::Truncate { tables, lsn });
-                }
-                WalRecord::Origin { .. } => {
-                    // Just tells where the transaction originated
-                }
-                WalRecord::Unknown(payload) => {
-                    error!(?payload, "Unknown message");
                 }
+
+                return Ok(Some(WalEvent::Truncate { tables, lsn }));
+            }
+            WalRecord::Origin { .. } => {
+                // Just tells where the transaction originated
+            }
+            WalRecord::Unknown(payload) => {
+                error!(?payload, "Unknown message");
+            }
+            WalRecord::StreamStart { .. }
+            | WalRecord::StreamStop
+            | WalRecord::StreamCommit { .. }
+            | WalRecord::StreamAbort { .. }
+            | WalRecord::Streamed { .. } => {
+                // These are handled by `next_event_inner`, since they affect which transaction the
+                // records that follow them belong to
+                return Err(WalError::CorruptStream);
             }
         }
+
+        Ok(None)
     }
 }
 
@@ -793,3 +898,56 @@ impl wal::TupleData {
         Ok(ret)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(id: i32) -> WalEvent {
+        WalEvent::Insert {
+            schema: "public".into(),
+            table: "t".into(),
+            tuple: vec![id.into()],
+            lsn: Lsn::default() + id as i64,
+        }
+    }
+
+    fn inserted_ids(stream: &StreamedTransaction) -> Vec<DfValue> {
+        stream
+            .events
+            .iter()
+            .map(|(_, event)| match event {
+                WalEvent::Insert { tuple, .. } => tuple[0].clone(),
+                event => panic!("Unexpected event: {event:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn abort_subtransaction_between_parent_events() {
+        let mut stream = StreamedTransaction::default();
+        stream.events.push((100, insert(1)));
+        stream.events.push((101, insert(2)));
+        stream.events.push((102, insert(3)));
+
+        // Rolling back to a savepoint aborts the subtransaction along with the ones nested in it
+        stream.abort_subtransaction(101);
+        assert_eq!(inserted_ids(&stream), vec![DfValue::from(1)]);
+
+        // The parent transaction's changes after the abort are kept
+        stream.events.push((100, insert(4)));
+        stream.events.push((103, insert(5)));
+        stream.abort_subtransaction(103);
+        assert_eq!(
+            inserted_ids(&stream),
+            vec![DfValue::from(1), DfValue::from(4)]
+        );
+
+        // Aborting a subtransaction that made no changes does nothing
+        stream.abort_subtransaction(104);
+        assert_eq!(
+            inserted_ids(&stream),
+            vec![DfValue::from(1), DfValue::from(4)]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Support for snapshotting large tables in chunks.
+//!
+//! Tables with a single-column integer primary key that are estimated to have at least
+//! [`MIN_ROWS_TO_CHUNK`] rows are split into ranges of that key, which are copied concurrently
+//! (all within the same consistent snapshot of the upstream database). If copying a chunk fails,
+//! only that chunk has to be copied again rather than the whole table.
+//!
+//! Completed chunks are also recorded with the controller as a [`SnapshotProgress`] along with the
+//! replication offset of the snapshot they were copied from, so that if the replicator restarts
+//! part-way through snapshotting a table it only copies the chunks that hadn't finished yet. The
+//! consistent snapshot the earlier chunks were read from doesn't outlive the snapshot process, so
+//! those remaining chunks are copied from a later snapshot. The table's replication offset is then
+//! set to the earliest offset of any of its chunks, and until replication has caught up with the
+//! latest one, changes to rows in each chunk are skipped if that chunk was copied after them (see
+//! [`included_in_snapshot`]).
+
+use nom_sql::Relation;
+use readyset_client::{Modification, ReadySetHandle, Table, TableOperation};
+use readyset_data::DfValue;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use replication_offset::{ReplicationOffset, SnapshotChunk, SnapshotProgress};
+use tracing::{error, warn};
+
+/// Tables estimated to have fewer rows than this are snapshotted in a single pass
+pub(crate) const MIN_ROWS_TO_CHUNK: u64 = 1_000_000;
+
+/// The number of rows we aim to copy in each chunk
+pub(crate) const ROWS_PER_CHUNK: u64 = 250_000;
+
+/// The maximum number of chunks of a single table to copy at the same time
+pub(crate) const MAX_CONCURRENT_CHUNKS: usize = 4;
+
+/// The number of times we try to copy a single chunk before giving up on the table
+pub(crate) const MAX_CHUNK_ATTEMPTS: usize = 3;
+
+/// An inclusive range of values of a table's primary key, copied as a single chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyRange {
+    pub(crate) start: i64,
+    pub(crate) end: i64,
+}
+
+/// Split the primary key values between `min` and `max` (inclusive) into ranges which, assuming
+/// the table's `rows` rows are evenly distributed between them, contain about [`ROWS_PER_CHUNK`]
+/// rows each.
+pub(crate) fn key_ranges(min: i64, max: i64, rows: u64) -> Vec<KeyRange> {
+    if min > max {
+        return vec![];
+    }
+
+    let span = max as i128 - min as i128 + 1;
+    let chunks = (rows.div_ceil(ROWS_PER_CHUNK) as i128).clamp(1, span);
+    // Round up, so that we never end up with more than `chunks` ranges
+    let step = (span + chunks - 1) / chunks;
+
+    let mut ranges = Vec::with_capacity(chunks as usize);
+    let mut start = min as i128;
+    while start <= max as i128 {
+        let end = (start + step - 1).min(max as i128);
+        ranges.push(KeyRange {
+            start: start as i64,
+            end: end as i64,
+        });
+        start = end + 1;
+    }
+    ranges
+}
+
+/// Remove the parts of each of `ranges` which are covered by chunks that have already been copied
+pub(crate) fn remaining_ranges(ranges: Vec<KeyRange>, copied: &[SnapshotChunk]) -> Vec<KeyRange> {
+    let mut copied = copied
+        .iter()
+        .map(|chunk| (chunk.start, chunk.end))
+        .collect::<Vec<_>>();
+    copied.sort_unstable();
+
+    let mut res = vec![];
+    for range in ranges {
+        let mut start = Some(range.start);
+        for &(copied_start, copied_end) in &copied {
+            let Some(s) = start else { break };
+            if copied_end < s || copied_start > range.end {
+                continue;
+            }
+            if copied_start > s {
+                res.push(KeyRange {
+                    start: s,
+                    end: copied_start - 1,
+                });
+            }
+            start = copied_end.checked_add(1);
+        }
+        if let Some(s) = start.filter(|s| *s <= range.end) {
+            res.push(KeyRange {
+                start: s,
+                end: range.end,
+            });
+        }
+    }
+    res
+}
+
+/// Keeps track of the chunks of a table that have been copied, recording each one with the
+/// controller as soon as it finishes.
+pub(crate) struct ChunkProgress {
+    noria: ReadySetHandle,
+    table: Relation,
+    progress: SnapshotProgress,
+}
+
+impl ChunkProgress {
+    /// Start keeping track of the chunks copied of `table`, continuing from `progress` if the table
+    /// was already part-way through being snapshotted
+    pub(crate) fn new(noria: ReadySetHandle, table: Relation, progress: SnapshotProgress) -> Self {
+        Self {
+            noria,
+            table,
+            progress,
+        }
+    }
+
+    /// The chunks copied so far
+    pub(crate) fn chunks(&self) -> &[SnapshotChunk] {
+        &self.progress.chunks
+    }
+
+    /// Record that the given range was copied from the snapshot at `offset`
+    pub(crate) async fn record(
+        &mut self,
+        range: KeyRange,
+        offset: &ReplicationOffset,
+    ) -> ReadySetResult<()> {
+        self.progress.chunks.push(SnapshotChunk {
+            start: range.start,
+            end: range.end,
+            offset: offset.clone(),
+        });
+        self.noria
+            .set_snapshot_progress(&self.table, Some(&self.progress))
+            .await
+    }
+
+    /// Called once all chunks have been copied. Returns the replication offset to set for the
+    /// table, which is the earliest offset of any of the chunks (or `offset`, if there weren't
+    /// any).
+    ///
+    /// If every chunk was copied from the same snapshot, there's nothing left to skip during
+    /// replication, so the recorded chunks are cleared. Otherwise they're left for the replicator
+    /// to use until it catches up (see [`included_in_snapshot`]).
+    pub(crate) async fn finish(
+        mut self,
+        offset: &ReplicationOffset,
+    ) -> ReadySetResult<ReplicationOffset> {
+        let min_offset = self.progress.min_offset()?.unwrap_or(offset).clone();
+        if self.progress.max_offset()?.unwrap_or(offset) == &min_offset {
+            self.noria.set_snapshot_progress(&self.table, None).await?;
+        }
+        Ok(min_offset)
+    }
+}
+
+/// Returns whether the effects of `op`, replicated at `pos` to a table that was snapshotted in the
+/// chunks recorded in `progress`, were already included in the chunk its row was copied in (and so
+/// should be skipped).
+///
+/// Returns `None` if that can't be determined, because `op` affects rows in more than one chunk
+/// (such as truncating the table, or updating a row's key); in that case the table has to be
+/// snapshotted again.
+pub(crate) fn included_in_snapshot(
+    progress: &SnapshotProgress,
+    op: &TableOperation,
+    pos: &ReplicationOffset,
+) -> ReadySetResult<Option<bool>> {
+    let key = match op {
+        TableOperation::Insert(row)
+        | TableOperation::DeleteRow { row }
+        | TableOperation::InsertOrUpdate { row, .. } => row.get(progress.key),
+        TableOperation::DeleteByKey { key } => key.first(),
+        TableOperation::Update { key, update } => {
+            if !matches!(update.get(progress.key), None | Some(Modification::None)) {
+                return Ok(None);
+            }
+            key.first()
+        }
+        TableOperation::Truncate => return Ok(None),
+        TableOperation::SetReplicationOffset(_) | TableOperation::SetSnapshotMode(_) => {
+            return Ok(Some(false))
+        }
+    };
+    let Some(key) = key.and_then(|key| i64::try_from(key).ok()) else {
+        return Ok(None);
+    };
+
+    Ok(Some(match progress.offset_for_key(key) {
+        Some(offset) => pos.try_partial_cmp(offset)?.is_le(),
+        // Any row outside of every chunk didn't exist when the last chunk was copied
+        None => false,
+    }))
+}
+
+/// Delete the rows with the given primary `keys` from `table`, to roll back a chunk that failed
+/// part-way through being copied.
+///
+/// Returns the error that caused the chunk to fail if the rollback succeeded, so that the chunk can
+/// be retried, or fails if the rows couldn't be deleted, in which case the chunk can't be retried
+/// without duplicating them.
+pub(crate) async fn roll_back_chunk(
+    table: &mut Table,
+    range: KeyRange,
+    keys: Vec<DfValue>,
+    error: ReadySetError,
+) -> ReadySetResult<ReadySetResult<u64>> {
+    warn!(%error, ?range, rows = keys.len(), "Failed to copy chunk, rolling it back");
+    if !keys.is_empty() {
+        table
+            .perform_all(
+                keys.into_iter()
+                    .map(|key| TableOperation::DeleteByKey { key: vec![key] }),
+            )
+            .await
+            .map_err(|rollback_error| {
+                error!(%rollback_error, ?range, "Failed to roll back chunk");
+                rollback_error
+            })?;
+    }
+    Ok(Err(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use replication_offset::postgres::PostgresPosition;
+
+    use super::*;
+
+    #[test]
+    fn key_ranges_cover_keys() {
+        let ranges = key_ranges(1, 1_000_000, 1_000_000);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges.first().unwrap().start, 1);
+        assert_eq!(ranges.last().unwrap().end, 1_000_000);
+        for (r1, r2) in ranges.iter().zip(ranges.iter().skip(1)) {
+            assert_eq!(r1.end + 1, r2.start);
+        }
+    }
+
+    #[test]
+    fn key_ranges_small_span() {
+        assert_eq!(
+            key_ranges(5, 6, 10_000_000),
+            vec![KeyRange { start: 5, end: 5 }, KeyRange { start: 6, end: 6 }]
+        );
+    }
+
+    #[test]
+    fn key_ranges_extreme_values() {
+        let ranges = key_ranges(i64::MIN, i64::MAX, 1_000_000);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges.first().unwrap().start, i64::MIN);
+        assert_eq!(ranges.last().unwrap().end, i64::MAX);
+    }
+
+    #[test]
+    fn key_ranges_empty() {
+        assert_eq!(key_ranges(1, 0, 0), vec![]);
+    }
+
+    fn offset(position: i64) -> ReplicationOffset {
+        PostgresPosition::commit_end(position.into()).into()
+    }
+
+    fn chunk(start: i64, end: i64, position: i64) -> SnapshotChunk {
+        SnapshotChunk {
+            start,
+            end,
+            offset: offset(position),
+        }
+    }
+
+    #[test]
+    fn remaining_ranges_skip_copied_chunks() {
+        let ranges = vec![
+            KeyRange { start: 1, end: 10 },
+            KeyRange { start: 11, end: 30 },
+        ];
+        assert_eq!(
+            remaining_ranges(
+                ranges,
+                &[chunk(11, 15, 1), chunk(1, 10, 1), chunk(20, 25, 1)]
+            ),
+            vec![
+                KeyRange { start: 16, end: 19 },
+                KeyRange { start: 26, end: 30 }
+            ]
+        );
+    }
+
+    #[test]
+    fn remaining_ranges_beyond_copied_chunks() {
+        // The table grew between the snapshot the copied chunks were taken from and this one
+        assert_eq!(
+            remaining_ranges(
+                vec![KeyRange {
+                    start: -5,
+                    end: i64::MAX
+                }],
+                &[chunk(1, 10, 1), chunk(100, i64::MAX, 1)]
+            ),
+            vec![
+                KeyRange { start: -5, end: 0 },
+                KeyRange { start: 11, end: 99 }
+            ]
+        );
+    }
+
+    #[test]
+    fn included_in_snapshot_by_chunk() {
+        let progress = SnapshotProgress {
+            key: 0,
+            chunks: vec![chunk(1, 10, 1), chunk(11, 20, 3)],
+        };
+        let insert = |key: i32| TableOperation::Insert(vec![key.into(), "a".into()]);
+
+        // Changes to rows in the earlier chunk have to be applied...
+        assert_eq!(
+            included_in_snapshot(&progress, &insert(5), &offset(2)).unwrap(),
+            Some(false)
+        );
+        // ...but the later chunk already includes them
+        assert_eq!(
+            included_in_snapshot(&progress, &insert(15), &offset(2)).unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            included_in_snapshot(&progress, &insert(15), &offset(4)).unwrap(),
+            Some(false)
+        );
+        assert_eq!(
+            included_in_snapshot(
+                &progress,
+                &TableOperation::DeleteByKey {
+                    key: vec![15.into()]
+                },
+                &offset(2)
+            )
+            .unwrap(),
+            Some(true)
+        );
+        // Rows outside of every chunk didn't exist when they were copied
+        assert_eq!(
+            included_in_snapshot(&progress, &insert(25), &offset(2)).unwrap(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn included_in_snapshot_unknown() {
+        let progress = SnapshotProgress {
+            key: 0,
+            chunks: vec![chunk(1, 10, 1), chunk(11, 20, 3)],
+        };
+        assert_eq!(
+            included_in_snapshot(&progress, &TableOperation::Truncate, &offset(2)).unwrap(),
+            None
+        );
+        assert_eq!(
+            included_in_snapshot(
+                &progress,
+                &TableOperation::Update {
+                    key: vec![5.into()],
+                    update: vec![Modification::Set(15.into()), Modification::None]
+                },
+                &offset(2)
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            included_in_snapshot(
+                &progress,
+                &TableOperation::Update {
+                    key: vec![15.into()],
+                    update: vec![Modification::None, Modification::Set("b".into())]
+                },
+                &offset(2)
+            )
+            .unwrap(),
+            Some(true)
+        );
+    }
+}
@@ -20,6 +20,7 @@ use readyset_server::Builder;
 use readyset_telemetry_reporter::{TelemetryEvent, TelemetryInitializer, TelemetrySender};
 use readyset_util::eventually;
 use readyset_util::shutdown::ShutdownSender;
+use replication_offset::ReplicationOffset;
 use replicators::db_util::error_is_slot_not_found;
 use replicators::{ControllerMessage, NoriaAdapter, ReplicatorMessage};
 use test_utils::slow;
@@ -448,6 +449,9 @@ impl TestHandle {
                                 .unwrap(),
                         ),
                         always: false,
+                        expiration: None,
+                        memory_limit: None,
+                        priority: None,
                     }),
                 ],
                 self.dialect,
@@ -595,6 +599,23 @@ fn pgsql13_url() -> String {
     )
 }
 
+fn pgsql16_url() -> String {
+    format!(
+        "postgresql://postgres:noria@{}:{}/noria",
+        env::var("PGHOST16").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("PGPORT16").unwrap_or_else(|_| "5434".into()),
+    )
+}
+
+/// The URL for a physical standby of the server at [`pgsql16_url`]
+fn pgsql16_standby_url() -> String {
+    format!(
+        "postgresql://postgres:noria@{}:{}/noria",
+        env::var("PGSTANDBYHOST16").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("PGSTANDBYPORT16").unwrap_or_else(|_| "5435".into()),
+    )
+}
+
 fn mysql_url() -> String {
     format!(
         "mysql://root:noria@{}:{}/public",
@@ -603,6 +624,15 @@ fn mysql_url() -> String {
     )
 }
 
+/// The URL for a MariaDB server, which has to have GTIDs and the binlog enabled
+fn mariadb_url() -> String {
+    format!(
+        "mysql://root:noria@{}:{}/public",
+        env::var("MARIADB_HOST").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("MARIADB_TCP_PORT").unwrap_or_else(|_| "3307".into()),
+    )
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 #[slow]
@@ -634,6 +664,105 @@ async fn mysql_replication() {
     replication_test_inner(&mysql_url()).await
 }
 
+/// Replicates from MariaDB with binlog compression enabled, then checks that replication resumes
+/// from the MariaDB GTID position it stopped at after a restart
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+#[ignore = "Requires a MariaDB server (see `mariadb_url`), which CI doesn't run"]
+async fn mariadb_replication_gtid_compressed() {
+    readyset_tracing::init_test_logging();
+    let url = mariadb_url();
+    let mut client = DbConnection::connect(&url).await.unwrap();
+    // Only events at least as long as `log_bin_compress_min_len` are compressed
+    client
+        .query(
+            "SET GLOBAL log_bin_compress = ON;
+            SET GLOBAL log_bin_compress_min_len = 10;
+            DROP TABLE IF EXISTS mariadb_t CASCADE;
+            DROP VIEW IF EXISTS mariadb_view;
+            CREATE TABLE mariadb_t (id int PRIMARY KEY, val text);
+            CREATE VIEW mariadb_view AS SELECT * FROM mariadb_t;",
+        )
+        .await
+        .unwrap();
+
+    let val = "I am a teapot".repeat(20);
+    let insert = |range: std::ops::Range<i32>| {
+        format!(
+            "INSERT INTO mariadb_t VALUES {}",
+            range.map(|i| format!("({i}, '{val}')")).join(", ")
+        )
+    };
+    let results = |n: i32| {
+        (0..n)
+            .map(|i| vec![DfValue::from(i), DfValue::from(val.as_str())])
+            .collect::<Vec<_>>()
+    };
+
+    client.query(&insert(0..10)).await.unwrap();
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(url.clone(), None).await.unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+    let rs = results(10);
+    let rs: Vec<&[DfValue]> = rs.iter().map(|r| r.as_slice()).collect();
+    ctx.check_results("mariadb_view", "Snapshot", rs.as_slice())
+        .await
+        .unwrap();
+
+    // These are replicated from compressed binlog events
+    client.query(&insert(10..20)).await.unwrap();
+    let rs = results(20);
+    let rs: Vec<&[DfValue]> = rs.iter().map(|r| r.as_slice()).collect();
+    ctx.check_results("mariadb_view", "Compressed events", rs.as_slice())
+        .await
+        .unwrap();
+
+    let offsets = ctx.noria.replication_offsets().await.unwrap();
+    assert!(
+        matches!(offsets.schema, Some(ReplicationOffset::MariaDbGtid(_))),
+        "offsets = {offsets:?}"
+    );
+
+    // Stop the replication task, and write some more while it's stopped
+    ctx.stop_repl().await;
+    client.query(&insert(20..30)).await.unwrap();
+    client
+        .query("DELETE FROM mariadb_t WHERE id >= 25")
+        .await
+        .unwrap();
+
+    // Once restarted, the replicator should pick up from the GTID position it stopped at, without
+    // replaying or missing anything
+    ctx.start_repl(None, TelemetrySender::new_no_op(), false)
+        .await
+        .unwrap();
+    let rs = results(25);
+    let rs: Vec<&[DfValue]> = rs.iter().map(|r| r.as_slice()).collect();
+    ctx.check_results("mariadb_view", "Resume from GTID", rs.as_slice())
+        .await
+        .unwrap();
+
+    client
+        .query(
+            "DROP TABLE IF EXISTS mariadb_t CASCADE;
+            DROP VIEW IF EXISTS mariadb_view;
+            SET GLOBAL log_bin_compress = OFF;",
+        )
+        .await
+        .unwrap();
+
+    client.stop().await;
+    ctx.stop().await;
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 #[slow]
@@ -1735,6 +1864,9 @@ async fn postgresql_ddl_replicate_drop_view_internal(url: &str) {
             .unwrap(),
         ),
         always: false,
+        expiration: None,
+        memory_limit: None,
+        priority: None,
     });
     ctx.noria
         .extend_recipe(ChangeList::from_change(
@@ -1816,6 +1948,9 @@ async fn postgresql_ddl_replicate_create_view_internal(url: &str) {
                     .unwrap()
                 ),
                 always: true,
+                expiration: None,
+                memory_limit: None,
+                priority: None,
             }),
             Dialect::DEFAULT_POSTGRESQL
         ))
@@ -2755,7 +2890,7 @@ async fn pgsql_delete_from_table_without_pk() {
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn pgsql_dont_replicate_partitioned_table() {
+async fn pgsql_replicate_partitioned_table() {
     readyset_tracing::init_test_logging();
     let url = pgsql_url();
     let mut client = DbConnection::connect(&url).await.unwrap();
@@ -2765,6 +2900,7 @@ async fn pgsql_dont_replicate_partitioned_table() {
             "DROP TABLE IF EXISTS t CASCADE;
              DROP TABLE IF EXISTS t_true CASCADE;
              DROP TABLE IF EXISTS t_false CASCADE;
+             DROP TABLE IF EXISTS t2 CASCADE;
 
              CREATE TABLE t (key bool not null, val int) PARTITION BY LIST (key);
              CREATE TABLE t_true PARTITION OF t FOR VALUES IN (true);
@@ -2789,62 +2925,130 @@ async fn pgsql_dont_replicate_partitioned_table() {
         .await
         .unwrap();
 
-    ctx.noria.table("t").await.unwrap_err();
-    let relation = Relation {
-        schema: Some("public".into()),
-        name: "t".into(),
-    };
-
-    assert!(ctx
-        .noria
-        .non_replicated_relations()
-        .await
-        .unwrap()
-        .contains(&NonReplicatedRelation::new(relation.clone())));
+    // The partitioned table is replicated as a single table, with the rows in all its partitions
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_partitioned_table",
+        &[
+            &[DfValue::from(false), DfValue::from(10)],
+            &[DfValue::from(false), DfValue::from(20)],
+            &[DfValue::from(true), DfValue::from(1)],
+            &[DfValue::from(true), DfValue::from(2)],
+        ],
+    )
+    .await
+    .unwrap();
 
-    ctx.noria
-        .table(Relation {
-            schema: Some("public".into()),
-            name: "t_true".into(),
-        })
-        .await
-        .unwrap();
-    ctx.noria
-        .table(Relation {
+    // ...and the partitions themselves aren't replicated
+    for partition in ["t_true", "t_false"] {
+        let relation = Relation {
             schema: Some("public".into()),
-            name: "t_false".into(),
-        })
+            name: partition.into(),
+        };
+        ctx.noria.table(relation.clone()).await.unwrap_err();
+        assert!(ctx
+            .noria
+            .non_replicated_relations()
+            .await
+            .unwrap()
+            .contains(&NonReplicatedRelation::new(relation)));
+    }
+
+    // Writes to the partitions are replicated to the partitioned table
+    client
+        .query(
+            "INSERT INTO t (key, val) VALUES (true, 3);
+             UPDATE t SET val = 11 WHERE val = 10;
+             DELETE FROM t_false WHERE val = 20;",
+        )
         .await
         .unwrap();
 
     ctx.check_results(
-        "t_true",
-        "pgsql_dont_replicate_partitioned_table",
+        "t",
+        "pgsql_replicate_partitioned_table",
         &[
+            &[DfValue::from(false), DfValue::from(11)],
             &[DfValue::from(true), DfValue::from(1)],
             &[DfValue::from(true), DfValue::from(2)],
+            &[DfValue::from(true), DfValue::from(3)],
         ],
     )
     .await
     .unwrap();
+
+    // Detaching a partition removes its rows from the partitioned table
+    client
+        .query("ALTER TABLE t DETACH PARTITION t_true")
+        .await
+        .unwrap();
+
+    // ...without dropping the caches that read from it
+    let cache = Relation {
+        schema: Some("public".into()),
+        name: "q_t".into(),
+    };
+    eventually! {
+        let mut getter = ctx
+            .noria
+            .view(cache.clone())
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+        let mut results = getter.lookup(&[0.into()], true).await.unwrap().into_vec();
+        results.sort();
+        results == vec![vec![DfValue::from(false), DfValue::from(11)]]
+    }
+
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_partitioned_table",
+        &[&[DfValue::from(false), DfValue::from(11)]],
+    )
+    .await
+    .unwrap();
+
+    // ...and attaching one adds them back
+    client
+        .query("ALTER TABLE t ATTACH PARTITION t_true FOR VALUES IN (true)")
+        .await
+        .unwrap();
+
     ctx.check_results(
-        "t_false",
-        "pgsql_dont_replicate_partitioned_table",
+        "t",
+        "pgsql_replicate_partitioned_table",
         &[
-            &[DfValue::from(false), DfValue::from(10)],
-            &[DfValue::from(false), DfValue::from(20)],
+            &[DfValue::from(false), DfValue::from(11)],
+            &[DfValue::from(true), DfValue::from(1)],
+            &[DfValue::from(true), DfValue::from(2)],
+            &[DfValue::from(true), DfValue::from(3)],
         ],
     )
     .await
     .unwrap();
 
+    // Partitioned tables created while replicating are replicated too
     client
-        .query("CREATE TABLE t2 (key int, val int) PARTITION BY RANGE (key)")
+        .query(
+            "CREATE TABLE t2 (key int, val int) PARTITION BY RANGE (key);
+             CREATE TABLE t2_low PARTITION OF t2 FOR VALUES FROM (0) TO (10);
+             INSERT INTO t2 (key, val) VALUES (1, 1);",
+        )
         .await
         .unwrap();
+
+    ctx.check_results(
+        "t2",
+        "pgsql_replicate_partitioned_table",
+        &[&[DfValue::from(1), DfValue::from(1)]],
+    )
+    .await
+    .unwrap();
+
     let relation = Relation {
         schema: Some("public".into()),
-        name: "t2".into(),
+        name: "t2_low".into(),
     };
     eventually! {
         ctx
@@ -2857,3 +3061,359 @@ async fn pgsql_dont_replicate_partitioned_table() {
 
     shutdown_tx.shutdown().await;
 }
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+async fn pgsql_replicate_streamed_transactions() {
+    readyset_tracing::init_test_logging();
+    let url = pgsql_url();
+    let mut client = DbConnection::connect(&url).await.unwrap();
+
+    // Make the server stream any transaction that's more than a few rows long to us while it's
+    // still in progress (on versions of PostgreSQL that support streaming)
+    client
+        .query("ALTER SYSTEM SET logical_decoding_work_mem = '64kB'")
+        .await
+        .unwrap();
+    client.query("SELECT pg_reload_conf()").await.unwrap();
+
+    client
+        .query(
+            "DROP TABLE IF EXISTS t CASCADE;
+             CREATE TABLE t (id int primary key, val text);",
+        )
+        .await
+        .unwrap();
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(url.to_string(), None)
+        .await
+        .unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+
+    // A transaction that rolls back one of its subtransactions, but keeps the changes made before
+    // and after it
+    client
+        .query(
+            "BEGIN;
+             INSERT INTO t SELECT i, repeat('a', 2000) FROM generate_series(1, 100) AS i;
+             SAVEPOINT s;
+             INSERT INTO t SELECT i, repeat('b', 2000) FROM generate_series(101, 200) AS i;
+             ROLLBACK TO SAVEPOINT s;
+             INSERT INTO t SELECT i, repeat('c', 2000) FROM generate_series(201, 300) AS i;
+             COMMIT;",
+        )
+        .await
+        .unwrap();
+
+    // A transaction that rolls back entirely
+    client
+        .query(
+            "BEGIN;
+             INSERT INTO t SELECT i, repeat('d', 2000) FROM generate_series(301, 400) AS i;
+             ROLLBACK;",
+        )
+        .await
+        .unwrap();
+
+    let rows = (1..=100)
+        .map(|i| vec![DfValue::from(i), DfValue::from("a".repeat(2000))])
+        .chain((201..=300).map(|i| vec![DfValue::from(i), DfValue::from("c".repeat(2000))]))
+        .collect::<Vec<_>>();
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_streamed_transactions",
+        &rows.iter().map(|r| r.as_slice()).collect::<Vec<_>>(),
+    )
+    .await
+    .unwrap();
+
+    client
+        .query("ALTER SYSTEM RESET logical_decoding_work_mem")
+        .await
+        .unwrap();
+    client.query("SELECT pg_reload_conf()").await.unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
+/// Wait until the standby at `standby_url` has replayed everything written to the primary at
+/// `primary_url` so far
+async fn wait_for_standby(primary_url: &str, standby_url: &str) {
+    let (primary, conn) = tokio_postgres::connect(primary_url, tokio_postgres::NoTls)
+        .await
+        .unwrap();
+    tokio::spawn(conn);
+    let lsn: String = primary
+        .query_one("SELECT pg_current_wal_lsn()::text", &[])
+        .await
+        .unwrap()
+        .get(0);
+
+    // The database we're testing with may not exist on the standby yet
+    let mut standby_opts = tokio_postgres::Config::from_str(standby_url).unwrap();
+    standby_opts.dbname("postgres");
+    let (standby, conn) = standby_opts.connect(tokio_postgres::NoTls).await.unwrap();
+    tokio::spawn(conn);
+    loop {
+        let caught_up: bool = standby
+            .query_one(
+                &format!("SELECT pg_last_wal_replay_lsn() >= '{lsn}'::pg_lsn"),
+                &[],
+            )
+            .await
+            .unwrap()
+            .get(0);
+        if caught_up {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Creates a table and the publication on the primary at [`pgsql16_url`], and starts replicating
+/// from its standby
+async fn start_standby_replication() -> (DbConnection, TestHandle, ShutdownSender) {
+    let primary_url = pgsql16_url();
+    let standby_url = pgsql16_standby_url();
+    let mut client = DbConnection::connect(&primary_url).await.unwrap();
+    client
+        .query(
+            "DROP TABLE IF EXISTS t CASCADE;
+             CREATE TABLE t (id int primary key, val text);
+             INSERT INTO t VALUES (1, 'a'), (2, 'b');
+             CREATE PUBLICATION readyset FOR ALL TABLES WITH (publish_via_partition_root = true);",
+        )
+        .await
+        .unwrap();
+    wait_for_standby(&primary_url, &standby_url).await;
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(
+        standby_url,
+        Some(Config {
+            standby_primary_db_url: Some(primary_url.into()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t",
+        "start_standby_replication",
+        &[&[1.into(), "a".into()], &[2.into(), "b".into()]],
+    )
+    .await
+    .unwrap();
+
+    (client, ctx, shutdown_tx)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+#[ignore = "Requires a PostgreSQL 16 primary and physical standby, at PGHOST16/PGSTANDBYHOST16"]
+async fn pgsql_replicate_from_standby() {
+    readyset_tracing::init_test_logging();
+    let (mut client, mut ctx, shutdown_tx) = start_standby_replication().await;
+
+    client.query("INSERT INTO t VALUES (3, 'c')").await.unwrap();
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_from_standby",
+        &[
+            &[1.into(), "a".into()],
+            &[2.into(), "b".into()],
+            &[3.into(), "c".into()],
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Writes made while we're not running should be replicated from the slot we already created on
+    // the standby once we start again
+    ctx.stop_repl().await;
+    client
+        .query("UPDATE t SET val = 'd' WHERE id = 1")
+        .await
+        .unwrap();
+    ctx.start_repl(
+        Some(Config {
+            standby_primary_db_url: Some(pgsql16_url().into()),
+            ..Default::default()
+        }),
+        TelemetrySender::new_no_op(),
+        false,
+    )
+    .await
+    .unwrap();
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_from_standby",
+        &[
+            &[1.into(), "d".into()],
+            &[2.into(), "b".into()],
+            &[3.into(), "c".into()],
+        ],
+    )
+    .await
+    .unwrap();
+
+    client.stop().await;
+    ctx.stop().await;
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+#[ignore = "Promotes the standby at PGSTANDBYHOST16, which then has to be recreated"]
+async fn pgsql_replicate_from_promoted_standby() {
+    readyset_tracing::init_test_logging();
+    let (client, mut ctx, shutdown_tx) = start_standby_replication().await;
+
+    let (standby, conn) = tokio_postgres::connect(&pgsql16_standby_url(), tokio_postgres::NoTls)
+        .await
+        .unwrap();
+    tokio::spawn(conn);
+    standby
+        .simple_query("SELECT pg_promote(true)")
+        .await
+        .unwrap();
+
+    // Once promoted, the former standby is written to directly, and we should keep replicating
+    // from the slot we created on it
+    standby
+        .simple_query("INSERT INTO t VALUES (3, 'c')")
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_from_promoted_standby",
+        &[
+            &[1.into(), "a".into()],
+            &[2.into(), "b".into()],
+            &[3.into(), "c".into()],
+        ],
+    )
+    .await
+    .unwrap();
+
+    client.stop().await;
+    ctx.stop().await;
+    shutdown_tx.shutdown().await;
+}
+
+/// The URL for a MySQL replica of the server at [`mysql_url`], with GTID mode enabled on both
+fn mysql_replica_url() -> String {
+    format!(
+        "mysql://root:noria@{}:{}/public",
+        env::var("MYSQL_REPLICA_HOST").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("MYSQL_REPLICA_TCP_PORT").unwrap_or_else(|_| "3307".into()),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+#[ignore = "Promotes the MySQL replica at MYSQL_REPLICA_HOST, which then has to be recreated"]
+async fn mysql_gtid_resume_after_failover() {
+    readyset_tracing::init_test_logging();
+    let mut client = DbConnection::connect(&mysql_url()).await.unwrap();
+    client
+        .query(
+            "CREATE TABLE t (id int primary key, val text);
+             INSERT INTO t VALUES (1, 'a'), (2, 'b');",
+        )
+        .await
+        .unwrap();
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(mysql_url(), None).await.unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+    client.query("INSERT INTO t VALUES (3, 'c')").await.unwrap();
+    ctx.check_results(
+        "t",
+        "mysql_gtid_resume_after_failover",
+        &[
+            &[1.into(), "a".into()],
+            &[2.into(), "b".into()],
+            &[3.into(), "c".into()],
+        ],
+    )
+    .await
+    .unwrap();
+    ctx.stop_repl().await;
+
+    // Wait for the replica to apply everything the primary has executed, then promote it
+    let DbConnection::MySQL(primary) = &mut client else {
+        unreachable!()
+    };
+    let executed: String = primary
+        .query_first("SELECT @@GLOBAL.gtid_executed")
+        .await
+        .unwrap()
+        .unwrap();
+    let mut replica =
+        mysql_async::Conn::new(mysql_async::Opts::from_url(&mysql_replica_url()).unwrap())
+            .await
+            .unwrap();
+    replica
+        .exec_drop("SELECT WAIT_FOR_EXECUTED_GTID_SET(?, 60)", (executed,))
+        .await
+        .unwrap();
+    replica
+        .query_drop("STOP REPLICA; RESET REPLICA ALL")
+        .await
+        .unwrap();
+
+    // A row that only exists on the promoted replica and was never written to its binlog would
+    // only show up if we snapshotted the replica again, rather than resuming from our GTID set
+    replica
+        .query_drop(
+            "SET SESSION sql_log_bin = 0;
+             INSERT INTO t VALUES (100, 'not replicated');
+             SET SESSION sql_log_bin = 1;",
+        )
+        .await
+        .unwrap();
+    replica
+        .query_drop("INSERT INTO t VALUES (4, 'd')")
+        .await
+        .unwrap();
+
+    ctx.url = mysql_replica_url();
+    ctx.start_repl(None, TelemetrySender::new_no_op(), false)
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t",
+        "mysql_gtid_resume_after_failover",
+        &[
+            &[1.into(), "a".into()],
+            &[2.into(), "b".into()],
+            &[3.into(), "c".into()],
+            &[4.into(), "d".into()],
+        ],
+    )
+    .await
+    .unwrap();
+
+    client.stop().await;
+    ctx.stop().await;
+    shutdown_tx.shutdown().await;
+}
@@ -52,7 +52,9 @@ const LEADER_URI: &str = "http://127.0.0.1:6033";
 fn readyset_url(database_type: DatabaseType) -> String {
     match database_type {
         DatabaseType::PostgreSQL => format!("postgres://127.0.0.1:{BENCHMARK_PORT}/{DB_NAME}"),
-        DatabaseType::MySQL => format!("mysql://127.0.0.1:{BENCHMARK_PORT}/{DB_NAME}"),
+        DatabaseType::MySQL | DatabaseType::MariaDB => {
+            format!("mysql://127.0.0.1:{BENCHMARK_PORT}/{DB_NAME}")
+        }
     }
 }
 
@@ -633,7 +635,7 @@ fn start_adapter(upstream_url: &str) -> anyhow::Result<()> {
     let adapter_options = Options::parse_from(options);
 
     match database_type {
-        DatabaseType::MySQL => {
+        DatabaseType::MySQL | DatabaseType::MariaDB => {
             let mut adapter = NoriaAdapter {
                 description: "ReadySet benchmark adapter",
                 default_address: SocketAddr::new(
@@ -642,8 +644,10 @@ fn start_adapter(upstream_url: &str) -> anyhow::Result<()> {
                 ),
                 connection_handler: MySqlHandler {
                     enable_statement_logging: false,
+                    tls_acceptor: None,
+                    require_tls: false,
                 },
-                database_type: DatabaseType::MySQL,
+                database_type,
                 parse_dialect: nom_sql::Dialect::MySQL,
                 expr_dialect: readyset_data::Dialect::DEFAULT_MYSQL,
             };
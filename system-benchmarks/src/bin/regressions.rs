@@ -125,6 +125,7 @@ async fn get_bench_descriptor(
         // For backwards compatibility, MySQL files do not have a "-mysql" suffix
         match database_type {
             DatabaseType::MySQL => a.filename == format!("{build}-bench.json"),
+            DatabaseType::MariaDB => a.filename == format!("{build}-bench-mariadb.json"),
             DatabaseType::PostgreSQL => a.filename == format!("{build}-bench-postgresql.json"),
         }
     }))